@@ -7,11 +7,14 @@
 /// These benchmarks measure individual components so you can see
 /// how much of that budget each piece consumes.
 use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
-use engine::audio_file::TARGET_SAMPLE_RATE;
+use engine::audio_file::{AudioClip, TARGET_SAMPLE_RATE};
 use engine::effects::{
-    Chorus, Compressor, Delay, Effect, Limiter, ParametricEQ, Reverb,
+    Chorus, Compressor, Delay, Effect, EffectManager, EffectType, Limiter, ParametricEQ, Reverb,
 };
+use parking_lot::Mutex;
+use std::sync::Arc;
 use engine::synth::{Synth, TrackSynthManager};
+use engine::{AudioGraph, TrackType};
 
 const BUFFER_SIZE: usize = 256;
 
@@ -78,6 +81,45 @@ fn bench_effects(c: &mut Criterion) {
     group.finish();
 }
 
+// ── Block vs per-frame processing of the heavy effects ──────────────────
+
+/// Creates a fresh effect for a benchmark
+type EffectFactory = fn() -> Box<dyn Effect>;
+
+fn bench_effect_blocks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("effect_blocks");
+
+    let effects: [(&str, EffectFactory); 3] = [
+        ("parametric_eq", || Box::new(ParametricEQ::new())),
+        ("reverb", || Box::new(Reverb::new())),
+        ("delay", || Box::new(Delay::new())),
+    ];
+    for (name, create) in effects {
+        group.bench_function(format!("{name}/frames/256"), |b| {
+            let mut effect = create();
+            b.iter(|| {
+                for _ in 0..BUFFER_SIZE {
+                    black_box(effect.process_frame(0.5, 0.5));
+                }
+            });
+        });
+
+        group.bench_function(format!("{name}/block/256"), |b| {
+            let mut effect = create();
+            let mut left = [0.5_f32; BUFFER_SIZE];
+            let mut right = [0.5_f32; BUFFER_SIZE];
+            b.iter(|| {
+                left.fill(0.5);
+                right.fill(0.5);
+                effect.process_block(&mut left, &mut right);
+                black_box((&left, &right));
+            });
+        });
+    }
+
+    group.finish();
+}
+
 // ── Effect chain benchmarks (simulates N effects per track) ─────────────
 
 fn bench_effect_chain(c: &mut Criterion) {
@@ -236,12 +278,111 @@ fn bench_full_signal_path(c: &mut Criterion) {
     group.finish();
 }
 
+// ── Effect manager locking (per frame vs once per buffer) ───────────────
+
+fn bench_effect_manager_locking(c: &mut Criterion) {
+    let mut group = c.benchmark_group("effect_manager_locking");
+    const TRACKS: usize = 16;
+
+    // 16 tracks with EQ + Compressor, held in the shared effect manager like the callback
+    let mut manager = EffectManager::new();
+    let chains: Vec<[u64; 2]> = (0..TRACKS)
+        .map(|_| {
+            [
+                manager.create_effect(EffectType::EQ(ParametricEQ::new())),
+                manager.create_effect(EffectType::Compressor(Compressor::new())),
+            ]
+        })
+        .collect();
+    let manager = Arc::new(Mutex::new(manager));
+
+    fn process_chains(manager: &EffectManager, chains: &[[u64; 2]]) -> (f32, f32) {
+        let mut mix = (0.0, 0.0);
+        for chain in chains {
+            let (mut l, mut r) = (0.5_f32, 0.5_f32);
+            for id in chain {
+                if let Some(effect_arc) = manager.get_effect(*id) {
+                    (l, r) = manager.process_frame(*id, &mut effect_arc.lock(), l, r);
+                }
+            }
+            mix.0 += l;
+            mix.1 += r;
+        }
+        mix
+    }
+
+    // How the callback used to work: re-lock the manager on every frame
+    group.bench_function("lock_per_frame/256", |b| {
+        b.iter(|| {
+            for _ in 0..BUFFER_SIZE {
+                black_box(process_chains(&manager.lock(), &chains));
+            }
+        });
+    });
+
+    // How it works now: lock once for the whole buffer
+    group.bench_function("lock_per_buffer/256", |b| {
+        b.iter(|| {
+            let guard = manager.lock();
+            for _ in 0..BUFFER_SIZE {
+                black_box(process_chains(&guard, &chains));
+            }
+        });
+    });
+
+    group.finish();
+}
+
+// ── Offline renders (final vs preview quality) ─────────────────────────
+
+fn bench_offline_render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("offline_render");
+    group.sample_size(10);
+
+    // Heavy project: 8 tracks playing a 1 s tone, each through two reverbs
+    let graph = AudioGraph::new_headless().unwrap();
+    let frames = TARGET_SAMPLE_RATE as usize;
+    let samples = (0..frames * 2)
+        .map(|i| ((i / 2) as f32 * 220.0 * std::f32::consts::TAU / TARGET_SAMPLE_RATE as f32).sin() * 0.2)
+        .collect();
+    let clip = Arc::new(AudioClip {
+        samples,
+        channels: 2,
+        sample_rate: TARGET_SAMPLE_RATE,
+        duration_seconds: 1.0,
+        file_path: "tone.wav".to_string(),
+        stream: None,
+    });
+    for i in 0..8 {
+        let track_id = graph.track_manager.lock().create_track(TrackType::Audio, format!("Track {i}"));
+        graph.add_clip_to_track(track_id, clip.clone(), 0.0).unwrap();
+        let track_arc = graph.track_manager.lock().get_track(track_id).unwrap();
+        for _ in 0..2 {
+            let effect_id = graph.effect_manager.lock().create_effect(EffectType::Reverb(Reverb::new()));
+            track_arc.lock().fx_chain.push(effect_id);
+        }
+    }
+
+    // The preview should stay well under the final render's time
+    group.bench_function("final/1s", |b| {
+        b.iter(|| black_box(graph.render_offline(1.0)));
+    });
+    group.bench_function("preview/1s", |b| {
+        b.iter(|| black_box(graph.render_offline_preview(0.0, 1.0)));
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_effects,
+    bench_effect_blocks,
     bench_effect_chain,
     bench_synth,
     bench_track_mixing,
     bench_full_signal_path,
+    bench_effect_manager_locking,
+    bench_offline_render,
 );
 criterion_main!(benches);
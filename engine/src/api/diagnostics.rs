@@ -0,0 +1,15 @@
+//! Diagnostics API functions
+//!
+//! Engine state dump for bug reports and support.
+
+use super::helpers::get_audio_graph;
+
+/// Get a JSON snapshot of engine state: sample rate, buffer size, devices,
+/// track/effect/clip counts, DSP load, xrun count and feature flags.
+/// Only takes brief locks, so it never stalls the audio callback.
+pub fn get_engine_diagnostics() -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let diagnostics = graph.get_diagnostics();
+    serde_json::to_string(&diagnostics).map_err(|e| e.to_string())
+}
@@ -3,7 +3,7 @@
 //! Functions for managing audio effects (EQ, compressor, reverb, etc.) on tracks.
 
 use super::helpers::get_audio_graph;
-use crate::track::TrackId;
+use crate::track::{AutomationPoint, TrackId};
 
 // ============================================================================
 // EFFECT MANAGEMENT
@@ -11,7 +11,7 @@ use crate::track::TrackId;
 
 /// Add an effect to a track's FX chain
 pub fn add_effect_to_track(track_id: TrackId, effect_type_str: &str) -> Result<u64, String> {
-    use crate::effects::{EffectType, ParametricEQ, Compressor, Reverb, Delay, Chorus, Limiter};
+    use crate::effects::{EffectType, ParametricEQ, Compressor, Reverb, Delay, Chorus, Limiter, BitCrusher, NoiseGate, StereoWidth, Filter, Gain};
 
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
@@ -26,6 +26,11 @@ pub fn add_effect_to_track(track_id: TrackId, effect_type_str: &str) -> Result<u
         "delay" => EffectType::Delay(Delay::new()),
         "chorus" => EffectType::Chorus(Chorus::new()),
         "limiter" => EffectType::Limiter(Limiter::new()),
+        "bitcrusher" => EffectType::BitCrusher(BitCrusher::new()),
+        "gate" => EffectType::NoiseGate(NoiseGate::new()),
+        "stereo_width" => EffectType::StereoWidth(StereoWidth::new()),
+        "filter" => EffectType::Filter(Filter::new()),
+        "gain" => EffectType::Gain(Gain::new()),
         _ => return Err(format!("Unknown effect type: {effect_type_str}")),
     };
 
@@ -110,24 +115,47 @@ pub fn get_effect_info(effect_id: u64) -> Result<String, String> {
                 eq.mid2_freq, eq.mid2_gain_db, eq.mid2_q, eq.high_freq, eq.high_gain_db
             ),
             EffectType::Compressor(comp) => format!(
-                "type:compressor,bypassed:{},threshold:{},ratio:{},attack:{},release:{},makeup:{}",
-                bypass_str, comp.threshold_db, comp.ratio, comp.attack_ms, comp.release_ms, comp.makeup_gain_db
+                "type:compressor,bypassed:{},threshold:{},ratio:{},attack:{},release:{},makeup:{},sidechain:{},oversample:{}",
+                bypass_str, comp.threshold_db, comp.ratio, comp.attack_ms, comp.release_ms, comp.makeup_gain_db,
+                comp.sidechain_track_id.map_or(-1, |id| id as i64), comp.oversample()
             ),
             EffectType::Reverb(rev) => format!(
-                "type:reverb,bypassed:{},room_size:{},damping:{},wet_dry:{}",
-                bypass_str, rev.room_size, rev.damping, rev.wet_dry_mix
+                "type:reverb,bypassed:{},room_size:{},damping:{},wet_dry:{},freeze:{}",
+                bypass_str, rev.room_size, rev.damping, rev.wet_dry_mix, u8::from(rev.freeze)
             ),
             EffectType::Delay(delay) => format!(
-                "type:delay,bypassed:{},time:{},feedback:{},wet_dry:{}",
-                bypass_str, delay.delay_time_ms, delay.feedback, delay.wet_dry_mix
+                "type:delay,bypassed:{},time:{},feedback:{},wet_dry:{},ping_pong:{},sync:{},division:{},division_type:{}",
+                bypass_str, delay.delay_time_ms, delay.feedback, delay.wet_dry_mix, u8::from(delay.ping_pong),
+                u8::from(delay.sync_enabled), delay.note_division, delay.note_modifier.index()
             ),
             EffectType::Chorus(chorus) => format!(
                 "type:chorus,bypassed:{},rate:{},depth:{},wet_dry:{}",
                 bypass_str, chorus.rate_hz, chorus.depth, chorus.wet_dry_mix
             ),
             EffectType::Limiter(lim) => format!(
-                "type:limiter,bypassed:{},threshold:{},release:{}",
-                bypass_str, lim.threshold_db, lim.release_ms
+                "type:limiter,bypassed:{},threshold:{},release:{},lookahead:{},oversample:{}",
+                bypass_str, lim.threshold_db, lim.release_ms, lim.lookahead_ms, lim.oversample()
+            ),
+            EffectType::BitCrusher(crusher) => format!(
+                "type:bitcrusher,bypassed:{},bit_depth:{},sample_rate_reduction:{},wet_dry:{}",
+                bypass_str, crusher.bit_depth, crusher.sample_rate_reduction, crusher.wet_dry_mix
+            ),
+            EffectType::NoiseGate(gate) => format!(
+                "type:gate,bypassed:{},threshold:{},attack:{},hold:{},release:{}",
+                bypass_str, gate.threshold_db, gate.attack_ms, gate.hold_ms, gate.release_ms
+            ),
+            EffectType::StereoWidth(sw) => format!(
+                "type:stereo_width,bypassed:{},width:{}",
+                bypass_str, sw.width
+            ),
+            EffectType::Filter(filter) => format!(
+                "type:filter,bypassed:{},filter_type:{},cutoff:{},resonance:{}",
+                bypass_str, filter.filter_type.index(), filter.cutoff_hz, filter.resonance
+            ),
+            EffectType::Gain(gain) => format!(
+                "type:gain,bypassed:{},gain:{},invert_left:{},invert_right:{},dc_block:{}",
+                bypass_str, gain.gain_db, u8::from(gain.invert_left), u8::from(gain.invert_right),
+                u8::from(gain.dc_block)
             ),
             #[cfg(all(feature = "vst3", not(target_os = "ios")))]
             EffectType::VST3(vst3) => {
@@ -135,7 +163,7 @@ pub fn get_effect_info(effect_id: u64) -> Result<String, String> {
                 format!("type:vst3,bypassed:{},name:{}", bypass_str, vst3.name())
             }
         };
-        Ok(info)
+        Ok(format!("{info},mix:{}", effect_manager.get_mix(effect_id)))
     } else {
         Err(format!("Effect {effect_id} not found"))
     }
@@ -176,34 +204,19 @@ pub fn reorder_track_effects(track_id: u64, effect_ids_csv: &str) -> Result<Stri
     let graph = graph_mutex.lock();
     let track_manager = graph.track_manager.lock();
 
-    // Parse the effect IDs
+    // Parse the effect IDs (reject the whole request on any malformed entry)
     let new_order: Vec<u64> = effect_ids_csv
         .split(',')
+        .map(str::trim)
         .filter(|s| !s.is_empty())
-        .filter_map(|s| s.trim().parse::<u64>().ok())
-        .collect();
+        .map(|s| s.parse::<u64>().map_err(|_| format!("Invalid effect ID '{s}'")))
+        .collect::<Result<_, _>>()?;
 
     if let Some(track_arc) = track_manager.get_track(track_id) {
         let mut track = track_arc.lock();
 
-        // Validate that all IDs in new_order are in the current fx_chain
-        for id in &new_order {
-            if !track.fx_chain.contains(id) {
-                return Err(format!("Effect {id} not found in track {track_id}'s FX chain"));
-            }
-        }
-
-        // Validate that all current effects are in the new order
-        if new_order.len() != track.fx_chain.len() {
-            return Err(format!(
-                "Effect count mismatch: expected {} effects, got {}",
-                track.fx_chain.len(),
-                new_order.len()
-            ));
-        }
-
-        // Apply the new order
-        track.fx_chain = new_order;
+        // Must be exactly the current chain, reordered
+        track.reorder_fx_chain(new_order)?;
 
         eprintln!(
             "🔄 [API] Reordered effects on track {}: {:?}",
@@ -217,9 +230,8 @@ pub fn reorder_track_effects(track_id: u64, effect_ids_csv: &str) -> Result<Stri
 }
 
 /// Set an effect parameter
+/// `mix` (0.0 = dry, 1.0 = wet) is accepted for every effect type
 pub fn set_effect_parameter(effect_id: u64, param_name: &str, value: f32) -> Result<String, String> {
-    use crate::effects::EffectType;
-
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
     let effect_manager = graph.effect_manager.lock();
@@ -227,132 +239,10 @@ pub fn set_effect_parameter(effect_id: u64, param_name: &str, value: f32) -> Res
     if let Some(effect_arc) = effect_manager.get_effect(effect_id) {
         let mut effect = effect_arc.lock();
 
-        match &mut *effect {
-            EffectType::EQ(eq) => match param_name {
-                "low_freq" => {
-                    eq.low_freq = value;
-                    eq.update_coefficients();
-                }
-                "low_gain" => {
-                    eq.low_gain_db = value;
-                    eq.update_coefficients();
-                }
-                "mid1_freq" => {
-                    eq.mid1_freq = value;
-                    eq.update_coefficients();
-                }
-                "mid1_gain" => {
-                    eq.mid1_gain_db = value;
-                    eq.update_coefficients();
-                }
-                "mid1_q" => {
-                    eq.mid1_q = value;
-                    eq.update_coefficients();
-                }
-                "mid2_freq" => {
-                    eq.mid2_freq = value;
-                    eq.update_coefficients();
-                }
-                "mid2_gain" => {
-                    eq.mid2_gain_db = value;
-                    eq.update_coefficients();
-                }
-                "mid2_q" => {
-                    eq.mid2_q = value;
-                    eq.update_coefficients();
-                }
-                "high_freq" => {
-                    eq.high_freq = value;
-                    eq.update_coefficients();
-                }
-                "high_gain" => {
-                    eq.high_gain_db = value;
-                    eq.update_coefficients();
-                }
-                _ => return Err(format!("Unknown EQ parameter: {param_name}")),
-            },
-            EffectType::Compressor(comp) => match param_name {
-                "threshold" => {
-                    comp.threshold_db = value;
-                }
-                "ratio" => {
-                    comp.ratio = value;
-                }
-                "attack" => {
-                    comp.attack_ms = value;
-                    comp.update_coefficients();
-                }
-                "release" => {
-                    comp.release_ms = value;
-                    comp.update_coefficients();
-                }
-                "makeup" => {
-                    comp.makeup_gain_db = value;
-                }
-                _ => return Err(format!("Unknown Compressor parameter: {param_name}")),
-            },
-            EffectType::Reverb(rev) => match param_name {
-                "room_size" => {
-                    rev.room_size = value;
-                }
-                "damping" => {
-                    rev.damping = value;
-                }
-                "wet_dry" => {
-                    rev.wet_dry_mix = value;
-                }
-                _ => return Err(format!("Unknown Reverb parameter: {param_name}")),
-            },
-            EffectType::Delay(delay) => match param_name {
-                "time" => {
-                    delay.delay_time_ms = value;
-                }
-                "feedback" => {
-                    delay.feedback = value;
-                }
-                "wet_dry" => {
-                    delay.wet_dry_mix = value;
-                }
-                _ => return Err(format!("Unknown Delay parameter: {param_name}")),
-            },
-            EffectType::Chorus(chorus) => match param_name {
-                "rate" => {
-                    chorus.rate_hz = value;
-                }
-                "depth" => {
-                    chorus.depth = value;
-                }
-                "wet_dry" => {
-                    chorus.wet_dry_mix = value;
-                }
-                _ => return Err(format!("Unknown Chorus parameter: {param_name}")),
-            },
-            EffectType::Limiter(lim) => match param_name {
-                "threshold" => {
-                    lim.threshold_db = value;
-                }
-                "release" => {
-                    lim.release_ms = value;
-                    lim.update_coefficients();
-                }
-                _ => return Err(format!("Unknown Limiter parameter: {param_name}")),
-            },
-            #[cfg(all(feature = "vst3", not(target_os = "ios")))]
-            EffectType::VST3(vst3) => {
-                // VST3 parameters are accessed by index (e.g., "param_0", "param_1")
-                if let Some(index_str) = param_name.strip_prefix("param_") {
-                    if let Ok(param_index) = index_str.parse::<u32>() {
-                        vst3.set_parameter_value(param_index, f64::from(value))
-                            .map_err(|e| format!("Failed to set VST3 parameter: {e}"))?;
-                    } else {
-                        return Err(format!("Invalid VST3 parameter index: {param_name}"));
-                    }
-                } else {
-                    return Err(format!(
-                        "VST3 parameter must be in format 'param_N': {param_name}"
-                    ));
-                }
-            }
+        if param_name == crate::effects::MIX_PARAM {
+            effect_manager.set_mix(effect_id, value);
+        } else {
+            effect.apply_automated_param(param_name, value)?;
         }
         Ok(format!(
             "Set {param_name} = {value} on effect {effect_id}"
@@ -361,3 +251,118 @@ pub fn set_effect_parameter(effect_id: u64, param_name: &str, value: f32) -> Res
         Err(format!("Effect {effect_id} not found"))
     }
 }
+
+/// Set an effect parameter automation curve
+///
+/// # Arguments
+/// * `effect_id` - Effect ID
+/// * `param_name` - Parameter name as accepted by `set_effect_parameter`
+/// * `csv` - Automation curve as CSV: "`time_seconds,value;time_seconds,value`;..."
+///           Empty string clears the automation curve
+///
+/// Automated values are applied once per audio buffer during playback and export
+pub fn set_effect_parameter_automation(effect_id: u64, param_name: &str, csv: &str) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let mut effect_manager = graph.effect_manager.lock();
+
+    let points = AutomationPoint::parse_csv(csv);
+    let point_count = points.len();
+    effect_manager.set_parameter_automation(effect_id, param_name, points)?;
+    Ok(format!("Effect {effect_id} {param_name} automation set ({point_count} points)"))
+}
+
+/// Lock a delay's time to a note value at the project tempo
+///
+/// # Arguments
+/// * `effect_id` - Delay effect ID
+/// * `enabled` - Follow the project tempo (false = use the manual `time` parameter)
+/// * `note_division` - 1, 2, 4, 8, 16, 32 or 64 (4 = quarter note)
+/// * `division_type` - 0 = straight, 1 = dotted, 2 = triplet
+pub fn set_delay_sync(effect_id: u64, enabled: bool, note_division: u32, division_type: u32) -> Result<String, String> {
+    use crate::effects::{EffectType, NoteModifier};
+
+    let modifier = NoteModifier::from_index(division_type)
+        .ok_or_else(|| format!("Invalid division type {division_type} (expected 0-2)"))?;
+
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let effect_manager = graph.effect_manager.lock();
+
+    let effect_arc = effect_manager
+        .get_effect(effect_id)
+        .ok_or_else(|| format!("Effect {effect_id} not found"))?;
+    let mut effect = effect_arc.lock();
+    let EffectType::Delay(delay) = &mut *effect else {
+        return Err(format!("Effect {effect_id} is not a delay"));
+    };
+
+    delay.set_sync(enabled, note_division, modifier)?;
+    Ok(format!("Delay {effect_id} sync {}: {:.1}ms", if enabled { "on" } else { "off" }, delay.delay_time_ms))
+}
+
+/// Route a compressor's detector to another track's output (None = detect from own input)
+/// The source track is mixed before the track hosting the compressor
+pub fn set_compressor_sidechain(effect_id: u64, source_track_id: Option<TrackId>) -> Result<String, String> {
+    use crate::effects::EffectType;
+    use crate::track::TrackType;
+
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    if let Some(source_id) = source_track_id {
+        let track_manager = graph.track_manager.lock();
+        let source_arc = track_manager
+            .get_track(source_id)
+            .ok_or_else(|| format!("Track {source_id} not found"))?;
+        let source = source_arc.lock();
+        if source.track_type == TrackType::Master {
+            return Err("Master track cannot be a sidechain source".to_string());
+        }
+        if source.fx_chain.contains(&effect_id) {
+            return Err(format!("Track {source_id} cannot sidechain its own compressor"));
+        }
+    }
+
+    let effect_manager = graph.effect_manager.lock();
+    let effect_arc = effect_manager
+        .get_effect(effect_id)
+        .ok_or_else(|| format!("Effect {effect_id} not found"))?;
+    let mut effect = effect_arc.lock();
+    let EffectType::Compressor(comp) = &mut *effect else {
+        return Err(format!("Effect {effect_id} is not a compressor"));
+    };
+    comp.sidechain_track_id = source_track_id;
+    comp.set_sidechain_level(0.0);
+
+    Ok(match source_track_id {
+        Some(source_id) => format!("Compressor {effect_id} sidechained from track {source_id}"),
+        None => format!("Compressor {effect_id} sidechain cleared"),
+    })
+}
+
+// ============================================================================
+// MASTER LIMITER
+// ============================================================================
+
+/// Configure the master limiter ceiling (dBFS, -30 to 0) and release (ms, 1 to 1000)
+pub fn set_master_limiter_params(threshold_db: f32, release_ms: f32) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let mut limiter = graph.master_limiter.lock();
+
+    limiter.set_threshold_db(threshold_db);
+    limiter.set_release_ms(release_ms);
+    Ok(format!(
+        "Master limiter: threshold {:.1} dB, release {:.0} ms",
+        limiter.threshold_db, limiter.release_ms
+    ))
+}
+
+/// Get the master limiter's current gain reduction in dB (0.0 = not limiting)
+pub fn get_master_limiter_gain_reduction() -> Result<f32, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let limiter = graph.master_limiter.lock();
+    Ok(limiter.get_gain_reduction_db())
+}
@@ -0,0 +1,80 @@
+//! Undo/redo API functions
+//!
+//! Clip add/move/remove and track volume/pan changes made through the API are
+//! recorded here; `undo`/`redo` replay their inverses against the audio graph.
+
+use super::helpers::{get_audio_clips, get_audio_graph};
+use crate::audio_graph::AudioGraph;
+use crate::history::{Command, History};
+use crate::track::{ClipId, TrackId};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+/// Engine-wide command history
+static HISTORY: Mutex<History> = Mutex::new(History::new());
+
+/// Record a mutation that has already been applied
+pub(super) fn record(command: Command) {
+    HISTORY.lock().record(command);
+}
+
+/// Record that an audio clip was added to a track (snapshotting its settings)
+pub(super) fn record_clip_added(graph: &AudioGraph, track_id: TrackId, clip_id: ClipId) {
+    let track_manager = graph.track_manager.lock();
+    let Some(track_arc) = track_manager.get_track(track_id) else {
+        return;
+    };
+    let clip = track_arc.lock().audio_clips.iter().find(|c| c.id == clip_id).cloned();
+    if let Some(clip) = clip {
+        record(Command::AddClip { track_id, clip });
+    }
+}
+
+/// Forget all history (the project it refers to is gone)
+pub(super) fn clear_history() {
+    HISTORY.lock().clear();
+}
+
+/// Undo the most recent change
+///
+/// Returns a description of what was undone. A step that can no longer be
+/// applied (e.g. its track was deleted) is dropped and reported as an error.
+pub fn undo() -> Result<String, String> {
+    let command = HISTORY.lock().pop_undo().ok_or("Nothing to undo")?;
+    apply(&command.inverse())?;
+    let description = format!("Undo {}", command.description());
+    HISTORY.lock().push_undone(command);
+    Ok(description)
+}
+
+/// Redo the most recently undone change
+pub fn redo() -> Result<String, String> {
+    let command = HISTORY.lock().pop_redo().ok_or("Nothing to redo")?;
+    apply(&command)?;
+    let description = format!("Redo {}", command.description());
+    HISTORY.lock().push_redone(command);
+    Ok(description)
+}
+
+/// Check whether there is a change to undo
+pub fn can_undo() -> bool {
+    HISTORY.lock().can_undo()
+}
+
+/// Check whether there is a change to redo
+pub fn can_redo() -> bool {
+    HISTORY.lock().can_redo()
+}
+
+fn apply(command: &Command) -> Result<(), String> {
+    // Register restored clips after releasing the graph, so the two locks never nest
+    let mut added_clips = HashMap::new();
+    {
+        let graph_mutex = get_audio_graph()?;
+        let graph = graph_mutex.lock();
+        let track_manager = graph.track_manager.lock();
+        command.apply(&track_manager, &mut added_clips)?;
+    }
+    get_audio_clips()?.lock().extend(added_clips);
+    Ok(())
+}
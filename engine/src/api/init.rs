@@ -125,5 +125,25 @@ pub fn init_audio_graph() -> Result<String, String> {
         .set(Mutex::new(HashMap::new()))
         .map_err(|_| "Audio clips already initialized")?;
 
+    spawn_device_watcher();
+
     Ok("Audio graph initialized".to_string())
 }
+
+/// How often the device watcher looks for unplugged or replugged output devices
+const DEVICE_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Rebuild the output stream when its device goes away or the selected device returns
+/// (see `AudioGraph::check_audio_device`)
+fn spawn_device_watcher() {
+    std::thread::spawn(|| loop {
+        std::thread::sleep(DEVICE_WATCH_INTERVAL);
+        let Some(graph_mutex) = AUDIO_GRAPH.get() else {
+            continue;
+        };
+        // Skip this check rather than wait on a busy graph
+        if let Some(mut graph) = graph_mutex.try_lock() {
+            graph.check_audio_device();
+        }
+    });
+}
@@ -2,7 +2,12 @@
 //!
 //! Functions for audio latency configuration and waveform visualization.
 
+use crate::audio_file::AudioClip;
 use crate::audio_graph::BufferSizePreset;
+use crate::waveform::WaveformPeaks;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Weak};
 use super::helpers::{get_audio_clips, get_audio_graph, with_graph, with_graph_mut};
 
 // ============================================================================
@@ -63,6 +68,36 @@ pub fn get_latency_info() -> Option<(u32, f32, f32, f32)> {
     Some(graph.get_latency_info())
 }
 
+/// Get the plugin delay compensation latency in samples
+/// (how far playback runs behind the timeline because of effect latency)
+pub fn get_reported_latency() -> Result<u64, String> {
+    with_graph(|graph| Ok(graph.get_reported_latency() as u64))
+}
+
+// ============================================================================
+// DSP LOAD
+// ============================================================================
+
+/// Get the audio callback's DSP load in percent of the buffer's time budget
+/// Averaged over about half a second; 0 until the callback has run
+pub fn get_dsp_load_percent() -> Result<f32, String> {
+    with_graph(|graph| Ok(graph.dsp_load.load_percent()))
+}
+
+/// Get the number of xruns (dropouts) since startup or the last reset
+/// Counts callbacks that overran their budget and gaps where the device ran dry
+pub fn get_xrun_count() -> Result<u64, String> {
+    with_graph(|graph| Ok(graph.dsp_load.xrun_count()))
+}
+
+/// Reset the xrun counter (e.g. after changing the buffer size)
+pub fn reset_xrun_count() -> Result<String, String> {
+    with_graph(|graph| {
+        graph.dsp_load.reset_xrun_count();
+        Ok("Xrun count reset".to_string())
+    })
+}
+
 // ============================================================================
 // LATENCY TEST
 // ============================================================================
@@ -101,46 +136,44 @@ pub fn get_latency_test_error() -> Result<Option<String>, String> {
 // WAVEFORM VISUALIZATION
 // ============================================================================
 
+/// Cached waveform peaks per clip ID, kept with the audio they were built from
+/// (a clip whose audio is replaced, e.g. by a channel conversion, is rebuilt)
+static WAVEFORM_PEAKS: Mutex<BTreeMap<u64, CachedPeaks>> = Mutex::new(BTreeMap::new());
+type CachedPeaks = (Weak<AudioClip>, Arc<WaveformPeaks>);
+
+/// Get (building on first use) the cached peaks for a clip's audio
+fn cached_waveform_peaks(clip_id: u64, clip: &Arc<AudioClip>) -> Arc<WaveformPeaks> {
+    let mut cache = WAVEFORM_PEAKS.lock();
+    if let Some((source, peaks)) = cache.get(&clip_id) {
+        if std::ptr::eq(source.as_ptr(), Arc::as_ptr(clip)) {
+            return peaks.clone();
+        }
+    }
+    // Drop peaks of clips that no longer exist
+    cache.retain(|_, (source, _)| source.strong_count() > 0);
+    let peaks = Arc::new(WaveformPeaks::new(clip));
+    cache.insert(clip_id, (Arc::downgrade(clip), peaks.clone()));
+    peaks
+}
+
 /// Get waveform peaks for visualization
-/// Returns downsampled peaks (min/max pairs) for rendering
+/// Returns downsampled peaks (min/max pairs of the first channel) for rendering,
+/// read from a peak cache built the first time the clip is drawn
 pub fn get_waveform_peaks(clip_id: u64, resolution: usize) -> Result<Vec<f32>, String> {
-    let clips_mutex = get_audio_clips()?;
-    let clips_map = clips_mutex.lock();
-
-    let clip = clips_map
+    let clip = get_audio_clips()?
+        .lock()
         .get(&clip_id)
+        .cloned()
         .ok_or_else(|| format!("Clip {clip_id} not found"))?;
 
-    // Downsample to resolution peaks
-    let frames = clip.frame_count();
-    let samples_per_peak = (frames / resolution).max(1);
-
-    let mut peaks = Vec::with_capacity(resolution * 2); // min/max pairs
-
-    for i in 0..resolution {
-        let start = i * samples_per_peak;
-        let end = ((i + 1) * samples_per_peak).min(frames);
-
-        if start >= frames {
-            break;
-        }
-
-        let mut min: f32 = 1.0;
-        let mut max: f32 = -1.0;
-
-        // Find min/max in this window (use left channel for mono visualization)
-        for frame in start..end {
-            if let Some(sample) = clip.get_sample(frame, 0) {
-                min = min.min(sample);
-                max = max.max(sample);
-            }
-        }
-
-        peaks.push(min);
-        peaks.push(max);
-    }
+    Ok(cached_waveform_peaks(clip_id, &clip).peaks(&clip, resolution))
+}
 
-    Ok(peaks)
+/// Get waveform peaks scaled to dBFS for a meter-style overview
+/// Same min/max pair layout as `get_waveform_peaks`: each window's most negative and
+/// most positive sample as dBFS of their size, floored at -60 dB (silence reads -60)
+pub fn get_waveform_peaks_db(clip_id: u64, resolution: usize) -> Result<Vec<f32>, String> {
+    get_waveform_peaks(clip_id, resolution).map(|peaks| crate::waveform::peaks_to_db(&peaks))
 }
 
 /// Get clip duration in seconds
@@ -154,3 +187,36 @@ pub fn get_clip_duration(clip_id: u64) -> Result<f64, String> {
 
     Ok(clip.duration_seconds)
 }
+
+/// Get a loaded audio clip's properties as JSON:
+/// `{"sample_rate", "channels", "duration", "file_path"}`
+///
+/// `sample_rate` is the rate the audio is held at (the engine rate; files are
+/// resampled on load) and `file_path` is the file it was loaded from.
+pub fn get_clip_info(clip_id: u64) -> Result<String, String> {
+    let stored = get_audio_clips()?.lock().get(&clip_id).cloned();
+    // Clips not in the global map (e.g. rendered freezes) are looked up on the tracks
+    let clip = if let Some(clip) = stored {
+        clip
+    } else {
+        let graph_mutex = get_audio_graph()?;
+        let graph = graph_mutex.lock();
+        let track_manager = graph.track_manager.lock();
+        track_manager
+            .get_all_tracks()
+            .iter()
+            .find_map(|track_arc| {
+                let track = track_arc.lock();
+                track.audio_clips.iter().find(|c| c.id == clip_id).map(|c| c.clip.clone())
+            })
+            .ok_or_else(|| format!("Clip {clip_id} not found"))?
+    };
+
+    let info = serde_json::json!({
+        "sample_rate": clip.sample_rate,
+        "channels": clip.channels,
+        "duration": clip.duration_seconds,
+        "file_path": clip.file_path,
+    });
+    serde_json::to_string(&info).map_err(|e| e.to_string())
+}
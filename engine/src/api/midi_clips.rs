@@ -102,7 +102,7 @@ pub fn get_midi_clip_info(clip_id: u64) -> Result<String, String> {
 }
 
 /// Get MIDI notes from a clip
-/// Returns semicolon-separated list of notes: "`note,velocity,start_time,duration`"
+/// Returns semicolon-separated list of notes: "`note,velocity,start_time,duration,note_id`"
 pub fn get_midi_clip_notes(clip_id: u64) -> Result<String, String> {
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
@@ -135,31 +135,17 @@ pub fn get_midi_clip_notes(clip_id: u64) -> Result<String, String> {
 
 /// Helper function to extract notes from a MIDI clip
 fn extract_notes_from_clip(clip: &crate::midi::MidiClip, sample_rate: u32) -> String {
-    use crate::midi::MidiEventType;
-    use std::collections::HashMap;
-
-    let mut notes_info = Vec::new();
-    let mut note_starts: HashMap<u8, (u64, u8)> = HashMap::new(); // note -> (start_samples, velocity)
-
-    for event in &clip.events {
-        match event.event_type {
-            MidiEventType::NoteOn { note, velocity } => {
-                note_starts.insert(note, (event.timestamp_samples, velocity));
-            }
-            MidiEventType::NoteOff { note, .. } => {
-                if let Some((start_samples, velocity)) = note_starts.remove(&note) {
-                    let start_time = start_samples as f64 / f64::from(sample_rate);
-                    let end_time = event.timestamp_samples as f64 / f64::from(sample_rate);
-                    let duration = end_time - start_time;
-
-                    // Format: note,velocity,start_time,duration
-                    notes_info.push(format!("{note},{velocity},{start_time},{duration}"));
-                }
-            }
-        }
-    }
+    clip.notes()
+        .iter()
+        .map(|(note_id, note)| {
+            let start_time = note.start_samples as f64 / f64::from(sample_rate);
+            let duration = note.duration_samples as f64 / f64::from(sample_rate);
 
-    notes_info.join(";")
+            // Format: note,velocity,start_time,duration,note_id
+            format!("{},{},{start_time},{duration},{note_id}", note.pitch, note.velocity)
+        })
+        .collect::<Vec<_>>()
+        .join(";")
 }
 
 // ============================================================================
@@ -169,17 +155,14 @@ fn extract_notes_from_clip(clip: &crate::midi::MidiClip, sample_rate: u32) -> St
 /// Send MIDI note on event directly to synthesizer (for virtual piano)
 /// Also records the event if MIDI recording is active
 pub fn send_midi_note_on(note: u8, velocity: u8) -> Result<String, String> {
-    use crate::midi::{MidiEvent, MidiEventType};
+    use crate::midi::MidiEvent;
 
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
 
     // Get current playhead position for timestamping
     let timestamp_samples = graph.get_playhead_samples();
-    let event = MidiEvent {
-        event_type: MidiEventType::NoteOn { note, velocity },
-        timestamp_samples,
-    };
+    let event = MidiEvent::note_on(note, velocity, timestamp_samples);
 
     // Record to MIDI recorder if recording is active
     { let mut recorder = graph.midi_recorder.lock();
@@ -226,17 +209,14 @@ pub fn send_midi_note_on(note: u8, velocity: u8) -> Result<String, String> {
 /// Send MIDI note off event directly to synthesizer (for virtual piano)
 /// Also records the event if MIDI recording is active
 pub fn send_midi_note_off(note: u8, velocity: u8) -> Result<String, String> {
-    use crate::midi::{MidiEvent, MidiEventType};
+    use crate::midi::MidiEvent;
 
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
 
     // Get current playhead position for timestamping
     let timestamp_samples = graph.get_playhead_samples();
-    let event = MidiEvent {
-        event_type: MidiEventType::NoteOff { note, velocity },
-        timestamp_samples,
-    };
+    let event = MidiEvent::note_off(note, velocity, timestamp_samples);
 
     // Record to MIDI recorder if recording is active
     { let mut recorder = graph.midi_recorder.lock();
@@ -309,6 +289,8 @@ pub fn create_midi_clip() -> Result<u64, String> {
 /// * `velocity` - Note velocity (0-127)
 /// * `start_time` - Start time in seconds
 /// * `duration` - Duration in seconds
+///
+/// The message ends with the new note's ID: "... (note id N)"
 pub fn add_midi_note_to_clip(
     clip_id: u64,
     note: u8,
@@ -316,13 +298,11 @@ pub fn add_midi_note_to_clip(
     start_time: f64,
     duration: f64,
 ) -> Result<String, String> {
-    use crate::midi::MidiEvent;
-
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
 
     // Get the MIDI clip and modify it
-    {
+    let note_id = {
         let mut midi_clips = graph.get_midi_clips().lock();
         let timeline_clip = midi_clips
             .iter_mut()
@@ -337,24 +317,75 @@ pub fn add_midi_note_to_clip(
         let start_samples = (start_time * f64::from(crate::audio_file::TARGET_SAMPLE_RATE)) as u64;
         let duration_samples = (duration * f64::from(crate::audio_file::TARGET_SAMPLE_RATE)) as u64;
 
-        // Create note events
-        let note_on = MidiEvent::note_on(note, velocity, start_samples);
-        let note_off = MidiEvent::note_off(note, 64, start_samples + duration_samples);
+        clip_data.add_note(crate::midi::Note {
+            pitch: note,
+            velocity,
+            start_samples,
+            duration_samples,
+        })
+    };
+
+    // Sync the updated clip to the track (needed because Arc::make_mut may have created a new copy)
+    graph.sync_midi_clip_to_track(clip_id);
 
-        // Add events to clip
-        clip_data.add_event(note_on);
-        clip_data.add_event(note_off);
+    Ok(format!("Added note {note} at {start_time:.3}s, duration {duration:.3}s (note id {note_id})"))
+}
+
+/// Apply a note edit to a MIDI clip and sync it to its track
+fn edit_midi_clip_notes(
+    clip_id: u64,
+    edit: impl FnOnce(&mut crate::midi::MidiClip) -> Result<(), String>,
+) -> Result<(), String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    {
+        let mut midi_clips = graph.get_midi_clips().lock();
+        let timeline_clip = midi_clips
+            .iter_mut()
+            .find(|c| c.id == clip_id)
+            .ok_or("MIDI clip not found")?;
+        edit(Arc::make_mut(&mut timeline_clip.clip))?;
     }
 
     // Sync the updated clip to the track (needed because Arc::make_mut may have created a new copy)
     graph.sync_midi_clip_to_track(clip_id);
+    Ok(())
+}
+
+/// Move a note to a new start time (seconds) and pitch, keeping its length
+pub fn move_midi_note(clip_id: u64, note_id: u32, new_start: f64, new_pitch: u8) -> Result<String, String> {
+    if !new_start.is_finite() || new_start < 0.0 {
+        return Err(format!("Invalid note start: {new_start}"));
+    }
+    if new_pitch > 127 {
+        return Err(format!("Invalid note pitch: {new_pitch}"));
+    }
+    let start_samples = (new_start * f64::from(crate::audio_file::TARGET_SAMPLE_RATE)) as u64;
+    edit_midi_clip_notes(clip_id, |clip| clip.move_note(note_id, start_samples, new_pitch))?;
+    Ok(format!("Moved note {note_id} to {new_start:.3}s, pitch {new_pitch}"))
+}
+
+/// Change a note's length (seconds)
+pub fn resize_midi_note(clip_id: u64, note_id: u32, new_duration: f64) -> Result<String, String> {
+    if !new_duration.is_finite() || new_duration <= 0.0 {
+        return Err(format!("Invalid note duration: {new_duration}"));
+    }
+    let duration_samples = (new_duration * f64::from(crate::audio_file::TARGET_SAMPLE_RATE)) as u64;
+    edit_midi_clip_notes(clip_id, |clip| clip.resize_note(note_id, duration_samples))?;
+    Ok(format!("Resized note {note_id} to {new_duration:.3}s"))
+}
 
-    Ok(format!("Added note {note} at {start_time:.3}s, duration {duration:.3}s"))
+/// Remove a single note from a MIDI clip
+pub fn delete_midi_note(clip_id: u64, note_id: u32) -> Result<String, String> {
+    edit_midi_clip_notes(clip_id, |clip| clip.delete_note(note_id))?;
+    Ok(format!("Deleted note {note_id}"))
 }
 
 /// Get all MIDI events from a clip
 /// Returns: Vec<(`event_type`, note, velocity, `timestamp_seconds`)>
-/// `event_type`: 0 = `NoteOn`, 1 = `NoteOff`
+/// `event_type`: 0 = `NoteOn`, 1 = `NoteOff`, 2 = CC (controller, value),
+/// 3 = poly aftertouch (note, pressure), 4 = pitch bend (14-bit value as MSB, LSB)
 pub fn get_midi_clip_events(clip_id: u64) -> Result<Vec<(i32, u8, u8, f64)>, String> {
     use crate::midi::MidiEventType;
 
@@ -377,6 +408,12 @@ pub fn get_midi_clip_events(clip_id: u64) -> Result<Vec<(i32, u8, u8, f64)>, Str
             let (event_type, note, velocity) = match event.event_type {
                 MidiEventType::NoteOn { note, velocity } => (0, note, velocity),
                 MidiEventType::NoteOff { note, velocity } => (1, note, velocity),
+                MidiEventType::ControlChange { controller, value } => (2, controller, value),
+                MidiEventType::PolyAftertouch { note, pressure } => (3, note, pressure),
+                MidiEventType::PitchBend { value } => {
+                    let raw = (i32::from(value) + 8192).clamp(0, 16383);
+                    (4, (raw >> 7) as u8, (raw & 0x7F) as u8)
+                }
             };
             let timestamp_seconds = event.timestamp_samples as f64 / f64::from(crate::audio_file::TARGET_SAMPLE_RATE);
             (event_type, note, velocity, timestamp_seconds)
@@ -439,30 +476,122 @@ pub fn clear_midi_clip(clip_id: u64) -> Result<String, String> {
 /// # Arguments
 /// * `clip_id` - The MIDI clip ID
 /// * `grid_division` - Grid division (4 = quarter note, 8 = eighth note, 16 = sixteenth note, etc.)
-pub fn quantize_midi_clip(clip_id: u64, grid_division: u32) -> Result<String, String> {
+/// * `swing` - Swing amount in percent (0 = straight, 100 = every second grid line delayed by half a step)
+/// * `strength` - How far notes move toward the grid (0.0-1.0, 1.0 = snap)
+pub fn quantize_midi_clip(clip_id: u64, grid_division: u32, swing: f32, strength: f32) -> Result<String, String> {
+    if grid_division == 0 {
+        return Err("Grid division must be at least 1".to_string());
+    }
+    if !swing.is_finite() || !strength.is_finite() {
+        return Err("Invalid swing or strength".to_string());
+    }
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
 
-    // Get the MIDI clip
-    let mut midi_clips = graph.get_midi_clips().lock();
-    let timeline_clip = midi_clips
-        .iter_mut()
-        .find(|c| c.id == clip_id)
-        .ok_or("MIDI clip not found")?;
+    {
+        // Get the MIDI clip
+        let mut midi_clips = graph.get_midi_clips().lock();
+        let timeline_clip = midi_clips
+            .iter_mut()
+            .find(|c| c.id == clip_id)
+            .ok_or("MIDI clip not found")?;
 
-    // Calculate grid size in samples based on tempo (assume 120 BPM for now)
-    let tempo = 120.0;
-    let seconds_per_beat = 60.0 / tempo;
-    let samples_per_beat = (seconds_per_beat * f64::from(crate::audio_file::TARGET_SAMPLE_RATE)) as u64;
-    let grid_samples = samples_per_beat / u64::from(grid_division);
+        // Calculate grid size in samples from the tempo at the clip start
+        let clip_start_samples = (timeline_clip.start_time * f64::from(crate::audio_file::TARGET_SAMPLE_RATE)) as u64;
+        let samples_per_beat = graph.recorder.get_tempo_map().segment_at_sample(clip_start_samples).samples_per_beat() as u64;
+        let grid_samples = samples_per_beat / u64::from(grid_division);
 
-    // Get mutable reference to the clip data
-    let clip_data: &mut crate::midi::MidiClip = Arc::make_mut(&mut timeline_clip.clip);
+        // Get mutable reference to the clip data
+        let clip_data: &mut crate::midi::MidiClip = Arc::make_mut(&mut timeline_clip.clip);
+
+        // Quantize the clip
+        clip_data.quantize_with_groove(grid_samples, swing.clamp(0.0, 100.0) / 100.0, strength);
+    }
+
+    // Sync the updated clip to the track (needed because Arc::make_mut may have created a new copy)
+    graph.sync_midi_clip_to_track(clip_id);
+
+    if swing > 0.0 {
+        Ok(format!("Quantized to 1/{grid_division} note grid with {swing}% swing"))
+    } else {
+        Ok(format!("Quantized to 1/{grid_division} note grid"))
+    }
+}
+
+/// Randomly vary the timing and velocity of every note in a MIDI clip
+///
+/// # Arguments
+/// * `clip_id` - The MIDI clip ID
+/// * `timing_ms` - Maximum start time change either way (milliseconds)
+/// * `velocity_amount` - Maximum velocity change either way
+/// * `seed` - Random seed (the same seed gives the same result)
+pub fn humanize_midi_clip(clip_id: u64, timing_ms: f64, velocity_amount: u8, seed: u64) -> Result<String, String> {
+    if !timing_ms.is_finite() || timing_ms < 0.0 {
+        return Err(format!("Invalid humanize timing: {timing_ms}"));
+    }
+    let timing_samples = (timing_ms / 1000.0 * f64::from(crate::audio_file::TARGET_SAMPLE_RATE)) as u64;
+    edit_midi_clip_notes(clip_id, |clip| {
+        clip.humanize(timing_samples, velocity_amount.min(127), seed);
+        Ok(())
+    })?;
+    Ok(format!("Humanized notes (±{timing_ms}ms, ±{velocity_amount} velocity)"))
+}
+
+/// Transpose every note in a MIDI clip by `semitones`
+///
+/// Notes pushed outside 0-127 are removed.
+pub fn transpose_midi_clip(clip_id: u64, semitones: i32) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    let dropped = {
+        let mut midi_clips = graph.get_midi_clips().lock();
+        let timeline_clip = midi_clips
+            .iter_mut()
+            .find(|c| c.id == clip_id)
+            .ok_or("MIDI clip not found")?;
+        Arc::make_mut(&mut timeline_clip.clip).transpose(semitones)
+    };
+
+    graph.sync_midi_clip_to_track(clip_id);
+
+    if dropped > 0 {
+        Ok(format!("Transposed by {semitones} semitones ({dropped} notes out of range removed)"))
+    } else {
+        Ok(format!("Transposed by {semitones} semitones"))
+    }
+}
+
+/// Snap every note in a MIDI clip to the nearest pitch of a scale
+///
+/// # Arguments
+/// * `clip_id` - The MIDI clip ID
+/// * `root` - Root pitch class (0 = C, 1 = C#, ... 11 = B)
+/// * `scale_mask` - Bit `i` set = the note `i` semitones above the root is in the scale
+///   (e.g. major = 0b1010_1011_0101)
+pub fn scale_quantize_midi_clip(clip_id: u64, root: u8, scale_mask: u16) -> Result<String, String> {
+    if root > 11 {
+        return Err(format!("Invalid scale root: {root} (expected 0-11)"));
+    }
+    // None of the 12 pitch-class bits set
+    if scale_mask.trailing_zeros() >= 12 {
+        return Err("Scale has no notes".to_string());
+    }
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    {
+        let mut midi_clips = graph.get_midi_clips().lock();
+        let timeline_clip = midi_clips
+            .iter_mut()
+            .find(|c| c.id == clip_id)
+            .ok_or("MIDI clip not found")?;
+        Arc::make_mut(&mut timeline_clip.clip).scale_quantize(root, scale_mask);
+    }
 
-    // Quantize the clip
-    clip_data.quantize(grid_samples);
+    graph.sync_midi_clip_to_track(clip_id);
 
-    Ok(format!("Quantized to 1/{grid_division} note grid"))
+    Ok("Snapped notes to scale".to_string())
 }
 
 // ============================================================================
@@ -497,6 +626,8 @@ pub fn add_midi_clip_to_track_api(track_id: u64, clip_id: u64, start_time_second
     // Add the clip to the track's timeline (use the same clip_id for consistency)
     graph.add_midi_clip_to_track(track_id, clip_arc, start_time_seconds, clip_id)
         .ok_or(format!("Failed to add MIDI clip to track {track_id}"))?;
+    // Bar snapping follows the signature where the clip now starts
+    graph.refresh_midi_clip_time_signatures();
 
     Ok(())
 }
@@ -522,3 +653,78 @@ pub fn remove_midi_clip(track_id: u64, clip_id: u64) -> Result<bool, String> {
     let removed = graph.remove_clip(clip_id);
     Ok(removed)
 }
+
+// ============================================================================
+// MIDI FILE IMPORT / EXPORT
+// ============================================================================
+
+/// Import a standard MIDI file, creating one MIDI track and clip per file track
+///
+/// Type-0 files produce a single track; type-1 files produce one track per
+/// file track that contains notes. Clips are placed at the start of the timeline.
+///
+/// # Returns
+/// The IDs of the newly created MIDI clips
+pub fn import_midi_file(path: String) -> Result<Vec<u64>, String> {
+    use crate::midi::MidiClip;
+    use crate::audio_file::TARGET_SAMPLE_RATE;
+
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    let imported = crate::midi_file::parse_midi_file(&bytes, TARGET_SAMPLE_RATE)?;
+    if imported.is_empty() {
+        return Err(format!("No notes found in {path}"));
+    }
+
+    let file_stem = std::path::Path::new(&path)
+        .file_stem()
+        .map_or_else(|| "MIDI".to_string(), |s| s.to_string_lossy().to_string());
+
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    let mut clip_ids = Vec::with_capacity(imported.len());
+    for (index, midi_track) in imported.into_iter().enumerate() {
+        let name = midi_track.name.unwrap_or_else(|| {
+            if index == 0 { file_stem.clone() } else { format!("{file_stem} {}", index + 1) }
+        });
+        let track_id = graph.track_manager.lock().create_track(TrackType::Midi, name);
+
+        let clip_arc = Arc::new(MidiClip::with_events(midi_track.events, TARGET_SAMPLE_RATE));
+        let clip_id = graph.add_midi_clip(clip_arc.clone(), 0.0);
+        if let Some(timeline_clip) = graph.get_midi_clips().lock().iter_mut().find(|c| c.id == clip_id) {
+            timeline_clip.track_id = Some(track_id);
+        }
+        graph.add_midi_clip_to_track(track_id, clip_arc, 0.0, clip_id)
+            .ok_or(format!("Failed to add MIDI clip to track {track_id}"))?;
+
+        clip_ids.push(clip_id);
+    }
+
+    eprintln!("🎹 [API] Imported {} MIDI clip(s) from {path}", clip_ids.len());
+    Ok(clip_ids)
+}
+
+/// Export a MIDI clip to a type-0 standard MIDI file
+///
+/// Event timestamps are converted to ticks at the tempo in effect at the clip start.
+pub fn export_midi_clip(clip_id: u64, path: String) -> Result<(), String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    let (clip_arc, start_time) = graph
+        .get_midi_clips()
+        .lock()
+        .iter()
+        .find(|c| c.id == clip_id)
+        .map(|c| (c.clip.clone(), c.start_time))
+        .ok_or(format!("MIDI clip {clip_id} not found"))?;
+    let clip_start_samples = (start_time * f64::from(crate::audio_file::TARGET_SAMPLE_RATE)) as u64;
+    let tempo = graph.recorder.get_tempo_map().segment_at_sample(clip_start_samples).bpm();
+    drop(graph);
+
+    let bytes = crate::midi_file::write_midi_file(&clip_arc.events, clip_arc.sample_rate, tempo)?;
+    std::fs::write(&path, bytes).map_err(|e| format!("Failed to write {path}: {e}"))?;
+
+    eprintln!("🎹 [API] Exported MIDI clip {clip_id} to {path}");
+    Ok(())
+}
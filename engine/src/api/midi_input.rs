@@ -133,14 +133,16 @@ pub fn start_midi_input() -> Result<String, String> {
 
         // 2. Route to all armed MIDI track synthesizers and VST3 instruments
         // Collect armed tracks with their FX chains (MIDI and Sampler tracks can receive MIDI)
-        let armed_tracks_with_fx: Vec<(TrackId, Vec<u64>)> = {
+        let armed_tracks_with_fx: Vec<(TrackId, Vec<u64>, bool)> = {
             { let tm = track_manager.lock();
+                let em = effect_manager.lock();
                 tm.get_all_tracks()
                     .iter()
                     .filter_map(|track_arc| {
                         { let track = track_arc.lock();
                             if (track.track_type == TrackType::Midi || track.track_type == TrackType::Sampler) && track.armed {
-                                Some((track.id, track.fx_chain.clone()))
+                                let has_instrument = em.chain_has_instrument(&track.fx_chain);
+                                Some((track.id, track.fx_chain.clone(), track.plays_builtin_synth(has_instrument)))
                             } else {
                                 None
                             }
@@ -150,13 +152,10 @@ pub fn start_midi_input() -> Result<String, String> {
             }
         };
 
-        // Route to each armed track's synthesizer OR VST3 instruments (not both)
-        for (track_id, fx_chain) in armed_tracks_with_fx {
-            // Check if track has VST3 plugins - if so, skip built-in synth
-            let has_vst3 = !fx_chain.is_empty();
-
-            // Only route to built-in synth if NO VST3 plugins in the chain
-            if !has_vst3 {
+        // Route to each armed track's VST3 plugins, and to its built-in synth unless
+        // a VST3 instrument replaces it
+        for (track_id, fx_chain, plays_synth) in armed_tracks_with_fx {
+            if plays_synth {
                 { let mut sm = synth_manager.lock();
                     match &engine_event.event_type {
                         MidiEventType::NoteOn { note, velocity } => {
@@ -165,13 +164,20 @@ pub fn start_midi_input() -> Result<String, String> {
                         MidiEventType::NoteOff { note, velocity: _ } => {
                             sm.note_off(track_id, *note);
                         }
+                        MidiEventType::PitchBend { value } => sm.pitch_bend(track_id, *value),
+                        MidiEventType::ControlChange { controller, value } => {
+                            sm.control_change(track_id, *controller, *value);
+                        }
+                        MidiEventType::PolyAftertouch { note, pressure } => {
+                            sm.poly_aftertouch(track_id, *note, *pressure);
+                        }
                     }
                 }
             }
 
-            // Route to VST3 instruments in the track's FX chain
+            // Route to VST3 plugins in the track's FX chain
             #[cfg(all(feature = "vst3", not(target_os = "ios")))]
-            if has_vst3 {
+            if !fx_chain.is_empty() {
                 { let em = effect_manager.lock();
                     for effect_id in fx_chain {
                         if let Some(effect_arc) = em.get_effect(effect_id) {
@@ -190,6 +196,8 @@ pub fn start_midi_input() -> Result<String, String> {
                                                 eprintln!("⚠️ [MIDI] Failed to send note off to VST3 {effect_id}: {e}");
                                             }
                                         }
+                                        // The VST3 host only takes note events so far
+                                        _ => {}
                                     }
                                 }
                             }
@@ -235,11 +243,11 @@ pub fn start_midi_recording() -> Result<String, String> {
 
     let count_in_bars = graph.recorder.get_count_in_bars();
     let tempo = graph.recorder.get_tempo();
-    let time_sig = graph.recorder.get_time_signature();
+    let bar_quarter_notes = graph.recorder.get_base_time_signature().quarter_notes_per_bar();
 
     // Calculate count-in duration in seconds and samples
     let count_in_seconds = if count_in_bars > 0 {
-        f64::from(count_in_bars) * f64::from(time_sig) * 60.0 / tempo
+        f64::from(count_in_bars) * bar_quarter_notes * 60.0 / tempo
     } else {
         0.0
     };
@@ -346,6 +354,8 @@ pub fn get_midi_recording_state() -> Result<i32, String> {
 /// Returns CSV: "`note,velocity,type,timestamp_samples`;..." where type: 0=NoteOff, 1=NoteOn
 /// Returns empty string if not recording or no events
 pub fn get_midi_recorder_live_events() -> Result<String, String> {
+    use std::fmt::Write;
+
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
 
@@ -360,22 +370,18 @@ pub fn get_midi_recorder_live_events() -> Result<String, String> {
         return Ok(String::new());
     }
 
-    // Build CSV: "note,velocity,type,timestamp_samples;..."
+    // Build CSV: "note,velocity,type,timestamp_samples;..." (notes only)
     let mut result = String::with_capacity(events.len() * 20);
-    for (i, event) in events.iter().enumerate() {
-        if i > 0 {
+    for event in events {
+        let (note, velocity, kind) = match &event.event_type {
+            MidiEventType::NoteOn { note, velocity } => (note, velocity, 1),
+            MidiEventType::NoteOff { note, velocity } => (note, velocity, 0),
+            _ => continue,
+        };
+        if !result.is_empty() {
             result.push(';');
         }
-        match &event.event_type {
-            MidiEventType::NoteOn { note, velocity } => {
-                use std::fmt::Write;
-                let _ = write!(result, "{},{},1,{}", note, velocity, event.timestamp_samples);
-            }
-            MidiEventType::NoteOff { note, velocity } => {
-                use std::fmt::Write;
-                let _ = write!(result, "{},{},0,{}", note, velocity, event.timestamp_samples);
-            }
-        }
+        let _ = write!(result, "{},{},{},{}", note, velocity, kind, event.timestamp_samples);
     }
 
     Ok(result)
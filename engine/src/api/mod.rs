@@ -13,11 +13,17 @@
 //! - `effects` - Effect chains
 //! - `vst3` - VST3 plugins
 //! - `project` - Save/load/export
+//! - `autosave` - Periodic recovery snapshots
+//! - `history` - Undo/redo
 //! - `synthesizer` - Per-track synth
+//! - `diagnostics` - Engine state dump for bug reports
 
 // Submodules
+pub mod autosave;
+pub mod diagnostics;
 pub mod effects;
 pub mod helpers;
+pub mod history;
 pub mod init;
 pub mod latency;
 pub mod midi_clips;
@@ -35,21 +41,33 @@ pub mod vst3;
 
 // Re-export all public functions from submodules
 pub use effects::{
-    add_effect_to_track, get_effect_bypass, get_effect_info, get_track_effects,
-    remove_effect_from_track, reorder_track_effects, set_effect_bypass, set_effect_parameter,
+    add_effect_to_track, get_effect_bypass, get_effect_info, get_master_limiter_gain_reduction,
+    get_track_effects, remove_effect_from_track, reorder_track_effects, set_compressor_sidechain,
+    set_delay_sync, set_effect_bypass, set_effect_parameter, set_effect_parameter_automation,
+    set_master_limiter_params,
 };
+pub use autosave::{
+    has_recovery_snapshot, is_autosave_running, load_recovery_snapshot, start_autosave,
+    stop_autosave,
+};
+pub use diagnostics::get_engine_diagnostics;
 pub use helpers::{get_audio_clips, get_audio_graph, AUDIO_CLIPS, AUDIO_GRAPH};
+pub use history::{can_redo, can_undo, redo, undo};
 pub use init::{init_audio_engine, init_audio_graph, play_sine_wave};
 pub use latency::{
-    get_actual_buffer_size, get_buffer_size_preset, get_clip_duration, get_latency_info,
-    get_latency_test_error, get_latency_test_status, get_waveform_peaks, set_buffer_size,
-    start_latency_test, stop_latency_test,
+    get_actual_buffer_size, get_buffer_size_preset, get_clip_duration, get_clip_info, get_dsp_load_percent,
+    get_latency_info, get_reported_latency,
+    get_latency_test_error, get_latency_test_status, get_waveform_peaks, get_waveform_peaks_db,
+    get_xrun_count,
+    reset_xrun_count, set_buffer_size, start_latency_test, stop_latency_test,
 };
 pub use midi_clips::{
     add_midi_clip_to_track_api, add_midi_clip_to_track_api as add_midi_clip_to_track,
-    add_midi_note_to_clip, clear_midi_clip, create_midi_clip, get_all_midi_clips_info,
-    get_midi_clip_count, get_midi_clip_events, get_midi_clip_info, get_midi_clip_notes,
-    quantize_midi_clip, remove_midi_clip, remove_midi_event, send_midi_note_off, send_midi_note_on,
+    add_midi_note_to_clip, clear_midi_clip, create_midi_clip, delete_midi_note, export_midi_clip,
+    get_all_midi_clips_info, get_midi_clip_count, get_midi_clip_events, get_midi_clip_info,
+    get_midi_clip_notes, humanize_midi_clip, import_midi_file, move_midi_note, quantize_midi_clip, remove_midi_clip,
+    remove_midi_event, resize_midi_note, scale_quantize_midi_clip, send_midi_note_off,
+    send_midi_note_on, transpose_midi_clip,
 };
 pub use midi_input::{
     get_midi_input_devices, get_midi_recorder_live_events, get_midi_recording_state,
@@ -57,46 +75,67 @@ pub use midi_input::{
     start_midi_input, start_midi_recording, stop_midi_input, stop_midi_recording,
 };
 pub use project::{
-    export_audio, export_mp3_with_options, export_stems, export_to_wav, export_wav_with_options,
-    get_tracks_for_stems, is_ffmpeg_available, load_project, save_project, write_mp3_metadata,
+    export_audio, export_flac_with_options, export_mp3_with_options, export_ogg_with_options,
+    export_stems, export_to_wav, export_wav_with_options, get_tracks_for_stems,
+    is_ffmpeg_available, load_project, save_project, write_mp3_metadata,
 };
 pub use recording::{
-    get_audio_input_devices, get_audio_output_devices, get_count_in_bars, get_count_in_beat,
-    get_count_in_progress, get_input_channel_count, get_input_channel_level,
-    get_punch_in_seconds, get_punch_out_seconds, get_recorded_duration, get_recording_state,
-    get_recording_waveform, get_sample_rate, get_selected_audio_output_device,
-    is_punch_complete, is_punch_in_enabled, is_punch_out_enabled, set_audio_input_device,
-    set_audio_output_device, set_count_in_bars, set_punch_in_enabled, set_punch_out_enabled,
-    set_punch_region, start_audio_input, start_recording, stop_audio_input, stop_recording,
+    get_audio_device_status, get_audio_input_devices, get_audio_output_devices, get_count_in_bars,
+    get_count_in_beat, get_count_in_progress, get_input_channel_count, get_input_channel_level,
+    get_input_sample_rate, get_last_recording_takes, get_monitor_mode, get_punch_in_seconds,
+    get_punch_out_seconds, get_recorded_duration, get_recording_latency_compensation,
+    get_recording_state, get_recording_waveform, get_sample_rate, get_selected_audio_input_device,
+    get_selected_audio_output_device, is_punch_complete, is_punch_in_enabled, is_punch_out_enabled,
+    set_audio_input_device, set_audio_input_device_by_name, set_audio_output_device,
+    set_count_in_bars, set_monitor_mode, set_punch_in_enabled, set_punch_out_enabled,
+    set_punch_region, set_recording_latency_compensation, start_audio_input, start_recording,
+    stop_audio_input, stop_recording, take_audio_device_changed,
 };
 pub use synthesizer::{
-    create_sampler_for_track, get_sampler_info, get_sampler_waveform_peaks,
-    get_synth_parameters, is_sampler_track, load_sample_for_track,
-    send_track_midi_note_off, send_track_midi_note_on, set_sampler_parameter, set_synth_parameter,
-    set_track_instrument,
+    add_sample_zone_for_track, create_sampler_for_track, get_sampler_info,
+    get_sampler_waveform_peaks, get_synth_envelope, get_synth_parameters, is_sampler_track,
+    load_sample_for_track, send_track_midi_note_off, send_track_midi_note_on,
+    set_sampler_parameter, set_sequencer_step, set_synth_parameter, set_synth_pitch_bend_range, set_track_arp_enabled,
+    set_track_arp_params, set_track_instrument, set_track_velocity_curve,
+};
+pub use timing::{
+    add_tempo_change, add_time_signature_change, beat_to_seconds, get_tempo, get_tempo_map,
+    get_time_signature, get_time_signature_map, is_metronome_enabled, remove_tempo_change,
+    remove_time_signature_change, seconds_to_beat, set_metronome_accent_enabled,
+    set_metronome_enabled, set_metronome_samples, set_metronome_subdivision, set_tempo,
+    set_time_signature,
 };
-pub use timing::{get_tempo, get_time_signature, is_metronome_enabled, set_metronome_enabled, set_tempo, set_time_signature};
 pub use tracks::{
-    create_track, get_all_track_ids, get_track_count, get_track_info, get_track_input,
-    get_track_peak_levels, move_clip_to_track, set_track_armed, set_track_input,
-    set_track_input_monitoring, set_track_mute, set_track_name, set_track_pan, set_track_solo,
-    set_track_volume, set_track_volume_automation,
+    clear_reference_track, create_track, freeze_track, get_all_track_ids, get_master_loudness,
+    get_master_spectrum, get_master_true_peak, get_reference_state,
+    get_track_count, get_track_info, get_track_input, get_track_peak_levels, is_track_frozen,
+    load_reference_track, match_reference_loudness, move_clip_to_track, reset_master_loudness,
+    set_reference_gain_db, toggle_reference, set_master_spectrum_settings, set_master_true_peak_enabled, set_track_armed,
+    set_track_group, set_track_input, set_track_input_gain, set_track_input_monitoring, set_track_layer_synth,
+    set_track_metering_point,
+    set_track_monitor_through_fx, set_track_mono_sum, set_track_mute, set_track_name,
+    set_track_pan, set_track_pan_automation, set_track_phase_invert, set_track_solo, set_track_solo_safe, set_track_volume, set_track_volume_automation,
+    split_audio_clip, split_midi_clip, unfreeze_track,
 };
 pub use transport::{
-    get_playhead_position, get_play_start_position, get_record_start_position, get_transport_state,
-    set_play_start_position, set_record_start_position, transport_pause, transport_play,
-    transport_seek, transport_stop,
+    add_marker, get_markers, get_playhead_position, get_play_start_position,
+    get_record_start_position, get_transport_state, is_loop_enabled, is_playback_counting_in,
+    jump_to_marker, jump_to_next_marker, jump_to_previous_marker, remove_marker, scrub_stop,
+    scrub_to, set_loop_enabled, set_loop_region, set_loop_to_markers, set_play_start_position,
+    set_record_start_position, start_playback_with_count_in, transport_pause, transport_play,
+    transport_seek, transport_seek_clean, transport_stop,
 };
 pub use preview::{
     preview_get_duration, preview_get_position, preview_get_waveform, preview_is_looping,
     preview_check_full_clip, preview_is_loaded, preview_is_playing, preview_load_audio,
     preview_load_audio_async,
-    preview_play, preview_process_sample, preview_seek, preview_set_looping, preview_stop,
+    preview_play, preview_process_sample, preview_seek, preview_set_fade_curve,
+    preview_set_fade_duration_ms, preview_set_looping, preview_stop,
 };
 
 #[cfg(all(feature = "vst3", not(target_os = "ios")))]
 pub use vst3::{
-    add_vst3_effect_to_track, get_vst3_parameter_count, get_vst3_parameter_info,
+    add_vst3_effect_to_track, get_vst3_latency, get_vst3_parameter_count, get_vst3_parameter_info,
     get_vst3_parameter_value, get_vst3_state, scan_vst3_plugins, scan_vst3_plugins_standard,
     set_vst3_parameter_value, set_vst3_state, vst3_attach_editor, vst3_close_editor,
     vst3_get_editor_size, vst3_has_editor, vst3_open_editor, vst3_send_midi_note,
@@ -106,7 +145,7 @@ pub use vst3::{
 // REMAINING FUNCTIONS (audio file loading and track utilities)
 // ============================================================================
 
-use crate::audio_file::load_audio_file;
+use crate::audio_file::{load_audio_file, load_audio_file_streaming, AudioClip};
 use std::sync::Arc;
 
 // Re-use helpers for global state access
@@ -119,6 +158,24 @@ pub fn load_audio_file_to_track_api(
     start_time: f64,
 ) -> Result<u64, String> {
     let clip = load_audio_file(&path).map_err(|e| e.to_string())?;
+    add_loaded_clip_to_track(clip, track_id, start_time)
+}
+
+/// Open an audio file for streaming on a specific track and return a clip ID
+///
+/// For very long files: a 48 kHz WAV is read from disk during playback instead of
+/// being decoded into memory (other files load normally). Streamed clips can't be
+/// warped, transposed or channel-converted.
+pub fn load_audio_file_to_track_streaming_api(
+    path: String,
+    track_id: u64,
+    start_time: f64,
+) -> Result<u64, String> {
+    let clip = load_audio_file_streaming(&path).map_err(|e| e.to_string())?;
+    add_loaded_clip_to_track(clip, track_id, start_time)
+}
+
+fn add_loaded_clip_to_track(clip: AudioClip, track_id: u64, start_time: f64) -> Result<u64, String> {
     let clip_arc = Arc::new(clip);
 
     let clips_mutex = clips()?;
@@ -132,6 +189,7 @@ pub fn load_audio_file_to_track_api(
         .ok_or(format!("Failed to add clip to track {track_id}"))?;
 
     clips_map.insert(clip_id, clip_arc);
+    history::record_clip_added(&graph, track_id, clip_id);
 
     Ok(clip_id)
 }
@@ -2,7 +2,7 @@
 //!
 //! Provides preview playback independent of the main timeline transport.
 
-use crate::preview::PreviewPlayer;
+use crate::preview::{PreviewFadeCurve, PreviewPlayer};
 use std::sync::{Arc, OnceLock};
 use parking_lot::Mutex;
 
@@ -51,6 +51,24 @@ pub fn preview_stop() -> Result<(), String> {
     Ok(())
 }
 
+/// Set the fade-out curve used when preview stops (0=linear, 1=exponential, 2=equal-power)
+pub fn preview_set_fade_curve(curve: i32) -> Result<(), String> {
+    let curve = PreviewFadeCurve::from_index(curve)
+        .ok_or_else(|| format!("Invalid fade curve {curve} (expected 0-2)"))?;
+    let player = get_preview_player();
+    let mut guard = player.lock();
+    guard.set_fade_curve(curve);
+    Ok(())
+}
+
+/// Set the fade-out length used when preview stops, in milliseconds (0 = cut)
+pub fn preview_set_fade_duration_ms(ms: u32) -> Result<(), String> {
+    let player = get_preview_player();
+    let mut guard = player.lock();
+    guard.set_fade_duration_ms(ms);
+    Ok(())
+}
+
 /// Seek to a position in seconds
 pub fn preview_seek(position_seconds: f64) -> Result<(), String> {
     let player = get_preview_player();
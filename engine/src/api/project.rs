@@ -3,6 +3,7 @@
 //! Functions for saving, loading, and exporting projects.
 
 use super::helpers::{get_audio_clips, get_audio_graph};
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -35,18 +36,23 @@ pub fn save_project(project_name: String, project_path_str: String) -> Result<St
     let mut project_data = graph.export_to_project_data(project_name);
 
     // Copy audio files to project folder and update paths
+    // (each unique file is stored once; clips sharing a source share its path)
     let clips_mutex = get_audio_clips()?;
     let clips_map = clips_mutex.lock();
+    let mut stored_paths: HashMap<&str, String> = HashMap::new();
 
     for audio_file in &mut project_data.audio_files {
         // Find the corresponding clip
         if let Some(clip_arc) = clips_map.get(&audio_file.id) {
-            let source_path = Path::new(&clip_arc.file_path);
-
-            // Copy file to project folder
-            let relative_path =
-                project::copy_audio_file_to_project(source_path, project_path, audio_file.id)
+            let relative_path = if let Some(stored) = stored_paths.get(clip_arc.file_path.as_str()) {
+                stored.clone()
+            } else {
+                // Copy file to project folder
+                let stored = project::copy_audio_file_to_project(Path::new(&clip_arc.file_path), project_path)
                     .map_err(|e| e.to_string())?;
+                stored_paths.insert(clip_arc.file_path.as_str(), stored.clone());
+                stored
+            };
 
             // Update the relative path in project data
             audio_file.relative_path = relative_path;
@@ -68,7 +74,6 @@ pub fn save_project(project_name: String, project_path_str: String) -> Result<St
 /// # Returns
 /// Success message with project name
 pub fn load_project(project_path_str: String) -> Result<String, String> {
-    use crate::audio_file::load_audio_file;
     use crate::project;
 
     let project_path = Path::new(&project_path_str);
@@ -78,6 +83,19 @@ pub fn load_project(project_path_str: String) -> Result<String, String> {
     // Load project data from JSON
     let project_data = project::load_project(project_path).map_err(|e| e.to_string())?;
 
+    restore_project(&project_data, project_path)?;
+
+    eprintln!("✅ [API] Project loaded successfully");
+    Ok(format!("Loaded project: {}", project_data.name))
+}
+
+/// Replace the current project with `project_data`, loading its audio files
+///
+/// Audio file paths are resolved against `base_path` (absolute paths are used as-is).
+pub(super) fn restore_project(project_data: &crate::project::ProjectData, base_path: &Path) -> Result<(), String> {
+    use crate::audio_file::load_audio_file;
+    use crate::project;
+
     // Get audio graph
     let graph_mutex = get_audio_graph()?;
     let mut graph = graph_mutex.lock();
@@ -92,20 +110,41 @@ pub fn load_project(project_path_str: String) -> Result<String, String> {
     let clips_mutex = get_audio_clips()?;
     let mut clips_map = clips_mutex.lock();
 
-    // Clear existing clips
+    // Clear existing clips (and the undo history that refers to them)
     clips_map.clear();
+    super::history::clear_history();
+
+    // Clips referencing the same stored file share one decoded copy
+    // (older projects store one file per clip, which loads the same way)
+    let mut loaded: HashMap<std::path::PathBuf, Arc<crate::audio_file::AudioClip>> = HashMap::new();
 
     for audio_file_data in &project_data.audio_files {
         let audio_file_path =
-            project::resolve_audio_file_path(project_path, &audio_file_data.relative_path);
-
-        eprintln!("📁 [API] Loading audio file: {}", audio_file_path.display());
+            project::resolve_audio_file_path(base_path, &audio_file_data.relative_path);
 
-        // Load the audio file
-        let clip = load_audio_file(&audio_file_path)
-            .map_err(|e| format!("Failed to load audio file {}: {e}", audio_file_path.display()))?;
+        let clip_arc = if let Some(clip_arc) = loaded.get(&audio_file_path) {
+            clip_arc.clone()
+        } else {
+            eprintln!("📁 [API] Loading audio file: {}", audio_file_path.display());
 
-        let clip_arc = Arc::new(clip);
+            // Load the audio file (long recordings stay streamed from disk)
+            let clip = if audio_file_data.streamed {
+                crate::audio_file::load_audio_file_streaming(&audio_file_path)
+            } else {
+                load_audio_file(&audio_file_path)
+            }
+                .map_err(|e| format!("Failed to load audio file {}: {e}", audio_file_path.display()))?;
+
+            let clip_arc = Arc::new(clip);
+            loaded.insert(audio_file_path, clip_arc.clone());
+            clip_arc
+        };
+        // Channel conversions (`convert_clip_channels`) are not written to the file; re-apply them
+        let clip_arc = match audio_file_data.channels as usize {
+            2 if clip_arc.channels == 1 => Arc::new(clip_arc.to_stereo()),
+            1 if clip_arc.channels == 2 => Arc::new(clip_arc.to_mono()),
+            _ => clip_arc,
+        };
         clips_map.insert(audio_file_data.id, clip_arc);
     }
 
@@ -132,7 +171,8 @@ pub fn load_project(project_path_str: String) -> Result<String, String> {
                         clip_data.offset,
                         clip_data.duration,
                     );
-                    if clip_id.is_some() {
+                    if let Some(clip_id) = clip_id {
+                        graph.restore_audio_clip_settings(track_data.id, clip_id, clip_data);
                         audio_clip_count += 1;
                         eprintln!(
                             "   📎 Restored audio clip {} to track {} at {:.2}s",
@@ -145,8 +185,7 @@ pub fn load_project(project_path_str: String) -> Result<String, String> {
     }
     eprintln!("📎 [API] Restored {audio_clip_count} audio clips");
 
-    eprintln!("✅ [API] Project loaded successfully");
-    Ok(format!("Loaded project: {}", project_data.name))
+    Ok(())
 }
 
 /// Export project to WAV file
@@ -245,7 +284,10 @@ pub fn is_ffmpeg_available() -> bool {
     crate::export::is_ffmpeg_available()
 }
 
-/// Export project with configurable options (WAV or MP3)
+/// Export project with configurable options (WAV, MP3, FLAC or Ogg)
+///
+/// Set `normalize_lufs` (e.g. -14.0) for loudness-normalized delivery: the rendered
+/// mix is gained to the target and limited to -1 dBFS, before any dither.
 ///
 /// # Arguments
 /// * `output_path_str` - Path to output file
@@ -254,7 +296,10 @@ pub fn is_ffmpeg_available() -> bool {
 /// # Returns
 /// JSON string with `ExportResult` on success
 pub fn export_audio(output_path_str: String, options_json: String) -> Result<String, String> {
-    use crate::export::{export_mp3, export_wav, ExportFormat, ExportOptions};
+    use crate::export::{
+        export_flac, export_mp3, export_ogg, export_progress, export_wav, ExportFormat,
+        ExportOptions,
+    };
 
     let output_path = Path::new(&output_path_str);
 
@@ -265,33 +310,16 @@ pub fn export_audio(output_path_str: String, options_json: String) -> Result<Str
     let options: ExportOptions =
         serde_json::from_str(&options_json).map_err(|e| format!("Invalid options JSON: {e}"))?;
 
-    // Get audio graph
-    let graph_mutex = get_audio_graph()?;
-    let graph = graph_mutex.lock();
-
-    // Calculate project duration
-    let duration = graph.calculate_project_duration();
-    if duration <= 1.0 {
-        return Err("No audio content to export".to_string());
-    }
-
-    eprintln!("🎵 [API] Project duration: {duration:.2}s");
-
-    // Render offline
-    let samples = graph.render_offline(duration);
-
-    if samples.is_empty() {
-        return Err("Render produced no audio".to_string());
-    }
+    export_progress().start("Preparing export...");
 
     // Export based on format
-    let result = match &options.format {
-        ExportFormat::Wav { .. } => export_wav(&samples, output_path, &options)?,
-        ExportFormat::Mp3 { .. } => export_mp3(&samples, output_path, &options)?,
+    let encode = match &options.format {
+        ExportFormat::Wav { .. } => export_wav,
+        ExportFormat::Mp3 { .. } => export_mp3,
+        ExportFormat::Flac { .. } => export_flac,
+        ExportFormat::Ogg { .. } => export_ogg,
     };
-
-    // Return result as JSON
-    Ok(result.to_json())
+    render_and_encode(output_path, &options, "Encoding audio file...", encode)
 }
 
 /// Export project to WAV with configurable options
@@ -354,8 +382,8 @@ pub fn export_wav_with_options(
     let graph = graph_mutex.lock();
 
     // Calculate project duration
-    let duration = graph.calculate_project_duration();
-    if duration <= 1.0 {
+    let content_end = graph.calculate_content_end();
+    if content_end <= 0.0 {
         progress.fail("No audio content");
         return Err("No audio content to export".to_string());
     }
@@ -366,22 +394,27 @@ pub fn export_wav_with_options(
         return Err("Export cancelled".to_string());
     }
 
-    progress.update(20, "Rendering audio...");
+    // Without normalization or an auto tail, write the file while it renders
+    if options.can_stream() {
+        return stream_project_to_wav(&graph, content_end, output_path, &options);
+    }
 
-    // Render offline
-    let samples = graph.render_offline(duration);
+    progress.begin_render(20, 70, "Rendering audio...");
 
-    if samples.is_empty() {
-        progress.fail("Render produced no audio");
-        return Err("Render produced no audio".to_string());
-    }
+    // Render offline
+    let samples = render_project_for_export(&graph, content_end, &options);
 
-    // Check for cancellation
+    // A cancelled render stops early, so check before judging its output
     if progress.is_cancelled() {
         progress.fail("Export cancelled");
         return Err("Export cancelled".to_string());
     }
 
+    if samples.is_empty() {
+        progress.fail("Render produced no audio");
+        return Err("Render produced no audio".to_string());
+    }
+
     progress.update(70, "Encoding WAV file...");
 
     // Export
@@ -455,8 +488,8 @@ pub fn export_mp3_with_options(
     let graph = graph_mutex.lock();
 
     // Calculate project duration
-    let duration = graph.calculate_project_duration();
-    if duration <= 1.0 {
+    let content_end = graph.calculate_content_end();
+    if content_end <= 0.0 {
         progress.fail("No audio content");
         return Err("No audio content to export".to_string());
     }
@@ -467,26 +500,194 @@ pub fn export_mp3_with_options(
         return Err("Export cancelled".to_string());
     }
 
-    progress.update(20, "Rendering audio...");
+    progress.begin_render(20, 60, "Rendering audio...");
 
     // Render offline
-    let samples = graph.render_offline(duration);
+    let samples = render_project_for_export(&graph, content_end, &options);
+
+    // A cancelled render stops early, so check before judging its output
+    if progress.is_cancelled() {
+        progress.fail("Export cancelled");
+        return Err("Export cancelled".to_string());
+    }
 
     if samples.is_empty() {
         progress.fail("Render produced no audio");
         return Err("Render produced no audio".to_string());
     }
 
+    progress.update(60, "Encoding MP3 file...");
+
+    // Export
+    let result = match export_mp3(&samples, output_path, &options) {
+        Ok(r) => r,
+        Err(e) => {
+            progress.fail(&e);
+            return Err(e);
+        }
+    };
+
+    progress.complete();
+
+    Ok(result.to_json())
+}
+
+/// Export project to FLAC (lossless) with configurable options
+///
+/// # Arguments
+/// * `output_path_str` - Path to output FLAC file
+/// * `bit_depth` - Bit depth: 16 or 24
+/// * `sample_rate` - Sample rate: 44100 or 48000
+/// * `normalize` - Whether to normalize the output
+/// * `mono` - Whether to mixdown to mono
+///
+/// # Returns
+/// JSON string with `ExportResult` on success
+pub fn export_flac_with_options(
+    output_path_str: String,
+    bit_depth: i32,
+    sample_rate: u32,
+    normalize: bool,
+    mono: bool,
+) -> Result<String, String> {
+    use crate::export::{export_flac, export_progress, ExportOptions, FlacBitDepth};
+
+    let progress = export_progress();
+    progress.start("Preparing FLAC export...");
+
+    let Some(bit_depth_enum) = FlacBitDepth::from_bits(bit_depth) else {
+        progress.fail("Invalid bit depth");
+        return Err(format!("Invalid bit depth: {bit_depth}. Use 16 or 24"));
+    };
+
+    let options = ExportOptions::flac(bit_depth_enum)
+        .with_sample_rate(sample_rate)
+        .with_normalize(normalize)
+        .with_mono(mono);
+
+    eprintln!(
+        "🎵 [API] Exporting FLAC: {output_path_str}, {bit_depth}-bit, {sample_rate}Hz"
+    );
+
+    render_and_encode(Path::new(&output_path_str), &options, "Encoding FLAC file...", export_flac)
+}
+
+/// Export project to Ogg Vorbis with configurable options
+///
+/// Encoded with the bundled libvorbis, so like FLAC it needs no ffmpeg.
+///
+/// # Arguments
+/// * `output_path_str` - Path to output OGG file
+/// * `quality` - Vorbis quality: 0 (smallest) to 10 (best)
+/// * `sample_rate` - Sample rate: 44100 or 48000
+/// * `normalize` - Whether to normalize the output
+/// * `mono` - Whether to mixdown to mono
+///
+/// # Returns
+/// JSON string with `ExportResult` on success
+pub fn export_ogg_with_options(
+    output_path_str: String,
+    quality: i32,
+    sample_rate: u32,
+    normalize: bool,
+    mono: bool,
+) -> Result<String, String> {
+    use crate::export::{export_ogg, export_progress, ExportOptions, MAX_OGG_QUALITY};
+
+    let progress = export_progress();
+    progress.start("Preparing Ogg Vorbis export...");
+
+    let Some(quality) = u8::try_from(quality).ok().filter(|q| *q <= MAX_OGG_QUALITY) else {
+        progress.fail("Invalid quality");
+        return Err(format!("Invalid quality: {quality}. Use 0 to {MAX_OGG_QUALITY}"));
+    };
+
+    let options = ExportOptions::ogg(quality)
+        .with_sample_rate(sample_rate)
+        .with_normalize(normalize)
+        .with_mono(mono);
+
+    eprintln!(
+        "🎵 [API] Exporting Ogg Vorbis: {output_path_str}, q{quality}, {sample_rate}Hz"
+    );
+
+    render_and_encode(Path::new(&output_path_str), &options, "Encoding Ogg Vorbis file...", export_ogg)
+}
+
+/// Render the project (or the options' range) and encode it, reporting through the
+/// export progress state
+fn render_and_encode(
+    output_path: &Path,
+    options: &crate::export::ExportOptions,
+    encode_message: &str,
+    encode: fn(&[f32], &Path, &crate::export::ExportOptions) -> Result<crate::export::ExportResult, String>,
+) -> Result<String, String> {
+    let progress = crate::export::export_progress();
+
+    // Check for cancellation
+    if progress.is_cancelled() {
+        progress.fail("Export cancelled");
+        return Err("Export cancelled".to_string());
+    }
+
+    progress.update(10, "Accessing audio graph...");
+
+    // Get audio graph
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    // Calculate project duration
+    let content_end = graph.calculate_content_end();
+    if content_end <= 0.0 {
+        progress.fail("No audio content");
+        return Err("No audio content to export".to_string());
+    }
+
     // Check for cancellation
     if progress.is_cancelled() {
         progress.fail("Export cancelled");
         return Err("Export cancelled".to_string());
     }
 
-    progress.update(60, "Encoding MP3 file...");
+    let range = match options.export_range(content_end + options.render_tail_seconds()) {
+        Ok(range) => range,
+        Err(e) => {
+            progress.fail(&e);
+            return Err(e);
+        }
+    };
+
+    // A whole-project WAV that needs no whole-mix processing is written while it renders
+    if range.is_none() && options.is_wav() && options.can_stream() {
+        return stream_project_to_wav(&graph, content_end, output_path, options);
+    }
+
+    progress.begin_render(20, 70, "Rendering audio...");
+
+    // Render the selected range, or the whole project
+    let samples = match range {
+        Some((start, end)) => {
+            eprintln!("🎵 [API] Export range: {start:.2}s - {end:.2}s (pre-roll: {})", options.pre_roll);
+            graph.render_offline_range(start, end, options.pre_roll)
+        }
+        None => render_project_for_export(&graph, content_end, options),
+    };
+
+    // A cancelled render stops early, so check before judging its output
+    if progress.is_cancelled() {
+        progress.fail("Export cancelled");
+        return Err("Export cancelled".to_string());
+    }
+
+    if samples.is_empty() {
+        progress.fail("Render produced no audio");
+        return Err("Render produced no audio".to_string());
+    }
+
+    progress.update(70, encode_message);
 
     // Export
-    let result = match export_mp3(&samples, output_path, &options) {
+    let result = match encode(&samples, output_path, options) {
         Ok(r) => r,
         Err(e) => {
             progress.fail(&e);
@@ -499,6 +700,81 @@ pub fn export_mp3_with_options(
     Ok(result.to_json())
 }
 
+/// Render the whole project for export: the clips plus the options' render tail
+/// (rendered up to the cap and trimmed where the mix falls silent in auto mode)
+fn render_project_for_export(
+    graph: &crate::audio_graph::AudioGraph,
+    content_end: f64,
+    options: &crate::export::ExportOptions,
+) -> Vec<f32> {
+    let mut samples = graph.render_offline(content_end + options.render_tail_seconds());
+    if options.auto_tail {
+        crate::export::trim_silent_tail(&mut samples, content_end, crate::audio_file::TARGET_SAMPLE_RATE);
+    }
+    samples
+}
+
+/// Frames rendered per chunk when an export is written while it renders (~170 ms)
+const EXPORT_CHUNK_FRAMES: usize = 8192;
+
+/// Render the whole project straight into a WAV file, a chunk at a time
+///
+/// Memory stays bounded however long the project is. Only for options that
+/// `can_stream`; the partial file is removed if the render is cancelled or empty.
+fn stream_project_to_wav(
+    graph: &crate::audio_graph::AudioGraph,
+    content_end: f64,
+    output_path: &Path,
+    options: &crate::export::ExportOptions,
+) -> Result<String, String> {
+    use crate::export::{export_progress, WavStreamWriter};
+
+    let progress = export_progress();
+    let duration = content_end + options.render_tail_seconds();
+    let total_frames = (duration * f64::from(crate::audio_file::TARGET_SAMPLE_RATE)) as usize;
+    let mut writer = match WavStreamWriter::create(output_path, options, total_frames) {
+        Ok(writer) => writer,
+        Err(e) => {
+            progress.fail(&e);
+            return Err(e);
+        }
+    };
+
+    progress.begin_render(20, 95, "Rendering and encoding audio...");
+
+    let mut rendered_samples = 0;
+    graph.render_offline_streaming(duration, EXPORT_CHUNK_FRAMES, |chunk| {
+        rendered_samples += chunk.len();
+        writer.write(chunk);
+    });
+
+    // A cancelled render stops early, so check before judging its output
+    let failure = if progress.is_cancelled() {
+        Some("Export cancelled")
+    } else if rendered_samples == 0 {
+        Some("Render produced no audio")
+    } else {
+        None
+    };
+    if let Some(message) = failure {
+        drop(writer);
+        let _ = std::fs::remove_file(output_path);
+        progress.fail(message);
+        return Err(message.to_string());
+    }
+
+    match writer.finish() {
+        Ok(result) => {
+            progress.complete();
+            Ok(result.to_json())
+        }
+        Err(e) => {
+            progress.fail(&e);
+            Err(e)
+        }
+    }
+}
+
 /// Write ID3 metadata to an MP3 file
 ///
 /// # Arguments
@@ -548,6 +824,10 @@ pub fn get_tracks_for_stems() -> Result<String, String> {
 
 /// Export stems (individual tracks) to a directory
 ///
+/// Stems are rendered dry unless the options set `stems_with_sends`, in which case
+/// each carries the track's groups and returns and the stems sum to the mix. Group
+/// and return tracks are only exported with `stems_include_buses`.
+///
 /// # Arguments
 /// * `output_dir` - Directory to export stems to
 /// * `base_name` - Base filename for stems (e.g., "My Song")
@@ -611,11 +891,18 @@ pub fn export_stems(
     let graph = graph_mutex.lock();
 
     // Calculate project duration
-    let duration = graph.calculate_project_duration();
-    if duration <= 1.0 {
+    let content_end = graph.calculate_content_end();
+    if content_end <= 0.0 {
         progress.fail("No audio content");
         return Err("No audio content to export".to_string());
     }
+    // Stems share one length so they line up; auto tails fall back to the default
+    let tail_seconds = if options.auto_tail {
+        crate::export::DEFAULT_TAIL_SECONDS
+    } else {
+        options.render_tail_seconds()
+    };
+    let duration = content_end + tail_seconds;
 
     // Get tracks for export
     let all_tracks = graph.get_tracks_for_stem_export();
@@ -638,7 +925,12 @@ pub fn export_stems(
     // Render each track
     let mut tracks_with_samples: Vec<(StemTrackInfo, Vec<f32>)> = Vec::new();
 
-    for (i, (track_id, track_name, _track_type)) in tracks_to_export.iter().enumerate() {
+    for (i, (track_id, track_name, track_type)) in tracks_to_export.iter().enumerate() {
+        let is_bus = track_type == "group" || track_type == "return";
+        if is_bus && !options.stems_include_buses {
+            continue;
+        }
+
         // Check for cancellation
         if progress.is_cancelled() {
             progress.fail("Export cancelled");
@@ -647,8 +939,10 @@ pub fn export_stems(
 
         // Progress: 10% to 70% is rendering tracks
         let track_progress = 10 + (i as u32 * 60 / total_tracks as u32);
-        progress.update(
+        let next_track_progress = 10 + ((i as u32 + 1) * 60 / total_tracks as u32);
+        progress.begin_render(
             track_progress,
+            next_track_progress,
             &format!("Rendering track {} of {}: {}", i + 1, total_tracks, track_name),
         );
 
@@ -656,7 +950,13 @@ pub fn export_stems(
             "🎚️ [API] Rendering track '{track_name}' (ID: {track_id})"
         );
 
-        let samples = graph.render_track_offline(*track_id, duration);
+        let samples = if is_bus {
+            graph.render_bus_offline(*track_id, duration)
+        } else if options.stems_with_sends {
+            graph.render_track_with_sends_offline(*track_id, duration)
+        } else {
+            graph.render_track_offline(*track_id, duration)
+        };
 
         // Skip empty tracks
         if samples.iter().all(|&s| s.abs() < 0.0001) {
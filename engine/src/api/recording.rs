@@ -3,6 +3,8 @@
 //! Functions for audio recording, input device management, and recording state.
 
 use super::helpers::{get_audio_clips, get_audio_graph};
+use crate::audio_graph::InputFrame;
+use parking_lot::Mutex;
 use std::sync::Arc;
 
 // ============================================================================
@@ -41,6 +43,29 @@ pub fn set_audio_input_device(device_index: i32) -> Result<String, String> {
     Ok(format!("Selected input device {device_index}"))
 }
 
+/// Select the audio input device by name
+/// Pass empty string to use the system default input. Input runs as its own stream,
+/// so it can differ from the output device; a running capture switches over.
+pub fn set_audio_input_device_by_name(device_name: &str) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    let name = if device_name.is_empty() { None } else { Some(device_name) };
+    let mut input_manager = graph.input_manager.lock();
+    input_manager.select_device_by_name(name).map_err(|e| e.to_string())?;
+
+    Ok(format!("Input device set to: {}", if device_name.is_empty() { "System Default" } else { device_name }))
+}
+
+/// Get currently selected input device name (empty string = system default)
+pub fn get_selected_audio_input_device() -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    let input_manager = graph.input_manager.lock();
+    Ok(input_manager.get_selected_device_name().unwrap_or_default())
+}
+
 /// Get list of available audio output devices
 pub fn get_audio_output_devices() -> Result<Vec<(String, String, bool)>, String> {
     use crate::audio_graph::AudioGraph;
@@ -72,6 +97,23 @@ pub fn get_selected_audio_output_device() -> Result<String, String> {
     Ok(graph.get_selected_output_device().unwrap_or_default())
 }
 
+/// Get the output device status (0 = running, 1 = disconnected, 2 = no stream)
+pub fn get_audio_device_status() -> Result<i32, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    Ok(graph.get_audio_device_status() as i32)
+}
+
+/// Whether the output device was lost or the stream rebuilt since the last call
+/// Poll this to tell the user about device changes.
+pub fn take_audio_device_changed() -> Result<bool, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    Ok(graph.take_audio_device_changed())
+}
+
 /// Get current sample rate
 pub fn get_sample_rate() -> u32 {
     use crate::audio_graph::AudioGraph;
@@ -83,7 +125,7 @@ pub fn get_sample_rate() -> u32 {
 // ============================================================================
 
 /// Get input channel peak level for metering
-/// Returns peak amplitude (0.0 to 1.0+) for the specified channel (0=left, 1=right)
+/// Returns peak amplitude (0.0 to 1.0+) for the specified device channel (0-based)
 pub fn get_input_channel_level(channel: u32) -> Result<f32, String> {
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
@@ -101,6 +143,16 @@ pub fn get_input_channel_count() -> Result<u32, String> {
     Ok(u32::from(input_manager.get_input_channels()))
 }
 
+/// Get the rate the input device captures at (Hz)
+/// Input at another rate is resampled to the engine rate while it is captured.
+pub fn get_input_sample_rate() -> Result<u32, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    let input_manager = graph.input_manager.lock();
+    Ok(input_manager.get_input_sample_rate())
+}
+
 // ============================================================================
 // AUDIO INPUT CAPTURE
 // ============================================================================
@@ -147,9 +199,9 @@ pub fn start_recording() -> Result<String, String> {
     // Calculate count-in duration so we know the actual recording start position
     let count_in_bars = graph.recorder.get_count_in_bars();
     let tempo = graph.recorder.get_tempo();
-    let time_sig = graph.recorder.get_time_signature();
+    let bar_quarter_notes = graph.recorder.get_base_time_signature().quarter_notes_per_bar();
     let count_in_seconds = if count_in_bars > 0 {
-        f64::from(count_in_bars) * f64::from(time_sig) * 60.0 / tempo
+        f64::from(count_in_bars) * bar_quarter_notes * 60.0 / tempo
     } else {
         0.0
     };
@@ -230,12 +282,29 @@ pub fn start_recording() -> Result<String, String> {
     Ok(msg)
 }
 
-/// Stop recording and return the recorded clip ID
-pub fn stop_recording() -> Result<Option<u64>, String> {
+/// Clip IDs created by the most recent `stop_recording` (one per take per armed track)
+static LAST_RECORDING_TAKES: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+/// Stop recording and return the recorded clip IDs
+///
+/// Each loop-recording pass becomes its own clip ("take") on every armed audio track;
+/// a pass stopped mid-loop is kept as the final take. Returns an empty list if
+/// nothing was recorded.
+pub fn stop_recording() -> Result<Vec<u64>, String> {
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
 
-    let clip_option = graph.recorder.stop_recording()?;
+    let mut takes = graph.recorder.stop_recording_takes()?;
+    LAST_RECORDING_TAKES.lock().clear();
+
+    // Place the takes where they were played, not where the input arrived
+    let compensation_samples = graph.recording_latency_compensation_samples();
+    if compensation_samples > 0 {
+        eprintln!("🎙️ [API] Compensating recording latency: {compensation_samples} samples");
+        for take in &mut takes {
+            take.compensate_latency(compensation_samples);
+        }
+    }
 
     // Stop audio input to prevent buffer overflow
     {
@@ -246,76 +315,80 @@ pub fn stop_recording() -> Result<Option<u64>, String> {
         }
     }
 
-    if let Some(clip) = clip_option {
-        // Find armed audio tracks — only place audio clips on explicitly armed tracks.
-        // If no audio tracks are armed, discard the audio clip (MIDI-only recording).
-        let armed_tracks: Vec<(u64, u32)> = {
-            let tm = graph.track_manager.lock();
-            let armed: Vec<(u64, u32)> = tm.get_all_tracks()
-                .into_iter()
-                .filter_map(|t| {
-                    { let track = t.lock();
-                        if track.track_type == crate::track::TrackType::Audio && track.armed {
-                            Some((track.id, track.input_channel))
-                        } else {
-                            None
-                        }
+    if takes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Find armed audio tracks — only place audio clips on explicitly armed tracks.
+    // If no audio tracks are armed, discard the audio clip (MIDI-only recording).
+    let armed_tracks: Vec<(u64, u32, u32, f32)> = {
+        let tm = graph.track_manager.lock();
+        let armed: Vec<(u64, u32, u32, f32)> = tm.get_all_tracks()
+            .into_iter()
+            .filter_map(|t| {
+                { let track = t.lock();
+                    if track.track_type == crate::track::TrackType::Audio && track.armed {
+                        Some((track.id, track.input_channel, track.input_channel_count, track.get_input_gain()))
+                    } else {
+                        None
                     }
-                })
-                .collect();
-            armed
+                }
+            })
+            .collect();
+        armed
+    };
+
+    // No armed audio tracks — discard audio clip (MIDI-only recording)
+    if armed_tracks.is_empty() {
+        eprintln!("🎙️ [API] No armed audio tracks — discarding audio clip");
+        return Ok(Vec::new());
+    }
+
+    eprintln!("🎙️ [API] Recording will be added to {} track(s): {:?}", armed_tracks.len(), armed_tracks);
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let mut clip_ids = Vec::with_capacity(takes.len() * armed_tracks.len());
+    let clips_mutex = get_audio_clips()?;
+    let mut clips_map = clips_mutex.lock();
+
+    for (take_index, take) in takes.iter().enumerate() {
+        // Place each take where its pass started (after count-in / at loop start)
+        let start_position = take.start_seconds;
+        eprintln!("🎙️ [API] Placing take {} at position {start_position:.3}s", take_index + 1);
+
+        let recorded_samples = &take.clip.samples;
+        let recorded_channels = take.clip.channels.max(1);
+        let duration = take.clip.duration_seconds;
+        let take_suffix = if takes.len() > 1 {
+            format!("_take{}", take_index + 1)
+        } else {
+            String::new()
         };
 
-        // No armed audio tracks — discard audio clip (MIDI-only recording)
-        if armed_tracks.is_empty() {
-            eprintln!("🎙️ [API] No armed audio tracks — discarding audio clip");
-            return Ok(None);
-        }
+        for (track_id, input_channel, input_channel_count, input_gain) in &armed_tracks {
+            // The recording holds every input channel; each track takes its own
+            // (a mono channel duplicated to both sides, or a stereo pair)
+            let mut track_samples =
+                extract_track_input(recorded_samples, recorded_channels, *input_channel, *input_channel_count);
 
-        eprintln!("🎙️ [API] Recording will be added to {} track(s): {:?}", armed_tracks.len(), armed_tracks);
-
-        // Place clip at the position where recording started (after count-in)
-        let start_position = graph.recorder.get_recording_start_seconds();
-        eprintln!("🎙️ [API] Placing recorded clip at position {start_position:.3}s");
-
-        let stereo_samples = &clip.samples;
-        let duration = clip.duration_seconds;
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        let mut first_clip_id = None;
-        let clips_mutex = get_audio_clips()?;
-        let mut clips_map = clips_mutex.lock();
-
-        for (track_id, input_channel) in &armed_tracks {
-            // Extract this track's assigned input channel from the stereo recording
-            // Channel 0 = left (even indices), Channel 1 = right (odd indices)
-            // Create a stereo clip where both channels contain the mono source
-            let track_samples: Vec<f32> = if armed_tracks.len() == 1 {
-                // Single track: use the full stereo recording as-is
-                stereo_samples.clone()
-            } else {
-                // Multi-track: extract assigned channel and duplicate to stereo
-                let channel_offset = *input_channel as usize;
-                let frame_count = stereo_samples.len() / 2;
-                let mut samples = Vec::with_capacity(frame_count * 2);
-                for frame in 0..frame_count {
-                    let sample = stereo_samples.get(frame * 2 + channel_offset.min(1))
-                        .copied().unwrap_or(0.0);
-                    samples.push(sample); // Left
-                    samples.push(sample); // Right (same mono source)
+            // Apply the track's input trim (the recorder captures the raw input)
+            if (*input_gain - 1.0).abs() > f32::EPSILON {
+                for sample in &mut track_samples {
+                    *sample *= input_gain;
                 }
-                samples
-            };
+            }
 
             let track_clip = crate::audio_file::AudioClip {
                 samples: track_samples,
                 channels: 2,
                 sample_rate: crate::audio_file::TARGET_SAMPLE_RATE,
                 duration_seconds: duration,
-                file_path: format!("recorded_t{track_id}_{timestamp}.wav"),
+                file_path: format!("recorded_t{track_id}_{timestamp}{take_suffix}.wav"),
+                stream: None,
             };
 
             let track_clip_arc = Arc::new(track_clip);
@@ -323,22 +396,34 @@ pub fn stop_recording() -> Result<Option<u64>, String> {
                 .ok_or(format!("Failed to add recorded clip to track {track_id}"))?;
 
             clips_map.insert(clip_id, track_clip_arc);
-
-            if first_clip_id.is_none() {
-                first_clip_id = Some(clip_id);
-            }
+            clip_ids.push(clip_id);
 
             eprintln!("✅ [API] Added clip {clip_id} to track {track_id} (input ch {input_channel})");
         }
+    }
 
-        let clip_id = first_clip_id.ok_or("Failed to create any clips")?;
+    eprintln!("📊 [API] Created {} clips ({} takes x {} armed tracks)", clip_ids.len(), takes.len(), armed_tracks.len());
 
-        eprintln!("📊 [API] Created {} clips for {} armed tracks", armed_tracks.len(), armed_tracks.len());
+    LAST_RECORDING_TAKES.lock().clone_from(&clip_ids);
+    Ok(clip_ids)
+}
 
-        Ok(Some(clip_id))
-    } else {
-        Ok(None)
-    }
+/// A track's input from an interleaved multi-channel recording, as interleaved stereo:
+/// `first_channel` on both sides, or with `channel_count` 2, the pair starting there
+/// (a channel the recording doesn't have comes out silent)
+fn extract_track_input(samples: &[f32], channels: usize, first_channel: u32, channel_count: u32) -> Vec<f32> {
+    samples
+        .chunks_exact(channels)
+        .flat_map(|frame| {
+            let (left, right) = InputFrame::from_samples(frame).track_input(first_channel, channel_count);
+            [left, right]
+        })
+        .collect()
+}
+
+/// Get the clip IDs created by the last recording, in take order
+pub fn get_last_recording_takes() -> Vec<u64> {
+    LAST_RECORDING_TAKES.lock().clone()
 }
 
 /// Get current recording state (0=Idle, 1=CountingIn, 2=Recording, 3=WaitingForPunchIn)
@@ -446,7 +531,7 @@ pub fn is_punch_out_enabled() -> Result<bool, String> {
 pub fn set_punch_region(in_seconds: f64, out_seconds: f64) -> Result<String, String> {
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
-    graph.recorder.set_punch_region(in_seconds, out_seconds);
+    graph.recorder.set_punch_region(in_seconds, out_seconds)?;
     Ok(format!("Punch region set: {in_seconds:.3}s - {out_seconds:.3}s"))
 }
 
@@ -467,3 +552,79 @@ pub fn is_punch_complete() -> Result<bool, String> {
     let graph = graph_mutex.lock();
     Ok(graph.recorder.is_punch_complete())
 }
+
+// ============================================================================
+// INPUT MONITORING AND LATENCY COMPENSATION
+// ============================================================================
+
+/// Set how input is monitored while recording
+/// 0=Off, 1=Software (the engine plays the input), 2=Direct (the interface monitors;
+/// the engine stays silent)
+pub fn set_monitor_mode(mode: i32) -> Result<String, String> {
+    use crate::audio_graph::MonitorMode;
+
+    let monitor_mode = u8::try_from(mode)
+        .ok()
+        .and_then(MonitorMode::from_u8)
+        .ok_or_else(|| format!("Invalid monitor mode {mode} (expected 0-2)"))?;
+
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    graph.set_monitor_mode(monitor_mode);
+    Ok(format!("Monitor mode set to {monitor_mode:?}"))
+}
+
+/// Get the monitor mode (0=Off, 1=Software, 2=Direct)
+pub fn get_monitor_mode() -> Result<i32, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    Ok(graph.get_monitor_mode() as i32)
+}
+
+/// Get the latency (in samples) recorded takes are moved earlier by when recording
+/// stops; the last latency test's round trip (or the override), 0 before either
+pub fn get_recording_latency_compensation() -> Result<u64, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    Ok(graph.recording_latency_compensation_samples())
+}
+
+/// Override the recording latency compensation in samples (0 = none); the next
+/// successful latency test replaces it
+pub fn set_recording_latency_compensation(samples: u64) -> Result<String, String> {
+    if samples > u64::from(crate::audio_file::TARGET_SAMPLE_RATE) {
+        return Err(format!("Latency compensation of {samples} samples is over a second"));
+    }
+
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    graph.set_recording_latency_compensation_samples(samples);
+    Ok(format!("Recording latency compensation set to {samples} samples"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recorder::Recorder;
+
+    #[test]
+    fn test_stereo_armed_track_records_a_channel_pair() {
+        // Four input channels; the stereo pair is channels 2 and 3
+        let recorder = Recorder::new();
+        recorder.set_count_in_bars(0);
+        recorder.start_recording().unwrap();
+        let refs = recorder.get_callback_refs();
+        for _ in 0..100 {
+            refs.process_frame(&[0.1, 0.2, 0.3, -0.4], true, 0.0);
+        }
+        let clip = recorder.stop_recording().unwrap().unwrap();
+
+        let stereo = extract_track_input(&clip.samples, clip.channels, 2, 2);
+        assert_eq!(stereo.len(), 100 * 2);
+        assert!(stereo.chunks_exact(2).all(|frame| frame == [0.3, -0.4]));
+
+        // A mono track on the same channel gets it on both sides
+        let mono = extract_track_input(&clip.samples, clip.channels, 2, 1);
+        assert!(mono.chunks_exact(2).all(|frame| frame == [0.3, 0.3]));
+    }
+}
@@ -55,10 +55,16 @@ pub fn set_clip_start_time(track_id: u64, clip_id: u64, start_time: f64) -> Resu
     if let Some(track_arc) = track_manager.get_track(track_id) {
         let mut track = track_arc.lock();
 
+        let record_move = |from: f64, to: f64| {
+            history::record(crate::history::Command::MoveClip { track_id, clip_id, from, to });
+        };
+
         // Try audio clips first
         for clip in &mut track.audio_clips {
             if clip.id == clip_id {
+                let from = clip.start_time;
                 clip.start_time = start_time.max(0.0); // Clamp to >= 0
+                record_move(from, clip.start_time);
                 return Ok(format!("Clip {clip_id} moved to {start_time:.3}s"));
             }
         }
@@ -66,7 +72,9 @@ pub fn set_clip_start_time(track_id: u64, clip_id: u64, start_time: f64) -> Resu
         // Try MIDI clips
         for clip in &mut track.midi_clips {
             if clip.id == clip_id {
+                let from = clip.start_time;
                 clip.start_time = start_time.max(0.0); // Clamp to >= 0
+                record_move(from, clip.start_time);
                 return Ok(format!("MIDI clip {clip_id} moved to {start_time:.3}s"));
             }
         }
@@ -143,11 +151,12 @@ pub fn delete_track(track_id: TrackId) -> Result<String, String> {
     graph.remove_midi_clips_for_track(track_id);
 
     // Get the track's fx_chain before deleting so we can clean up effects
+    // (a frozen track's effects are held in its frozen state)
     let fx_chain: Vec<u64> = {
         let track_manager = graph.track_manager.lock();
         if let Some(track_arc) = track_manager.get_track(track_id) {
             let track = track_arc.lock();
-            track.fx_chain.clone()
+            track.saved_contents().2.to_vec()
         } else {
             Vec::new()
         }
@@ -248,6 +257,7 @@ pub fn clear_all_tracks() -> Result<String, String> {
     let clips_mutex = clips()?;
     let mut clips_map = clips_mutex.lock();
     clips_map.clear();
+    history::clear_history();
 
     // Reset master track to defaults (volume = 0dB, pan = 0, unmuted)
     {
@@ -400,8 +410,9 @@ pub fn duplicate_audio_clip(
 
     // Find the source clip and capture all its properties
     let (clip_arc, offset, duration, gain_db, warp_enabled, stretch_factor,
-         warp_mode, stretched_cache, cached_stretch_factor,
-         transpose_semitones, transpose_cents) = {
+         warp_mode, stretched_cache, cached_stretch_factor, cached_pitch_ratio,
+         transpose_semitones, transpose_cents, polarity_inverted, mono_sum,
+         fade_in_seconds, fade_out_seconds, reversed, gain_envelope) = {
         let track_manager = graph.track_manager.lock();
         let track_arc = track_manager
             .get_track(track_id)
@@ -427,8 +438,15 @@ pub fn duplicate_audio_clip(
             source_clip.warp_mode,
             source_clip.stretched_cache.clone(),
             source_clip.cached_stretch_factor,
+            source_clip.cached_pitch_ratio,
             source_clip.transpose_semitones,
             source_clip.transpose_cents,
+            source_clip.polarity_inverted,
+            source_clip.mono_sum,
+            source_clip.fade_in_seconds,
+            source_clip.fade_out_seconds,
+            source_clip.reversed,
+            source_clip.gain_envelope.clone(),
         )
     };
 
@@ -458,12 +476,21 @@ pub fn duplicate_audio_clip(
                 new_clip.warp_mode = warp_mode;
                 new_clip.stretched_cache = stretched_cache;
                 new_clip.cached_stretch_factor = cached_stretch_factor;
+                new_clip.cached_pitch_ratio = cached_pitch_ratio;
                 new_clip.transpose_semitones = transpose_semitones;
                 new_clip.transpose_cents = transpose_cents;
+                new_clip.polarity_inverted = polarity_inverted;
+                new_clip.mono_sum = mono_sum;
+                new_clip.fade_in_seconds = fade_in_seconds;
+                new_clip.fade_out_seconds = fade_out_seconds;
+                new_clip.reversed = reversed;
+                new_clip.gain_envelope = gain_envelope;
             }
         }
     }
 
+    history::record_clip_added(&graph, track_id, new_clip_id);
+
     eprintln!(
         "📋 [API] Duplicated clip {source_clip_id} → new clip {new_clip_id} at {new_start_time:.3}s"
     );
@@ -502,6 +529,86 @@ pub fn set_audio_clip_gain(track_id: TrackId, clip_id: u64, gain_db: f32) -> Res
     }
 }
 
+/// Set an audio clip's gain envelope (clip-level volume breakpoints)
+///
+/// # Arguments
+/// * `track_id` - Track containing the clip
+/// * `clip_id` - ID of the clip to modify
+/// * `csv` - Envelope as CSV: "`time_seconds,db;time_seconds,db`;..." with time measured
+///           from the clip's start on the timeline; empty string clears the envelope
+///
+/// The envelope multiplies the clip's static gain; -96 dB and below is silence
+pub fn set_clip_gain_envelope(track_id: TrackId, clip_id: u64, csv: &str) -> Result<String, String> {
+    let graph_mutex = graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    if let Some(track_arc) = track_manager.get_track(track_id) {
+        let mut track = track_arc.lock();
+
+        if let Some(clip) = track.audio_clips.iter_mut().find(|c| c.id == clip_id) {
+            clip.set_gain_envelope_csv(csv);
+            let point_count = clip.gain_envelope.len();
+            return Ok(format!("Clip {clip_id} gain envelope set ({point_count} points)"));
+        }
+
+        Err(format!("Clip {clip_id} not found on track {track_id}"))
+    } else {
+        Err(format!("Track {track_id} not found"))
+    }
+}
+
+/// Set an audio clip's gain so its peak reaches `target_db` (non-destructive)
+///
+/// The peak is measured over the clip's active (trimmed) region. A silent clip
+/// keeps its current gain.
+///
+/// # Arguments
+/// * `track_id` - Track containing the clip
+/// * `clip_id` - ID of the clip to normalize
+/// * `target_db` - Target peak level in dBFS (e.g. -1.0)
+///
+/// # Returns
+/// The clip's resulting gain in dB (clamped to -70.0 to +24.0)
+pub fn normalize_audio_clip(track_id: TrackId, clip_id: u64, target_db: f32) -> Result<f32, String> {
+    if !target_db.is_finite() {
+        return Err(format!("Invalid target level: {target_db}"));
+    }
+
+    let graph_mutex = graph()?;
+    let track_arc = graph_mutex
+        .lock()
+        .track_manager
+        .lock()
+        .get_track(track_id)
+        .ok_or(format!("Track {track_id} not found"))?;
+
+    // Scan the audio without holding the track lock: streamed clips read from disk
+    let (audio, region) = track_arc
+        .lock()
+        .audio_clips
+        .iter()
+        .find(|c| c.id == clip_id)
+        .map(|c| (c.clip.clone(), c.active_region_frames()))
+        .ok_or(format!("Clip {clip_id} not found on track {track_id}"))?;
+    let peak = audio.peak(region.start, region.len());
+
+    let mut track = track_arc.lock();
+    let clip = track
+        .audio_clips
+        .iter_mut()
+        .find(|c| c.id == clip_id)
+        .ok_or(format!("Clip {clip_id} not found on track {track_id}"))?;
+    if peak <= 0.0 {
+        eprintln!("🔊 [API] Clip {clip_id} is silent, gain left at {:.2} dB", clip.gain_db);
+        return Ok(clip.gain_db);
+    }
+
+    clip.gain_db = (target_db - 20.0 * peak.log10()).clamp(-70.0, 24.0);
+    eprintln!("🔊 [API] Normalized clip {clip_id} to {target_db:.1} dBFS (gain {:.2} dB)", clip.gain_db);
+    Ok(clip.gain_db)
+}
+
 /// Set the warp (time-stretch) settings of an audio clip
 ///
 /// # Arguments
@@ -580,6 +687,9 @@ pub fn set_audio_clip_transpose(
                 clip.transpose_semitones = semitones.clamp(-48, 48);
                 clip.transpose_cents = cents.clamp(-50, 50);
 
+                // Rebuild the pitch-shifted audio cache (length is unchanged)
+                clip.rebuild_stretched_cache();
+
                 return Ok(format!(
                     "Clip {} transpose: {} st, {} ct",
                     clip_id, clip.transpose_semitones, clip.transpose_cents
@@ -593,6 +703,175 @@ pub fn set_audio_clip_transpose(
     }
 }
 
+/// Invert the polarity of an audio clip (non-destructive)
+///
+/// # Arguments
+/// * `track_id` - Track containing the clip
+/// * `clip_id` - ID of the clip to modify
+/// * `inverted` - true to negate the clip's samples during playback and export
+///
+/// # Returns
+/// Success message
+pub fn set_clip_polarity_invert(track_id: TrackId, clip_id: u64, inverted: bool) -> Result<String, String> {
+    let graph_mutex = graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    if let Some(track_arc) = track_manager.get_track(track_id) {
+        let mut track = track_arc.lock();
+
+        for clip in &mut track.audio_clips {
+            if clip.id == clip_id {
+                clip.polarity_inverted = inverted;
+                return Ok(format!("Clip {clip_id} polarity inverted: {inverted}"));
+            }
+        }
+
+        Err(format!("Clip {clip_id} not found on track {track_id}"))
+    } else {
+        Err(format!("Track {track_id} not found"))
+    }
+}
+
+/// Play an audio clip backwards (non-destructive)
+///
+/// The clip's trimmed region (offset and duration) is kept; only the playback
+/// direction within it flips.
+///
+/// # Arguments
+/// * `track_id` - Track containing the clip
+/// * `clip_id` - ID of the clip to modify
+/// * `reversed` - true to play the clip backwards during playback and export
+///
+/// # Returns
+/// Success message
+pub fn set_audio_clip_reversed(track_id: TrackId, clip_id: u64, reversed: bool) -> Result<String, String> {
+    let graph_mutex = graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    if let Some(track_arc) = track_manager.get_track(track_id) {
+        let mut track = track_arc.lock();
+
+        for clip in &mut track.audio_clips {
+            if clip.id == clip_id {
+                clip.reversed = reversed;
+                return Ok(format!("Clip {clip_id} reversed: {reversed}"));
+            }
+        }
+
+        Err(format!("Clip {clip_id} not found on track {track_id}"))
+    } else {
+        Err(format!("Track {track_id} not found"))
+    }
+}
+
+/// Convert an audio clip's audio to mono or stereo
+///
+/// Mono is duplicated to both channels; stereo is averaged to mono. Unlike
+/// `set_clip_mono_sum` this replaces the clip's audio; the project saves the
+/// converted channel count and re-applies it on load.
+///
+/// # Arguments
+/// * `clip_id` - ID of the clip to convert
+/// * `channels` - 1 (mono) or 2 (stereo)
+pub fn convert_clip_channels(clip_id: u64, channels: usize) -> Result<String, String> {
+    if !matches!(channels, 1 | 2) {
+        return Err(format!("Invalid channel count: {channels} (expected 1 or 2)"));
+    }
+
+    let source = clips()?
+        .lock()
+        .get(&clip_id)
+        .cloned()
+        .ok_or_else(|| format!("Clip {clip_id} not found"))?;
+    if source.channels == channels {
+        return Ok(format!("Clip {clip_id} already has {channels} channel(s)"));
+    }
+    if source.is_streaming() {
+        return Err(format!("Clip {clip_id} is streamed from disk and can't be converted"));
+    }
+
+    let converted = Arc::new(if channels == 2 { source.to_stereo() } else { source.to_mono() });
+    {
+        let graph_mutex = graph()?;
+        let graph = graph_mutex.lock();
+        graph.replace_audio_clip_data(clip_id, &converted);
+    }
+    clips()?.lock().insert(clip_id, converted);
+
+    Ok(format!("Clip {clip_id} converted from {} to {channels} channel(s)", source.channels))
+}
+
+/// Sum an audio clip's stereo channels to mono (non-destructive)
+///
+/// # Arguments
+/// * `track_id` - Track containing the clip
+/// * `clip_id` - ID of the clip to modify
+/// * `enabled` - true to play the average of L and R on both channels
+///
+/// # Returns
+/// Success message
+pub fn set_clip_mono_sum(track_id: TrackId, clip_id: u64, enabled: bool) -> Result<String, String> {
+    let graph_mutex = graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    if let Some(track_arc) = track_manager.get_track(track_id) {
+        let mut track = track_arc.lock();
+
+        for clip in &mut track.audio_clips {
+            if clip.id == clip_id {
+                clip.mono_sum = enabled;
+                return Ok(format!("Clip {clip_id} mono sum: {enabled}"));
+            }
+        }
+
+        Err(format!("Clip {clip_id} not found on track {track_id}"))
+    } else {
+        Err(format!("Track {track_id} not found"))
+    }
+}
+
+/// Set fade-in/fade-out lengths on an audio clip (non-destructive)
+///
+/// Overlapping clips on the same track crossfade when the earlier clip's fade-out
+/// and the later clip's fade-in cover the overlap.
+///
+/// # Arguments
+/// * `track_id` - Track containing the clip
+/// * `clip_id` - ID of the clip to modify
+/// * `fade_in` - Fade-in length in seconds (0.0 = none)
+/// * `fade_out` - Fade-out length in seconds (0.0 = none)
+///
+/// # Returns
+/// Success message
+pub fn set_clip_fades(track_id: TrackId, clip_id: u64, fade_in: f64, fade_out: f64) -> Result<String, String> {
+    if !(fade_in >= 0.0 && fade_out >= 0.0) {
+        return Err(format!("Invalid fade lengths: in {fade_in}, out {fade_out}"));
+    }
+
+    let graph_mutex = graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    if let Some(track_arc) = track_manager.get_track(track_id) {
+        let mut track = track_arc.lock();
+
+        for clip in &mut track.audio_clips {
+            if clip.id == clip_id {
+                clip.fade_in_seconds = fade_in;
+                clip.fade_out_seconds = fade_out;
+                return Ok(format!("Clip {clip_id} fades: in {fade_in:.3}s, out {fade_out:.3}s"));
+            }
+        }
+
+        Err(format!("Clip {clip_id} not found on track {track_id}"))
+    } else {
+        Err(format!("Track {track_id} not found"))
+    }
+}
+
 /// Remove an audio clip from a track
 ///
 /// # Arguments
@@ -613,13 +892,12 @@ pub fn remove_audio_clip(track_id: TrackId, clip_id: u64) -> Result<bool, String
     let mut track = track_arc.lock();
 
     // Find and remove the clip
-    let initial_len = track.audio_clips.len();
-    track.audio_clips.retain(|c| c.id != clip_id);
-    let removed = track.audio_clips.len() < initial_len;
-
-    if removed {
-        eprintln!("🗑️  [API] Removed audio clip {clip_id} from track {track_id}");
-    }
+    let Some(index) = track.audio_clips.iter().position(|c| c.id == clip_id) else {
+        return Ok(false);
+    };
+    let clip = track.audio_clips.remove(index);
+    history::record(crate::history::Command::RemoveClip { track_id, clip });
 
-    Ok(removed)
+    eprintln!("🗑️  [API] Removed audio clip {clip_id} from track {track_id}");
+    Ok(true)
 }
@@ -4,21 +4,31 @@
 
 use std::sync::Arc;
 use super::helpers::get_audio_graph;
+use crate::arpeggiator::ArpMode;
 use crate::audio_file::load_audio_file;
+use crate::sampler::SampleZone;
 use crate::effects::EffectType;
+use crate::midi::VelocityCurve;
 
 // ============================================================================
 // PER-TRACK SYNTHESIZER API
 // ============================================================================
 
 /// Set instrument for a track
+/// "`step_sequencer`" adds a step sequencer that plays the track's synth or sampler
+/// (creating a synth if there is none); any other type creates a synth.
 /// Returns instrument ID or -1 on error
-pub fn set_track_instrument(track_id: u64, _instrument_type: String) -> Result<i64, String> {
+pub fn set_track_instrument(track_id: u64, instrument_type: String) -> Result<i64, String> {
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
     let mut synth_manager = graph.track_synth_manager.lock();
 
-    let instrument_id = synth_manager.create_synth(track_id);
+    let instrument_id = if instrument_type == "step_sequencer" {
+        synth_manager.create_step_sequencer(track_id)
+    } else {
+        synth_manager.remove_step_sequencer(track_id);
+        synth_manager.create_synth(track_id)
+    };
     println!(
         "✅ Created instrument {instrument_id} for track {track_id}"
     );
@@ -50,6 +60,100 @@ pub fn get_synth_parameters(_track_id: u64) -> Result<String, String> {
     Ok(String::new())
 }
 
+/// Turn a step of a track's step sequencer on or off
+/// `lane` 0-15 plays MIDI note 36 + lane, `step` 0-15 is the sixteenth note within the bar
+pub fn set_sequencer_step(track_id: u64, lane: u32, step: u32, on: bool, velocity: u8) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let mut synth_manager = graph.track_synth_manager.lock();
+
+    synth_manager.set_sequencer_step(track_id, lane as usize, step as usize, on, velocity)?;
+    Ok(format!("Track {track_id} step {lane}:{step} {}", if on { "on" } else { "off" }))
+}
+
+/// Set the velocity curve of a track's instrument (synth or sampler)
+/// `curve`: "linear", "exponential", "logarithmic" or "fixed"
+pub fn set_track_velocity_curve(track_id: u64, curve: String) -> Result<String, String> {
+    let velocity_curve = VelocityCurve::from_name(&curve)
+        .ok_or_else(|| format!("Unknown velocity curve: {curve}"))?;
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let mut synth_manager = graph.track_synth_manager.lock();
+
+    if synth_manager.set_velocity_curve(track_id, velocity_curve) {
+        Ok(format!("Track {track_id} velocity curve: {}", velocity_curve.name()))
+    } else {
+        Err(format!("Track {track_id} has no instrument"))
+    }
+}
+
+/// Turn a track's arpeggiator on or off (the track needs a synth or sampler)
+pub fn set_track_arp_enabled(track_id: u64, enabled: bool) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let mut synth_manager = graph.track_synth_manager.lock();
+
+    if !synth_manager.has_synth(track_id) {
+        return Err(format!("Track {track_id} has no instrument"));
+    }
+    synth_manager.set_arp_enabled(track_id, enabled);
+    Ok(format!("Track {track_id} arpeggiator {}", if enabled { "on" } else { "off" }))
+}
+
+/// Set a track's arpeggiator pattern
+/// `rate_beats`: step length in beats (0.25 = 1/16 note), `mode`: "up", "down", "updown" or "random",
+/// `octaves`: 1-4, `gate`: fraction of each step a note sounds for (0.05-1.0)
+pub fn set_track_arp_params(
+    track_id: u64,
+    rate_beats: f64,
+    mode: String,
+    octaves: u8,
+    gate: f32,
+) -> Result<String, String> {
+    let arp_mode = ArpMode::from_name(&mode).ok_or_else(|| format!("Unknown arpeggiator mode: {mode}"))?;
+    if !rate_beats.is_finite() || rate_beats <= 0.0 {
+        return Err(format!("Invalid arpeggiator rate: {rate_beats}"));
+    }
+    if !gate.is_finite() {
+        return Err(format!("Invalid arpeggiator gate: {gate}"));
+    }
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let mut synth_manager = graph.track_synth_manager.lock();
+
+    if !synth_manager.has_synth(track_id) {
+        return Err(format!("Track {track_id} has no instrument"));
+    }
+    synth_manager.set_arp_params(track_id, rate_beats, arp_mode, octaves, gate);
+    Ok(format!("Track {track_id} arpeggiator: {} every {rate_beats} beats", arp_mode.name()))
+}
+
+/// Set a track synth's pitch wheel range in semitones each way (clamped to 0..24)
+pub fn set_synth_pitch_bend_range(track_id: u64, semitones: f32) -> Result<String, String> {
+    if !semitones.is_finite() {
+        return Err(format!("Invalid pitch bend range: {semitones}"));
+    }
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let mut synth_manager = graph.track_synth_manager.lock();
+
+    let semitones = semitones.clamp(0.0, crate::synth::MAX_PITCH_BEND_RANGE);
+    if synth_manager.set_pitch_bend_range(track_id, semitones) {
+        Ok(format!("Track {track_id} pitch bend range: ±{semitones} semitones"))
+    } else {
+        Err(format!("Track {track_id} has no synth"))
+    }
+}
+
+/// Get a track synth's envelope as (attack, decay, sustain, release)
+/// Times are in seconds, sustain is a 0.0-1.0 level; None if the track has no synth
+pub fn get_synth_envelope(track_id: u64) -> Result<Option<(f32, f32, f32, f32)>, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let synth_manager = graph.track_synth_manager.lock();
+    Ok(synth_manager.get_synth_envelope(track_id))
+}
+
 /// Send MIDI note on to track synthesizer and any VST3 instruments
 /// Also records the event if MIDI recording is active
 pub fn send_track_midi_note_on(track_id: u64, note: u8, velocity: u8) -> Result<String, String> {
@@ -62,31 +166,30 @@ pub fn send_track_midi_note_on(track_id: u64, note: u8, velocity: u8) -> Result<
     // Record to MIDI recorder if recording is active
     { let mut recorder = graph.midi_recorder.lock();
         if recorder.is_recording() {
-            use crate::midi::{MidiEvent, MidiEventType};
-            let event = MidiEvent {
-                event_type: MidiEventType::NoteOn { note, velocity },
-                timestamp_samples,
-            };
+            use crate::midi::MidiEvent;
+            let event = MidiEvent::note_on(note, velocity, timestamp_samples);
             recorder.record_event(event);
         }
     }
 
-    // Send to track synthesizer for live playback (built-in synth)
-    let mut synth_manager = graph.track_synth_manager.lock();
-    synth_manager.note_on(track_id, note, velocity);
-
-    // Also send to VST3 instruments in the track's FX chain
-    // Get the track's FX chain and send MIDI to any VST3 plugins
-    let fx_chain: Vec<u64> = {
+    // The track's FX chain, and whether a VST3 instrument in it replaces the built-in synth
+    let (fx_chain, plays_synth) = {
         let track_manager = graph.track_manager.lock();
         if let Some(track_arc) = track_manager.get_track(track_id) {
             let track = track_arc.lock();
-            track.fx_chain.clone()
+            let has_instrument = graph.effect_manager.lock().chain_has_instrument(&track.fx_chain);
+            (track.fx_chain.clone(), track.plays_builtin_synth(has_instrument))
         } else {
-            Vec::new()
+            (Vec::new(), true)
         }
     };
 
+    // Send to track synthesizer for live playback (built-in synth)
+    if plays_synth {
+        let mut synth_manager = graph.track_synth_manager.lock();
+        synth_manager.note_on(track_id, note, velocity);
+    }
+
     // Send MIDI to VST3 plugins in the FX chain
     if !fx_chain.is_empty() {
         let effect_manager = graph.effect_manager.lock();
@@ -120,30 +223,30 @@ pub fn send_track_midi_note_off(track_id: u64, note: u8, velocity: u8) -> Result
     // Record to MIDI recorder if recording is active
     { let mut recorder = graph.midi_recorder.lock();
         if recorder.is_recording() {
-            use crate::midi::{MidiEvent, MidiEventType};
-            let event = MidiEvent {
-                event_type: MidiEventType::NoteOff { note, velocity },
-                timestamp_samples,
-            };
+            use crate::midi::MidiEvent;
+            let event = MidiEvent::note_off(note, velocity, timestamp_samples);
             recorder.record_event(event);
         }
     }
 
-    // Send to track synthesizer for live playback (built-in synth)
-    let mut synth_manager = graph.track_synth_manager.lock();
-    synth_manager.note_off(track_id, note);
-
-    // Also send to VST3 instruments in the track's FX chain
-    let fx_chain: Vec<u64> = {
+    // The track's FX chain, and whether a VST3 instrument in it replaces the built-in synth
+    let (fx_chain, plays_synth) = {
         let track_manager = graph.track_manager.lock();
         if let Some(track_arc) = track_manager.get_track(track_id) {
             let track = track_arc.lock();
-            track.fx_chain.clone()
+            let has_instrument = graph.effect_manager.lock().chain_has_instrument(&track.fx_chain);
+            (track.fx_chain.clone(), track.plays_builtin_synth(has_instrument))
         } else {
-            Vec::new()
+            (Vec::new(), true)
         }
     };
 
+    // Send to track synthesizer for live playback (built-in synth)
+    if plays_synth {
+        let mut synth_manager = graph.track_synth_manager.lock();
+        synth_manager.note_off(track_id, note);
+    }
+
     // Send MIDI to VST3 plugins in the FX chain
     if !fx_chain.is_empty() {
         let effect_manager = graph.effect_manager.lock();
@@ -205,6 +308,53 @@ pub fn load_sample_for_track(track_id: u64, path: String, root_note: u8) -> Resu
     }
 }
 
+/// Add a velocity-layered sample zone to a sampler track
+///
+/// The zone plays for notes in `low_note..=high_note` with velocities in
+/// `low_velocity..=high_velocity`, pitched relative to `root_note`.
+pub fn add_sample_zone_for_track(
+    track_id: u64,
+    path: String,
+    root_note: u8,
+    low_note: u8,
+    high_note: u8,
+    low_velocity: u8,
+    high_velocity: u8,
+) -> Result<String, String> {
+    if [root_note, low_note, high_note, low_velocity, high_velocity].iter().any(|&v| v > 127) {
+        return Err("Zone notes and velocities must be in 0-127".to_string());
+    }
+    if low_note > high_note || low_velocity > high_velocity {
+        return Err(format!(
+            "Invalid zone range: notes {low_note}-{high_note}, velocities {low_velocity}-{high_velocity}"
+        ));
+    }
+
+    let audio_clip = load_audio_file(&path)
+        .map_err(|e| format!("Failed to load sample '{path}': {e}"))?;
+
+    let zone = SampleZone {
+        root_note,
+        low_note,
+        high_note,
+        low_velocity,
+        high_velocity,
+        clip: Arc::new(audio_clip),
+    };
+
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let mut synth_manager = graph.track_synth_manager.lock();
+
+    if synth_manager.add_sample_zone(track_id, zone) {
+        Ok(format!(
+            "Added zone '{path}' to track {track_id}: notes {low_note}-{high_note}, velocities {low_velocity}-{high_velocity}, root {root_note}"
+        ))
+    } else {
+        Err(format!("Track {track_id} is not a sampler track"))
+    }
+}
+
 /// Set sampler parameter for a track (`root_note`, `attack_ms`, `release_ms`)
 pub fn set_sampler_parameter(
     track_id: u64,
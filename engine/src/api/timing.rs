@@ -3,35 +3,101 @@
 //! Functions for tempo control and metronome settings.
 
 use super::helpers::get_audio_graph;
+use crate::audio_file::{load_audio_file, TARGET_SAMPLE_RATE};
+use crate::audio_graph::AudioGraph;
+use crate::recorder::{MetronomeSamples, Recorder};
+use crate::time_signature::TimeSignature;
+use std::sync::Arc;
 
 // ============================================================================
 // TEMPO CONTROL
 // ============================================================================
 
-/// Set tempo in BPM
+/// Set tempo in BPM (the tempo at the timeline start; later tempo changes are kept)
 /// Adjusts playhead position so visual position stays the same (no jump when tempo changes)
 pub fn set_tempo(bpm: f64) -> Result<String, String> {
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
 
-    // Get current tempo and playhead before changing
-    let old_tempo = graph.recorder.get_tempo();
-    let current_samples = graph.get_playhead_samples();
+    change_tempo_map(&graph, |recorder| recorder.set_tempo(bpm));
 
-    // Calculate ratio adjustment to keep visual position stable
-    // visual_pos = samples * tempo_ratio / sample_rate
-    // To keep visual_pos same: new_samples = samples * old_ratio / new_ratio
-    let old_ratio = old_tempo / 120.0;
-    let new_ratio = bpm / 120.0;
-    let adjusted_samples = (current_samples as f64 * old_ratio / new_ratio) as u64;
+    Ok(format!("Tempo set to {bpm:.1} BPM"))
+}
 
-    // Update tempo
-    graph.recorder.set_tempo(bpm);
+/// Apply a tempo map edit, keeping the playhead at the same timeline position
+/// Synths and tempo-synced effects follow the project (starting) tempo.
+fn change_tempo_map(graph: &AudioGraph, edit: impl FnOnce(&Recorder)) {
+    // The timeline position (beats) under the playhead stays put; its real-time position moves
+    let timeline_seconds = graph.recorder.get_tempo_map().timeline_seconds_at_sample(graph.get_playhead_samples());
+
+    edit(&graph.recorder);
+    let bpm = graph.recorder.get_tempo();
+    graph.set_instrument_tempo(bpm);
+    graph.effect_manager.lock().set_tempo(bpm);
 
     // Adjust playhead to maintain visual position
+    let adjusted_samples = graph.recorder.get_tempo_map().sample_at_timeline_seconds(timeline_seconds);
     graph.set_playhead_samples(adjusted_samples);
+}
 
-    Ok(format!("Tempo set to {bpm:.1} BPM"))
+/// Change the tempo from `beat` on (quarter notes from the timeline start)
+/// A change at beat 0 sets the project tempo; one at an existing change's beat replaces it.
+pub fn add_tempo_change(beat: f64, bpm: f64) -> Result<String, String> {
+    if !beat.is_finite() || !bpm.is_finite() {
+        return Err("Tempo change beat and BPM must be finite".to_string());
+    }
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    change_tempo_map(&graph, |recorder| recorder.add_tempo_change(beat, bpm));
+
+    Ok(format!("Tempo change at beat {beat:.2}: {bpm:.1} BPM"))
+}
+
+/// Remove the tempo change at `beat` (the project tempo at beat 0 can't be removed)
+pub fn remove_tempo_change(beat: f64) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    let mut removed = false;
+    change_tempo_map(&graph, |recorder| removed = recorder.remove_tempo_change(beat));
+    if !removed {
+        return Err(format!("No tempo change at beat {beat:.2}"));
+    }
+
+    Ok(format!("Removed tempo change at beat {beat:.2}"))
+}
+
+/// Get the tempo map as JSON, starting with the project tempo: `[{"beat", "bpm"}, ...]`
+pub fn get_tempo_map() -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    let changes: Vec<serde_json::Value> = graph
+        .recorder
+        .get_tempo_map()
+        .changes()
+        .iter()
+        .map(|change| serde_json::json!({ "beat": change.beat, "bpm": change.bpm }))
+        .collect();
+    Ok(serde_json::Value::Array(changes).to_string())
+}
+
+/// Convert a beat position to real seconds from the timeline start, through the tempo map
+pub fn beat_to_seconds(beat: f64) -> Result<f64, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    Ok(graph.recorder.get_tempo_map().sample_at_beat(beat) / f64::from(TARGET_SAMPLE_RATE))
+}
+
+/// Convert real seconds from the timeline start to a beat position, through the tempo map
+pub fn seconds_to_beat(seconds: f64) -> Result<f64, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    let sample = (seconds.max(0.0) * f64::from(TARGET_SAMPLE_RATE)) as u64;
+    Ok(graph.recorder.get_tempo_map().beat_at_sample(sample))
 }
 
 /// Get tempo in BPM
@@ -63,16 +129,61 @@ pub fn is_metronome_enabled() -> Result<bool, String> {
     Ok(graph.recorder.is_metronome_enabled())
 }
 
+/// Set metronome clicks per beat (1 = beats only, 2 = eighths, 3 = triplets, 4 = sixteenths)
+pub fn set_metronome_subdivision(subdivision: u32) -> Result<String, String> {
+    if !(1..=crate::recorder::MAX_METRONOME_SUBDIVISION).contains(&subdivision) {
+        return Err(format!("Invalid metronome subdivision {subdivision} (expected 1-4)"));
+    }
+
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    graph.recorder.set_metronome_subdivision(subdivision);
+    Ok(format!("Metronome subdivision: {subdivision} per beat"))
+}
+
+/// Enable or disable the metronome accent on beat 1 of each bar
+pub fn set_metronome_accent_enabled(enabled: bool) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    graph.recorder.set_metronome_accent_enabled(enabled);
+    Ok(format!("Metronome accent {}", if enabled { "enabled" } else { "disabled" }))
+}
+
+/// Load custom metronome click sounds (accent on beat 1, normal on other beats)
+///
+/// Passing two empty paths restores the synthesized click.
+pub fn set_metronome_samples(accent_path: String, normal_path: String) -> Result<String, String> {
+    let samples = if accent_path.is_empty() && normal_path.is_empty() {
+        None
+    } else {
+        let accent = load_audio_file(&accent_path)
+            .map_err(|e| format!("Failed to load accent click '{accent_path}': {e}"))?;
+        let normal = load_audio_file(&normal_path)
+            .map_err(|e| format!("Failed to load click '{normal_path}': {e}"))?;
+        Some(MetronomeSamples { accent: Arc::new(accent), normal: Arc::new(normal) })
+    };
+
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    let custom = samples.is_some();
+    graph.recorder.set_metronome_samples(samples);
+    Ok(if custom { "Metronome using custom click samples" } else { "Metronome using synthesized click" }.to_string())
+}
+
 // ============================================================================
 // TIME SIGNATURE CONTROL
 // ============================================================================
 
-/// Set time signature (beats per bar)
+/// Set time signature (beats per bar, in quarter notes) of the first bar
 pub fn set_time_signature(beats_per_bar: u32) -> Result<String, String> {
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
 
     graph.recorder.set_time_signature(beats_per_bar);
+    graph.refresh_midi_clip_time_signatures();
     Ok(format!("Time signature set to {beats_per_bar}/4"))
 }
 
@@ -83,3 +194,53 @@ pub fn get_time_signature() -> Result<u32, String> {
 
     Ok(graph.recorder.get_time_signature())
 }
+
+/// Change the time signature from the start of `bar` (counted from 0 at the timeline start)
+/// A change at bar 0 sets the project signature; one at an existing change's bar replaces it.
+pub fn add_time_signature_change(bar: u32, numerator: u32, denominator: u32) -> Result<String, String> {
+    let signature = TimeSignature::new(numerator, denominator)?;
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    graph.recorder.add_time_signature_change(bar, signature);
+    graph.refresh_midi_clip_time_signatures();
+
+    Ok(format!("Time signature change at bar {bar}: {numerator}/{denominator}"))
+}
+
+/// Remove the time-signature change at `bar` (the signature of bar 0 can't be removed)
+pub fn remove_time_signature_change(bar: u32) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    if !graph.recorder.remove_time_signature_change(bar) {
+        return Err(format!("No time signature change at bar {bar}"));
+    }
+    graph.refresh_midi_clip_time_signatures();
+
+    Ok(format!("Removed time signature change at bar {bar}"))
+}
+
+/// Get the time-signature map as JSON, starting with the project signature:
+/// `[{"bar", "numerator", "denominator", "beat"}, ...]` where `beat` is the quarter-note
+/// beat the change starts on (bar lines follow every `numerator * 4 / denominator` beats)
+pub fn get_time_signature_map() -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    let map = graph.recorder.get_time_signature_map();
+    let changes: Vec<serde_json::Value> = map
+        .changes()
+        .iter()
+        .zip(map.change_start_beats())
+        .map(|(change, beat)| {
+            serde_json::json!({
+                "bar": change.bar,
+                "numerator": change.signature.numerator,
+                "denominator": change.signature.denominator,
+                "beat": beat,
+            })
+        })
+        .collect();
+    Ok(serde_json::Value::Array(changes).to_string())
+}
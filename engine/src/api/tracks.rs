@@ -3,8 +3,10 @@
 //! Functions for creating and managing tracks, including volume, pan, mute, solo,
 //! armed state, and clip management.
 
-use super::helpers::get_audio_graph;
-use crate::track::{ClipId, TrackId, TrackType};
+use super::helpers::{get_audio_clips, get_audio_graph};
+use crate::history::Command;
+use crate::track::{ClipId, MeteringPoint, TrackId, TrackType};
+use std::sync::Arc;
 
 // ============================================================================
 // TRACK CREATION
@@ -59,7 +61,11 @@ pub fn set_track_volume(track_id: TrackId, volume_db: f32) -> Result<String, Str
 
     if let Some(track_arc) = track_manager.get_track(track_id) {
         let mut track = track_arc.lock();
+        let from = track.volume_db;
         track.volume_db = volume_db.clamp(-96.0, 6.0);
+        if (track.volume_db - from).abs() > f32::EPSILON {
+            super::history::record(Command::SetTrackVolume { track_id, from, to: track.volume_db });
+        }
         eprintln!("🎚️ Track {} volume now = {:.2} dB, gain = {:.4}", track_id, track.volume_db, track.get_gain());
         Ok(format!("Track {} volume set to {:.2} dB", track_id, track.volume_db))
     } else {
@@ -79,7 +85,11 @@ pub fn set_track_pan(track_id: TrackId, pan: f32) -> Result<String, String> {
 
     if let Some(track_arc) = track_manager.get_track(track_id) {
         let mut track = track_arc.lock();
+        let from = track.pan;
         track.pan = pan.clamp(-1.0, 1.0);
+        if (track.pan - from).abs() > f32::EPSILON {
+            super::history::record(Command::SetTrackPan { track_id, from, to: track.pan });
+        }
         Ok(format!("Track {} pan set to {:.2}", track_id, track.pan))
     } else {
         Err(format!("Track {track_id} not found"))
@@ -133,6 +143,24 @@ pub fn set_track_solo(track_id: TrackId, solo: bool) -> Result<String, String> {
     }
 }
 
+/// Mark a track solo-safe: it keeps playing while other tracks are soloed
+/// Returns are solo-safe by default. Soloing a group soloes the tracks routed into
+/// it, and soloing a track keeps its groups audible; muting a group still silences
+/// its children, solo-safe or not.
+pub fn set_track_solo_safe(track_id: TrackId, solo_safe: bool) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    if let Some(track_arc) = track_manager.get_track(track_id) {
+        let mut track = track_arc.lock();
+        track.solo_safe = solo_safe;
+        Ok(format!("Track {track_id} solo safe: {solo_safe}"))
+    } else {
+        Err(format!("Track {track_id} not found"))
+    }
+}
+
 /// Set track name
 pub fn set_track_name(track_id: TrackId, name: String) -> Result<String, String> {
     let graph_mutex = get_audio_graph()?;
@@ -173,17 +201,45 @@ pub fn set_track_volume_automation(track_id: TrackId, csv: &str) -> Result<Strin
     }
 }
 
+/// Set track pan automation curve
+///
+/// # Arguments
+/// * `track_id` - Track ID
+/// * `csv` - Automation curve as CSV: "`time_seconds,pan;time_seconds,pan`;..."
+///           Pan values are -1.0 (left) to +1.0 (right); empty string clears the curve
+///
+/// When automation is set, it overrides the static `pan` during playback
+pub fn set_track_pan_automation(track_id: TrackId, csv: &str) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    if let Some(track_arc) = track_manager.get_track(track_id) {
+        let mut track = track_arc.lock();
+        track.set_pan_automation_csv(csv);
+        let point_count = track.pan_automation.len();
+        Ok(format!("Track {track_id} pan automation set ({point_count} points)"))
+    } else {
+        Err(format!("Track {track_id} not found"))
+    }
+}
+
 // ============================================================================
 // INPUT ROUTING
 // ============================================================================
 
-/// Set audio input device and channel for a track
+/// Set audio input device and channels for a track
 ///
 /// # Arguments
 /// * `track_id` - Track ID
 /// * `device_index` - Input device index (-1 = no input)
-/// * `channel` - Channel index within the device (0-based, mono)
-pub fn set_track_input(track_id: TrackId, device_index: i32, channel: u32) -> Result<String, String> {
+/// * `channel` - First channel index within the device (0-based)
+/// * `channel_count` - 1 = mono, 2 = stereo pair (`channel` and `channel + 1`)
+pub fn set_track_input(track_id: TrackId, device_index: i32, channel: u32, channel_count: u32) -> Result<String, String> {
+    if !(1..=2).contains(&channel_count) {
+        return Err(format!("Invalid input channel count {channel_count} (expected 1 or 2)"));
+    }
+
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
     let track_manager = graph.track_manager.lock();
@@ -193,20 +249,26 @@ pub fn set_track_input(track_id: TrackId, device_index: i32, channel: u32) -> Re
         if device_index < 0 {
             track.input_device_index = None;
             track.input_channel = 0;
+            track.input_channel_count = 1;
             Ok(format!("Track {track_id} input cleared"))
         } else {
             track.input_device_index = Some(device_index as usize);
             track.input_channel = channel;
-            Ok(format!("Track {track_id} input set to device {device_index} channel {channel}"))
+            track.input_channel_count = channel_count;
+            if channel_count == 2 {
+                Ok(format!("Track {track_id} input set to device {device_index} channels {}-{}", channel, channel + 1))
+            } else {
+                Ok(format!("Track {track_id} input set to device {device_index} channel {channel}"))
+            }
         }
     } else {
         Err(format!("Track {track_id} not found"))
     }
 }
 
-/// Get audio input device and channel for a track
+/// Get audio input device and channels for a track
 ///
-/// Returns: "`device_index,channel`" (-1 if no input assigned)
+/// Returns: "`device_index,channel,channel_count`" (-1 if no input assigned)
 pub fn get_track_input(track_id: TrackId) -> Result<String, String> {
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
@@ -215,7 +277,7 @@ pub fn get_track_input(track_id: TrackId) -> Result<String, String> {
     if let Some(track_arc) = track_manager.get_track(track_id) {
         let track = track_arc.lock();
         let device_idx = track.input_device_index.map_or(-1, |i| i as i32);
-        Ok(format!("{},{}", device_idx, track.input_channel))
+        Ok(format!("{},{},{}", device_idx, track.input_channel, track.input_channel_count))
     } else {
         Err(format!("Track {track_id} not found"))
     }
@@ -236,6 +298,102 @@ pub fn set_track_input_monitoring(track_id: TrackId, enabled: bool) -> Result<St
     }
 }
 
+/// Set whether monitored input is heard through the track's FX chain
+/// The recorded audio is always captured dry, before the FX chain
+pub fn set_track_monitor_through_fx(track_id: TrackId, enabled: bool) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    if let Some(track_arc) = track_manager.get_track(track_id) {
+        let mut track = track_arc.lock();
+        track.monitor_through_fx = enabled;
+        Ok(format!("Track {track_id} monitor through FX: {enabled}"))
+    } else {
+        Err(format!("Track {track_id} not found"))
+    }
+}
+
+/// Invert a track's polarity (applied before volume/pan)
+pub fn set_track_phase_invert(track_id: TrackId, inverted: bool) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    if let Some(track_arc) = track_manager.get_track(track_id) {
+        let mut track = track_arc.lock();
+        track.phase_invert = inverted;
+        Ok(format!("Track {track_id} phase invert: {inverted}"))
+    } else {
+        Err(format!("Track {track_id} not found"))
+    }
+}
+
+/// Sum a track to mono (applied before volume/pan, so pan still places it)
+pub fn set_track_mono_sum(track_id: TrackId, enabled: bool) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    if let Some(track_arc) = track_manager.get_track(track_id) {
+        let mut track = track_arc.lock();
+        track.mono_sum = enabled;
+        Ok(format!("Track {track_id} mono sum: {enabled}"))
+    } else {
+        Err(format!("Track {track_id} not found"))
+    }
+}
+
+/// Layer the built-in synth under a VST3 instrument on the track
+/// (by default a VST3 instrument replaces the synth)
+pub fn set_track_layer_synth(track_id: TrackId, enabled: bool) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    if let Some(track_arc) = track_manager.get_track(track_id) {
+        let mut track = track_arc.lock();
+        track.layer_synth = enabled;
+        Ok(format!("Track {track_id} layer synth: {enabled}"))
+    } else {
+        Err(format!("Track {track_id} not found"))
+    }
+}
+
+/// Route a track into a group track (None = straight to the master)
+/// The group's FX, volume, pan and mute then apply to the track; groups can be nested
+pub fn set_track_group(track_id: TrackId, group_id: Option<TrackId>) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    track_manager.set_parent_group(track_id, group_id)?;
+    Ok(match group_id {
+        Some(group_id) => format!("Track {track_id} routed to group {group_id}"),
+        None => format!("Track {track_id} routed to master"),
+    })
+}
+
+/// Set a track's input trim in dB (clamped to -24..+24)
+/// Applied to the live input before monitoring, metering and recording
+pub fn set_track_input_gain(track_id: TrackId, gain_db: f32) -> Result<String, String> {
+    if !gain_db.is_finite() {
+        return Err(format!("Invalid input gain: {gain_db}"));
+    }
+
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    if let Some(track_arc) = track_manager.get_track(track_id) {
+        let mut track = track_arc.lock();
+        track.set_input_gain_db(gain_db);
+        Ok(format!("Track {} input gain: {:.1} dB", track_id, track.input_gain_db))
+    } else {
+        Err(format!("Track {track_id} not found"))
+    }
+}
+
 // ============================================================================
 // TRACK QUERIES
 // ============================================================================
@@ -300,8 +458,25 @@ pub fn get_track_info(track_id: TrackId) -> Result<String, String> {
     }
 }
 
-/// Get track peak levels (M5.5)
-/// Returns CSV: "`peak_left_db,peak_right_db`"
+/// Choose where a track's meter reads: "`pre_fx`", "`post_fx_pre_fader`" or "`post_fader`"
+pub fn set_track_metering_point(track_id: TrackId, point: &str) -> Result<String, String> {
+    let metering_point = MeteringPoint::from_name(point)
+        .ok_or_else(|| format!("Unknown metering point '{point}' (use pre_fx, post_fx_pre_fader or post_fader)"))?;
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+
+    if let Some(track_arc) = track_manager.get_track(track_id) {
+        let mut track = track_arc.lock();
+        track.metering_point = metering_point;
+        Ok(format!("Track {track_id} metering point: {point}"))
+    } else {
+        Err(format!("Track {track_id} not found"))
+    }
+}
+
+/// Get track peak levels (M5.5) at the track's metering point
+/// Returns CSV: "`peak_left_db,peak_right_db,metering_point`"
 pub fn get_track_peak_levels(track_id: TrackId) -> Result<String, String> {
     let graph_mutex = get_audio_graph()?;
     let graph = graph_mutex.lock();
@@ -310,12 +485,136 @@ pub fn get_track_peak_levels(track_id: TrackId) -> Result<String, String> {
     if let Some(track_arc) = track_manager.get_track(track_id) {
         let track = track_arc.lock();
         let (peak_left_db, peak_right_db) = track.get_peak_db();
-        Ok(format!("{peak_left_db:.2},{peak_right_db:.2}"))
+        Ok(format!("{peak_left_db:.2},{peak_right_db:.2},{}", track.metering_point.name()))
     } else {
         Err(format!("Track {track_id} not found"))
     }
 }
 
+/// Get master loudness in LUFS as (momentary, short-term, integrated)
+/// Integrated loudness is measured since playback last started
+pub fn get_master_loudness() -> Result<(f32, f32, f32), String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    Ok(graph.master_loudness.get())
+}
+
+/// Clear the master loudness meter (restarts the integrated measurement)
+pub fn reset_master_loudness() -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    graph.master_loudness.request_reset();
+    Ok("Master loudness meter reset".to_string())
+}
+
+/// Get master true peak in dBTP as (left, right, maximum since playback started)
+/// Reads the floor until enabled with `set_master_true_peak_enabled`
+pub fn get_master_true_peak() -> Result<(f32, f32, f32), String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    Ok(graph.master_true_peak.get())
+}
+
+/// Enable/disable 4x oversampled true-peak metering of the master output
+pub fn set_master_true_peak_enabled(enabled: bool) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    graph.master_true_peak.set_enabled(enabled);
+    Ok(format!("True peak meter {}", if enabled { "enabled" } else { "disabled" }))
+}
+
+/// Get the latest master spectrum as `num_bins` linear magnitudes (0Hz to Nyquist)
+/// Returns zeros until the analyzer is enabled and has filled a window
+pub fn get_master_spectrum(num_bins: usize) -> Result<Vec<f32>, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    Ok(graph.master_spectrum.get(num_bins))
+}
+
+/// Enable/disable the master spectrum analyzer and set its FFT window size (512, 1024 or 2048)
+pub fn set_master_spectrum_settings(enabled: bool, window_size: usize) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    graph.master_spectrum.set_window_size(window_size)?;
+    graph.master_spectrum.set_enabled(enabled);
+    Ok(format!(
+        "Spectrum analyzer {} (window {window_size})",
+        if enabled { "enabled" } else { "disabled" }
+    ))
+}
+
+// ============================================================================
+// REFERENCE TRACK (A/B)
+// ============================================================================
+
+/// Load an audio file as the reference track (replaces any loaded reference)
+/// Returns the reference's integrated loudness in LUFS
+pub fn load_reference_track(path: &str) -> Result<f32, String> {
+    // Decode before locking the graph (this can take a while)
+    let clip = crate::audio_file::load_audio_file(path).map_err(|e| e.to_string())?;
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let mut reference = graph.reference_track.lock();
+    reference.load(Arc::new(clip));
+    Ok(reference.loudness_lufs().unwrap_or(crate::loudness::LOUDNESS_FLOOR_LUFS))
+}
+
+/// Remove the reference track (the master is heard again)
+pub fn clear_reference_track() -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    graph.reference_track.lock().clear();
+    Ok("Reference track cleared".to_string())
+}
+
+/// Hear the reference track (true) or the master (false); takes effect on the next buffer
+pub fn toggle_reference(enabled: bool) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let mut reference = graph.reference_track.lock();
+    if enabled && reference.clip().is_none() {
+        return Err("No reference track loaded".to_string());
+    }
+    reference.set_enabled(enabled);
+    Ok(format!("Listening to {}", if enabled { "reference" } else { "master" }))
+}
+
+/// Set the reference track gain in dB (clamped to -48..+24)
+pub fn set_reference_gain_db(gain_db: f32) -> Result<String, String> {
+    if !gain_db.is_finite() {
+        return Err(format!("Invalid reference gain: {gain_db}"));
+    }
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let mut reference = graph.reference_track.lock();
+    reference.set_gain_db(gain_db);
+    Ok(format!("Reference gain set to {:.1} dB", reference.gain_db()))
+}
+
+/// Get the reference state as (enabled, gain in dB, loudness in LUFS)
+pub fn get_reference_state() -> Result<(bool, f32, f32), String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let reference = graph.reference_track.lock();
+    let loudness = reference.loudness_lufs().ok_or("No reference track loaded")?;
+    Ok((reference.is_enabled(), reference.gain_db(), loudness))
+}
+
+/// Set the reference gain so it matches the master's integrated loudness
+/// Play the mix first so the master meter has a reading; returns the gain in dB
+pub fn match_reference_loudness() -> Result<f32, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let (_, _, master_lufs) = graph.master_loudness.get();
+    let mut reference = graph.reference_track.lock();
+    if reference.clip().is_none() {
+        return Err("No reference track loaded".to_string());
+    }
+    reference
+        .match_loudness(master_lufs)
+        .ok_or_else(|| "No master loudness reading yet (play the mix first)".to_string())
+}
+
 // ============================================================================
 // CLIP MANAGEMENT
 // ============================================================================
@@ -353,3 +652,150 @@ pub fn move_clip_to_track(track_id: TrackId, clip_id: ClipId) -> Result<String,
         Err(format!("Track {track_id} not found"))
     }
 }
+
+/// Split an audio clip in two at a timeline position
+///
+/// Both halves share the original audio; the original clip is replaced. The gain
+/// envelope and clip automation after the cut move to the second half.
+///
+/// # Arguments
+/// * `track_id` - Track containing the clip
+/// * `clip_id` - ID of the clip to split
+/// * `split_time_seconds` - Timeline position of the cut (must be inside the clip)
+///
+/// # Returns
+/// The IDs of the first and second halves
+pub fn split_audio_clip(track_id: TrackId, clip_id: ClipId, split_time_seconds: f64) -> Result<(ClipId, ClipId), String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    let (first, second) = {
+        let track_manager = graph.track_manager.lock();
+        let track_arc = track_manager
+            .get_track(track_id)
+            .ok_or_else(|| format!("Track {track_id} not found"))?;
+        let mut track = track_arc.lock();
+        let index = track
+            .audio_clips
+            .iter()
+            .position(|c| c.id == clip_id)
+            .ok_or_else(|| format!("Clip {clip_id} not found on track {track_id}"))?;
+
+        let (first_id, second_id) = {
+            let mut next_id = graph.next_clip_id.lock();
+            let id = *next_id;
+            *next_id += 2;
+            (id, id + 1)
+        };
+        let (first, second) = track.audio_clips[index]
+            .split_at(split_time_seconds, first_id, second_id)
+            .ok_or_else(|| format!("Split time {split_time_seconds:.3}s is outside clip {clip_id}"))?;
+
+        track.audio_clips.splice(index..=index, [first.clone(), second.clone()]);
+        (first, second)
+    };
+
+    {
+        let mut clips_map = get_audio_clips()?.lock();
+        clips_map.remove(&clip_id);
+        clips_map.insert(first.id, first.clip);
+        clips_map.insert(second.id, second.clip);
+    }
+
+    eprintln!("✂️  [API] Split clip {clip_id} at {split_time_seconds:.3}s → clips {} and {}", first.id, second.id);
+    Ok((first.id, second.id))
+}
+
+/// Split a MIDI clip in two at a timeline position
+///
+/// Notes held across the cut end at the cut and restart in the second clip.
+/// The original clip is replaced on the track and in global storage.
+///
+/// # Returns
+/// The IDs of the first and second halves
+pub fn split_midi_clip(track_id: TrackId, clip_id: ClipId, split_time_seconds: f64) -> Result<(ClipId, ClipId), String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    let (start_time, first_clip, second_clip) = {
+        let track_manager = graph.track_manager.lock();
+        let track_arc = track_manager
+            .get_track(track_id)
+            .ok_or_else(|| format!("Track {track_id} not found"))?;
+        let mut track = track_arc.lock();
+        let index = track
+            .midi_clips
+            .iter()
+            .position(|c| c.id == clip_id)
+            .ok_or_else(|| format!("MIDI clip {clip_id} not found on track {track_id}"))?;
+
+        let timeline_clip = &track.midi_clips[index];
+        let split_samples = ((split_time_seconds - timeline_clip.start_time)
+            * f64::from(timeline_clip.clip.sample_rate))
+        .round();
+        if split_samples <= 0.0 || split_samples >= timeline_clip.clip.duration_samples as f64 {
+            return Err(format!("Split time {split_time_seconds:.3}s is outside MIDI clip {clip_id}"));
+        }
+        let (first, second) = timeline_clip.clip.split_at(split_samples as u64);
+        let start_time = timeline_clip.start_time;
+        track.midi_clips.remove(index);
+        (start_time, Arc::new(first), Arc::new(second))
+    };
+    graph.remove_clip(clip_id);
+
+    let mut ids = [0; 2];
+    for (slot, (clip, start)) in ids.iter_mut().zip([
+        (first_clip, start_time),
+        (second_clip, split_time_seconds),
+    ]) {
+        let id = graph.add_midi_clip(clip.clone(), start);
+        if let Some(stored) = graph.get_midi_clips().lock().iter_mut().find(|c| c.id == id) {
+            stored.track_id = Some(track_id);
+        }
+        graph.add_midi_clip_to_track(track_id, clip, start, id)
+            .ok_or_else(|| format!("Failed to add MIDI clip to track {track_id}"))?;
+        *slot = id;
+    }
+
+    eprintln!("✂️  [API] Split MIDI clip {clip_id} at {split_time_seconds:.3}s → clips {} and {}", ids[0], ids[1]);
+    Ok((ids[0], ids[1]))
+}
+
+// ============================================================================
+// TRACK FREEZE
+// ============================================================================
+
+/// Freeze a track: render it to audio and replace its clips, instrument and FX
+///
+/// The rendered clip is registered in the clips map (for waveforms) until unfrozen.
+///
+/// # Returns
+/// The frozen audio clip's ID
+pub fn freeze_track(track_id: TrackId) -> Result<ClipId, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let (clip_id, clip) = graph.freeze_track(track_id)?;
+    get_audio_clips()?.lock().insert(clip_id, clip);
+    Ok(clip_id)
+}
+
+/// Unfreeze a track, restoring its original clips, instrument and FX
+pub fn unfreeze_track(track_id: TrackId) -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let clip_id = graph.unfreeze_track(track_id)?;
+    get_audio_clips()?.lock().remove(&clip_id);
+    Ok(format!("Track {track_id} unfrozen"))
+}
+
+/// Check whether a track is frozen
+pub fn is_track_frozen(track_id: TrackId) -> Result<bool, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let track_manager = graph.track_manager.lock();
+    let track_arc = track_manager
+        .get_track(track_id)
+        .ok_or_else(|| format!("Track {track_id} not found"))?;
+    let frozen = track_arc.lock().frozen.is_some();
+    Ok(frozen)
+}
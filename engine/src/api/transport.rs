@@ -30,6 +30,28 @@ pub fn transport_play() -> Result<String, String> {
     }
 }
 
+/// Play the metronome for `count_in_bars` bars, then start playback
+///
+/// Count-in beat/progress are reported through `get_count_in_beat`/`get_count_in_progress`.
+pub fn start_playback_with_count_in() -> Result<String, String> {
+    let graph_mutex = get_audio_graph()?;
+    let mut graph = graph_mutex.lock();
+
+    if graph.play_with_count_in().map_err(|e| e.to_string())? {
+        Ok(format!("Counting in {} bars", graph.recorder.get_count_in_bars()))
+    } else {
+        Ok("Playing".to_string())
+    }
+}
+
+/// Check if the metronome is counting in before playback starts
+pub fn is_playback_counting_in() -> Result<bool, String> {
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+
+    Ok(graph.recorder.is_playback_counting_in())
+}
+
 /// Pause playback (non-blocking: uses `try_lock` to avoid UI freeze)
 pub fn transport_pause() -> Result<String, String> {
     let graph_mutex = get_audio_graph()?;
@@ -78,6 +100,38 @@ pub fn transport_seek(position_seconds: f64) -> Result<String, String> {
     })
 }
 
+/// Seek to a position in seconds, clearing effect tails and the master limiter
+/// (`transport_seek` keeps tails running, for scrubbing)
+pub fn transport_seek_clean(position_seconds: f64) -> Result<String, String> {
+    with_graph(|graph| {
+        graph.seek_clean(position_seconds);
+        Ok(format!("Seeked to {position_seconds:.2}s (effects reset)"))
+    })
+}
+
+/// Scrub: loop a short grain of the mix around a position without moving the
+/// transport (while stopped). Each call replaces the previous grain.
+pub fn scrub_to(position_seconds: f64, window_ms: f64) -> Result<String, String> {
+    if !position_seconds.is_finite() || !window_ms.is_finite() {
+        return Err("Scrub position and window must be finite".to_string());
+    }
+    with_graph(|graph| {
+        if graph.get_state() == TransportState::Playing {
+            return Err("Cannot scrub while the transport is playing".to_string());
+        }
+        graph.scrub_to(position_seconds, window_ms);
+        Ok(format!("Scrubbing at {position_seconds:.3}s"))
+    })
+}
+
+/// Stop scrubbing (the current grain finishes its pass)
+pub fn scrub_stop() -> Result<String, String> {
+    with_graph(|graph| {
+        graph.stop_scrub();
+        Ok("Scrub stopped".to_string())
+    })
+}
+
 /// Get current playhead position in seconds
 pub fn get_playhead_position() -> Result<f64, String> {
     with_graph(|graph| Ok(graph.get_playhead_position()))
@@ -120,3 +174,134 @@ pub fn set_record_start_position(position_seconds: f64) -> Result<String, String
         Ok(format!("Record start position set to {position_seconds:.2}s"))
     })
 }
+
+// ============================================================================
+// LOOP REGION
+// ============================================================================
+
+/// Set the loop region (timeline seconds)
+pub fn set_loop_region(start_seconds: f64, end_seconds: f64) -> Result<String, String> {
+    if start_seconds < 0.0 || end_seconds <= start_seconds {
+        return Err(format!(
+            "Invalid loop region: {start_seconds:.3}s - {end_seconds:.3}s"
+        ));
+    }
+    with_graph(|graph| {
+        graph.set_loop_region(start_seconds, end_seconds);
+        Ok(format!("Loop region set: {start_seconds:.3}s - {end_seconds:.3}s"))
+    })
+}
+
+/// Enable or disable loop playback
+pub fn set_loop_enabled(enabled: bool) -> Result<String, String> {
+    with_graph(|graph| {
+        graph.set_loop_enabled(enabled);
+        Ok(format!("Loop {}", if enabled { "enabled" } else { "disabled" }))
+    })
+}
+
+/// Check if loop playback is enabled
+pub fn is_loop_enabled() -> Result<bool, String> {
+    with_graph(|graph| Ok(graph.is_loop_enabled()))
+}
+
+// ============================================================================
+// MARKERS
+// ============================================================================
+
+/// Add a named marker at a timeline position; returns the marker ID
+pub fn add_marker(time_seconds: f64, label: String) -> Result<u64, String> {
+    if !time_seconds.is_finite() {
+        return Err("Marker time must be finite".to_string());
+    }
+    with_graph(|graph| Ok(graph.markers.lock().add(time_seconds, label)))
+}
+
+/// Remove a marker
+pub fn remove_marker(marker_id: u64) -> Result<String, String> {
+    with_graph(|graph| {
+        let marker = graph
+            .markers
+            .lock()
+            .remove(marker_id)
+            .ok_or_else(|| format!("Marker {marker_id} not found"))?;
+        Ok(format!("Removed marker '{}'", marker.label))
+    })
+}
+
+/// Get all markers as JSON, earliest first: `[{"id", "time_seconds", "label"}, ...]`
+pub fn get_markers() -> Result<String, String> {
+    with_graph(|graph| {
+        let markers: Vec<serde_json::Value> = graph
+            .markers
+            .lock()
+            .markers()
+            .iter()
+            .map(|marker| {
+                serde_json::json!({
+                    "id": marker.id,
+                    "time_seconds": marker.time_seconds,
+                    "label": marker.label,
+                })
+            })
+            .collect();
+        Ok(serde_json::Value::Array(markers).to_string())
+    })
+}
+
+/// Move the playhead to a marker; returns the new position in seconds
+pub fn jump_to_marker(marker_id: u64) -> Result<f64, String> {
+    with_graph(|graph| {
+        let time_seconds = graph
+            .markers
+            .lock()
+            .get(marker_id)
+            .map(|marker| marker.time_seconds)
+            .ok_or_else(|| format!("Marker {marker_id} not found"))?;
+        graph.seek(time_seconds);
+        Ok(time_seconds)
+    })
+}
+
+/// Move the playhead to the first marker after it; returns the new position in seconds
+pub fn jump_to_next_marker() -> Result<f64, String> {
+    with_graph(|graph| {
+        let playhead = graph.get_playhead_position();
+        let time_seconds = graph
+            .markers
+            .lock()
+            .next_after(playhead)
+            .map(|marker| marker.time_seconds)
+            .ok_or("No marker after the playhead")?;
+        graph.seek(time_seconds);
+        Ok(time_seconds)
+    })
+}
+
+/// Move the playhead to the last marker before it; returns the new position in seconds
+pub fn jump_to_previous_marker() -> Result<f64, String> {
+    with_graph(|graph| {
+        let playhead = graph.get_playhead_position();
+        let time_seconds = graph
+            .markers
+            .lock()
+            .previous_before(playhead)
+            .map(|marker| marker.time_seconds)
+            .ok_or("No marker before the playhead")?;
+        graph.seek(time_seconds);
+        Ok(time_seconds)
+    })
+}
+
+/// Set the loop region to run from one marker to another
+pub fn set_loop_to_markers(start_marker_id: u64, end_marker_id: u64) -> Result<String, String> {
+    let (start_seconds, end_seconds) = with_graph(|graph| {
+        let markers = graph.markers.lock();
+        let time_of = |id: u64| markers.get(id).map(|marker| marker.time_seconds).ok_or_else(|| format!("Marker {id} not found"));
+        Ok((time_of(start_marker_id)?, time_of(end_marker_id)?))
+    })?;
+    if end_seconds <= start_seconds {
+        return Err(format!("Marker {end_marker_id} must come after marker {start_marker_id}"));
+    }
+    set_loop_region(start_seconds, end_seconds)
+}
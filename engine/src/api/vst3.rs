@@ -74,6 +74,29 @@ pub fn get_vst3_parameter_count(effect_id: u64) -> Result<u32, String> {
     }
 }
 
+#[cfg(not(target_os = "ios"))]
+/// Get the processing latency a VST3 plugin reports, in samples
+/// (plugin delay compensation lines the other tracks up with it)
+pub fn get_vst3_latency(effect_id: u64) -> Result<u32, String> {
+    use crate::effects::EffectType;
+
+    let graph_mutex = get_audio_graph()?;
+    let graph = graph_mutex.lock();
+    let effect_manager = graph.effect_manager.lock();
+
+    if let Some(effect_arc) = effect_manager.get_effect(effect_id) {
+        let effect = effect_arc.lock();
+
+        if let EffectType::VST3(vst3) = &*effect {
+            Ok(vst3.latency_samples() as u32)
+        } else {
+            Err(format!("Effect {effect_id} is not a VST3 plugin"))
+        }
+    } else {
+        Err(format!("Effect {effect_id} not found"))
+    }
+}
+
 #[cfg(not(target_os = "ios"))]
 /// Get information about a VST3 parameter (returns "name,min,max,default")
 pub fn get_vst3_parameter_info(effect_id: u64, param_index: u32) -> Result<String, String> {
@@ -493,6 +516,11 @@ pub fn get_vst3_parameter_count(_effect_id: u64) -> Result<u32, String> {
     Err("VST3 plugins are not supported on iOS".to_string())
 }
 
+#[cfg(target_os = "ios")]
+pub fn get_vst3_latency(_effect_id: u64) -> Result<u32, String> {
+    Err("VST3 plugins are not supported on iOS".to_string())
+}
+
 #[cfg(target_os = "ios")]
 pub fn get_vst3_parameter_info(_effect_id: u64, _param_index: u32) -> Result<String, String> {
     Err("VST3 plugins are not supported on iOS".to_string())
@@ -0,0 +1,302 @@
+/// Arpeggiator - turns held notes into a tempo-synced pattern of single notes
+/// Sits between incoming MIDI (clips, live input, virtual piano) and a track's instrument.
+/// Steps land on the beat grid of the playhead while the transport runs, and on a
+/// free-running clock while it is stopped.
+use serde::{Deserialize, Serialize};
+
+/// Shortest and longest step length in beats (1/64 note to one bar of 4/4)
+pub const MIN_RATE_BEATS: f64 = 0.0625;
+pub const MAX_RATE_BEATS: f64 = 4.0;
+/// Octave range the pattern can span
+pub const MAX_OCTAVES: u8 = 4;
+/// Shortest gate (fraction of a step a note sounds for)
+pub const MIN_GATE: f32 = 0.05;
+
+/// Order in which held notes are played
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArpMode {
+    /// Lowest to highest
+    #[default]
+    Up,
+    /// Highest to lowest
+    Down,
+    /// Up then back down, without repeating the top and bottom notes
+    UpDown,
+    /// A random held note each step
+    Random,
+}
+
+impl ArpMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "up" => Some(Self::Up),
+            "down" => Some(Self::Down),
+            "updown" => Some(Self::UpDown),
+            "random" => Some(Self::Random),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::UpDown => "updown",
+            Self::Random => "random",
+        }
+    }
+}
+
+/// Note changes the arpeggiator wants on this sample (note-off goes first)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArpEvents {
+    pub note_off: Option<u8>,
+    pub note_on: Option<(u8, u8)>,
+}
+
+/// Saved arpeggiator settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArpeggiatorData {
+    pub enabled: bool,
+    pub rate_beats: f64,
+    pub mode: ArpMode,
+    pub octaves: u8,
+    pub gate: f32,
+}
+
+#[derive(Clone)]
+pub struct Arpeggiator {
+    pub enabled: bool,
+    /// Step length in beats (0.25 = 1/16 note)
+    pub rate_beats: f64,
+    pub mode: ArpMode,
+    /// Octaves the pattern spans (1-4)
+    pub octaves: u8,
+    /// Fraction of each step a note sounds for (0.05-1.0)
+    pub gate: f32,
+    /// Held notes as (note, velocity), sorted by note
+    held: Vec<(u8, u8)>,
+    /// Steps played since the first note was held
+    step_index: usize,
+    /// Grid step the last note was played on
+    last_step: Option<i64>,
+    /// Currently sounding note and the beat it ends on
+    sounding: Option<(u8, f64)>,
+    /// Beat position used while the transport is stopped
+    free_clock: f64,
+    rng_state: u32,
+}
+
+impl Default for Arpeggiator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Arpeggiator {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            rate_beats: 0.25,
+            mode: ArpMode::Up,
+            octaves: 1,
+            gate: 0.5,
+            held: Vec::new(),
+            step_index: 0,
+            last_step: None,
+            sounding: None,
+            free_clock: 0.0,
+            rng_state: 0x9E37_79B9,
+        }
+    }
+
+    /// Set the pattern parameters (values are clamped to their valid ranges)
+    pub fn set_params(&mut self, rate_beats: f64, mode: ArpMode, octaves: u8, gate: f32) {
+        self.rate_beats = rate_beats.clamp(MIN_RATE_BEATS, MAX_RATE_BEATS);
+        self.mode = mode;
+        self.octaves = octaves.clamp(1, MAX_OCTAVES);
+        self.gate = gate.clamp(MIN_GATE, 1.0);
+    }
+
+    pub fn note_on(&mut self, note: u8, velocity: u8) {
+        match self.held.binary_search_by_key(&note, |&(n, _)| n) {
+            Ok(index) => self.held[index].1 = velocity,
+            Err(index) => self.held.insert(index, (note, velocity)),
+        }
+    }
+
+    pub fn note_off(&mut self, note: u8) {
+        self.held.retain(|&(n, _)| n != note);
+    }
+
+    /// Forget held notes and return the sounding one (if any) so it can be released
+    pub fn reset(&mut self) -> Option<u8> {
+        self.held.clear();
+        self.step_index = 0;
+        self.last_step = None;
+        self.sounding.take().map(|(note, _)| note)
+    }
+
+    /// Advance by one sample
+    ///
+    /// `playhead_beats` is the transport position while playing (None when stopped,
+    /// in which case the free-running clock advances by `beats_per_sample`).
+    pub fn tick(&mut self, playhead_beats: Option<f64>, beats_per_sample: f64) -> ArpEvents {
+        let position = playhead_beats.unwrap_or(self.free_clock);
+        self.free_clock = position + beats_per_sample;
+
+        let mut events = ArpEvents::default();
+        if let Some((note, end_beat)) = self.sounding {
+            if position >= end_beat || self.held.is_empty() {
+                events.note_off = Some(note);
+                self.sounding = None;
+            }
+        }
+        if self.held.is_empty() {
+            self.step_index = 0;
+            self.last_step = None;
+            return events;
+        }
+
+        // A new grid step (or the first held note) plays the next note in the pattern
+        let step = (position / self.rate_beats).floor() as i64;
+        if self.last_step == Some(step) {
+            return events;
+        }
+        self.last_step = Some(step);
+
+        if let Some((note, _)) = self.sounding.take() {
+            events.note_off = Some(note);
+        }
+        let (note, velocity) = self.next_note();
+        let step_end = (step + 1) as f64 * self.rate_beats;
+        let end_beat = (position + f64::from(self.gate) * self.rate_beats).min(step_end);
+        self.sounding = Some((note, end_beat));
+        events.note_on = Some((note, velocity));
+        events
+    }
+
+    /// Next note of the pattern: held notes repeated an octave up for each extra octave
+    fn next_note(&mut self) -> (u8, u8) {
+        let held_count = self.held.len();
+        let length = held_count * usize::from(self.octaves);
+        let index = match self.mode {
+            ArpMode::Up => self.step_index % length,
+            ArpMode::Down => length - 1 - self.step_index % length,
+            ArpMode::UpDown if length > 1 => {
+                let position = self.step_index % (2 * length - 2);
+                if position < length { position } else { 2 * length - 2 - position }
+            }
+            ArpMode::UpDown => 0,
+            ArpMode::Random => self.next_random() as usize % length,
+        };
+        self.step_index = self.step_index.wrapping_add(1);
+
+        let (note, velocity) = self.held[index % held_count];
+        let octave = (index / held_count) as u8;
+        (note.saturating_add(octave * 12).min(127), velocity)
+    }
+
+    /// xorshift32
+    fn next_random(&mut self) -> u32 {
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng_state = x;
+        x
+    }
+
+    pub fn get_parameters(&self) -> ArpeggiatorData {
+        ArpeggiatorData {
+            enabled: self.enabled,
+            rate_beats: self.rate_beats,
+            mode: self.mode,
+            octaves: self.octaves,
+            gate: self.gate,
+        }
+    }
+
+    pub fn restore_parameters(&mut self, data: &ArpeggiatorData) {
+        self.enabled = data.enabled;
+        self.set_params(data.rate_beats, data.mode, data.octaves, data.gate);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (beat, note) pairs
+    type NoteTimes = Vec<(f64, u8)>;
+
+    /// Run the arpeggiator against a playing transport for `beats` beats and
+    /// collect the note-ons and note-offs
+    fn run(arp: &mut Arpeggiator, beats: f64) -> (NoteTimes, NoteTimes) {
+        let beats_per_sample = 1.0 / 1000.0;
+        let mut ons = Vec::new();
+        let mut offs = Vec::new();
+        for i in 0..(beats * 1000.0) as usize {
+            let beat = i as f64 / 1000.0;
+            let events = arp.tick(Some(beat), beats_per_sample);
+            if let Some(note) = events.note_off {
+                offs.push((beat, note));
+            }
+            if let Some((note, _)) = events.note_on {
+                ons.push((beat, note));
+            }
+        }
+        (ons, offs)
+    }
+
+    #[test]
+    fn test_up_pattern_plays_on_the_step_grid() {
+        let mut arp = Arpeggiator::new();
+        arp.set_params(0.25, ArpMode::Up, 1, 0.5);
+        arp.note_on(64, 100);
+        arp.note_on(60, 100);
+        arp.note_on(67, 100);
+
+        let (ons, offs) = run(&mut arp, 1.0);
+        let notes: Vec<u8> = ons.iter().map(|&(_, n)| n).collect();
+        assert_eq!(notes, vec![60, 64, 67, 60]);
+        for (i, &(beat, _)) in ons.iter().enumerate() {
+            assert!((beat - i as f64 * 0.25).abs() < 1e-9, "step {i} at beat {beat}");
+        }
+        // Half-step gate: each note ends halfway through its step
+        assert!((offs[0].0 - 0.125).abs() < 1e-9);
+        assert_eq!(offs[0].1, 60);
+    }
+
+    #[test]
+    fn test_octaves_and_updown_do_not_repeat_the_ends() {
+        let mut arp = Arpeggiator::new();
+        arp.set_params(0.25, ArpMode::UpDown, 2, 1.0);
+        arp.note_on(60, 100);
+        arp.note_on(64, 100);
+
+        let (ons, _) = run(&mut arp, 2.0);
+        let notes: Vec<u8> = ons.iter().map(|&(_, n)| n).collect();
+        assert_eq!(notes, vec![60, 64, 72, 76, 72, 64, 60, 64]);
+    }
+
+    #[test]
+    fn test_first_note_plays_immediately_and_release_stops_the_pattern() {
+        let mut arp = Arpeggiator::new();
+        arp.set_params(1.0, ArpMode::Down, 1, 1.0);
+
+        // Held halfway through a step: plays now, next step on the grid
+        arp.note_on(60, 90);
+        let events = arp.tick(Some(0.5), 0.001);
+        assert_eq!(events.note_on, Some((60, 90)));
+        assert_eq!(arp.tick(Some(0.9), 0.001).note_on, None);
+        assert_eq!(arp.tick(Some(1.0), 0.001).note_on, Some((60, 90)));
+
+        arp.note_off(60);
+        let events = arp.tick(Some(1.1), 0.001);
+        assert_eq!(events.note_off, Some(60));
+        assert_eq!(events.note_on, None);
+        assert_eq!(arp.tick(Some(2.0), 0.001), ArpEvents::default());
+    }
+}
@@ -2,6 +2,8 @@
 use anyhow::{Context, Result};
 use rubato::{FftFixedInOut, Resampler};
 use std::io::{Read as _, Seek as _, SeekFrom};
+use crate::clip_stream::{ClipStream, STREAM_WINDOW_FRAMES};
+use std::borrow::Cow;
 use std::path::Path;
 use std::sync::Arc;
 use symphonia::core::audio::{AudioBufferRef, Signal};
@@ -16,9 +18,13 @@ use symphonia::core::probe::Hint;
 pub const TARGET_SAMPLE_RATE: u32 = 48000;
 
 /// Represents a loaded audio clip with decoded samples
+///
+/// A streamed clip (see `load_audio_file_streaming`) leaves `samples` empty and reads
+/// through `stream` instead; use `get_sample`/`read_frames` rather than `samples`
+/// for audio that may come from a file.
 #[derive(Clone, Debug)]
 pub struct AudioClip {
-    /// Decoded audio samples (interleaved stereo, f32 format)
+    /// Decoded audio samples (interleaved stereo, f32 format; empty when streamed)
     pub samples: Vec<f32>,
     /// Number of channels (1 = mono, 2 = stereo)
     pub channels: usize,
@@ -28,12 +34,17 @@ pub struct AudioClip {
     pub duration_seconds: f64,
     /// Original file path
     pub file_path: String,
+    /// On-demand reader for clips too large to hold in memory
+    pub stream: Option<Arc<ClipStream>>,
 }
 
 impl AudioClip {
     /// Get the number of frames (samples per channel)
     pub fn frame_count(&self) -> usize {
-        self.samples.len() / self.channels
+        match &self.stream {
+            Some(stream) => stream.frame_count(),
+            None => self.samples.len() / self.channels,
+        }
     }
 
     /// Get sample at specific frame and channel
@@ -41,7 +52,86 @@ impl AudioClip {
         if channel >= self.channels || frame >= self.frame_count() {
             return None;
         }
-        Some(self.samples[frame * self.channels + channel])
+        match &self.stream {
+            Some(stream) => Some(stream.get_sample(frame, channel)),
+            None => Some(self.samples[frame * self.channels + channel]),
+        }
+    }
+
+    /// Whether samples are read from disk on demand
+    pub fn is_streaming(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Interleaved samples of frames `start..start + frames` (clamped to the clip)
+    /// Borrowed for in-memory clips; streamed clips read from disk (silence on error)
+    pub fn read_frames(&self, start: usize, frames: usize) -> Cow<'_, [f32]> {
+        let start = start.min(self.frame_count());
+        let frames = frames.min(self.frame_count() - start);
+        match &self.stream {
+            Some(stream) => Cow::Owned(stream.read_frames(start, frames).unwrap_or_else(|e| {
+                eprintln!("⚠️ [ClipStream] {e}");
+                vec![0.0; frames * self.channels]
+            })),
+            None => Cow::Borrowed(&self.samples[start * self.channels..(start + frames) * self.channels]),
+        }
+    }
+
+    /// Absolute peak of frames `start..start + frames` (clamped to the clip)
+    ///
+    /// Reads one stream window at a time, so a streamed clip is never fully
+    /// loaded; callers should not hold engine locks while scanning one.
+    pub fn peak(&self, start: usize, frames: usize) -> f32 {
+        let end = start.saturating_add(frames).min(self.frame_count());
+        (start..end)
+            .step_by(STREAM_WINDOW_FRAMES)
+            .map(|chunk_start| {
+                self.read_frames(chunk_start, STREAM_WINDOW_FRAMES.min(end - chunk_start))
+                    .iter()
+                    .fold(0.0_f32, |peak, sample| peak.max(sample.abs()))
+            })
+            .fold(0.0_f32, f32::max)
+    }
+
+    /// Convert to stereo: mono is duplicated to both channels, channels beyond
+    /// the first two are dropped
+    pub fn to_stereo(&self) -> Self {
+        if self.channels == 2 {
+            return self.clone();
+        }
+        let channels = self.channels.max(1);
+        let samples = self
+            .samples
+            .chunks_exact(channels)
+            .flat_map(|frame| [frame[0], frame.get(1).copied().unwrap_or(frame[0])])
+            .collect();
+        Self { samples, channels: 2, ..self.clone_metadata() }
+    }
+
+    /// Convert to mono by averaging all channels
+    pub fn to_mono(&self) -> Self {
+        if self.channels == 1 {
+            return self.clone();
+        }
+        let channels = self.channels.max(1);
+        let samples = self
+            .samples
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect();
+        Self { samples, channels: 1, ..self.clone_metadata() }
+    }
+
+    /// Same clip properties with no audio (for building converted copies)
+    fn clone_metadata(&self) -> Self {
+        Self {
+            samples: Vec::new(),
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            duration_seconds: self.duration_seconds,
+            file_path: self.file_path.clone(),
+            stream: None,
+        }
     }
 }
 
@@ -68,6 +158,39 @@ pub fn load_audio_file<P: AsRef<Path>>(path: P) -> Result<AudioClip> {
     load_audio_file_symphonia(path_ref)
 }
 
+/// Open an audio file for on-demand playback without decoding it into memory
+///
+/// Meant for long recordings: memory stays at a few cached windows per clip instead
+/// of the whole file, at the cost of disk reads on playback (see `ClipStream` for the
+/// seeking cost). Only 48 kHz WAV files stream; other files (compressed formats or
+/// other sample rates) are fully decoded by `load_audio_file`. Streamed clips are
+/// never time-stretched or transposed, since that would render them into memory.
+pub fn load_audio_file_streaming<P: AsRef<Path>>(path: P) -> Result<AudioClip> {
+    let path_ref = path.as_ref();
+    let is_wav = path_ref
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wav") || ext.eq_ignore_ascii_case("wave"));
+    if is_wav {
+        match ClipStream::open(path_ref) {
+            Ok(stream) => {
+                let frame_count = stream.frame_count();
+                return Ok(AudioClip {
+                    samples: Vec::new(),
+                    channels: stream.channels(),
+                    sample_rate: stream.sample_rate(),
+                    duration_seconds: frame_count as f64 / f64::from(stream.sample_rate()),
+                    file_path: path_ref.to_string_lossy().to_string(),
+                    stream: Some(Arc::new(stream)),
+                });
+            }
+            Err(e) => eprintln!("[ClipStream] can't stream: {e}, loading into memory"),
+        }
+    }
+
+    load_audio_file(path_ref)
+}
+
 /// Raw preview clip — stores raw bytes, converts samples on-the-fly during playback.
 /// This avoids the expensive upfront f32 conversion for large files.
 pub struct RawPreviewClip {
@@ -90,108 +213,36 @@ impl RawPreviewClip {
         }
         let bps = (self.bits_per_sample / 8) as usize;
         let offset = (frame * self.channels + channel) * bps;
-
-        match (self.audio_format, self.bits_per_sample) {
-            (1, 16) => {
-                let s = i16::from_le_bytes([self.raw_data[offset], self.raw_data[offset + 1]]);
-                f32::from(s) / 32768.0
-            }
-            (1, 24) => {
-                let s = i32::from_le_bytes([0, self.raw_data[offset], self.raw_data[offset + 1], self.raw_data[offset + 2]]) >> 8;
-                s as f32 / 8_388_608.0
-            }
-            (3, 32) => {
-                f32::from_le_bytes([self.raw_data[offset], self.raw_data[offset + 1], self.raw_data[offset + 2], self.raw_data[offset + 3]])
-            }
-            (1, 32) => {
-                let s = i32::from_le_bytes([self.raw_data[offset], self.raw_data[offset + 1], self.raw_data[offset + 2], self.raw_data[offset + 3]]);
-                s as f32 / 2_147_483_648.0
-            }
-            _ => 0.0,
-        }
+        decode_pcm_sample(&self.raw_data[offset..offset + bps], self.audio_format, self.bits_per_sample)
     }
 }
 
-/// Load a WAV file for preview — stores raw bytes, no f32 conversion, no resampling.
-/// Returns nearly instantly for any size WAV.
-pub fn load_wav_for_preview<P: AsRef<Path>>(path: P) -> Result<RawPreviewClip> {
-    let path_ref = path.as_ref();
-    let mut file = std::fs::File::open(path_ref)
-        .context("Failed to open WAV file")?;
-
-    // Read RIFF header
-    let mut riff_header = [0u8; 12];
-    file.read_exact(&mut riff_header)?;
-    if &riff_header[0..4] != b"RIFF" || &riff_header[8..12] != b"WAVE" {
-        anyhow::bail!("Not a valid WAV file");
-    }
-
-    let mut sample_rate: u32 = 0;
-    let mut channels: u16 = 0;
-    let mut bits_per_sample: u16 = 0;
-    let mut audio_format: u16 = 0;
-    let mut data_offset: u64 = 0;
-    let mut data_size: u32 = 0;
-
-    loop {
-        let mut chunk_header = [0u8; 8];
-        if file.read_exact(&mut chunk_header).is_err() { break; }
-        let chunk_id = &chunk_header[0..4];
-        let chunk_size = u32::from_le_bytes([chunk_header[4], chunk_header[5], chunk_header[6], chunk_header[7]]);
+/// Layout of a WAV file's sample data (see `read_wav_format`)
+pub(crate) struct WavFormat {
+    /// 1 = PCM, 3 = IEEE float
+    pub audio_format: u16,
+    pub channels: usize,
+    pub sample_rate: u32,
+    pub bits_per_sample: u16,
+    /// Byte offset of the first sample in the file
+    pub data_offset: u64,
+    /// Size of the sample data in bytes
+    pub data_size: u32,
+}
 
-        if chunk_id == b"fmt " {
-            let mut fmt_data = vec![0u8; chunk_size as usize];
-            file.read_exact(&mut fmt_data)?;
-            audio_format = u16::from_le_bytes([fmt_data[0], fmt_data[1]]);
-            channels = u16::from_le_bytes([fmt_data[2], fmt_data[3]]);
-            sample_rate = u32::from_le_bytes([fmt_data[4], fmt_data[5], fmt_data[6], fmt_data[7]]);
-            bits_per_sample = u16::from_le_bytes([fmt_data[14], fmt_data[15]]);
-        } else if chunk_id == b"data" {
-            data_offset = file.stream_position()?;
-            data_size = chunk_size;
-            break;
-        } else {
-            let skip = (chunk_size + 1) & !1;
-            file.seek(SeekFrom::Current(i64::from(skip)))?;
-        }
+impl WavFormat {
+    pub fn bytes_per_sample(&self) -> usize {
+        (self.bits_per_sample / 8) as usize
     }
 
-    if data_offset == 0 || data_size == 0 { anyhow::bail!("WAV missing data chunk"); }
-    if audio_format != 1 && audio_format != 3 { anyhow::bail!("Unsupported format: {audio_format}"); }
-    let ch = channels as usize;
-    if ch == 0 || ch > 2 { anyhow::bail!("Unsupported channels: {ch}"); }
-    if !matches!((audio_format, bits_per_sample), (1, 16 | 24 | 32) | (3, 32)) {
-        anyhow::bail!("Unsupported bit depth: {bits_per_sample}");
+    pub fn frame_count(&self) -> usize {
+        self.data_size as usize / (self.bytes_per_sample() * self.channels)
     }
-
-    // Just read raw bytes — no conversion
-    file.seek(SeekFrom::Start(data_offset))?;
-    let mut raw_data = vec![0u8; data_size as usize];
-    file.read_exact(&mut raw_data)?;
-
-    let bps = (bits_per_sample / 8) as usize;
-    let total_samples = raw_data.len() / bps;
-    let frame_count = total_samples / ch;
-    let duration_seconds = frame_count as f64 / f64::from(sample_rate);
-
-    Ok(RawPreviewClip {
-        raw_data,
-        channels: ch,
-        sample_rate,
-        bits_per_sample,
-        audio_format,
-        frame_count,
-        duration_seconds,
-        file_path: path_ref.to_string_lossy().to_string(),
-    })
 }
 
-/// Fast WAV loader — reads raw PCM directly from the RIFF structure.
-/// Supports 16-bit, 24-bit, and 32-bit float PCM.
-fn load_wav_fast(path: &Path) -> Result<AudioClip> {
-    let mut file = std::fs::File::open(path)
-        .context("Failed to open WAV file")?;
-
+/// Read a WAV file's RIFF header and find its fmt and data chunks
+/// Only mono/stereo 16/24/32-bit PCM and 32-bit float are supported
+pub(crate) fn read_wav_format(file: &mut std::fs::File) -> Result<WavFormat> {
     // Read RIFF header (12 bytes)
     let mut riff_header = [0u8; 12];
     file.read_exact(&mut riff_header)?;
@@ -249,48 +300,91 @@ fn load_wav_fast(path: &Path) -> Result<AudioClip> {
     if ch == 0 || ch > 2 {
         anyhow::bail!("Unsupported channel count: {ch}");
     }
+    if !matches!((audio_format, bits_per_sample), (1, 16 | 24 | 32) | (3, 32)) {
+        anyhow::bail!("Unsupported WAV bit depth: {bits_per_sample}");
+    }
 
-    // Read raw data
-    file.seek(SeekFrom::Start(data_offset))?;
-    let mut raw_data = vec![0u8; data_size as usize];
-    file.read_exact(&mut raw_data)?;
+    Ok(WavFormat { audio_format, channels: ch, sample_rate, bits_per_sample, data_offset, data_size })
+}
 
-    // Convert to f32 based on bit depth
-    let bytes_per_sample = (bits_per_sample / 8) as usize;
-    let total_samples = raw_data.len() / bytes_per_sample;
-    let mut samples = Vec::with_capacity(total_samples);
+/// Convert raw WAV sample data to interleaved f32 samples
+pub(crate) fn pcm_to_f32(raw_data: &[u8], format: &WavFormat) -> Vec<f32> {
+    raw_data
+        .chunks_exact(format.bytes_per_sample())
+        .map(|bytes| decode_pcm_sample(bytes, format.audio_format, format.bits_per_sample))
+        .collect()
+}
 
+/// Convert one little-endian WAV sample to f32 (0.0 for unsupported formats)
+#[inline]
+pub(crate) fn decode_pcm_sample(bytes: &[u8], audio_format: u16, bits_per_sample: u16) -> f32 {
     match (audio_format, bits_per_sample) {
         (1, 16) => {
             // PCM 16-bit signed
-            for chunk in raw_data.chunks_exact(2) {
-                let s = i16::from_le_bytes([chunk[0], chunk[1]]);
-                samples.push(f32::from(s) / 32768.0);
-            }
+            let s = i16::from_le_bytes([bytes[0], bytes[1]]);
+            f32::from(s) / 32768.0
         }
         (1, 24) => {
             // PCM 24-bit signed
-            for chunk in raw_data.chunks_exact(3) {
-                let s = i32::from_le_bytes([0, chunk[0], chunk[1], chunk[2]]) >> 8;
-                samples.push(s as f32 / 8_388_608.0);
-            }
+            let s = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+            s as f32 / 8_388_608.0
         }
         (3, 32) => {
             // IEEE float 32-bit
-            for chunk in raw_data.chunks_exact(4) {
-                let s = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
-                samples.push(s);
-            }
+            f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
         }
         (1, 32) => {
             // PCM 32-bit signed
-            for chunk in raw_data.chunks_exact(4) {
-                let s = i32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
-                samples.push(s as f32 / 2_147_483_648.0);
-            }
+            let s = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            s as f32 / 2_147_483_648.0
         }
-        _ => anyhow::bail!("Unsupported WAV bit depth: {bits_per_sample}"),
+        _ => 0.0,
     }
+}
+
+/// Load a WAV file for preview — stores raw bytes, no f32 conversion, no resampling.
+/// Returns nearly instantly for any size WAV.
+pub fn load_wav_for_preview<P: AsRef<Path>>(path: P) -> Result<RawPreviewClip> {
+    let path_ref = path.as_ref();
+    let mut file = std::fs::File::open(path_ref)
+        .context("Failed to open WAV file")?;
+    let format = read_wav_format(&mut file)?;
+
+    // Just read raw bytes — no conversion
+    file.seek(SeekFrom::Start(format.data_offset))?;
+    let mut raw_data = vec![0u8; format.data_size as usize];
+    file.read_exact(&mut raw_data)?;
+
+    let frame_count = format.frame_count();
+    let duration_seconds = frame_count as f64 / f64::from(format.sample_rate);
+
+    Ok(RawPreviewClip {
+        raw_data,
+        channels: format.channels,
+        sample_rate: format.sample_rate,
+        bits_per_sample: format.bits_per_sample,
+        audio_format: format.audio_format,
+        frame_count,
+        duration_seconds,
+        file_path: path_ref.to_string_lossy().to_string(),
+    })
+}
+
+/// Fast WAV loader — reads raw PCM directly from the RIFF structure.
+/// Supports 16-bit, 24-bit, and 32-bit float PCM.
+fn load_wav_fast(path: &Path) -> Result<AudioClip> {
+    let mut file = std::fs::File::open(path)
+        .context("Failed to open WAV file")?;
+    let format = read_wav_format(&mut file)?;
+    let (sample_rate, ch) = (format.sample_rate, format.channels);
+
+    // Read raw data
+    file.seek(SeekFrom::Start(format.data_offset))?;
+    let mut raw_data = vec![0u8; format.data_size as usize];
+    file.read_exact(&mut raw_data)?;
+
+    // Convert to f32 based on bit depth
+    let samples = pcm_to_f32(&raw_data, &format);
 
     // Resample if needed
     let final_samples = if sample_rate == TARGET_SAMPLE_RATE {
@@ -308,6 +402,7 @@ fn load_wav_fast(path: &Path) -> Result<AudioClip> {
         sample_rate: TARGET_SAMPLE_RATE,
         duration_seconds,
         file_path: path.to_string_lossy().to_string(),
+        stream: None,
     })
 }
 
@@ -516,6 +611,7 @@ pub fn load_audio_file_partial<P: AsRef<Path>>(path: P, max_seconds: f64) -> Res
         sample_rate: source_sample_rate,
         duration_seconds,
         file_path: path_ref.to_string_lossy().to_string(),
+        stream: None,
     })
 }
 
@@ -614,6 +710,7 @@ fn load_audio_file_symphonia(path_ref: &Path) -> Result<AudioClip> {
         sample_rate: TARGET_SAMPLE_RATE,
         duration_seconds,
         file_path: path_ref.to_string_lossy().to_string(),
+        stream: None,
     })
 }
 
@@ -796,6 +893,7 @@ mod tests {
             sample_rate: 48000,
             duration_seconds: 3.0 / 48000.0,
             file_path: "test.wav".to_string(),
+            stream: None,
         };
 
         assert_eq!(clip.frame_count(), 3);
@@ -806,6 +904,65 @@ mod tests {
         assert_eq!(clip.get_sample(3, 0), None); // Out of bounds
     }
 
+    #[test]
+    fn test_peak_spans_window_boundaries() {
+        let frames = STREAM_WINDOW_FRAMES * 2 + 5;
+        let mut samples = vec![0.1_f32; frames];
+        samples[STREAM_WINDOW_FRAMES - 1] = -0.4;
+        samples[STREAM_WINDOW_FRAMES * 2 + 2] = 0.7;
+        let clip = AudioClip {
+            samples,
+            channels: 1,
+            sample_rate: 48000,
+            duration_seconds: frames as f64 / 48000.0,
+            file_path: String::new(),
+            stream: None,
+        };
+
+        assert!((clip.peak(0, frames) - 0.7).abs() < 1e-6);
+        assert!((clip.peak(0, STREAM_WINDOW_FRAMES * 2) - 0.4).abs() < 1e-6);
+        assert!((clip.peak(STREAM_WINDOW_FRAMES, STREAM_WINDOW_FRAMES) - 0.1).abs() < 1e-6);
+        assert!((clip.peak(frames - 1, 100) - 0.1).abs() < 1e-6);
+        assert!(clip.peak(frames, 10).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_mono_to_stereo_duplicates_channel() {
+        let clip = AudioClip {
+            samples: vec![0.1, -0.2, 0.3],
+            channels: 1,
+            sample_rate: 48000,
+            duration_seconds: 3.0 / 48000.0,
+            file_path: "mono.wav".to_string(),
+            stream: None,
+        };
+
+        let stereo = clip.to_stereo();
+        assert_eq!(stereo.channels, 2);
+        assert_eq!(stereo.samples, vec![0.1, 0.1, -0.2, -0.2, 0.3, 0.3]);
+        assert_eq!(stereo.frame_count(), clip.frame_count());
+        assert_eq!(stereo.file_path, "mono.wav");
+        assert_eq!(stereo.to_mono().samples, clip.samples);
+    }
+
+    #[test]
+    fn test_stereo_to_mono_averages_channels() {
+        let clip = AudioClip {
+            samples: vec![1.0, 0.0, 0.5, -0.5, -0.25, -0.75],
+            channels: 2,
+            sample_rate: 48000,
+            duration_seconds: 3.0 / 48000.0,
+            file_path: "stereo.wav".to_string(),
+            stream: None,
+        };
+
+        let mono = clip.to_mono();
+        assert_eq!(mono.channels, 1);
+        assert_eq!(mono.samples, vec![0.5, 0.0, -0.5]);
+        assert!((mono.duration_seconds - clip.duration_seconds).abs() < f64::EPSILON);
+        assert_eq!(clip.to_stereo().samples, clip.samples);
+    }
+
     #[test]
     fn test_no_resample_when_rates_match() {
         let input = vec![1.0, 2.0, 3.0, 4.0];
@@ -1,6 +1,9 @@
 /// Audio device selection, buffer size management, and latency control
-use super::{AudioGraph, BufferSizePreset};
+use super::mixer::{resolve_bus_routing, resolve_latency_compensation, TrackSnapshot};
+use super::{AudioDeviceStatus, AudioGraph, BufferSizePreset, MonitorMode};
 use crate::audio_file::TARGET_SAMPLE_RATE;
+use crate::effects::Effect;
+use crate::track::TrackType;
 use std::sync::atomic::Ordering;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -56,6 +59,62 @@ impl AudioGraph {
         (buffer_samples, input_latency_ms, output_latency_ms, total_roundtrip_ms)
     }
 
+    /// Plugin delay compensation latency in frames: how far playback runs behind the
+    /// timeline. The most latent track path (FX chains through their groups), plus
+    /// the master FX chain and the master limiter's lookahead.
+    pub fn get_reported_latency(&self) -> usize {
+        let (mut tracks, master_fx_chain) = {
+            let tm = self.track_manager.lock();
+            let mut tracks = Vec::new();
+            let mut master_fx_chain = Vec::new();
+            for track_arc in tm.get_all_tracks() {
+                let track = track_arc.lock();
+                if track.track_type == TrackType::Master {
+                    master_fx_chain.clone_from(&track.fx_chain);
+                } else {
+                    tracks.push(TrackSnapshot::new(&track));
+                }
+            }
+            (tracks, master_fx_chain)
+        };
+        resolve_bus_routing(&mut tracks);
+        let effect_mgr = self.effect_manager.lock();
+        let mix_latency = resolve_latency_compensation(&mut tracks, &effect_mgr);
+        mix_latency + effect_mgr.chain_latency(&master_fx_chain) + self.master_limiter.lock().latency_samples()
+    }
+
+    // --- Input Monitoring and Recording Latency Compensation ---
+
+    /// Set how input is monitored while recording
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_monitor_mode(&self, mode: MonitorMode) {
+        self.monitor_mode.store(mode as u8, Ordering::Relaxed);
+    }
+
+    /// Get how input is monitored while recording
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn get_monitor_mode(&self) -> MonitorMode {
+        MonitorMode::from_u8(self.monitor_mode.load(Ordering::Relaxed)).unwrap_or(MonitorMode::Software)
+    }
+
+    /// Samples recorded takes are moved earlier by when recording stops
+    ///
+    /// Input is captured one input/output round trip after the performer heard the
+    /// playback they played along to, whatever the monitor mode, so takes land late
+    /// by that much. Each successful latency test stores its measured round trip
+    /// (the reported device latencies are only estimates); 0 until then.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn recording_latency_compensation_samples(&self) -> u64 {
+        self.recording_latency_compensation_samples.load(Ordering::SeqCst)
+    }
+
+    /// Override the recording latency compensation (samples); the next successful
+    /// latency test replaces it
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_recording_latency_compensation_samples(&self, samples: u64) {
+        self.recording_latency_compensation_samples.store(samples, Ordering::SeqCst);
+    }
+
     /// Query hardware audio latency from `CoreAudio` device (macOS only)
     /// Updates the `hardware_input_latency_ms` and `hardware_output_latency_ms` fields
     #[cfg(target_os = "macos")]
@@ -214,6 +273,7 @@ impl AudioGraph {
         stream.play()?;
 
         self.stream = Some(stream);
+        self.output_device_lost.store(false, Ordering::SeqCst);
         eprintln!("✅ [AudioGraph] Audio stream restarted");
 
         // Re-query hardware latency after stream change
@@ -336,9 +396,76 @@ impl AudioGraph {
         Ok(())
     }
 
+    /// State of the output stream - native only
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn get_audio_device_status(&self) -> AudioDeviceStatus {
+        if self.output_device_lost.load(Ordering::SeqCst) {
+            AudioDeviceStatus::Disconnected
+        } else if self.stream.is_some() {
+            AudioDeviceStatus::Running
+        } else {
+            AudioDeviceStatus::NoStream
+        }
+    }
+
+    /// Whether the output stream was lost or rebuilt since the last call (clears the flag) - native only
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn take_audio_device_changed(&self) -> bool {
+        self.output_device_changed.swap(false, Ordering::SeqCst)
+    }
+
+    /// Rebuild the output stream after a device change - native only
+    ///
+    /// Called periodically by the device watcher. A lost stream is rebuilt on the
+    /// selected device, or the default one while the selected device is missing; a
+    /// stream running on the default moves back once the selected device reappears.
+    /// Returns true when the stream was rebuilt.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn check_audio_device(&mut self) -> bool {
+        let lost = self.output_device_lost.load(Ordering::SeqCst);
+        let selected_returned = self.output_on_fallback.load(Ordering::SeqCst)
+            && self.get_selected_output_device().is_some_and(|name| output_device_present(&name));
+        if !lost && !selected_returned {
+            return false;
+        }
+        if lost && cpal::default_host().default_output_device().is_none() {
+            return false; // Nothing to play on yet
+        }
+
+        eprintln!("🔌 [AudioGraph] Output device changed, rebuilding the audio stream...");
+        match self.restart_audio_stream() {
+            Ok(()) => {
+                self.output_device_changed.store(true, Ordering::SeqCst);
+                true
+            }
+            Err(e) => {
+                // Stay marked lost so the next check retries
+                self.output_device_lost.store(true, Ordering::SeqCst);
+                eprintln!("⚠️ [AudioGraph] Failed to rebuild the audio stream: {e}");
+                false
+            }
+        }
+    }
+
     /// Get the currently selected output device name (None = system default)
     pub fn get_selected_output_device(&self) -> Option<String> {
         self.selected_output_device.lock()
             .clone()
     }
 }
+
+/// Whether an output device with this name (as listed by `get_output_devices`) is connected
+#[cfg(not(target_arch = "wasm32"))]
+fn output_device_present(name: &str) -> bool {
+    let has_device = |devices: Result<cpal::OutputDevices<cpal::Devices>, cpal::DevicesError>, name: &str| {
+        devices.is_ok_and(|mut devices| devices.any(|d| d.name().ok().as_deref() == Some(name)))
+    };
+
+    #[cfg(all(windows, feature = "asio"))]
+    if let Some(asio_name) = name.strip_prefix("[ASIO] ") {
+        return cpal::host_from_id(cpal::HostId::Asio)
+            .is_ok_and(|host| has_device(host.output_devices(), asio_name));
+    }
+
+    has_device(cpal::default_host().output_devices(), name)
+}
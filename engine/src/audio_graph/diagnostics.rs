@@ -0,0 +1,140 @@
+/// Engine diagnostics snapshot for bug reports and support
+use super::AudioGraph;
+use crate::audio_file::TARGET_SAMPLE_RATE;
+use serde::Serialize;
+
+/// Point-in-time summary of engine state, serialized to JSON by the API
+#[derive(Serialize, Debug, Clone)]
+pub struct EngineDiagnostics {
+    /// Engine crate version
+    pub engine_version: String,
+    /// Engine sample rate in Hz
+    pub sample_rate: u32,
+    /// Preferred buffer size in samples
+    pub buffer_size: u32,
+    /// Buffer size reported by the last audio callback (0 = no callback yet)
+    pub actual_buffer_size: u32,
+    /// Transport state: "stopped", "playing" or "paused"
+    pub transport_state: String,
+    /// Selected output device (None = system default)
+    pub output_device: Option<String>,
+    /// Selected input device (None = no input device)
+    pub input_device: Option<String>,
+    /// Whether an output stream is running
+    pub output_stream_active: bool,
+    /// Number of tracks, including master
+    pub track_count: usize,
+    /// Number of effect instances across all tracks
+    pub effect_count: usize,
+    /// Number of audio clips across all tracks
+    pub audio_clip_count: usize,
+    /// Number of MIDI clips across all tracks
+    pub midi_clip_count: usize,
+    /// Audio callback DSP load in percent (None = not measured)
+    pub dsp_load_percent: Option<f32>,
+    /// Audio dropouts since startup or the last reset (None = not measured)
+    pub xrun_count: Option<u64>,
+    /// Compile-time feature flags enabled in this build
+    pub features: Vec<String>,
+}
+
+impl AudioGraph {
+    /// Collect a diagnostics snapshot.
+    /// Each manager lock is held only long enough to read counts, so this
+    /// is safe to call from any thread without stalling the audio callback.
+    pub fn get_diagnostics(&self) -> EngineDiagnostics {
+        let (track_count, audio_clip_count, midi_clip_count) = {
+            let tm = self.track_manager.lock();
+            let tracks = tm.get_all_tracks();
+            let mut audio_clips = 0;
+            let mut midi_clips = 0;
+            for track_arc in &tracks {
+                let track = track_arc.lock();
+                audio_clips += track.audio_clips.len();
+                midi_clips += track.midi_clips.len();
+            }
+            (tracks.len(), audio_clips, midi_clips)
+        };
+
+        let effect_count = self.effect_manager.lock().get_all_effect_ids().len();
+
+        let transport_state = match self.get_state() {
+            super::TransportState::Stopped => "stopped",
+            super::TransportState::Playing => "playing",
+            super::TransportState::Paused => "paused",
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let (input_device, output_stream_active) = {
+            let input_device = self.input_manager.try_lock().and_then(|input_mgr| {
+                let index = input_mgr.get_selected_device_index()?;
+                input_mgr.get_devices().get(index).map(|d| d.name.clone())
+            });
+            (input_device, self.stream.is_some())
+        };
+        #[cfg(target_arch = "wasm32")]
+        let (input_device, output_stream_active) = (None, false);
+
+        let mut features = Vec::new();
+        if cfg!(feature = "vst3") {
+            features.push("vst3".to_string());
+        }
+        if cfg!(feature = "midi") {
+            features.push("midi".to_string());
+        }
+        if cfg!(feature = "native-audio") {
+            features.push("native-audio".to_string());
+        }
+        if cfg!(feature = "asio") {
+            features.push("asio".to_string());
+        }
+
+        EngineDiagnostics {
+            engine_version: env!("CARGO_PKG_VERSION").to_string(),
+            sample_rate: TARGET_SAMPLE_RATE,
+            buffer_size: self.get_buffer_size_preset().samples(),
+            actual_buffer_size: self.get_actual_buffer_size(),
+            transport_state: transport_state.to_string(),
+            output_device: self.get_selected_output_device(),
+            input_device,
+            output_stream_active,
+            track_count,
+            effect_count,
+            audio_clip_count,
+            midi_clip_count,
+            dsp_load_percent: self.dsp_load.is_measured().then(|| self.dsp_load.load_percent()),
+            xrun_count: self.dsp_load.is_measured().then(|| self.dsp_load.xrun_count()),
+            features,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audio_graph::AudioGraph;
+    use crate::track::TrackType;
+
+    #[test]
+    fn test_diagnostics_json_has_expected_fields() {
+        let graph = AudioGraph::new_headless().unwrap();
+        {
+            let mut tm = graph.track_manager.lock();
+            tm.create_track(TrackType::Audio, "Audio 1".to_string());
+            tm.create_track(TrackType::Midi, "MIDI 1".to_string());
+        }
+
+        let json = serde_json::to_string(&graph.get_diagnostics()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        for field in [
+            "engine_version", "sample_rate", "buffer_size", "actual_buffer_size",
+            "transport_state", "output_device", "input_device", "track_count",
+            "effect_count", "audio_clip_count", "midi_clip_count", "dsp_load_percent",
+            "xrun_count", "features",
+        ] {
+            assert!(value.get(field).is_some(), "missing field {field}");
+        }
+        assert_eq!(value["track_count"], 3); // master + 2
+        assert_eq!(value["transport_state"], "stopped");
+    }
+}
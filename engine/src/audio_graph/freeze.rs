@@ -0,0 +1,156 @@
+/// Track freeze: render a track to audio and swap it in for its clips, instrument and FX
+use super::{AudioGraph, TransportState};
+use crate::audio_file::{AudioClip, TARGET_SAMPLE_RATE};
+use crate::tempo_map::{timeline_seconds_to_beats, TempoMap};
+use crate::track::{ClipId, TimelineClip, TrackId, TrackType};
+use std::sync::Arc;
+
+impl AudioGraph {
+    /// Render a track to an audio clip that replaces its clips, instrument and FX
+    ///
+    /// The render covers the whole project and is taken before volume/pan, which
+    /// stay live. The render runs in real time and is retimed through the tempo
+    /// map, so the clip lines up with the timeline across tempo changes.
+    /// Returns the new clip's ID and audio.
+    pub fn freeze_track(&self, track_id: TrackId) -> Result<(ClipId, Arc<AudioClip>), String> {
+        if self.get_state() == TransportState::Playing {
+            return Err("Cannot freeze a track during playback".to_string());
+        }
+
+        {
+            let track_manager = self.track_manager.lock();
+            let track_arc = track_manager
+                .get_track(track_id)
+                .ok_or_else(|| format!("Track {track_id} not found"))?;
+            let track = track_arc.lock();
+            if track.track_type == TrackType::Master {
+                return Err("Cannot freeze the master track".to_string());
+            }
+            if track.frozen.is_some() {
+                return Err(format!("Track {track_id} is already frozen"));
+            }
+        }
+
+        // The project length is in timeline seconds; the render needs real seconds
+        let duration_seconds = self.calculate_project_duration();
+        let tempo_map = self.recorder.get_tempo_map();
+        let sample_rate = f64::from(TARGET_SAMPLE_RATE);
+        let real_seconds = tempo_map.sample_at_timeline_seconds(duration_seconds) as f64 / sample_rate;
+
+        self.track_synth_manager.lock().all_notes_off(track_id);
+        let rendered = self.render_track_pre_fader(track_id, real_seconds);
+        // Don't let the render's trailing voices leak into live playback
+        self.track_synth_manager.lock().all_notes_off(track_id);
+
+        let timeline_frames = (duration_seconds * sample_rate) as usize;
+        let samples = retime_to_timeline(&rendered, &tempo_map, timeline_frames);
+        let clip = Arc::new(AudioClip {
+            duration_seconds: timeline_frames as f64 / sample_rate,
+            samples,
+            channels: 2,
+            sample_rate: TARGET_SAMPLE_RATE,
+            file_path: String::new(),
+            stream: None,
+        });
+
+        let clip_id = {
+            let mut next_id = self.next_clip_id.lock();
+            let id = *next_id;
+            *next_id += 1;
+            id
+        };
+
+        // Already in timeline time, so the clip plays unwarped from the start
+        let rendered = TimelineClip::new(clip_id, clip.clone(), 0.0);
+
+        {
+            let track_manager = self.track_manager.lock();
+            let track_arc = track_manager
+                .get_track(track_id)
+                .ok_or_else(|| format!("Track {track_id} not found"))?;
+            track_arc.lock().freeze(rendered)?;
+        }
+        self.track_synth_manager.lock().set_instrument_frozen(track_id, true);
+
+        eprintln!("🧊 [AudioGraph] Track {track_id} frozen to clip {clip_id} ({duration_seconds:.2}s)");
+        Ok((clip_id, clip))
+    }
+
+    /// Restore a frozen track's clips, instrument and FX
+    /// Returns the ID of the rendered clip that was removed
+    pub fn unfreeze_track(&self, track_id: TrackId) -> Result<ClipId, String> {
+        let clip_id = {
+            let track_manager = self.track_manager.lock();
+            let track_arc = track_manager
+                .get_track(track_id)
+                .ok_or_else(|| format!("Track {track_id} not found"))?;
+            let mut track = track_arc.lock();
+            track
+                .unfreeze()
+                .ok_or_else(|| format!("Track {track_id} is not frozen"))?
+        };
+        self.track_synth_manager.lock().set_instrument_frozen(track_id, false);
+
+        eprintln!("🧊 [AudioGraph] Track {track_id} unfrozen (removed clip {clip_id})");
+        Ok(clip_id)
+    }
+}
+
+/// Resample a real-time stereo render onto the timeline
+///
+/// Frame `k` of the result is the render at timeline second `k / TARGET_SAMPLE_RATE`,
+/// found through the tempo map and linearly interpolated between rendered frames.
+fn retime_to_timeline(rendered: &[f32], tempo_map: &TempoMap, timeline_frames: usize) -> Vec<f32> {
+    let rendered_frames = rendered.len() / 2;
+    let sample_rate = f64::from(TARGET_SAMPLE_RATE);
+    let mut samples = Vec::with_capacity(timeline_frames * 2);
+    for frame in 0..timeline_frames {
+        let position = tempo_map.sample_at_beat(timeline_seconds_to_beats(frame as f64 / sample_rate));
+        let index = position as usize;
+        let fraction = (position - index as f64) as f32;
+        for channel in 0..2 {
+            let at = |i: usize| if i < rendered_frames { rendered[i * 2 + channel] } else { 0.0 };
+            samples.push(at(index) + (at(index + 1) - at(index)) * fraction);
+        }
+    }
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_freeze_follows_tempo_changes() {
+        let graph = AudioGraph::new_headless().unwrap();
+        let track_id = graph.track_manager.lock().create_track(TrackType::Audio, "Audio".to_string());
+        let sample_rate = f64::from(TARGET_SAMPLE_RATE);
+        let clip = Arc::new(AudioClip {
+            samples: vec![0.1; TARGET_SAMPLE_RATE as usize * 2],
+            channels: 2,
+            sample_rate: TARGET_SAMPLE_RATE,
+            duration_seconds: 1.0,
+            file_path: String::new(),
+            stream: None,
+        });
+        graph.add_clip_to_track(track_id, clip, 3.0).unwrap();
+        // 120 BPM for the first 2 timeline seconds, then half speed
+        graph.recorder.set_tempo(120.0);
+        graph.recorder.add_tempo_change(4.0, 60.0);
+
+        let project_duration = graph.calculate_project_duration();
+        let (_, frozen) = graph.freeze_track(track_id).unwrap();
+
+        // The frozen clip is as long as the timeline, and its audio sits where the clip was
+        assert!((frozen.duration_seconds - project_duration).abs() < 1e-3);
+        let level_at = |seconds: f64| frozen.get_sample((seconds * sample_rate) as usize, 0).unwrap();
+        assert!(level_at(2.95).abs() < 1e-6, "audio before the clip: {}", level_at(2.95));
+        assert!((level_at(3.05) - 0.1).abs() < 1e-3, "clip start: {}", level_at(3.05));
+        assert!((level_at(3.95) - 0.1).abs() < 1e-3, "clip end: {}", level_at(3.95));
+        assert!(level_at(4.05).abs() < 1e-6, "audio after the clip: {}", level_at(4.05));
+
+        let track_arc = graph.track_manager.lock().get_track(track_id).unwrap();
+        let track = track_arc.lock();
+        assert!(!track.audio_clips[0].warp_enabled);
+    }
+}
@@ -0,0 +1,1508 @@
+/// Per-track mixing shared by the real-time callback and offline renders
+use super::{interpolate_automation_gain, interpolate_automation_pan_gains};
+use super::renderer::{feed_sidechain, render_audio_clip_sample, sidechain_level};
+use crate::audio_file::TARGET_SAMPLE_RATE;
+use crate::effects::{EffectManager, LatencyDelay};
+use crate::midi::MidiEventType;
+use crate::synth::TrackSynthManager;
+use crate::track::{apply_channel_utilities, AutomationPoint, MeteringPoint, Send, TimelineClip, TimelineMidiClip, Track, TrackId, TrackType};
+use std::collections::HashMap;
+
+/// Audio routed to bus tracks (groups and returns) this frame, summed per target track
+pub(super) type BusInputs = HashMap<TrackId, (f32, f32)>;
+
+/// Track snapshot data extracted from locked tracks for lock-free audio processing.
+pub(super) struct TrackSnapshot {
+    pub id: u64,
+    pub audio_clips: Vec<TimelineClip>,
+    pub midi_clips: Vec<TimelineMidiClip>,
+    pub volume_gain: f32, // Static volume (used when no automation)
+    pub pan_left: f32,
+    pub pan_right: f32,
+    pub phase_invert: bool,
+    pub mono_sum: bool,
+    pub muted: bool,
+    pub soloed: bool,
+    pub solo_safe: bool,
+    /// Audible under solo: soloed, inside a soloed group or a group holding a
+    /// soloed track (set by `resolve_bus_routing`)
+    solo_path: bool,
+    pub fx_chain: Vec<u64>,
+    pub sends: Vec<Send>,
+    /// Return track: mixes the sends it receives (see `BusInputs`)
+    pub is_return: bool,
+    /// Group track: mixes the tracks routed into it (see `BusInputs`)
+    pub is_group: bool,
+    /// Group the track belongs to, as set on the track
+    pub parent_group: Option<TrackId>,
+    /// Group the track's output is summed into (None = master), set by `resolve_bus_routing`
+    pub output_group: Option<TrackId>,
+    /// Number of groups above this track, set by `resolve_bus_routing`
+    group_depth: usize,
+    /// Latency the track's output carries (its FX chain plus, for a group, its
+    /// latest input), set by `resolve_latency_compensation`
+    output_latency: usize,
+    /// Frames the track's output is held back to line up with the rest of its bus,
+    /// set by `resolve_latency_compensation`
+    pub compensation_delay: usize,
+    pub layer_synth: bool,
+    pub volume_automation: Vec<AutomationPoint>, // For per-frame interpolation
+    pub pan_automation: Vec<AutomationPoint>,
+    pub armed: bool,
+    pub input_monitoring: bool,
+    pub monitor_through_fx: bool,
+    pub input_channel: u32,
+    pub input_channel_count: u32,
+    pub input_gain: f32,
+    pub is_audio_track: bool,
+    pub monitoring_fade_gain: f64,
+    pub metering_point: MeteringPoint,
+    /// Signal at `metering_point` on the last processed frame
+    pub meter: (f32, f32),
+    /// Last evaluated automation gain and pan gains (see `TrackFrame::refresh_automation`)
+    automation_gain: f32,
+    automation_pan: (f32, f32),
+}
+
+impl TrackSnapshot {
+    pub fn new(track: &Track) -> Self {
+        let (pan_left, pan_right) = track.get_pan_gains();
+        Self {
+            id: track.id,
+            audio_clips: track.audio_clips.clone(),
+            midi_clips: track.midi_clips.clone(),
+            volume_gain: track.get_gain(),
+            pan_left,
+            pan_right,
+            phase_invert: track.phase_invert,
+            mono_sum: track.mono_sum,
+            muted: track.mute,
+            soloed: track.solo,
+            solo_safe: track.solo_safe,
+            solo_path: track.solo,
+            fx_chain: track.fx_chain.clone(),
+            sends: track.sends.clone(),
+            is_return: track.track_type == TrackType::Return,
+            is_group: track.track_type == TrackType::Group,
+            parent_group: track.parent_group,
+            output_group: None,
+            group_depth: 0,
+            output_latency: 0,
+            compensation_delay: 0,
+            layer_synth: track.layer_synth,
+            volume_automation: track.volume_automation.clone(),
+            pan_automation: track.pan_automation.clone(),
+            armed: track.armed,
+            input_monitoring: track.input_monitoring,
+            monitor_through_fx: track.monitor_through_fx,
+            input_channel: track.input_channel,
+            input_channel_count: track.input_channel_count,
+            input_gain: track.get_input_gain(),
+            is_audio_track: track.track_type == TrackType::Audio,
+            monitoring_fade_gain: track.monitoring_fade_gain,
+            metering_point: track.metering_point,
+            meter: (0.0, 0.0),
+            automation_gain: track.get_gain(),
+            automation_pan: (pan_left, pan_right),
+        }
+    }
+
+    /// Mixing order: plain tracks, then groups from the innermost out, then returns
+    fn mix_rank(&self) -> (u8, std::cmp::Reverse<usize>) {
+        let class = if self.is_return { 2 } else { u8::from(self.is_group) };
+        (class, std::cmp::Reverse(self.group_depth))
+    }
+
+    /// Whether mute/solo silences this track
+    /// While anything is soloed, only tracks on a soloed path (see `solo_path`) and
+    /// solo-safe tracks play. Returns are solo-safe by default, so a soloed track
+    /// keeps its reverb; a muted group still silences everything routed into it.
+    pub fn is_silenced(&self, has_solo: bool) -> bool {
+        self.muted || (has_solo && !self.solo_path && !self.solo_safe)
+    }
+
+    /// Master bus volume and pan, applied to the summed mix before the master FX chain.
+    /// Each channel is scaled by its own pan gain (channels are never summed).
+    #[inline]
+    pub fn apply_master_fader(&self, left: f32, right: f32) -> (f32, f32) {
+        (
+            left * self.volume_gain * self.pan_left,
+            right * self.volume_gain * self.pan_right,
+        )
+    }
+}
+
+/// Most device channels captured; channels past this are dropped
+pub const MAX_INPUT_CHANNELS: usize = 32;
+
+/// One frame of live input, one sample per device channel
+/// A mono device is duplicated to two channels so it still records as stereo.
+#[derive(Clone, Copy, Debug)]
+pub struct InputFrame {
+    samples: [f32; MAX_INPUT_CHANNELS],
+    channels: usize,
+}
+
+impl Default for InputFrame {
+    fn default() -> Self {
+        Self::silent()
+    }
+}
+
+impl InputFrame {
+    /// Silent stereo frame (no input device capturing)
+    pub fn silent() -> Self {
+        Self { samples: [0.0; MAX_INPUT_CHANNELS], channels: 2 }
+    }
+
+    /// Frame from one interleaved device frame
+    pub fn from_samples(device_frame: &[f32]) -> Self {
+        let mut frame = Self::silent();
+        let channels = device_frame.len().min(MAX_INPUT_CHANNELS);
+        frame.samples[..channels].copy_from_slice(&device_frame[..channels]);
+        if channels == 1 {
+            frame.samples[1] = frame.samples[0];
+        }
+        frame.channels = channels.max(2);
+        frame
+    }
+
+    /// Number of channels in the frame (at least 2)
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Sample on `channel` (0-based); channels the device doesn't have are silent
+    pub fn channel(&self, channel: usize) -> f32 {
+        if channel < self.channels { self.samples[channel] } else { 0.0 }
+    }
+
+    /// All channels, in device order
+    pub fn as_slice(&self) -> &[f32] {
+        &self.samples[..self.channels]
+    }
+
+    /// A track's input as (left, right): a mono input on both sides, or a stereo
+    /// pair of `first_channel` and the channel after it
+    pub fn track_input(&self, first_channel: u32, channel_count: u32) -> (f32, f32) {
+        let left = self.channel(first_channel as usize);
+        if channel_count >= 2 {
+            (left, self.channel(first_channel as usize + 1))
+        } else {
+            (left, left)
+        }
+    }
+}
+
+/// Per-frame inputs shared by every track
+pub(super) struct TrackFrame {
+    /// Playhead in real (pre-tempo) samples; MIDI events are timed against this
+    pub playhead_frame: u64,
+    /// Playhead on the timeline in seconds; clips and automation are read here
+    pub playhead_seconds: f64,
+    /// Play the timeline (audio clips and MIDI clips); false while stopped
+    pub play_timeline: bool,
+    /// Recording is running: armed tracks skip their existing clips
+    pub recording: bool,
+    /// Live input (every device channel) for monitoring
+    pub input: InputFrame,
+    /// Software monitoring is on (`MonitorMode::Software`): armed tracks with
+    /// input monitoring play their input
+    pub monitor_input: bool,
+    /// Re-evaluate volume/pan automation this frame (otherwise the last value is held)
+    pub refresh_automation: bool,
+    /// Apply phase/mono and volume/pan (false for pre-fader renders)
+    pub apply_fader: bool,
+    /// Use preview-quality effect processing (offline previews only)
+    pub preview: bool,
+    /// The playhead wrapped to the loop start on this frame: silence the
+    /// instrument before it plays
+    pub release_notes: bool,
+}
+
+/// Mix one frame of a track: clips, MIDI, instrument, monitored input, FX chain,
+/// phase/mono and finally volume/pan. Mute and solo are left to the caller.
+///
+/// `sidechain_levels` holds this frame's output level of tracks mixed so far.
+/// Sends are added to `bus_inputs`; group and return tracks mix their entry there,
+/// so they must come after their sources (see `resolve_bus_routing`).
+/// The result is the track's output; `route_to_group` decides where it goes.
+/// The signal at the track's metering point is left in `track.meter`.
+#[inline]
+pub(super) fn process_track_frame(
+    track: &mut TrackSnapshot,
+    synth_manager: &mut TrackSynthManager,
+    effect_mgr: &EffectManager,
+    frame: &TrackFrame,
+    sidechain_levels: &HashMap<u64, f32>,
+    bus_inputs: &mut BusInputs,
+) -> (f32, f32) {
+    let bus_input = if track.is_return || track.is_group {
+        bus_inputs.get(&track.id).copied().unwrap_or_default()
+    } else {
+        (0.0, 0.0)
+    };
+    let (dry, monitor) = track_dry_frame(track, synth_manager, effect_mgr, frame, bus_input);
+
+    // Process FX chain BEFORE volume/pan (fader controls post-FX level)
+    // Monitored input joins before or after the chain; the record tap stays dry
+    let fx = process_track_fx_with_monitor(
+        &track.fx_chain, effect_mgr, dry.0, dry.1,
+        monitor, track.monitor_through_fx, frame.preview, sidechain_levels,
+    );
+    track.meter = match track.metering_point {
+        MeteringPoint::PreFx => (dry.0 + monitor.0, dry.1 + monitor.1),
+        MeteringPoint::PostFxPreFader | MeteringPoint::PostFader => fx,
+    };
+    track_post_fx_frame(track, frame, fx, |target, left, right| add_to_bus(bus_inputs, target, left, right))
+}
+
+/// The part of a track's frame before its FX chain: clips, MIDI, instrument,
+/// `bus_input` (for group and return tracks) and monitored input.
+/// Returns the dry signal and the faded monitor input, kept apart for
+/// `process_track_fx_with_monitor`.
+#[inline]
+fn track_dry_frame(
+    track: &mut TrackSnapshot,
+    synth_manager: &mut TrackSynthManager,
+    effect_mgr: &EffectManager,
+    frame: &TrackFrame,
+    bus_input: (f32, f32),
+) -> ((f32, f32), (f32, f32)) {
+    if frame.release_notes {
+        synth_manager.all_notes_off(track.id);
+    }
+    let mut track_left = 0.0f32;
+    let mut track_right = 0.0f32;
+
+    // Skip existing clip playback on armed tracks during recording
+    // (user should only hear new input, not old overlapping clips)
+    let skip_clips = track.armed && frame.recording;
+    if frame.play_timeline && !skip_clips {
+        for timeline_clip in &track.audio_clips {
+            let (cl, cr) = render_audio_clip_sample(timeline_clip, frame.playhead_seconds);
+            track_left += cl;
+            track_right += cr;
+        }
+        trigger_midi_events(track, synth_manager, effect_mgr, frame.playhead_frame);
+    }
+    let transport_seconds = frame.play_timeline.then_some(frame.playhead_seconds);
+    synth_manager.tick_step_sequencer(track.id, transport_seconds);
+    synth_manager.tick_arpeggiator(track.id, transport_seconds);
+
+    // Add per-track instrument output
+    let (synth_left, synth_right) = if frame.preview {
+        synth_manager.process_sample_stereo_preview(track.id)
+    } else {
+        synth_manager.process_sample_stereo(track.id)
+    };
+    track_left += synth_left;
+    track_right += synth_right;
+
+    // Group and return tracks mix what was routed into them
+    track_left += bus_input.0;
+    track_right += bus_input.1;
+
+    // Input monitoring: live input for armed audio tracks
+    let monitor = {
+        let should_monitor = frame.monitor_input && track.armed && track.input_monitoring && track.is_audio_track;
+        update_monitoring_fade(&mut track.monitoring_fade_gain, should_monitor);
+
+        if track.monitoring_fade_gain > 0.0 {
+            let (input_left, input_right) = frame.input.track_input(track.input_channel, track.input_channel_count);
+            let gain = track.input_gain * track.monitoring_fade_gain as f32;
+            (input_left * gain, input_right * gain)
+        } else {
+            (0.0, 0.0)
+        }
+    };
+
+    ((track_left, track_right), monitor)
+}
+
+/// The part of a track's frame after its FX chain (output `fx`): sends, phase/mono
+/// and volume/pan. `feed_bus` receives what the sends add to each bus. Updates the
+/// meter for `MeteringPoint::PostFader`; the other points are the caller's.
+#[inline]
+fn track_post_fx_frame(
+    track: &mut TrackSnapshot,
+    frame: &TrackFrame,
+    fx: (f32, f32),
+    mut feed_bus: impl FnMut(TrackId, f32, f32),
+) -> (f32, f32) {
+    let (fx_left, fx_right) = fx;
+    feed_sends(&track.sends, true, fx_left, fx_right, &mut feed_bus);
+    if !frame.apply_fader {
+        return (fx_left, fx_right);
+    }
+    let (mut out_left, mut out_right) =
+        apply_channel_utilities(fx_left, fx_right, track.mono_sum, track.phase_invert);
+
+    // Automation curves override the static volume and pan
+    if frame.refresh_automation {
+        if !track.volume_automation.is_empty() {
+            track.automation_gain = interpolate_automation_gain(&track.volume_automation, frame.playhead_seconds);
+        }
+        if !track.pan_automation.is_empty() {
+            track.automation_pan = interpolate_automation_pan_gains(&track.pan_automation, frame.playhead_seconds);
+        }
+    }
+    let volume_gain = if track.volume_automation.is_empty() {
+        track.volume_gain
+    } else {
+        track.automation_gain
+    };
+    let (pan_left, pan_right) = if track.pan_automation.is_empty() {
+        (track.pan_left, track.pan_right)
+    } else {
+        track.automation_pan
+    };
+    out_left *= volume_gain * pan_left;
+    out_right *= volume_gain * pan_right;
+    feed_sends(&track.sends, false, out_left, out_right, &mut feed_bus);
+    if track.metering_point == MeteringPoint::PostFader {
+        track.meter = (out_left, out_right);
+    }
+
+    (out_left, out_right)
+}
+
+/// Add a track's pre- or post-fader signal to the buses its sends feed
+#[inline]
+fn feed_sends(sends: &[Send], pre_fader: bool, left: f32, right: f32, feed_bus: &mut impl FnMut(TrackId, f32, f32)) {
+    for send in sends.iter().filter(|send| send.pre_fader == pre_fader && send.amount > 0.0) {
+        feed_bus(send.target_track_id, left * send.amount, right * send.amount);
+    }
+}
+
+/// Add a frame to a bus track's input
+#[inline]
+fn add_to_bus(bus_inputs: &mut BusInputs, bus_id: TrackId, left: f32, right: f32) {
+    let bus = bus_inputs.entry(bus_id).or_insert((0.0, 0.0));
+    bus.0 += left;
+    bus.1 += right;
+}
+
+/// Send a track's output into its group's bus, returning what reaches the master directly
+#[inline]
+pub(super) fn route_to_group(track: &TrackSnapshot, left: f32, right: f32, bus_inputs: &mut BusInputs) -> (f32, f32) {
+    match track.output_group {
+        Some(group_id) => {
+            add_to_bus(bus_inputs, group_id, left, right);
+            (0.0, 0.0)
+        }
+        None => (left, right),
+    }
+}
+
+/// Which part of the mix an offline stem render keeps
+///
+/// Both kinds sum back to the mix (before the master fader and FX, and as long as
+/// bus FX are linear): the `WithSends` stems of every non-bus track, or the dry
+/// stems of the tracks routed straight to the master plus the `Bus` stems of the
+/// groups and returns that feed it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum StemSource {
+    /// Only this track plays; its signal is followed through its groups and the
+    /// returns its sends feed, so the stem carries its wet signal too
+    WithSends(TrackId),
+    /// Every track plays; the stem is this group or return track's output
+    Bus(TrackId),
+}
+
+impl StemSource {
+    /// Whether the track is processed in this stem's render
+    pub(super) fn plays(self, track: &TrackSnapshot) -> bool {
+        match self {
+            StemSource::WithSends(id) => track.id == id || track.is_group || track.is_return,
+            StemSource::Bus(_) => true,
+        }
+    }
+
+    /// A processed track's share of the stem, given its output and what of it
+    /// reaches the master directly (see `route_to_group`)
+    #[inline]
+    pub(super) fn contribution(self, track: &TrackSnapshot, output: (f32, f32), to_master: (f32, f32)) -> (f32, f32) {
+        match self {
+            StemSource::WithSends(_) => to_master,
+            StemSource::Bus(id) if id == track.id => output,
+            StemSource::Bus(_) => (0.0, 0.0),
+        }
+    }
+}
+
+/// Resolve group routing and order tracks so every bus is mixed after its sources:
+/// plain tracks first, then groups from the innermost out, then returns (keeping
+/// relative order otherwise). A track whose group is missing, is not a group track
+/// or leads back to the track itself (a cycle) goes straight to the master; returns
+/// always feed the master. Also works out which tracks solo keeps audible.
+/// Does not allocate.
+pub(super) fn resolve_bus_routing(tracks: &mut [TrackSnapshot]) {
+    let find_group = |tracks: &[TrackSnapshot], id: TrackId| tracks.iter().position(|t| t.id == id && t.is_group);
+    for i in 0..tracks.len() {
+        let mut depth = 0;
+        let mut current = i;
+        let mut cyclic = false;
+        // Walk up the groups; a chain that runs into a cycle further up stops after
+        // `len` steps (the groups in that cycle are cut off on their own turn)
+        while let Some(parent) = tracks[current].parent_group.and_then(|id| find_group(tracks, id)) {
+            if parent == i {
+                cyclic = true;
+                break;
+            }
+            depth += 1;
+            current = parent;
+            if depth > tracks.len() {
+                break;
+            }
+        }
+        let output_group = tracks[i].parent_group.filter(|&id| find_group(tracks, id).is_some());
+        let track = &mut tracks[i];
+        track.group_depth = if cyclic { 0 } else { depth };
+        track.output_group = if cyclic || track.is_return { None } else { output_group };
+    }
+
+    // Soloing a group soloes its children; soloing a child keeps its groups open
+    let any_soloed = tracks.iter().any(|t| t.soloed);
+    for i in 0..tracks.len() {
+        tracks[i].solo_path = tracks[i].soloed
+            || (any_soloed
+                && (0..tracks.len()).any(|j| {
+                    tracks[j].soloed && (is_routed_into(tracks, i, j) || is_routed_into(tracks, j, i))
+                }));
+    }
+
+    // Stable insertion sort by mixing rank
+    for i in 1..tracks.len() {
+        let mut j = i;
+        while j > 0 && tracks[j - 1].mix_rank() > tracks[j].mix_rank() {
+            tracks.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+/// Plugin delay compensation: work out how long each track's output is held back
+/// so everything summed into the same bus (a group or the master) lines up with
+/// its latest input. Groups carry the latency of their children on top of their own
+/// chain. Call after `resolve_bus_routing` (bus tracks come after their sources).
+/// Sends reach their returns uncompensated. Returns the latency of the mix reaching
+/// the master bus. Does not allocate.
+pub(super) fn resolve_latency_compensation(tracks: &mut [TrackSnapshot], effect_mgr: &EffectManager) -> usize {
+    let latest_input = |tracks: &[TrackSnapshot], bus: Option<TrackId>| {
+        tracks.iter().filter(|t| t.output_group == bus).map(|t| t.output_latency).max().unwrap_or(0)
+    };
+    for i in 0..tracks.len() {
+        let upstream = if tracks[i].is_group { latest_input(&tracks[..i], Some(tracks[i].id)) } else { 0 };
+        tracks[i].output_latency = upstream + effect_mgr.chain_latency(&tracks[i].fx_chain);
+    }
+    for i in 0..tracks.len() {
+        tracks[i].compensation_delay = latest_input(tracks, tracks[i].output_group) - tracks[i].output_latency;
+    }
+    latest_input(tracks, None)
+}
+
+/// Tracks the real-time callback can hold back at once (see `CompensationDelays`)
+pub(super) const MAX_COMPENSATED_TRACKS: usize = 64;
+
+/// Compensation delay lines, handed out to tracks by id
+///
+/// The lines are allocated up front and outlive the per-buffer snapshots, so the
+/// real-time callback never allocates: a track claims a free line the first time it
+/// needs holding back and returns it once it's gone. Tracks beyond the pool's
+/// capacity play uncompensated, and delays are clamped to `MAX_LATENCY_FRAMES`.
+pub(super) struct CompensationDelays {
+    lines: Vec<LatencyDelay>,
+    owners: Vec<Option<TrackId>>,
+}
+
+impl CompensationDelays {
+    /// A pool for `track_capacity` tracks
+    pub fn new(track_capacity: usize) -> Self {
+        Self {
+            lines: (0..track_capacity).map(|_| LatencyDelay::new()).collect(),
+            owners: vec![None; track_capacity],
+        }
+    }
+
+    /// Return the lines of tracks no longer in `tracks` (once per buffer)
+    pub fn release_missing(&mut self, tracks: &[TrackSnapshot]) {
+        for owner in &mut self.owners {
+            if owner.is_some_and(|id| !tracks.iter().any(|t| t.id == id)) {
+                *owner = None;
+            }
+        }
+    }
+
+    /// Silence every line (while the transport is stopped)
+    pub fn clear(&mut self) {
+        for line in &mut self.lines {
+            line.clear();
+        }
+    }
+
+    /// The track's line, claiming a free one if `claim` is set
+    fn line(&mut self, track_id: TrackId, claim: bool) -> Option<&mut LatencyDelay> {
+        let index = match self.owners.iter().position(|&owner| owner == Some(track_id)) {
+            Some(index) => index,
+            None if claim => {
+                let index = self.owners.iter().position(Option::is_none)?;
+                self.owners[index] = Some(track_id);
+                self.lines[index].clear();
+                index
+            }
+            None => return None,
+        };
+        Some(&mut self.lines[index])
+    }
+}
+
+/// Hold back a track's output by its `compensation_delay`
+#[inline]
+pub(super) fn compensate_latency(track: &TrackSnapshot, delays: &mut CompensationDelays, left: f32, right: f32) -> (f32, f32) {
+    match delays.line(track.id, track.compensation_delay > 0) {
+        Some(line) => line.process(track.compensation_delay, left, right),
+        None => (left, right),
+    }
+}
+
+/// Whether track `index` feeds group `group_index`, directly or through nested groups
+fn is_routed_into(tracks: &[TrackSnapshot], index: usize, group_index: usize) -> bool {
+    let group_id = tracks[group_index].id;
+    let mut current = tracks[index].output_group;
+    for _ in 0..tracks.len() {
+        let Some(id) = current else { return false };
+        if id == group_id {
+            return true;
+        }
+        current = tracks.iter().find(|t| t.id == id).and_then(|t| t.output_group);
+    }
+    false
+}
+
+/// Send the track's MIDI clip events that fall on this frame to its instruments.
+/// VST3 plugins in the chain always get the events; the built-in synth is skipped
+/// when a VST3 instrument is present, unless the track layers them.
+#[inline]
+fn trigger_midi_events(
+    track: &TrackSnapshot,
+    synth_manager: &mut TrackSynthManager,
+    effect_mgr: &EffectManager,
+    playhead_frame: u64,
+) {
+    // Only looked up once an event actually fires on this frame
+    let mut to_synth: Option<bool> = None;
+
+    for timeline_midi_clip in &track.midi_clips {
+        let clip_start_samples = (timeline_midi_clip.start_time * f64::from(TARGET_SAMPLE_RATE)) as u64;
+        let clip_end_samples = clip_start_samples + timeline_midi_clip.clip.duration_samples;
+
+        // Check if clip is active at this frame
+        // Use <= for end boundary to ensure note-offs at exact clip end are triggered
+        if playhead_frame < clip_start_samples || playhead_frame > clip_end_samples {
+            continue;
+        }
+        let frame_in_clip = playhead_frame - clip_start_samples;
+
+        // Check for MIDI events that should trigger at this exact sample
+        for event in &timeline_midi_clip.clip.events {
+            if event.timestamp_samples != frame_in_clip {
+                continue;
+            }
+            let to_synth = *to_synth.get_or_insert_with(|| {
+                !effect_mgr.chain_has_instrument(&track.fx_chain) || track.layer_synth
+            });
+            match event.event_type {
+                MidiEventType::NoteOn { note, velocity } => {
+                    send_vst3_midi(&track.fx_chain, effect_mgr, 0, note, velocity);
+                    if to_synth {
+                        synth_manager.note_on(track.id, note, velocity);
+                    }
+                }
+                MidiEventType::NoteOff { note, velocity: _ } => {
+                    send_vst3_midi(&track.fx_chain, effect_mgr, 1, note, 0);
+                    if to_synth {
+                        synth_manager.note_off(track.id, note);
+                    }
+                }
+                // The VST3 host only takes note events so far
+                MidiEventType::PitchBend { value } => {
+                    if to_synth {
+                        synth_manager.pitch_bend(track.id, value);
+                    }
+                }
+                MidiEventType::ControlChange { controller, value } => {
+                    if to_synth {
+                        synth_manager.control_change(track.id, controller, value);
+                    }
+                }
+                MidiEventType::PolyAftertouch { note, pressure } => {
+                    if to_synth {
+                        synth_manager.poly_aftertouch(track.id, note, pressure);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Send a note event to the VST3 instruments in an FX chain
+/// `event_type`: 0 = note on, 1 = note off
+#[inline]
+#[allow(unused_variables)]
+fn send_vst3_midi(fx_chain: &[u64], effect_mgr: &EffectManager, event_type: i32, note: u8, velocity: u8) {
+    #[cfg(all(feature = "vst3", not(target_os = "ios")))]
+    for effect_id in fx_chain {
+        if let Some(effect_arc) = effect_mgr.get_effect(*effect_id) {
+            let mut effect = effect_arc.lock();
+            if let crate::effects::EffectType::VST3(ref mut vst3) = *effect {
+                let _ = vst3.process_midi_event(event_type, 0, i32::from(note), i32::from(velocity), 0);
+            }
+        }
+    }
+}
+
+/// Update monitoring fade gain with a 20ms ramp to avoid clicks.
+/// Modifies `fade_gain` in place toward 0.0 or 1.0.
+#[inline]
+fn update_monitoring_fade(fade_gain: &mut f64, should_monitor: bool) {
+    let target = if should_monitor { 1.0_f64 } else { 0.0_f64 };
+    #[allow(clippy::float_cmp)]
+    if *fade_gain != target {
+        let step = 1.0 / (0.020 * f64::from(TARGET_SAMPLE_RATE));
+        if target > *fade_gain {
+            *fade_gain = (*fade_gain + step).min(1.0);
+        } else {
+            *fade_gain = (*fade_gain - step).max(0.0);
+        }
+    }
+}
+
+/// Process an effect chain through a locked `EffectManager`.
+/// When `silent` is true, feeds zeros to keep VST3 plugins alive (muted tracks).
+/// `preview` selects the cheaper preview-quality processing (offline previews only).
+/// `sidechain_levels` holds this frame's output level of tracks mixed so far.
+#[inline]
+pub(super) fn process_effect_chain(
+    fx_chain: &[u64],
+    effect_mgr: &EffectManager,
+    left: f32,
+    right: f32,
+    silent: bool,
+    preview: bool,
+    sidechain_levels: &HashMap<u64, f32>,
+) -> (f32, f32) {
+    let mut out_l = if silent { 0.0 } else { left };
+    let mut out_r = if silent { 0.0 } else { right };
+    for effect_id in fx_chain {
+        if !silent && effect_mgr.is_bypassed(*effect_id) {
+            continue;
+        }
+        if let Some(effect_arc) = effect_mgr.get_effect(*effect_id) {
+            let mut effect = effect_arc.lock();
+            feed_sidechain(&mut effect, sidechain_levels);
+            let (fx_l, fx_r) = if preview {
+                effect_mgr.process_frame_preview(*effect_id, &mut effect, out_l, out_r)
+            } else {
+                effect_mgr.process_frame(*effect_id, &mut effect, out_l, out_r)
+            };
+            out_l = fx_l;
+            out_r = fx_r;
+        }
+    }
+    (out_l, out_r)
+}
+
+/// Block counterpart of `process_effect_chain`: each effect runs over the whole
+/// block before the next one. Only for chains without sidechain-keyed effects
+/// (see `EffectManager::chain_has_sidechain`), whose levels change every frame.
+/// When `silent` is true, the block is zeroed and fed to every effect (muted tracks).
+pub(super) fn process_effect_chain_block(fx_chain: &[u64], effect_mgr: &EffectManager, left: &mut [f32], right: &mut [f32], silent: bool) {
+    if silent {
+        left.fill(0.0);
+        right.fill(0.0);
+    }
+    for effect_id in fx_chain {
+        if !silent && effect_mgr.is_bypassed(*effect_id) {
+            continue;
+        }
+        if let Some(effect_arc) = effect_mgr.get_effect(*effect_id) {
+            effect_mgr.process_block(*effect_id, &mut effect_arc.lock(), left, right);
+        }
+    }
+}
+
+/// Per-frame stereo blocks handed out to tracks by id and emptied every buffer
+///
+/// Like `CompensationDelays`, the blocks are allocated up front; ids beyond the
+/// pool's capacity are dropped, and blocks only grow if a buffer is longer than
+/// the frame capacity.
+struct BlockPool {
+    blocks: Vec<Vec<(f32, f32)>>,
+    owners: Vec<Option<TrackId>>,
+    frames: usize,
+}
+
+impl BlockPool {
+    fn new(capacity: usize, frame_capacity: usize) -> Self {
+        Self {
+            blocks: (0..capacity).map(|_| Vec::with_capacity(frame_capacity)).collect(),
+            owners: vec![None; capacity],
+            frames: 0,
+        }
+    }
+
+    /// Empty every block for a buffer of `frames`
+    fn reset(&mut self, frames: usize) {
+        self.owners.fill(None);
+        self.frames = frames;
+    }
+
+    fn index(&self, id: TrackId) -> Option<usize> {
+        self.owners.iter().position(|&owner| owner == Some(id))
+    }
+
+    /// Add a frame to `id`'s block, claiming a silent block on first use
+    fn add(&mut self, id: TrackId, frame: usize, left: f32, right: f32) {
+        let index = if let Some(index) = self.index(id) {
+            index
+        } else {
+            let Some(index) = self.owners.iter().position(Option::is_none) else { return };
+            self.owners[index] = Some(id);
+            self.blocks[index].clear();
+            self.blocks[index].resize(self.frames, (0.0, 0.0));
+            index
+        };
+        let sample = &mut self.blocks[index][frame];
+        sample.0 += left;
+        sample.1 += right;
+    }
+
+    /// `id`'s frame (silence if nothing was added to it)
+    fn get(&self, id: TrackId, frame: usize) -> (f32, f32) {
+        self.index(id).map_or((0.0, 0.0), |index| self.blocks[index][frame])
+    }
+}
+
+/// Buffers for mixing a buffer of frames track by track (see `process_track_block`)
+/// Allocated up front, so mixing is safe on the audio thread.
+pub(super) struct MixBlocks {
+    /// Input of each group and return track: sends and grouped tracks' outputs
+    buses: BlockPool,
+    /// Output level of each sidechain source (left channel)
+    sidechain_levels: BlockPool,
+    /// One frame of `sidechain_levels`, for chains that run frame by frame
+    frame_levels: HashMap<TrackId, f32>,
+    /// The track being mixed: its dry signal, then its FX output
+    track_left: Vec<f32>,
+    track_right: Vec<f32>,
+    /// The track's faded monitor input
+    monitor_left: Vec<f32>,
+    monitor_right: Vec<f32>,
+    /// Sum of the tracks reaching the master bus
+    master_left: Vec<f32>,
+    master_right: Vec<f32>,
+}
+
+impl MixBlocks {
+    /// Buffers for up to `track_capacity` buses and sidechain sources, and
+    /// `frame_capacity` frames per buffer
+    pub fn new(track_capacity: usize, frame_capacity: usize) -> Self {
+        Self {
+            buses: BlockPool::new(track_capacity, frame_capacity),
+            sidechain_levels: BlockPool::new(track_capacity, frame_capacity),
+            frame_levels: HashMap::with_capacity(track_capacity),
+            track_left: Vec::with_capacity(frame_capacity),
+            track_right: Vec::with_capacity(frame_capacity),
+            monitor_left: Vec::with_capacity(frame_capacity),
+            monitor_right: Vec::with_capacity(frame_capacity),
+            master_left: Vec::with_capacity(frame_capacity),
+            master_right: Vec::with_capacity(frame_capacity),
+        }
+    }
+
+    /// Start a buffer of `frames` with every bus and the master sum silent
+    pub fn reset(&mut self, frames: usize) {
+        self.buses.reset(frames);
+        self.sidechain_levels.reset(frames);
+        for block in [&mut self.master_left, &mut self.master_right] {
+            block.clear();
+            block.resize(frames, 0.0);
+        }
+    }
+
+    /// Master sum of `frame`
+    pub fn master(&self, frame: usize) -> (f32, f32) {
+        (self.master_left[frame], self.master_right[frame])
+    }
+
+    /// Output level of every sidechain source mixed so far on `frame`
+    pub fn sidechain_levels_at(&mut self, frame: usize) -> &HashMap<TrackId, f32> {
+        self.frame_levels.clear();
+        for (owner, block) in self.sidechain_levels.owners.iter().zip(&self.sidechain_levels.blocks) {
+            if let Some(id) = owner {
+                self.frame_levels.insert(*id, block[frame].0);
+            }
+        }
+        &self.frame_levels
+    }
+
+    fn clear_track(&mut self, frames: usize) {
+        for block in [&mut self.track_left, &mut self.track_right, &mut self.monitor_left, &mut self.monitor_right] {
+            block.clear();
+            block.resize(frames, 0.0);
+        }
+    }
+}
+
+/// Block counterpart of `process_track_frame` followed by `compensate_latency` and
+/// `route_to_group`: mixes `frames` of a track into `blocks`, where its groups and
+/// returns pick it up and the master sum collects the rest.
+///
+/// The FX chain runs on the whole block through `process_effect_chain_block`, unless
+/// it is keyed from a sidechain or the frames ask for preview processing; then it
+/// runs frame by frame. Tracks must come in mixing order (see `resolve_bus_routing`),
+/// and sidechain sources (`publish_sidechain`) before the tracks keyed from them.
+/// Returns the track's peak at its metering point over the block.
+pub(super) fn process_track_block(
+    track: &mut TrackSnapshot,
+    synth_manager: &mut TrackSynthManager,
+    effect_mgr: &EffectManager,
+    frames: &[TrackFrame],
+    publish_sidechain: bool,
+    delays: &mut CompensationDelays,
+    blocks: &mut MixBlocks,
+) -> (f32, f32) {
+    let mut peak = (0.0f32, 0.0f32);
+    let mut add_peak = |(left, right): (f32, f32)| {
+        peak = (peak.0.max(left.abs()), peak.1.max(right.abs()));
+    };
+
+    blocks.clear_track(frames.len());
+    let is_bus = track.is_return || track.is_group;
+    for (index, frame) in frames.iter().enumerate() {
+        let bus_input = if is_bus { blocks.buses.get(track.id, index) } else { (0.0, 0.0) };
+        let (dry, monitor) = track_dry_frame(track, synth_manager, effect_mgr, frame, bus_input);
+        (blocks.track_left[index], blocks.track_right[index]) = dry;
+        (blocks.monitor_left[index], blocks.monitor_right[index]) = monitor;
+        if track.metering_point == MeteringPoint::PreFx {
+            track.meter = (dry.0 + monitor.0, dry.1 + monitor.1);
+            add_peak(track.meter);
+        }
+    }
+
+    // Process FX chain BEFORE volume/pan (fader controls post-FX level)
+    let per_frame = frames.iter().any(|frame| frame.preview) || effect_mgr.chain_has_sidechain(&track.fx_chain);
+    if per_frame {
+        for (index, frame) in frames.iter().enumerate() {
+            let monitor = (blocks.monitor_left[index], blocks.monitor_right[index]);
+            let (left, right) = (blocks.track_left[index], blocks.track_right[index]);
+            let levels = blocks.sidechain_levels_at(index);
+            let fx = process_track_fx_with_monitor(
+                &track.fx_chain, effect_mgr, left, right,
+                monitor, track.monitor_through_fx, frame.preview, levels,
+            );
+            (blocks.track_left[index], blocks.track_right[index]) = fx;
+        }
+    } else {
+        // Monitored input joins before or after the chain, as in `process_track_fx_with_monitor`
+        let add_monitor = |blocks: &mut MixBlocks| {
+            for (sample, monitor) in blocks.track_left.iter_mut().zip(&blocks.monitor_left) {
+                *sample += monitor;
+            }
+            for (sample, monitor) in blocks.track_right.iter_mut().zip(&blocks.monitor_right) {
+                *sample += monitor;
+            }
+        };
+        if track.monitor_through_fx {
+            add_monitor(blocks);
+        }
+        process_effect_chain_block(&track.fx_chain, effect_mgr, &mut blocks.track_left, &mut blocks.track_right, false);
+        if !track.monitor_through_fx {
+            add_monitor(blocks);
+        }
+    }
+
+    for (index, frame) in frames.iter().enumerate() {
+        let fx = (blocks.track_left[index], blocks.track_right[index]);
+        if track.metering_point != MeteringPoint::PreFx {
+            track.meter = fx;
+        }
+        let buses = &mut blocks.buses;
+        let (left, right) = track_post_fx_frame(track, frame, fx, |target, left, right| buses.add(target, index, left, right));
+        if track.metering_point != MeteringPoint::PreFx {
+            add_peak(track.meter);
+        }
+
+        // Publish this track's level for sidechain compressors on later tracks
+        if publish_sidechain {
+            blocks.sidechain_levels.add(track.id, index, sidechain_level(left, right), 0.0);
+        }
+
+        // Line up with the most latent track on the same bus, then mix into its group or the master
+        let (left, right) = compensate_latency(track, delays, left, right);
+        if let Some(group_id) = track.output_group {
+            blocks.buses.add(group_id, index, left, right);
+        } else {
+            blocks.master_left[index] += left;
+            blocks.master_right[index] += right;
+        }
+    }
+    peak
+}
+
+/// Keep a silenced track's instrument and FX (VST3) running on silence for `frames`
+pub(super) fn keep_track_alive(
+    track: &TrackSnapshot,
+    synth_manager: &mut TrackSynthManager,
+    effect_mgr: &EffectManager,
+    frames: usize,
+    blocks: &mut MixBlocks,
+) {
+    for _ in 0..frames {
+        synth_manager.process_sample_stereo(track.id);
+    }
+    blocks.clear_track(frames);
+    process_effect_chain_block(&track.fx_chain, effect_mgr, &mut blocks.track_left, &mut blocks.track_right, true);
+}
+
+/// Process a track's FX chain together with its monitored input.
+/// `monitor` is the faded live input (left, right): it runs through the FX chain when
+/// `monitor_through_fx` is set, otherwise it joins after the chain (dry monitoring).
+/// The record tap reads the raw input separately, so recordings are always dry.
+#[inline]
+fn process_track_fx_with_monitor(
+    fx_chain: &[u64],
+    effect_mgr: &EffectManager,
+    left: f32,
+    right: f32,
+    monitor: (f32, f32),
+    monitor_through_fx: bool,
+    preview: bool,
+    sidechain_levels: &HashMap<u64, f32>,
+) -> (f32, f32) {
+    if monitor_through_fx {
+        process_effect_chain(fx_chain, effect_mgr, left + monitor.0, right + monitor.1, false, preview, sidechain_levels)
+    } else {
+        let (fx_l, fx_r) = process_effect_chain(fx_chain, effect_mgr, left, right, false, preview, sidechain_levels);
+        (fx_l + monitor.0, fx_r + monitor.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_file::AudioClip;
+    use crate::effects::{Compressor, Delay, Effect, EffectType, Gain, Limiter};
+    use crate::midi::{MidiClip, MidiEvent};
+    use crate::recorder::Recorder;
+    use std::sync::Arc;
+
+    fn playing_frame() -> TrackFrame {
+        TrackFrame {
+            playhead_frame: 0,
+            playhead_seconds: 0.0,
+            play_timeline: true,
+            recording: false,
+            input: InputFrame::silent(),
+            monitor_input: true,
+            refresh_automation: true,
+            apply_fader: true,
+            preview: false,
+            release_notes: false,
+        }
+    }
+
+    /// Audio track snapshot with one constant-level clip at the start of the timeline
+    fn track_with_clip(level: f32, volume_db: f32) -> TrackSnapshot {
+        let frames = 100;
+        let clip = AudioClip {
+            samples: vec![level; frames * 2],
+            channels: 2,
+            sample_rate: TARGET_SAMPLE_RATE,
+            duration_seconds: frames as f64 / f64::from(TARGET_SAMPLE_RATE),
+            file_path: "test.wav".to_string(),
+            stream: None,
+        };
+        let mut track = Track::new(1, TrackType::Audio, "Audio 1".to_string());
+        track.volume_db = volume_db;
+        track.audio_clips.push(TimelineClip::new(0, Arc::new(clip), 0.0));
+        TrackSnapshot::new(&track)
+    }
+
+    #[test]
+    fn test_clip_output_goes_through_volume_and_pan() {
+        let mut track = track_with_clip(0.5, -6.0);
+        let mut synths = TrackSynthManager::new(TARGET_SAMPLE_RATE as f32);
+        let effect_mgr = EffectManager::new();
+
+        let (left, right) = process_track_frame(&mut track, &mut synths, &effect_mgr, &playing_frame(), &HashMap::new(), &mut HashMap::new());
+        let gain = 10_f32.powf(-6.0 / 20.0);
+        assert!((left - 0.5 * gain * track.pan_left).abs() < 1e-6);
+        assert!((right - 0.5 * gain * track.pan_right).abs() < 1e-6);
+
+        // Pre-fader output skips volume and pan
+        let frame = TrackFrame { apply_fader: false, ..playing_frame() };
+        assert_eq!(process_track_frame(&mut track, &mut synths, &effect_mgr, &frame, &HashMap::new(), &mut HashMap::new()), (0.5, 0.5));
+    }
+
+    #[test]
+    fn test_send_feeds_return_track_fx_and_master() {
+        // Return track with a -6 dB gain effect, fed by a 100% post-fader send
+        let mut effect_mgr = EffectManager::new();
+        let mut gain = Gain::new();
+        gain.gain_db = -6.0;
+        let gain_id = effect_mgr.create_effect(EffectType::Gain(gain));
+        let mut bus = Track::new(2, TrackType::Return, "Reverb".to_string());
+        bus.fx_chain = vec![gain_id];
+        let bus = TrackSnapshot::new(&bus);
+        let mut source = track_with_clip(0.5, 0.0);
+        source.sends = vec![Send { target_track_id: 2, amount: 1.0, pre_fader: false }];
+        let mut synths = TrackSynthManager::new(TARGET_SAMPLE_RATE as f32);
+
+        // Listed before its source, the return is still mixed after it
+        let mut tracks = vec![bus, source];
+        resolve_bus_routing(&mut tracks);
+        assert_eq!(tracks.iter().map(|t| t.id).collect::<Vec<_>>(), vec![1, 2]);
+
+        let mut bus_inputs = BusInputs::new();
+        let mut outputs = Vec::new();
+        for track in &mut tracks {
+            outputs.push(process_track_frame(track, &mut synths, &effect_mgr, &playing_frame(), &HashMap::new(), &mut bus_inputs));
+        }
+        let (source_left, _) = outputs[0];
+        let (return_left, _) = outputs[1];
+        let expected = source_left * 10_f32.powf(-6.0 / 20.0) * tracks[1].pan_left;
+        assert!((return_left - expected).abs() < 1e-6, "got {return_left}, expected {expected}");
+        assert!(source_left + return_left > source_left);
+
+        // Soloing the source keeps the return audible
+        assert!(!tracks[1].is_silenced(true));
+    }
+
+    /// Mix one frame of every track the way the callback does, returning the master input
+    fn mix_to_master(tracks: &mut [TrackSnapshot], synths: &mut TrackSynthManager, effect_mgr: &EffectManager) -> f32 {
+        let mut bus_inputs = BusInputs::new();
+        let mut master = 0.0;
+        for track in tracks {
+            if track.is_silenced(false) {
+                continue;
+            }
+            let (left, right) = process_track_frame(track, synths, effect_mgr, &playing_frame(), &HashMap::new(), &mut bus_inputs);
+            master += route_to_group(track, left, right, &mut bus_inputs).0;
+        }
+        master
+    }
+
+    fn group(id: TrackId, parent_group: Option<TrackId>) -> TrackSnapshot {
+        let mut group = Track::new(id, TrackType::Group, format!("Group {id}"));
+        group.parent_group = parent_group;
+        TrackSnapshot::new(&group)
+    }
+
+    #[test]
+    fn test_nested_groups_sum_children_and_group_mute_silences_them() {
+        // Audio track 1 -> group 3 -> group 2 -> master, listed outermost first
+        let mut child = track_with_clip(0.5, 0.0);
+        child.parent_group = Some(3);
+        let mut tracks = vec![group(2, None), group(3, Some(2)), child];
+        tracks[1].volume_gain = 0.5;
+        resolve_bus_routing(&mut tracks);
+        assert_eq!(tracks.iter().map(|t| t.id).collect::<Vec<_>>(), vec![1, 3, 2]);
+
+        let mut synths = TrackSynthManager::new(TARGET_SAMPLE_RATE as f32);
+        let effect_mgr = EffectManager::new();
+        let pan = tracks[0].pan_left;
+        // Each stage (track, inner group, outer group) applies its pan law once
+        let expected = 0.5 * pan * 0.5 * pan * pan;
+        let master = mix_to_master(&mut tracks, &mut synths, &effect_mgr);
+        assert!((master - expected).abs() < 1e-6, "got {master}, expected {expected}");
+
+        // Muting the outer group silences the child two levels down
+        tracks[2].muted = true;
+        assert!(mix_to_master(&mut tracks, &mut synths, &effect_mgr).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_solo_follows_groups_and_spares_solo_safe_tracks() {
+        let mut inside = track_with_clip(0.5, 0.0);
+        inside.parent_group = Some(3);
+        let mut outside = track_with_clip(0.5, 0.0);
+        outside.id = 4;
+        let bus = TrackSnapshot::new(&Track::new(5, TrackType::Return, "Reverb".to_string()));
+        let mut tracks = vec![group(2, None), group(3, Some(2)), inside, outside, bus];
+        let silenced = |tracks: &mut [TrackSnapshot]| {
+            resolve_bus_routing(tracks);
+            let mut ids: Vec<TrackId> = tracks.iter().filter(|t| t.is_silenced(true)).map(|t| t.id).collect();
+            ids.sort_unstable();
+            ids
+        };
+
+        // Soloing a child keeps both of its groups open
+        tracks.iter_mut().find(|t| t.id == 1).unwrap().soloed = true;
+        assert_eq!(silenced(&mut tracks), vec![4]);
+
+        // Soloing the outer group soloes everything inside it; the return is solo-safe
+        for track in &mut tracks {
+            track.soloed = track.id == 2;
+        }
+        assert_eq!(silenced(&mut tracks), vec![4]);
+
+        tracks.iter_mut().find(|t| t.id == 5).unwrap().solo_safe = false;
+        tracks.iter_mut().find(|t| t.id == 4).unwrap().solo_safe = true;
+        assert_eq!(silenced(&mut tracks), vec![5]);
+    }
+
+    /// Mix one frame of a stem the way the offline stem render does
+    fn mix_stem(tracks: &mut [TrackSnapshot], synths: &mut TrackSynthManager, effect_mgr: &EffectManager, stem: StemSource) -> f32 {
+        let mut bus_inputs = BusInputs::new();
+        let mut stem_left = 0.0;
+        for track in tracks {
+            if track.is_silenced(false) || !stem.plays(track) {
+                continue;
+            }
+            let output = process_track_frame(track, synths, effect_mgr, &playing_frame(), &HashMap::new(), &mut bus_inputs);
+            let to_master = route_to_group(track, output.0, output.1, &mut bus_inputs);
+            stem_left += stem.contribution(track, output, to_master).0;
+        }
+        stem_left
+    }
+
+    #[test]
+    fn test_stems_sum_to_the_mix() {
+        // Track 1 -> master with a send to return 5 (-6 dB gain); track 4 -> group 3
+        let mut effect_mgr = EffectManager::new();
+        let mut gain = Gain::new();
+        gain.gain_db = -6.0;
+        let gain_id = effect_mgr.create_effect(EffectType::Gain(gain));
+        let mut bus = Track::new(5, TrackType::Return, "Reverb".to_string());
+        bus.fx_chain = vec![gain_id];
+        let mut direct = track_with_clip(0.5, 0.0);
+        direct.sends = vec![Send { target_track_id: 5, amount: 0.5, pre_fader: false }];
+        let mut grouped = track_with_clip(0.25, 0.0);
+        grouped.id = 4;
+        grouped.parent_group = Some(3);
+        let mut tracks = vec![TrackSnapshot::new(&bus), group(3, None), direct, grouped];
+        tracks[1].volume_gain = 0.5;
+        resolve_bus_routing(&mut tracks);
+
+        let mut synths = TrackSynthManager::new(TARGET_SAMPLE_RATE as f32);
+        let mix = mix_to_master(&mut tracks, &mut synths, &effect_mgr);
+
+        // Stems with sends: one per non-bus track, each carrying its wet signal
+        let with_sends_1 = mix_stem(&mut tracks, &mut synths, &effect_mgr, StemSource::WithSends(1));
+        let with_sends_4 = mix_stem(&mut tracks, &mut synths, &effect_mgr, StemSource::WithSends(4));
+        let direct = tracks.iter_mut().find(|t| t.id == 1).unwrap();
+        let dry_1 = process_track_frame(direct, &mut synths, &effect_mgr, &playing_frame(), &HashMap::new(), &mut BusInputs::new()).0;
+        assert!(with_sends_1 > dry_1);
+        assert!((with_sends_1 + with_sends_4 - mix).abs() < 1e-6, "stems {with_sends_1} + {with_sends_4}, mix {mix}");
+
+        // Bus stems: the dry track feeding the master plus the group and return
+        let group_stem = mix_stem(&mut tracks, &mut synths, &effect_mgr, StemSource::Bus(3));
+        let return_stem = mix_stem(&mut tracks, &mut synths, &effect_mgr, StemSource::Bus(5));
+        assert!(group_stem > 0.0 && return_stem > 0.0);
+        assert!((dry_1 + group_stem + return_stem - mix).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_group_cycles_and_missing_groups_route_to_master() {
+        let mut orphan = track_with_clip(0.5, 0.0);
+        orphan.parent_group = Some(9);
+        let mut tracks = vec![group(2, Some(3)), group(3, Some(2)), orphan];
+        resolve_bus_routing(&mut tracks);
+        assert!(tracks.iter().all(|t| t.output_group.is_none()));
+    }
+
+    #[test]
+    fn test_meter_reads_the_chosen_metering_point() {
+        let mut effect_mgr = EffectManager::new();
+        let mut gain = Gain::new();
+        gain.gain_db = -20.0;
+        let gain_id = effect_mgr.create_effect(EffectType::Gain(gain));
+        let mut track = track_with_clip(0.5, -6.0);
+        track.fx_chain = vec![gain_id];
+        let mut synths = TrackSynthManager::new(TARGET_SAMPLE_RATE as f32);
+
+        let mut meter_at = |point| {
+            track.metering_point = point;
+            let output = process_track_frame(&mut track, &mut synths, &effect_mgr, &playing_frame(), &HashMap::new(), &mut HashMap::new());
+            (track.meter.0, output.0)
+        };
+        let (pre_fx, _) = meter_at(MeteringPoint::PreFx);
+        let (pre_fader, _) = meter_at(MeteringPoint::PostFxPreFader);
+        let (post_fader, output) = meter_at(MeteringPoint::PostFader);
+        assert!((pre_fx - 0.5).abs() < 1e-6);
+        assert!((pre_fader - 0.05).abs() < 1e-6);
+        assert!((post_fader - output).abs() < 1e-9 && post_fader < pre_fader);
+    }
+
+    #[test]
+    fn test_fader_is_applied_after_fx() {
+        // A limiter at -6 dB catches a full-scale clip; turning the fader down
+        // afterwards must lower the output instead of just easing the limiter
+        let mut effect_mgr = EffectManager::new();
+        let mut limiter = Limiter::new();
+        limiter.set_threshold_db(-6.0);
+        limiter.set_lookahead_ms(0.0);
+        let limiter_id = effect_mgr.create_effect(EffectType::Limiter(limiter));
+        let mut track = track_with_clip(1.0, -6.0);
+        track.fx_chain = vec![limiter_id];
+        let mut synths = TrackSynthManager::new(TARGET_SAMPLE_RATE as f32);
+
+        let (left, _) = process_track_frame(&mut track, &mut synths, &effect_mgr, &playing_frame(), &HashMap::new(), &mut HashMap::new());
+        let ceiling = 10_f32.powf(-6.0 / 20.0);
+        assert!((left - ceiling * ceiling * track.pan_left).abs() < 1e-3, "got {left}");
+    }
+
+    #[test]
+    fn test_latency_compensation_aligns_tracks() {
+        // Two tracks whose clips start together; one runs through a lookahead limiter
+        let mut effect_mgr = EffectManager::new();
+        let limiter = Limiter::new();
+        let latency = limiter.latency_samples();
+        let limiter_id = effect_mgr.create_effect(EffectType::Limiter(limiter));
+        let plain = track_with_clip(0.5, 0.0);
+        let mut latent = track_with_clip(0.5, 0.0);
+        latent.id = 2;
+        latent.fx_chain = vec![limiter_id];
+        let mut tracks = vec![plain, latent];
+        resolve_bus_routing(&mut tracks);
+        assert_eq!(resolve_latency_compensation(&mut tracks, &effect_mgr), latency);
+        assert_eq!((tracks[0].compensation_delay, tracks[1].compensation_delay), (latency, 0));
+
+        let mut synths = TrackSynthManager::new(TARGET_SAMPLE_RATE as f32);
+        let mut delays = CompensationDelays::new(2);
+        let mut onsets = [None, None];
+        for n in 0..latency + 20 {
+            let frame = TrackFrame { playhead_seconds: n as f64 / f64::from(TARGET_SAMPLE_RATE), ..playing_frame() };
+            for (track, onset) in tracks.iter_mut().zip(&mut onsets) {
+                let (left, right) = process_track_frame(track, &mut synths, &effect_mgr, &frame, &HashMap::new(), &mut BusInputs::new());
+                let (left, _) = compensate_latency(track, &mut delays, left, right);
+                if left.abs() > 1e-6 && onset.is_none() {
+                    *onset = Some(n);
+                }
+            }
+        }
+        assert_eq!(onsets, [Some(latency), Some(latency)]);
+    }
+
+    #[test]
+    fn test_armed_recording_track_skips_clips_and_bypassed_fx_is_skipped() {
+        let mut effect_mgr = EffectManager::new();
+        let mut gain = Gain::new();
+        gain.gain_db = -20.0;
+        let gain_id = effect_mgr.create_effect(EffectType::Gain(gain));
+        let mut track = track_with_clip(0.5, 0.0);
+        track.fx_chain = vec![gain_id];
+        let mut synths = TrackSynthManager::new(TARGET_SAMPLE_RATE as f32);
+        let frame = TrackFrame { apply_fader: false, ..playing_frame() };
+
+        let (left, _) = process_track_frame(&mut track, &mut synths, &effect_mgr, &frame, &HashMap::new(), &mut HashMap::new());
+        assert!((left - 0.05).abs() < 1e-6);
+
+        effect_mgr.set_bypass(gain_id, true);
+        let (left, _) = process_track_frame(&mut track, &mut synths, &effect_mgr, &frame, &HashMap::new(), &mut HashMap::new());
+        assert!((left - 0.5).abs() < 1e-6);
+
+        track.armed = true;
+        let frame = TrackFrame { recording: true, ..frame };
+        assert_eq!(process_track_frame(&mut track, &mut synths, &effect_mgr, &frame, &HashMap::new(), &mut HashMap::new()), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_effect_plugin_does_not_silence_builtin_synth() {
+        // Only an effect (no instrument) in the chain: MIDI must still reach the synth
+        let mut effect_mgr = EffectManager::new();
+        let gain_id = effect_mgr.create_effect(EffectType::Gain(Gain::new()));
+        assert!(!effect_mgr.chain_has_instrument(&[gain_id]));
+
+        let mut clip = MidiClip::new(TARGET_SAMPLE_RATE);
+        clip.add_event(MidiEvent::new(MidiEventType::NoteOn { note: 60, velocity: 100 }, 0));
+        clip.add_event(MidiEvent::new(MidiEventType::NoteOff { note: 60, velocity: 0 }, 4800));
+        let mut track = Track::new(1, TrackType::Midi, "MIDI 1".to_string());
+        track.fx_chain = vec![gain_id];
+        track.midi_clips.push(TimelineMidiClip {
+            id: 0,
+            clip: Arc::new(clip),
+            start_time: 0.0,
+            track_id: Some(1),
+            volume_automation: Vec::new(),
+            pan_automation: Vec::new(),
+        });
+        let mut track = TrackSnapshot::new(&track);
+
+        let mut synths = TrackSynthManager::new(TARGET_SAMPLE_RATE as f32);
+        synths.create_synth(1);
+        let mut peak = 0.0f32;
+        for frame_idx in 0..480u64 {
+            let frame = TrackFrame {
+                playhead_frame: frame_idx,
+                playhead_seconds: frame_idx as f64 / f64::from(TARGET_SAMPLE_RATE),
+                ..playing_frame()
+            };
+            let (left, _) = process_track_frame(&mut track, &mut synths, &effect_mgr, &frame, &HashMap::new(), &mut HashMap::new());
+            peak = peak.max(left.abs());
+        }
+        assert!(peak > 1e-3, "built-in synth should play through an effect-only chain");
+    }
+
+    #[test]
+    fn test_master_pan_scales_each_channel() {
+        let mut master = Track::new(0, TrackType::Master, "Master".to_string());
+        master.volume_db = -3.0;
+        master.pan = -0.5;
+        let master = TrackSnapshot::new(&master);
+
+        // Sine on the left, silence on the right: the old offline path summed
+        // both channels into each output, leaking the left signal to the right
+        let (mut left_energy, mut right_energy) = (0.0f64, 0.0f64);
+        let mut expected_left = 0.0f64;
+        for i in 0..480 {
+            let sample = (i as f32 * 0.1).sin() * 0.5;
+            let (l, r) = master.apply_master_fader(sample, 0.0);
+            left_energy += f64::from(l * l);
+            right_energy += f64::from(r * r);
+
+            // Real-time formula: volume, then each channel by its own pan gain
+            let rt_left = sample * master.volume_gain * master.pan_left;
+            expected_left += f64::from(rt_left * rt_left);
+        }
+        assert!((left_energy - expected_left).abs() < 1e-9);
+        assert!(right_energy < 1e-12, "left-only input must stay on the left");
+    }
+
+    /// Track 1 (delay, send to return 5, in group 3) and track 4 (compressor keyed
+    /// from track 1), in mixing order
+    fn block_test_project() -> (Vec<TrackSnapshot>, EffectManager) {
+        let mut effect_mgr = EffectManager::new();
+        let mut delay = Delay::new();
+        delay.delay_time_ms = 1.0;
+        let delay_id = effect_mgr.create_effect(EffectType::Delay(delay));
+        let mut compressor = Compressor::new();
+        compressor.sidechain_track_id = Some(1);
+        let compressor_id = effect_mgr.create_effect(EffectType::Compressor(compressor));
+        let mut gain = Gain::new();
+        gain.gain_db = -6.0;
+        let gain_id = effect_mgr.create_effect(EffectType::Gain(gain));
+
+        let mut source = track_with_clip(0.5, 0.0);
+        source.fx_chain = vec![delay_id];
+        source.parent_group = Some(3);
+        source.sends = vec![Send { target_track_id: 5, amount: 0.5, pre_fader: false }];
+        let mut keyed = track_with_clip(0.25, -3.0);
+        keyed.id = 4;
+        keyed.fx_chain = vec![compressor_id];
+        let mut bus = Track::new(5, TrackType::Return, "Return".to_string());
+        bus.fx_chain = vec![gain_id];
+        let mut tracks = vec![TrackSnapshot::new(&bus), group(3, None), source, keyed];
+        resolve_bus_routing(&mut tracks);
+        (tracks, effect_mgr)
+    }
+
+    #[test]
+    fn test_block_mix_matches_frame_mix() {
+        let frames: Vec<TrackFrame> = (0..256u64)
+            .map(|n| TrackFrame {
+                playhead_frame: n,
+                playhead_seconds: n as f64 / f64::from(TARGET_SAMPLE_RATE),
+                ..playing_frame()
+            })
+            .collect();
+        let mut synths = TrackSynthManager::new(TARGET_SAMPLE_RATE as f32);
+
+        // Frame by frame, the way the offline renders mix
+        let (mut tracks, effect_mgr) = block_test_project();
+        let mut delays = CompensationDelays::new(4);
+        let mut expected = Vec::new();
+        for frame in &frames {
+            let mut bus_inputs = BusInputs::new();
+            let mut levels = HashMap::new();
+            let mut master = (0.0, 0.0);
+            for track in &mut tracks {
+                let (left, right) = process_track_frame(track, &mut synths, &effect_mgr, frame, &levels, &mut bus_inputs);
+                if track.id == 1 {
+                    levels.insert(1, sidechain_level(left, right));
+                }
+                let (left, right) = compensate_latency(track, &mut delays, left, right);
+                let (left, right) = route_to_group(track, left, right, &mut bus_inputs);
+                master = (master.0 + left, master.1 + right);
+            }
+            expected.push(master);
+        }
+
+        // A block at a time, the way the real-time callback mixes
+        let (mut tracks, effect_mgr) = block_test_project();
+        let mut delays = CompensationDelays::new(4);
+        let mut blocks = MixBlocks::new(4, frames.len());
+        blocks.reset(frames.len());
+        for track in &mut tracks {
+            let publish_sidechain = track.id == 1;
+            process_track_block(track, &mut synths, &effect_mgr, &frames, publish_sidechain, &mut delays, &mut blocks);
+        }
+
+        assert!(expected.iter().any(|&(left, _)| left.abs() > 0.1));
+        for (index, &(left, right)) in expected.iter().enumerate() {
+            let (block_left, block_right) = blocks.master(index);
+            assert!(
+                (block_left - left).abs() < 1e-6 && (block_right - right).abs() < 1e-6,
+                "frame {index}: block ({block_left}, {block_right}) vs frame ({left}, {right})"
+            );
+        }
+    }
+
+    #[test]
+    fn test_monitor_mode_gates_software_monitoring() {
+        let mut track = Track::new(1, TrackType::Audio, "Vocal".to_string());
+        track.armed = true;
+        track.input_monitoring = true;
+        let effect_mgr = EffectManager::new();
+        let mut synths = TrackSynthManager::new(TARGET_SAMPLE_RATE as f32);
+
+        // Run past the 20 ms monitoring fade and return the last output
+        let mut monitor = |track: &Track, monitor_input: bool| {
+            let mut snapshot = TrackSnapshot::new(track);
+            let frame = TrackFrame { input: InputFrame::from_samples(&[0.5, 0.5]), monitor_input, ..playing_frame() };
+            let mut output = (0.0, 0.0);
+            for _ in 0..TARGET_SAMPLE_RATE / 40 {
+                output = process_track_frame(&mut snapshot, &mut synths, &effect_mgr, &frame, &HashMap::new(), &mut HashMap::new());
+            }
+            output.0
+        };
+        assert!(monitor(&track, true) > 0.1, "software monitoring plays the input");
+        assert!(monitor(&track, false).abs() < 1e-9, "off/direct monitoring keeps the input silent");
+        track.input_monitoring = false;
+        assert!(monitor(&track, true).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_monitor_through_fx_keeps_recording_dry() {
+        // A fully wet delay outputs silence until the delay time has elapsed,
+        // so any input that reached the chain is easy to tell from dry input
+        let mut effect_mgr = EffectManager::new();
+        let mut delay = Delay::new();
+        delay.wet_dry_mix = 1.0;
+        let fx_chain = vec![effect_mgr.create_effect(EffectType::Delay(delay))];
+
+        let recorder = Recorder::new();
+        recorder.set_count_in_bars(0);
+        recorder.set_metronome_enabled(false);
+        recorder.start_recording().unwrap();
+        let refs = recorder.get_callback_refs();
+
+        let input = 0.5f32;
+        for frame in 0..64u32 {
+            refs.process_frame(&[input, input], true, f64::from(frame) / f64::from(TARGET_SAMPLE_RATE));
+
+            let (wet_l, wet_r) = process_track_fx_with_monitor(&fx_chain, &effect_mgr, 0.0, 0.0, (input, input), true, false, &HashMap::new());
+            assert!(wet_l.abs() < 1e-6 && wet_r.abs() < 1e-6, "monitored signal should go through the delay");
+
+            let (dry_l, dry_r) = process_track_fx_with_monitor(&fx_chain, &effect_mgr, 0.0, 0.0, (input, input), false, false, &HashMap::new());
+            assert!((dry_l - input).abs() < 1e-6 && (dry_r - input).abs() < 1e-6);
+        }
+
+        let clip = recorder.stop_recording().unwrap().unwrap();
+        assert_eq!(clip.samples.len(), 128);
+        assert!(clip.samples.iter().all(|s| (s - input).abs() < 1e-6), "recording must stay dry");
+    }
+
+}
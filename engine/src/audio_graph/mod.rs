@@ -2,22 +2,38 @@
 ///
 /// Split into focused modules:
 /// - `renderer` — Real-time audio render callback (audio thread)
+/// - `mixer` — Per-track mixing shared by the callback and offline renders
 /// - `offline` — Offline rendering for export/bounce
 /// - `project` — Project serialization (save/load)
 /// - `device` — Audio device selection, buffer size, latency
+/// - `diagnostics` — Engine state snapshot for bug reports
 mod renderer;
+mod mixer;
 mod offline;
 mod project;
 mod device;
+mod diagnostics;
+mod freeze;
+
+pub use diagnostics::EngineDiagnostics;
+pub use mixer::{InputFrame, MAX_INPUT_CHANNELS};
 
 use crate::audio_file::{AudioClip, TARGET_SAMPLE_RATE};
 use crate::midi::MidiClip;
+use crate::sampler::WarpStretch;
 use crate::synth::TrackSynthManager;
 use crate::track::{AutomationPoint, ClipId, TimelineClip, TimelineMidiClip, TrackId, TrackManager};  // Import from track module
 use crate::effects::{EffectManager, Limiter};  // Import from effects module
+use crate::loudness::LoudnessReadings;
+use crate::spectrum::SpectrumReadings;
+use crate::true_peak::TruePeakReadings;
+use crate::dsp_load::DspLoadReadings;
+use crate::reference::ReferenceTrack;
+use crate::markers::MarkerList;
+use crate::scrub::{ScrubPlayer, MAX_SCRUB_WINDOW_MS, MIN_SCRUB_WINDOW_MS};
 use std::sync::Arc;
 use parking_lot::Mutex;
-use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering};
 
 // Native-only imports
 #[cfg(not(target_arch = "wasm32"))]
@@ -50,6 +66,42 @@ impl TransportState {
     }
 }
 
+/// How the performer hears their input while recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorMode {
+    /// No input monitoring
+    Off = 0,
+    /// The engine plays armed tracks' input (where `input_monitoring` is on),
+    /// heard one input/output round trip late
+    Software = 1,
+    /// The audio interface monitors the input directly, without latency; the engine
+    /// keeps the input silent so it isn't heard twice
+    DirectHint = 2,
+}
+
+impl MonitorMode {
+    /// Convert from the atomic/FFI value
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(MonitorMode::Off),
+            1 => Some(MonitorMode::Software),
+            2 => Some(MonitorMode::DirectHint),
+            _ => None,
+        }
+    }
+}
+
+/// State of the audio output device stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioDeviceStatus {
+    /// The output stream is running
+    Running = 0,
+    /// The output device went away; the stream is rebuilt when a device is available
+    Disconnected = 1,
+    /// No output stream (headless engine, or a rebuild failed)
+    NoStream = 2,
+}
+
 /// Buffer size presets for audio latency control
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BufferSizePreset {
@@ -88,24 +140,23 @@ impl BufferSizePreset {
     }
 }
 
-/// Interpolate volume gain from automation curve at a specific time
+/// Interpolate an automation curve at a specific time
 /// Uses binary search and linear interpolation for efficient per-frame lookup
-pub(crate) fn interpolate_automation_gain(automation: &[AutomationPoint], time_seconds: f64) -> f32 {
+/// Holds the first/last value outside the curve; returns 0.0 for an empty curve
+pub(crate) fn interpolate_automation_value(automation: &[AutomationPoint], time_seconds: f64) -> f32 {
     if automation.is_empty() {
-        return 1.0; // Unity gain fallback
+        return 0.0;
     }
 
     // Before first point - use first point's value
     if time_seconds <= automation[0].time_seconds {
-        let db = automation[0].value_db;
-        return if db <= -96.0 { 0.0 } else { 10_f32.powf(db / 20.0) };
+        return automation[0].value;
     }
 
     // After last point - use last point's value
     let last_idx = automation.len() - 1;
     if time_seconds >= automation[last_idx].time_seconds {
-        let db = automation[last_idx].value_db;
-        return if db <= -96.0 { 0.0 } else { 10_f32.powf(db / 20.0) };
+        return automation[last_idx].value;
     }
 
     // Binary search for surrounding points
@@ -125,11 +176,25 @@ pub(crate) fn interpolate_automation_gain(automation: &[AutomationPoint], time_s
     let p1 = &automation[low];
     let p2 = &automation[high];
     let t = (time_seconds - p1.time_seconds) / (p2.time_seconds - p1.time_seconds);
-    let db = p1.value_db + (p2.value_db - p1.value_db) * t as f32;
+    p1.value + (p2.value - p1.value) * t as f32
+}
+
+/// Interpolate volume gain from automation curve at a specific time
+/// Curve values are in dB; returns linear gain
+pub(crate) fn interpolate_automation_gain(automation: &[AutomationPoint], time_seconds: f64) -> f32 {
+    if automation.is_empty() {
+        return 1.0; // Unity gain fallback
+    }
 
+    let db = interpolate_automation_value(automation, time_seconds);
     if db <= -96.0 { 0.0 } else { 10_f32.powf(db / 20.0) }
 }
 
+/// Interpolate pan gains (left, right) from a pan automation curve at a specific time
+pub(crate) fn interpolate_automation_pan_gains(automation: &[AutomationPoint], time_seconds: f64) -> (f32, f32) {
+    crate::track::Track::pan_gains(interpolate_automation_value(automation, time_seconds))
+}
+
 /// The main audio graph that manages playback
 pub struct AudioGraph {
     /// All audio clips on the timeline (legacy - will migrate to tracks)
@@ -142,6 +207,12 @@ pub struct AudioGraph {
     pub(crate) play_start_position_samples: Arc<AtomicU64>,
     /// Position when recording actually started (after count-in, for Stop button during recording)
     pub(crate) record_start_position_samples: Arc<AtomicU64>,
+    /// Loop region start in seconds (timeline position)
+    pub(crate) loop_start_seconds: Arc<Mutex<f64>>,
+    /// Loop region end in seconds (timeline position)
+    pub(crate) loop_end_seconds: Arc<Mutex<f64>>,
+    /// Whether playback wraps from loop end back to loop start
+    pub(crate) loop_enabled: Arc<AtomicBool>,
     /// Transport state (atomic: 0=Stopped, 1=Playing, 2=Paused)
     pub(crate) state: Arc<AtomicU8>,
     /// Audio output stream (kept alive) - native only
@@ -168,6 +239,20 @@ pub struct AudioGraph {
     pub effect_manager: Arc<Mutex<EffectManager>>,
     /// Master limiter (prevents clipping)
     pub master_limiter: Arc<Mutex<Limiter>>,
+    /// Master loudness readings (LUFS), updated by the audio callback
+    pub master_loudness: Arc<LoudnessReadings>,
+    /// Master spectrum analyzer output (FFT magnitudes)
+    pub master_spectrum: Arc<SpectrumReadings>,
+    /// Master true-peak readings (dBTP), updated by the audio callback when enabled
+    pub master_true_peak: Arc<TruePeakReadings>,
+    /// Audio callback DSP load and xrun count, updated by the audio callback
+    pub dsp_load: Arc<DspLoadReadings>,
+    /// Scrub grain looped by the audio callback while the transport is stopped
+    pub scrub_player: Arc<Mutex<ScrubPlayer>>,
+    /// Reference track that replaces the master output while A/B is on
+    pub reference_track: Arc<Mutex<ReferenceTrack>>,
+    /// Named timeline markers
+    pub markers: Arc<Mutex<MarkerList>>,
 
     // --- M6: Per-Track Synthesizers ---
     /// Per-track synthesizer manager
@@ -186,10 +271,28 @@ pub struct AudioGraph {
     // --- Device Selection ---
     /// Selected output device name (None = use system default)
     pub(crate) selected_output_device: Arc<Mutex<Option<String>>>,
+    /// Set by the stream error callback when the output device disappears - native only
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) output_device_lost: Arc<AtomicBool>,
+    /// The stream runs on the default device because the selected one was missing - native only
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) output_on_fallback: Arc<AtomicBool>,
+    /// Set when the output stream is lost or rebuilt, until the UI takes it - native only
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) output_device_changed: Arc<AtomicBool>,
 
     // --- Latency Testing --- (native only)
     #[cfg(not(target_arch = "wasm32"))]
     pub latency_test: Arc<crate::latency_test::LatencyTest>,
+
+    // --- Recording Monitoring --- (native only)
+    /// `MonitorMode` as u8, read by the audio callback
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) monitor_mode: Arc<AtomicU8>,
+    /// Samples recorded takes are moved earlier by when recording stops; set by each
+    /// successful latency test, or by hand
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) recording_latency_compensation_samples: Arc<AtomicU64>,
 }
 
 // SAFETY: AudioGraph is stored in a Mutex<Option<AudioGraph>> in the API layer.
@@ -204,6 +307,29 @@ impl AudioGraph {
     /// Create a new audio graph (native platforms)
     #[cfg(not(target_arch = "wasm32"))]
     pub fn new() -> anyhow::Result<Self> {
+        let mut graph = Self::new_headless()?;
+
+        // Create audio stream immediately (prevents deadlock on first play)
+        eprintln!("🔊 [AudioGraph] Creating audio stream during initialization...");
+        let stream = graph.create_audio_stream()?;
+        // Keep stream running even when stopped - needed for real-time MIDI preview
+        // The callback checks transport state to decide whether to advance playhead
+        stream.play()?;
+        graph.stream = Some(stream);
+        eprintln!("✅ [AudioGraph] Audio stream created and running (for MIDI preview)");
+
+        // Query hardware latency from CoreAudio device
+        if let Err(e) = graph.query_coreaudio_latency() {
+            eprintln!("⚠️ [AudioGraph] Failed to query hardware latency: {e}");
+        }
+
+        Ok(graph)
+    }
+
+    /// Create an audio graph without an output stream (native platforms)
+    /// Used for offline rendering and tests where no audio device is available
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new_headless() -> anyhow::Result<Self> {
         let mut input_manager = AudioInputManager::new()?;
         // Enumerate devices on creation
         let _ = input_manager.enumerate_devices();
@@ -220,12 +346,18 @@ impl AudioGraph {
         let effect_manager = EffectManager::new();
         let master_limiter = Limiter::new();
 
-        let mut graph = Self {
+        // Latency test results feed recording latency compensation
+        let recording_latency_compensation_samples = Arc::new(AtomicU64::new(0));
+
+        let graph = Self {
             clips: Arc::new(Mutex::new(Vec::new())),
             midi_clips: Arc::new(Mutex::new(Vec::new())),
             playhead_samples,
             play_start_position_samples: Arc::new(AtomicU64::new(0)),
             record_start_position_samples: Arc::new(AtomicU64::new(0)),
+            loop_start_seconds: Arc::new(Mutex::new(0.0)),
+            loop_end_seconds: Arc::new(Mutex::new(0.0)),
+            loop_enabled: Arc::new(AtomicBool::new(false)),
             state: Arc::new(AtomicU8::new(TransportState::Stopped as u8)),
             stream: None,
             next_clip_id: Arc::new(Mutex::new(0)),
@@ -236,29 +368,30 @@ impl AudioGraph {
             track_manager: Arc::new(Mutex::new(track_manager)),
             effect_manager: Arc::new(Mutex::new(effect_manager)),
             master_limiter: Arc::new(Mutex::new(master_limiter)),
+            master_loudness: Arc::new(LoudnessReadings::new()),
+            master_spectrum: Arc::new(SpectrumReadings::new()),
+            master_true_peak: Arc::new(TruePeakReadings::new()),
+            dsp_load: Arc::new(DspLoadReadings::new()),
+            scrub_player: Arc::new(Mutex::new(ScrubPlayer::new())),
+            reference_track: Arc::new(Mutex::new(ReferenceTrack::new())),
+            markers: Arc::new(Mutex::new(MarkerList::new())),
             track_synth_manager: Arc::new(Mutex::new(TrackSynthManager::new(TARGET_SAMPLE_RATE as f32))),
             preferred_buffer_size: Arc::new(Mutex::new(BufferSizePreset::Balanced)),
             actual_buffer_size: Arc::new(std::sync::atomic::AtomicU32::new(0)),
             hardware_input_latency_ms: Arc::new(Mutex::new(0.0)),
             hardware_output_latency_ms: Arc::new(Mutex::new(0.0)),
             selected_output_device: Arc::new(Mutex::new(None)),
-            latency_test: Arc::new(crate::latency_test::LatencyTest::new(TARGET_SAMPLE_RATE)),
+            output_device_lost: Arc::new(AtomicBool::new(false)),
+            output_on_fallback: Arc::new(AtomicBool::new(false)),
+            output_device_changed: Arc::new(AtomicBool::new(false)),
+            latency_test: Arc::new(
+                crate::latency_test::LatencyTest::new(TARGET_SAMPLE_RATE)
+                    .with_result_target(recording_latency_compensation_samples.clone()),
+            ),
+            monitor_mode: Arc::new(AtomicU8::new(MonitorMode::Software as u8)),
+            recording_latency_compensation_samples,
         };
 
-        // Create audio stream immediately (prevents deadlock on first play)
-        eprintln!("🔊 [AudioGraph] Creating audio stream during initialization...");
-        let stream = graph.create_audio_stream()?;
-        // Keep stream running even when stopped - needed for real-time MIDI preview
-        // The callback checks transport state to decide whether to advance playhead
-        stream.play()?;
-        graph.stream = Some(stream);
-        eprintln!("✅ [AudioGraph] Audio stream created and running (for MIDI preview)");
-
-        // Query hardware latency from CoreAudio device
-        if let Err(e) = graph.query_coreaudio_latency() {
-            eprintln!("⚠️ [AudioGraph] Failed to query hardware latency: {e}");
-        }
-
         Ok(graph)
     }
 
@@ -279,11 +412,21 @@ impl AudioGraph {
             playhead_samples,
             play_start_position_samples: Arc::new(AtomicU64::new(0)),
             record_start_position_samples: Arc::new(AtomicU64::new(0)),
+            loop_start_seconds: Arc::new(Mutex::new(0.0)),
+            loop_end_seconds: Arc::new(Mutex::new(0.0)),
+            loop_enabled: Arc::new(AtomicBool::new(false)),
             state: Arc::new(AtomicU8::new(TransportState::Stopped as u8)),
             next_clip_id: Arc::new(Mutex::new(0)),
             track_manager: Arc::new(Mutex::new(track_manager)),
             effect_manager: Arc::new(Mutex::new(effect_manager)),
             master_limiter: Arc::new(Mutex::new(master_limiter)),
+            master_loudness: Arc::new(LoudnessReadings::new()),
+            master_spectrum: Arc::new(SpectrumReadings::new()),
+            master_true_peak: Arc::new(TruePeakReadings::new()),
+            dsp_load: Arc::new(DspLoadReadings::new()),
+            scrub_player: Arc::new(Mutex::new(ScrubPlayer::new())),
+            reference_track: Arc::new(Mutex::new(ReferenceTrack::new())),
+            markers: Arc::new(Mutex::new(MarkerList::new())),
             track_synth_manager: Arc::new(Mutex::new(TrackSynthManager::new(TARGET_SAMPLE_RATE as f32))),
             preferred_buffer_size: Arc::new(Mutex::new(BufferSizePreset::Balanced)),
             actual_buffer_size: Arc::new(std::sync::atomic::AtomicU32::new(0)),
@@ -305,23 +448,7 @@ impl AudioGraph {
             id
         };
 
-        clips.push(TimelineClip {
-            id,
-            clip,
-            start_time,
-            offset: 0.0,
-            duration: None,
-            gain_db: 0.0,
-            warp_enabled: false,
-            stretch_factor: 1.0,
-            warp_mode: 0,
-            stretched_cache: None,
-            cached_stretch_factor: 0.0,
-            transpose_semitones: 0,
-            transpose_cents: 0,
-            volume_automation: Vec::new(),
-            pan_automation: Vec::new(),
-        });
+        clips.push(TimelineClip::new(id, clip, start_time));
 
         id
     }
@@ -374,21 +501,9 @@ impl AudioGraph {
         if let Some(track_arc) = track_manager.get_track(track_id) {
             let mut track = track_arc.lock();
             track.audio_clips.push(TimelineClip {
-                id,
-                clip,
-                start_time,
                 offset,
                 duration,
-                gain_db: 0.0,
-                warp_enabled: false,
-                stretch_factor: 1.0,
-                warp_mode: 0,
-                stretched_cache: None,
-                cached_stretch_factor: 0.0,
-                transpose_semitones: 0,
-                transpose_cents: 0,
-                volume_automation: Vec::new(),
-                pan_automation: Vec::new(),
+                ..TimelineClip::new(id, clip, start_time)
             });
             Some(id)
         } else {
@@ -411,21 +526,9 @@ impl AudioGraph {
         if let Some(track_arc) = track_manager.get_track(track_id) {
             let mut track = track_arc.lock();
             track.audio_clips.push(TimelineClip {
-                id: clip_id,
-                clip,
-                start_time,
                 offset,
                 duration,
-                gain_db: 0.0,
-                warp_enabled: false,
-                stretch_factor: 1.0,
-                warp_mode: 0,
-                stretched_cache: None,
-                cached_stretch_factor: 0.0,
-                transpose_semitones: 0,
-                transpose_cents: 0,
-                volume_automation: Vec::new(),
-                pan_automation: Vec::new(),
+                ..TimelineClip::new(clip_id, clip, start_time)
             });
             true
         } else {
@@ -477,6 +580,60 @@ impl AudioGraph {
         }
     }
 
+    /// Give every placed MIDI clip the time signature of the bar it starts in, so
+    /// its duration snaps to bars of the local signature
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn refresh_midi_clip_time_signatures(&self) {
+        let starts: Vec<(ClipId, f64)> = {
+            let track_manager = self.track_manager.lock();
+            track_manager
+                .get_all_tracks()
+                .iter()
+                .flat_map(|track_arc| track_arc.lock().midi_clips.iter().map(|c| (c.id, c.start_time)).collect::<Vec<_>>())
+                .collect()
+        };
+
+        let mut changed = Vec::new();
+        {
+            let mut midi_clips = self.midi_clips.lock();
+            for (clip_id, start_time) in starts {
+                let signature = self.recorder.time_signature_at_seconds(start_time);
+                if let Some(timeline_clip) = midi_clips.iter_mut().find(|c| c.id == clip_id) {
+                    if timeline_clip.clip.time_signature != signature {
+                        Arc::make_mut(&mut timeline_clip.clip).time_signature = signature;
+                        changed.push(clip_id);
+                    }
+                }
+            }
+        }
+        for clip_id in changed {
+            self.sync_midi_clip_to_track(clip_id);
+        }
+    }
+
+    /// Replace the audio an audio clip plays (e.g. after a channel conversion)
+    /// Updates the legacy timeline and the track copy; returns false if no clip has the ID
+    pub fn replace_audio_clip_data(&self, clip_id: ClipId, clip: &Arc<AudioClip>) -> bool {
+        let mut found = false;
+        for timeline_clip in self.clips.lock().iter_mut().filter(|c| c.id == clip_id) {
+            timeline_clip.clip = clip.clone();
+            found = true;
+        }
+
+        let track_manager = self.track_manager.lock();
+        for track_arc in track_manager.get_all_tracks() {
+            let mut track = track_arc.lock();
+            if let Some(timeline_clip) = track.audio_clips.iter_mut().find(|c| c.id == clip_id) {
+                timeline_clip.clip = clip.clone();
+                // The warp/transpose cache was built from the old audio
+                timeline_clip.clear_stretched_cache();
+                timeline_clip.rebuild_stretched_cache();
+                found = true;
+            }
+        }
+        found
+    }
+
     /// Remove a clip from the timeline (audio or MIDI)
     pub fn remove_clip(&self, clip_id: ClipId) -> bool {
         // Try to remove from audio clips
@@ -527,6 +684,14 @@ impl AudioGraph {
         self.playhead_samples.store(samples, Ordering::SeqCst);
     }
 
+    /// Pass a new project tempo to the instruments. Warp mode samplers are re-stretched
+    /// between two short locks of the synth manager, not while holding it.
+    pub fn set_instrument_tempo(&self, bpm: f64) {
+        let stretches = self.track_synth_manager.lock().warp_stretches_for_tempo(bpm);
+        let stretches: Vec<_> = stretches.into_iter().map(WarpStretch::build).collect();
+        self.track_synth_manager.lock().set_project_tempo_prestretched(bpm, &stretches);
+    }
+
     /// Get the position when Play was pressed (in seconds)
     pub fn get_play_start_position(&self) -> f64 {
         let samples = self.play_start_position_samples.load(Ordering::SeqCst);
@@ -568,6 +733,56 @@ impl AudioGraph {
         self.recorder.seek_metronome(samples);
     }
 
+    /// Seek, also clearing every effect (delay lines, reverb tails, envelopes) and
+    /// the master limiter so nothing from the old position bleeds into the new one
+    ///
+    /// `seek` leaves effect tails running, which suits scrubbing; use this one to
+    /// jump the transport.
+    pub fn seek_clean(&self, position_seconds: f64) {
+        self.seek(position_seconds);
+        self.effect_manager.lock().reset_all();
+        crate::effects::Effect::reset(&mut *self.master_limiter.lock());
+    }
+
+    /// Play a short looped grain of the mix around `position_seconds`, like tape
+    /// scrubbing, without moving the transport (see `crate::scrub`)
+    ///
+    /// The grain is `window_ms` long (clamped to the scrub window limits) and is
+    /// rendered with the fast preview path on copies of the synths and effects, so
+    /// the live ones are left alone. Only heard while the transport is stopped.
+    pub fn scrub_to(&self, position_seconds: f64, window_ms: f64) {
+        let window_seconds = window_ms.clamp(MIN_SCRUB_WINDOW_MS, MAX_SCRUB_WINDOW_MS) / 1000.0;
+        let start = (position_seconds - window_seconds / 2.0).max(0.0);
+        let grain = self.render_scrub_grain(start, start + window_seconds);
+        self.scrub_player.lock().queue_grain(grain);
+    }
+
+    /// Stop scrubbing once the current grain finishes
+    pub fn stop_scrub(&self) {
+        self.scrub_player.lock().stop();
+    }
+
+    /// Set the loop region (timeline seconds)
+    pub fn set_loop_region(&self, start_seconds: f64, end_seconds: f64) {
+        *self.loop_start_seconds.lock() = start_seconds;
+        *self.loop_end_seconds.lock() = end_seconds;
+    }
+
+    /// Get the loop region as (start, end) in seconds
+    pub fn get_loop_region(&self) -> (f64, f64) {
+        (*self.loop_start_seconds.lock(), *self.loop_end_seconds.lock())
+    }
+
+    /// Enable or disable loop playback
+    pub fn set_loop_enabled(&self, enabled: bool) {
+        self.loop_enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Check if loop playback is enabled
+    pub fn is_loop_enabled(&self) -> bool {
+        self.loop_enabled.load(Ordering::SeqCst)
+    }
+
     /// Get current transport state
     pub fn get_state(&self) -> TransportState {
         TransportState::from_u8(self.state.load(Ordering::SeqCst))
@@ -581,12 +796,7 @@ impl AudioGraph {
             return Ok(()); // Already playing
         }
 
-        // Save current playhead position as play start position
-        let current_pos = self.playhead_samples.load(Ordering::SeqCst);
-        self.play_start_position_samples.store(current_pos, Ordering::SeqCst);
-        eprintln!("▶️  [AudioGraph] play() - saving play_start_position: {} samples ({:.3}s)",
-            current_pos, current_pos as f64 / f64::from(TARGET_SAMPLE_RATE));
-
+        self.prepare_play();
         self.state.store(TransportState::Playing as u8, Ordering::SeqCst);
 
         // Stream is always running (for MIDI preview) - no need to start/stop it
@@ -595,9 +805,47 @@ impl AudioGraph {
         Ok(())
     }
 
+    /// Start playback after a metronome count-in of `count_in_bars` bars
+    /// The audio callback flips the transport to Playing once the count-in ends.
+    /// Returns true if a count-in was started, false if playback started immediately.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn play_with_count_in(&mut self) -> anyhow::Result<bool> {
+        if self.state.load(Ordering::SeqCst) == TransportState::Playing as u8
+            || self.recorder.is_playback_counting_in()
+        {
+            return Ok(false);
+        }
+
+        self.prepare_play();
+        if self.recorder.start_playback_count_in().map_err(anyhow::Error::msg)? {
+            eprintln!("▶️  [AudioGraph] play_with_count_in() - counting in {} bars", self.recorder.get_count_in_bars());
+            Ok(true)
+        } else {
+            self.state.store(TransportState::Playing as u8, Ordering::SeqCst);
+            Ok(false)
+        }
+    }
+
+    /// Reset loudness and remember where playback starts
+    fn prepare_play(&self) {
+        self.scrub_player.lock().cancel();
+
+        // Start a fresh integrated loudness measurement and true-peak maximum
+        self.master_loudness.request_reset();
+        self.master_true_peak.request_reset();
+
+        // Save current playhead position as play start position
+        let current_pos = self.playhead_samples.load(Ordering::SeqCst);
+        self.play_start_position_samples.store(current_pos, Ordering::SeqCst);
+        eprintln!("▶️  [AudioGraph] play() - saving play_start_position: {} samples ({:.3}s)",
+            current_pos, current_pos as f64 / f64::from(TARGET_SAMPLE_RATE));
+    }
+
     /// Pause playback (keeps position) - lock-free state change
     pub fn pause(&mut self) -> anyhow::Result<()> {
         eprintln!("⏸️  [AudioGraph] pause() called");
+        #[cfg(not(target_arch = "wasm32"))]
+        self.recorder.cancel_playback_count_in();
         self.state.store(TransportState::Paused as u8, Ordering::SeqCst);
         // Stream keeps running for MIDI preview
 
@@ -639,6 +887,8 @@ impl AudioGraph {
     pub fn stop(&mut self) -> anyhow::Result<()> {
         eprintln!("⏹️  [AudioGraph] stop() called - silencing notes and stopping metronome");
 
+        #[cfg(not(target_arch = "wasm32"))]
+        self.recorder.cancel_playback_count_in();
         self.state.store(TransportState::Stopped as u8, Ordering::SeqCst);
         // Stream keeps running for MIDI preview
 
@@ -728,6 +978,7 @@ mod tests {
             sample_rate: TARGET_SAMPLE_RATE,
             duration_seconds: duration,
             file_path: "test.wav".to_string(),
+            stream: None,
         }
     }
 
@@ -777,4 +1028,210 @@ mod tests {
         graph.stop().unwrap();
         assert_eq!(graph.get_state(), TransportState::Stopped);
     }
+
+    #[test]
+    fn test_seek_clean_clears_effect_tails() {
+        let graph = AudioGraph::new_headless().unwrap();
+        let track_id = graph.track_manager.lock()
+            .create_track(crate::track::TrackType::Audio, "Delay".to_string());
+        let effect_id = graph.effect_manager.lock()
+            .create_effect(crate::effects::EffectType::Delay(crate::effects::Delay::new()));
+        graph.track_manager.lock().get_track(track_id).unwrap().lock().fx_chain.push(effect_id);
+        let effect = graph.effect_manager.lock().get_effect(effect_id).unwrap();
+
+        // Play an impulse into the delay, then listen for its echo (500 ms later)
+        let echo_after_seek = |seek: &dyn Fn(&AudioGraph)| {
+            { let mut delay = effect.lock();
+                delay.process_frame(1.0, 1.0);
+                for _ in 0..1000 {
+                    delay.process_frame(0.0, 0.0);
+                }
+            }
+            seek(&graph);
+            let mut delay = effect.lock();
+            (0..TARGET_SAMPLE_RATE)
+                .map(|_| delay.process_frame(0.0, 0.0).0.abs())
+                .fold(0.0f32, f32::max)
+        };
+
+        // A plain seek keeps the tail (for scrubbing); a clean seek clears it
+        assert!(echo_after_seek(&|graph| graph.seek(2.0)) > 0.1);
+        assert!(echo_after_seek(&|graph| graph.seek_clean(2.0)) < 1e-9);
+        assert!((graph.get_playhead_position() - 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_offline_render_removes_latency() {
+        // A clip at t=0 on a track with a lookahead limiter, next to a plain track,
+        // through the master limiter's own lookahead
+        let graph = AudioGraph::new_headless().unwrap();
+        let latent_track = graph.track_manager.lock()
+            .create_track(crate::track::TrackType::Audio, "Latent".to_string());
+        let plain_track = graph.track_manager.lock()
+            .create_track(crate::track::TrackType::Audio, "Plain".to_string());
+        let clip = Arc::new(create_test_clip(0.5));
+        graph.add_clip_to_track(latent_track, clip.clone(), 0.0).unwrap();
+        graph.add_clip_to_track(plain_track, clip, 0.0).unwrap();
+        let limiter_id = graph.effect_manager.lock()
+            .create_effect(crate::effects::EffectType::Limiter(Limiter::new()));
+        graph.track_manager.lock().get_track(latent_track).unwrap().lock().fx_chain.push(limiter_id);
+        assert!(graph.get_reported_latency() > 0);
+
+        let output = graph.render_offline(1.0);
+        assert_eq!(output.len(), TARGET_SAMPLE_RATE as usize * 2);
+        assert!(output[0].abs() > 0.05, "mix starts late: first sample {}", output[0]);
+        // The clip ends at 0.5 s, not a latency later
+        let end = TARGET_SAMPLE_RATE as usize; // frame 24000, interleaved
+        assert!(output[end - 2].abs() > 0.05 && output[end + 2].abs() < 1e-6);
+
+        let stem = graph.render_track_offline(latent_track, 1.0);
+        assert!(stem[0].abs() > 0.05, "stem starts late: first sample {}", stem[0]);
+    }
+
+    #[test]
+    fn test_scrub_loops_a_grain_without_moving_the_playhead() {
+        let graph = AudioGraph::new_headless().unwrap();
+        let track_id = graph.track_manager.lock()
+            .create_track(crate::track::TrackType::Audio, "Audio".to_string());
+        graph.add_clip_to_track(track_id, Arc::new(create_test_clip(2.0)), 0.0).unwrap();
+        graph.seek(0.25);
+
+        graph.scrub_to(1.0, 40.0);
+        let mut scrub = graph.scrub_player.lock();
+        assert!(scrub.is_playing());
+        let peak = (0..TARGET_SAMPLE_RATE / 25).map(|_| scrub.next_frame().0.abs()).fold(0.0f32, f32::max);
+        assert!(peak > 0.01, "grain peak {peak}");
+        drop(scrub);
+        assert!((graph.get_playhead_position() - 0.25).abs() < 1e-3);
+
+        graph.stop_scrub();
+        let mut scrub = graph.scrub_player.lock();
+        for _ in 0..TARGET_SAMPLE_RATE / 25 {
+            scrub.next_frame();
+        }
+        assert!(!scrub.is_playing());
+    }
+
+    #[test]
+    fn test_scrub_grain_leaves_live_synths_and_effects_alone() {
+        use crate::midi::{MidiClip, MidiEvent, MidiEventType};
+        let graph = AudioGraph::new_headless().unwrap();
+        let track_id = graph.track_manager.lock()
+            .create_track(crate::track::TrackType::Midi, "Synth".to_string());
+        graph.track_synth_manager.lock().create_synth(track_id);
+        let effect_id = graph.effect_manager.lock()
+            .create_effect(crate::effects::EffectType::Delay(crate::effects::Delay::new()));
+        graph.track_manager.lock().get_track(track_id).unwrap().lock().fx_chain.push(effect_id);
+
+        // A note starting inside the grain and held well past it
+        let mut clip = MidiClip::new(TARGET_SAMPLE_RATE);
+        clip.add_event(MidiEvent::new(MidiEventType::NoteOn { note: 60, velocity: 100 }, 4800));
+        clip.add_event(MidiEvent::new(MidiEventType::NoteOff { note: 60, velocity: 0 }, 96_000));
+        graph.add_midi_clip_to_track(track_id, Arc::new(clip), 0.0, 1).unwrap();
+
+        graph.scrub_to(0.1, 100.0);
+        let mut scrub = graph.scrub_player.lock();
+        let peak = (0..TARGET_SAMPLE_RATE / 10).map(|_| scrub.next_frame().0.abs()).fold(0.0f32, f32::max);
+        assert!(peak > 1e-3, "grain peak {peak}");
+        drop(scrub);
+
+        // The live synth never got the note, and the live delay line is still empty
+        let (left, right) = graph.track_synth_manager.lock().process_sample_stereo(track_id);
+        assert!(left.abs() < 1e-9 && right.abs() < 1e-9, "live synth is sounding: ({left}, {right})");
+        let effect = graph.effect_manager.lock().get_effect(effect_id).unwrap();
+        let mut delay = effect.lock();
+        let echo = (0..TARGET_SAMPLE_RATE).map(|_| delay.process_frame(0.0, 0.0).0.abs()).fold(0.0f32, f32::max);
+        assert!(echo < 1e-9, "grain echoed through the live delay: {echo}");
+    }
+
+    /// Build a project with several tracks, each running two reverbs over a decaying tone
+    fn create_heavy_project() -> AudioGraph {
+        let graph = AudioGraph::new_headless().unwrap();
+        let frames = TARGET_SAMPLE_RATE as usize;
+        let mut samples = Vec::with_capacity(frames * 2);
+        for i in 0..frames {
+            let t = i as f32 / TARGET_SAMPLE_RATE as f32;
+            let s = (t * 220.0 * std::f32::consts::TAU).sin() * (1.0 - t) * 0.2;
+            samples.push(s);
+            samples.push(s);
+        }
+        let clip = Arc::new(AudioClip {
+            samples,
+            channels: 2,
+            sample_rate: TARGET_SAMPLE_RATE,
+            duration_seconds: 1.0,
+            file_path: "tone.wav".to_string(),
+            stream: None,
+        });
+
+        for i in 0..8 {
+            let track_id = graph.track_manager.lock()
+                .create_track(crate::track::TrackType::Audio, format!("Track {i}"));
+            graph.add_clip_to_track(track_id, clip.clone(), 0.0).unwrap();
+            for _ in 0..2 {
+                let effect_id = graph.effect_manager.lock()
+                    .create_effect(crate::effects::EffectType::Reverb(crate::effects::Reverb::new()));
+                let track_arc = graph.track_manager.lock().get_track(track_id).unwrap();
+                track_arc.lock().fx_chain.push(effect_id);
+            }
+        }
+        graph
+    }
+
+    /// RMS of each 100ms block of an interleaved stereo buffer
+    fn block_rms(samples: &[f32]) -> Vec<f32> {
+        let block = TARGET_SAMPLE_RATE as usize / 10 * 2;
+        samples
+            .chunks(block)
+            .map(|c| (c.iter().map(|s| s * s).sum::<f32>() / c.len() as f32).sqrt())
+            .collect()
+    }
+
+    #[test]
+    fn test_render_offline_preview_matches_final_envelope() {
+        let graph = create_heavy_project();
+
+        // Half a second past the end of the 1s clips, where only reverb tails are left
+        let full = graph.render_offline(1.5);
+
+        { let effect_mgr = graph.effect_manager.lock();
+            for effect_id in effect_mgr.get_all_effect_ids() {
+                if let Some(effect_arc) = effect_mgr.get_effect(effect_id) {
+                    effect_arc.lock().reset();
+                }
+            }
+        }
+        crate::effects::Effect::reset(&mut *graph.master_limiter.lock());
+
+        let preview = graph.render_offline_preview(0.0, 1.5);
+
+        assert_eq!(preview.len(), full.len());
+        let (preview_rms, full_rms) = (block_rms(&preview), block_rms(&full));
+        for (p, f) in preview_rms.iter().zip(&full_rms).take(10) {
+            assert!((p - f).abs() <= f * 0.5 + 0.01, "preview rms {p} vs full rms {f}");
+        }
+        // Reverb tails follow the final envelope within 6 dB as they decay (the
+        // speed-up is measured by the `offline_render` bench)
+        assert!(full_rms[10] > 1e-4, "no reverb tail in the full render: {}", full_rms[10]);
+        for (block, (p, f)) in preview_rms.iter().zip(&full_rms).enumerate().skip(10) {
+            assert!((0.5..=2.0).contains(&(p / f)), "block {block}: preview tail rms {p} vs full rms {f}");
+        }
+    }
+
+    #[test]
+    fn test_lost_output_device_is_reported_until_rebuilt() {
+        let graph = AudioGraph::new_headless().unwrap();
+        assert_eq!(graph.get_audio_device_status(), AudioDeviceStatus::NoStream);
+        assert!(!graph.take_audio_device_changed());
+
+        // What the stream error callback does when the device is unplugged
+        graph.output_device_lost.store(true, Ordering::SeqCst);
+        graph.output_device_changed.store(true, Ordering::SeqCst);
+        assert_eq!(graph.get_audio_device_status(), AudioDeviceStatus::Disconnected);
+
+        // The change is reported once; the status stays until the stream is rebuilt
+        assert!(graph.take_audio_device_changed());
+        assert!(!graph.take_audio_device_changed());
+        assert_eq!(graph.get_audio_device_status(), AudioDeviceStatus::Disconnected);
+    }
 }
@@ -1,42 +1,269 @@
 /// Offline rendering for export and bounce
-use super::{AudioGraph, interpolate_automation_gain};
-use crate::audio_file::{AudioClip, TARGET_SAMPLE_RATE};
-use crate::track::{AutomationPoint, TimelineClip, TimelineMidiClip};
-use crate::effects::Effect;
+use super::AudioGraph;
+use super::mixer::{compensate_latency, process_effect_chain, process_effect_chain_block, process_track_frame, resolve_bus_routing, resolve_latency_compensation, route_to_group, BusInputs, CompensationDelays, InputFrame, StemSource, TrackFrame, TrackSnapshot};
+use super::renderer::{collect_sidechain_sources, mix_sidechain_sources_first, sidechain_level};
+use crate::audio_file::TARGET_SAMPLE_RATE;
+use crate::export::export_progress;
+use std::collections::HashMap;
+use crate::effects::{Effect, EffectManager, Limiter};
+use crate::synth::TrackSynthManager;
+use parking_lot::Mutex;
+
+/// Seconds rendered (and discarded) ahead of a range export when pre-roll is enabled
+const RANGE_PRE_ROLL_SECONDS: f64 = 1.0;
+
+/// Frames between export progress reports and cancellation checks (~100 ms)
+const PROGRESS_REPORT_FRAMES: usize = 4800;
+
+/// Block size (in frames) at which preview renders evaluate automation
+const PREVIEW_BLOCK_FRAMES: usize = 256;
+
+/// Block size (in frames) of master bus processing
+const MASTER_BLOCK_FRAMES: usize = 256;
+
+/// Chunk size (in frames) in which in-memory renders collect their output
+const COLLECT_CHUNK_FRAMES: usize = 4096;
+
+/// Quality setting for offline rendering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenderQuality {
+    /// Full-quality render used for export and bounce
+    Final,
+    /// Fast, lower-quality render for waveform previews and scrubbing.
+    /// Reverbs run half their comb filters, samplers skip sinc resampling
+    /// and automation is evaluated once per block. Never use the result for export.
+    Preview,
+}
+
+impl RenderQuality {
+    /// Number of frames between automation evaluations
+    fn control_block_frames(self) -> usize {
+        match self {
+            RenderQuality::Final => 1,
+            RenderQuality::Preview => PREVIEW_BLOCK_FRAMES,
+        }
+    }
+}
+
+/// Synths, effects and master limiter an offline render plays through
+///
+/// Normally the live ones (`AudioGraph::live_engines`); a scrub grain renders on
+/// copies instead, since the audio callback keeps running the live ones meanwhile.
+struct RenderEngines<'a> {
+    synths: &'a Mutex<TrackSynthManager>,
+    effects: &'a Mutex<EffectManager>,
+    limiter: &'a Mutex<Limiter>,
+}
+
+impl RenderEngines<'_> {
+    /// Run a block of master bus audio through the master FX chain (`fx_chain`, when
+    /// block-processed) and the limiter, append it to `output` and empty the block
+    fn finish_master_block(&self, fx_chain: Option<&[u64]>, left: &mut Vec<f32>, right: &mut Vec<f32>, output: &mut ChunkedOutput<'_>) {
+        if let Some(fx_chain) = fx_chain {
+            process_effect_chain_block(fx_chain, &self.effects.lock(), left, right, false);
+        }
+        let mut limiter = self.limiter.lock();
+        for (&left, &right) in left.iter().zip(right.iter()) {
+            let (limited_left, limited_right) = limiter.process_frame(left, right);
+            output.push(limited_left, limited_right);
+        }
+        left.clear();
+        right.clear();
+    }
+}
+
+/// Interleaved stereo render output, handed to a sink in fixed-size chunks
+struct ChunkedOutput<'a> {
+    buffer: Vec<f32>,
+    chunk_samples: usize,
+    samples_written: usize,
+    /// Frames still to drop from the start (the render's latency)
+    skip_frames: usize,
+    sink: &'a mut dyn FnMut(&[f32]),
+}
+
+impl<'a> ChunkedOutput<'a> {
+    fn new(chunk_frames: usize, skip_frames: usize, sink: &'a mut dyn FnMut(&[f32])) -> Self {
+        let chunk_samples = chunk_frames.max(1) * 2;
+        Self { buffer: Vec::with_capacity(chunk_samples), chunk_samples, samples_written: 0, skip_frames, sink }
+    }
+
+    fn push(&mut self, left: f32, right: f32) {
+        if self.skip_frames > 0 {
+            self.skip_frames -= 1;
+            return;
+        }
+        self.buffer.push(left);
+        self.buffer.push(right);
+        if self.buffer.len() == self.chunk_samples {
+            self.flush();
+        }
+    }
+
+    /// Hand the buffered frames (a full or final partial chunk) to the sink
+    fn flush(&mut self) {
+        if !self.buffer.is_empty() {
+            (self.sink)(&self.buffer);
+            self.samples_written += self.buffer.len();
+            self.buffer.clear();
+        }
+    }
+}
 
 impl AudioGraph {
     // --- Offline Rendering (Export) ---
 
+    fn live_engines(&self) -> RenderEngines<'_> {
+        RenderEngines { synths: &self.track_synth_manager, effects: &self.effect_manager, limiter: &self.master_limiter }
+    }
+
     /// Render the entire project offline to a buffer of stereo f32 samples
     /// Returns interleaved stereo audio (L, R, L, R, ...)
+    /// During an export, progress is reported to `export_progress()`, and a
+    /// cancelled export stops the render early with a truncated result.
     pub fn render_offline(&self, duration_seconds: f64) -> Vec<f32> {
-        // Create track snapshots (same as real-time rendering)
-        struct TrackSnapshot {
-            id: u64,
-            audio_clips: Vec<TimelineClip>,
-            midi_clips: Vec<TimelineMidiClip>,
-            volume_gain: f32, // Static volume (used when no automation)
-            pan_left: f32,
-            pan_right: f32,
-            muted: bool,
-            soloed: bool,
-            fx_chain: Vec<u64>,
-            volume_automation: Vec<AutomationPoint>, // For per-frame interpolation
+        let total_frames = (duration_seconds * f64::from(TARGET_SAMPLE_RATE)) as usize;
+        self.render_offline_frames(&self.live_engines(), 0, total_frames, RenderQuality::Final, None)
+    }
+
+    /// Render the entire project offline, handing it to `sink` a chunk at a time
+    ///
+    /// Each chunk is interleaved stereo of `chunk_frames` frames (the last one may be
+    /// shorter), so memory stays bounded however long the project is. Otherwise the
+    /// same as `render_offline`, including progress reporting and cancellation.
+    pub fn render_offline_streaming(&self, duration_seconds: f64, chunk_frames: usize, mut sink: impl FnMut(&[f32])) {
+        let total_frames = (duration_seconds * f64::from(TARGET_SAMPLE_RATE)) as usize;
+        self.render_offline_chunks(&self.live_engines(), 0, total_frames, RenderQuality::Final, None, chunk_frames, &mut sink);
+    }
+
+    /// Render a time range offline for export
+    ///
+    /// Synth voices, effect tails and the master limiter are reset first, so nothing
+    /// from earlier renders or playback leaks in. With `pre_roll`, up to
+    /// `RANGE_PRE_ROLL_SECONDS` before `start_seconds` is rendered and discarded so
+    /// reverb/delay tails and notes from just before the range carry into it.
+    /// Returns interleaved stereo covering exactly `start_seconds..end_seconds`.
+    pub fn render_offline_range(&self, start_seconds: f64, end_seconds: f64, pre_roll: bool) -> Vec<f32> {
+        let sample_rate = f64::from(TARGET_SAMPLE_RATE);
+        let start_frame = (start_seconds.max(0.0) * sample_rate) as usize;
+        let end_frame = (end_seconds.max(0.0) * sample_rate) as usize;
+        if end_frame <= start_frame {
+            return Vec::new();
         }
+        let render_start = if pre_roll {
+            start_frame.saturating_sub((RANGE_PRE_ROLL_SECONDS * sample_rate) as usize)
+        } else {
+            start_frame
+        };
 
-        let sample_rate = TARGET_SAMPLE_RATE;
-        let total_frames = (duration_seconds * f64::from(sample_rate)) as usize;
+        self.track_synth_manager.lock().all_notes_off_all_tracks();
+        self.effect_manager.lock().reset_all();
+        self.master_limiter.lock().reset();
+
+        let mut output = self.render_offline_frames(&self.live_engines(), render_start, end_frame - render_start, RenderQuality::Final, None);
+        output.drain(..((start_frame - render_start) * 2).min(output.len()));
+        output
+    }
+
+    /// Render a timeline range with the fast preview path (NOT for export)
+    ///
+    /// Output is interleaved stereo at the engine sample rate, like `render_offline`,
+    /// but uses cheaper DSP (see `RenderQuality::Preview`). Notes that started before
+    /// `start_seconds` are not retriggered, so the result is only suitable for
+    /// waveform display and scrubbing.
+    pub fn render_offline_preview(&self, start_seconds: f64, end_seconds: f64) -> Vec<f32> {
+        let sample_rate = f64::from(TARGET_SAMPLE_RATE);
+        let start_frame = (start_seconds.max(0.0) * sample_rate) as usize;
+        let end_frame = (end_seconds.max(0.0) * sample_rate) as usize;
+        self.render_offline_frames(&self.live_engines(), start_frame, end_frame.saturating_sub(start_frame), RenderQuality::Preview, None)
+    }
+
+    /// Render a scrub grain (`start_seconds..end_seconds`) with the preview path, on
+    /// copies of the synths, effects and master limiter
+    ///
+    /// The audio callback keeps running the live ones while the transport is stopped,
+    /// so the grain must not leave notes sounding on them or move their delay lines,
+    /// reverb tails, arpeggiators and sequencers. VST3 effects are skipped (see
+    /// `EffectManager::isolated_copy`).
+    pub fn render_scrub_grain(&self, start_seconds: f64, end_seconds: f64) -> Vec<f32> {
+        let synths = Mutex::new(self.track_synth_manager.lock().clone());
+        let effects = Mutex::new(self.effect_manager.lock().isolated_copy());
+        let limiter = Mutex::new(self.master_limiter.lock().clone());
+        let engines = RenderEngines { synths: &synths, effects: &effects, limiter: &limiter };
+        let sample_rate = f64::from(TARGET_SAMPLE_RATE);
+        let start_frame = (start_seconds.max(0.0) * sample_rate) as usize;
+        let end_frame = (end_seconds.max(0.0) * sample_rate) as usize;
+        self.render_offline_frames(&engines, start_frame, end_frame.saturating_sub(start_frame), RenderQuality::Preview, None)
+    }
+
+    /// Render a track's stem including its wet signal
+    ///
+    /// Only this track plays, through its groups and the returns its sends feed, and
+    /// the result is taken before the master fader and FX. Mute and solo apply as in
+    /// the mix, so the stems of all non-bus tracks sum to the mix.
+    pub fn render_track_with_sends_offline(&self, track_id: u64, duration_seconds: f64) -> Vec<f32> {
+        let total_frames = (duration_seconds * f64::from(TARGET_SAMPLE_RATE)) as usize;
+        self.render_offline_frames(&self.live_engines(), 0, total_frames, RenderQuality::Final, Some(StemSource::WithSends(track_id)))
+    }
+
+    /// Render a group or return track's output with everything routed into it
+    /// Taken post-fader, before the master; the whole project plays as in the mix.
+    pub fn render_bus_offline(&self, track_id: u64, duration_seconds: f64) -> Vec<f32> {
+        let total_frames = (duration_seconds * f64::from(TARGET_SAMPLE_RATE)) as usize;
+        self.render_offline_frames(&self.live_engines(), 0, total_frames, RenderQuality::Final, Some(StemSource::Bus(track_id)))
+    }
+
+    /// Render into one pre-allocated buffer (see `render_offline_chunks`)
+    fn render_offline_frames(
+        &self,
+        engines: &RenderEngines<'_>,
+        start_frame: usize,
+        total_frames: usize,
+        quality: RenderQuality,
+        stem: Option<StemSource>,
+    ) -> Vec<f32> {
         let mut output = Vec::with_capacity(total_frames * 2); // stereo interleaved
+        self.render_offline_chunks(engines, start_frame, total_frames, quality, stem, COLLECT_CHUNK_FRAMES, &mut |chunk| {
+            output.extend_from_slice(chunk);
+        });
+        output
+    }
 
-        eprintln!("🎵 [AudioGraph] Starting offline render: {duration_seconds:.2}s ({total_frames} frames)");
+    /// Shared offline render loop for `render_offline`, `render_offline_streaming`,
+    /// `render_offline_range`, `render_offline_preview` and the bus-aware stem renders
+    ///
+    /// With a `stem`, only its part of the mix is kept and the master bus is skipped.
+    /// The interleaved stereo result goes to `sink` in chunks of `chunk_frames`.
+    /// The render runs on past the end by the mix's latency (delay compensation,
+    /// master FX and limiter lookahead) and that much is dropped from the start, so
+    /// audio at `start_frame` on the timeline lands on the first output frame.
+    fn render_offline_chunks(
+        &self,
+        engines: &RenderEngines<'_>,
+        start_frame: usize,
+        total_frames: usize,
+        quality: RenderQuality,
+        stem: Option<StemSource>,
+        chunk_frames: usize,
+        sink: &mut dyn FnMut(&[f32]),
+    ) {
+        let sample_rate = TARGET_SAMPLE_RATE;
+        let is_final = quality == RenderQuality::Final;
+        let control_block_frames = quality.control_block_frames();
 
-        // Get tempo for timeline positioning
+        if is_final {
+            let duration_seconds = total_frames as f64 / f64::from(sample_rate);
+            eprintln!("🎵 [AudioGraph] Starting offline render: {duration_seconds:.2}s ({total_frames} frames)");
+        }
+
+        // Get the tempo map for timeline positioning
         // Timeline positions are tempo-dependent: at 120 BPM, 1 timeline second = 1 real second
-        let current_tempo = self.recorder.get_tempo();
-        let tempo_ratio = current_tempo / 120.0;
-        eprintln!("🎵 [AudioGraph] Using tempo {current_tempo} BPM (ratio: {tempo_ratio:.3})");
+        let tempo_map = self.recorder.get_tempo_map();
+        if is_final {
+            eprintln!("🎵 [AudioGraph] Using tempo {} BPM ({} tempo changes)", tempo_map.base_tempo(), tempo_map.changes().len() - 1);
+        }
 
-        let (track_snapshots, has_solo, master_snapshot) = {
+        let (mut track_snapshots, has_solo, master_snapshot) = {
             let tm = self.track_manager.lock();
             let has_solo_flag = tm.has_solo();
             let all_tracks = tm.get_all_tracks();
@@ -45,18 +272,7 @@ impl AudioGraph {
 
             for track_arc in all_tracks {
                 { let track = track_arc.lock();
-                    let snap = TrackSnapshot {
-                        id: track.id,
-                        audio_clips: track.audio_clips.clone(),
-                        midi_clips: track.midi_clips.clone(),
-                        volume_gain: track.get_gain(),
-                        pan_left: track.get_pan_gains().0,
-                        pan_right: track.get_pan_gains().1,
-                        muted: track.mute,
-                        soloed: track.solo,
-                        fx_chain: track.fx_chain.clone(),
-                        volume_automation: track.volume_automation.clone(),
-                    };
+                    let snap = TrackSnapshot::new(&track);
 
                     if track.track_type == crate::track::TrackType::Master {
                         master_snap = Some(snap);
@@ -69,197 +285,115 @@ impl AudioGraph {
             (snapshots, has_solo_flag, master_snap)
         };
 
-        eprintln!("🎵 [AudioGraph] Rendering {} tracks", track_snapshots.len());
+        if is_final {
+            eprintln!("🎵 [AudioGraph] Rendering {} tracks", track_snapshots.len());
+        }
+
+        // Sidechain sources are mixed before the tracks that key from them
+        let mut sidechain_sources = Vec::new();
+        collect_sidechain_sources(
+            track_snapshots.iter().map(|snap| snap.fx_chain.as_slice()),
+            &engines.effects.lock(),
+            &mut sidechain_sources,
+        );
+        mix_sidechain_sources_first(&mut track_snapshots, |snap| snap.id, &sidechain_sources);
+        resolve_bus_routing(&mut track_snapshots);
+        let mix_latency = resolve_latency_compensation(&mut track_snapshots, &engines.effects.lock());
+        let latency = if stem.is_some() {
+            mix_latency
+        } else {
+            let master_fx_latency = master_snapshot
+                .as_ref()
+                .map_or(0, |master_snap| engines.effects.lock().chain_latency(&master_snap.fx_chain));
+            mix_latency + master_fx_latency + engines.limiter.lock().latency_samples()
+        };
+        let render_frames = total_frames + latency;
+        let mut output = ChunkedOutput::new(chunk_frames.min(total_frames), latency, sink);
+        // Only tracks that are held back need a line
+        let mut compensation_delays =
+            CompensationDelays::new(track_snapshots.iter().filter(|t| t.compensation_delay > 0).count());
+        let mut sidechain_levels: HashMap<u64, f32> = HashMap::with_capacity(sidechain_sources.len());
+        let mut bus_inputs = BusInputs::new();
+
+        // The master FX chain runs a block at a time, like the real-time callback,
+        // unless it keys from a sidechain or this is a preview render
+        let master_fx_chain = master_snapshot.as_ref().map(|master_snap| master_snap.fx_chain.as_slice());
+        let master_fx_per_block = is_final
+            && master_fx_chain.is_some_and(|fx_chain| !engines.effects.lock().chain_has_sidechain(fx_chain));
+        let block_fx_chain = if master_fx_per_block { master_fx_chain } else { None };
+        let mut master_block_left = Vec::with_capacity(MASTER_BLOCK_FRAMES);
+        let mut master_block_right = Vec::with_capacity(MASTER_BLOCK_FRAMES);
 
         // Process each frame
-        for frame_idx in 0..total_frames {
-            // Apply tempo ratio: at 120 BPM, playhead advances 1:1 with real time
-            let real_seconds = frame_idx as f64 / f64::from(sample_rate);
-            let playhead_seconds = real_seconds * tempo_ratio;
+        for render_idx in 0..render_frames {
+            if is_final && render_idx % PROGRESS_REPORT_FRAMES == 0 {
+                let progress = export_progress();
+                if progress.is_render_cancelled() {
+                    eprintln!("⏹️ [AudioGraph] Offline render cancelled at frame {render_idx}");
+                    break;
+                }
+                progress.report_render(render_idx as f64 / render_frames as f64);
+            }
+
+            let frame_idx = start_frame + render_idx;
+            // Apply the tempo: at 120 BPM, playhead advances 1:1 with real time
+            let playhead_seconds = tempo_map.timeline_seconds_at_sample(frame_idx as u64);
+            let refresh_automation = render_idx % control_block_frames == 0;
 
             let mut mix_left = 0.0f32;
             let mut mix_right = 0.0f32;
+            sidechain_levels.clear();
+            bus_inputs.clear();
 
-            // Mix all tracks
-            for track_snap in &track_snapshots {
-                // Handle mute/solo logic
-                if track_snap.muted {
-                    continue;
-                }
-                if has_solo && !track_snap.soloed {
-                    continue;
-                }
+            if refresh_automation {
+                engines.effects.lock().apply_automation(playhead_seconds);
+            }
 
-                let mut track_left = 0.0f32;
-                let mut track_right = 0.0f32;
-
-                // Mix all audio clips on this track
-                for timeline_clip in &track_snap.audio_clips {
-                    let clip_duration = timeline_clip.duration
-                        .unwrap_or(timeline_clip.clip.duration_seconds);
-                    // When warp is enabled, the clip's timeline duration changes:
-                    // stretch > 1 = faster playback = clip ends sooner
-                    // stretch < 1 = slower playback = clip ends later
-                    let effective_duration = if timeline_clip.warp_enabled {
-                        clip_duration / f64::from(timeline_clip.stretch_factor)
-                    } else {
-                        clip_duration
-                    };
-                    let clip_end = timeline_clip.start_time + effective_duration;
-
-                    if playhead_seconds >= timeline_clip.start_time
-                        && playhead_seconds < clip_end
-                    {
-                        let time_in_clip = playhead_seconds - timeline_clip.start_time + timeline_clip.offset;
-                        let clip_gain = timeline_clip.get_gain();
-                        let pitch_ratio = f64::from(timeline_clip.get_pitch_ratio());
-
-                        // Determine which audio source to use and calculate frame index
-                        let (frame_in_clip, source_clip): (usize, &AudioClip) = if timeline_clip.warp_enabled {
-                            if timeline_clip.warp_mode == 0 {
-                                // Warp mode: use pre-stretched cached audio (pitch preserved)
-                                // Apply pitch ratio for transpose
-                                if let Some(ref stretched) = timeline_clip.stretched_cache {
-                                    let frame = (time_in_clip * pitch_ratio * f64::from(sample_rate)) as usize;
-                                    (frame, stretched.as_ref())
-                                } else {
-                                    // Fallback to Re-Pitch if cache not ready
-                                    let stretched_time = time_in_clip * f64::from(timeline_clip.stretch_factor) * pitch_ratio;
-                                    ((stretched_time * f64::from(sample_rate)) as usize, &*timeline_clip.clip)
-                                }
-                            } else {
-                                // Re-Pitch mode: sample-rate shift (pitch follows speed)
-                                // Also apply any additional transpose
-                                let stretched_time = time_in_clip * f64::from(timeline_clip.stretch_factor) * pitch_ratio;
-                                ((stretched_time * f64::from(sample_rate)) as usize, &*timeline_clip.clip)
-                            }
-                        } else {
-                            // No warp - apply pitch ratio for transpose
-                            ((time_in_clip * pitch_ratio * f64::from(sample_rate)) as usize, &*timeline_clip.clip)
-                        };
-
-                        if let Some(l) = source_clip.get_sample(frame_in_clip, 0) {
-                            track_left += l * clip_gain;
-                        }
-                        if source_clip.channels > 1 {
-                            if let Some(r) = source_clip.get_sample(frame_in_clip, 1) {
-                                track_right += r * clip_gain;
-                            }
-                        } else {
-                            // Mono clip - duplicate to right
-                            if let Some(l) = source_clip.get_sample(frame_in_clip, 0) {
-                                track_right += l * clip_gain;
-                            }
-                        }
+            // Mix all tracks
+            let frame = TrackFrame {
+                playhead_frame: frame_idx as u64,
+                playhead_seconds,
+                play_timeline: true,
+                recording: false,
+                input: InputFrame::silent(),
+                monitor_input: false,
+                refresh_automation,
+                apply_fader: true,
+                preview: !is_final,
+                release_notes: false,
+            };
+            { let mut synth_manager = engines.synths.lock();
+                let effect_mgr = engines.effects.lock();
+                for track_snap in &mut track_snapshots {
+                    if track_snap.is_silenced(has_solo) || stem.is_some_and(|stem| !stem.plays(track_snap)) {
+                        continue;
                     }
-                }
 
-                // Process MIDI clips - route to EITHER built-in synth OR VST3 (not both)
-                let has_vst3 = !track_snap.fx_chain.is_empty();
-                for timeline_midi_clip in &track_snap.midi_clips {
-                    let clip_start_samples = (timeline_midi_clip.start_time * f64::from(sample_rate)) as u64;
-                    let clip_end_samples = clip_start_samples + timeline_midi_clip.clip.duration_samples;
-
-                    // Check if clip is active at this frame
-                    // Use <= for end boundary to ensure note-offs at exact clip end are triggered
-                    if frame_idx as u64 >= clip_start_samples && (frame_idx as u64) <= clip_end_samples {
-                        let frame_in_clip = frame_idx as u64 - clip_start_samples;
-
-                        // Check for MIDI events at this exact frame
-                        for event in &timeline_midi_clip.clip.events {
-                            if event.timestamp_samples == frame_in_clip {
-                                match event.event_type {
-                                    crate::midi::MidiEventType::NoteOn { note, velocity } => {
-                                        // Send to built-in synth ONLY if no VST3 plugins
-                                        if !has_vst3 {
-                                            { let mut synth_manager = self.track_synth_manager.lock();
-                                                synth_manager.note_on(track_snap.id, note, velocity);
-                                            }
-                                        }
-                                        // Send to VST3 instruments in FX chain
-                                        if has_vst3 {
-                                            { let effect_mgr = self.effect_manager.lock();
-                                                for effect_id in &track_snap.fx_chain {
-                                                    if let Some(effect_arc) = effect_mgr.get_effect(*effect_id) {
-                                                        { let mut effect = effect_arc.lock();
-                                                            #[cfg(all(feature = "vst3", not(target_os = "ios")))]
-                                                            if let crate::effects::EffectType::VST3(ref mut vst3) = *effect {
-                                                                let _ = vst3.process_midi_event(0, 0, i32::from(note), i32::from(velocity), 0);
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                    crate::midi::MidiEventType::NoteOff { note, velocity: _ } => {
-                                        // Send to built-in synth ONLY if no VST3 plugins
-                                        if !has_vst3 {
-                                            { let mut synth_manager = self.track_synth_manager.lock();
-                                                synth_manager.note_off(track_snap.id, note);
-                                            }
-                                        }
-                                        // Send to VST3 instruments in FX chain
-                                        if has_vst3 {
-                                            { let effect_mgr = self.effect_manager.lock();
-                                                for effect_id in &track_snap.fx_chain {
-                                                    if let Some(effect_arc) = effect_mgr.get_effect(*effect_id) {
-                                                        { let mut effect = effect_arc.lock();
-                                                            #[cfg(all(feature = "vst3", not(target_os = "ios")))]
-                                                            if let crate::effects::EffectType::VST3(ref mut vst3) = *effect {
-                                                                let _ = vst3.process_midi_event(1, 0, i32::from(note), 0, 0);
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+                    let (track_left, track_right) =
+                        process_track_frame(track_snap, &mut synth_manager, &effect_mgr, &frame, &sidechain_levels, &mut bus_inputs);
+
+                    if sidechain_sources.contains(&track_snap.id) {
+                        sidechain_levels.insert(track_snap.id, sidechain_level(track_left, track_right));
                     }
-                }
 
-                // Add synthesizer output
-                { let mut synth_manager = self.track_synth_manager.lock();
-                    let (synth_left, synth_right) = synth_manager.process_sample_stereo(track_snap.id);
-                    track_left += synth_left;
-                    track_right += synth_right;
-                }
+                    // Line up with the most latent track on the same bus, as in playback
+                    let (track_left, track_right) =
+                        compensate_latency(track_snap, &mut compensation_delays, track_left, track_right);
 
-                // Apply track volume (use automation if available)
-                let frame_volume_gain = if track_snap.volume_automation.is_empty() {
-                    track_snap.volume_gain
-                } else {
-                    interpolate_automation_gain(&track_snap.volume_automation, playhead_seconds)
-                };
-                track_left *= frame_volume_gain;
-                track_right *= frame_volume_gain;
-
-                // Apply track pan
-                track_left *= track_snap.pan_left;
-                track_right *= track_snap.pan_right;
-
-                // Process FX chain on this track
-                let mut fx_left = track_left;
-                let mut fx_right = track_right;
-
-                { let effect_mgr = self.effect_manager.lock();
-                    for effect_id in &track_snap.fx_chain {
-                        if let Some(effect_arc) = effect_mgr.get_effect(*effect_id) {
-                            { let mut effect = effect_arc.lock();
-                                let (out_l, out_r) = effect.process_frame(fx_left, fx_right);
-                                fx_left = out_l;
-                                fx_right = out_r;
-                            }
-                        }
-                    }
+                    // Accumulate to mix bus (or into the track's group)
+                    let to_master = route_to_group(track_snap, track_left, track_right, &mut bus_inputs);
+                    let (to_master_left, to_master_right) = match stem {
+                        Some(stem) => stem.contribution(track_snap, (track_left, track_right), to_master),
+                        None => to_master,
+                    };
+                    mix_left += to_master_left;
+                    mix_right += to_master_right;
                 }
+            }
 
-                // Accumulate to mix bus
-                mix_left += fx_left;
-                mix_right += fx_right;
+            if stem.is_some() {
+                output.push(mix_left, mix_right);
+                continue;
             }
 
             // Apply master track processing
@@ -267,65 +401,60 @@ impl AudioGraph {
             let mut master_right = mix_right;
 
             if let Some(ref master_snap) = master_snapshot {
-                // Apply master volume
-                master_left *= master_snap.volume_gain;
-                master_right *= master_snap.volume_gain;
-
-                // Apply master pan
-                let temp_l = master_left * master_snap.pan_left + master_right * master_snap.pan_left;
-                let temp_r = master_left * master_snap.pan_right + master_right * master_snap.pan_right;
-                master_left = temp_l;
-                master_right = temp_r;
-
-                // Process master FX chain
-                { let effect_mgr = self.effect_manager.lock();
-                    for effect_id in &master_snap.fx_chain {
-                        if let Some(effect_arc) = effect_mgr.get_effect(*effect_id) {
-                            { let mut effect = effect_arc.lock();
-                                let (out_l, out_r) = effect.process_frame(master_left, master_right);
-                                master_left = out_l;
-                                master_right = out_r;
-                            }
-                        }
-                    }
+                // Apply master volume and pan (same as the real-time callback)
+                (master_left, master_right) = master_snap.apply_master_fader(master_left, master_right);
+
+                // Sidechained master FX chain (or a preview): process it frame by frame
+                if !master_fx_per_block {
+                    let effect_mgr = engines.effects.lock();
+                    (master_left, master_right) = process_effect_chain(&master_snap.fx_chain, &effect_mgr, master_left, master_right, false, !is_final, &sidechain_levels);
                 }
             }
 
-            // Apply master limiter
-            let (limited_left, limited_right) = { let mut limiter = self.master_limiter.lock();
-                limiter.process_frame(master_left, master_right)
-            };
-
-            // Write to output buffer (interleaved stereo)
-            output.push(limited_left);
-            output.push(limited_right);
+            master_block_left.push(master_left);
+            master_block_right.push(master_right);
+            if master_block_left.len() == MASTER_BLOCK_FRAMES {
+                engines.finish_master_block(block_fx_chain, &mut master_block_left, &mut master_block_right, &mut output);
+            }
 
             // Progress logging every 10%
-            if frame_idx % (total_frames / 10).max(1) == 0 {
-                let progress = (frame_idx as f64 / total_frames as f64 * 100.0) as i32;
+            if is_final && render_idx % (render_frames / 10).max(1) == 0 {
+                let progress = (render_idx as f64 / render_frames as f64 * 100.0) as i32;
                 eprintln!("   {progress}% complete...");
             }
         }
 
-        eprintln!("✅ [AudioGraph] Offline render complete: {} samples", output.len());
-        output
+        // Last partial block (or what was mixed before a cancel)
+        if !master_block_left.is_empty() {
+            engines.finish_master_block(block_fx_chain, &mut master_block_left, &mut master_block_right, &mut output);
+        }
+        output.flush();
+
+        if is_final {
+            eprintln!("✅ [AudioGraph] Offline render complete: {} samples", output.samples_written);
+        }
     }
 
     /// Render a single track offline to a buffer of stereo f32 samples
     /// Returns interleaved stereo audio (L, R, L, R, ...)
     /// This renders the track in isolation without master bus processing
+    /// Reports export progress and honours cancellation like `render_offline`
     pub fn render_track_offline(&self, track_id: u64, duration_seconds: f64) -> Vec<f32> {
-        // Get track snapshot
-        struct TrackSnapshot {
-            audio_clips: Vec<TimelineClip>,
-            midi_clips: Vec<TimelineMidiClip>,
-            volume_gain: f32,
-            pan_left: f32,
-            pan_right: f32,
-            fx_chain: Vec<u64>,
-            volume_automation: Vec<AutomationPoint>,
-        }
+        self.render_track_offline_inner(track_id, duration_seconds, true)
+    }
+
+    /// Render a single track offline without its volume/pan (clips, synth and FX only)
+    /// Used for track freeze, where the live mixer still applies volume and pan
+    pub fn render_track_pre_fader(&self, track_id: u64, duration_seconds: f64) -> Vec<f32> {
+        self.render_track_offline_inner(track_id, duration_seconds, false)
+    }
 
+    fn render_track_offline_inner(
+        &self,
+        track_id: u64,
+        duration_seconds: f64,
+        apply_mixer: bool,
+    ) -> Vec<f32> {
         let sample_rate = TARGET_SAMPLE_RATE;
         let total_frames = (duration_seconds * f64::from(sample_rate)) as usize;
         let mut output = Vec::with_capacity(total_frames * 2);
@@ -334,9 +463,8 @@ impl AudioGraph {
             "🎚️ [AudioGraph] Starting track {track_id} offline render: {duration_seconds:.2}s ({total_frames} frames)"
         );
 
-        // Get tempo for timeline positioning
-        let current_tempo = self.recorder.get_tempo();
-        let tempo_ratio = current_tempo / 120.0;
+        // Get the tempo map for timeline positioning
+        let tempo_map = self.recorder.get_tempo_map();
 
         let track_snapshot = {
             let tm = self.track_manager.lock();
@@ -345,15 +473,7 @@ impl AudioGraph {
             for track_arc in tm.get_all_tracks() {
                 { let track = track_arc.lock();
                     if track.id == track_id {
-                        snapshot = Some(TrackSnapshot {
-                            audio_clips: track.audio_clips.clone(),
-                            midi_clips: track.midi_clips.clone(),
-                            volume_gain: track.get_gain(),
-                            pan_left: track.get_pan_gains().0,
-                            pan_right: track.get_pan_gains().1,
-                            fx_chain: track.fx_chain.clone(),
-                            volume_automation: track.volume_automation.clone(),
-                        });
+                        snapshot = Some(TrackSnapshot::new(&track));
                         break;
                     }
                 }
@@ -362,190 +482,62 @@ impl AudioGraph {
             snapshot
         };
 
-        let Some(track_snap) = track_snapshot else {
+        let Some(mut track_snap) = track_snapshot else {
             eprintln!("❌ [AudioGraph] Track {track_id} not found for stem export");
             return output;
         };
+        let no_sidechain = HashMap::new();
+        // Stems are rendered dry: the track's sends have no return to feed here
+        let mut bus_inputs = BusInputs::new();
+        // Render on past the end by the FX chain's latency and drop that much from the
+        // start, so the track's audio lines up with the timeline
+        let latency = self.effect_manager.lock().chain_latency(&track_snap.fx_chain);
+        let render_frames = total_frames + latency;
 
         // Process each frame
-        for frame_idx in 0..total_frames {
-            // Apply tempo ratio: at 120 BPM, playhead advances 1:1 with real time
-            let real_seconds = frame_idx as f64 / f64::from(sample_rate);
-            let playhead_seconds = real_seconds * tempo_ratio;
-
-            let mut track_left = 0.0f32;
-            let mut track_right = 0.0f32;
-
-            // Mix all audio clips on this track
-            for timeline_clip in &track_snap.audio_clips {
-                let clip_duration = timeline_clip
-                    .duration
-                    .unwrap_or(timeline_clip.clip.duration_seconds);
-                // When warp is enabled, the clip's timeline duration changes:
-                // stretch > 1 = faster playback = clip ends sooner
-                // stretch < 1 = slower playback = clip ends later
-                let effective_duration = if timeline_clip.warp_enabled {
-                    clip_duration / f64::from(timeline_clip.stretch_factor)
-                } else {
-                    clip_duration
-                };
-                let clip_end = timeline_clip.start_time + effective_duration;
-
-                if playhead_seconds >= timeline_clip.start_time && playhead_seconds < clip_end {
-                    let time_in_clip =
-                        playhead_seconds - timeline_clip.start_time + timeline_clip.offset;
-                    let clip_gain = timeline_clip.get_gain();
-                    let pitch_ratio = f64::from(timeline_clip.get_pitch_ratio());
-
-                    // Determine which audio source to use and calculate frame index
-                    let (frame_in_clip, source_clip): (usize, &AudioClip) = if timeline_clip.warp_enabled {
-                        if timeline_clip.warp_mode == 0 {
-                            // Warp mode: use pre-stretched cached audio (pitch preserved)
-                            // Apply pitch ratio for transpose
-                            if let Some(ref stretched) = timeline_clip.stretched_cache {
-                                let frame = (time_in_clip * pitch_ratio * f64::from(sample_rate)) as usize;
-                                (frame, stretched.as_ref())
-                            } else {
-                                // Fallback to Re-Pitch if cache not ready
-                                let stretched_time = time_in_clip * f64::from(timeline_clip.stretch_factor) * pitch_ratio;
-                                ((stretched_time * f64::from(sample_rate)) as usize, &*timeline_clip.clip)
-                            }
-                        } else {
-                            // Re-Pitch mode: sample-rate shift (pitch follows speed)
-                            // Also apply any additional transpose
-                            let stretched_time = time_in_clip * f64::from(timeline_clip.stretch_factor) * pitch_ratio;
-                            ((stretched_time * f64::from(sample_rate)) as usize, &*timeline_clip.clip)
-                        }
-                    } else {
-                        // No warp - apply pitch ratio for transpose
-                        ((time_in_clip * pitch_ratio * f64::from(sample_rate)) as usize, &*timeline_clip.clip)
-                    };
-
-                    if let Some(l) = source_clip.get_sample(frame_in_clip, 0) {
-                        track_left += l * clip_gain;
-                    }
-                    if source_clip.channels > 1 {
-                        if let Some(r) = source_clip.get_sample(frame_in_clip, 1) {
-                            track_right += r * clip_gain;
-                        }
-                    } else {
-                        // Mono clip - duplicate to right
-                        if let Some(l) = source_clip.get_sample(frame_in_clip, 0) {
-                            track_right += l * clip_gain;
-                        }
-                    }
+        for frame_idx in 0..render_frames {
+            if frame_idx % PROGRESS_REPORT_FRAMES == 0 {
+                let progress = export_progress();
+                if progress.is_render_cancelled() {
+                    eprintln!("⏹️ [AudioGraph] Track {track_id} render cancelled at frame {frame_idx}");
+                    break;
                 }
+                progress.report_render(frame_idx as f64 / render_frames as f64);
             }
 
-            // Process MIDI clips - route to EITHER built-in synth OR VST3 (not both)
-            let has_vst3 = !track_snap.fx_chain.is_empty();
-            for timeline_midi_clip in &track_snap.midi_clips {
-                let clip_start_samples = (timeline_midi_clip.start_time * f64::from(sample_rate)) as u64;
-                let clip_end_samples =
-                    clip_start_samples + timeline_midi_clip.clip.duration_samples;
-
-                if frame_idx as u64 >= clip_start_samples && (frame_idx as u64) <= clip_end_samples
-                {
-                    let frame_in_clip = frame_idx as u64 - clip_start_samples;
-
-                    for event in &timeline_midi_clip.clip.events {
-                        if event.timestamp_samples == frame_in_clip {
-                            match event.event_type {
-                                crate::midi::MidiEventType::NoteOn { note, velocity } => {
-                                    // Send to built-in synth ONLY if no VST3 plugins
-                                    if !has_vst3 {
-                                        { let mut synth_manager = self.track_synth_manager.lock();
-                                            synth_manager.note_on(track_id, note, velocity);
-                                        }
-                                    }
-                                    // Send to VST3 instruments in FX chain
-                                    if has_vst3 {
-                                        { let effect_mgr = self.effect_manager.lock();
-                                            for effect_id in &track_snap.fx_chain {
-                                                if let Some(effect_arc) = effect_mgr.get_effect(*effect_id) {
-                                                    { let mut effect = effect_arc.lock();
-                                                        #[cfg(all(feature = "vst3", not(target_os = "ios")))]
-                                                        if let crate::effects::EffectType::VST3(ref mut vst3) = *effect {
-                                                            let _ = vst3.process_midi_event(0, 0, i32::from(note), i32::from(velocity), 0);
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                                crate::midi::MidiEventType::NoteOff { note, velocity: _ } => {
-                                    // Send to built-in synth ONLY if no VST3 plugins
-                                    if !has_vst3 {
-                                        { let mut synth_manager = self.track_synth_manager.lock();
-                                            synth_manager.note_off(track_id, note);
-                                        }
-                                    }
-                                    // Send to VST3 instruments in FX chain
-                                    if has_vst3 {
-                                        { let effect_mgr = self.effect_manager.lock();
-                                            for effect_id in &track_snap.fx_chain {
-                                                if let Some(effect_arc) = effect_mgr.get_effect(*effect_id) {
-                                                    { let mut effect = effect_arc.lock();
-                                                        #[cfg(all(feature = "vst3", not(target_os = "ios")))]
-                                                        if let crate::effects::EffectType::VST3(ref mut vst3) = *effect {
-                                                            let _ = vst3.process_midi_event(1, 0, i32::from(note), 0, 0);
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-
-            // Add synthesizer output
-            { let mut synth_manager = self.track_synth_manager.lock();
-                let (synth_left, synth_right) = synth_manager.process_sample_stereo(track_id);
-                track_left += synth_left;
-                track_right += synth_right;
-            }
-
-            // Apply track volume (use automation if available)
-            let frame_volume_gain = if track_snap.volume_automation.is_empty() {
-                track_snap.volume_gain
-            } else {
-                interpolate_automation_gain(&track_snap.volume_automation, playhead_seconds)
+            // Apply the tempo: at 120 BPM, playhead advances 1:1 with real time
+            let playhead_seconds = tempo_map.timeline_seconds_at_sample(frame_idx as u64);
+
+            self.effect_manager.lock().apply_automation(playhead_seconds);
+            bus_inputs.clear();
+
+            let frame = TrackFrame {
+                playhead_frame: frame_idx as u64,
+                playhead_seconds,
+                play_timeline: true,
+                recording: false,
+                input: InputFrame::silent(),
+                monitor_input: false,
+                refresh_automation: true,
+                apply_fader: apply_mixer,
+                preview: false,
+                release_notes: false,
+            };
+            let (track_left, track_right) = {
+                let mut synth_manager = self.track_synth_manager.lock();
+                let effect_mgr = self.effect_manager.lock();
+                process_track_frame(&mut track_snap, &mut synth_manager, &effect_mgr, &frame, &no_sidechain, &mut bus_inputs)
             };
-            track_left *= frame_volume_gain;
-            track_right *= frame_volume_gain;
-
-            // Apply track pan
-            track_left *= track_snap.pan_left;
-            track_right *= track_snap.pan_right;
-
-            // Process FX chain on this track
-            let mut fx_left = track_left;
-            let mut fx_right = track_right;
-
-            { let effect_mgr = self.effect_manager.lock();
-                for effect_id in &track_snap.fx_chain {
-                    if let Some(effect_arc) = effect_mgr.get_effect(*effect_id) {
-                        { let mut effect = effect_arc.lock();
-                            let (out_l, out_r) = effect.process_frame(fx_left, fx_right);
-                            fx_left = out_l;
-                            fx_right = out_r;
-                        }
-                    }
-                }
-            }
 
             // Write to output buffer (interleaved stereo)
-            output.push(fx_left);
-            output.push(fx_right);
+            if frame_idx >= latency {
+                output.push(track_left);
+                output.push(track_right);
+            }
 
             // Progress logging every 25%
-            if frame_idx % (total_frames / 4).max(1) == 0 && frame_idx > 0 {
-                let progress = (frame_idx as f64 / total_frames as f64 * 100.0) as i32;
+            if frame_idx % (render_frames / 4).max(1) == 0 && frame_idx > 0 {
+                let progress = (frame_idx as f64 / render_frames as f64 * 100.0) as i32;
                 eprintln!("   Track {track_id} - {progress}% complete...");
             }
         }
@@ -587,8 +579,14 @@ impl AudioGraph {
         tracks
     }
 
-    /// Calculate the total duration of the project based on clips
+    /// Calculate the total duration of the project: the clips plus a short tail
+    /// for reverb/delay to decay (`DEFAULT_TAIL_SECONDS`)
     pub fn calculate_project_duration(&self) -> f64 {
+        self.calculate_content_end() + crate::export::DEFAULT_TAIL_SECONDS
+    }
+
+    /// End of the last clip on the timeline in seconds (0 for an empty project)
+    pub fn calculate_content_end(&self) -> f64 {
         let mut max_end_time = 0.0f64;
 
         // Check all tracks for clips
@@ -613,7 +611,6 @@ impl AudioGraph {
             }
         }
 
-        // Add a small tail for reverb/delay to decay (1 second)
-        max_end_time + 1.0
+        max_end_time
     }
 }
@@ -2,7 +2,7 @@
 use super::{AudioGraph, BufferSizePreset};
 use crate::audio_file::TARGET_SAMPLE_RATE;
 use crate::midi::MidiClip;
-use crate::track::TimelineMidiClip;
+use crate::track::{AutomationPoint, ClipId, MeteringPoint, TimelineMidiClip};
 use std::sync::Arc;
 
 impl AudioGraph {
@@ -13,7 +13,7 @@ impl AudioGraph {
     /// Export current state to `ProjectData` (for saving) - native only (uses recorder)
     #[cfg(not(target_arch = "wasm32"))]
     pub fn export_to_project_data(&self, project_name: String) -> crate::project::ProjectData {
-        use crate::project::{TrackData, EffectData, ClipData, SendData, Vst3PluginData, AudioFileData, ProjectData};
+        use crate::project::{TrackData, EffectData, ClipData, SendData, Vst3PluginData, AudioFileData, ProjectData, AutomationPointData, MarkerData, TempoChangeData, TimeSignatureChangeData};
         use crate::effects::EffectType as ET;
         use std::collections::HashMap;
         #[cfg(all(feature = "vst3", not(target_os = "ios")))]
@@ -27,9 +27,11 @@ impl AudioGraph {
         let all_tracks = track_manager.get_all_tracks();
         let tracks_data: Vec<TrackData> = all_tracks.iter().map(|track_arc| {
             let track = track_arc.lock();
+            // A frozen track saves its original contents, so it reloads unfrozen
+            let (saved_audio_clips, saved_midi_clips, saved_fx_chain) = track.saved_contents();
 
             // Get effect chain for this track
-            let fx_chain: Vec<EffectData> = track.fx_chain.iter().filter_map(|effect_id| {
+            let fx_chain: Vec<EffectData> = saved_fx_chain.iter().filter_map(|effect_id| {
                 // Get effect from effect manager
                 if let Some(effect_arc) = effect_manager.get_effect(*effect_id) {
                     let effect = effect_arc.lock();
@@ -58,18 +60,27 @@ impl AudioGraph {
                             parameters.insert("attack_ms".to_string(), comp.attack_ms);
                             parameters.insert("release_ms".to_string(), comp.release_ms);
                             parameters.insert("makeup_gain_db".to_string(), comp.makeup_gain_db);
+                            if let Some(source_id) = comp.sidechain_track_id {
+                                parameters.insert("sidechain_track_id".to_string(), source_id as f32);
+                            }
+                            parameters.insert("oversample".to_string(), comp.oversample() as f32);
                         }
                         ET::Reverb(rev) => {
                             effect_type_str = "reverb".to_string();
                             parameters.insert("room_size".to_string(), rev.room_size);
                             parameters.insert("damping".to_string(), rev.damping);
                             parameters.insert("wet_dry_mix".to_string(), rev.wet_dry_mix);
+                            parameters.insert("freeze".to_string(), f32::from(u8::from(rev.freeze)));
                         }
                         ET::Delay(dly) => {
                             effect_type_str = "delay".to_string();
                             parameters.insert("delay_time_ms".to_string(), dly.delay_time_ms);
                             parameters.insert("feedback".to_string(), dly.feedback);
                             parameters.insert("wet_dry_mix".to_string(), dly.wet_dry_mix);
+                            parameters.insert("ping_pong".to_string(), f32::from(u8::from(dly.ping_pong)));
+                            parameters.insert("sync_enabled".to_string(), f32::from(u8::from(dly.sync_enabled)));
+                            parameters.insert("note_division".to_string(), dly.note_division as f32);
+                            parameters.insert("note_modifier".to_string(), dly.note_modifier.index() as f32);
                         }
                         ET::Chorus(chr) => {
                             effect_type_str = "chorus".to_string();
@@ -77,9 +88,42 @@ impl AudioGraph {
                             parameters.insert("depth".to_string(), chr.depth);
                             parameters.insert("wet_dry_mix".to_string(), chr.wet_dry_mix);
                         }
-                        ET::Limiter(_) => {
+                        ET::Limiter(lim) => {
                             effect_type_str = "limiter".to_string();
-                            // Limiter has no user-adjustable parameters
+                            parameters.insert("threshold_db".to_string(), lim.threshold_db);
+                            parameters.insert("release_ms".to_string(), lim.release_ms);
+                            parameters.insert("lookahead_ms".to_string(), lim.lookahead_ms);
+                            parameters.insert("oversample".to_string(), lim.oversample() as f32);
+                        }
+                        ET::BitCrusher(crs) => {
+                            effect_type_str = "bitcrusher".to_string();
+                            parameters.insert("bit_depth".to_string(), crs.bit_depth);
+                            parameters.insert("sample_rate_reduction".to_string(), crs.sample_rate_reduction);
+                            parameters.insert("wet_dry_mix".to_string(), crs.wet_dry_mix);
+                        }
+                        ET::NoiseGate(gate) => {
+                            effect_type_str = "gate".to_string();
+                            parameters.insert("threshold_db".to_string(), gate.threshold_db);
+                            parameters.insert("attack_ms".to_string(), gate.attack_ms);
+                            parameters.insert("hold_ms".to_string(), gate.hold_ms);
+                            parameters.insert("release_ms".to_string(), gate.release_ms);
+                        }
+                        ET::StereoWidth(sw) => {
+                            effect_type_str = "stereo_width".to_string();
+                            parameters.insert("width".to_string(), sw.width);
+                        }
+                        ET::Filter(filter) => {
+                            effect_type_str = "filter".to_string();
+                            parameters.insert("filter_type".to_string(), filter.filter_type.index() as f32);
+                            parameters.insert("cutoff_hz".to_string(), filter.cutoff_hz);
+                            parameters.insert("resonance".to_string(), filter.resonance);
+                        }
+                        ET::Gain(gain) => {
+                            effect_type_str = "gain".to_string();
+                            parameters.insert("gain_db".to_string(), gain.gain_db);
+                            parameters.insert("invert_left".to_string(), f32::from(u8::from(gain.invert_left)));
+                            parameters.insert("invert_right".to_string(), f32::from(u8::from(gain.invert_right)));
+                            parameters.insert("dc_block".to_string(), f32::from(u8::from(gain.dc_block)));
                         }
                         #[cfg(all(feature = "vst3", not(target_os = "ios")))]
                         ET::VST3(_vst3) => {
@@ -90,10 +134,25 @@ impl AudioGraph {
                         }
                     }
 
+                    let automation = effect_manager
+                        .get_parameter_automation(*effect_id)
+                        .into_iter()
+                        .map(|(param_name, points)| {
+                            let points = points.iter().map(|point| AutomationPointData {
+                                time_seconds: point.time_seconds,
+                                value: point.value,
+                            }).collect();
+                            (param_name.to_string(), points)
+                        })
+                        .collect();
+
+                    parameters.insert(crate::effects::MIX_PARAM.to_string(), effect_manager.get_mix(*effect_id));
+
                     Some(EffectData {
                         id: *effect_id,
                         effect_type: effect_type_str,
                         parameters,
+                        automation,
                     })
                 } else {
                     None
@@ -101,7 +160,7 @@ impl AudioGraph {
             }).collect();
 
             // Get audio clips on this track
-            let audio_clips_data: Vec<ClipData> = track.audio_clips.iter().map(|timeline_clip| {
+            let audio_clips_data: Vec<ClipData> = saved_audio_clips.iter().map(|timeline_clip| {
                 ClipData {
                     id: timeline_clip.id,
                     start_time: timeline_clip.start_time,
@@ -109,11 +168,26 @@ impl AudioGraph {
                     duration: timeline_clip.duration,
                     audio_file_id: Some(timeline_clip.id), // Simplified: use clip ID as file ID
                     midi_notes: None,
+                    midi_controls: Vec::new(),
+                    polarity_inverted: timeline_clip.polarity_inverted,
+                    mono_sum: timeline_clip.mono_sum,
+                    fade_in_seconds: timeline_clip.fade_in_seconds,
+                    fade_out_seconds: timeline_clip.fade_out_seconds,
+                    warp_enabled: timeline_clip.warp_enabled,
+                    stretch_factor: timeline_clip.stretch_factor,
+                    warp_mode: timeline_clip.warp_mode,
+                    reversed: timeline_clip.reversed,
+                    transpose_semitones: timeline_clip.transpose_semitones,
+                    transpose_cents: timeline_clip.transpose_cents,
+                    gain_envelope: timeline_clip.gain_envelope.iter().map(|point| AutomationPointData {
+                        time_seconds: point.time_seconds,
+                        value: point.value,
+                    }).collect(),
                 }
             }).collect();
 
             // Get MIDI clips on this track - convert events to note data
-            let midi_clips_data: Vec<ClipData> = track.midi_clips.iter().map(|timeline_clip| {
+            let midi_clips_data: Vec<ClipData> = saved_midi_clips.iter().map(|timeline_clip| {
                 let midi_notes = convert_midi_events_to_notes(
                     &timeline_clip.clip.events,
                     timeline_clip.clip.sample_rate
@@ -128,6 +202,21 @@ impl AudioGraph {
                     duration: Some(duration_seconds),
                     audio_file_id: None, // MIDI clip, not audio
                     midi_notes: Some(midi_notes),
+                    midi_controls: convert_midi_events_to_controls(
+                        &timeline_clip.clip.events,
+                        timeline_clip.clip.sample_rate,
+                    ),
+                    polarity_inverted: false,
+                    mono_sum: false,
+                    fade_in_seconds: 0.0,
+                    fade_out_seconds: 0.0,
+                    warp_enabled: false,
+                    stretch_factor: 1.0,
+                    warp_mode: 0,
+                    reversed: false,
+                    transpose_semitones: 0,
+                    transpose_cents: 0,
+                    gain_envelope: Vec::new(),
                 }
             }).collect();
 
@@ -142,6 +231,8 @@ impl AudioGraph {
             // Get instrument settings for MIDI tracks (synth or sampler)
             let synth_settings = synth_manager.get_synth_parameters(track.id);
             let sampler_settings = synth_manager.get_sampler_parameters(track.id);
+            let arpeggiator = synth_manager.get_arp_parameters(track.id);
+            let step_sequencer = synth_manager.get_step_sequencer_data(track.id);
 
             // Export send routing
             let sends: Vec<SendData> = track.sends.iter().map(|s| SendData {
@@ -152,7 +243,7 @@ impl AudioGraph {
 
             // Collect VST3 plugin data with state
             #[cfg(all(feature = "vst3", not(target_os = "ios")))]
-            let vst3_plugins: Vec<Vst3PluginData> = track.fx_chain.iter().filter_map(|effect_id| {
+            let vst3_plugins: Vec<Vst3PluginData> = saved_fx_chain.iter().filter_map(|effect_id| {
                 if let Some(effect_arc) = effect_manager.get_effect(*effect_id) {
                     let effect = effect_arc.lock();
                     if let ET::VST3(vst3) = &*effect {
@@ -186,32 +277,50 @@ impl AudioGraph {
                 pan: track.pan,
                 mute: track.mute,
                 solo: track.solo,
+                solo_safe: Some(track.solo_safe),
+                metering_point: Some(track.metering_point.name().to_string()),
                 armed: track.armed,
                 clips: clips_data,
                 fx_chain,
                 synth_settings,
                 sampler_settings,
+                arpeggiator,
+                step_sequencer,
                 sends,
                 parent_group_id: track.parent_group,
                 input_monitoring: track.input_monitoring,
+                monitor_through_fx: track.monitor_through_fx,
+                input_gain_db: track.input_gain_db,
+                input_device_index: Some(track.input_device_index.map_or(-1, |index| index as i32)),
+                input_channel: track.input_channel,
+                input_channel_count: track.input_channel_count,
+                phase_invert: track.phase_invert,
+                mono_sum: track.mono_sum,
+                layer_synth: track.layer_synth,
                 vst3_plugins,
+                pan_automation: track.pan_automation.iter().map(|point| AutomationPointData {
+                    time_seconds: point.time_seconds,
+                    value: point.value,
+                }).collect(),
             }
         }).collect();
 
         // Collect audio files from all tracks' audio clips (not the legacy self.clips)
         let audio_files: Vec<AudioFileData> = all_tracks.iter().flat_map(|track_arc| {
             let track = track_arc.lock();
-            track.audio_clips.iter().map(|timeline_clip| {
+            track.saved_contents().0.iter().map(|timeline_clip| {
                 // Extract just the filename from the path for cleaner storage
                 let filename = std::path::Path::new(&timeline_clip.clip.file_path)
                     .file_name().map_or_else(|| timeline_clip.clip.file_path.clone(), |f| f.to_string_lossy().to_string());
                 AudioFileData {
                     id: timeline_clip.id,
                     original_name: filename.clone(),
+                    // Placeholder: saving replaces it with the content-addressed stored path
                     relative_path: format!("audio/{:03}-{}", timeline_clip.id, filename),
                     duration: timeline_clip.clip.duration_seconds,
                     sample_rate: timeline_clip.clip.sample_rate,
                     channels: timeline_clip.clip.channels as u32,
+                    streamed: timeline_clip.clip.is_streaming(),
                 }
             }).collect::<Vec<_>>()
         }).collect();
@@ -229,26 +338,47 @@ impl AudioGraph {
             BufferSizePreset::Safe => 3,
             BufferSizePreset::HighStability => 4,
         };
+        let tempo_changes = self.recorder.get_tempo_map().changes().iter().skip(1).map(|change| TempoChangeData {
+            beat: change.beat,
+            bpm: change.bpm,
+        }).collect();
+        let time_signature_map = self.recorder.get_time_signature_map();
+        let time_signature_changes = time_signature_map.changes().iter().skip(1).map(|change| TimeSignatureChangeData {
+            bar: change.bar,
+            numerator: change.signature.numerator,
+            denominator: change.signature.denominator,
+        }).collect();
+        let markers = self.markers.lock().markers().iter().map(|marker| MarkerData {
+            id: marker.id,
+            time_seconds: marker.time_seconds,
+            label: marker.label.clone(),
+        }).collect();
 
         ProjectData {
-            version: "1.0".to_string(),
+            version: crate::project::PROJECT_FORMAT_VERSION.to_string(),
             name: project_name,
             tempo: self.recorder.get_tempo(),
             sample_rate: TARGET_SAMPLE_RATE,
-            time_sig_numerator: 4,
-            time_sig_denominator: 4,
+            time_sig_numerator: time_signature_map.base_signature().numerator,
+            time_sig_denominator: time_signature_map.base_signature().denominator,
             tracks: tracks_data,
             audio_files,
             metronome_enabled,
+            metronome_subdivision: self.recorder.get_metronome_subdivision(),
+            metronome_accent: self.recorder.is_metronome_accent_enabled(),
+            metronome_samples: self.recorder.get_metronome_sample_paths(),
             count_in_bars,
             buffer_size_preset,
+            markers,
+            tempo_changes,
+            time_signature_changes,
         }
     }
 
     /// Restore state from `ProjectData` (for loading) - native only (uses recorder)
     #[cfg(not(target_arch = "wasm32"))]
     pub fn restore_from_project_data(&mut self, project_data: crate::project::ProjectData) -> anyhow::Result<()> {
-        use crate::effects::{ParametricEQ, EffectType, Compressor, Reverb, Delay, Chorus, Limiter};
+        use crate::effects::{ParametricEQ, EffectType, Compressor, Reverb, Delay, Chorus, Limiter, BitCrusher, NoiseGate, StereoWidth, Filter, FilterType, Gain};
         use crate::track::TrackType;
 
         // Stop playback
@@ -276,17 +406,56 @@ impl AudioGraph {
             eprintln!("   - Cleared existing tracks");
         }
 
-        // Restore tempo (via recorder)
-        self.recorder.set_tempo(project_data.tempo);
+        // Restore tempo and tempo changes (via recorder)
+        let mut tempo_map = crate::tempo_map::TempoMap::new(project_data.tempo);
+        for change in &project_data.tempo_changes {
+            tempo_map.add_change(change.beat, change.bpm);
+        }
+        self.recorder.set_tempo_map(tempo_map);
+        self.set_instrument_tempo(project_data.tempo);
+        self.effect_manager.lock().set_tempo(project_data.tempo);
         eprintln!("   - Tempo: {} BPM", project_data.tempo);
 
+        // Restore time signature and its changes (before MIDI clips, which snap to its bars)
+        let base_signature = crate::time_signature::TimeSignature::new(project_data.time_sig_numerator, project_data.time_sig_denominator)
+            .unwrap_or_default();
+        let mut time_signature_map = crate::time_signature::TimeSignatureMap::new(base_signature);
+        for change in &project_data.time_signature_changes {
+            match crate::time_signature::TimeSignature::new(change.numerator, change.denominator) {
+                Ok(signature) => time_signature_map.add_change(change.bar, signature),
+                Err(e) => eprintln!("⚠️  Skipping time signature change at bar {}: {e}", change.bar),
+            }
+        }
+        self.recorder.set_time_signature_map(time_signature_map);
+        eprintln!("   - Time signature: {}/{}", base_signature.numerator, base_signature.denominator);
+
         // Restore metronome and count-in settings
         self.recorder.set_metronome_enabled(project_data.metronome_enabled);
+        self.recorder.set_metronome_subdivision(project_data.metronome_subdivision);
+        self.recorder.set_metronome_accent_enabled(project_data.metronome_accent);
+        let metronome_samples = project_data.metronome_samples.as_ref().and_then(|(accent, normal)| {
+            if let (Ok(accent), Ok(normal)) =
+                (crate::audio_file::load_audio_file(accent), crate::audio_file::load_audio_file(normal))
+            {
+                Some(crate::recorder::MetronomeSamples { accent: Arc::new(accent), normal: Arc::new(normal) })
+            } else {
+                eprintln!("⚠️  Failed to load metronome samples, using synthesized click");
+                None
+            }
+        });
+        self.recorder.set_metronome_samples(metronome_samples);
         self.recorder.set_count_in_bars(project_data.count_in_bars);
         eprintln!("   - Metronome: {}, Count-in: {} bars",
             if project_data.metronome_enabled { "ON" } else { "OFF" },
             project_data.count_in_bars);
 
+        // Restore markers
+        self.markers.lock().restore(project_data.markers.iter().map(|marker| crate::markers::Marker {
+            id: marker.id,
+            time_seconds: marker.time_seconds,
+            label: marker.label.clone(),
+        }).collect());
+
         // Restore buffer size preset
         let buffer_preset = match project_data.buffer_size_preset {
             0 => BufferSizePreset::Lowest,
@@ -348,11 +517,30 @@ impl AudioGraph {
                     track.pan = track_data.pan;
                     track.mute = track_data.mute;
                     track.solo = track_data.solo;
+                    if let Some(solo_safe) = track_data.solo_safe {
+                        track.solo_safe = solo_safe;
+                    }
+                    if let Some(point) = track_data.metering_point.as_deref().and_then(MeteringPoint::from_name) {
+                        track.metering_point = point;
+                    }
                     track.armed = track_data.armed;
 
                     // Restore parent group and input monitoring
                     track.parent_group = track_data.parent_group_id;
                     track.input_monitoring = track_data.input_monitoring;
+                    track.monitor_through_fx = track_data.monitor_through_fx;
+                    track.set_input_gain_db(track_data.input_gain_db);
+                    if let Some(device_index) = track_data.input_device_index {
+                        track.input_device_index = usize::try_from(device_index).ok();
+                    }
+                    track.input_channel = track_data.input_channel;
+                    track.input_channel_count = track_data.input_channel_count.clamp(1, 2);
+                    track.phase_invert = track_data.phase_invert;
+                    track.mono_sum = track_data.mono_sum;
+                    track.layer_synth = track_data.layer_synth;
+                    track.pan_automation = track_data.pan_automation.iter()
+                        .map(|point| AutomationPoint::new(point.time_seconds, point.value.clamp(-1.0, 1.0)))
+                        .collect();
 
                     // Restore send routing
                     for send_data in &track_data.sends {
@@ -380,12 +568,32 @@ impl AudioGraph {
                             synth_manager.load_sample(track_id, Arc::new(clip), sampler_data.root_note);
                         }
                     }
+                    for zone in &sampler_data.zones {
+                        if let Ok(clip) = crate::audio_file::load_audio_file(&zone.sample_path) {
+                            synth_manager.add_sample_zone(track_id, crate::sampler::SampleZone {
+                                root_note: zone.root_note,
+                                low_note: zone.low_note,
+                                high_note: zone.high_note,
+                                low_velocity: zone.low_velocity,
+                                high_velocity: zone.high_velocity,
+                                clip: Arc::new(clip),
+                            });
+                        } else {
+                            eprintln!("⚠️ Failed to load sampler zone '{}'", zone.sample_path);
+                        }
+                    }
                     synth_manager.restore_sampler_parameters(track_id, sampler_data);
                 } else if track_data.track_type == "Sampler" {
                     // Legacy: old project with Sampler type but no sampler_settings
                     let mut synth_manager = self.track_synth_manager.lock();
                     synth_manager.create_sampler(track_id);
                 }
+                if let Some(arp_data) = &track_data.arpeggiator {
+                    self.track_synth_manager.lock().restore_arp_parameters(track_id, arp_data);
+                }
+                if let Some(pattern) = &track_data.step_sequencer {
+                    self.track_synth_manager.lock().restore_step_sequencer(track_id, pattern);
+                }
             }
 
             // Recreate effects on this track
@@ -418,6 +626,12 @@ impl AudioGraph {
                         if let Some(&v) = effect_data.parameters.get("attack_ms") { comp.attack_ms = v; }
                         if let Some(&v) = effect_data.parameters.get("release_ms") { comp.release_ms = v; }
                         if let Some(&v) = effect_data.parameters.get("makeup_gain_db") { comp.makeup_gain_db = v; }
+                        if let Some(&v) = effect_data.parameters.get("sidechain_track_id") { comp.sidechain_track_id = Some(v as u64); }
+                        if let Some(&v) = effect_data.parameters.get("oversample") {
+                            if let Err(e) = comp.set_oversample(v.round() as u32) {
+                                eprintln!("⚠️  Ignoring compressor oversampling: {e}");
+                            }
+                        }
                         comp.update_coefficients();
                         EffectType::Compressor(comp)
                     }
@@ -426,13 +640,24 @@ impl AudioGraph {
                         if let Some(&v) = effect_data.parameters.get("room_size") { rev.room_size = v; }
                         if let Some(&v) = effect_data.parameters.get("damping") { rev.damping = v; }
                         if let Some(&v) = effect_data.parameters.get("wet_dry_mix") { rev.wet_dry_mix = v; }
+                        rev.freeze = effect_data.parameters.get("freeze").is_some_and(|&v| v >= 0.5);
                         EffectType::Reverb(rev)
                     }
                     "delay" => {
                         let mut dly = Delay::new();
-                        if let Some(&v) = effect_data.parameters.get("delay_time_ms") { dly.delay_time_ms = v; }
+                        if let Some(&v) = effect_data.parameters.get("delay_time_ms") { dly.set_delay_time_ms(v); }
                         if let Some(&v) = effect_data.parameters.get("feedback") { dly.feedback = v; }
                         if let Some(&v) = effect_data.parameters.get("wet_dry_mix") { dly.wet_dry_mix = v; }
+                        dly.ping_pong = effect_data.parameters.get("ping_pong").is_some_and(|&v| v >= 0.5);
+                        if effect_data.parameters.get("sync_enabled").is_some_and(|&v| v >= 0.5) {
+                            let division = effect_data.parameters.get("note_division").map_or(4, |&v| v as u32);
+                            let modifier = effect_data.parameters.get("note_modifier")
+                                .and_then(|&v| crate::effects::NoteModifier::from_index(v as u32))
+                                .unwrap_or_default();
+                            if let Err(e) = dly.set_sync(true, division, modifier) {
+                                eprintln!("⚠️  Ignoring delay sync settings: {e}");
+                            }
+                        }
                         EffectType::Delay(dly)
                     }
                     "chorus" => {
@@ -442,7 +667,58 @@ impl AudioGraph {
                         if let Some(&v) = effect_data.parameters.get("wet_dry_mix") { chr.wet_dry_mix = v; }
                         EffectType::Chorus(chr)
                     }
-                    "limiter" => EffectType::Limiter(Limiter::new()),
+                    "limiter" => {
+                        let mut lim = Limiter::new();
+                        if let Some(&v) = effect_data.parameters.get("threshold_db") { lim.set_threshold_db(v); }
+                        if let Some(&v) = effect_data.parameters.get("release_ms") { lim.set_release_ms(v); }
+                        if let Some(&v) = effect_data.parameters.get("lookahead_ms") { lim.set_lookahead_ms(v); }
+                        if let Some(&v) = effect_data.parameters.get("oversample") {
+                            if let Err(e) = lim.set_oversample(v.round() as u32) {
+                                eprintln!("⚠️  Ignoring limiter oversampling: {e}");
+                            }
+                        }
+                        EffectType::Limiter(lim)
+                    }
+                    "bitcrusher" => {
+                        let mut crs = BitCrusher::new();
+                        if let Some(&v) = effect_data.parameters.get("bit_depth") { crs.bit_depth = v; }
+                        if let Some(&v) = effect_data.parameters.get("sample_rate_reduction") { crs.sample_rate_reduction = v; }
+                        if let Some(&v) = effect_data.parameters.get("wet_dry_mix") { crs.wet_dry_mix = v; }
+                        EffectType::BitCrusher(crs)
+                    }
+                    "gate" => {
+                        let mut gate = NoiseGate::new();
+                        if let Some(&v) = effect_data.parameters.get("threshold_db") { gate.threshold_db = v; }
+                        if let Some(&v) = effect_data.parameters.get("attack_ms") { gate.attack_ms = v; }
+                        if let Some(&v) = effect_data.parameters.get("hold_ms") { gate.hold_ms = v; }
+                        if let Some(&v) = effect_data.parameters.get("release_ms") { gate.release_ms = v; }
+                        gate.update_coefficients();
+                        EffectType::NoiseGate(gate)
+                    }
+                    "stereo_width" => {
+                        let mut sw = StereoWidth::new();
+                        if let Some(&v) = effect_data.parameters.get("width") { sw.width = v; }
+                        EffectType::StereoWidth(sw)
+                    }
+                    "filter" => {
+                        let mut filter = Filter::new();
+                        if let Some(filter_type) = effect_data.parameters.get("filter_type").and_then(|&v| FilterType::from_index(v as u32)) {
+                            filter.filter_type = filter_type;
+                        }
+                        if let Some(&v) = effect_data.parameters.get("cutoff_hz") { filter.cutoff_hz = v; }
+                        if let Some(&v) = effect_data.parameters.get("resonance") { filter.resonance = v; }
+                        filter.update_coefficients();
+                        EffectType::Filter(filter)
+                    }
+                    "gain" => {
+                        let mut gain = Gain::new();
+                        let flag = |name: &str| effect_data.parameters.get(name).is_some_and(|&v| v >= 0.5);
+                        if let Some(&v) = effect_data.parameters.get("gain_db") { gain.gain_db = v; }
+                        gain.invert_left = flag("invert_left");
+                        gain.invert_right = flag("invert_right");
+                        gain.dc_block = flag("dc_block");
+                        EffectType::Gain(gain)
+                    }
                     _ => {
                         eprintln!("⚠️  Unknown effect type: {}", effect_data.effect_type);
                         continue;
@@ -451,6 +727,17 @@ impl AudioGraph {
 
                 // Add effect to effect manager
                 let effect_id = effect_manager.create_effect(effect);
+                if let Some(&mix) = effect_data.parameters.get(crate::effects::MIX_PARAM) {
+                    effect_manager.set_mix(effect_id, mix);
+                }
+                for (param_name, points) in &effect_data.automation {
+                    let points = points.iter()
+                        .map(|point| AutomationPoint::new(point.time_seconds, point.value))
+                        .collect();
+                    if let Err(e) = effect_manager.set_parameter_automation(effect_id, param_name, points) {
+                        eprintln!("⚠️  Skipping automation for {param_name}: {e}");
+                    }
+                }
 
                 // Add to track's FX chain
                 let tm = self.track_manager.lock();
@@ -523,11 +810,13 @@ impl AudioGraph {
             for clip_data in &track_data.clips {
                 if let Some(midi_notes) = &clip_data.midi_notes {
                     // Reconstruct MIDI clip from serialized notes (with saved duration)
-                    let midi_clip = reconstruct_midi_clip_from_notes(
+                    let mut midi_clip = reconstruct_midi_clip_from_notes(
                         midi_notes,
                         project_data.sample_rate,
                         clip_data.duration,
+                        self.recorder.time_signature_at_seconds(clip_data.start_time),
                     );
+                    add_midi_controls_to_clip(&mut midi_clip, &clip_data.midi_controls);
                     let clip_arc = Arc::new(midi_clip);
 
                     // Generate a new clip ID
@@ -579,6 +868,31 @@ impl AudioGraph {
 
         Ok(())
     }
+
+    /// Apply saved per-clip settings to an audio clip restored from a project
+    /// Called from the API layer once the clip has been re-attached to its track
+    pub fn restore_audio_clip_settings(&self, track_id: u64, clip_id: ClipId, clip_data: &crate::project::ClipData) {
+        let tm = self.track_manager.lock();
+        if let Some(track_arc) = tm.get_track(track_id) {
+            let mut track = track_arc.lock();
+            if let Some(clip) = track.audio_clips.iter_mut().find(|c| c.id == clip_id) {
+                clip.polarity_inverted = clip_data.polarity_inverted;
+                clip.mono_sum = clip_data.mono_sum;
+                clip.fade_in_seconds = clip_data.fade_in_seconds;
+                clip.fade_out_seconds = clip_data.fade_out_seconds;
+                clip.warp_enabled = clip_data.warp_enabled;
+                clip.stretch_factor = clip_data.stretch_factor.clamp(0.25, 4.0);
+                clip.warp_mode = clip_data.warp_mode;
+                clip.reversed = clip_data.reversed;
+                clip.transpose_semitones = clip_data.transpose_semitones.clamp(-48, 48);
+                clip.transpose_cents = clip_data.transpose_cents.clamp(-50, 50);
+                clip.gain_envelope = clip_data.gain_envelope.iter()
+                    .map(|point| AutomationPoint::new(point.time_seconds, point.value.min(24.0)))
+                    .collect();
+                clip.rebuild_stretched_cache();
+            }
+        }
+    }
 }
 
 // ============================================================================
@@ -616,7 +930,8 @@ pub(crate) fn convert_midi_events_to_notes(
                     });
                 }
             }
-            MidiEventType::NoteOn { .. } => {}
+            // Controller data is saved separately (see `convert_midi_events_to_controls`)
+            _ => {}
         }
     }
 
@@ -625,11 +940,59 @@ pub(crate) fn convert_midi_events_to_notes(
     notes
 }
 
+/// Convert MIDI controller events (pitch bend, CC, aftertouch) to `MidiControlData`
+pub(crate) fn convert_midi_events_to_controls(
+    events: &[crate::midi::MidiEvent],
+    sample_rate: u32,
+) -> Vec<crate::project::MidiControlData> {
+    use crate::midi::MidiEventType;
+    use crate::project::MidiControlData;
+
+    events.iter().filter_map(|event| {
+        let (kind, number, value) = match event.event_type {
+            MidiEventType::PitchBend { value } => ("pitch_bend", 0, value),
+            MidiEventType::ControlChange { controller, value } => ("cc", controller, i16::from(value)),
+            MidiEventType::PolyAftertouch { note, pressure } => ("poly_aftertouch", note, i16::from(pressure)),
+            MidiEventType::NoteOn { .. } | MidiEventType::NoteOff { .. } => return None,
+        };
+        Some(MidiControlData {
+            kind: kind.to_string(),
+            number,
+            value,
+            time: event.timestamp_samples as f64 / f64::from(sample_rate),
+        })
+    }).collect()
+}
+
+/// Add serialized controller events to a reconstructed `MidiClip` (unknown kinds are skipped)
+pub(crate) fn add_midi_controls_to_clip(clip: &mut MidiClip, controls: &[crate::project::MidiControlData]) {
+    use crate::midi::{MidiEvent, MidiEventType};
+
+    for control in controls {
+        let event_type = match control.kind.as_str() {
+            "pitch_bend" => MidiEventType::PitchBend { value: control.value.clamp(-8192, 8191) },
+            "cc" => MidiEventType::ControlChange {
+                controller: control.number.min(127),
+                value: control.value.clamp(0, 127) as u8,
+            },
+            "poly_aftertouch" => MidiEventType::PolyAftertouch {
+                note: control.number.min(127),
+                pressure: control.value.clamp(0, 127) as u8,
+            },
+            _ => continue,
+        };
+        let timestamp_samples = (control.time.max(0.0) * f64::from(clip.sample_rate)) as u64;
+        clip.events.push(MidiEvent::new(event_type, timestamp_samples));
+    }
+    clip.events.sort();
+}
+
 /// Reconstruct `MidiClip` from serialized `MidiNoteData`
 pub(crate) fn reconstruct_midi_clip_from_notes(
     notes: &[crate::project::MidiNoteData],
     sample_rate: u32,
     saved_duration: Option<f64>,
+    time_signature: crate::time_signature::TimeSignature,
 ) -> MidiClip {
     use crate::midi::{MidiClip, MidiEvent, MidiEventType};
 
@@ -649,9 +1012,6 @@ pub(crate) fn reconstruct_midi_clip_from_notes(
         ));
     }
 
-    // Sort events by timestamp
-    events.sort_by_key(|e| e.timestamp_samples);
-
     // Use saved duration if available, otherwise calculate from notes
     let duration_samples = if let Some(dur) = saved_duration {
         (dur * f64::from(sample_rate)) as u64
@@ -664,11 +1024,11 @@ pub(crate) fn reconstruct_midi_clip_from_notes(
     };
 
     // Apply snap_to_bar to ensure proper alignment
-    let snapped_duration = MidiClip::snap_to_bar(duration_samples, sample_rate);
+    let snapped_duration = MidiClip::snap_to_bar(duration_samples, sample_rate, time_signature);
 
-    MidiClip {
-        events,
-        duration_samples: snapped_duration,
-        sample_rate,
-    }
+    // Sorts the events and gives each note an ID
+    let mut clip = MidiClip::with_events(events, sample_rate);
+    clip.time_signature = time_signature;
+    clip.duration_samples = snapped_duration;
+    clip
 }
@@ -1,134 +1,213 @@
 /// Real-time audio render callback — runs on the audio thread
-use super::{AudioGraph, TransportState, interpolate_automation_gain};
+use super::{AudioGraph, MonitorMode, TransportState};
+use super::mixer::{keep_track_alive, process_effect_chain, process_effect_chain_block, process_track_block, resolve_bus_routing, resolve_latency_compensation, CompensationDelays, InputFrame, MixBlocks, TrackFrame, TrackSnapshot, MAX_COMPENSATED_TRACKS};
 use crate::audio_file::{AudioClip, TARGET_SAMPLE_RATE};
-use crate::track::{AutomationPoint, TimelineClip, TimelineMidiClip, TrackId};
+use crate::clip_stream::mark_realtime_thread;
+use crate::track::{TimelineClip, TrackId};
 use crate::effects::{Effect, EffectManager};
+use crate::tempo_map::TempoMap;
 use std::collections::HashMap;
 use std::sync::atomic::Ordering;
 
 #[cfg(not(target_arch = "wasm32"))]
 use cpal::traits::DeviceTrait;
+#[cfg(not(target_arch = "wasm32"))]
+use rubato::{FftFixedInOut, Resampler};
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::VecDeque;
 
-/// Track snapshot data extracted from locked tracks for lock-free audio processing.
-/// Defined at module scope so helper functions can reference it.
-struct TrackSnapshot {
-    id: u64,
-    audio_clips: Vec<TimelineClip>,
-    midi_clips: Vec<TimelineMidiClip>,
-    volume_gain: f32, // Static volume (used when no automation)
-    pan_left: f32,
-    pan_right: f32,
-    muted: bool,
-    soloed: bool,
-    fx_chain: Vec<u64>,
-    volume_automation: Vec<AutomationPoint>, // For per-frame interpolation
-    armed: bool,
-    input_monitoring: bool,
-    input_channel: u32,
-    is_audio_track: bool,
-    monitoring_fade_gain: f64,
-}
+/// Frames of master bus audio preallocated for block processing (covers the
+/// largest buffer size preset)
+#[cfg(not(target_arch = "wasm32"))]
+const MASTER_BLOCK_CAPACITY: usize = 4096;
+
+/// Engine-rate chunk size hint (in frames) for converting output to the device rate
+#[cfg(not(target_arch = "wasm32"))]
+const OUTPUT_RESAMPLE_CHUNK_FRAMES: usize = 256;
 
 // ── Helper functions for the audio callback ─────────────────────────────
 // These are called from the hot path — no allocations, no panics.
 
-/// Read stereo input samples from the input manager.
+/// Read one frame of input (every device channel) from the input manager.
 /// Uses try_lock to avoid blocking the audio thread.
 #[cfg(not(target_arch = "wasm32"))]
 #[inline]
-fn read_input_samples(input_manager: &parking_lot::Mutex<crate::audio_input::AudioInputManager>) -> (f32, f32) {
-    if let Some(input_mgr) = input_manager.try_lock() {
-        let channels = input_mgr.get_input_channels();
-        if channels == 1 {
-            if let Some(samples) = input_mgr.read_samples(1) {
-                let s = samples.first().copied().unwrap_or(0.0);
-                (s, s)
-            } else {
-                (0.0, 0.0)
+fn read_input_frame(input_manager: &parking_lot::Mutex<crate::audio_input::AudioInputManager>) -> InputFrame {
+    input_manager
+        .try_lock()
+        .and_then(|input_mgr| input_mgr.read_frame())
+        .unwrap_or_default()
+}
+
+/// The device's output config at `TARGET_SAMPLE_RATE` if it supports that rate,
+/// otherwise its default config (the mix is then resampled by `OutputResampler`)
+#[cfg(not(target_arch = "wasm32"))]
+fn negotiate_output_config(device: &cpal::Device) -> anyhow::Result<cpal::SupportedStreamConfig> {
+    let default_config = device.default_output_config()?;
+    let target = cpal::SampleRate(TARGET_SAMPLE_RATE);
+    let at_target = device.supported_output_configs()?.find(|range| {
+        range.channels() == default_config.channels()
+            && range.sample_format() == default_config.sample_format()
+            && range.min_sample_rate() <= target
+            && target <= range.max_sample_rate()
+    });
+    Ok(at_target.map_or(default_config, |range| range.with_sample_rate(target)))
+}
+
+/// Converts the engine's `TARGET_SAMPLE_RATE` output to the device rate
+///
+/// The engine renders fixed-size chunks into `pending`, which are resampled into
+/// `queued` until it holds a device buffer. Buffers are allocated up front (and only
+/// grow if the device asks for more frames than expected), so processing is safe on
+/// the audio thread.
+#[cfg(not(target_arch = "wasm32"))]
+struct OutputResampler {
+    resampler: FftFixedInOut<f32>,
+    /// Interleaved engine-rate chunk from the render callback
+    pending: Vec<f32>,
+    /// Deinterleaved engine-rate chunk fed to the resampler
+    input: Vec<Vec<f32>>,
+    /// Resampled output of the last chunk, per channel
+    output: Vec<Vec<f32>>,
+    /// Device-rate interleaved samples not yet handed to the device
+    queued: VecDeque<f32>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl OutputResampler {
+    fn new(device_rate: u32) -> anyhow::Result<Self> {
+        let resampler = FftFixedInOut::<f32>::new(
+            TARGET_SAMPLE_RATE as usize,
+            device_rate as usize,
+            OUTPUT_RESAMPLE_CHUNK_FRAMES,
+            2,
+        )?;
+        let chunk_frames = resampler.input_frames_next();
+        let output_frames = resampler.output_frames_max();
+        Ok(Self {
+            pending: vec![0.0; chunk_frames * 2],
+            input: vec![vec![0.0; chunk_frames]; 2],
+            output: vec![vec![0.0; output_frames]; 2],
+            queued: VecDeque::with_capacity((MASTER_BLOCK_CAPACITY + output_frames) * 2),
+            resampler,
+        })
+    }
+
+    /// Fill the interleaved stereo device buffer `data`, rendering engine-rate
+    /// chunks with `render` as needed
+    fn process(&mut self, data: &mut [f32], render: &mut impl FnMut(&mut [f32])) {
+        while self.queued.len() < data.len() {
+            render(&mut self.pending);
+            for (channel, input) in self.input.iter_mut().enumerate() {
+                for (sample, frame) in input.iter_mut().zip(self.pending.chunks_exact(2)) {
+                    *sample = frame[channel];
+                }
+            }
+            let frames_out = match self.resampler.process_into_buffer(&self.input, &mut self.output, None) {
+                Ok((_, frames_out)) => frames_out,
+                Err(_) => 0,
+            };
+            if frames_out == 0 {
+                break;
+            }
+            for index in 0..frames_out {
+                self.queued.push_back(self.output[0][index]);
+                self.queued.push_back(self.output[1][index]);
             }
-        } else if let Some(samples) = input_mgr.read_samples(2) {
-            (
-                samples.first().copied().unwrap_or(0.0),
-                samples.get(1).copied().unwrap_or(0.0),
-            )
-        } else {
-            (0.0, 0.0)
         }
-    } else {
-        (0.0, 0.0)
+
+        for sample in data.iter_mut() {
+            *sample = self.queued.pop_front().unwrap_or(0.0);
+        }
     }
 }
 
-/// Update monitoring fade gain with a 20ms ramp to avoid clicks.
-/// Modifies `fade_gain` in place toward 0.0 or 1.0.
+/// Where the playhead should jump when it reaches the loop end.
+/// Returns the loop start in real (pre-tempo) samples, or None if no wrap is due.
+/// The tempo map is only locked when the playhead wraps.
 #[inline]
-fn update_monitoring_fade(fade_gain: &mut f64, should_monitor: bool) {
-    let target = if should_monitor { 1.0_f64 } else { 0.0_f64 };
-    #[allow(clippy::float_cmp)]
-    if *fade_gain != target {
-        let step = 1.0 / (0.020 * f64::from(TARGET_SAMPLE_RATE));
-        if target > *fade_gain {
-            *fade_gain = (*fade_gain + step).min(1.0);
-        } else {
-            *fade_gain = (*fade_gain - step).max(0.0);
-        }
+fn loop_wrap_target(playhead_seconds: f64, tempo_map: &parking_lot::Mutex<TempoMap>, loop_region: Option<(f64, f64)>) -> Option<u64> {
+    let (loop_start, loop_end) = loop_region?;
+    if playhead_seconds >= loop_end {
+        Some(tempo_map.lock().sample_at_timeline_seconds(loop_start))
+    } else {
+        None
     }
 }
 
 /// Render a single audio clip at the given playhead position.
 /// Returns (left, right) sample values, or (0, 0) if the playhead is outside the clip.
 #[inline]
-fn render_audio_clip_sample(timeline_clip: &TimelineClip, playhead_seconds: f64) -> (f32, f32) {
+pub(super) fn render_audio_clip_sample(timeline_clip: &TimelineClip, playhead_seconds: f64) -> (f32, f32) {
     let clip_duration = timeline_clip
         .duration
         .unwrap_or(timeline_clip.clip.duration_seconds);
-    let effective_duration = if timeline_clip.warp_enabled {
-        clip_duration / f64::from(timeline_clip.stretch_factor)
-    } else {
-        clip_duration
-    };
+    let effective_duration = timeline_clip.timeline_duration();
     let clip_end = timeline_clip.start_time + effective_duration;
 
     if playhead_seconds < timeline_clip.start_time || playhead_seconds >= clip_end {
         return (0.0, 0.0);
     }
 
-    let time_in_clip = playhead_seconds - timeline_clip.start_time + timeline_clip.offset;
-    let clip_gain = timeline_clip.get_gain();
-    let pitch_ratio = f64::from(timeline_clip.get_pitch_ratio());
-
-    let (frame_in_clip, source_clip): (usize, &AudioClip) = if timeline_clip.warp_enabled {
+    // `offset` is measured in the source audio, so it is never scaled by stretch
+    let elapsed = playhead_seconds - timeline_clip.start_time;
+    let offset = timeline_clip.offset;
+    let stretch = f64::from(timeline_clip.stretch_factor);
+    let clip_gain = timeline_clip.get_gain()
+        * timeline_clip.get_fade_gain(elapsed, effective_duration)
+        * timeline_clip.get_envelope_gain(elapsed);
+
+    // `time_scale` maps source seconds to seconds in `source_clip` (the warp cache is stretched)
+    // Transpose is baked into the cache, so it never changes the read speed
+    let (frame_in_clip, source_clip, time_scale): (usize, &AudioClip, f64) = if timeline_clip.warp_enabled {
         if timeline_clip.warp_mode == 0 {
             // Warp mode: use pre-stretched cached audio (pitch preserved)
             if let Some(ref stretched) = timeline_clip.stretched_cache {
-                let frame = (time_in_clip * pitch_ratio * f64::from(TARGET_SAMPLE_RATE)) as usize;
-                (frame, stretched.as_ref())
+                let stretched_time = offset / stretch + elapsed;
+                let frame = (stretched_time * f64::from(TARGET_SAMPLE_RATE)) as usize;
+                (frame, stretched.as_ref(), 1.0 / stretch)
             } else {
                 // Fallback to Re-Pitch if cache not ready
-                let stretched_time =
-                    time_in_clip * f64::from(timeline_clip.stretch_factor) * pitch_ratio;
+                let stretched_time = offset + elapsed * stretch;
                 (
                     (stretched_time * f64::from(TARGET_SAMPLE_RATE)) as usize,
                     &*timeline_clip.clip,
+                    1.0,
                 )
             }
         } else {
-            // Re-Pitch mode: sample-rate shift (pitch follows speed)
-            let stretched_time =
-                time_in_clip * f64::from(timeline_clip.stretch_factor) * pitch_ratio;
+            // Re-Pitch mode: sample-rate shift (pitch follows speed), on top of any transpose
+            let stretched_time = offset + elapsed * stretch;
             (
                 (stretched_time * f64::from(TARGET_SAMPLE_RATE)) as usize,
-                &*timeline_clip.clip,
+                timeline_clip.stretched_cache.as_deref().unwrap_or(&timeline_clip.clip),
+                1.0,
             )
         }
     } else {
-        // No warp — apply pitch ratio for transpose
+        // No warp — a transposed clip reads its pitch-shifted cache (same length)
         (
-            (time_in_clip * pitch_ratio * f64::from(TARGET_SAMPLE_RATE)) as usize,
-            &*timeline_clip.clip,
+            ((offset + elapsed) * f64::from(TARGET_SAMPLE_RATE)) as usize,
+            timeline_clip.stretched_cache.as_deref().unwrap_or(&timeline_clip.clip),
+            1.0,
         )
     };
 
+    let frame_in_clip = if timeline_clip.reversed {
+        // Mirror the read position within the clip's active (trimmed) region
+        let sample_rate = f64::from(TARGET_SAMPLE_RATE);
+        let region_start = (offset * time_scale * sample_rate) as usize;
+        let region_end = (((offset + clip_duration) * time_scale * sample_rate) as usize)
+            .min(source_clip.frame_count());
+        let into_region = frame_in_clip.saturating_sub(region_start);
+        match region_end.checked_sub(into_region + 1) {
+            Some(frame) if frame >= region_start => frame,
+            _ => return (0.0, 0.0),
+        }
+    } else {
+        frame_in_clip
+    };
+
     let left = source_clip.get_sample(frame_in_clip, 0).unwrap_or(0.0) * clip_gain;
     let right = if source_clip.channels > 1 {
         source_clip.get_sample(frame_in_clip, 1).unwrap_or(0.0) * clip_gain
@@ -136,33 +215,80 @@ fn render_audio_clip_sample(timeline_clip: &TimelineClip, playhead_seconds: f64)
         left // mono clip — duplicate to right
     };
 
-    (left, right)
+    timeline_clip.apply_channel_utilities(left, right)
+}
+
+/// Feed a sidechain compressor the level its source track produced this frame.
+/// Sources that were not mixed this frame (muted, not soloed) read as silence.
+#[inline]
+pub(super) fn feed_sidechain(effect: &mut crate::effects::EffectType, sidechain_levels: &HashMap<u64, f32>) {
+    if let crate::effects::EffectType::Compressor(comp) = effect {
+        if let Some(source_id) = comp.sidechain_track_id {
+            comp.set_sidechain_level(sidechain_levels.get(&source_id).copied().unwrap_or(0.0));
+        }
+    }
 }
 
-/// Process an effect chain through a locked `EffectManager`.
-/// When `silent` is true, feeds zeros to keep VST3 plugins alive (muted tracks).
+/// Level a track's output frame contributes to sidechain detectors (stereo RMS)
 #[inline]
-fn process_effect_chain(
-    fx_chain: &[u64],
+pub(super) fn sidechain_level(left: f32, right: f32) -> f32 {
+    f32::midpoint(left * left, right * right).sqrt()
+}
+
+/// Collect the tracks that sidechain compressors key from (no duplicates).
+pub(super) fn collect_sidechain_sources<'a>(
+    fx_chains: impl Iterator<Item = &'a [u64]>,
     effect_mgr: &EffectManager,
-    left: f32,
-    right: f32,
-    silent: bool,
-) -> (f32, f32) {
-    let mut out_l = if silent { 0.0 } else { left };
-    let mut out_r = if silent { 0.0 } else { right };
-    for effect_id in fx_chain {
-        if !silent && effect_mgr.is_bypassed(*effect_id) {
-            continue;
+    sources: &mut Vec<u64>,
+) {
+    sources.clear();
+    for fx_chain in fx_chains {
+        for effect_id in fx_chain {
+            if let Some(source_id) = effect_mgr.sidechain_source(*effect_id) {
+                if !sources.contains(&source_id) {
+                    sources.push(source_id);
+                }
+            }
         }
-        if let Some(effect_arc) = effect_mgr.get_effect(*effect_id) {
-            let mut effect = effect_arc.lock();
-            let (fx_l, fx_r) = effect.process_frame(out_l, out_r);
-            out_l = fx_l;
-            out_r = fx_r;
+    }
+}
+
+/// Move sidechain source tracks to the front so they are mixed before the
+/// tracks whose compressors read their level. Relative order is otherwise kept.
+/// Only one level of routing is ordered: a source keyed from another source may
+/// see that source's level one frame late.
+pub(super) fn mix_sidechain_sources_first<T>(tracks: &mut [T], track_id: impl Fn(&T) -> u64, sources: &[u64]) {
+    if sources.is_empty() {
+        return;
+    }
+    let mut next = 0;
+    for i in 0..tracks.len() {
+        if sources.contains(&track_id(&tracks[i])) {
+            tracks[next..=i].rotate_right(1);
+            next += 1;
+        }
+    }
+}
+
+/// Write a buffer's track meters and monitoring fades back to the tracks
+#[cfg(not(target_arch = "wasm32"))]
+fn store_track_meters(
+    tm: &crate::track::TrackManager,
+    snapshots: &[TrackSnapshot],
+    peaks: &HashMap<TrackId, (f32, f32)>,
+    (master_peak_left, master_peak_right): (f32, f32),
+) {
+    for track_snap in snapshots {
+        if let Some(track_arc) = tm.get_track(track_snap.id) {
+            track_arc.lock().monitoring_fade_gain = track_snap.monitoring_fade_gain;
+        }
+    }
+    for (track_id, (peak_l, peak_r)) in peaks {
+        if let Some(track_arc) = tm.get_track(*track_id) {
+            track_arc.lock().update_peaks(*peak_l, *peak_r);
         }
     }
-    (out_l, out_r)
+    tm.get_master_track().lock().update_peaks(master_peak_left, master_peak_right);
 }
 
 impl AudioGraph {
@@ -253,7 +379,14 @@ impl AudioGraph {
             eprintln!("🔊 [AudioGraph] Using device: {name}");
         }
 
-        let supported_config = device.default_output_config()?;
+        // Note when the selected device was missing, so it can be picked up when it returns
+        let device_name = device.name().ok();
+        let on_fallback = selected_name.as_ref().is_some_and(|name| {
+            device_name.as_deref() != Some(name.strip_prefix("[ASIO] ").unwrap_or(name))
+        });
+        self.output_on_fallback.store(on_fallback, Ordering::SeqCst);
+
+        let supported_config = negotiate_output_config(&device)?;
         eprintln!("🔊 [AudioGraph] Device config: {supported_config:?}");
 
         // Get preferred buffer size
@@ -291,13 +424,29 @@ impl AudioGraph {
         // Clone Arcs for the audio callback
         let playhead_samples = self.playhead_samples.clone();
         let state = self.state.clone();
+        let loop_start_seconds = self.loop_start_seconds.clone();
+        let loop_end_seconds = self.loop_end_seconds.clone();
+        let loop_enabled = self.loop_enabled.clone();
         let input_manager = self.input_manager.clone();
         let recorder_refs = self.recorder.get_callback_refs();
+        let output_device_lost = self.output_device_lost.clone();
+        let output_device_changed = self.output_device_changed.clone();
 
         // M4: Clone track and effect managers
         let track_manager = self.track_manager.clone();
         let effect_manager = self.effect_manager.clone();
         let master_limiter = self.master_limiter.clone();
+        let master_loudness = self.master_loudness.clone();
+        let mut loudness_meter = crate::loudness::LoudnessMeter::new();
+        let master_spectrum = self.master_spectrum.clone();
+        let mut spectrum_analyzer = crate::spectrum::SpectrumAnalyzer::new();
+        let master_true_peak = self.master_true_peak.clone();
+        let mut true_peak_meter = crate::true_peak::TruePeakMeter::new();
+        let dsp_load = self.dsp_load.clone();
+        let mut dsp_load_meter = crate::dsp_load::DspLoadMeter::new(TARGET_SAMPLE_RATE);
+        let scrub_player = self.scrub_player.clone();
+        let reference_track = self.reference_track.clone();
+        let monitor_mode = self.monitor_mode.clone();
 
         // M6: Clone track synth manager
         let track_synth_manager = self.track_synth_manager.clone();
@@ -309,463 +458,404 @@ impl AudioGraph {
         // per-callback allocations on the audio thread
         let mut snapshot_buf: Vec<TrackSnapshot> = Vec::with_capacity(16);
         let mut peak_buf: HashMap<TrackId, (f32, f32)> = HashMap::with_capacity(16);
+        // Sidechain routing: source tracks, mixed before the tracks keyed from them
+        let mut sidechain_sources: Vec<TrackId> = Vec::with_capacity(16);
+        // Per-frame inputs shared by every track, and the blocks tracks are mixed
+        // through (bus inputs, sidechain levels, the master sum)
+        let mut frame_buf: Vec<TrackFrame> = Vec::with_capacity(MASTER_BLOCK_CAPACITY);
+        let mut mix_blocks = MixBlocks::new(MAX_COMPENSATED_TRACKS, MASTER_BLOCK_CAPACITY);
+        // Plugin delay compensation: per-track delay lines, kept across buffers
+        let mut compensation_delays = CompensationDelays::new(MAX_COMPENSATED_TRACKS);
+        // Master bus input for the whole buffer, block-processed after the tracks
+        // (only grows if the device asks for more frames than this)
+        let mut master_block_left: Vec<f32> = Vec::with_capacity(MASTER_BLOCK_CAPACITY);
+        let mut master_block_right: Vec<f32> = Vec::with_capacity(MASTER_BLOCK_CAPACITY);
+
+        // Renders one buffer at the engine rate (interleaved stereo)
+        let mut render = move |data: &mut [f32]| {
+            // Track actual buffer size (frames = samples / 2 for stereo)
+            let frames = data.len() / 2;
+            actual_buffer_size.store(frames as u32, Ordering::Relaxed);
+            // Times the whole callback, including the early return while stopped
+            let _dsp_load = dsp_load_meter.measure(frames, &dsp_load);
+            // Streamed clips must not read from disk on this thread
+            mark_realtime_thread();
+
+            // Check if we should be playing (lock-free atomic read)
+            let is_playing = state.load(Ordering::SeqCst) == TransportState::Playing as u8;
+            let monitor_input = monitor_mode.load(Ordering::Relaxed) == MonitorMode::Software as u8;
+
+            if !is_playing {
+                // Even when not playing, we might be recording or using virtual piano
+                // Process metronome, recording, AND synths (for real-time MIDI input)
+                // but DON'T advance playhead or trigger MIDI clips from timeline
+
+                // Get current playhead for latency test sample counting
+                let current_playhead = playhead_samples.load(Ordering::SeqCst);
+                let playhead_seconds = recorder_refs.tempo_map.lock().timeline_seconds_at_sample(current_playhead);
 
-        let stream = device.build_output_stream(
-            &config,
-            move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
-                // Track actual buffer size (frames = samples / 2 for stereo)
-                let frames = data.len() / 2;
-                actual_buffer_size.store(frames as u32, Ordering::Relaxed);
-
-                // Check if we should be playing (lock-free atomic read)
-                let is_playing = state.load(Ordering::SeqCst) == TransportState::Playing as u8;
-
-                if !is_playing {
-                    // Even when not playing, we might be recording or using virtual piano
-                    // Process metronome, recording, AND synths (for real-time MIDI input)
-                    // but DON'T advance playhead or trigger MIDI clips from timeline
-
-                    // Get current playhead for latency test sample counting
-                    let current_playhead = playhead_samples.load(Ordering::SeqCst);
-
-                    // Lock synth manager once for the entire buffer
-                    let mut synth_guard = Some(track_synth_manager.lock());
-
-                    for frame_idx in 0..frames {
-                        let (input_left, input_right) = read_input_samples(&input_manager);
-
-                        // Process recording and get metronome output
-                        let (met_left, met_right) = recorder_refs.process_frame(input_left, input_right, false, 0.0);
-
-                        // Start with metronome output
-                        let mut out_left = met_left;
-                        let mut out_right = met_right;
-                        let mut master_peak_left = 0.0f32;
-                        let mut master_peak_right = 0.0f32;
-
-                        // Process each track (synth + VST3 instruments + volume/pan + metering)
-                        // This is necessary for:
-                        // 1. Per-track synthesizer output from MIDI input
-                        // 2. VST3 instruments that need continuous process() calls
-                        // 3. Track-level metering for level meters in UI
-                        { let effect_mgr = effect_manager.lock();
-                            { let tm = track_manager.lock();
-                                let has_solo = tm.has_solo();
-                                for track_arc in tm.get_all_tracks() {
-                                    { let mut track = track_arc.lock();
-                                        // Skip master track in per-track processing
-                                        if track.track_type == crate::track::TrackType::Master {
-                                            continue;
-                                        }
-
-                                        // Get per-track synth output FIRST
-                                        let mut track_left = 0.0f32;
-                                        let mut track_right = 0.0f32;
-
-                                        if let Some(ref mut synth_manager) = synth_guard {
-                                            let (synth_left, synth_right) = synth_manager.process_sample_stereo(track.id);
-                                            track_left += synth_left;
-                                            track_right += synth_right;
-                                        }
-
-                                        // Input monitoring: mix live input for armed audio tracks
-                                        {
-                                            let should_monitor = track.armed && track.input_monitoring
-                                                && track.track_type == crate::track::TrackType::Audio;
-                                            update_monitoring_fade(&mut track.monitoring_fade_gain, should_monitor);
-
-                                            if track.monitoring_fade_gain > 0.0 {
-                                                let ch = track.input_channel as usize;
-                                                let input_sample = if ch == 0 { input_left } else { input_right };
-                                                track_left += input_sample * track.monitoring_fade_gain as f32;
-                                                track_right += input_sample * track.monitoring_fade_gain as f32;
-                                            }
-                                        }
-
-                                        // Handle mute/solo
-                                        if track.mute {
-                                            // Process FX with silence to keep VST3 alive
-                                            process_effect_chain(&track.fx_chain, &effect_mgr, 0.0, 0.0, true);
-                                            if frame_idx == frames - 1 {
-                                                track.update_peaks(0.0, 0.0);
-                                            }
-                                            continue;
-                                        }
-                                        if has_solo && !track.solo {
-                                            process_effect_chain(&track.fx_chain, &effect_mgr, 0.0, 0.0, true);
-                                            if frame_idx == frames - 1 {
-                                                track.update_peaks(0.0, 0.0);
-                                            }
-                                            continue;
-                                        }
-
-                                        // Process FX chain for this track
-                                        let (fx_l, fx_r) = process_effect_chain(&track.fx_chain, &effect_mgr, track_left, track_right, false);
-                                        track_left = fx_l;
-                                        track_right = fx_r;
-
-                                        // Apply track volume and pan AFTER FX chain
-                                        let volume_gain = track.get_gain();
-                                        let (pan_left, pan_right) = track.get_pan_gains();
-
-                                        track_left *= volume_gain * pan_left;
-                                        track_right *= volume_gain * pan_right;
-
-                                        // Update track peak levels for metering
-                                        // This allows UI to show level meters even when stopped
-                                        let current_peak_left = track.peak_left;
-                                        let current_peak_right = track.peak_right;
-                                        track.update_peaks(
-                                            current_peak_left.max(track_left.abs()),
-                                            current_peak_right.max(track_right.abs())
-                                        );
-
-                                        // Mix into output
-                                        out_left += track_left;
-                                        out_right += track_right;
-                                    }
-                                }
-
-                                // Update master track peaks
-                                master_peak_left = master_peak_left.max(out_left.abs());
-                                master_peak_right = master_peak_right.max(out_right.abs());
-
-                                // Update master track peaks at end of buffer
-                                if frame_idx == frames - 1 {
-                                    let master_arc = tm.get_master_track();
-                                    { let mut master = master_arc.lock();
-                                        master.update_peaks(master_peak_left, master_peak_right);
-                                    };
-                                }
-                            }
+                // Tracks still run while stopped: per-track synths play live MIDI input,
+                // VST3 instruments need continuous process() calls, and the UI shows
+                // track meters
+                snapshot_buf.clear();
+                peak_buf.clear();
+                let has_solo = { let tm = track_manager.lock();
+                    for track_arc in tm.get_all_tracks() {
+                        let track = track_arc.lock();
+                        if track.track_type != crate::track::TrackType::Master {
+                            snapshot_buf.push(TrackSnapshot::new(&track));
                         }
-
-                        // Process latency test (if running)
-                        let sample_idx = current_playhead.wrapping_add(frame_idx as u64);
-                        latency_test.process_input(input_left, sample_idx);
-                        let test_tone = latency_test.generate_output(sample_idx);
-                        out_left += test_tone;
-                        out_right += test_tone;
-
-                        // Mix library preview audio (independent of transport)
-                        let (preview_left, preview_right) = crate::api::preview::preview_process_sample();
-                        out_left += preview_left;
-                        out_right += preview_right;
-
-                        // Output metronome + synths + VST3 + preview when not playing
-                        data[frame_idx * 2] = out_left;
-                        data[frame_idx * 2 + 1] = out_right;
                     }
-                    return;
-                }
-
-                // frames already calculated at top of callback
-                let current_playhead = playhead_samples.load(Ordering::SeqCst);
+                    tm.has_solo()
+                };
+                resolve_bus_routing(&mut snapshot_buf);
+                // Live input isn't compensated; playback restarts from silent delay lines
+                compensation_delays.clear();
+
+                // Lock synth and effect managers once for the entire buffer
+                let mut synth_manager = track_synth_manager.lock();
+                let effect_mgr = effect_manager.lock();
+                // Skip the scrub grain for a buffer rather than wait on the API
+                let mut scrub = scrub_player.try_lock();
+
+                // Recording, metronome and the sources outside the mix, frame by frame;
+                // the tracks are mixed in below
+                frame_buf.clear();
+                for frame_idx in 0..frames {
+                    let input = read_input_frame(&input_manager);
 
-                // Get current tempo for playback scaling
-                // Timeline positions are tempo-dependent: at 120 BPM, 1 timeline second = 1 real second
-                // At other tempos, the playhead must advance faster/slower through the timeline
-                let current_tempo = *recorder_refs.tempo.lock();
-                let tempo_ratio = current_tempo / 120.0;
+                    // Process recording and get metronome output
+                    let (mut out_left, mut out_right) = recorder_refs.process_frame(input.as_slice(), false, 0.0);
 
-                // NOTE: Legacy MIDI clip processing removed - all MIDI now handled per-track
+                    // Process latency test (if running)
+                    let sample_idx = current_playhead.wrapping_add(frame_idx as u64);
+                    latency_test.process_input(input.channel(0), sample_idx);
+                    let test_tone = latency_test.generate_output(sample_idx);
+                    out_left += test_tone;
+                    out_right += test_tone;
 
-                // M5.5: Track-based mixing (replaces legacy clip mixing)
+                    // Mix library preview audio (independent of transport)
+                    let (preview_left, preview_right) = crate::api::preview::preview_process_sample();
+                    out_left += preview_left;
+                    out_right += preview_right;
+
+                    // Scrub grain while the playhead is dragged
+                    if let Some(scrub) = scrub.as_mut() {
+                        let (scrub_left, scrub_right) = scrub.next_frame();
+                        out_left += scrub_left;
+                        out_right += scrub_right;
+                    }
 
-                // Reuse pre-allocated buffers (clear without deallocating)
-                snapshot_buf.clear();
-                peak_buf.clear();
+                    data[frame_idx * 2] = out_left;
+                    data[frame_idx * 2 + 1] = out_right;
+
+                    frame_buf.push(TrackFrame {
+                        playhead_frame: current_playhead,
+                        playhead_seconds,
+                        play_timeline: false,
+                        recording: false,
+                        input,
+                        monitor_input,
+                        refresh_automation: frame_idx == 0,
+                        apply_fader: true,
+                        preview: false,
+                        release_notes: false,
+                    });
+                }
 
-                let (has_solo, master_snapshot) = { let tm = track_manager.lock();
-                    let has_solo_flag = tm.has_solo();
-                    let all_tracks = tm.get_all_tracks();
-                    let mut master_snap = None;
-
-                    for track_arc in all_tracks {
-                        { let track = track_arc.lock();
-                            let snap = TrackSnapshot {
-                                id: track.id,
-                                audio_clips: track.audio_clips.clone(),
-                                midi_clips: track.midi_clips.clone(),
-                                volume_gain: track.get_gain(),
-                                pan_left: track.get_pan_gains().0,
-                                pan_right: track.get_pan_gains().1,
-                                muted: track.mute,
-                                soloed: track.solo,
-                                fx_chain: track.fx_chain.clone(),
-                                volume_automation: track.volume_automation.clone(),
-                                armed: track.armed,
-                                input_monitoring: track.input_monitoring,
-                                input_channel: track.input_channel,
-                                is_audio_track: track.track_type == crate::track::TrackType::Audio,
-                                monitoring_fade_gain: track.monitoring_fade_gain,
-                            };
-
-                            if track.track_type == crate::track::TrackType::Master {
-                                master_snap = Some(snap);
-                            } else {
-                                snapshot_buf.push(snap);
-                            }
-                        }
+                // Tracks run a block at a time (see `process_track_block`)
+                mix_blocks.reset(frames);
+                for track_snap in &mut snapshot_buf {
+                    if track_snap.is_silenced(has_solo) {
+                        // Keep the instrument and FX (VST3) running on silence
+                        keep_track_alive(track_snap, &mut synth_manager, &effect_mgr, frames, &mut mix_blocks);
+                        peak_buf.insert(track_snap.id, (0.0, 0.0));
+                        continue;
                     }
 
-                    (has_solo_flag, master_snap)
-                }; // All locks released here!
+                    // Track meters show levels even when stopped
+                    let meter = process_track_block(track_snap, &mut synth_manager, &effect_mgr, &frame_buf, false, &mut compensation_delays, &mut mix_blocks);
+                    peak_buf.insert(track_snap.id, meter);
+                }
+
+                // Output metronome + synths + VST3 + preview when not playing
                 let mut master_peak_left = 0.0f32;
                 let mut master_peak_right = 0.0f32;
-
-                // OPTIMIZATION: Lock synth manager ONCE before the frame loop
-                // This prevents lock contention that causes audio dropouts
-                let mut synth_guard = Some(track_synth_manager.lock());
-
-                // Check if recording is active (skip clip playback on armed tracks)
-                let is_recording = *recorder_refs.state.lock() == crate::recorder::RecordingState::Recording;
-
-                // Process each frame (using snapshots - NO LOCKS in hot path!)
                 for frame_idx in 0..frames {
-                    let playhead_frame = current_playhead + frame_idx as u64;
-                    // Apply tempo ratio: at 120 BPM, playhead advances 1:1 with real time
-                    // At 100 BPM, playhead advances slower (0.833x) through timeline
-                    // At 140 BPM, playhead advances faster (1.167x) through timeline
-                    let real_seconds = playhead_frame as f64 / f64::from(TARGET_SAMPLE_RATE);
-                    let playhead_seconds = real_seconds * tempo_ratio;
-
-                    let mut mix_left = 0.0;
-                    let mut mix_right = 0.0;
-
-                    // Read input samples (needed for both recording and input monitoring)
-                    let (input_left, input_right) = read_input_samples(&input_manager);
-
-                    // Mix all tracks using snapshots (no locking!)
-                    for track_snap in &mut snapshot_buf {
-                        // Handle mute/solo logic
-                        if track_snap.muted {
-                            continue; // Muted tracks produce no sound
-                        }
-                        if has_solo && !track_snap.soloed {
-                            continue; // If any track is soloed, skip non-soloed tracks
-                        }
+                    let (mix_left, mix_right) = mix_blocks.master(frame_idx);
+                    master_peak_left = master_peak_left.max(mix_left.abs());
+                    master_peak_right = master_peak_right.max(mix_right.abs());
+                    data[frame_idx * 2] += mix_left;
+                    data[frame_idx * 2 + 1] += mix_right;
+                }
+                drop(scrub);
+                drop(effect_mgr);
+                drop(synth_manager);
 
-                        let mut track_left = 0.0;
-                        let mut track_right = 0.0;
+                store_track_meters(&track_manager.lock(), &snapshot_buf, &peak_buf, (master_peak_left, master_peak_right));
 
-                        // Skip existing clip playback on armed tracks during recording
-                        // (user should only hear new input, not old overlapping clips)
-                        let skip_clips = track_snap.armed && is_recording;
+                // Playback count-in finished: start the transport from the next buffer
+                if recorder_refs.take_playback_count_in_done() {
+                    state.store(TransportState::Playing as u8, Ordering::SeqCst);
+                }
+                return;
+            }
 
-                        // Mix all audio clips on this track
-                        if !skip_clips {
-                            for timeline_clip in &track_snap.audio_clips {
-                                let (cl, cr) = render_audio_clip_sample(timeline_clip, playhead_seconds);
-                                track_left += cl;
-                                track_right += cr;
-                            }
-                        }
+            // frames already calculated at top of callback
+            let current_playhead = playhead_samples.load(Ordering::SeqCst);
 
-                        // Process per-track MIDI clips (using pre-acquired synth lock)
-                        // Route MIDI to EITHER built-in synth OR VST3 instruments (not both)
-                        if let Some(ref mut synth_manager) = synth_guard {
-                            // Check if track has VST3 plugins - if so, skip built-in synth
-                            let has_vst3 = !track_snap.fx_chain.is_empty();
-
-                            for timeline_midi_clip in &track_snap.midi_clips {
-                                if skip_clips { continue; }
-                                let clip_start_samples = (timeline_midi_clip.start_time * f64::from(TARGET_SAMPLE_RATE)) as u64;
-                                let clip_end_samples = clip_start_samples + timeline_midi_clip.clip.duration_samples;
-
-                                // Check if clip is active at this frame
-                                // Use <= for end boundary to ensure note-offs at exact clip end are triggered
-                                if playhead_frame >= clip_start_samples && playhead_frame <= clip_end_samples {
-                                    let frame_in_clip = playhead_frame - clip_start_samples;
-
-                                    // Check for MIDI events that should trigger at this exact sample
-                                    for event in &timeline_midi_clip.clip.events {
-                                        if event.timestamp_samples == frame_in_clip {
-                                            match event.event_type {
-                                                crate::midi::MidiEventType::NoteOn { note, velocity } => {
-                                                    // Send to built-in synth ONLY if no VST3 plugins
-                                                    if !has_vst3 {
-                                                        synth_manager.note_on(track_snap.id, note, velocity);
-                                                    }
-
-                                                    // Send to VST3 instruments in FX chain
-                                                    if has_vst3 {
-                                                        { let effect_mgr = effect_manager.lock();
-                                                            for effect_id in &track_snap.fx_chain {
-                                                                if let Some(effect_arc) = effect_mgr.get_effect(*effect_id) {
-                                                                    { let mut effect = effect_arc.lock();
-                                                                        #[cfg(all(feature = "vst3", not(target_os = "ios")))]
-                                                                        if let crate::effects::EffectType::VST3(ref mut vst3) = *effect {
-                                                                            let _ = vst3.process_midi_event(0, 0, i32::from(note), i32::from(velocity), 0);
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                                crate::midi::MidiEventType::NoteOff { note, velocity: _ } => {
-                                                    // Send to built-in synth ONLY if no VST3 plugins
-                                                    if !has_vst3 {
-                                                        synth_manager.note_off(track_snap.id, note);
-                                                    }
-
-                                                    // Send to VST3 instruments in FX chain
-                                                    if has_vst3 {
-                                                        { let effect_mgr = effect_manager.lock();
-                                                            for effect_id in &track_snap.fx_chain {
-                                                                if let Some(effect_arc) = effect_mgr.get_effect(*effect_id) {
-                                                                    { let mut effect = effect_arc.lock();
-                                                                        #[cfg(all(feature = "vst3", not(target_os = "ios")))]
-                                                                        if let crate::effects::EffectType::VST3(ref mut vst3) = *effect {
-                                                                            let _ = vst3.process_midi_event(1, 0, i32::from(note), 0, 0);
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
+            // Get the tempo under the playhead for playback scaling
+            // Timeline positions are tempo-dependent: at 120 BPM, 1 timeline second = 1 real second
+            // At other tempos, the playhead must advance faster/slower through the timeline
+            // Tempo is constant within a segment; the map is read again once the playhead leaves it
+            let mut tempo_segment = recorder_refs.tempo_map.lock().segment_at_sample(current_playhead);
 
-                            // Add per-track synthesizer output (M6) - no lock needed, already held
-                            let (synth_left, synth_right) = synth_manager.process_sample_stereo(track_snap.id);
-                            track_left += synth_left;
-                            track_right += synth_right;
-                        }
+            // NOTE: Legacy MIDI clip processing removed - all MIDI now handled per-track
 
-                        // Input monitoring: mix live input for armed audio tracks
-                        {
-                            let should_monitor = track_snap.armed && track_snap.input_monitoring && track_snap.is_audio_track;
-                            update_monitoring_fade(&mut track_snap.monitoring_fade_gain, should_monitor);
+            // M5.5: Track-based mixing (replaces legacy clip mixing)
 
-                            if track_snap.monitoring_fade_gain > 0.0 {
-                                let ch = track_snap.input_channel as usize;
-                                let input_sample = if ch == 0 { input_left } else { input_right };
-                                track_left += input_sample * track_snap.monitoring_fade_gain as f32;
-                                track_right += input_sample * track_snap.monitoring_fade_gain as f32;
-                            }
-                        }
+            // Reuse pre-allocated buffers (clear without deallocating)
+            snapshot_buf.clear();
+            peak_buf.clear();
+
+            let (has_solo, master_snapshot) = { let tm = track_manager.lock();
+                let has_solo_flag = tm.has_solo();
+                let all_tracks = tm.get_all_tracks();
+                let mut master_snap = None;
 
-                        // Process FX chain BEFORE volume/pan (fader controls post-FX level)
-                        let (mut fx_left, mut fx_right) = { let effect_mgr = effect_manager.lock();
-                            process_effect_chain(&track_snap.fx_chain, &effect_mgr, track_left, track_right, false)
-                        };
+                for track_arc in all_tracks {
+                    { let track = track_arc.lock();
+                        let snap = TrackSnapshot::new(&track);
 
-                        // Apply track volume AFTER FX chain (from snapshot)
-                        // This ensures VST3 instrument output is also affected by the fader
-                        // Use automation curve if available, otherwise static volume_gain
-                        let frame_volume_gain = if track_snap.volume_automation.is_empty() {
-                            track_snap.volume_gain
+                        if track.track_type == crate::track::TrackType::Master {
+                            master_snap = Some(snap);
                         } else {
-                            // Interpolate volume from automation curve
-                            interpolate_automation_gain(&track_snap.volume_automation, playhead_seconds)
-                        };
-                        fx_left *= frame_volume_gain;
-                        fx_right *= frame_volume_gain;
-
-                        // Apply track pan AFTER FX chain (from snapshot)
-                        fx_left *= track_snap.pan_left;
-                        fx_right *= track_snap.pan_right;
-
-                        // Update track peak levels for metering
-                        let entry = peak_buf.entry(track_snap.id).or_insert((0.0, 0.0));
-                        entry.0 = entry.0.max(fx_left.abs());
-                        entry.1 = entry.1.max(fx_right.abs());
-
-                        // Accumulate to mix bus
-                        mix_left += fx_left;
-                        mix_right += fx_right;
+                            snapshot_buf.push(snap);
+                        }
                     }
+                }
 
-                    // Process recording (metronome handled separately below)
-                    let (met_left, met_right) = recorder_refs.process_frame(input_left, input_right, true, playhead_seconds);
-
-                    // Apply master track processing (using snapshot - no locks!)
-                    let mut master_left = mix_left;
-                    let mut master_right = mix_right;
-
-                    if let Some(ref master_snap) = master_snapshot {
-                        // Apply master volume
-                        master_left *= master_snap.volume_gain;
-                        master_right *= master_snap.volume_gain;
-
-                        // Apply master pan
-                        master_left *= master_snap.pan_left;
-                        master_right *= master_snap.pan_right;
-
-                        // Process master FX chain
-                        { let effect_mgr = effect_manager.lock();
-                            let (ml, mr) = process_effect_chain(&master_snap.fx_chain, &effect_mgr, master_left, master_right, false);
-                            master_left = ml;
-                            master_right = mr;
-                        }
+                (has_solo_flag, master_snap)
+            }; // All locks released here!
+
+            // OPTIMIZATION: Lock the synth manager, effect manager and master limiter
+            // ONCE before the frame loop (in that order, as everywhere else) instead of
+            // re-locking them every frame. This prevents lock contention that causes
+            // audio dropouts
+            let mut synth_manager = track_synth_manager.lock();
+            let effect_mgr = effect_manager.lock();
+            let mut limiter = master_limiter.lock();
+
+            // Sidechain sources must be mixed before the tracks that key from them
+            // Effect parameter automation is evaluated once per buffer at its start position
+            effect_mgr.apply_automation(tempo_segment.timeline_seconds_at_sample(current_playhead));
+            collect_sidechain_sources(
+                snapshot_buf.iter().map(|snap| snap.fx_chain.as_slice()),
+                &effect_mgr,
+                &mut sidechain_sources,
+            );
+            mix_sidechain_sources_first(&mut snapshot_buf, |snap| snap.id, &sidechain_sources);
+            resolve_bus_routing(&mut snapshot_buf);
+            resolve_latency_compensation(&mut snapshot_buf, &effect_mgr);
+            compensation_delays.release_missing(&snapshot_buf);
+            // The master FX chain runs on the whole buffer once the tracks are mixed,
+            // unless it keys from a sidechain (those levels change every frame)
+            let master_fx_per_block = master_snapshot
+                .as_ref()
+                .is_some_and(|master_snap| !effect_mgr.chain_has_sidechain(&master_snap.fx_chain));
+            master_block_left.clear();
+            master_block_left.resize(frames, 0.0);
+            master_block_right.clear();
+            master_block_right.resize(frames, 0.0);
+            let mut master_peak_left = 0.0f32;
+            let mut master_peak_right = 0.0f32;
+            // Reference A/B: skip the reference for a buffer rather than wait on the API
+            let reference = reference_track.try_lock().filter(|reference| reference.is_active());
+
+            // Check if recording is active (skip clip playback on armed tracks)
+            let is_recording = *recorder_refs.state.lock() == crate::recorder::RecordingState::Recording;
+
+            // Read loop region once per buffer (ignored if empty or disabled)
+            let loop_region = if loop_enabled.load(Ordering::SeqCst) {
+                let loop_start = *loop_start_seconds.lock();
+                let loop_end = *loop_end_seconds.lock();
+                (loop_end > loop_start).then_some((loop_start, loop_end))
+            } else {
+                None
+            };
+            // Playhead for frame N is `segment_start + (N - segment_frame)`; a loop wrap starts a new segment
+            let mut segment_start = current_playhead;
+            let mut segment_frame = 0;
+            let mut looped = false;
+
+            // Playhead, recording and the sources outside the mix, frame by frame;
+            // the tracks are mixed in below
+            frame_buf.clear();
+            for frame_idx in 0..frames {
+                let mut playhead_frame = segment_start + (frame_idx - segment_frame) as u64;
+                // Apply the tempo: at 120 BPM, playhead advances 1:1 with real time
+                // At 100 BPM, playhead advances slower (0.833x) through timeline
+                // At 140 BPM, playhead advances faster (1.167x) through timeline
+                if !tempo_segment.contains(playhead_frame) {
+                    tempo_segment = recorder_refs.tempo_map.lock().segment_at_sample(playhead_frame);
+                }
+                let mut playhead_seconds = tempo_segment.timeline_seconds_at_sample(playhead_frame);
+
+                // Wrap back to loop start, re-syncing the metronome like seek()
+                // (each track silences its instrument when it reaches this frame)
+                let mut release_notes = false;
+                if let Some(loop_start_frame) = loop_wrap_target(playhead_seconds, &recorder_refs.tempo_map, loop_region) {
+                    release_notes = true;
+                    recorder_refs.seek_metronome(loop_start_frame);
+                    // Loop recording: audio so far becomes a take, the next pass starts fresh
+                    recorder_refs.mark_loop_take(loop_region.map_or(0.0, |(start, _)| start));
+                    segment_start = loop_start_frame;
+                    segment_frame = frame_idx;
+                    looped = true;
+                    playhead_frame = loop_start_frame;
+                    if !tempo_segment.contains(playhead_frame) {
+                        tempo_segment = recorder_refs.tempo_map.lock().segment_at_sample(playhead_frame);
                     }
+                    playhead_seconds = tempo_segment.timeline_seconds_at_sample(playhead_frame);
+                }
 
-                    // Apply master limiter to prevent clipping
-                    let (limited_left, limited_right) = { let mut limiter = master_limiter.lock();
-                        limiter.process_frame(master_left, master_right)
-                    };
+                // Read input samples (needed for both recording and input monitoring)
+                let input = read_input_frame(&input_manager);
+
+                // Process recording; the metronome goes directly to output, bypassing
+                // master volume/effects
+                let (mut output_left, mut output_right) = recorder_refs.process_frame(input.as_slice(), true, playhead_seconds);
+
+                // Process latency test (if running)
+                latency_test.process_input(input.channel(0), playhead_frame);
+                let test_tone = latency_test.generate_output(playhead_frame);
+                output_left += test_tone;
+                output_right += test_tone;
+
+                // Mix library preview audio (independent of transport)
+                let (preview_left, preview_right) = crate::api::preview::preview_process_sample();
+                output_left += preview_left;
+                output_right += preview_right;
+
+                // The reference replaces the master bus (added below) while A/B is on
+                if let Some(ref reference) = reference {
+                    let (reference_left, reference_right) = reference.frame(playhead_frame);
+                    output_left += reference_left;
+                    output_right += reference_right;
+                }
 
-                    // Update master peak levels for metering (before metronome is added)
-                    master_peak_left = master_peak_left.max(limited_left.abs());
-                    master_peak_right = master_peak_right.max(limited_right.abs());
+                // Write to output buffer (interleaved stereo); the master bus is added below
+                data[frame_idx * 2] = output_left;
+                data[frame_idx * 2 + 1] = output_right;
+
+                frame_buf.push(TrackFrame {
+                    playhead_frame,
+                    playhead_seconds,
+                    play_timeline: true,
+                    recording: is_recording,
+                    input,
+                    monitor_input,
+                    refresh_automation: true,
+                    apply_fader: true,
+                    preview: false,
+                    release_notes,
+                });
+            }
 
-                    // Add metronome AFTER metering so it doesn't affect the master meter
-                    // Metronome goes directly to output, bypassing master volume/effects
-                    let mut output_left = limited_left + met_left;
-                    let mut output_right = limited_right + met_right;
+            // Mix the tracks a block at a time (using snapshots - NO LOCKS in hot path!)
+            mix_blocks.reset(frames);
+            for track_snap in &mut snapshot_buf {
+                // Muted tracks produce no sound; if any track is soloed, skip non-soloed tracks
+                if track_snap.is_silenced(has_solo) {
+                    if looped {
+                        synth_manager.all_notes_off(track_snap.id);
+                    }
+                    continue;
+                }
 
-                    // Process latency test (if running)
-                    latency_test.process_input(input_left, playhead_frame);
-                    let test_tone = latency_test.generate_output(playhead_frame);
-                    output_left += test_tone;
-                    output_right += test_tone;
+                let publish_sidechain = sidechain_sources.contains(&track_snap.id);
+                let meter = process_track_block(track_snap, &mut synth_manager, &effect_mgr, &frame_buf, publish_sidechain, &mut compensation_delays, &mut mix_blocks);
+                peak_buf.insert(track_snap.id, meter);
+            }
 
-                    // Mix library preview audio (independent of transport)
-                    let (preview_left, preview_right) = crate::api::preview::preview_process_sample();
-                    output_left += preview_left;
-                    output_right += preview_right;
+            // Apply master track processing (using snapshot - no locks!)
+            for frame_idx in 0..frames {
+                let (mut master_left, mut master_right) = mix_blocks.master(frame_idx);
+                if let Some(ref master_snap) = master_snapshot {
+                    // Apply master volume and pan
+                    (master_left, master_right) = master_snap.apply_master_fader(master_left, master_right);
+
+                    // Sidechained master FX chain: process it frame by frame
+                    if !master_fx_per_block {
+                        let sidechain_levels = mix_blocks.sidechain_levels_at(frame_idx);
+                        (master_left, master_right) = process_effect_chain(&master_snap.fx_chain, &effect_mgr, master_left, master_right, false, false, sidechain_levels);
+                    }
+                }
+                master_block_left[frame_idx] = master_left;
+                master_block_right[frame_idx] = master_right;
+            }
 
-                    // Write to output buffer (interleaved stereo)
-                    data[frame_idx * 2] = output_left;
-                    data[frame_idx * 2 + 1] = output_right;
+            // Process the master FX chain on the whole buffer
+            if master_fx_per_block {
+                if let Some(ref master_snap) = master_snapshot {
+                    process_effect_chain_block(&master_snap.fx_chain, &effect_mgr, &mut master_block_left, &mut master_block_right, false);
                 }
+            }
 
-                // Update track peak levels and monitoring fade gains (brief lock after buffer processing)
-                { let tm = track_manager.lock();
-                    for track_snap in &snapshot_buf {
-                        if let Some(track_arc) = tm.get_track(track_snap.id) {
-                            { let mut track = track_arc.lock();
-                                track.monitoring_fade_gain = track_snap.monitoring_fade_gain;
-                            }
-                        }
-                    }
-                    for (track_id, (peak_l, peak_r)) in &peak_buf {
-                        if let Some(track_arc) = tm.get_track(*track_id) {
-                            { let mut track = track_arc.lock();
-                                track.update_peaks(*peak_l, *peak_r);
-                            }
-                        }
-                    }
-                    // Update master track peaks
-                    {
-                        let master_arc = tm.get_master_track();
-                        { let mut master = master_arc.lock();
-                            master.update_peaks(master_peak_left, master_peak_right);
-                        };
-                    }
+            for (frame_idx, (&master_left, &master_right)) in master_block_left.iter().zip(&master_block_right).enumerate() {
+                // Apply master limiter to prevent clipping
+                let (limited_left, limited_right) = limiter.process_frame(master_left, master_right);
+
+                // Update master peak levels for metering (the metronome is not metered)
+                master_peak_left = master_peak_left.max(limited_left.abs());
+                master_peak_right = master_peak_right.max(limited_right.abs());
+                loudness_meter.process_frame(limited_left, limited_right, &master_loudness);
+                spectrum_analyzer.process_frame(limited_left, limited_right, &master_spectrum);
+                true_peak_meter.process_frame(limited_left, limited_right, &master_true_peak);
+
+                // The mix keeps running (and metering) under the reference
+                if reference.is_none() {
+                    data[frame_idx * 2] += limited_left;
+                    data[frame_idx * 2 + 1] += limited_right;
                 }
+            }
+            drop(reference);
+
+            // Update track peak levels and monitoring fade gains (brief lock after buffer processing)
+            drop(limiter);
+            drop(effect_mgr);
+            drop(synth_manager);
+            store_track_meters(&track_manager.lock(), &snapshot_buf, &peak_buf, (master_peak_left, master_peak_right));
 
-                // Advance playhead
+            // Advance playhead
+            if looped {
+                playhead_samples.store(segment_start + (frames - segment_frame) as u64, Ordering::SeqCst);
+            } else {
                 playhead_samples.fetch_add(frames as u64, Ordering::SeqCst);
+            }
+        };
+
+        // Devices that can't run at the engine rate get the mix resampled to their rate
+        let mut resampler = if config.sample_rate.0 == TARGET_SAMPLE_RATE {
+            None
+        } else {
+            eprintln!("🔊 [AudioGraph] Resampling output {TARGET_SAMPLE_RATE}Hz → {}Hz", config.sample_rate.0);
+            Some(OutputResampler::new(config.sample_rate.0)?)
+        };
+
+        let stream = device.build_output_stream(
+            &config,
+            move |data: &mut [f32], _: &cpal::OutputCallbackInfo| match resampler.as_mut() {
+                Some(resampler) => resampler.process(data, &mut render),
+                None => render(data),
             },
             move |err| {
                 eprintln!("Audio stream error: {err}");
+                // Unplugged device: the stream is dead until the device watcher rebuilds it
+                if matches!(err, cpal::StreamError::DeviceNotAvailable) {
+                    output_device_lost.store(true, Ordering::SeqCst);
+                    output_device_changed.store(true, Ordering::SeqCst);
+                }
             },
             None,
         )?;
@@ -773,3 +863,349 @@ impl AudioGraph {
         Ok(stream)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn stereo_clip(left: f32, right: f32) -> TimelineClip {
+        let frames = 100;
+        let clip = AudioClip {
+            samples: [left, right].repeat(frames),
+            channels: 2,
+            sample_rate: TARGET_SAMPLE_RATE,
+            duration_seconds: frames as f64 / f64::from(TARGET_SAMPLE_RATE),
+            file_path: "test.wav".to_string(),
+            stream: None,
+        };
+        TimelineClip::new(0, Arc::new(clip), 0.0)
+    }
+
+    #[test]
+    fn test_output_resampler_keeps_pitch_at_device_rate() {
+        // A 1 kHz tone rendered at the engine rate, played on a 44.1 kHz device
+        let mut resampler = OutputResampler::new(44_100).unwrap();
+        let mut rendered_frames = 0usize;
+        let mut render = |data: &mut [f32]| {
+            for frame in data.chunks_exact_mut(2) {
+                let t = rendered_frames as f32 / TARGET_SAMPLE_RATE as f32;
+                let sample = (t * 1000.0 * std::f32::consts::TAU).sin() * 0.5;
+                frame.copy_from_slice(&[sample, sample]);
+                rendered_frames += 1;
+            }
+        };
+
+        // Uneven device buffers, about a second in total
+        let mut output = Vec::new();
+        for frames in [441, 1024, 300].into_iter().cycle().take(60) {
+            let mut data = vec![0.0; frames * 2];
+            resampler.process(&mut data, &mut render);
+            output.extend_from_slice(&data);
+        }
+        // The engine ran at its own rate, about a device buffer ahead
+        let device_frames = output.len() / 2;
+        assert!(rendered_frames.abs_diff(device_frames * 48_000 / 44_100) < 1024, "{rendered_frames} engine frames");
+
+        // Skip the resampler's delay, then count cycles over the rest at the device rate
+        let left: Vec<f32> = output.iter().step_by(2).skip(4410).copied().collect();
+        let crossings = left.windows(2).filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0).count();
+        let expected = 1000.0 * left.len() as f32 / 44_100.0;
+        assert!((crossings as f32 - expected).abs() <= 2.0, "{crossings} cycles, expected {expected}");
+        let rms = (left.iter().map(|s| s * s).sum::<f32>() / left.len() as f32).sqrt();
+        assert!((rms - 0.5 / 2.0_f32.sqrt()).abs() < 0.01, "rms {rms}");
+    }
+
+    #[test]
+    fn test_clip_polarity_invert_negates_samples() {
+        let mut clip = stereo_clip(0.4, -0.2);
+        assert_eq!(render_audio_clip_sample(&clip, 0.0), (0.4, -0.2));
+
+        clip.polarity_inverted = true;
+        assert_eq!(render_audio_clip_sample(&clip, 0.0), (-0.4, 0.2));
+    }
+
+    #[test]
+    fn test_clip_mono_sum_averages_channels() {
+        let mut clip = stereo_clip(0.4, 0.2);
+        clip.mono_sum = true;
+        let (left, right) = render_audio_clip_sample(&clip, 0.0);
+        assert!((left - 0.3).abs() < 1e-6);
+        assert!((right - 0.3).abs() < 1e-6);
+
+        clip.polarity_inverted = true;
+        let (left, right) = render_audio_clip_sample(&clip, 0.0);
+        assert!((left + 0.3).abs() < 1e-6);
+        assert!((right + 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_clip_fades_ramp_gain_at_edges() {
+        let mut clip = stereo_clip(1.0, 1.0);
+        let length = clip.clip.duration_seconds;
+        clip.fade_in_seconds = length / 4.0;
+        clip.fade_out_seconds = length / 4.0;
+
+        // Silent at the very start, unity in the middle, quieter towards the end
+        assert_eq!(render_audio_clip_sample(&clip, 0.0), (0.0, 0.0));
+        assert_eq!(render_audio_clip_sample(&clip, length / 2.0), (1.0, 1.0));
+        let (near_end, _) = render_audio_clip_sample(&clip, length * 0.95);
+        assert!(near_end > 0.0 && near_end < 0.5);
+    }
+
+    #[test]
+    fn test_reversed_clip_plays_its_region_backwards() {
+        // Each frame holds its own index, so the output shows which frame was read
+        let frames = TARGET_SAMPLE_RATE as usize;
+        let clip = AudioClip {
+            samples: (0..frames).map(|n| n as f32).collect(),
+            channels: 1,
+            sample_rate: TARGET_SAMPLE_RATE,
+            duration_seconds: 1.0,
+            file_path: "index.wav".to_string(),
+            stream: None,
+        };
+        let mut clip = TimelineClip::new(0, Arc::new(clip), 0.0);
+        clip.reversed = true;
+        let frame_at = |clip: &TimelineClip, seconds: f64| render_audio_clip_sample(clip, seconds).0;
+
+        // Last sample first
+        assert!((frame_at(&clip, 0.0) - (frames - 1) as f32).abs() < 0.5);
+        assert!(frame_at(&clip, 0.5) < frame_at(&clip, 0.25));
+
+        // Trimming keeps the same region, played from its end
+        clip.offset = 0.25;
+        clip.duration = Some(0.5);
+        let region_end = frames * 3 / 4;
+        assert!((frame_at(&clip, 0.0) - (region_end - 1) as f32).abs() < 0.5);
+        assert!((frame_at(&clip, 0.25) - (region_end - 1 - frames / 4) as f32).abs() < 0.5);
+        assert_eq!(render_audio_clip_sample(&clip, 0.5), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_split_clip_renders_like_the_original() {
+        let frames = TARGET_SAMPLE_RATE as usize;
+        let clip = AudioClip {
+            samples: (0..frames).map(|n| n as f32).collect(),
+            channels: 1,
+            sample_rate: TARGET_SAMPLE_RATE,
+            duration_seconds: 1.0,
+            file_path: "index.wav".to_string(),
+            stream: None,
+        };
+        let mut original = TimelineClip::new(0, Arc::new(clip), 1.0);
+        original.offset = 0.125;
+        original.duration = Some(0.75);
+        original.warp_enabled = true;
+        original.warp_mode = 1;
+        original.stretch_factor = 2.0;
+
+        for reversed in [false, true] {
+            original.reversed = reversed;
+            let (first, second) = original.split_at(1.25, 1, 2).unwrap();
+            assert!((second.start_time - 1.25).abs() < 1e-9);
+            for step in 0..40 {
+                let time = 1.0 + f64::from(step) * 0.01 + 0.001;
+                let expected = render_audio_clip_sample(&original, time).0;
+                let (a, _) = render_audio_clip_sample(&first, time);
+                let (b, _) = render_audio_clip_sample(&second, time);
+                assert!((a + b - expected).abs() <= 1.0, "reversed={reversed} t={time}: {a}+{b} vs {expected}");
+            }
+        }
+
+        assert!(original.split_at(1.0, 1, 2).is_none());
+        assert!(original.split_at(1.375, 1, 2).is_none());
+    }
+
+    /// Seconds of non-silent output from a clip rendered at the engine rate
+    fn audible_seconds(clip: &TimelineClip) -> f64 {
+        let rate = f64::from(TARGET_SAMPLE_RATE);
+        let audible = (0..(rate * 2.0) as usize)
+            .filter(|&n| render_audio_clip_sample(clip, n as f64 / rate).0.abs() > 1e-4)
+            .count();
+        audible as f64 / rate
+    }
+
+    fn sine_clip(seconds: f64) -> TimelineClip {
+        let frames = (seconds * f64::from(TARGET_SAMPLE_RATE)) as usize;
+        let samples = (0..frames)
+            .map(|n| (n as f32 * 0.05).sin() * 0.5 + 0.6)
+            .collect();
+        let clip = AudioClip {
+            samples,
+            channels: 1,
+            sample_rate: TARGET_SAMPLE_RATE,
+            duration_seconds: seconds,
+            file_path: "sine.wav".to_string(),
+            stream: None,
+        };
+        TimelineClip::new(0, Arc::new(clip), 0.0)
+    }
+
+    #[test]
+    fn test_repitch_clip_plays_for_stretched_duration() {
+        let mut clip = sine_clip(0.5);
+        clip.warp_enabled = true;
+        clip.warp_mode = 1;
+        clip.stretch_factor = 2.0;
+        assert!((audible_seconds(&clip) - 0.25).abs() < 0.005);
+
+        clip.stretch_factor = 0.5;
+        assert!((audible_seconds(&clip) - 1.0).abs() < 0.005);
+    }
+
+    #[test]
+    fn test_warp_clip_plays_for_stretched_duration() {
+        let mut clip = sine_clip(0.5);
+        clip.warp_enabled = true;
+        clip.warp_mode = 0;
+        clip.stretch_factor = 0.5;
+        clip.rebuild_stretched_cache();
+        assert!(clip.stretched_cache.is_some());
+        assert!((audible_seconds(&clip) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_gain_envelope_ramps_clip_from_silence() {
+        let frames = TARGET_SAMPLE_RATE as usize;
+        let clip = AudioClip {
+            samples: vec![0.5; frames],
+            channels: 1,
+            sample_rate: TARGET_SAMPLE_RATE,
+            duration_seconds: 1.0,
+            file_path: "dc.wav".to_string(),
+            stream: None,
+        };
+        let mut clip = TimelineClip::new(0, Arc::new(clip), 2.0);
+        clip.set_gain_envelope_csv("0,-96;1,0");
+        assert_eq!(clip.gain_envelope.len(), 2);
+
+        assert_eq!(render_audio_clip_sample(&clip, 2.0), (0.0, 0.0));
+        // Interpolated in dB: halfway is -48 dB, three quarters is -24 dB
+        assert!((render_audio_clip_sample(&clip, 2.75).0 - 0.5 * 10f32.powf(-24.0 / 20.0)).abs() < 1e-3);
+        assert!((render_audio_clip_sample(&clip, 2.9999).0 - 0.5).abs() < 1e-3);
+        let ramp: Vec<f32> = (0..100).map(|step| render_audio_clip_sample(&clip, 2.0 + f64::from(step) * 0.01).0).collect();
+        assert!(ramp.windows(2).all(|pair| pair[1] >= pair[0]));
+
+        // Splitting keeps the ramp where it was on the timeline
+        let (_, second) = clip.split_at(2.5, 1, 2).unwrap();
+        for time in [2.5, 2.7, 2.95] {
+            let expected = render_audio_clip_sample(&clip, time).0;
+            assert!((render_audio_clip_sample(&second, time).0 - expected).abs() < 1e-5);
+        }
+
+        clip.set_gain_envelope_csv("");
+        assert_eq!(render_audio_clip_sample(&clip, 2.0), (0.5, 0.5));
+    }
+
+    /// Frequency (Hz) of the strongest FFT bin in a mono signal at the engine rate
+    fn spectral_peak_hz(samples: &[f32]) -> f64 {
+        use rustfft::num_complex::Complex;
+        let mut buffer: Vec<Complex<f32>> = samples.iter().map(|&s| Complex::new(s, 0.0)).collect();
+        rustfft::FftPlanner::new().plan_fft_forward(buffer.len()).process(&mut buffer);
+        let peak_bin = (1..buffer.len() / 2)
+            .max_by(|&a, &b| buffer[a].norm().total_cmp(&buffer[b].norm()))
+            .unwrap_or(0);
+        peak_bin as f64 * f64::from(TARGET_SAMPLE_RATE) / buffer.len() as f64
+    }
+
+    #[test]
+    fn test_transposed_clip_is_an_octave_up_at_the_same_length() {
+        let rate = f64::from(TARGET_SAMPLE_RATE);
+        let frames = TARGET_SAMPLE_RATE as usize;
+        let clip = AudioClip {
+            samples: (0..frames)
+                .map(|n| (n as f64 * 440.0 * std::f64::consts::TAU / rate).sin() as f32 * 0.5)
+                .collect(),
+            channels: 1,
+            sample_rate: TARGET_SAMPLE_RATE,
+            duration_seconds: 1.0,
+            file_path: "a440.wav".to_string(),
+            stream: None,
+        };
+        let mut clip = TimelineClip::new(0, Arc::new(clip), 0.0);
+        clip.transpose_semitones = 12;
+        clip.rebuild_stretched_cache();
+        assert!(clip.stretched_cache.is_some());
+
+        // 8192 frames from the middle of the clip (~5.9 Hz per bin)
+        let window: Vec<f32> = (0..8192)
+            .map(|n| render_audio_clip_sample(&clip, 0.25 + f64::from(n) / rate).0)
+            .collect();
+        let peak_hz = spectral_peak_hz(&window);
+        assert!((peak_hz - 880.0).abs() < 12.0, "spectral peak at {peak_hz} Hz");
+        assert!((audible_seconds(&clip) - 1.0).abs() < 0.01);
+
+        // Back to no transpose: the cache is dropped and the source plays untouched
+        clip.transpose_semitones = 0;
+        clip.rebuild_stretched_cache();
+        assert!(clip.stretched_cache.is_none());
+    }
+
+    #[test]
+    fn test_stretched_clip_offset_is_in_source_time() {
+        // Source ramps 0..1 over one second; trimming 0.5s must start mid-ramp
+        let frames = TARGET_SAMPLE_RATE as usize;
+        let clip = AudioClip {
+            samples: (0..frames).map(|n| n as f32 / frames as f32).collect(),
+            channels: 1,
+            sample_rate: TARGET_SAMPLE_RATE,
+            duration_seconds: 1.0,
+            file_path: "ramp.wav".to_string(),
+            stream: None,
+        };
+        let mut clip = TimelineClip::new(0, Arc::new(clip), 0.0);
+        clip.offset = 0.5;
+        clip.warp_enabled = true;
+        clip.warp_mode = 1;
+        clip.stretch_factor = 2.0;
+        let (left, _) = render_audio_clip_sample(&clip, 0.0);
+        assert!((left - 0.5).abs() < 1e-3);
+        let (left, _) = render_audio_clip_sample(&clip, 0.1);
+        assert!((left - 0.7).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_overlapping_clip_crossfade_is_equal_power() {
+        // Clip A fades out over the same span clip B fades in
+        let mut clip_a = stereo_clip(1.0, 1.0);
+        let length = clip_a.clip.duration_seconds;
+        let overlap = length / 2.0;
+        clip_a.fade_out_seconds = overlap;
+        let mut clip_b = stereo_clip(1.0, 1.0);
+        clip_b.start_time = length - overlap;
+        clip_b.fade_in_seconds = overlap;
+
+        let mut t = clip_b.start_time;
+        while t < length {
+            let (a, _) = render_audio_clip_sample(&clip_a, t);
+            let (b, _) = render_audio_clip_sample(&clip_b, t);
+            assert!((a * a + b * b - 1.0).abs() < 1e-3, "power not constant at {t}");
+            t += 1.0 / f64::from(TARGET_SAMPLE_RATE);
+        }
+    }
+
+    #[test]
+    fn test_sidechain_sources_are_mixed_first() {
+        let mut track_ids = vec![1, 2, 3, 4, 5];
+        mix_sidechain_sources_first(&mut track_ids, |id| *id, &[4, 2]);
+        assert_eq!(track_ids, vec![2, 4, 1, 3, 5]);
+    }
+
+    #[test]
+    fn test_loop_wrap_target() {
+        let region = Some((2.0, 4.0));
+        let sr = f64::from(TARGET_SAMPLE_RATE);
+
+        let tempo_map = |bpm: f64| parking_lot::Mutex::new(TempoMap::new(bpm));
+
+        // Before the loop end: no wrap
+        assert_eq!(loop_wrap_target(3.99, &tempo_map(120.0), region), None);
+        // At/after the loop end: jump to loop start
+        assert_eq!(loop_wrap_target(4.0, &tempo_map(120.0), region), Some((2.0 * sr) as u64));
+        // Loop start is converted back to real samples using the tempo
+        assert_eq!(loop_wrap_target(4.5, &tempo_map(240.0), region), Some(sr as u64));
+        // Loop disabled
+        assert_eq!(loop_wrap_target(10.0, &tempo_map(120.0), None), None);
+    }
+}
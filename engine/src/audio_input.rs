@@ -1,12 +1,21 @@
 /// Audio input and recording functionality
 use cpal::traits::{DeviceTrait, HostTrait};
 use ringbuf::{traits::{Observer, Consumer, Producer}, HeapRb};
+use rubato::{FftFixedInOut, Resampler};
 use std::sync::Arc;
 use parking_lot::Mutex;
 use std::sync::atomic::{AtomicU32, Ordering};
 use anyhow::Result;
 
 use crate::audio_file::TARGET_SAMPLE_RATE;
+use crate::audio_graph::{InputFrame, MAX_INPUT_CHANNELS};
+
+/// Name prefix of ASIO input devices (matches the output device list)
+#[cfg(all(windows, feature = "asio"))]
+const ASIO_PREFIX: &str = "[ASIO] ";
+
+/// Input chunk size hint (in frames) for converting device-rate input
+const INPUT_RESAMPLE_CHUNK_FRAMES: usize = 256;
 
 /// Represents an audio input device
 #[derive(Clone, Debug)]
@@ -22,17 +31,23 @@ pub struct AudioInputManager {
     devices: Vec<AudioInputDevice>,
     /// Currently selected device index
     selected_device_index: Option<usize>,
+    /// Name of the device chosen by the user (None = system default input)
+    /// Kept by name so the choice survives devices being added or removed.
+    selected_device_name: Option<String>,
+    /// Ring buffer length of the running capture, reused when it is restarted
+    buffer_size_seconds: f64,
     /// Input stream (if active)
     input_stream: Option<cpal::Stream>,
     /// Ring buffer for captured audio (lock-free, thread-safe)
-    /// Stores raw samples (mono or stereo depending on device)
+    /// Stores whole device frames, interleaved across all `input_channels`
     input_buffer: Option<Arc<Mutex<HeapRb<f32>>>>,
-    /// Number of input channels (1 = mono, 2 = stereo)
+    /// Number of device input channels (1 = mono, 2 = stereo, more for interfaces)
     input_channels: u16,
-    /// Peak levels per channel (stored as f32 bits in `AtomicU32` for lock-free access)
+    /// Rate the device captures at (input is resampled to `TARGET_SAMPLE_RATE` when it differs)
+    input_sample_rate: u32,
+    /// Peak level per device channel (stored as f32 bits in `AtomicU32` for lock-free access)
     /// Updated in the input callback, read by the UI for metering
-    input_peak_left: Arc<AtomicU32>,
-    input_peak_right: Arc<AtomicU32>,
+    input_peaks: Arc<[AtomicU32]>,
 }
 
 impl AudioInputManager {
@@ -41,11 +56,13 @@ impl AudioInputManager {
         Ok(Self {
             devices: Vec::new(),
             selected_device_index: None,
+            selected_device_name: None,
+            buffer_size_seconds: 0.0,
             input_stream: None,
             input_buffer: None,
             input_channels: 1, // Default to mono
-            input_peak_left: Arc::new(AtomicU32::new(0)),
-            input_peak_right: Arc::new(AtomicU32::new(0)),
+            input_sample_rate: TARGET_SAMPLE_RATE,
+            input_peaks: (0..MAX_INPUT_CHANNELS).map(|_| AtomicU32::new(0)).collect(),
         })
     }
 
@@ -82,10 +99,30 @@ impl AudioInputManager {
             });
         }
 
+        // ASIO inputs (when feature enabled, Windows only), listed with an [ASIO] prefix
+        #[cfg(all(windows, feature = "asio"))]
+        if let Ok(asio_host) = cpal::host_from_id(cpal::HostId::Asio) {
+            if let Ok(asio_devices) = asio_host.input_devices() {
+                for device in asio_devices {
+                    if let Ok(name) = device.name() {
+                        devices.push(AudioInputDevice {
+                            id: format!("input_{}", devices.len()),
+                            name: format!("{ASIO_PREFIX}{name}"),
+                            is_default: false,
+                        });
+                    }
+                }
+            }
+        }
+
         self.devices.clone_from(&devices);
 
-        // Auto-select default device
-        if let Some(default_idx) = devices.iter().position(|d| d.is_default) {
+        // Keep the user's device if it is still connected, otherwise use the default
+        let selected = self.selected_device_name.as_ref()
+            .and_then(|name| devices.iter().position(|d| &d.name == name));
+        if let Some(selected_idx) = selected {
+            self.selected_device_index = Some(selected_idx);
+        } else if let Some(default_idx) = devices.iter().position(|d| d.is_default) {
             self.selected_device_index = Some(default_idx);
         } else if !devices.is_empty() {
             self.selected_device_index = Some(0);
@@ -101,11 +138,34 @@ impl AudioInputManager {
 
     /// Select an input device by index
     pub fn select_device(&mut self, device_index: usize) -> Result<()> {
-        if device_index >= self.devices.len() {
-            return Err(anyhow::anyhow!("Invalid device index"));
-        }
+        let device = self.devices.get(device_index)
+            .ok_or_else(|| anyhow::anyhow!("Invalid device index"))?;
+        self.selected_device_name = Some(device.name.clone());
         self.selected_device_index = Some(device_index);
-        Ok(())
+        self.restart_capture()
+    }
+
+    /// Select an input device by name (None = system default input)
+    /// The input runs as its own stream, so it can be a different device, or a
+    /// different host, from the output. A running capture moves to the new device.
+    pub fn select_device_by_name(&mut self, name: Option<&str>) -> Result<()> {
+        let devices = self.enumerate_devices()?;
+        if let Some(name) = name {
+            let index = devices.iter().position(|d| d.name == name)
+                .ok_or_else(|| anyhow::anyhow!("Input device '{name}' not found"))?;
+            self.selected_device_name = Some(name.to_string());
+            self.selected_device_index = Some(index);
+        } else {
+            self.selected_device_name = None;
+            self.selected_device_index = devices.iter().position(|d| d.is_default)
+                .or((!devices.is_empty()).then_some(0));
+        }
+        self.restart_capture()
+    }
+
+    /// Get the name of the device chosen by the user (None = system default input)
+    pub fn get_selected_device_name(&self) -> Option<String> {
+        self.selected_device_name.clone()
     }
 
     /// Get the currently selected device index
@@ -118,77 +178,36 @@ impl AudioInputManager {
     pub fn start_capture(&mut self, buffer_size_seconds: f64) -> Result<()> {
         use cpal::traits::StreamTrait;
 
-        let device_index = self.selected_device_index
-            .ok_or_else(|| anyhow::anyhow!("No input device selected"))?;
-
-        let host = cpal::default_host();
-        let mut input_devices: Vec<_> = host.input_devices()?.collect();
-
-        if device_index >= input_devices.len() {
-            return Err(anyhow::anyhow!("Device index out of range"));
-        }
-
-        let device = input_devices.remove(device_index);
-        let config = device.default_input_config()?;
+        let device = find_input_device(self.selected_device_name.as_deref())?;
+        let config = negotiate_input_config(&device)?;
+        self.buffer_size_seconds = buffer_size_seconds;
 
         println!("Starting audio capture:");
         println!("  Device: {}", device.name()?);
         println!("  Config: {config:?}");
 
-        // Store the number of input channels
-        self.input_channels = config.channels();
-        eprintln!("🎙️  [AudioInput] Input channels: {} (1=mono, 2=stereo)", self.input_channels);
+        // Capture every device channel; tracks pick theirs when reading frames
+        let num_channels = config.channels();
+        eprintln!("🎙️  [AudioInput] Input channels: {num_channels}");
 
-        // Create ring buffer (stereo, size based on buffer_size_seconds)
-        let buffer_samples = (buffer_size_seconds * f64::from(TARGET_SAMPLE_RATE) * 2.0) as usize;
-        let ring_buffer: HeapRb<f32> = HeapRb::new(buffer_samples);
-        let ring_buffer_arc = Arc::new(Mutex::new(ring_buffer));
-        let ring_buffer_clone = ring_buffer_arc.clone();
+        let ring_buffer_clone = self.open_buffer(num_channels, buffer_size_seconds);
+        let peaks = self.input_peaks.clone();
+        let num_channels = usize::from(num_channels);
 
-        // Clone peak tracking atomics for use in the callback
-        let peak_left = self.input_peak_left.clone();
-        let peak_right = self.input_peak_right.clone();
-        let num_channels = self.input_channels;
+        // Convert to the engine rate when the device can't capture at it
+        self.input_sample_rate = config.sample_rate().0;
+        let mut resampler = if self.input_sample_rate == TARGET_SAMPLE_RATE {
+            None
+        } else {
+            eprintln!("🎙️  [AudioInput] Resampling input {}Hz → {TARGET_SAMPLE_RATE}Hz", self.input_sample_rate);
+            Some(InputResampler::new(self.input_sample_rate, num_channels)?)
+        };
 
         // Create input stream
         let stream = device.build_input_stream(
             &config.into(),
             move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                // Track peak levels per channel
-                let mut max_left: f32 = 0.0;
-                let mut max_right: f32 = 0.0;
-
-                if num_channels == 1 {
-                    // Mono: all samples are the same channel
-                    for &sample in data {
-                        let abs = sample.abs();
-                        if abs > max_left { max_left = abs; }
-                    }
-                    max_right = max_left;
-                } else {
-                    // Stereo (or more): interleaved L R L R ...
-                    for (i, &sample) in data.iter().enumerate() {
-                        let abs = sample.abs();
-                        if i % 2 == 0 {
-                            if abs > max_left { max_left = abs; }
-                        } else if abs > max_right { max_right = abs; }
-                    }
-                }
-
-                // Update peak atomics (store f32 bits as u32)
-                peak_left.store(max_left.to_bits(), Ordering::Relaxed);
-                peak_right.store(max_right.to_bits(), Ordering::Relaxed);
-
-                // Write input samples to ring buffer
-                { let mut buffer = ring_buffer_clone.lock();
-                    for &sample in data {
-                        // If buffer is full, drop oldest samples
-                        if buffer.is_full() {
-                            let _ = buffer.try_pop();
-                        }
-                        let _ = buffer.try_push(sample);
-                    }
-                }
+                capture_block(data, num_channels, &peaks, &mut ring_buffer_clone.lock(), resampler.as_mut());
             },
             move |err| {
                 eprintln!("Audio input stream error: {err}");
@@ -199,11 +218,28 @@ impl AudioInputManager {
         stream.play()?;
 
         self.input_stream = Some(stream);
-        self.input_buffer = Some(ring_buffer_arc);
 
         Ok(())
     }
 
+    /// Create the capture ring buffer for a device with `channels` channels
+    fn open_buffer(&mut self, channels: u16, buffer_size_seconds: f64) -> Arc<Mutex<HeapRb<f32>>> {
+        self.input_channels = channels;
+        let buffer_samples = (buffer_size_seconds * f64::from(TARGET_SAMPLE_RATE) * f64::from(channels.max(1))) as usize;
+        let ring_buffer = Arc::new(Mutex::new(HeapRb::new(buffer_samples.max(1))));
+        self.input_buffer = Some(ring_buffer.clone());
+        ring_buffer
+    }
+
+    /// Restart a running capture (on the currently selected device)
+    fn restart_capture(&mut self) -> Result<()> {
+        if !self.is_capturing() {
+            return Ok(());
+        }
+        self.stop_capture()?;
+        self.start_capture(self.buffer_size_seconds)
+    }
+
     /// Stop capturing audio
     pub fn stop_capture(&mut self) -> Result<()> {
         if let Some(stream) = self.input_stream.take() {
@@ -239,6 +275,25 @@ impl AudioInputManager {
         None
     }
 
+    /// Read the next captured frame (all device channels) without allocating
+    /// Returns None when no device is capturing or the buffer has run dry.
+    pub fn read_frame(&self) -> Option<InputFrame> {
+        let buffer_arc = self.input_buffer.as_ref()?;
+        let mut buffer = buffer_arc.lock();
+        let channels = usize::from(self.input_channels).max(1);
+        if buffer.occupied_len() < channels {
+            return None;
+        }
+        let mut device_frame = [0.0; MAX_INPUT_CHANNELS];
+        for index in 0..channels {
+            let sample = buffer.try_pop().unwrap_or(0.0);
+            if let Some(slot) = device_frame.get_mut(index) {
+                *slot = sample;
+            }
+        }
+        Some(InputFrame::from_samples(&device_frame[..channels.min(MAX_INPUT_CHANNELS)]))
+    }
+
     /// Get the number of samples currently in the buffer
     pub fn get_buffer_fill(&self) -> usize {
         if let Some(buffer_arc) = &self.input_buffer {
@@ -258,28 +313,142 @@ impl AudioInputManager {
         }
     }
 
-    /// Get the number of input channels (1 = mono, 2 = stereo)
+    /// Get the number of device input channels (1 = mono, 2 = stereo, more for interfaces)
     pub fn get_input_channels(&self) -> u16 {
         self.input_channels
     }
 
-    /// Get peak level for a specific input channel (0 = left, 1 = right)
+    /// Get the rate the input device captures at (Hz)
+    /// Captured audio is converted to `TARGET_SAMPLE_RATE` before it reaches the recorder.
+    pub fn get_input_sample_rate(&self) -> u32 {
+        self.input_sample_rate
+    }
+
+    /// Get peak level for a specific input channel (0-based)
     /// Returns the peak amplitude (0.0 to 1.0+) from the most recent input callback.
-    /// Used for live input metering in the UI.
+    /// A mono device reports its channel on both 0 and 1. Used for live input metering in the UI.
     pub fn get_channel_peak(&self, channel: u32) -> f32 {
-        let bits = if channel == 0 {
-            self.input_peak_left.load(Ordering::Relaxed)
-        } else {
-            self.input_peak_right.load(Ordering::Relaxed)
-        };
-        f32::from_bits(bits)
+        let channel = if self.input_channels == 1 && channel == 1 { 0 } else { channel as usize };
+        self.input_peaks
+            .get(channel)
+            .map_or(0.0, |peak| f32::from_bits(peak.load(Ordering::Relaxed)))
     }
 
-    /// Get both channel peaks as (left, right)
+    /// Get the first two channel peaks as (left, right)
     pub fn get_peaks(&self) -> (f32, f32) {
-        let left = f32::from_bits(self.input_peak_left.load(Ordering::Relaxed));
-        let right = f32::from_bits(self.input_peak_right.load(Ordering::Relaxed));
-        (left, right)
+        (self.get_channel_peak(0), self.get_channel_peak(1))
+    }
+}
+
+/// Find an input device by name (as listed by `enumerate_devices`), or the
+/// default input device for None
+fn find_input_device(name: Option<&str>) -> Result<cpal::Device> {
+    let find = |mut devices: cpal::InputDevices<cpal::Devices>, name: &str| {
+        devices.find(|d| d.name().ok().as_deref() == Some(name))
+            .ok_or_else(|| anyhow::anyhow!("Input device '{name}' not found"))
+    };
+
+    #[cfg(all(windows, feature = "asio"))]
+    if let Some(asio_name) = name.and_then(|name| name.strip_prefix(ASIO_PREFIX)) {
+        let asio_host = cpal::host_from_id(cpal::HostId::Asio)?;
+        return find(asio_host.input_devices()?, asio_name);
+    }
+
+    let host = cpal::default_host();
+    match name {
+        Some(name) => find(host.input_devices()?, name),
+        None => host.default_input_device().ok_or_else(|| anyhow::anyhow!("No input device available")),
+    }
+}
+
+/// The device's input config at `TARGET_SAMPLE_RATE` if it supports that rate,
+/// otherwise its default config
+fn negotiate_input_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConfig> {
+    let default_config = device.default_input_config()?;
+    let target = cpal::SampleRate(TARGET_SAMPLE_RATE);
+    let at_target = device.supported_input_configs()?.find(|range| {
+        range.channels() == default_config.channels()
+            && range.sample_format() == default_config.sample_format()
+            && range.min_sample_rate() <= target
+            && target <= range.max_sample_rate()
+    });
+    Ok(at_target.map_or(default_config, |range| range.with_sample_rate(target)))
+}
+
+/// Input callback body: meter each channel of an interleaved block and queue it
+/// (converted to the engine rate by `resampler`, if given).
+/// When the ring buffer is full the oldest samples are dropped.
+fn capture_block(
+    data: &[f32],
+    channels: usize,
+    peaks: &[AtomicU32],
+    buffer: &mut HeapRb<f32>,
+    resampler: Option<&mut InputResampler>,
+) {
+    let channels = channels.max(1);
+    for (channel, peak) in peaks.iter().enumerate().take(channels) {
+        let max = data.iter().skip(channel).step_by(channels).fold(0.0f32, |max, sample| max.max(sample.abs()));
+        peak.store(max.to_bits(), Ordering::Relaxed);
+    }
+
+    let mut push = |sample: f32| {
+        if buffer.is_full() {
+            let _ = buffer.try_pop();
+        }
+        let _ = buffer.try_push(sample);
+    };
+    match resampler {
+        Some(resampler) => resampler.process(data, push),
+        None => data.iter().for_each(|&sample| push(sample)),
+    }
+}
+
+/// Converts interleaved device-rate input to `TARGET_SAMPLE_RATE` a chunk at a time
+/// Buffers are allocated up front, so processing is safe on the input callback.
+struct InputResampler {
+    resampler: FftFixedInOut<f32>,
+    /// Deinterleaved input waiting for a full resampler chunk
+    pending: Vec<Vec<f32>>,
+    /// Resampled output of the last chunk, per channel
+    output: Vec<Vec<f32>>,
+}
+
+impl InputResampler {
+    fn new(device_rate: u32, channels: usize) -> Result<Self> {
+        let resampler = FftFixedInOut::<f32>::new(
+            device_rate as usize,
+            TARGET_SAMPLE_RATE as usize,
+            INPUT_RESAMPLE_CHUNK_FRAMES,
+            channels,
+        )?;
+        let pending = vec![Vec::with_capacity(resampler.input_frames_next()); channels];
+        let output = vec![vec![0.0; resampler.output_frames_max()]; channels];
+        Ok(Self { resampler, pending, output })
+    }
+
+    /// Feed an interleaved block; `emit` receives the resampled, interleaved samples
+    fn process(&mut self, data: &[f32], mut emit: impl FnMut(f32)) {
+        let channels = self.pending.len();
+        let chunk_frames = self.resampler.input_frames_next();
+        for frame in data.chunks_exact(channels) {
+            for (pending, &sample) in self.pending.iter_mut().zip(frame) {
+                pending.push(sample);
+            }
+            if self.pending[0].len() < chunk_frames {
+                continue;
+            }
+
+            let frames_out = match self.resampler.process_into_buffer(&self.pending, &mut self.output, None) {
+                Ok((_, frames_out)) => frames_out,
+                Err(_) => 0,
+            };
+            for index in 0..frames_out {
+                for output in &self.output {
+                    emit(output[index]);
+                }
+            }
+            self.pending.iter_mut().for_each(Vec::clear);
+        }
     }
 }
 
@@ -314,5 +483,81 @@ mod tests {
             }
         }
     }
-}
 
+    #[test]
+    fn test_input_device_is_selected_by_name() {
+        let mut manager = AudioInputManager::new().unwrap();
+        assert_eq!(manager.get_selected_device_name(), None);
+
+        // Unknown devices are rejected and leave the selection alone
+        assert!(manager.select_device_by_name(Some("No Such Interface")).is_err());
+        assert_eq!(manager.get_selected_device_name(), None);
+
+        // Back to the system default (works with or without devices attached)
+        assert!(manager.select_device_by_name(None).is_ok());
+        assert_eq!(manager.get_selected_device_name(), None);
+        assert!(!manager.is_capturing());
+    }
+
+    #[test]
+    fn test_four_channel_input_is_captured_per_channel() {
+        use crate::recorder::Recorder;
+
+        // Simulated 4-channel interface: channel n carries (n + 1) / 10
+        let mut manager = AudioInputManager::new().unwrap();
+        let buffer = manager.open_buffer(4, 1.0);
+        let block: Vec<f32> = (0..64).flat_map(|_| [0.1, -0.2, 0.3, -0.4]).collect();
+        capture_block(&block, 4, &manager.input_peaks, &mut buffer.lock(), None);
+
+        assert_eq!(manager.get_input_channels(), 4);
+        for channel in 0..4 {
+            let expected = (channel + 1) as f32 / 10.0;
+            assert!((manager.get_channel_peak(channel) - expected).abs() < 1e-6);
+        }
+
+        // Every channel reaches the recorder; a track on channel 3 reads only that one
+        let recorder = Recorder::new();
+        recorder.set_count_in_bars(0);
+        recorder.start_recording().unwrap();
+        let refs = recorder.get_callback_refs();
+        while let Some(frame) = manager.read_frame() {
+            assert_eq!(frame.channels(), 4);
+            assert!((frame.channel(3) + 0.4).abs() < 1e-6);
+            assert!(frame.channel(4).abs() < 1e-6);
+            refs.process_frame(frame.as_slice(), true, 0.0);
+        }
+
+        let clip = recorder.stop_recording().unwrap().unwrap();
+        assert_eq!(clip.channels, 4);
+        assert_eq!(clip.samples.len(), 64 * 4);
+        assert!((clip.samples[2] - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_44k1_input_resampled_to_48k_preserves_duration() {
+        let mut resampler = InputResampler::new(44_100, 2).unwrap();
+        let latency_frames = resampler.resampler.output_delay();
+
+        // One second of a stereo 44.1k sine, delivered in device-sized blocks
+        let input: Vec<f32> = (0..44_100)
+            .flat_map(|i| {
+                let sample = (i as f32 * 440.0 * std::f32::consts::TAU / 44_100.0).sin() * 0.5;
+                [sample, sample]
+            })
+            .collect();
+        let mut output = Vec::new();
+        for block in input.chunks(512 * 2) {
+            resampler.process(block, |sample| output.push(sample));
+        }
+
+        // Everything but the last partial chunk comes out, at 48k frames per second
+        let chunk_out = resampler.resampler.output_frames_max();
+        let frames = output.len() / 2;
+        assert!(frames <= TARGET_SAMPLE_RATE as usize);
+        assert!(frames + chunk_out >= TARGET_SAMPLE_RATE as usize, "got {frames} frames");
+
+        // The converted signal keeps its level (past the resampler's delay)
+        let peak = output[(latency_frames + 100) * 2..].iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        assert!((peak - 0.5).abs() < 0.02, "peak {peak}");
+    }
+}
@@ -0,0 +1,346 @@
+/// On-demand sample storage for audio clips too large to decode into RAM
+///
+/// A `ClipStream` keeps only the WAV header in memory and reads samples from disk in
+/// windows of `STREAM_WINDOW_FRAMES`, holding the `STREAM_CACHED_WINDOWS` most recently
+/// used ones. A background prefetch thread per clip loads the window under the read
+/// position and its neighbours, so playing forward (or reversed) finds the next window
+/// already in memory. The audio thread (see `mark_realtime_thread`) never touches the
+/// disk: a window that isn't loaded yet plays as silence until the prefetcher catches
+/// up. Other threads (offline renders, analysis) read missing windows synchronously.
+/// Only 48 kHz WAV files can be streamed: anything else needs decoding or resampling first.
+use crate::audio_file::{pcm_to_f32, read_wav_format, WavFormat};
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read as _, Seek as _, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread::{JoinHandle, Thread};
+
+/// Frames per cached window
+pub const STREAM_WINDOW_FRAMES: usize = 1 << 16;
+/// Number of windows kept in memory per clip
+const STREAM_CACHED_WINDOWS: usize = 4;
+/// No window requested yet
+const NO_WINDOW: usize = usize::MAX;
+
+thread_local! {
+    static REALTIME_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Mark the calling thread as the audio thread: from now on its reads never block
+/// on disk and return silence for windows the prefetcher hasn't loaded yet
+pub fn mark_realtime_thread() {
+    REALTIME_THREAD.with(|realtime| realtime.set(true));
+}
+
+/// Windowed reader over a WAV file's sample data
+pub struct ClipStream {
+    shared: Arc<StreamShared>,
+    prefetcher: Option<JoinHandle<()>>,
+}
+
+/// State shared between readers and the prefetch thread
+struct StreamShared {
+    path: PathBuf,
+    format: WavFormat,
+    frames: usize,
+    /// Most recently used windows (front = newest); never locked across a disk read
+    windows: Mutex<VecDeque<Window>>,
+    /// Window under the latest read position, for the prefetcher
+    wanted: AtomicUsize,
+    shutdown: AtomicBool,
+    prefetch_thread: Mutex<Option<Thread>>,
+}
+
+struct Window {
+    index: usize,
+    samples: Vec<f32>,
+}
+
+impl std::fmt::Debug for ClipStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClipStream").field("path", &self.shared.path).field("frames", &self.shared.frames).finish_non_exhaustive()
+    }
+}
+
+impl ClipStream {
+    /// Read the WAV header of `path` and start the prefetch thread; fails for files
+    /// that can't be streamed
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut file = File::open(path).context("Failed to open WAV file")?;
+        let format = read_wav_format(&mut file)?;
+        if format.sample_rate != crate::audio_file::TARGET_SAMPLE_RATE {
+            anyhow::bail!("Streaming needs a {} Hz WAV, got {} Hz", crate::audio_file::TARGET_SAMPLE_RATE, format.sample_rate);
+        }
+        let shared = Arc::new(StreamShared {
+            path: path.to_path_buf(),
+            frames: format.frame_count(),
+            format,
+            windows: Mutex::new(VecDeque::with_capacity(STREAM_CACHED_WINDOWS)),
+            wanted: AtomicUsize::new(0),
+            shutdown: AtomicBool::new(false),
+            prefetch_thread: Mutex::new(None),
+        });
+        let weak = Arc::downgrade(&shared);
+        let prefetcher = std::thread::Builder::new()
+            .name("clip-stream-prefetch".to_string())
+            .spawn(move || prefetch_loop(&weak, file))
+            .context("Failed to start prefetch thread")?;
+        *shared.prefetch_thread.lock() = Some(prefetcher.thread().clone());
+        Ok(Self { shared, prefetcher: Some(prefetcher) })
+    }
+
+    pub fn channels(&self) -> usize {
+        self.shared.format.channels
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.shared.format.sample_rate
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.shared.frames
+    }
+
+    /// Sample at (frame, channel) through the window cache; 0.0 if the file can't be
+    /// read, or on the audio thread while the window is still being prefetched
+    pub fn get_sample(&self, frame: usize, channel: usize) -> f32 {
+        let shared = &self.shared;
+        let channels = shared.format.channels;
+        if frame >= shared.frames || channel >= channels {
+            return 0.0;
+        }
+        let index = frame / STREAM_WINDOW_FRAMES;
+        let position = (frame % STREAM_WINDOW_FRAMES) * channels + channel;
+        let cached = shared.cached_sample(index, position);
+        shared.request(index);
+        if let Some(sample) = cached {
+            return sample;
+        }
+        if REALTIME_THREAD.with(Cell::get) {
+            return 0.0;
+        }
+        // Off the audio thread: load the window here rather than wait for the prefetcher
+        let samples = shared.read_window(index).unwrap_or_else(|e| {
+            eprintln!("⚠️ [ClipStream] Failed to read {}: {e}", shared.path.display());
+            Vec::new()
+        });
+        let sample = samples.get(position).copied().unwrap_or(0.0);
+        shared.insert(index, samples);
+        sample
+    }
+
+    /// Interleaved samples of frames `start..start + frames` (clamped to the clip),
+    /// read straight from disk without touching the window cache
+    pub fn read_frames(&self, start: usize, frames: usize) -> Result<Vec<f32>> {
+        let shared = &self.shared;
+        let start = start.min(shared.frames);
+        let frames = frames.min(shared.frames - start);
+        let mut file = File::open(&shared.path).context("Failed to open WAV file")?;
+        shared.read_from(&mut file, start, frames)
+    }
+}
+
+impl Drop for ClipStream {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::Release);
+        if let Some(prefetcher) = self.prefetcher.take() {
+            prefetcher.thread().unpark();
+            prefetcher.join().ok();
+        }
+    }
+}
+
+impl StreamShared {
+    /// Point the prefetcher at window `index` (a no-op while it stays the same)
+    fn request(&self, index: usize) {
+        if self.wanted.swap(index, Ordering::AcqRel) != index {
+            if let Some(thread) = self.prefetch_thread.lock().as_ref() {
+                thread.unpark();
+            }
+        }
+    }
+
+    /// Look up an already-loaded window, marking it most recently used
+    fn cached_sample(&self, index: usize, position: usize) -> Option<f32> {
+        let mut windows = self.windows.lock();
+        let hit = windows.iter().position(|window| window.index == index)?;
+        if hit != 0 {
+            let window = windows.remove(hit).expect("window index in range");
+            windows.push_front(window);
+        }
+        Some(windows[0].samples.get(position).copied().unwrap_or(0.0))
+    }
+
+    fn is_cached(&self, index: usize) -> bool {
+        self.windows.lock().iter().any(|window| window.index == index)
+    }
+
+    /// Add a freshly read window, evicting the least recently used one
+    fn insert(&self, index: usize, samples: Vec<f32>) {
+        let evicted = {
+            let mut windows = self.windows.lock();
+            if windows.iter().any(|window| window.index == index) {
+                return;
+            }
+            let evicted = (windows.len() >= STREAM_CACHED_WINDOWS).then(|| windows.pop_back());
+            windows.push_front(Window { index, samples });
+            evicted
+        };
+        // Free the evicted window outside the lock
+        drop(evicted);
+    }
+
+    /// Samples of window `index` read from disk on a fresh file handle
+    fn read_window(&self, index: usize) -> Result<Vec<f32>> {
+        let mut file = File::open(&self.path).context("Failed to open WAV file")?;
+        self.read_window_from(&mut file, index)
+    }
+
+    fn read_window_from(&self, file: &mut File, index: usize) -> Result<Vec<f32>> {
+        let start = index * STREAM_WINDOW_FRAMES;
+        let frames = STREAM_WINDOW_FRAMES.min(self.frames.saturating_sub(start));
+        self.read_from(file, start, frames)
+    }
+
+    fn read_from(&self, file: &mut File, start: usize, frames: usize) -> Result<Vec<f32>> {
+        let frame_bytes = self.format.bytes_per_sample() * self.format.channels;
+        file.seek(SeekFrom::Start(self.format.data_offset + (start * frame_bytes) as u64))?;
+        let mut raw_data = vec![0u8; frames * frame_bytes];
+        file.read_exact(&mut raw_data)?;
+        Ok(pcm_to_f32(&raw_data, &self.format))
+    }
+}
+
+/// Prefetch thread body: keep the requested window and its neighbours loaded until
+/// the stream is dropped
+fn prefetch_loop(shared: &Weak<StreamShared>, mut file: File) {
+    let mut loaded_for = NO_WINDOW;
+    loop {
+        let Some(stream) = shared.upgrade() else { return };
+        if stream.shutdown.load(Ordering::Acquire) {
+            return;
+        }
+        let wanted = stream.wanted.load(Ordering::Acquire);
+        if wanted == loaded_for {
+            drop(stream);
+            std::thread::park();
+            continue;
+        }
+        // Current window first, then the one ahead, then the one behind (reversed clips)
+        let last_window = stream.frames.saturating_sub(1) / STREAM_WINDOW_FRAMES;
+        let neighbours = [Some(wanted), wanted.checked_add(1), wanted.checked_sub(1)];
+        for index in neighbours.into_iter().flatten().filter(|&index| index <= last_window) {
+            if stream.is_cached(index) {
+                continue;
+            }
+            match stream.read_window_from(&mut file, index) {
+                Ok(samples) => stream.insert(index, samples),
+                Err(e) => {
+                    // Retried once playback moves to another window, with the file reopened
+                    eprintln!("⚠️ [ClipStream] Failed to prefetch {}: {e}", stream.path.display());
+                    if let Ok(reopened) = File::open(&stream.path) {
+                        file = reopened;
+                    }
+                    break;
+                }
+            }
+        }
+        loaded_for = wanted;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_file::{load_audio_file, load_audio_file_streaming, TARGET_SAMPLE_RATE};
+
+    fn write_test_wav(name: &str, sample_rate: u32, frames: usize) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let spec = hound::WavSpec { channels: 2, sample_rate, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for n in 0..frames {
+            let phase = n as f32 * 0.013;
+            writer.write_sample((phase.sin() * 20_000.0) as i16).unwrap();
+            writer.write_sample((phase.cos() * -15_000.0) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_streamed_clip_matches_fully_loaded_clip() {
+        let frames = STREAM_WINDOW_FRAMES * 5 + 321;
+        let path = write_test_wav("boojy_test_clip_stream.wav", TARGET_SAMPLE_RATE, frames);
+        let loaded = load_audio_file(&path).unwrap();
+        let streamed = load_audio_file_streaming(&path).unwrap();
+        assert!(streamed.is_streaming());
+        assert!(streamed.samples.is_empty());
+        assert_eq!((streamed.channels, streamed.frame_count()), (2, frames));
+
+        // Forward across window boundaries, then jumping backwards past the cache size
+        let edges = (0..=5).flat_map(|w| [w * STREAM_WINDOW_FRAMES, (w * STREAM_WINDOW_FRAMES).saturating_sub(1)]);
+        let positions: Vec<usize> = edges.chain((0..frames).step_by(7919)).chain((0..frames).rev().step_by(6007)).collect();
+        for frame in positions.into_iter().filter(|&frame| frame < frames) {
+            for channel in 0..2 {
+                assert_eq!(streamed.get_sample(frame, channel), loaded.get_sample(frame, channel), "frame {frame}");
+            }
+        }
+        assert_eq!(streamed.get_sample(frames, 0), None);
+        assert_eq!(streamed.get_sample(0, 2), None);
+
+        let start = STREAM_WINDOW_FRAMES - 10;
+        assert_eq!(streamed.read_frames(start, 20), loaded.read_frames(start, 20));
+        assert_eq!(streamed.read_frames(frames - 5, 100).len(), 10);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_audio_thread_reads_only_prefetched_windows() {
+        let frames = STREAM_WINDOW_FRAMES * 4;
+        let path = write_test_wav("boojy_test_clip_stream_prefetch.wav", TARGET_SAMPLE_RATE, frames);
+        let loaded = load_audio_file(&path).unwrap();
+        let stream = Arc::new(ClipStream::open(&path).unwrap());
+
+        let frame = STREAM_WINDOW_FRAMES * 3 + 100;
+        let expected = loaded.get_sample(frame, 0);
+        assert_ne!(expected, Some(0.0));
+        let reader = Arc::clone(&stream);
+        let (first, prefetched) = std::thread::spawn(move || {
+            mark_realtime_thread();
+            // A jump to an unloaded window plays silence instead of reading the disk...
+            let first = reader.get_sample(frame, 0);
+            // ...until the prefetch thread has loaded it, along with the window behind
+            // it for reversed playback
+            let index = frame / STREAM_WINDOW_FRAMES;
+            let loaded = || reader.shared.is_cached(index) && reader.shared.is_cached(index - 1);
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+            while !loaded() && std::time::Instant::now() < deadline {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+            (first, reader.get_sample(frame, 0))
+        })
+        .join()
+        .unwrap();
+        assert_eq!(Some(first), Some(0.0));
+        assert_eq!(Some(prefetched), expected);
+        assert!(stream.shared.is_cached(frame / STREAM_WINDOW_FRAMES - 1));
+        drop(stream);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_other_sample_rates_fall_back_to_full_load() {
+        let path = write_test_wav("boojy_test_clip_stream_44k.wav", 44_100, 4410);
+        let clip = load_audio_file_streaming(&path).unwrap();
+        assert!(!clip.is_streaming());
+        assert_eq!(clip.sample_rate, TARGET_SAMPLE_RATE);
+        assert!(!clip.samples.is_empty());
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -0,0 +1,184 @@
+/// Audio callback DSP load and overrun measurement
+///
+/// The callback times itself against its buffer's wall-clock budget
+/// (`frames / sample_rate`). The load is smoothed over roughly half a second and
+/// published through atomics. Xruns are counted when a callback takes longer than
+/// its budget, or when the next callback starts well after the previous buffer
+/// should have run out (the device dropped audio between callbacks).
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Time constant of the load average (seconds)
+const LOAD_AVERAGE_SECONDS: f64 = 0.5;
+/// A gap between callbacks longer than this many buffer durations is an xrun
+const XRUN_GAP_FACTOR: f64 = 1.5;
+
+/// Latest DSP load readings, shared between the audio thread and the API
+pub struct DspLoadReadings {
+    /// Smoothed load in percent of the buffer budget (f32 bits)
+    load_percent: AtomicU32,
+    xrun_count: AtomicU64,
+    /// Whether any callback has been measured yet
+    measured: AtomicBool,
+}
+
+impl Default for DspLoadReadings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DspLoadReadings {
+    pub fn new() -> Self {
+        Self {
+            load_percent: AtomicU32::new(0),
+            xrun_count: AtomicU64::new(0),
+            measured: AtomicBool::new(false),
+        }
+    }
+
+    /// Smoothed callback load in percent (can exceed 100 when overloaded)
+    pub fn load_percent(&self) -> f32 {
+        f32::from_bits(self.load_percent.load(Ordering::Relaxed))
+    }
+
+    /// Xruns since startup or the last `reset_xrun_count`
+    pub fn xrun_count(&self) -> u64 {
+        self.xrun_count.load(Ordering::Relaxed)
+    }
+
+    pub fn reset_xrun_count(&self) {
+        self.xrun_count.store(0, Ordering::Relaxed);
+    }
+
+    /// Whether the audio callback has run (readings are zero until then)
+    pub fn is_measured(&self) -> bool {
+        self.measured.load(Ordering::Relaxed)
+    }
+}
+
+/// DSP load state (owned by the audio thread, allocation-free)
+pub struct DspLoadMeter {
+    /// Device sample rate the callback buffers are timed against
+    sample_rate: u32,
+    average: f64,
+    /// Start time and frame count of the previous callback
+    previous: Option<(Instant, usize)>,
+    /// Whether the previous callback overran (its late successor is the same xrun)
+    previous_overran: bool,
+}
+
+impl DspLoadMeter {
+    pub fn new(sample_rate: u32) -> Self {
+        Self { sample_rate, average: 0.0, previous: None, previous_overran: false }
+    }
+
+    /// Start timing a callback of `frames` frames; the measurement is recorded
+    /// when the returned guard is dropped, so early returns are covered too
+    pub fn measure<'a>(&'a mut self, frames: usize, readings: &'a DspLoadReadings) -> DspLoadGuard<'a> {
+        let start = Instant::now();
+        self.callback_started(start, frames, readings);
+        DspLoadGuard { meter: self, readings, frames, start }
+    }
+
+    /// Count an xrun if this callback starts well after the previous buffer ran out
+    pub fn callback_started(&mut self, start: Instant, frames: usize, readings: &DspLoadReadings) {
+        if let Some((previous_start, previous_frames)) = self.previous {
+            let expected = previous_frames as f64 / f64::from(self.sample_rate.max(1));
+            let gap = start.saturating_duration_since(previous_start).as_secs_f64();
+            if gap > expected * XRUN_GAP_FACTOR && !self.previous_overran {
+                readings.xrun_count.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.previous = Some((start, frames));
+    }
+
+    /// Record one callback that took `elapsed` to render `frames` frames
+    pub fn record(&mut self, frames: usize, elapsed: Duration, readings: &DspLoadReadings) {
+        if frames == 0 || self.sample_rate == 0 {
+            return;
+        }
+        let budget = frames as f64 / f64::from(self.sample_rate);
+        let load = elapsed.as_secs_f64() / budget;
+        self.previous_overran = load > 1.0;
+        if self.previous_overran {
+            readings.xrun_count.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let coefficient = (budget / LOAD_AVERAGE_SECONDS).min(1.0);
+        self.average += (load - self.average) * coefficient;
+        readings.load_percent.store(((self.average * 100.0) as f32).to_bits(), Ordering::Relaxed);
+        readings.measured.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Times one audio callback (see `DspLoadMeter::measure`)
+pub struct DspLoadGuard<'a> {
+    meter: &'a mut DspLoadMeter,
+    readings: &'a DspLoadReadings,
+    frames: usize,
+    start: Instant,
+}
+
+impl Drop for DspLoadGuard<'_> {
+    fn drop(&mut self) {
+        self.meter.record(self.frames, self.start.elapsed(), self.readings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_averages_and_overruns_count_as_xruns() {
+        let mut meter = DspLoadMeter::new(48000);
+        let readings = DspLoadReadings::new();
+        assert!(!readings.is_measured());
+
+        // 512 frames at 48 kHz is a 10.67ms budget; spending a quarter of it
+        // settles at 25%
+        let budget = Duration::from_secs_f64(512.0 / 48000.0);
+        for _ in 0..1000 {
+            meter.record(512, budget / 4, &readings);
+        }
+        assert!((readings.load_percent() - 25.0).abs() < 0.1, "load {}", readings.load_percent());
+        assert_eq!(readings.xrun_count(), 0);
+
+        // One overrun counts once and nudges the average up without jumping to it
+        meter.record(512, budget * 2, &readings);
+        assert_eq!(readings.xrun_count(), 1);
+        assert!(readings.load_percent() > 25.0 && readings.load_percent() < 50.0);
+    }
+
+    #[test]
+    fn test_late_callbacks_count_as_xruns_once() {
+        let mut meter = DspLoadMeter::new(48000);
+        let readings = DspLoadReadings::new();
+        let budget = Duration::from_secs_f64(512.0 / 48000.0);
+        let mut start = Instant::now();
+
+        // Steady callbacks with a little jitter
+        for i in 0..10 {
+            start += budget + Duration::from_micros(if i % 2 == 0 { 500 } else { 0 });
+            meter.callback_started(start, 512, &readings);
+            meter.record(512, budget / 4, &readings);
+        }
+        assert_eq!(readings.xrun_count(), 0);
+
+        // The device skipped a buffer
+        start += budget * 2;
+        meter.callback_started(start, 512, &readings);
+        meter.record(512, budget / 4, &readings);
+        assert_eq!(readings.xrun_count(), 1);
+
+        // An overrun delays the next callback too, but is only counted once
+        meter.record(512, budget * 2, &readings);
+        start += budget * 2;
+        meter.callback_started(start, 512, &readings);
+        assert_eq!(readings.xrun_count(), 2);
+
+        readings.reset_xrun_count();
+        assert_eq!(readings.xrun_count(), 0);
+    }
+}
@@ -5,9 +5,16 @@
 /// - Compressor (RMS/peak with attack/release)
 /// - Reverb (Freeverb algorithm)
 /// - Delay (tempo-synced or time-based)
-/// - Limiter (brick-wall, for master track)
+/// - Limiter (brick-wall with lookahead, for master track)
+///   (compressor and limiter can run 2×/4× oversampled to reduce aliasing)
 /// - Chorus (modulated delay with LFO)
+/// - Bit crusher (bit-depth and sample-rate reduction)
+/// - Noise gate (threshold with attack/hold/release)
+/// - Stereo width (mid/side)
+/// - Filter (resonant low-pass/high-pass/band-pass)
+/// - Gain (utility: trim, polarity invert, DC removal)
 use crate::audio_file::TARGET_SAMPLE_RATE;
+use std::collections::VecDeque;
 use std::f32::consts::PI;
 
 /// Effect trait: all effects implement this
@@ -30,6 +37,12 @@ pub trait Effect: Send {
     /// Reset internal state (clear buffers, etc.)
     fn reset(&mut self);
 
+    /// Delay the effect adds to its output, in frames at the engine rate
+    /// (lookahead, oversampling filters); used for plugin delay compensation.
+    fn latency_samples(&self) -> usize {
+        0
+    }
+
     /// Get effect name
     fn name(&self) -> &str;
 }
@@ -37,6 +50,262 @@ pub trait Effect: Send {
 /// Unique identifier for effects
 pub type EffectId = u64;
 
+// ========================================================================
+// PARAMETER SMOOTHING
+// ========================================================================
+
+/// Time for a smoothed parameter to cover 99% of a step (ms)
+pub const PARAM_SMOOTHING_MS: f32 = 10.0;
+
+/// One-pole smoother for an effect parameter
+///
+/// The effect's public parameter field is the target: the DSP reads the smoothed value,
+/// which glides toward it, so stepping a parameter during playback doesn't zipper. The
+/// first value read after creation or `reset` jumps straight to the target.
+#[derive(Debug, Clone, Copy)]
+pub struct SmoothedParam {
+    current: f32,
+    coeff: f32,
+    primed: bool,
+}
+
+impl Default for SmoothedParam {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SmoothedParam {
+    pub fn new() -> Self {
+        // e^-4.6 ≈ 1%: 99% of the step is covered after PARAM_SMOOTHING_MS
+        let samples = PARAM_SMOOTHING_MS * 0.001 * TARGET_SAMPLE_RATE as f32;
+        Self { current: 0.0, coeff: (-4.6 / samples).exp(), primed: false }
+    }
+
+    /// Whether the value still differs from `target` (the next `next` call moves it)
+    pub fn is_moving(&self, target: f32) -> bool {
+        !self.primed || (target - self.current).abs() > 1e-4 * target.abs().max(1.0)
+    }
+
+    /// Step toward `target` and return the value for this sample
+    #[inline]
+    pub fn next(&mut self, target: f32) -> f32 {
+        if !self.primed || !self.is_moving(target) {
+            self.current = target;
+            self.primed = true;
+        } else {
+            self.current = target + (self.current - target) * self.coeff;
+        }
+        self.current
+    }
+
+    /// Jump to `target` without gliding
+    pub fn snap(&mut self, target: f32) {
+        self.current = target;
+        self.primed = true;
+    }
+
+    /// Make the next read jump to its target
+    pub fn reset(&mut self) {
+        self.primed = false;
+    }
+}
+
+// ========================================================================
+// LATENCY DELAY (dry path of latent effects, plugin delay compensation)
+// ========================================================================
+
+/// Longest delay a `LatencyDelay` holds (about 340 ms at 48 kHz); longer delays are
+/// clamped to it
+pub const MAX_LATENCY_FRAMES: usize = 16384;
+
+/// Stereo delay line for lining a signal up with a latent one
+///
+/// Allocated once at full length, so it can be used on the audio thread; changing
+/// the delay only moves the read position (after silencing the line).
+#[derive(Clone)]
+pub struct LatencyDelay {
+    buffer: Vec<(f32, f32)>,
+    write_pos: usize,
+    delay: usize,
+    /// Holds audio (`clear` skips silent lines)
+    dirty: bool,
+}
+
+impl Default for LatencyDelay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyDelay {
+    pub fn new() -> Self {
+        Self { buffer: vec![(0.0, 0.0); MAX_LATENCY_FRAMES], write_pos: 0, delay: 0, dirty: false }
+    }
+
+    /// Delay one frame by `delay` frames; a new delay starts from silence
+    #[inline]
+    pub fn process(&mut self, delay: usize, left: f32, right: f32) -> (f32, f32) {
+        let delay = delay.min(MAX_LATENCY_FRAMES - 1);
+        if delay != self.delay {
+            self.clear();
+            self.delay = delay;
+        }
+        self.dirty = true;
+        self.buffer[self.write_pos] = (left, right);
+        let read_pos = (self.write_pos + MAX_LATENCY_FRAMES - delay) % MAX_LATENCY_FRAMES;
+        self.write_pos = (self.write_pos + 1) % MAX_LATENCY_FRAMES;
+        self.buffer[read_pos]
+    }
+
+    /// Silence the line
+    pub fn clear(&mut self) {
+        if self.dirty {
+            self.buffer.fill((0.0, 0.0));
+            self.dirty = false;
+        }
+    }
+}
+
+// ========================================================================
+// OVERSAMPLING (used by Compressor and Limiter)
+// ========================================================================
+
+/// Oversampling factors accepted by `set_oversample` (1 = off)
+pub const OVERSAMPLE_FACTORS: [u32; 3] = [1, 2, 4];
+/// Low-pass FIR taps per polyphase branch
+const OVERSAMPLE_TAPS_PER_PHASE: usize = 48;
+
+/// Stereo oversampler: polyphase FIR interpolation up, FIR decimation down
+///
+/// Both stages use the same linear-phase windowed-sinc low-pass, cut just below the
+/// original Nyquist, so harmonics a non-linear process adds above it are filtered out
+/// before decimation instead of folding back as aliasing. Adds `latency_frames` of delay.
+#[derive(Clone)]
+pub struct Oversampler {
+    factor: usize,
+    /// Low-pass taps at the oversampled rate (symmetric)
+    taps: Vec<f32>,
+    /// Interpolation taps per output phase, ordered to match the input history
+    phases: Vec<Vec<f32>>,
+    /// Input history per channel (one sample per original frame)
+    input: [FirHistory; 2],
+    /// Processed history per channel (one sample per oversampled frame)
+    output: [FirHistory; 2],
+}
+
+impl Oversampler {
+    /// `factor` must be 2 or 4
+    fn new(factor: usize) -> Self {
+        let len = OVERSAMPLE_TAPS_PER_PHASE * factor;
+        // Blackman transition band is ~5.5/len wide; end it at the original Nyquist
+        let cutoff = 0.5 / factor as f32 - 2.75 / len as f32;
+        let center = (len - 1) as f32 * 0.5;
+        let mut taps: Vec<f32> = (0..len)
+            .map(|i| {
+                let x = i as f32 - center;
+                let sinc = if x == 0.0 { 1.0 } else { (2.0 * PI * cutoff * x).sin() / (2.0 * PI * cutoff * x) };
+                let phase = 2.0 * PI * i as f32 / (len - 1) as f32;
+                let window = 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+                2.0 * cutoff * sinc * window
+            })
+            .collect();
+        let dc_gain: f32 = taps.iter().sum();
+        for tap in &mut taps {
+            *tap /= dc_gain;
+        }
+
+        // Output phase k of input frame n is factor * sum_j taps[k + j * factor] * x[n - j];
+        // the history is oldest first, so each branch is stored reversed
+        let phases = (0..factor)
+            .map(|k| {
+                (0..OVERSAMPLE_TAPS_PER_PHASE)
+                    .rev()
+                    .map(|j| taps[k + j * factor] * factor as f32)
+                    .collect()
+            })
+            .collect();
+        Self {
+            factor,
+            taps,
+            phases,
+            input: [FirHistory::new(OVERSAMPLE_TAPS_PER_PHASE), FirHistory::new(OVERSAMPLE_TAPS_PER_PHASE)],
+            output: [FirHistory::new(len), FirHistory::new(len)],
+        }
+    }
+
+    pub fn factor(&self) -> usize {
+        self.factor
+    }
+
+    /// Delay added by the two filters, in original frames
+    pub fn latency_frames(&self) -> usize {
+        self.taps.len() / self.factor
+    }
+
+    /// Run `process` on `factor` interpolated frames and decimate the result to one frame
+    pub fn process(&mut self, left: f32, right: f32, mut process: impl FnMut(f32, f32) -> (f32, f32)) -> (f32, f32) {
+        self.input[0].push(left);
+        self.input[1].push(right);
+        for phase in &self.phases {
+            let up_left = dot(phase, self.input[0].samples());
+            let up_right = dot(phase, self.input[1].samples());
+            let (out_left, out_right) = process(up_left, up_right);
+            self.output[0].push(out_left);
+            self.output[1].push(out_right);
+        }
+        // The taps are symmetric, so they need no reversing against the oldest-first history
+        (dot(&self.taps, self.output[0].samples()), dot(&self.taps, self.output[1].samples()))
+    }
+
+    pub fn reset(&mut self) {
+        for history in self.input.iter_mut().chain(self.output.iter_mut()) {
+            history.clear();
+        }
+    }
+}
+
+/// Oversampler for a factor from `OVERSAMPLE_FACTORS` (None = off)
+fn oversampler_for(factor: u32) -> Result<Option<Oversampler>, String> {
+    if !OVERSAMPLE_FACTORS.contains(&factor) {
+        return Err(format!("Invalid oversampling factor {factor} (expected 1, 2 or 4)"));
+    }
+    Ok((factor > 1).then(|| Oversampler::new(factor as usize)))
+}
+
+/// The last `len` samples of a signal, readable as one slice (oldest first)
+#[derive(Clone)]
+struct FirHistory {
+    /// Every sample is written twice, `len` apart, so any window is contiguous
+    buffer: Vec<f32>,
+    pos: usize,
+    len: usize,
+}
+
+impl FirHistory {
+    fn new(len: usize) -> Self {
+        Self { buffer: vec![0.0; len * 2], pos: 0, len }
+    }
+
+    fn push(&mut self, sample: f32) {
+        self.pos = (self.pos + 1) % self.len;
+        self.buffer[self.pos] = sample;
+        self.buffer[self.pos + self.len] = sample;
+    }
+
+    fn samples(&self) -> &[f32] {
+        &self.buffer[self.pos + 1..=self.pos + self.len]
+    }
+
+    fn clear(&mut self) {
+        self.buffer.fill(0.0);
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
 // ========================================================================
 // BIQUAD FILTER (used by EQ)
 // ========================================================================
@@ -47,6 +316,10 @@ pub enum BiquadType {
     LowShelf,
     HighShelf,
     Parametric,
+    LowPass,
+    HighPass,
+    /// Band-pass with constant 0 dB peak gain
+    BandPass,
 }
 
 /// Biquad filter (2nd-order IIR filter)
@@ -124,6 +397,26 @@ impl BiquadFilter {
                 let a1 = -2.0 * cos_omega;
                 let a2 = 1.0 - alpha / a;
 
+                // Normalize
+                self.b0 = b0 / a0;
+                self.b1 = b1 / a0;
+                self.b2 = b2 / a0;
+                self.a1 = a1 / a0;
+                self.a2 = a2 / a0;
+            }
+            BiquadType::LowPass | BiquadType::HighPass | BiquadType::BandPass => {
+                // Pass filters (gain_db unused)
+                let low = 1.0 - cos_omega;
+                let high = 1.0 + cos_omega;
+                let (b0, b1, b2) = match biquad_type {
+                    BiquadType::LowPass => (low * 0.5, low, low * 0.5),
+                    BiquadType::HighPass => (high * 0.5, -high, high * 0.5),
+                    _ => (alpha, 0.0, -alpha),
+                };
+                let a0 = 1.0 + alpha;
+                let a1 = -2.0 * cos_omega;
+                let a2 = 1.0 - alpha;
+
                 // Normalize
                 self.b0 = b0 / a0;
                 self.b1 = b1 / a0;
@@ -187,6 +480,9 @@ pub struct ParametricEQ {
     pub mid2_q: f32,
     pub high_freq: f32,
     pub high_gain_db: f32,
+
+    /// Smoothed parameters, in `targets` order
+    smoothed: [SmoothedParam; 10],
 }
 
 impl Default for ParametricEQ {
@@ -216,28 +512,59 @@ impl ParametricEQ {
             mid2_q: 1.0,
             high_freq: 8000.0,
             high_gain_db: 0.0,
+            smoothed: [SmoothedParam::new(); 10],
         };
         eq.update_coefficients();
         eq
     }
 
-    /// Update filter coefficients when parameters change
+    /// Parameter values the smoothed parameters glide toward
+    fn targets(&self) -> [f32; 10] {
+        [
+            self.low_freq, self.low_gain_db,
+            self.mid1_freq, self.mid1_gain_db, self.mid1_q,
+            self.mid2_freq, self.mid2_gain_db, self.mid2_q,
+            self.high_freq, self.high_gain_db,
+        ]
+    }
+
+    /// Apply the parameters immediately (no smoothing), e.g. after loading a project
     pub fn update_coefficients(&mut self) {
-        self.low_shelf.design(BiquadType::LowShelf, self.low_freq, self.low_gain_db, 0.707);
-        self.mid1.design(BiquadType::Parametric, self.mid1_freq, self.mid1_gain_db, self.mid1_q);
-        self.mid2.design(BiquadType::Parametric, self.mid2_freq, self.mid2_gain_db, self.mid2_q);
-        self.high_shelf.design(BiquadType::HighShelf, self.high_freq, self.high_gain_db, 0.707);
+        let targets = self.targets();
+        for (param, target) in self.smoothed.iter_mut().zip(targets) {
+            param.snap(target);
+        }
+        self.design_filters(targets);
+    }
+
+    /// Step the smoothed parameters toward the current settings, redesigning the
+    /// filters while any of them is still moving
+    fn step_smoothing(&mut self) {
+        let targets = self.targets();
+        if self.smoothed.iter().zip(targets).any(|(param, target)| param.is_moving(target)) {
+            let values = std::array::from_fn(|i| self.smoothed[i].next(targets[i]));
+            self.design_filters(values);
+        }
+    }
+
+    fn design_filters(&mut self, [low_freq, low_gain_db, mid1_freq, mid1_gain_db, mid1_q, mid2_freq, mid2_gain_db, mid2_q, high_freq, high_gain_db]: [f32; 10]) {
+        self.low_shelf.design(BiquadType::LowShelf, low_freq, low_gain_db, 0.707);
+        self.mid1.design(BiquadType::Parametric, mid1_freq, mid1_gain_db, mid1_q);
+        self.mid2.design(BiquadType::Parametric, mid2_freq, mid2_gain_db, mid2_q);
+        self.high_shelf.design(BiquadType::HighShelf, high_freq, high_gain_db, 0.707);
 
         // Copy to right channel
-        self.low_shelf_r.design(BiquadType::LowShelf, self.low_freq, self.low_gain_db, 0.707);
-        self.mid1_r.design(BiquadType::Parametric, self.mid1_freq, self.mid1_gain_db, self.mid1_q);
-        self.mid2_r.design(BiquadType::Parametric, self.mid2_freq, self.mid2_gain_db, self.mid2_q);
-        self.high_shelf_r.design(BiquadType::HighShelf, self.high_freq, self.high_gain_db, 0.707);
+        self.low_shelf_r.design(BiquadType::LowShelf, low_freq, low_gain_db, 0.707);
+        self.mid1_r.design(BiquadType::Parametric, mid1_freq, mid1_gain_db, mid1_q);
+        self.mid2_r.design(BiquadType::Parametric, mid2_freq, mid2_gain_db, mid2_q);
+        self.high_shelf_r.design(BiquadType::HighShelf, high_freq, high_gain_db, 0.707);
     }
 }
 
 impl Effect for ParametricEQ {
     fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        self.step_smoothing();
+
         // Process left channel through all bands
         let mut left_out = left;
         left_out = self.low_shelf.process(left_out);
@@ -255,6 +582,26 @@ impl Effect for ParametricEQ {
         (left_out, right_out)
     }
 
+    /// Same as `process_frame`; once the parameters have settled, each channel runs
+    /// through the bands in one pass with no per-frame smoothing check
+    fn process_block(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let targets = self.targets();
+        if self.smoothed.iter().zip(targets).any(|(param, target)| param.is_moving(target)) {
+            // Filters are redesigned every frame while a parameter glides
+            for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+                (*l, *r) = self.process_frame(*l, *r);
+            }
+            return;
+        }
+
+        for sample in left.iter_mut() {
+            *sample = self.high_shelf.process(self.mid2.process(self.mid1.process(self.low_shelf.process(*sample))));
+        }
+        for sample in right.iter_mut() {
+            *sample = self.high_shelf_r.process(self.mid2_r.process(self.mid1_r.process(self.low_shelf_r.process(*sample))));
+        }
+    }
+
     fn reset(&mut self) {
         self.low_shelf.reset();
         self.mid1.reset();
@@ -264,6 +611,7 @@ impl Effect for ParametricEQ {
         self.mid1_r.reset();
         self.mid2_r.reset();
         self.high_shelf_r.reset();
+        self.update_coefficients();
     }
 
     fn name(&self) -> &'static str {
@@ -284,11 +632,18 @@ pub struct Compressor {
     pub attack_ms: f32,
     pub release_ms: f32,
     pub makeup_gain_db: f32,
+    /// Sidechain source track (None = detect from own input)
+    /// The mixer processes source tracks before the tracks that key from them
+    pub sidechain_track_id: Option<u64>,
 
     // State
     envelope: f32,           // Current gain reduction envelope
     attack_coeff: f32,
     release_coeff: f32,
+    sidechain_level: f32,    // Source track level for the current frame (set by the mixer)
+    smoothed_makeup: SmoothedParam,
+    /// Oversampled detection and gain (None = off)
+    oversampler: Option<Oversampler>,
 }
 
 impl Default for Compressor {
@@ -305,21 +660,43 @@ impl Compressor {
             attack_ms: 10.0,
             release_ms: 100.0,
             makeup_gain_db: 0.0,
+            sidechain_track_id: None,
             envelope: 1.0,       // Start at no gain reduction
             attack_coeff: 0.0,
             release_coeff: 0.0,
+            sidechain_level: 0.0,
+            smoothed_makeup: SmoothedParam::new(),
+            oversampler: None,
         };
         comp.update_coefficients();
         comp
     }
 
+    /// Set the oversampling factor (1 = off, 2 or 4)
+    pub fn set_oversample(&mut self, factor: u32) -> Result<(), String> {
+        self.oversampler = oversampler_for(factor)?;
+        self.update_coefficients();
+        Ok(())
+    }
+
+    /// Oversampling factor (1 = off)
+    pub fn oversample(&self) -> u32 {
+        self.oversampler.as_ref().map_or(1, |oversampler| oversampler.factor() as u32)
+    }
+
     /// Update attack/release coefficients when parameters change
     pub fn update_coefficients(&mut self) {
-        let sample_rate = TARGET_SAMPLE_RATE as f32;
+        // The envelope runs at the oversampled rate
+        let sample_rate = TARGET_SAMPLE_RATE as f32 * self.oversample() as f32;
         self.attack_coeff = (-1.0 / (self.attack_ms * 0.001 * sample_rate)).exp();
         self.release_coeff = (-1.0 / (self.release_ms * 0.001 * sample_rate)).exp();
     }
 
+    /// Feed the sidechain source's level (linear RMS) for the next frame
+    pub fn set_sidechain_level(&mut self, level: f32) {
+        self.sidechain_level = level;
+    }
+
     /// Calculate gain reduction for a given input level (in linear)
     fn calculate_gain_reduction(&self, input_level: f32) -> f32 {
         if input_level <= 0.0 {
@@ -338,10 +715,15 @@ impl Compressor {
     }
 }
 
-impl Effect for Compressor {
-    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
-        // Calculate RMS level (stereo average)
-        let level = f32::midpoint(left * left, right * right).sqrt();
+impl Compressor {
+    /// Detect the level and apply the gain reduction (no makeup) to one frame
+    fn compress(&mut self, left: f32, right: f32) -> (f32, f32) {
+        // Calculate RMS level (stereo average), or use the sidechain source's level
+        let level = if self.sidechain_track_id.is_some() {
+            self.sidechain_level
+        } else {
+            f32::midpoint(left * left, right * right).sqrt()
+        };
 
         // Calculate target gain reduction
         let target_gain = self.calculate_gain_reduction(level);
@@ -355,15 +737,36 @@ impl Effect for Compressor {
             self.envelope = self.release_coeff * self.envelope + (1.0 - self.release_coeff) * target_gain;
         }
 
-        // Apply gain reduction + makeup gain
-        let makeup_gain = 10_f32.powf(self.makeup_gain_db / 20.0);
-        let total_gain = self.envelope * makeup_gain;
+        (left * self.envelope, right * self.envelope)
+    }
+}
+
+impl Effect for Compressor {
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let (left, right) = if let Some(mut oversampler) = self.oversampler.take() {
+            let out = oversampler.process(left, right, |l, r| self.compress(l, r));
+            self.oversampler = Some(oversampler);
+            out
+        } else {
+            self.compress(left, right)
+        };
 
-        (left * total_gain, right * total_gain)
+        // Apply makeup gain
+        let makeup_gain = 10_f32.powf(self.smoothed_makeup.next(self.makeup_gain_db) / 20.0);
+        (left * makeup_gain, right * makeup_gain)
     }
 
     fn reset(&mut self) {
         self.envelope = 1.0;
+        self.sidechain_level = 0.0;
+        self.smoothed_makeup.reset();
+        if let Some(oversampler) = &mut self.oversampler {
+            oversampler.reset();
+        }
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.oversampler.as_ref().map_or(0, Oversampler::latency_frames)
     }
 
     fn name(&self) -> &'static str {
@@ -375,6 +778,45 @@ impl Effect for Compressor {
 // DELAY
 // ========================================================================
 
+/// Note value modifier for tempo-synced delay times
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoteModifier {
+    #[default]
+    Straight,
+    /// 1.5x the straight note length
+    Dotted,
+    /// 2/3 of the straight note length
+    Triplet,
+}
+
+impl NoteModifier {
+    /// Parse from index (0=straight, 1=dotted, 2=triplet)
+    pub fn from_index(index: u32) -> Option<Self> {
+        match index {
+            0 => Some(Self::Straight),
+            1 => Some(Self::Dotted),
+            2 => Some(Self::Triplet),
+            _ => None,
+        }
+    }
+
+    pub fn index(self) -> u32 {
+        match self {
+            Self::Straight => 0,
+            Self::Dotted => 1,
+            Self::Triplet => 2,
+        }
+    }
+
+    fn length_multiplier(self) -> f64 {
+        match self {
+            Self::Straight => 1.0,
+            Self::Dotted => 1.5,
+            Self::Triplet => 2.0 / 3.0,
+        }
+    }
+}
+
 /// Stereo delay effect
 #[derive(Clone)]
 pub struct Delay {
@@ -382,11 +824,23 @@ pub struct Delay {
     pub delay_time_ms: f32,
     pub feedback: f32,       // 0.0 to 0.99
     pub wet_dry_mix: f32,    // 0.0 = dry, 1.0 = wet
+    /// Alternate echoes between left and right (cross-feedback delay lines)
+    pub ping_pong: bool,
+
+    // Tempo sync: when enabled, `delay_time_ms` follows the project tempo
+    pub sync_enabled: bool,
+    /// Note division (1 = whole, 4 = quarter, 8 = eighth, ...)
+    pub note_division: u32,
+    pub note_modifier: NoteModifier,
+    tempo_bpm: f64,
 
     // Buffers
     buffer_left: Vec<f32>,
     buffer_right: Vec<f32>,
     write_pos: usize,
+
+    smoothed_feedback: SmoothedParam,
+    smoothed_wet: SmoothedParam,
 }
 
 impl Default for Delay {
@@ -396,16 +850,81 @@ impl Default for Delay {
 }
 
 impl Delay {
+    /// Longest delay the buffer holds; longer times (including synced ones at slow
+    /// tempos) are clamped to it
+    pub const MAX_DELAY_MS: f32 = 2000.0;
+
     pub fn new() -> Self {
-        // Max 2 seconds delay
-        let max_samples = (TARGET_SAMPLE_RATE as f32 * 2.0) as usize;
+        let max_samples = (Self::MAX_DELAY_MS * 0.001 * TARGET_SAMPLE_RATE as f32) as usize + 1;
         Self {
             delay_time_ms: 500.0,
             feedback: 0.4,
             wet_dry_mix: 0.3,
+            ping_pong: false,
+            sync_enabled: false,
+            note_division: 4,
+            note_modifier: NoteModifier::Straight,
+            tempo_bpm: 120.0,
             buffer_left: vec![0.0; max_samples],
             buffer_right: vec![0.0; max_samples],
             write_pos: 0,
+            smoothed_feedback: SmoothedParam::new(),
+            smoothed_wet: SmoothedParam::new(),
+        }
+    }
+
+    /// Note divisions accepted by `set_sync` (whole note to 1/64)
+    pub const NOTE_DIVISIONS: [u32; 7] = [1, 2, 4, 8, 16, 32, 64];
+
+    /// Configure tempo sync; synced delay times are recomputed from the current tempo
+    ///
+    /// Fails if the division is longer than `MAX_DELAY_MS` at the current tempo. A later
+    /// tempo change that makes it too long clamps the delay time instead.
+    pub fn set_sync(&mut self, enabled: bool, note_division: u32, modifier: NoteModifier) -> Result<(), String> {
+        if !Self::NOTE_DIVISIONS.contains(&note_division) {
+            return Err(format!("Invalid note division 1/{note_division} (expected 1, 2, 4, 8, 16, 32 or 64)"));
+        }
+        let synced_ms = self.synced_time_ms(note_division, modifier);
+        if enabled && synced_ms > f64::from(Self::MAX_DELAY_MS) {
+            return Err(format!(
+                "Note division lasts {synced_ms:.0}ms at {:.1} BPM, longer than the {:.0}ms delay maximum",
+                self.tempo_bpm,
+                Self::MAX_DELAY_MS
+            ));
+        }
+        self.sync_enabled = enabled;
+        self.note_division = note_division;
+        self.note_modifier = modifier;
+        self.update_synced_time();
+        Ok(())
+    }
+
+    /// Update the project tempo (recomputes the delay time when synced)
+    pub fn set_tempo(&mut self, bpm: f64) {
+        if bpm.is_finite() && bpm > 0.0 {
+            self.tempo_bpm = bpm;
+            self.update_synced_time();
+        }
+    }
+
+    /// Length of a note division at the current tempo, in ms
+    fn synced_time_ms(&self, note_division: u32, modifier: NoteModifier) -> f64 {
+        // A whole note lasts 4 beats
+        let whole_note_ms = 4.0 * 60_000.0 / self.tempo_bpm;
+        whole_note_ms / f64::from(note_division.max(1)) * modifier.length_multiplier()
+    }
+
+    fn update_synced_time(&mut self) {
+        if self.sync_enabled {
+            let ms = self.synced_time_ms(self.note_division, self.note_modifier);
+            self.delay_time_ms = (ms as f32).min(Self::MAX_DELAY_MS);
+        }
+    }
+
+    /// Set the (unsynced) delay time, clamped to `0..=MAX_DELAY_MS`
+    pub fn set_delay_time_ms(&mut self, ms: f32) {
+        if ms.is_finite() {
+            self.delay_time_ms = ms.clamp(0.0, Self::MAX_DELAY_MS);
         }
     }
 
@@ -426,25 +945,72 @@ impl Effect for Delay {
         // Read delayed samples
         let delayed_left = self.buffer_left[read_pos];
         let delayed_right = self.buffer_right[read_pos];
+        let feedback = self.smoothed_feedback.next(self.feedback);
+        let wet = self.smoothed_wet.next(self.wet_dry_mix);
 
-        // Write input + feedback to buffer
-        self.buffer_left[self.write_pos] = left + delayed_left * self.feedback;
-        self.buffer_right[self.write_pos] = right + delayed_right * self.feedback;
+        if self.ping_pong {
+            // Mono input enters the left line; each line feeds the other, so echoes alternate sides
+            self.buffer_left[self.write_pos] = (left + right) * 0.5 + delayed_right * feedback;
+            self.buffer_right[self.write_pos] = delayed_left * feedback;
+        } else {
+            // Write input + feedback to buffer
+            self.buffer_left[self.write_pos] = left + delayed_left * feedback;
+            self.buffer_right[self.write_pos] = right + delayed_right * feedback;
+        }
 
         // Advance write position
         self.write_pos = (self.write_pos + 1) % buffer_size;
 
         // Mix wet/dry
-        let out_left = left * (1.0 - self.wet_dry_mix) + delayed_left * self.wet_dry_mix;
-        let out_right = right * (1.0 - self.wet_dry_mix) + delayed_right * self.wet_dry_mix;
+        let out_left = left * (1.0 - wet) + delayed_left * wet;
+        let out_right = right * (1.0 - wet) + delayed_right * wet;
 
         (out_left, out_right)
     }
 
+    /// Same as `process_frame`, with the delay time read once per block and the buffer
+    /// positions wrapped without a division
+    fn process_block(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let delay_samples = self.get_delay_samples();
+        let buffer_size = self.buffer_left.len();
+        let ping_pong = self.ping_pong;
+        let mut write_pos = self.write_pos;
+        let mut read_pos = (write_pos + buffer_size - delay_samples) % buffer_size;
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let delayed_left = self.buffer_left[read_pos];
+            let delayed_right = self.buffer_right[read_pos];
+            let feedback = self.smoothed_feedback.next(self.feedback);
+            let wet = self.smoothed_wet.next(self.wet_dry_mix);
+
+            if ping_pong {
+                self.buffer_left[write_pos] = (*l + *r) * 0.5 + delayed_right * feedback;
+                self.buffer_right[write_pos] = delayed_left * feedback;
+            } else {
+                self.buffer_left[write_pos] = *l + delayed_left * feedback;
+                self.buffer_right[write_pos] = *r + delayed_right * feedback;
+            }
+            write_pos += 1;
+            if write_pos == buffer_size {
+                write_pos = 0;
+            }
+            read_pos += 1;
+            if read_pos == buffer_size {
+                read_pos = 0;
+            }
+
+            *l = *l * (1.0 - wet) + delayed_left * wet;
+            *r = *r * (1.0 - wet) + delayed_right * wet;
+        }
+        self.write_pos = write_pos;
+    }
+
     fn reset(&mut self) {
         self.buffer_left.fill(0.0);
         self.buffer_right.fill(0.0);
         self.write_pos = 0;
+        self.smoothed_feedback.reset();
+        self.smoothed_wet.reset();
     }
 
     fn name(&self) -> &'static str {
@@ -463,6 +1029,8 @@ pub struct Reverb {
     pub room_size: f32,      // 0.0 to 1.0
     pub damping: f32,        // 0.0 to 1.0
     pub wet_dry_mix: f32,    // 0.0 = dry, 1.0 = wet
+    /// Sustain the current tail indefinitely and stop feeding new input into it
+    pub freeze: bool,
 
     // Comb filters (8 per channel for stereo)
     comb_buffers_l: Vec<Vec<f32>>,
@@ -477,6 +1045,8 @@ pub struct Reverb {
     allpass_buffers_r: Vec<Vec<f32>>,
     allpass_positions_l: Vec<usize>,
     allpass_positions_r: Vec<usize>,
+
+    smoothed_wet: SmoothedParam,
 }
 
 impl Default for Reverb {
@@ -517,6 +1087,7 @@ impl Reverb {
             room_size: 0.5,
             damping: 0.5,
             wet_dry_mix: 0.3,
+            freeze: false,
             comb_buffers_l,
             comb_buffers_r,
             comb_positions_l: vec![0; 8],
@@ -527,6 +1098,7 @@ impl Reverb {
             allpass_buffers_r,
             allpass_positions_l: vec![0; 4],
             allpass_positions_r: vec![0; 4],
+            smoothed_wet: SmoothedParam::new(),
         }
     }
 
@@ -561,12 +1133,62 @@ impl Reverb {
 
         delayed - input * 0.5
     }
+
+    /// Cheap approximation of `process_frame` for preview renders: every other comb
+    /// filter, scaled by sqrt(2) to keep the level of the (uncorrelated) full bank,
+    /// so the tail keeps its length and level but loses density
+    pub fn process_frame_preview(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let (mono_input, room_size, damping) = if self.freeze {
+            (0.0, 1.0, 1.0)
+        } else {
+            ((left + right) * 0.5, self.room_size, self.damping)
+        };
+
+        let mut comb_out_l = 0.0;
+        let mut comb_out_r = 0.0;
+        for i in (0..8).step_by(2) {
+            comb_out_l += Self::process_comb(
+                mono_input,
+                room_size,
+                damping,
+                &mut self.comb_buffers_l[i],
+                &mut self.comb_positions_l[i],
+                &mut self.comb_filter_state_l[i],
+            );
+            comb_out_r += Self::process_comb(
+                mono_input,
+                room_size,
+                damping,
+                &mut self.comb_buffers_r[i],
+                &mut self.comb_positions_r[i],
+                &mut self.comb_filter_state_r[i],
+            );
+        }
+
+        let mut out_l = comb_out_l * std::f32::consts::SQRT_2;
+        let mut out_r = comb_out_r * std::f32::consts::SQRT_2;
+        for i in 0..4 {
+            out_l = Self::process_allpass(out_l, &mut self.allpass_buffers_l[i], &mut self.allpass_positions_l[i]);
+            out_r = Self::process_allpass(out_r, &mut self.allpass_buffers_r[i], &mut self.allpass_positions_r[i]);
+        }
+
+        let wet = self.smoothed_wet.next(self.wet_dry_mix);
+        let final_left = left * (1.0 - wet) + out_l * wet * 0.015;
+        let final_right = right * (1.0 - wet) + out_r * wet * 0.015;
+
+        (final_left, final_right)
+    }
 }
 
 impl Effect for Reverb {
     fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
-        // Mix to mono for input
-        let mono_input = (left + right) * 0.5;
+        // Frozen: lossless comb feedback (no damping) and no new input
+        let (mono_input, room_size, damping) = if self.freeze {
+            (0.0, 1.0, 1.0)
+        } else {
+            // Mix to mono for input
+            ((left + right) * 0.5, self.room_size, self.damping)
+        };
 
         // Process comb filters (parallel) - separate positions for L and R
         let mut comb_out_l = 0.0;
@@ -574,16 +1196,16 @@ impl Effect for Reverb {
         for i in 0..8 {
             comb_out_l += Self::process_comb(
                 mono_input,
-                self.room_size,
-                self.damping,
+                room_size,
+                damping,
                 &mut self.comb_buffers_l[i],
                 &mut self.comb_positions_l[i],
                 &mut self.comb_filter_state_l[i],
             );
             comb_out_r += Self::process_comb(
                 mono_input,
-                self.room_size,
-                self.damping,
+                room_size,
+                damping,
                 &mut self.comb_buffers_r[i],
                 &mut self.comb_positions_r[i],
                 &mut self.comb_filter_state_r[i],
@@ -607,12 +1229,52 @@ impl Effect for Reverb {
         }
 
         // Mix wet/dry
-        let final_left = left * (1.0 - self.wet_dry_mix) + out_l * self.wet_dry_mix * 0.015;
-        let final_right = right * (1.0 - self.wet_dry_mix) + out_r * self.wet_dry_mix * 0.015;
+        let wet = self.smoothed_wet.next(self.wet_dry_mix);
+        let final_left = left * (1.0 - wet) + out_l * wet * 0.015;
+        let final_right = right * (1.0 - wet) + out_r * wet * 0.015;
 
         (final_left, final_right)
     }
 
+    /// Same as `process_frame` with the room parameters read once per block
+    fn process_block(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let freeze = self.freeze;
+        let (room_size, damping) = if freeze { (1.0, 1.0) } else { (self.room_size, self.damping) };
+
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let mono_input = if freeze { 0.0 } else { (*l + *r) * 0.5 };
+
+            let mut out_l = 0.0;
+            let mut out_r = 0.0;
+            for i in 0..8 {
+                out_l += Self::process_comb(
+                    mono_input,
+                    room_size,
+                    damping,
+                    &mut self.comb_buffers_l[i],
+                    &mut self.comb_positions_l[i],
+                    &mut self.comb_filter_state_l[i],
+                );
+                out_r += Self::process_comb(
+                    mono_input,
+                    room_size,
+                    damping,
+                    &mut self.comb_buffers_r[i],
+                    &mut self.comb_positions_r[i],
+                    &mut self.comb_filter_state_r[i],
+                );
+            }
+            for i in 0..4 {
+                out_l = Self::process_allpass(out_l, &mut self.allpass_buffers_l[i], &mut self.allpass_positions_l[i]);
+                out_r = Self::process_allpass(out_r, &mut self.allpass_buffers_r[i], &mut self.allpass_positions_r[i]);
+            }
+
+            let wet = self.smoothed_wet.next(self.wet_dry_mix);
+            *l = *l * (1.0 - wet) + out_l * wet * 0.015;
+            *r = *r * (1.0 - wet) + out_r * wet * 0.015;
+        }
+    }
+
     fn reset(&mut self) {
         for buffer in &mut self.comb_buffers_l {
             buffer.fill(0.0);
@@ -632,6 +1294,7 @@ impl Effect for Reverb {
         self.comb_filter_state_r.fill(0.0);
         self.allpass_positions_l.fill(0);
         self.allpass_positions_r.fill(0);
+        self.smoothed_wet.reset();
     }
 
     fn name(&self) -> &'static str {
@@ -643,15 +1306,43 @@ impl Effect for Reverb {
 // LIMITER
 // ========================================================================
 
+/// Longest limiter lookahead (ms)
+pub const MAX_LIMITER_LOOKAHEAD_MS: f32 = 10.0;
+/// Default limiter lookahead (ms): enough to ramp into a drum hit without a click
+pub const DEFAULT_LIMITER_LOOKAHEAD_MS: f32 = 1.0;
+
 /// Brick-wall limiter (for master track)
+///
+/// The audio is delayed by the lookahead while the gain computer sees the undelayed
+/// signal, so the gain has already ramped down when a peak leaves the delay line and
+/// fast transients stay under the ceiling. The lookahead is reported as latency.
 #[derive(Clone)]
 pub struct Limiter {
     pub threshold_db: f32,
     pub release_ms: f32,
+    pub lookahead_ms: f32,
 
-    envelope_left: f32,
-    envelope_right: f32,
+    /// Peak envelope (held over the lookahead window, then released)
+    envelope: f32,
     release_coeff: f32,
+    /// Gain applied to the most recent frame (1.0 = no limiting)
+    current_gain: f32,
+    /// Oversampled peak detection and gain (None = off)
+    oversampler: Option<Oversampler>,
+
+    /// Lookahead at the processing (oversampled) rate, in frames
+    lookahead_frames: usize,
+    /// Audio delay lines (sized for the longest lookahead at 4× oversampling)
+    delay_left: Vec<f32>,
+    delay_right: Vec<f32>,
+    delay_pos: usize,
+    /// Sliding-window peak maximum: (frame index, peak), decreasing peaks
+    peak_window: VecDeque<(u64, f32)>,
+    frame_index: u64,
+    /// Moving average of the last `lookahead_frames` gains (ramps into the reduction)
+    gain_history: Vec<f32>,
+    gain_history_pos: usize,
+    gain_sum: f64,
 }
 
 impl Default for Limiter {
@@ -662,65 +1353,173 @@ impl Default for Limiter {
 
 impl Limiter {
     pub fn new() -> Self {
+        let max_frames = Self::max_lookahead_frames();
         let mut limiter = Self {
             threshold_db: -0.1, // Just below 0 dBFS
             release_ms: 50.0,
-            envelope_left: 0.0,
-            envelope_right: 0.0,
+            lookahead_ms: DEFAULT_LIMITER_LOOKAHEAD_MS,
+            envelope: 0.0,
             release_coeff: 0.0,
+            current_gain: 1.0,
+            oversampler: None,
+            lookahead_frames: 0,
+            delay_left: vec![0.0; max_frames + 1],
+            delay_right: vec![0.0; max_frames + 1],
+            delay_pos: 0,
+            peak_window: VecDeque::with_capacity(max_frames + 1),
+            frame_index: 0,
+            gain_history: vec![1.0; max_frames],
+            gain_history_pos: 0,
+            gain_sum: 0.0,
         };
         limiter.update_coefficients();
         limiter
     }
 
-    pub fn update_coefficients(&mut self) {
-        let sample_rate = TARGET_SAMPLE_RATE as f32;
-        self.release_coeff = (-1.0 / (self.release_ms * 0.001 * sample_rate)).exp();
+    fn max_lookahead_frames() -> usize {
+        let max_factor = OVERSAMPLE_FACTORS[OVERSAMPLE_FACTORS.len() - 1] as usize;
+        (MAX_LIMITER_LOOKAHEAD_MS * 0.001 * TARGET_SAMPLE_RATE as f32).round() as usize * max_factor
     }
-}
 
-impl Effect for Limiter {
-    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
-        let threshold_linear = 10_f32.powf(self.threshold_db / 20.0);
+    /// Set the ceiling in dBFS (clamped to -30..0)
+    pub fn set_threshold_db(&mut self, threshold_db: f32) {
+        self.threshold_db = threshold_db.clamp(-30.0, 0.0);
+    }
 
-        // Track peaks with release
-        let left_abs = left.abs();
-        let right_abs = right.abs();
+    /// Set the release time in milliseconds (clamped to 1..1000)
+    pub fn set_release_ms(&mut self, release_ms: f32) {
+        self.release_ms = release_ms.clamp(1.0, 1000.0);
+        self.update_coefficients();
+    }
 
-        if left_abs > self.envelope_left {
-            self.envelope_left = left_abs;
-        } else {
-            self.envelope_left *= self.release_coeff;
-        }
+    /// Set the lookahead in milliseconds (clamped to 0..10; 0 = react instantly, no latency)
+    /// Changing it clears the delay line, so it isn't meant to be automated.
+    pub fn set_lookahead_ms(&mut self, lookahead_ms: f32) {
+        self.lookahead_ms = lookahead_ms.clamp(0.0, MAX_LIMITER_LOOKAHEAD_MS);
+        self.update_coefficients();
+    }
 
-        if right_abs > self.envelope_right {
-            self.envelope_right = right_abs;
+    /// Current gain reduction in dB (positive, 0.0 = not limiting)
+    pub fn get_gain_reduction_db(&self) -> f32 {
+        if self.current_gain >= 1.0 {
+            0.0
         } else {
-            self.envelope_right *= self.release_coeff;
+            -20.0 * self.current_gain.max(1e-6).log10()
         }
+    }
 
-        // Calculate gain reduction
-        let gain_left = if self.envelope_left > threshold_linear {
-            threshold_linear / self.envelope_left
-        } else {
-            1.0
-        };
+    /// Set the oversampling factor (1 = off, 2 or 4); catches inter-sample peaks too
+    pub fn set_oversample(&mut self, factor: u32) -> Result<(), String> {
+        self.oversampler = oversampler_for(factor)?;
+        self.update_coefficients();
+        Ok(())
+    }
 
-        let gain_right = if self.envelope_right > threshold_linear {
-            threshold_linear / self.envelope_right
-        } else {
-            1.0
-        };
+    /// Oversampling factor (1 = off)
+    pub fn oversample(&self) -> u32 {
+        self.oversampler.as_ref().map_or(1, |oversampler| oversampler.factor() as u32)
+    }
+
+    /// Lookahead in frames at the engine rate
+    fn lookahead_base_frames(&self) -> usize {
+        (self.lookahead_ms.clamp(0.0, MAX_LIMITER_LOOKAHEAD_MS) * 0.001 * TARGET_SAMPLE_RATE as f32).round() as usize
+    }
 
-        // Use minimum gain for both channels (link)
-        let gain = gain_left.min(gain_right);
+    pub fn update_coefficients(&mut self) {
+        // The envelope runs at the oversampled rate
+        let factor = self.oversample();
+        let sample_rate = TARGET_SAMPLE_RATE as f32 * factor as f32;
+        self.release_coeff = (-1.0 / (self.release_ms * 0.001 * sample_rate)).exp();
 
-        (left * gain, right * gain)
+        let lookahead_frames = self.lookahead_base_frames() * factor as usize;
+        if lookahead_frames != self.lookahead_frames {
+            self.lookahead_frames = lookahead_frames;
+            self.clear_lookahead();
+        }
     }
 
-    fn reset(&mut self) {
-        self.envelope_left = 0.0;
-        self.envelope_right = 0.0;
+    /// Empty the delay line and gain computer
+    fn clear_lookahead(&mut self) {
+        self.delay_left.fill(0.0);
+        self.delay_right.fill(0.0);
+        self.delay_pos = 0;
+        self.peak_window.clear();
+        self.gain_history.fill(1.0);
+        self.gain_history_pos = 0;
+        self.gain_sum = self.lookahead_frames as f64;
+    }
+
+    /// Feed one frame to the gain computer and return the delayed frame with its gain
+    fn limit(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let threshold_linear = 10_f32.powf(self.threshold_db / 20.0);
+        let lookahead = self.lookahead_frames;
+
+        // Loudest linked peak of the frames still in the delay line (including this one)
+        let peak = left.abs().max(right.abs());
+        while self.peak_window.back().is_some_and(|&(_, held)| held <= peak) {
+            self.peak_window.pop_back();
+        }
+        self.peak_window.push_back((self.frame_index, peak));
+        while self.peak_window.front().is_some_and(|&(index, _)| index + (lookahead as u64) < self.frame_index) {
+            self.peak_window.pop_front();
+        }
+        self.frame_index += 1;
+        let window_peak = self.peak_window.front().map_or(0.0, |&(_, held)| held);
+
+        // Instant attack, release from the held peak
+        self.envelope = window_peak.max(self.envelope * self.release_coeff);
+        let target_gain = if self.envelope > threshold_linear {
+            threshold_linear / self.envelope
+        } else {
+            1.0
+        };
+
+        // Average the gain over the lookahead so it ramps down ahead of a peak; every
+        // gain in the average already holds that peak, so it's reached in time
+        let gain = if lookahead == 0 {
+            target_gain
+        } else {
+            let oldest = std::mem::replace(&mut self.gain_history[self.gain_history_pos], target_gain);
+            self.gain_history_pos = (self.gain_history_pos + 1) % lookahead;
+            self.gain_sum += f64::from(target_gain) - f64::from(oldest);
+            ((self.gain_sum / lookahead as f64) as f32).min(1.0)
+        };
+        self.current_gain = gain;
+
+        // Delay the audio by the lookahead
+        let len = self.delay_left.len();
+        self.delay_left[self.delay_pos] = left;
+        self.delay_right[self.delay_pos] = right;
+        let read_pos = (self.delay_pos + len - lookahead) % len;
+        let (delayed_left, delayed_right) = (self.delay_left[read_pos], self.delay_right[read_pos]);
+        self.delay_pos = (self.delay_pos + 1) % len;
+
+        (delayed_left * gain, delayed_right * gain)
+    }
+}
+
+impl Effect for Limiter {
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        if let Some(mut oversampler) = self.oversampler.take() {
+            let out = oversampler.process(left, right, |l, r| self.limit(l, r));
+            self.oversampler = Some(oversampler);
+            out
+        } else {
+            self.limit(left, right)
+        }
+    }
+
+    fn reset(&mut self) {
+        self.envelope = 0.0;
+        self.current_gain = 1.0;
+        self.clear_lookahead();
+        if let Some(oversampler) = &mut self.oversampler {
+            oversampler.reset();
+        }
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.lookahead_base_frames() + self.oversampler.as_ref().map_or(0, Oversampler::latency_frames)
     }
 
     fn name(&self) -> &'static str {
@@ -746,6 +1545,8 @@ pub struct Chorus {
 
     // LFO
     lfo_phase: f32,
+
+    smoothed_wet: SmoothedParam,
 }
 
 impl Default for Chorus {
@@ -766,6 +1567,7 @@ impl Chorus {
             buffer_right: vec![0.0; max_samples],
             write_pos: 0,
             lfo_phase: 0.0,
+            smoothed_wet: SmoothedParam::new(),
         }
     }
 }
@@ -799,8 +1601,9 @@ impl Effect for Chorus {
         self.write_pos = (self.write_pos + 1) % buffer_size;
 
         // Mix
-        let out_left = left * (1.0 - self.wet_dry_mix) + delayed_left * self.wet_dry_mix;
-        let out_right = right * (1.0 - self.wet_dry_mix) + delayed_right * self.wet_dry_mix;
+        let wet = self.smoothed_wet.next(self.wet_dry_mix);
+        let out_left = left * (1.0 - wet) + delayed_left * wet;
+        let out_right = right * (1.0 - wet) + delayed_right * wet;
 
         (out_left, out_right)
     }
@@ -810,6 +1613,7 @@ impl Effect for Chorus {
         self.buffer_right.fill(0.0);
         self.write_pos = 0;
         self.lfo_phase = 0.0;
+        self.smoothed_wet.reset();
     }
 
     fn name(&self) -> &'static str {
@@ -817,6 +1621,432 @@ impl Effect for Chorus {
     }
 }
 
+// ========================================================================
+// BIT CRUSHER
+// ========================================================================
+
+/// Bit crusher (bit-depth quantization + sample-and-hold downsampling)
+#[derive(Clone)]
+pub struct BitCrusher {
+    pub bit_depth: f32,             // 1.0 to 16.0
+    pub sample_rate_reduction: f32, // Downsample factor, 1.0 to 50.0
+    pub wet_dry_mix: f32,
+
+    // Sample-and-hold state
+    held_left: f32,
+    held_right: f32,
+    hold_counter: f32,
+
+    smoothed_wet: SmoothedParam,
+}
+
+impl Default for BitCrusher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BitCrusher {
+    pub fn new() -> Self {
+        Self {
+            bit_depth: 8.0,
+            sample_rate_reduction: 1.0,
+            wet_dry_mix: 1.0,
+            held_left: 0.0,
+            held_right: 0.0,
+            hold_counter: 0.0,
+            smoothed_wet: SmoothedParam::new(),
+        }
+    }
+
+    fn quantize(&self, sample: f32) -> f32 {
+        let bits = self.bit_depth.clamp(1.0, 16.0);
+        let steps = 2_f32.powf(bits - 1.0);
+        (sample * steps).round() / steps
+    }
+}
+
+impl Effect for BitCrusher {
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        // Grab a new sample every `sample_rate_reduction` frames, hold it in between
+        if self.hold_counter <= 0.0 {
+            self.held_left = self.quantize(left);
+            self.held_right = self.quantize(right);
+            self.hold_counter += self.sample_rate_reduction.clamp(1.0, 50.0);
+        }
+        self.hold_counter -= 1.0;
+
+        // Mix
+        let wet = self.smoothed_wet.next(self.wet_dry_mix);
+        let out_left = left * (1.0 - wet) + self.held_left * wet;
+        let out_right = right * (1.0 - wet) + self.held_right * wet;
+
+        (out_left, out_right)
+    }
+
+    fn reset(&mut self) {
+        self.held_left = 0.0;
+        self.held_right = 0.0;
+        self.hold_counter = 0.0;
+        self.smoothed_wet.reset();
+    }
+
+    fn name(&self) -> &'static str {
+        "BitCrusher"
+    }
+}
+
+// ========================================================================
+// NOISE GATE
+// ========================================================================
+
+/// Per-channel noise gate state
+#[derive(Clone, Copy, Default)]
+struct GateChannel {
+    envelope: f32,       // Envelope follower (linear peak)
+    gain: f32,           // Current gate gain (0.0 = closed, 1.0 = open)
+    hold_remaining: u32, // Samples left before the gate may start closing
+}
+
+impl GateChannel {
+    fn process(&mut self, input: f32, threshold_linear: f32, gate: &GateCoefficients) -> f32 {
+        // Peak envelope follower
+        let level = input.abs();
+        self.envelope = if level > self.envelope {
+            level
+        } else {
+            self.envelope * gate.detector
+        };
+
+        // Open above threshold, stay open through the hold time, then close
+        let target = if self.envelope >= threshold_linear {
+            self.hold_remaining = gate.hold_samples;
+            1.0
+        } else if self.hold_remaining > 0 {
+            self.hold_remaining -= 1;
+            1.0
+        } else {
+            0.0
+        };
+
+        // Smooth gain toward target with attack (opening) / release (closing)
+        let coeff = if target > self.gain { gate.attack } else { gate.release };
+        self.gain = coeff * self.gain + (1.0 - coeff) * target;
+
+        input * self.gain
+    }
+}
+
+/// Smoothing coefficients derived from the gate's time parameters
+#[derive(Clone, Copy, Default)]
+struct GateCoefficients {
+    attack: f32,
+    release: f32,
+    detector: f32,
+    hold_samples: u32,
+}
+
+/// Noise gate (attenuates signal below threshold with smooth open/close)
+#[derive(Clone)]
+pub struct NoiseGate {
+    // Parameters
+    pub threshold_db: f32,
+    pub attack_ms: f32,
+    pub hold_ms: f32,
+    pub release_ms: f32,
+
+    // State
+    left: GateChannel,
+    right: GateChannel,
+    coeffs: GateCoefficients,
+}
+
+impl Default for NoiseGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NoiseGate {
+    pub fn new() -> Self {
+        let mut gate = Self {
+            threshold_db: -40.0,
+            attack_ms: 1.0,
+            hold_ms: 50.0,
+            release_ms: 100.0,
+            left: GateChannel::default(),
+            right: GateChannel::default(),
+            coeffs: GateCoefficients::default(),
+        };
+        gate.update_coefficients();
+        gate
+    }
+
+    /// Update attack/hold/release coefficients when parameters change
+    pub fn update_coefficients(&mut self) {
+        let sample_rate = TARGET_SAMPLE_RATE as f32;
+        self.coeffs = GateCoefficients {
+            attack: (-1.0 / (self.attack_ms.max(0.01) * 0.001 * sample_rate)).exp(),
+            release: (-1.0 / (self.release_ms.max(0.01) * 0.001 * sample_rate)).exp(),
+            // Envelope follower decays over ~10ms so it rides over individual waveform cycles
+            detector: (-1.0 / (0.010 * sample_rate)).exp(),
+            hold_samples: (self.hold_ms.max(0.0) * 0.001 * sample_rate) as u32,
+        };
+    }
+}
+
+impl Effect for NoiseGate {
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let threshold_linear = 10_f32.powf(self.threshold_db / 20.0);
+
+        (
+            self.left.process(left, threshold_linear, &self.coeffs),
+            self.right.process(right, threshold_linear, &self.coeffs),
+        )
+    }
+
+    fn reset(&mut self) {
+        self.left = GateChannel::default();
+        self.right = GateChannel::default();
+    }
+
+    fn name(&self) -> &'static str {
+        "NoiseGate"
+    }
+}
+
+// ========================================================================
+// FILTER
+// ========================================================================
+
+/// Filter response for the `Filter` effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterType {
+    #[default]
+    LowPass,
+    HighPass,
+    BandPass,
+}
+
+impl FilterType {
+    /// Parse from index (0=low-pass, 1=high-pass, 2=band-pass)
+    pub fn from_index(index: u32) -> Option<Self> {
+        match index {
+            0 => Some(Self::LowPass),
+            1 => Some(Self::HighPass),
+            2 => Some(Self::BandPass),
+            _ => None,
+        }
+    }
+
+    pub fn index(self) -> u32 {
+        match self {
+            Self::LowPass => 0,
+            Self::HighPass => 1,
+            Self::BandPass => 2,
+        }
+    }
+
+    fn biquad_type(self) -> BiquadType {
+        match self {
+            Self::LowPass => BiquadType::LowPass,
+            Self::HighPass => BiquadType::HighPass,
+            Self::BandPass => BiquadType::BandPass,
+        }
+    }
+}
+
+/// Resonant filter (12 dB/oct biquad)
+#[derive(Clone)]
+pub struct Filter {
+    pub filter_type: FilterType,
+    pub cutoff_hz: f32,  // 20 Hz to just below Nyquist
+    pub resonance: f32,  // Q: 0.1 to 20.0 (0.707 = no resonant peak)
+
+    left: BiquadFilter,
+    right: BiquadFilter,
+    smoothed_cutoff: SmoothedParam,
+    smoothed_resonance: SmoothedParam,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        let mut filter = Self {
+            filter_type: FilterType::LowPass,
+            cutoff_hz: 1000.0,
+            resonance: std::f32::consts::FRAC_1_SQRT_2,
+            left: BiquadFilter::new(),
+            right: BiquadFilter::new(),
+            smoothed_cutoff: SmoothedParam::new(),
+            smoothed_resonance: SmoothedParam::new(),
+        };
+        filter.update_coefficients();
+        filter
+    }
+
+    /// Keep cutoff and resonance in range
+    pub fn clamp_parameters(&mut self) {
+        let nyquist = TARGET_SAMPLE_RATE as f32 * 0.5;
+        self.cutoff_hz = self.cutoff_hz.clamp(20.0, nyquist * 0.95);
+        self.resonance = self.resonance.clamp(0.1, 20.0);
+    }
+
+    /// Apply the parameters immediately (no smoothing), e.g. after loading a project
+    pub fn update_coefficients(&mut self) {
+        self.clamp_parameters();
+        self.smoothed_cutoff.snap(self.cutoff_hz);
+        self.smoothed_resonance.snap(self.resonance);
+        self.design(self.cutoff_hz, self.resonance);
+    }
+
+    fn design(&mut self, cutoff_hz: f32, resonance: f32) {
+        let biquad_type = self.filter_type.biquad_type();
+        self.left.design(biquad_type, cutoff_hz, 0.0, resonance);
+        self.right.design(biquad_type, cutoff_hz, 0.0, resonance);
+    }
+}
+
+impl Effect for Filter {
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        // Glide cutoff and resonance toward their settings
+        if self.smoothed_cutoff.is_moving(self.cutoff_hz) || self.smoothed_resonance.is_moving(self.resonance) {
+            let cutoff_hz = self.smoothed_cutoff.next(self.cutoff_hz);
+            let resonance = self.smoothed_resonance.next(self.resonance);
+            self.design(cutoff_hz, resonance);
+        }
+        (self.left.process(left), self.right.process(right))
+    }
+
+    fn reset(&mut self) {
+        self.left.reset();
+        self.right.reset();
+        self.update_coefficients();
+    }
+
+    fn name(&self) -> &'static str {
+        "Filter"
+    }
+}
+
+// ========================================================================
+// GAIN (utility)
+// ========================================================================
+
+/// DC blocker cutoff frequency
+const DC_BLOCK_HZ: f32 = 5.0;
+
+/// Utility gain: trim, per-channel polarity invert and DC offset removal
+#[derive(Clone)]
+pub struct Gain {
+    pub gain_db: f32,
+    pub invert_left: bool,
+    pub invert_right: bool,
+    /// One-pole high-pass at ~5 Hz
+    pub dc_block: bool,
+
+    // DC blocker state per channel: (previous input, previous output)
+    dc_left: (f32, f32),
+    dc_right: (f32, f32),
+    smoothed_gain_db: SmoothedParam,
+}
+
+impl Default for Gain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Gain {
+    pub fn new() -> Self {
+        Self {
+            gain_db: 0.0,
+            invert_left: false,
+            invert_right: false,
+            dc_block: false,
+            dc_left: (0.0, 0.0),
+            dc_right: (0.0, 0.0),
+            smoothed_gain_db: SmoothedParam::new(),
+        }
+    }
+
+    fn dc_block_sample(state: &mut (f32, f32), input: f32) -> f32 {
+        let r = (-2.0 * PI * DC_BLOCK_HZ / TARGET_SAMPLE_RATE as f32).exp();
+        let output = input - state.0 + r * state.1;
+        *state = (input, output);
+        output
+    }
+}
+
+impl Effect for Gain {
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let gain = 10_f32.powf(self.smoothed_gain_db.next(self.gain_db) / 20.0);
+        let mut out_left = if self.invert_left { -left } else { left } * gain;
+        let mut out_right = if self.invert_right { -right } else { right } * gain;
+
+        if self.dc_block {
+            out_left = Self::dc_block_sample(&mut self.dc_left, out_left);
+            out_right = Self::dc_block_sample(&mut self.dc_right, out_right);
+        }
+
+        (out_left, out_right)
+    }
+
+    fn reset(&mut self) {
+        self.dc_left = (0.0, 0.0);
+        self.dc_right = (0.0, 0.0);
+        self.smoothed_gain_db.reset();
+    }
+
+    fn name(&self) -> &'static str {
+        "Gain"
+    }
+}
+
+// ========================================================================
+// STEREO WIDTH
+// ========================================================================
+
+/// Stereo width control via mid/side processing
+#[derive(Clone)]
+pub struct StereoWidth {
+    pub width: f32, // 0.0 = mono, 1.0 = unchanged, 2.0 = exaggerated
+    smoothed_width: SmoothedParam,
+}
+
+impl Default for StereoWidth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StereoWidth {
+    pub fn new() -> Self {
+        Self { width: 1.0, smoothed_width: SmoothedParam::new() }
+    }
+}
+
+impl Effect for StereoWidth {
+    fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let mid = (left + right) * 0.5;
+        let side = (left - right) * 0.5 * self.smoothed_width.next(self.width);
+        (mid + side, mid - side)
+    }
+
+    fn reset(&mut self) {
+        self.smoothed_width.reset();
+    }
+
+    fn name(&self) -> &'static str {
+        "StereoWidth"
+    }
+}
+
 // ========================================================================
 // EFFECT CONTAINER
 // ========================================================================
@@ -830,6 +2060,11 @@ pub enum EffectType {
     Delay(Delay),
     Limiter(Limiter),
     Chorus(Chorus),
+    BitCrusher(BitCrusher),
+    NoiseGate(NoiseGate),
+    StereoWidth(StereoWidth),
+    Filter(Filter),
+    Gain(Gain),
     #[cfg(all(feature = "vst3", not(target_os = "ios")))]
     VST3(crate::vst3_host::VST3Effect),  // M7: VST3 plugin support (desktop only)
 }
@@ -843,11 +2078,54 @@ impl EffectType {
             EffectType::Delay(fx) => fx.process_frame(left, right),
             EffectType::Limiter(fx) => fx.process_frame(left, right),
             EffectType::Chorus(fx) => fx.process_frame(left, right),
+            EffectType::BitCrusher(fx) => fx.process_frame(left, right),
+            EffectType::NoiseGate(fx) => fx.process_frame(left, right),
+            EffectType::StereoWidth(fx) => fx.process_frame(left, right),
+            EffectType::Filter(fx) => fx.process_frame(left, right),
+            EffectType::Gain(fx) => fx.process_frame(left, right),
             #[cfg(all(feature = "vst3", not(target_os = "ios")))]
             EffectType::VST3(fx) => fx.process_frame(left, right),
         }
     }
 
+    /// Process a block of stereo frames in place (see `Effect::process_block`)
+    pub fn process_block(&mut self, left: &mut [f32], right: &mut [f32]) {
+        match self {
+            EffectType::EQ(fx) => fx.process_block(left, right),
+            EffectType::Compressor(fx) => fx.process_block(left, right),
+            EffectType::Reverb(fx) => fx.process_block(left, right),
+            EffectType::Delay(fx) => fx.process_block(left, right),
+            EffectType::Limiter(fx) => fx.process_block(left, right),
+            EffectType::Chorus(fx) => fx.process_block(left, right),
+            EffectType::BitCrusher(fx) => fx.process_block(left, right),
+            EffectType::NoiseGate(fx) => fx.process_block(left, right),
+            EffectType::StereoWidth(fx) => fx.process_block(left, right),
+            EffectType::Filter(fx) => fx.process_block(left, right),
+            EffectType::Gain(fx) => fx.process_block(left, right),
+            #[cfg(all(feature = "vst3", not(target_os = "ios")))]
+            EffectType::VST3(fx) => fx.process_block(left, right),
+        }
+    }
+
+    /// True for plugin instruments (they generate audio from MIDI rather than process it)
+    pub fn is_instrument(&self) -> bool {
+        match self {
+            #[cfg(all(feature = "vst3", not(target_os = "ios")))]
+            EffectType::VST3(fx) => fx.is_instrument,
+            _ => false,
+        }
+    }
+
+    /// Cheap approximation of `process_frame` for preview renders.
+    /// Reverb runs a lighter network (see `Reverb::process_frame_preview`); other
+    /// effects process normally.
+    pub fn process_frame_preview(&mut self, left: f32, right: f32) -> (f32, f32) {
+        match self {
+            EffectType::Reverb(fx) => fx.process_frame_preview(left, right),
+            _ => self.process_frame(left, right),
+        }
+    }
+
     pub fn reset(&mut self) {
         match self {
             EffectType::EQ(fx) => fx.reset(),
@@ -856,11 +2134,55 @@ impl EffectType {
             EffectType::Delay(fx) => fx.reset(),
             EffectType::Limiter(fx) => fx.reset(),
             EffectType::Chorus(fx) => fx.reset(),
+            EffectType::BitCrusher(fx) => fx.reset(),
+            EffectType::NoiseGate(fx) => fx.reset(),
+            EffectType::StereoWidth(fx) => fx.reset(),
+            EffectType::Filter(fx) => fx.reset(),
+            EffectType::Gain(fx) => fx.reset(),
             #[cfg(all(feature = "vst3", not(target_os = "ios")))]
             EffectType::VST3(fx) => fx.reset(),
         }
     }
 
+    /// Output delay in frames at the engine rate (see `Effect::latency_samples`)
+    pub fn latency_samples(&self) -> usize {
+        match self {
+            EffectType::EQ(fx) => fx.latency_samples(),
+            EffectType::Compressor(fx) => fx.latency_samples(),
+            EffectType::Reverb(fx) => fx.latency_samples(),
+            EffectType::Delay(fx) => fx.latency_samples(),
+            EffectType::Limiter(fx) => fx.latency_samples(),
+            EffectType::Chorus(fx) => fx.latency_samples(),
+            EffectType::BitCrusher(fx) => fx.latency_samples(),
+            EffectType::NoiseGate(fx) => fx.latency_samples(),
+            EffectType::StereoWidth(fx) => fx.latency_samples(),
+            EffectType::Filter(fx) => fx.latency_samples(),
+            EffectType::Gain(fx) => fx.latency_samples(),
+            #[cfg(all(feature = "vst3", not(target_os = "ios")))]
+            EffectType::VST3(fx) => fx.latency_samples(),
+        }
+    }
+
+    /// Copy of the effect with its current state (None for a VST3 plugin, which
+    /// can't be copied)
+    pub fn try_clone(&self) -> Option<Self> {
+        match self {
+            EffectType::EQ(fx) => Some(EffectType::EQ(fx.clone())),
+            EffectType::Compressor(fx) => Some(EffectType::Compressor(fx.clone())),
+            EffectType::Reverb(fx) => Some(EffectType::Reverb(fx.clone())),
+            EffectType::Delay(fx) => Some(EffectType::Delay(fx.clone())),
+            EffectType::Limiter(fx) => Some(EffectType::Limiter(fx.clone())),
+            EffectType::Chorus(fx) => Some(EffectType::Chorus(fx.clone())),
+            EffectType::BitCrusher(fx) => Some(EffectType::BitCrusher(fx.clone())),
+            EffectType::NoiseGate(fx) => Some(EffectType::NoiseGate(fx.clone())),
+            EffectType::StereoWidth(fx) => Some(EffectType::StereoWidth(fx.clone())),
+            EffectType::Filter(fx) => Some(EffectType::Filter(fx.clone())),
+            EffectType::Gain(fx) => Some(EffectType::Gain(fx.clone())),
+            #[cfg(all(feature = "vst3", not(target_os = "ios")))]
+            EffectType::VST3(_) => None,
+        }
+    }
+
     pub fn name(&self) -> &str {
         match self {
             EffectType::EQ(fx) => fx.name(),
@@ -869,10 +2191,236 @@ impl EffectType {
             EffectType::Delay(fx) => fx.name(),
             EffectType::Limiter(fx) => fx.name(),
             EffectType::Chorus(fx) => fx.name(),
+            EffectType::BitCrusher(fx) => fx.name(),
+            EffectType::NoiseGate(fx) => fx.name(),
+            EffectType::StereoWidth(fx) => fx.name(),
+            EffectType::Filter(fx) => fx.name(),
+            EffectType::Gain(fx) => fx.name(),
             #[cfg(all(feature = "vst3", not(target_os = "ios")))]
             EffectType::VST3(fx) => fx.name(),
         }
     }
+
+    /// Set a parameter by name (shared by `set_effect_parameter` and automation playback)
+    /// Parameter names match the keys reported by `get_effect_info`. Continuous
+    /// parameters glide to the new value over `PARAM_SMOOTHING_MS` while processing.
+    pub fn apply_automated_param(&mut self, param_name: &str, value: f32) -> Result<(), String> {
+        match self {
+            EffectType::EQ(eq) => match param_name {
+                "low_freq" => {
+                    eq.low_freq = value;
+                }
+                "low_gain" => {
+                    eq.low_gain_db = value;
+                }
+                "mid1_freq" => {
+                    eq.mid1_freq = value;
+                }
+                "mid1_gain" => {
+                    eq.mid1_gain_db = value;
+                }
+                "mid1_q" => {
+                    eq.mid1_q = value;
+                }
+                "mid2_freq" => {
+                    eq.mid2_freq = value;
+                }
+                "mid2_gain" => {
+                    eq.mid2_gain_db = value;
+                }
+                "mid2_q" => {
+                    eq.mid2_q = value;
+                }
+                "high_freq" => {
+                    eq.high_freq = value;
+                }
+                "high_gain" => {
+                    eq.high_gain_db = value;
+                }
+                _ => return Err(format!("Unknown EQ parameter: {param_name}")),
+            },
+            EffectType::Compressor(comp) => match param_name {
+                "threshold" => {
+                    comp.threshold_db = value;
+                }
+                "ratio" => {
+                    comp.ratio = value;
+                }
+                "attack" => {
+                    comp.attack_ms = value;
+                    comp.update_coefficients();
+                }
+                "release" => {
+                    comp.release_ms = value;
+                    comp.update_coefficients();
+                }
+                "makeup" => {
+                    comp.makeup_gain_db = value;
+                }
+                "oversample" => comp.set_oversample(value.round() as u32)?,
+                _ => return Err(format!("Unknown Compressor parameter: {param_name}")),
+            },
+            EffectType::Reverb(rev) => match param_name {
+                "room_size" => {
+                    rev.room_size = value;
+                }
+                "damping" => {
+                    rev.damping = value;
+                }
+                "wet_dry" => {
+                    rev.wet_dry_mix = value;
+                }
+                "freeze" => {
+                    rev.freeze = value >= 0.5;
+                }
+                _ => return Err(format!("Unknown Reverb parameter: {param_name}")),
+            },
+            EffectType::Delay(delay) => match param_name {
+                "time" => {
+                    // Ignored while synced: the time follows the tempo
+                    if !delay.sync_enabled {
+                        delay.set_delay_time_ms(value);
+                    }
+                }
+                "ping_pong" => {
+                    delay.ping_pong = value >= 0.5;
+                }
+                "sync" => {
+                    delay.sync_enabled = value >= 0.5;
+                    delay.update_synced_time();
+                }
+                "division" => {
+                    let division = value.round() as u32;
+                    if Delay::NOTE_DIVISIONS.contains(&division) {
+                        delay.note_division = division;
+                        delay.update_synced_time();
+                    }
+                }
+                "division_type" => {
+                    if let Some(modifier) = NoteModifier::from_index(value.round() as u32) {
+                        delay.note_modifier = modifier;
+                        delay.update_synced_time();
+                    }
+                }
+                "feedback" => {
+                    delay.feedback = value;
+                }
+                "wet_dry" => {
+                    delay.wet_dry_mix = value;
+                }
+                _ => return Err(format!("Unknown Delay parameter: {param_name}")),
+            },
+            EffectType::Chorus(chorus) => match param_name {
+                "rate" => {
+                    chorus.rate_hz = value;
+                }
+                "depth" => {
+                    chorus.depth = value;
+                }
+                "wet_dry" => {
+                    chorus.wet_dry_mix = value;
+                }
+                _ => return Err(format!("Unknown Chorus parameter: {param_name}")),
+            },
+            EffectType::Limiter(lim) => match param_name {
+                "threshold" => {
+                    lim.threshold_db = value;
+                }
+                "release" => {
+                    lim.release_ms = value;
+                    lim.update_coefficients();
+                }
+                "oversample" => lim.set_oversample(value.round() as u32)?,
+                "lookahead" => lim.set_lookahead_ms(value),
+                _ => return Err(format!("Unknown Limiter parameter: {param_name}")),
+            },
+            EffectType::BitCrusher(crusher) => match param_name {
+                "bit_depth" => {
+                    crusher.bit_depth = value.clamp(1.0, 16.0);
+                }
+                "sample_rate_reduction" => {
+                    crusher.sample_rate_reduction = value.clamp(1.0, 50.0);
+                }
+                "wet_dry" => {
+                    crusher.wet_dry_mix = value;
+                }
+                _ => return Err(format!("Unknown BitCrusher parameter: {param_name}")),
+            },
+            EffectType::NoiseGate(gate) => match param_name {
+                "threshold" => {
+                    gate.threshold_db = value;
+                }
+                "attack" => {
+                    gate.attack_ms = value;
+                    gate.update_coefficients();
+                }
+                "hold" => {
+                    gate.hold_ms = value;
+                    gate.update_coefficients();
+                }
+                "release" => {
+                    gate.release_ms = value;
+                    gate.update_coefficients();
+                }
+                _ => return Err(format!("Unknown NoiseGate parameter: {param_name}")),
+            },
+            EffectType::StereoWidth(sw) => match param_name {
+                "width" => {
+                    sw.width = value.clamp(0.0, 2.0);
+                }
+                _ => return Err(format!("Unknown StereoWidth parameter: {param_name}")),
+            },
+            EffectType::Filter(filter) => match param_name {
+                "filter_type" => {
+                    if let Some(filter_type) = FilterType::from_index(value.round() as u32) {
+                        filter.filter_type = filter_type;
+                        filter.update_coefficients();
+                    }
+                }
+                "cutoff" => {
+                    filter.cutoff_hz = value;
+                    filter.clamp_parameters();
+                }
+                "resonance" => {
+                    filter.resonance = value;
+                    filter.clamp_parameters();
+                }
+                _ => return Err(format!("Unknown Filter parameter: {param_name}")),
+            },
+            EffectType::Gain(gain) => match param_name {
+                "gain" => {
+                    gain.gain_db = value;
+                }
+                "invert_left" => {
+                    gain.invert_left = value >= 0.5;
+                }
+                "invert_right" => {
+                    gain.invert_right = value >= 0.5;
+                }
+                "dc_block" => {
+                    gain.dc_block = value >= 0.5;
+                }
+                _ => return Err(format!("Unknown Gain parameter: {param_name}")),
+            },
+            #[cfg(all(feature = "vst3", not(target_os = "ios")))]
+            EffectType::VST3(vst3) => {
+                // VST3 parameters are accessed by index (e.g., "param_0", "param_1")
+                if let Some(index_str) = param_name.strip_prefix("param_") {
+                    if let Ok(param_index) = index_str.parse::<u32>() {
+                        vst3.set_parameter_value(param_index, f64::from(value))
+                            .map_err(|e| format!("Failed to set VST3 parameter: {e}"))?;
+                    } else {
+                        return Err(format!("Invalid VST3 parameter index: {param_name}"));
+                    }
+                } else {
+                    return Err(format!(
+                        "VST3 parameter must be in format 'param_N': {param_name}"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 // ========================================================================
@@ -882,12 +2430,39 @@ impl EffectType {
 use std::sync::Arc;
 use parking_lot::Mutex;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use crate::track::AutomationPoint;
+
+/// Parameter name of the per-effect dry/wet mix handled by `EffectManager`
+pub const MIX_PARAM: &str = "mix";
+
+/// Linear dry/wet blend (mix 1.0 = wet only)
+#[inline]
+fn blend_mix(dry: (f32, f32), wet: (f32, f32), mix: f32) -> (f32, f32) {
+    if mix >= 1.0 {
+        wet
+    } else {
+        (
+            dry.0 + (wet.0 - dry.0) * mix,
+            dry.1 + (wet.1 - dry.1) * mix,
+        )
+    }
+}
 
 /// Effect manager: holds all effect instances
 pub struct EffectManager {
     effects: HashMap<EffectId, Arc<Mutex<EffectType>>>,
     /// Bypass state per effect (true = bypassed, audio passes through unchanged)
     bypass_states: HashMap<EffectId, bool>,
+    /// Dry/wet mix per effect as f32 bits (0.0 = dry, 1.0 = fully processed)
+    /// Atomic so automation can drive it from the audio thread
+    mix_levels: HashMap<EffectId, AtomicU32>,
+    /// Dry path per effect, delayed by the effect's latency before the mix blend
+    dry_delays: HashMap<EffectId, Mutex<LatencyDelay>>,
+    /// Parameter automation curves keyed by (effect, parameter name)
+    automation: HashMap<(EffectId, String), Vec<AutomationPoint>>,
+    /// Project tempo, pushed into tempo-synced effects
+    tempo_bpm: f64,
     next_id: EffectId,
 }
 
@@ -902,12 +2477,19 @@ impl EffectManager {
         Self {
             effects: HashMap::new(),
             bypass_states: HashMap::new(),
+            mix_levels: HashMap::new(),
+            dry_delays: HashMap::new(),
+            automation: HashMap::new(),
+            tempo_bpm: 120.0,
             next_id: 0,
         }
     }
 
     /// Create a new effect and return its ID
-    pub fn create_effect(&mut self, effect: EffectType) -> EffectId {
+    pub fn create_effect(&mut self, mut effect: EffectType) -> EffectId {
+        if let EffectType::Delay(delay) = &mut effect {
+            delay.set_tempo(self.tempo_bpm);
+        }
         let id = self.next_id;
         self.next_id += 1;
 
@@ -915,18 +2497,74 @@ impl EffectManager {
 
         self.effects.insert(id, Arc::new(Mutex::new(effect)));
         self.bypass_states.insert(id, false); // Effects start not bypassed
+        self.mix_levels.insert(id, AtomicU32::new(1.0_f32.to_bits()));
+        self.dry_delays.insert(id, Mutex::new(LatencyDelay::new()));
         id
     }
 
+    /// Set the project tempo and recompute tempo-synced delay times
+    pub fn set_tempo(&mut self, bpm: f64) {
+        self.tempo_bpm = bpm;
+        for effect_arc in self.effects.values() {
+            if let EffectType::Delay(delay) = &mut *effect_arc.lock() {
+                delay.set_tempo(bpm);
+            }
+        }
+    }
+
+    /// Get the sidechain source track of a compressor (None for other effects)
+    pub fn sidechain_source(&self, id: EffectId) -> Option<u64> {
+        let effect = self.effects.get(&id)?.lock();
+        match &*effect {
+            EffectType::Compressor(comp) => comp.sidechain_track_id,
+            _ => None,
+        }
+    }
+
     /// Get an effect by ID
     pub fn get_effect(&self, id: EffectId) -> Option<Arc<Mutex<EffectType>>> {
         self.effects.get(&id).cloned()
     }
 
+    /// Copy of every effect with its current state, bypass, mix and automation,
+    /// for renders that must not disturb the live effects (scrub grains)
+    /// VST3 plugins can't be copied and are left out, so chains skip them.
+    pub fn isolated_copy(&self) -> Self {
+        let effects: HashMap<EffectId, Arc<Mutex<EffectType>>> = self
+            .effects
+            .iter()
+            .filter_map(|(&id, effect)| Some((id, Arc::new(Mutex::new(effect.lock().try_clone()?)))))
+            .collect();
+        let mix_levels = self
+            .mix_levels
+            .iter()
+            .filter(|(id, _)| effects.contains_key(id))
+            .map(|(&id, level)| (id, AtomicU32::new(level.load(Ordering::Relaxed))))
+            .collect();
+        let dry_delays = self
+            .dry_delays
+            .iter()
+            .filter(|(id, _)| effects.contains_key(id))
+            .map(|(&id, delay)| (id, Mutex::new(delay.lock().clone())))
+            .collect();
+        Self {
+            bypass_states: self.bypass_states.clone(),
+            mix_levels,
+            dry_delays,
+            automation: self.automation.clone(),
+            tempo_bpm: self.tempo_bpm,
+            next_id: self.next_id,
+            effects,
+        }
+    }
+
     /// Remove an effect
     pub fn remove_effect(&mut self, id: EffectId) -> bool {
         if self.effects.remove(&id).is_some() {
             self.bypass_states.remove(&id);
+            self.mix_levels.remove(&id);
+            self.dry_delays.remove(&id);
+            self.automation.retain(|(effect_id, _), _| *effect_id != id);
             eprintln!("🗑️ [EffectManager] Removed effect {id}");
             true
         } else {
@@ -934,6 +2572,129 @@ impl EffectManager {
         }
     }
 
+    /// Set (or clear, with an empty curve) automation for one effect parameter
+    /// The parameter name is validated here so playback never hits an unknown name
+    pub fn set_parameter_automation(
+        &mut self,
+        id: EffectId,
+        param_name: &str,
+        points: Vec<AutomationPoint>,
+    ) -> Result<(), String> {
+        let effect_arc = self.effects.get(&id).ok_or_else(|| format!("Effect {id} not found"))?;
+        let key = (id, param_name.to_string());
+        if let Some(first) = points.first() {
+            if param_name == MIX_PARAM {
+                self.set_mix(id, first.value);
+            } else {
+                effect_arc.lock().apply_automated_param(param_name, first.value)?;
+            }
+            self.automation.insert(key, points);
+        } else {
+            self.automation.remove(&key);
+        }
+        Ok(())
+    }
+
+    /// Get all automation curves for an effect as (parameter name, curve)
+    pub fn get_parameter_automation(&self, id: EffectId) -> Vec<(&str, &[AutomationPoint])> {
+        self.automation
+            .iter()
+            .filter(|((effect_id, _), _)| *effect_id == id)
+            .map(|((_, param_name), points)| (param_name.as_str(), points.as_slice()))
+            .collect()
+    }
+
+    /// Apply automated parameter values at a timeline position (seconds)
+    /// Called from the audio thread once per block
+    pub fn apply_automation(&self, time_seconds: f64) {
+        for ((id, param_name), points) in &self.automation {
+            if let Some(effect_arc) = self.effects.get(id) {
+                let value = crate::audio_graph::interpolate_automation_value(points, time_seconds);
+                if param_name == MIX_PARAM {
+                    self.set_mix(*id, value);
+                } else {
+                    let _ = effect_arc.lock().apply_automated_param(param_name, value);
+                }
+            }
+        }
+    }
+
+    /// Set an effect's dry/wet mix (clamped to 0.0-1.0); false if the effect doesn't exist
+    /// Applies on top of any effect-specific `wet_dry_mix`
+    pub fn set_mix(&self, id: EffectId, mix: f32) -> bool {
+        let Some(level) = self.mix_levels.get(&id) else {
+            return false;
+        };
+        if mix.is_finite() {
+            level.store(mix.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+        }
+        true
+    }
+
+    /// Get an effect's dry/wet mix (1.0 if the effect doesn't exist)
+    pub fn get_mix(&self, id: EffectId) -> f32 {
+        self.mix_levels
+            .get(&id)
+            .map_or(1.0, |level| f32::from_bits(level.load(Ordering::Relaxed)))
+    }
+
+    /// Dry input lined up with the wet output of a latent effect (unchanged otherwise)
+    #[inline]
+    fn delay_dry(&self, id: EffectId, effect: &EffectType, left: f32, right: f32) -> (f32, f32) {
+        let latency = effect.latency_samples();
+        match self.dry_delays.get(&id) {
+            Some(delay) if latency > 0 => delay.lock().process(latency, left, right),
+            _ => (left, right),
+        }
+    }
+
+    /// Process one frame through an effect, blended with the dry input by its mix
+    /// `effect` is the locked instance of effect `id`. The dry input is delayed by the
+    /// effect's latency so a partial mix doesn't comb-filter.
+    pub fn process_frame(&self, id: EffectId, effect: &mut EffectType, left: f32, right: f32) -> (f32, f32) {
+        let wet = effect.process_frame(left, right);
+        let mix = self.get_mix(id);
+        if mix >= 1.0 {
+            return wet;
+        }
+        blend_mix(self.delay_dry(id, effect, left, right), wet, mix)
+    }
+
+    /// Process a block of frames in place through an effect, blended with the dry
+    /// input by its mix (block counterpart of `process_frame`)
+    pub fn process_block(&self, id: EffectId, effect: &mut EffectType, left: &mut [f32], right: &mut [f32]) {
+        // Dry input is copied a chunk at a time (no allocation)
+        const CHUNK: usize = 64;
+        let mix = self.get_mix(id);
+        if mix >= 1.0 {
+            effect.process_block(left, right);
+            return;
+        }
+        let len = left.len().min(right.len());
+        for (left, right) in left[..len].chunks_mut(CHUNK).zip(right[..len].chunks_mut(CHUNK)) {
+            let n = left.len();
+            let mut dry_l = [0.0f32; CHUNK];
+            let mut dry_r = [0.0f32; CHUNK];
+            dry_l[..n].copy_from_slice(left);
+            dry_r[..n].copy_from_slice(right);
+            effect.process_block(left, right);
+            for i in 0..n {
+                let dry = self.delay_dry(id, effect, dry_l[i], dry_r[i]);
+                (left[i], right[i]) = blend_mix(dry, (left[i], right[i]), mix);
+            }
+        }
+    }
+
+    /// Preview-quality variant of `process_frame` (see `EffectType::process_frame_preview`)
+    pub fn process_frame_preview(&self, id: EffectId, effect: &mut EffectType, left: f32, right: f32) -> (f32, f32) {
+        let wet = effect.process_frame_preview(left, right);
+        let mix = self.get_mix(id);
+        if mix >= 1.0 {
+            return wet;
+        }
+        blend_mix(self.delay_dry(id, effect, left, right), wet, mix)
+    }
+
     /// Set bypass state for an effect
     pub fn set_bypass(&mut self, id: EffectId, bypassed: bool) -> bool {
         if self.effects.contains_key(&id) {
@@ -955,11 +2716,44 @@ impl EffectManager {
         self.bypass_states.get(&id).copied().unwrap_or(false)
     }
 
+    /// Check if an FX chain contains a plugin instrument
+    pub fn chain_has_instrument(&self, fx_chain: &[EffectId]) -> bool {
+        fx_chain.iter().any(|id| {
+            self.get_effect(*id).is_some_and(|effect| effect.lock().is_instrument())
+        })
+    }
+
+    /// Check if an FX chain contains an effect keyed from a sidechain source
+    /// (these need per-frame levels, so the chain can't be block-processed)
+    pub fn chain_has_sidechain(&self, fx_chain: &[EffectId]) -> bool {
+        fx_chain.iter().any(|id| self.sidechain_source(*id).is_some())
+    }
+
+    /// Total latency of an FX chain in frames, skipping bypassed effects
+    pub fn chain_latency(&self, fx_chain: &[EffectId]) -> usize {
+        fx_chain
+            .iter()
+            .filter(|id| !self.is_bypassed(**id))
+            .filter_map(|id| self.get_effect(*id))
+            .map(|effect| effect.lock().latency_samples())
+            .sum()
+    }
+
     /// Get all effect IDs
     pub fn get_all_effect_ids(&self) -> Vec<EffectId> {
         self.effects.keys().copied().collect()
     }
 
+    /// Clear the internal state (delay lines, envelopes, tails) of every effect
+    pub fn reset_all(&self) {
+        for effect in self.effects.values() {
+            effect.lock().reset();
+        }
+        for delay in self.dry_delays.values() {
+            delay.lock().clear();
+        }
+    }
+
     /// Duplicate an effect (deep copy with new ID)
     /// Returns new effect ID on success, None if source effect not found
     pub fn duplicate_effect(&mut self, source_effect_id: EffectId) -> Option<EffectId> {
@@ -975,6 +2769,9 @@ impl EffectManager {
             self.next_id += 1;
 
             self.effects.insert(new_id, Arc::new(Mutex::new(cloned_effect)));
+            let mix = self.get_mix(source_effect_id);
+            self.mix_levels.insert(new_id, AtomicU32::new(mix.to_bits()));
+            self.dry_delays.insert(new_id, Mutex::new(LatencyDelay::new()));
             eprintln!("🎛️ [EffectManager] Duplicated effect {} → {} ({})",
                       source_effect_id, new_id, self.effects.get(&new_id).unwrap().lock().name());
 
@@ -984,3 +2781,574 @@ impl EffectManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine_peak_after_gate(gate: &mut NoiseGate, amplitude: f32) -> f32 {
+        let mut peak = 0.0_f32;
+        for i in 0..TARGET_SAMPLE_RATE / 2 {
+            let sample = amplitude * (2.0 * PI * 440.0 * i as f32 / TARGET_SAMPLE_RATE as f32).sin();
+            let (l, _) = gate.process_frame(sample, sample);
+            // Measure the settled output over the last 100ms
+            if i > TARGET_SAMPLE_RATE / 2 - TARGET_SAMPLE_RATE / 10 {
+                peak = peak.max(l.abs());
+            }
+        }
+        peak
+    }
+
+    #[test]
+    fn test_noise_gate_attenuates_quiet_signal() {
+        let mut gate = NoiseGate::new();
+        gate.threshold_db = -30.0;
+        gate.update_coefficients();
+
+        // -40 dBFS sine is below threshold: gate closes
+        let quiet = sine_peak_after_gate(&mut gate, 0.01);
+        assert!(quiet < 0.001, "quiet sine should be gated, got {quiet}");
+
+        // -6 dBFS sine is above threshold: passes unchanged
+        gate.reset();
+        let loud = sine_peak_after_gate(&mut gate, 0.5);
+        assert!((loud - 0.5).abs() < 0.01, "loud sine should pass, got {loud}");
+    }
+
+    #[test]
+    fn test_stereo_width_zero_is_mono() {
+        let mut width = StereoWidth::new();
+        width.width = 0.0;
+
+        let (l, r) = width.process_frame(0.8, -0.2);
+        assert!((l - r).abs() < 1e-6);
+        assert!((l - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_stereo_width_unity_is_transparent() {
+        let mut width = StereoWidth::new();
+        let (l, r) = width.process_frame(0.8, -0.2);
+        assert!((l - 0.8).abs() < 1e-6);
+        assert!((r + 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_limiter_threshold_and_gain_reduction() {
+        let mut limiter = Limiter::new();
+        limiter.set_lookahead_ms(0.0);
+        limiter.process_frame(0.5, 0.5);
+        assert!(limiter.get_gain_reduction_db().abs() < 1e-6);
+
+        // -6 dB ceiling: a full-scale peak is pulled down by ~6 dB
+        limiter.set_threshold_db(-6.0);
+        let (l, _) = limiter.process_frame(1.0, 1.0);
+        assert!((l - 10_f32.powf(-6.0 / 20.0)).abs() < 1e-4);
+        assert!((limiter.get_gain_reduction_db() - 6.0).abs() < 0.01);
+
+        limiter.set_threshold_db(12.0);
+        assert!(limiter.threshold_db.abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_partial_mix_lines_dry_up_with_latent_effect() {
+        // A 500 Hz tone is half a cycle long over the limiter's 1 ms lookahead: an
+        // undelayed dry path would cancel the wet one at mix 0.5
+        let mut manager = EffectManager::new();
+        let id = manager.create_effect(EffectType::Limiter(Limiter::new()));
+        manager.set_mix(id, 0.5);
+        let effect_arc = manager.get_effect(id).unwrap();
+        let latency = effect_arc.lock().latency_samples();
+        assert_eq!(latency, 48);
+
+        let tone = |n: usize| 0.5 * (2.0 * PI * 500.0 * n as f32 / TARGET_SAMPLE_RATE as f32).sin();
+        let frames = 4800;
+        let per_frame: Vec<f32> = (0..frames)
+            .map(|n| manager.process_frame(id, &mut effect_arc.lock(), tone(n), tone(n)).0)
+            .collect();
+        for (n, &out) in per_frame.iter().enumerate().skip(latency) {
+            assert!((out - tone(n - latency)).abs() < 1e-4, "frame {n}: {out} vs {}", tone(n - latency));
+        }
+
+        // The block path lines up the same way
+        manager.reset_all();
+        let mut left: Vec<f32> = (0..frames).map(tone).collect();
+        let mut right = left.clone();
+        manager.process_block(id, &mut effect_arc.lock(), &mut left, &mut right);
+        for (n, (block, frame)) in left.iter().zip(&per_frame).enumerate().skip(latency) {
+            assert!((block - frame).abs() < 1e-5, "block frame {n}");
+        }
+    }
+
+    #[test]
+    fn test_limiter_lookahead_catches_transient() {
+        // A quiet tone, then a full-scale hit: the gain has to be down before the hit
+        // leaves the delay line
+        let input: Vec<f32> = (0..4800)
+            .map(|n| if (2400..2410).contains(&n) { 1.0 } else { 0.1 * (n as f32 * 0.05).sin() })
+            .collect();
+        let ceiling = 10_f32.powf(-6.0 / 20.0);
+
+        for factor in [1, 4] {
+            let mut limiter = Limiter::new();
+            limiter.set_threshold_db(-6.0);
+            limiter.set_oversample(factor).unwrap();
+            let latency = limiter.latency_samples();
+            assert!(latency >= 48, "lookahead is reported as latency");
+            let output: Vec<f32> = input.iter().map(|&x| limiter.process_frame(x, x).0).collect();
+
+            let peak = output.iter().fold(0.0_f32, |max, x| max.max(x.abs()));
+            assert!(peak <= ceiling * 1.01, "{factor}x: transient reached {peak}");
+            // Gain ramps down ahead of the hit instead of jumping on it
+            let before_hit = output[2400 + latency - 12].abs();
+            let dry = input[2400 - 12].abs();
+            assert!(before_hit < dry * 0.9, "{factor}x: {before_hit} vs dry {dry}");
+        }
+
+        let mut limiter = Limiter::new();
+        limiter.set_lookahead_ms(0.0);
+        assert_eq!(limiter.latency_samples(), 0);
+    }
+
+    #[test]
+    fn test_oversampling_reduces_aliasing() {
+        // A hot 15 kHz sine: every harmonic the gain changes add lies above 24 kHz, so
+        // anything in the output besides the 15 kHz tone is aliasing
+        fn aliasing(effect: &mut impl Effect) -> f64 {
+            let omega = std::f64::consts::TAU * 15_000.0 / f64::from(TARGET_SAMPLE_RATE);
+            let output: Vec<f64> = (0..TARGET_SAMPLE_RATE / 2 + 4800)
+                .map(|n| f64::from(effect.process_frame((omega * f64::from(n)).sin() as f32, 0.0).0))
+                .skip(4800)
+                .collect();
+            // Remove the 15 kHz component (0.5 s holds a whole number of cycles)
+            let (mut sin_part, mut cos_part) = (0.0, 0.0);
+            for (n, &y) in output.iter().enumerate() {
+                sin_part += y * (omega * n as f64).sin();
+                cos_part += y * (omega * n as f64).cos();
+            }
+            let scale = 2.0 / output.len() as f64;
+            let residual: f64 = output
+                .iter()
+                .enumerate()
+                .map(|(n, &y)| (y - scale * (sin_part * (omega * n as f64).sin() + cos_part * (omega * n as f64).cos())).powi(2))
+                .sum();
+            residual / output.len() as f64
+        }
+
+        // 2x still folds back the harmonics above 72 kHz; 4x keeps nearly everything
+        for (factor, max_ratio) in [(2, 0.5), (4, 0.05)] {
+            let mut plain = Compressor::new();
+            plain.threshold_db = -30.0;
+            plain.ratio = 20.0;
+            plain.attack_ms = 0.05;
+            plain.release_ms = 1.0;
+            plain.update_coefficients();
+            let mut oversampled = plain.clone();
+            oversampled.set_oversample(factor).unwrap();
+            assert_eq!(oversampled.oversample(), factor);
+            let (plain_aliasing, oversampled_aliasing) = (aliasing(&mut plain), aliasing(&mut oversampled));
+            assert!(oversampled_aliasing < plain_aliasing * max_ratio, "compressor {factor}x: {oversampled_aliasing} vs {plain_aliasing}");
+
+            // Without lookahead, so the gain follows every cycle of the sine
+            let mut plain = Limiter::new();
+            plain.set_threshold_db(-12.0);
+            plain.set_release_ms(1.0);
+            plain.set_lookahead_ms(0.0);
+            let mut oversampled = plain.clone();
+            oversampled.set_oversample(factor).unwrap();
+            let (plain_aliasing, oversampled_aliasing) = (aliasing(&mut plain), aliasing(&mut oversampled));
+            assert!(oversampled_aliasing < plain_aliasing * max_ratio, "limiter {factor}x: {oversampled_aliasing} vs {plain_aliasing}");
+        }
+        assert!(Limiter::new().set_oversample(3).is_err());
+    }
+
+    #[test]
+    fn test_bitcrusher_quantizes_to_bit_depth() {
+        let mut crusher = BitCrusher::new();
+        crusher.bit_depth = 2.0; // Steps of 0.5
+
+        let (l, r) = crusher.process_frame(0.3, -0.8);
+        assert!((l - 0.5).abs() < 1e-6);
+        assert!((r + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_bitcrusher_holds_samples_when_downsampling() {
+        let mut crusher = BitCrusher::new();
+        crusher.bit_depth = 16.0;
+        crusher.sample_rate_reduction = 4.0;
+
+        let outputs: Vec<f32> = (0..8u8)
+            .map(|i| crusher.process_frame(f32::from(i) * 0.1, 0.0).0)
+            .collect();
+
+        // Frames 0-3 hold the first sample, frames 4-7 hold the fifth
+        assert!(outputs[..4].iter().all(|&s| s.abs() < 1e-3));
+        assert!(outputs[4..].iter().all(|&s| (s - 0.4).abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_bitcrusher_dry_mix_passes_input() {
+        let mut crusher = BitCrusher::new();
+        crusher.bit_depth = 1.0;
+        crusher.wet_dry_mix = 0.0;
+
+        let (l, r) = crusher.process_frame(0.123, -0.456);
+        assert!((l - 0.123).abs() < 1e-6);
+        assert!((r + 0.456).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sidechain_compressor_keys_from_source_level() {
+        let mut comp = Compressor::new();
+        comp.threshold_db = -20.0;
+        comp.ratio = 10.0;
+        comp.sidechain_track_id = Some(7);
+
+        // Loud input but silent sidechain: no gain reduction
+        let mut out = 0.0;
+        for _ in 0..TARGET_SAMPLE_RATE / 10 {
+            comp.set_sidechain_level(0.0);
+            out = comp.process_frame(0.9, 0.9).0;
+        }
+        assert!((out - 0.9).abs() < 1e-3);
+
+        // Quiet input keyed by a loud source gets ducked
+        for _ in 0..TARGET_SAMPLE_RATE / 10 {
+            comp.set_sidechain_level(1.0);
+            out = comp.process_frame(0.05, 0.05).0;
+        }
+        assert!(out < 0.05 * 0.2, "ducked output {out}");
+    }
+
+    /// Steady-state peak amplitude of a sine through the filter
+    fn filter_gain(filter: &mut Filter, freq: f32) -> f32 {
+        filter.reset();
+        let sample_rate = TARGET_SAMPLE_RATE as f32;
+        (0..9600)
+            .map(|i| {
+                let x = (2.0 * PI * freq * i as f32 / sample_rate).sin();
+                filter.process_frame(x, x).0
+            })
+            .skip(4800)
+            .fold(0.0f32, |peak, y| peak.max(y.abs()))
+    }
+
+    #[test]
+    fn test_stepped_gain_change_ramps() {
+        let mut gain = Gain::new();
+        gain.gain_db = 6.0;
+        // A fresh effect starts at its setting
+        assert!((gain.process_frame(1.0, 1.0).0 - 1.9953).abs() < 1e-3);
+
+        gain.gain_db = -20.0;
+        let ramp: Vec<f32> = (0..960).map(|_| gain.process_frame(1.0, 1.0).0).collect();
+        assert!(ramp[0] > 1.9, "no jump on the first sample after the step");
+        assert!(ramp.windows(2).all(|w| w[1] <= w[0]));
+        // Within 1% of the 26 dB step after PARAM_SMOOTHING_MS, settled soon after
+        assert!((20.0 * ramp[480].log10() + 20.0).abs() < 0.27);
+        assert!((ramp[959] - 0.1).abs() < 1e-4);
+
+        // Filter cutoff glides through the parameter API too
+        let mut filter = EffectType::Filter(Filter::new());
+        filter.process_frame(0.0, 0.0);
+        filter.apply_automated_param("cutoff", 8000.0).unwrap();
+        let EffectType::Filter(inner) = &mut filter else { unreachable!() };
+        inner.process_frame(0.0, 0.0);
+        assert!(inner.smoothed_cutoff.current < 2000.0);
+        for _ in 0..960 {
+            inner.process_frame(0.0, 0.0);
+        }
+        assert!(!inner.smoothed_cutoff.is_moving(8000.0));
+    }
+
+    #[test]
+    fn test_filter_types_pass_and_reject() {
+        let mut filter = Filter::new();
+        filter.cutoff_hz = 1000.0;
+        filter.update_coefficients();
+        assert!(filter_gain(&mut filter, 100.0) > 0.95);
+        assert!(filter_gain(&mut filter, 10_000.0) < 0.02);
+
+        filter.filter_type = FilterType::HighPass;
+        filter.update_coefficients();
+        assert!(filter_gain(&mut filter, 100.0) < 0.02);
+        assert!(filter_gain(&mut filter, 10_000.0) > 0.95);
+
+        filter.filter_type = FilterType::BandPass;
+        filter.resonance = 4.0;
+        filter.update_coefficients();
+        assert!(filter_gain(&mut filter, 1000.0) > 0.95);
+        assert!(filter_gain(&mut filter, 100.0) < 0.05);
+        assert!(filter_gain(&mut filter, 10_000.0) < 0.05);
+
+        // Resonance boosts the low-pass at the cutoff
+        filter.filter_type = FilterType::LowPass;
+        filter.resonance = 8.0;
+        filter.update_coefficients();
+        assert!(filter_gain(&mut filter, 1000.0) > 4.0);
+    }
+
+    #[test]
+    fn test_reverb_preview_runs_half_the_combs() {
+        let mut reverb = Reverb::new();
+        reverb.wet_dry_mix = 1.0;
+        let mut tail = 0.0f32;
+        for i in 0..TARGET_SAMPLE_RATE / 2 {
+            let x = if i == 0 { 1.0 } else { 0.0 };
+            tail = tail.max(reverb.process_frame_preview(x, x).0.abs());
+        }
+        assert!(tail > 1e-4, "no preview tail");
+
+        // Only the even combs were touched: the preview does half the comb work
+        for (i, buffer) in reverb.comb_buffers_l.iter().enumerate() {
+            let touched = buffer.iter().any(|&s| s != 0.0);
+            assert_eq!(touched, i % 2 == 0, "comb {i} touched: {touched}");
+        }
+    }
+
+    #[test]
+    fn test_reverb_freeze_sustains_tail() {
+        let wet_rms = |reverb: &mut Reverb, frames: u32| {
+            let sum: f32 = (0..frames).map(|_| reverb.process_frame(0.0, 0.0).0.powi(2)).sum();
+            (sum / frames as f32).sqrt()
+        };
+        let excite = |reverb: &mut Reverb| {
+            reverb.wet_dry_mix = 1.0;
+            for i in 0..TARGET_SAMPLE_RATE / 2 {
+                let x = ((i * 7919) % 1000) as f32 / 500.0 - 1.0; // Deterministic noise
+                reverb.process_frame(x, x);
+            }
+        };
+
+        let mut frozen = Reverb::new();
+        excite(&mut frozen);
+        frozen.freeze = true;
+        wet_rms(&mut frozen, TARGET_SAMPLE_RATE / 2); // Let the allpass stage settle
+        let early = wet_rms(&mut frozen, TARGET_SAMPLE_RATE / 2);
+        wet_rms(&mut frozen, TARGET_SAMPLE_RATE * 3);
+        let late = wet_rms(&mut frozen, TARGET_SAMPLE_RATE / 2);
+        assert!(early > 1e-3);
+        assert!(late > early * 0.8, "frozen tail decayed: {early} -> {late}");
+
+        // Unfrozen tail decays
+        let mut normal = Reverb::new();
+        excite(&mut normal);
+        let early = wet_rms(&mut normal, TARGET_SAMPLE_RATE / 2);
+        wet_rms(&mut normal, TARGET_SAMPLE_RATE * 2);
+        let late = wet_rms(&mut normal, TARGET_SAMPLE_RATE / 2);
+        assert!(late < early * 0.1);
+    }
+
+    #[test]
+    fn test_gain_invert_flips_sign() {
+        let mut gain = Gain::new();
+        gain.invert_left = true;
+        let (l, r) = gain.process_frame(0.5, 0.25);
+        assert!((l + 0.5).abs() < 1e-6);
+        assert!((r - 0.25).abs() < 1e-6);
+
+        gain.invert_right = true;
+        gain.gain_db = 6.0;
+        // Read once the gain change has glided in
+        let (l, r) = (0..960).map(|_| gain.process_frame(0.5, 0.25)).last().unwrap();
+        assert!((l + 0.5 * 1.995).abs() < 1e-3);
+        assert!((r + 0.25 * 1.995).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_gain_dc_block_removes_offset() {
+        let mut gain = Gain::new();
+        gain.dc_block = true;
+        let last = (0..TARGET_SAMPLE_RATE).map(|_| gain.process_frame(0.5, -0.5)).last().unwrap();
+        assert!(last.0.abs() < 1e-3 && last.1.abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_delay_ping_pong_alternates_channels() {
+        let mut delay = Delay::new();
+        delay.delay_time_ms = 10.0; // 480 samples
+        delay.feedback = 0.5;
+        delay.wet_dry_mix = 1.0;
+        delay.ping_pong = true;
+
+        let output: Vec<(f32, f32)> = (0..1500)
+            .map(|i| if i == 0 { delay.process_frame(1.0, 1.0) } else { delay.process_frame(0.0, 0.0) })
+            .collect();
+
+        // First echo on the left only, second on the right only, third back on the left
+        assert!(output[480].0 > 0.5 && output[480].1.abs() < 1e-6);
+        assert!(output[960].1 > 0.1 && output[960].0.abs() < 1e-6);
+        assert!(output[1440].0 > 0.1 && output[1440].1.abs() < 1e-6);
+
+        // Without ping-pong the echo lands on both channels at once
+        let mut delay = Delay::new();
+        delay.delay_time_ms = 10.0;
+        delay.wet_dry_mix = 1.0;
+        delay.process_frame(1.0, 1.0);
+        let echo = (1..=480).map(|_| delay.process_frame(0.0, 0.0)).last().unwrap();
+        assert!(echo.0 > 0.5 && echo.1 > 0.5);
+    }
+
+    #[test]
+    fn test_block_processing_matches_frame_processing() {
+        let mut eq = ParametricEQ::new();
+        eq.low_gain_db = 6.0;
+        eq.mid2_gain_db = -4.0;
+        eq.update_coefficients();
+        let mut delay = Delay::new();
+        delay.delay_time_ms = 1.0;
+        delay.ping_pong = true;
+        let mut reverb = Reverb::new();
+        reverb.room_size = 0.9;
+        let effects = [EffectType::EQ(eq), EffectType::Delay(delay), EffectType::Reverb(reverb)];
+
+        let mut manager = EffectManager::new();
+        for effect in effects {
+            let mut by_frame = effect.clone();
+            let mut by_block = effect.clone();
+            let id = manager.create_effect(effect);
+            manager.set_mix(id, 0.7);
+
+            // Odd block length, so the last reverb and mix chunks are partial
+            let input = |i: usize| ((i as f32 * 0.37).sin() * 0.5, (i as f32 * 0.11).cos() * 0.5);
+            let mut left: Vec<f32> = (0..1000).map(|i| input(i).0).collect();
+            let mut right: Vec<f32> = (0..1000).map(|i| input(i).1).collect();
+            for block in (0..1000).collect::<Vec<_>>().chunks(333) {
+                let range = block[0]..block[block.len() - 1] + 1;
+                manager.process_block(id, &mut by_block, &mut left[range.clone()], &mut right[range]);
+            }
+            for i in 0..1000 {
+                let (l, r) = manager.process_frame(id, &mut by_frame, input(i).0, input(i).1);
+                assert!((l - left[i]).abs() < 1e-6 && (r - right[i]).abs() < 1e-6, "{} frame {i}", by_frame.name());
+            }
+        }
+    }
+
+    #[test]
+    fn test_eq_block_matches_frames_while_parameters_glide() {
+        let mut by_frame = ParametricEQ::new();
+        by_frame.mid1_gain_db = 9.0;
+        by_frame.high_freq = 4000.0;
+        let mut by_block = by_frame.clone();
+
+        // The first blocks see the smoothed parameters move, the later ones settled filters
+        let input = |i: usize| ((i as f32 * 0.21).sin() * 0.5, (i as f32 * 0.05).cos() * 0.5);
+        let mut left: Vec<f32> = (0..4096).map(|i| input(i).0).collect();
+        let mut right: Vec<f32> = (0..4096).map(|i| input(i).1).collect();
+        for (l, r) in left.chunks_mut(256).zip(right.chunks_mut(256)) {
+            by_block.process_block(l, r);
+        }
+        for i in 0..4096 {
+            let (l, r) = by_frame.process_frame(input(i).0, input(i).1);
+            assert!((l - left[i]).abs() < 1e-6 && (r - right[i]).abs() < 1e-6, "frame {i}");
+        }
+    }
+
+    #[test]
+    fn test_effect_mix_blends_dry_and_wet() {
+        let mut manager = EffectManager::new();
+        let mut gain = Gain::new();
+        gain.invert_left = true;
+        gain.invert_right = true;
+        let id = manager.create_effect(EffectType::Gain(gain));
+        let effect_arc = manager.get_effect(id).unwrap();
+
+        // Fully wet by default
+        let (l, _) = manager.process_frame(id, &mut effect_arc.lock(), 0.5, 0.5);
+        assert!((l + 0.5).abs() < 1e-6);
+
+        // Half mix of an inverted signal cancels out
+        assert!(manager.set_mix(id, 0.5));
+        let (l, r) = manager.process_frame(id, &mut effect_arc.lock(), 0.5, 0.5);
+        assert!(l.abs() < 1e-6 && r.abs() < 1e-6);
+
+        // Dry passes input through, and mix is clamped
+        manager.set_mix(id, -1.0);
+        let (l, _) = manager.process_frame(id, &mut effect_arc.lock(), 0.5, 0.5);
+        assert!((l - 0.5).abs() < 1e-6);
+
+        // Mix can be automated like any other parameter
+        manager
+            .set_parameter_automation(id, MIX_PARAM, vec![AutomationPoint::new(0.0, 0.0), AutomationPoint::new(1.0, 1.0)])
+            .unwrap();
+        manager.apply_automation(0.25);
+        assert!((manager.get_mix(id) - 0.25).abs() < 1e-6);
+
+        let copy = manager.duplicate_effect(id).unwrap();
+        assert!((manager.get_mix(copy) - 0.25).abs() < 1e-6);
+        assert!(!manager.set_mix(999, 0.5));
+    }
+
+    #[test]
+    fn test_delay_sync_follows_tempo() {
+        let mut manager = EffectManager::new();
+        let mut delay = Delay::new();
+        delay.set_sync(true, 8, NoteModifier::Dotted).unwrap();
+        let id = manager.create_effect(EffectType::Delay(delay));
+        let time_ms = |manager: &EffectManager| match &*manager.get_effect(id).unwrap().lock() {
+            EffectType::Delay(d) => d.delay_time_ms,
+            _ => unreachable!(),
+        };
+
+        // Dotted eighth at 120 BPM = 375ms
+        assert!((time_ms(&manager) - 375.0).abs() < 1e-3);
+
+        // Quarter-note triplet at 100 BPM = 400ms
+        manager.set_tempo(100.0);
+        manager.get_effect(id).unwrap().lock().apply_automated_param("division", 4.0).unwrap();
+        manager.get_effect(id).unwrap().lock().apply_automated_param("division_type", 2.0).unwrap();
+        assert!((time_ms(&manager) - 400.0).abs() < 1e-3);
+
+        // Manual time is ignored while synced, used once sync is off
+        manager.get_effect(id).unwrap().lock().apply_automated_param("time", 123.0).unwrap();
+        assert!((time_ms(&manager) - 400.0).abs() < 1e-3);
+        manager.get_effect(id).unwrap().lock().apply_automated_param("sync", 0.0).unwrap();
+        manager.get_effect(id).unwrap().lock().apply_automated_param("time", 123.0).unwrap();
+        assert!((time_ms(&manager) - 123.0).abs() < 1e-3);
+
+        assert!(Delay::new().set_sync(true, 3, NoteModifier::Straight).is_err());
+    }
+
+    #[test]
+    fn test_delay_time_never_exceeds_buffer() {
+        // A whole note lasts 2s at 120 BPM and fits; dotted it doesn't
+        let mut delay = Delay::new();
+        delay.set_sync(true, 1, NoteModifier::Straight).unwrap();
+        assert!((delay.delay_time_ms - Delay::MAX_DELAY_MS).abs() < 1e-3);
+        assert!(delay.set_sync(true, 1, NoteModifier::Dotted).is_err());
+        assert_eq!(delay.note_modifier, NoteModifier::Straight);
+
+        // Slowing down afterwards clamps the reported time to what is played
+        delay.set_tempo(60.0);
+        assert!((delay.delay_time_ms - Delay::MAX_DELAY_MS).abs() < 1e-3);
+        assert_eq!(delay.get_delay_samples(), delay.buffer_left.len() - 1);
+
+        let mut effect = EffectType::Delay(delay);
+        effect.apply_automated_param("sync", 0.0).unwrap();
+        effect.apply_automated_param("time", 5000.0).unwrap();
+        let EffectType::Delay(delay) = effect else { unreachable!() };
+        assert!((delay.delay_time_ms - Delay::MAX_DELAY_MS).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_parameter_automation_applies_interpolated_value() {
+        let mut manager = EffectManager::new();
+        let id = manager.create_effect(EffectType::StereoWidth(StereoWidth::new()));
+
+        let points = AutomationPoint::parse_csv("0.0,0.0;2.0,2.0");
+        assert!(manager.set_parameter_automation(id, "bogus", points.clone()).is_err());
+        manager.set_parameter_automation(id, "width", points).unwrap();
+
+        manager.apply_automation(0.5);
+        let width = |manager: &EffectManager| match &*manager.get_effect(id).unwrap().lock() {
+            EffectType::StereoWidth(sw) => sw.width,
+            _ => unreachable!(),
+        };
+        assert!((width(&manager) - 0.5).abs() < 1e-6);
+
+        // Removing the effect drops its curves
+        manager.remove_effect(id);
+        assert!(manager.get_parameter_automation(id).is_empty());
+    }
+}
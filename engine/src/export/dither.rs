@@ -1,12 +1,16 @@
 //! Dithering algorithms for bit depth reduction
 //!
-//! Implements TPDF (Triangular Probability Density Function) dithering
-//! to reduce quantization distortion when converting to lower bit depths.
+//! Implements TPDF (Triangular Probability Density Function) dithering,
+//! optionally noise-shaped, to reduce quantization distortion when
+//! converting to lower bit depths.
 
+use super::options::DitherType;
 use std::sync::atomic::{AtomicU64, Ordering};
 
-/// LFSR-based pseudo-random number generator for dithering
-/// Uses a 64-bit LFSR for high-quality random noise
+/// Pseudo-random number generator for dithering (xorshift64)
+///
+/// Successive outputs are independent enough for TPDF: a shift-register
+/// sequence read one bit at a time correlates neighbouring samples.
 struct DitherRng {
     state: AtomicU64,
 }
@@ -21,14 +25,14 @@ impl DitherRng {
 
     /// Generate next random value (0.0 to 1.0)
     fn next_f32(&self) -> f32 {
-        // LFSR with taps at 64, 63, 61, 60 (maximal period)
         let mut state = self.state.load(Ordering::Relaxed);
-        let bit = ((state >> 63) ^ (state >> 62) ^ (state >> 60) ^ (state >> 59)) & 1;
-        state = (state << 1) | bit;
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
         self.state.store(state, Ordering::Relaxed);
 
-        // Convert to 0.0-1.0 range
-        (state & 0x00FF_FFFF) as f32 / 0x00FF_FFFF as f32
+        // Top 24 bits → 0.0-1.0 range
+        (state >> 40) as f32 / 0x00FF_FFFF as f32
     }
 
     /// Generate TPDF noise (-1.0 to 1.0, triangular distribution)
@@ -40,6 +44,65 @@ impl DitherRng {
     }
 }
 
+/// Error-feedback coefficients for 2nd-order noise shaping
+///
+/// Shapes the quantization noise by (1 - z⁻¹)², moving it out of the midrange
+/// toward Nyquist where hearing is least sensitive.
+const NOISE_SHAPING_COEFFS: [f32; 2] = [2.0, -1.0];
+
+/// Stateful quantizer for stereo interleaved samples
+///
+/// Keeps the dither RNG and the noise-shaping errors between calls, so a signal
+/// converted a chunk at a time comes out the same as one converted in one go.
+pub struct Quantizer {
+    dither: DitherType,
+    full_scale: f32,
+    rng: DitherRng,
+    /// Last two quantization errors per channel (in LSBs)
+    errors: [[f32; 2]; 2],
+    /// Channel of the next sample
+    channel: usize,
+}
+
+impl Quantizer {
+    fn new(dither: DitherType, full_scale: f32, seed: u64) -> Self {
+        Self { dither, full_scale, rng: DitherRng::new(seed), errors: [[0.0; 2]; 2], channel: 0 }
+    }
+
+    /// Quantizer for 16-bit output (same dither sequence as `convert_to_16bit`)
+    pub fn for_16bit(dither: DitherType) -> Self {
+        Self::new(dither, 32767.0, 0x1234_5678)
+    }
+
+    /// Quantizer for 24-bit output (same dither sequence as `convert_to_24bit`)
+    pub fn for_24bit(dither: DitherType) -> Self {
+        Self::new(dither, 8_388_607.0, 0x8765_4321)
+    }
+
+    /// Quantize the next sample, returning the rounded value clamped to
+    /// `-(full_scale + 1)..=full_scale`
+    pub fn quantize(&mut self, sample: f32) -> f32 {
+        let scaled = sample * self.full_scale;
+        let error = &mut self.errors[self.channel];
+        self.channel ^= 1;
+        let quantized = match self.dither {
+            DitherType::None => scaled.round(),
+            DitherType::Tpdf => (scaled + self.rng.next_tpdf()).round(),
+            DitherType::NoiseShaped => {
+                let target = scaled
+                    - NOISE_SHAPING_COEFFS[0] * error[0]
+                    - NOISE_SHAPING_COEFFS[1] * error[1];
+                let quantized = (target + self.rng.next_tpdf()).round();
+                // Feed back the unclamped error so clipping can't destabilise the loop
+                error[1] = error[0];
+                error[0] = quantized - target;
+                quantized
+            }
+        };
+        quantized.clamp(-self.full_scale - 1.0, self.full_scale)
+    }
+}
+
 /// Apply TPDF dithering to a single sample for 16-bit conversion
 ///
 /// # Arguments
@@ -86,48 +149,26 @@ pub fn dither_to_24bit(sample: f32, dither_noise: f32) -> i32 {
 ///
 /// # Arguments
 /// * `samples` - Input samples (stereo interleaved, -1.0 to 1.0)
-/// * `apply_dither` - Whether to apply TPDF dithering
+/// * `dither` - Dither algorithm (`DitherType::None` for plain rounding)
 ///
 /// # Returns
 /// 16-bit samples (stereo interleaved)
-pub fn convert_to_16bit(samples: &[f32], apply_dither: bool) -> Vec<i16> {
-    let rng = DitherRng::new(0x1234_5678);
-
-    samples
-        .iter()
-        .map(|&sample| {
-            if apply_dither {
-                dither_to_16bit(sample, rng.next_tpdf())
-            } else {
-                // Simple quantization without dither
-                (sample * 32767.0).round().clamp(-32768.0, 32767.0) as i16
-            }
-        })
-        .collect()
+pub fn convert_to_16bit(samples: &[f32], dither: DitherType) -> Vec<i16> {
+    let mut quantizer = Quantizer::for_16bit(dither);
+    samples.iter().map(|&sample| quantizer.quantize(sample) as i16).collect()
 }
 
 /// Convert 32-bit float samples to 24-bit with optional dithering
 ///
 /// # Arguments
 /// * `samples` - Input samples (stereo interleaved, -1.0 to 1.0)
-/// * `apply_dither` - Whether to apply TPDF dithering
+/// * `dither` - Dither algorithm (`DitherType::None` for plain rounding)
 ///
 /// # Returns
 /// 24-bit samples as i32 (stereo interleaved, lower 24 bits used)
-pub fn convert_to_24bit(samples: &[f32], apply_dither: bool) -> Vec<i32> {
-    let rng = DitherRng::new(0x8765_4321);
-
-    samples
-        .iter()
-        .map(|&sample| {
-            if apply_dither {
-                dither_to_24bit(sample, rng.next_tpdf())
-            } else {
-                // Simple quantization without dither
-                (sample * 8_388_607.0).round().clamp(-8_388_608.0, 8_388_607.0) as i32
-            }
-        })
-        .collect()
+pub fn convert_to_24bit(samples: &[f32], dither: DitherType) -> Vec<i32> {
+    let mut quantizer = Quantizer::for_24bit(dither);
+    samples.iter().map(|&sample| quantizer.quantize(sample) as i32).collect()
 }
 
 /// Pack 24-bit samples into bytes (little-endian, 3 bytes per sample)
@@ -216,13 +257,54 @@ mod tests {
         let samples = vec![0.5f32, -0.5, 0.0, 1.0, -1.0];
 
         // 16-bit without dither
-        let i16_samples = convert_to_16bit(&samples, false);
+        let i16_samples = convert_to_16bit(&samples, DitherType::None);
         assert_eq!(i16_samples.len(), 5);
         assert_eq!(i16_samples[2], 0); // Silence
 
         // 24-bit without dither
-        let i24_samples = convert_to_24bit(&samples, false);
+        let i24_samples = convert_to_24bit(&samples, DitherType::None);
         assert_eq!(i24_samples.len(), 5);
         assert_eq!(i24_samples[2], 0); // Silence
     }
+
+    /// Amplitude (in LSBs) of the `freq` component of a 48kHz signal
+    fn tone_amplitude(signal: &[f32], freq: f32) -> f32 {
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        for (n, &x) in signal.iter().enumerate() {
+            let phase = 2.0 * std::f64::consts::PI * f64::from(freq) * n as f64 / 48000.0;
+            re += f64::from(x) * phase.cos();
+            im += f64::from(x) * phase.sin();
+        }
+        (2.0 * re.hypot(im) / signal.len() as f64) as f32
+    }
+
+    #[test]
+    fn test_dither_removes_quantization_harmonics_from_quiet_sine() {
+        // 1kHz sine at 1.5 LSB: plain rounding turns it into a stepped wave full of odd harmonics
+        let amplitude = 1.5 / 32767.0;
+        let samples: Vec<f32> = (0..48000)
+            .flat_map(|n| {
+                let s = amplitude * (2.0 * std::f32::consts::PI * 1000.0 * n as f32 / 48000.0).sin();
+                [s, s]
+            })
+            .collect();
+
+        let left = |dither| -> Vec<f32> {
+            convert_to_16bit(&samples, dither).iter().step_by(2).map(|&q| f32::from(q)).collect()
+        };
+
+        let truncated = left(DitherType::None);
+        let distortion = tone_amplitude(&truncated, 3000.0) + tone_amplitude(&truncated, 5000.0);
+        assert!(distortion > 0.1, "undithered distortion {distortion}");
+
+        for dither in [DitherType::Tpdf, DitherType::NoiseShaped] {
+            let output = left(dither);
+            let fundamental = tone_amplitude(&output, 1000.0);
+            assert!((fundamental - 1.5).abs() < 0.1, "{dither:?} fundamental {fundamental}");
+            for harmonic in [3000.0, 5000.0] {
+                let level = tone_amplitude(&output, harmonic);
+                assert!(level < 0.03, "{dither:?} harmonic {harmonic}Hz at {level} LSB");
+            }
+        }
+    }
 }
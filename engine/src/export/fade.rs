@@ -0,0 +1,88 @@
+//! Fade-in/fade-out of the whole exported mix
+//!
+//! Equal-power (sine/cosine) curves, applied to the final stereo buffer after
+//! normalization. Independent of per-clip fades.
+
+use std::f64::consts::FRAC_PI_2;
+
+/// Fade the start and end of interleaved stereo `samples`
+///
+/// Each fade is clamped to the length of the buffer; the first sample of a fade-in
+/// and the last sample of a fade-out are silent.
+pub fn apply_export_fades(samples: &mut [f32], sample_rate: u32, fade_in_seconds: f64, fade_out_seconds: f64) {
+    let frames = samples.len() / 2;
+    apply_export_fades_at(samples, 0, frames, sample_rate, fade_in_seconds, fade_out_seconds);
+}
+
+/// Fade one chunk of a mix that is `total_frames` long and is being processed a
+/// chunk at a time; `first_frame` is the chunk's position in the mix
+///
+/// Applied chunk by chunk, this gives the same result as `apply_export_fades` on
+/// the whole mix.
+pub fn apply_export_fades_at(
+    samples: &mut [f32],
+    first_frame: usize,
+    total_frames: usize,
+    sample_rate: u32,
+    fade_in_seconds: f64,
+    fade_out_seconds: f64,
+) {
+    let fade_frames = |seconds: f64| {
+        if seconds.is_finite() && seconds > 0.0 {
+            ((seconds * f64::from(sample_rate)) as usize).min(total_frames)
+        } else {
+            0
+        }
+    };
+    let fade_in = fade_frames(fade_in_seconds);
+    let fade_out = fade_frames(fade_out_seconds);
+    if fade_in == 0 && fade_out == 0 {
+        return;
+    }
+    let fade_out_start = total_frames - fade_out;
+
+    for (i, frame) in samples.chunks_exact_mut(2).enumerate() {
+        let position = first_frame + i;
+        let mut gain = 1.0;
+        if position < fade_in {
+            gain *= (FRAC_PI_2 * position as f64 / fade_in as f64).sin();
+        }
+        if position >= fade_out_start {
+            gain *= (FRAC_PI_2 * (position - fade_out_start + 1) as f64 / fade_out as f64).cos();
+        }
+        frame[0] *= gain as f32;
+        frame[1] *= gain as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fades_silence_the_ends_with_equal_power_curves() {
+        let mut samples = vec![0.8; 48000 * 2 * 2];
+        apply_export_fades(&mut samples, 48000, 0.5, 0.25);
+
+        assert!(samples[0].abs() < 1e-6 && samples[1].abs() < 1e-6);
+        let last = samples.len() - 1;
+        assert!(samples[last].abs() < 1e-6 && samples[last - 1].abs() < 1e-6);
+
+        // Halfway through the fade-in the gain is -3 dB (equal power), not -6 dB
+        let halfway = samples[12000 * 2] / 0.8;
+        assert!((halfway - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-3, "gain {halfway}");
+        // Untouched between the fades
+        assert!((samples[48000 * 2] - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_zero_fades_leave_the_mix_alone() {
+        let mut samples = vec![0.5; 200];
+        apply_export_fades(&mut samples, 48000, 0.0, 0.0);
+        assert!(samples.iter().all(|&s| (s - 0.5).abs() < 1e-9));
+
+        // A fade longer than the buffer is clamped to it
+        apply_export_fades(&mut samples, 48000, 10.0, 0.0);
+        assert!(samples[0].abs() < 1e-6 && samples[198] > 0.49);
+    }
+}
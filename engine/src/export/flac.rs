@@ -0,0 +1,198 @@
+//! FLAC file export (lossless)
+//!
+//! Encoded with the pure-Rust `flacenc` crate, so it needs no external tools.
+
+use super::dither::{convert_to_16bit, convert_to_24bit};
+use super::fade::apply_export_fades;
+use super::normalize::{normalize_lufs, normalize_peak};
+use super::options::{ExportFormat, ExportOptions, ExportResult, FlacBitDepth};
+use super::resample::{mono_to_stereo, resample_stereo, stereo_to_mono};
+use super::wav::ENGINE_SAMPLE_RATE;
+use flacenc::component::BitRepr;
+use flacenc::error::Verify;
+use std::path::Path;
+
+/// Export audio samples to FLAC file
+///
+/// # Arguments
+/// * `samples` - Stereo interleaved f32 samples from `render_offline`
+/// * `output_path` - Path to output FLAC file
+/// * `options` - Export options (bit depth, sample rate, normalize, dither)
+///
+/// # Returns
+/// Export result with file info
+pub fn export_flac(
+    samples: &[f32],
+    output_path: &Path,
+    options: &ExportOptions,
+) -> Result<ExportResult, String> {
+    eprintln!("🎵 [FLAC Export] Starting export to {}", output_path.display());
+
+    // Get bit depth from options
+    let ExportFormat::Flac { bit_depth } = options.format else {
+        return Err("export_flac called with non-FLAC format".to_string());
+    };
+
+    // Make a mutable copy of samples for processing
+    let mut processed = samples.to_vec();
+
+    // Apply mono mixdown if requested
+    if options.mono {
+        eprintln!("🔊 [FLAC Export] Converting to mono");
+        processed = stereo_to_mono(&processed);
+        processed = mono_to_stereo(&processed); // Convert back for stereo output file
+    }
+
+    // Loudness normalization runs at the engine rate the meter is calibrated for
+    let loudness_target = options.loudness_target();
+    if let Some(target_lufs) = loudness_target {
+        eprintln!("📊 [FLAC Export] Normalizing to {target_lufs:.1} LUFS");
+        normalize_lufs(&mut processed, target_lufs);
+    }
+
+    // Apply sample rate conversion if needed
+    if options.sample_rate != ENGINE_SAMPLE_RATE {
+        eprintln!(
+            "🔄 [FLAC Export] Resampling {}Hz → {}Hz",
+            ENGINE_SAMPLE_RATE, options.sample_rate
+        );
+        processed = resample_stereo(&processed, ENGINE_SAMPLE_RATE, options.sample_rate)?;
+    }
+
+    // Apply peak normalization if requested (a loudness target takes precedence)
+    if options.normalize && loudness_target.is_none() {
+        eprintln!("📊 [FLAC Export] Normalizing to -0.1 dBFS");
+        normalize_peak(&mut processed, -0.1);
+    }
+
+    // Fade the final mix in/out after normalization
+    apply_export_fades(&mut processed, options.sample_rate, options.fade_in_seconds, options.fade_out_seconds);
+
+    // Calculate duration
+    let num_frames = processed.len() / 2;
+    let duration = num_frames as f64 / f64::from(options.sample_rate);
+
+    // Quantize with optional dithering, then encode
+    let pcm: Vec<i32> = match bit_depth {
+        FlacBitDepth::Int16 => convert_to_16bit(&processed, options.effective_dither())
+            .into_iter()
+            .map(i32::from)
+            .collect(),
+        FlacBitDepth::Int24 => convert_to_24bit(&processed, options.effective_dither()),
+    };
+    let encoded = encode_flac(&pcm, options.sample_rate, bit_depth.bits_per_sample())?;
+    std::fs::write(output_path, encoded).map_err(|e| format!("Failed to write FLAC file: {e}"))?;
+
+    // Get file size
+    let file_size = std::fs::metadata(output_path).map_or(0, |m| m.len());
+
+    let format_description = format!("FLAC {}-bit", bit_depth.bits_per_sample());
+
+    eprintln!(
+        "✅ [FLAC Export] Complete: {:.2}s, {:.2} MB, {}",
+        duration,
+        file_size as f64 / 1024.0 / 1024.0,
+        format_description
+    );
+
+    Ok(ExportResult::new(
+        output_path.to_string_lossy().to_string(),
+        file_size,
+        duration,
+        options.sample_rate,
+        format_description,
+    ))
+}
+
+/// Encode stereo interleaved integer PCM to a complete FLAC stream
+///
+/// `bits_per_sample` must be 16 or 24 and every sample must fit in it. The
+/// STREAMINFO block carries the MD5 of the unencoded audio.
+pub fn encode_flac(interleaved: &[i32], sample_rate: u32, bits_per_sample: u32) -> Result<Vec<u8>, String> {
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| format!("Invalid FLAC encoder settings: {e}"))?;
+    let source = flacenc::source::MemSource::from_samples(interleaved, 2, bits_per_sample as usize, sample_rate as usize);
+    let mut stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| format!("FLAC encoding failed: {e:?}"))?;
+    // The shorter last frame lowers the minimum block size, which makes decoders
+    // (Symphonia among them) treat the stream as variable-block; the spec leaves
+    // the last frame out of it
+    stream
+        .stream_info_mut()
+        .set_block_sizes(config.block_size, config.block_size)
+        .map_err(|e| format!("FLAC encoding failed: {e}"))?;
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream.write(&mut sink).map_err(|e| format!("FLAC encoding failed: {e}"))?;
+    Ok(sink.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use md5::{Digest, Md5};
+    use std::env;
+
+    fn create_test_samples() -> Vec<f32> {
+        // 0.25 seconds of stereo sine waves at 48kHz (different pitch per channel)
+        (0..12000)
+            .flat_map(|i| {
+                let t = i as f32 / 48000.0;
+                let left = 0.5 * (t * 440.0 * 2.0 * std::f32::consts::PI).sin();
+                let right = 0.3 * (t * 660.0 * 2.0 * std::f32::consts::PI).sin();
+                [left, right]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_streaminfo_md5_covers_raw_samples() {
+        // "fLaC" + block header, then the MD5 closes the 34-byte STREAMINFO
+        let md5_at = |stream: &[u8]| stream[26..42].to_vec();
+        let stream = encode_flac(&[1, -1, 256, 0], 48000, 16).unwrap();
+        let expected = Md5::digest([0x01, 0x00, 0xFF, 0xFF, 0x00, 0x01, 0x00, 0x00]);
+        assert_eq!(md5_at(&stream), expected.to_vec());
+
+        let stream = encode_flac(&[-2, 0x12_3456], 48000, 24).unwrap();
+        let expected = Md5::digest([0xFE, 0xFF, 0xFF, 0x56, 0x34, 0x12]);
+        assert_eq!(md5_at(&stream), expected.to_vec());
+    }
+
+    #[test]
+    fn test_export_flac_round_trip() {
+        let samples = create_test_samples();
+        let output_path = env::temp_dir().join("boojy_test_export.flac");
+        let options = ExportOptions::flac(FlacBitDepth::Int16).with_sample_rate(48000);
+
+        let result = export_flac(&samples, &output_path, &options).unwrap();
+        assert_eq!(result.format_description, "FLAC 16-bit");
+        // Lossless but compressed: smaller than the raw 16-bit PCM
+        assert!(result.file_size > 0 && result.file_size < (samples.len() * 2) as u64);
+
+        let decoded = crate::audio_file::load_audio_file(&output_path).unwrap();
+        assert_eq!(decoded.channels, 2);
+        assert_eq!(decoded.samples.len(), samples.len());
+        for (original, restored) in samples.iter().zip(&decoded.samples) {
+            assert!((original - restored).abs() < 2.0 / 32768.0);
+        }
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_export_flac_24bit_round_trip() {
+        let samples = create_test_samples();
+        let output_path = env::temp_dir().join("boojy_test_export_24.flac");
+        let options = ExportOptions::flac(FlacBitDepth::Int24).with_sample_rate(48000);
+
+        export_flac(&samples, &output_path, &options).unwrap();
+
+        let decoded = crate::audio_file::load_audio_file(&output_path).unwrap();
+        assert_eq!(decoded.samples.len(), samples.len());
+        for (original, restored) in samples.iter().zip(&decoded.samples) {
+            assert!((original - restored).abs() < 1e-5);
+        }
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+}
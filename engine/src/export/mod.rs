@@ -3,9 +3,13 @@
 //! This module provides comprehensive audio export functionality including:
 //! - WAV export with configurable bit depth (16-bit, 24-bit, 32-bit float)
 //! - MP3 export with configurable bitrate (128, 192, 320 kbps)
+//! - FLAC export (lossless, 16/24-bit) with the pure-Rust `flacenc` encoder
+//! - Ogg Vorbis export with configurable quality (0-10) through the bundled libvorbis
 //! - Sample rate conversion (48kHz to 44.1kHz)
 //! - Dithering for bit depth reduction
 //! - Normalization (peak and LUFS-based)
+//! - Render tail control (fixed or until the mix falls silent)
+//! - Fade-in/fade-out of the final mix
 //! - Stem export (per-track rendering)
 //! - Metadata embedding (ID3 tags)
 //! - Progress tracking (polling-based)
@@ -13,9 +17,13 @@
 mod options;
 mod wav;
 mod mp3;
+mod flac;
+mod ogg;
 mod dither;
 mod resample;
 mod normalize;
+mod tail;
+mod fade;
 mod stems;
 mod metadata;
 mod progress;
@@ -23,9 +31,13 @@ mod progress;
 pub use options::*;
 pub use wav::*;
 pub use mp3::*;
+pub use flac::*;
+pub use ogg::*;
 pub use dither::*;
 pub use resample::*;
 pub use normalize::*;
+pub use tail::*;
+pub use fade::*;
 pub use stems::*;
 pub use metadata::*;
 pub use progress::*;
@@ -3,7 +3,8 @@
 //! Uses ffmpeg for encoding, which is commonly available on macOS/Linux.
 //! Falls back to WAV export if ffmpeg is not available.
 
-use super::normalize::normalize_peak;
+use super::fade::apply_export_fades;
+use super::normalize::{normalize_lufs, normalize_peak};
 use super::options::{ExportOptions, ExportResult, Mp3Bitrate};
 use super::resample::{mono_to_stereo, resample_stereo, stereo_to_mono};
 use super::wav::ENGINE_SAMPLE_RATE;
@@ -49,7 +50,7 @@ pub fn export_mp3(
     // Get bitrate from options
     let bitrate = match &options.format {
         super::options::ExportFormat::Mp3 { bitrate } => *bitrate,
-        super::options::ExportFormat::Wav { .. } => return Err("export_mp3 called with non-MP3 format".to_string()),
+        _ => return Err("export_mp3 called with non-MP3 format".to_string()),
     };
 
     // Make a mutable copy of samples for processing
@@ -62,6 +63,13 @@ pub fn export_mp3(
         processed = mono_to_stereo(&processed); // Convert back for stereo encoding
     }
 
+    // Loudness normalization runs at the engine rate the meter is calibrated for
+    let loudness_target = options.loudness_target();
+    if let Some(target_lufs) = loudness_target {
+        eprintln!("📊 [MP3 Export] Normalizing to {target_lufs:.1} LUFS");
+        normalize_lufs(&mut processed, target_lufs);
+    }
+
     // Apply sample rate conversion if needed
     if options.sample_rate != ENGINE_SAMPLE_RATE {
         eprintln!(
@@ -71,12 +79,15 @@ pub fn export_mp3(
         processed = resample_stereo(&processed, ENGINE_SAMPLE_RATE, options.sample_rate)?;
     }
 
-    // Apply normalization if requested
-    if options.normalize {
+    // Apply peak normalization if requested (a loudness target takes precedence)
+    if options.normalize && loudness_target.is_none() {
         eprintln!("📊 [MP3 Export] Normalizing to -0.1 dBFS");
         normalize_peak(&mut processed, -0.1);
     }
 
+    // Fade the final mix in/out after normalization
+    apply_export_fades(&mut processed, options.sample_rate, options.fade_in_seconds, options.fade_out_seconds);
+
     // Calculate duration
     let num_frames = processed.len() / 2;
     let duration = num_frames as f64 / f64::from(options.sample_rate);
@@ -121,6 +132,32 @@ fn encode_mp3_ffmpeg(
         bitrate.kbps()
     );
 
+    encode_with_ffmpeg(
+        samples,
+        output_path,
+        sample_rate,
+        &[
+            "-b:a",
+            &format!("{}k", bitrate.kbps()), // Audio bitrate
+            "-codec:a",
+            "libmp3lame", // Use LAME encoder
+            "-q:a",
+            "0", // Best quality VBR setting
+        ],
+    )?;
+
+    eprintln!("✅ [MP3 Encode] ffmpeg encoding complete");
+
+    Ok(())
+}
+
+/// Pipe stereo samples to ffmpeg as 16-bit PCM and encode with the given codec arguments
+pub(super) fn encode_with_ffmpeg(
+    samples: &[f32],
+    output_path: &Path,
+    sample_rate: u32,
+    codec_args: &[&str],
+) -> Result<(), String> {
     // Convert f32 samples to i16 PCM bytes for piping to ffmpeg
     let mut pcm_bytes: Vec<u8> = Vec::with_capacity(samples.len() * 2);
     for &sample in samples {
@@ -130,7 +167,7 @@ fn encode_mp3_ffmpeg(
 
     // Build ffmpeg command
     // Input: raw 16-bit signed little-endian PCM, stereo, at specified sample rate
-    // Output: MP3 with specified bitrate
+    // Output: encoded with `codec_args`
     let mut child = Command::new("ffmpeg")
         .args([
             "-y", // Overwrite output file
@@ -142,14 +179,9 @@ fn encode_mp3_ffmpeg(
             "2", // Input channels (stereo)
             "-i",
             "pipe:0", // Read from stdin
-            "-b:a",
-            &format!("{}k", bitrate.kbps()), // Audio bitrate
-            "-codec:a",
-            "libmp3lame", // Use LAME encoder
-            "-q:a",
-            "0", // Best quality VBR setting
-            output_path.to_str().unwrap_or("output.mp3"),
         ])
+        .args(codec_args)
+        .arg(output_path)
         .stdin(std::process::Stdio::piped())
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::piped())
@@ -173,8 +205,6 @@ fn encode_mp3_ffmpeg(
         return Err(format!("ffmpeg encoding failed: {stderr}"));
     }
 
-    eprintln!("✅ [MP3 Encode] ffmpeg encoding complete");
-
     Ok(())
 }
 
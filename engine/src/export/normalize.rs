@@ -3,6 +3,9 @@
 //! Provides peak normalization and LUFS-based loudness normalization
 //! for mastering and platform-specific export.
 
+use crate::effects::{Effect, Limiter};
+use crate::loudness::LoudnessMeter;
+
 /// Peak normalize audio to a target amplitude
 ///
 /// # Arguments
@@ -137,17 +140,24 @@ fn apply_k_weighting(samples: &[f64], sample_rate: u32) -> Vec<f64> {
     output
 }
 
-/// Normalize audio to target LUFS
+/// Ceiling for the safety limiter after loudness normalization (dBFS)
+///
+/// Leaves headroom for inter-sample peaks and resampling overshoot.
+pub const LOUDNESS_CEILING_DB: f32 = -1.0;
+
+/// Normalize audio to target integrated loudness (LUFS)
+///
+/// Measures with the same gated BS.1770 meter as the master loudness display,
+/// applies a single gain, then runs the limiter so the boost can't clip.
 ///
 /// # Arguments
-/// * `samples` - Stereo interleaved audio samples (modified in place)
-/// * `sample_rate` - Sample rate in Hz
+/// * `samples` - Stereo interleaved audio samples at the engine rate (modified in place)
 /// * `target_lufs` - Target loudness in LUFS (e.g., -14.0 for Spotify)
 ///
 /// # Returns
-/// The gain applied in dB
-pub fn normalize_lufs(samples: &mut [f32], sample_rate: u32, target_lufs: f64) -> f64 {
-    let current_lufs = calculate_lufs(samples, sample_rate);
+/// The gain applied in dB (0.0 if the audio is too quiet to measure)
+pub fn normalize_lufs(samples: &mut [f32], target_lufs: f32) -> f32 {
+    let current_lufs = LoudnessMeter::measure_integrated(samples);
 
     if current_lufs < -60.0 {
         eprintln!("⚠️ [LUFS] Audio too quiet to measure ({current_lufs:.1} LUFS)");
@@ -155,33 +165,35 @@ pub fn normalize_lufs(samples: &mut [f32], sample_rate: u32, target_lufs: f64) -
     }
 
     let gain_db = target_lufs - current_lufs;
-    let gain_linear = 10.0f64.powf(gain_db / 20.0) as f32;
+    let gain_linear = 10.0f32.powf(gain_db / 20.0);
 
     eprintln!(
         "📊 [LUFS] Current: {current_lufs:.1} LUFS, Target: {target_lufs:.1} LUFS, Gain: {gain_db:.1} dB"
     );
 
-    // Apply gain
-    for sample in samples.iter_mut() {
-        *sample *= gain_linear;
+    let mut limiter = Limiter::new();
+    limiter.set_threshold_db(LOUDNESS_CEILING_DB);
+    let mut max_reduction_db = 0.0f32;
+    // Write each output frame back over the input it belongs to, skipping the lookahead
+    // delay, then flush the frames still in the delay line with silence
+    let latency = limiter.latency_samples();
+    let frame_count = samples.len() / 2;
+    for input_index in 0..frame_count + latency {
+        let (left, right) = if input_index < frame_count {
+            (samples[input_index * 2] * gain_linear, samples[input_index * 2 + 1] * gain_linear)
+        } else {
+            (0.0, 0.0)
+        };
+        let (left, right) = limiter.process_frame(left, right);
+        max_reduction_db = max_reduction_db.max(limiter.get_gain_reduction_db());
+        if let Some(output_index) = input_index.checked_sub(latency) {
+            samples[output_index * 2] = left;
+            samples[output_index * 2 + 1] = right;
+        }
     }
 
-    // Check for clipping and apply limiter if needed
-    let peak_after: f32 = samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
-
-    if peak_after > 1.0 {
-        eprintln!(
-            "⚠️ [LUFS] Clipping detected (peak {peak_after:.2}), applying limiter"
-        );
-
-        // Simple soft-clipping limiter
-        for sample in samples.iter_mut() {
-            if *sample > 0.99 {
-                *sample = 0.99 + (*sample - 0.99).tanh() * 0.01;
-            } else if *sample < -0.99 {
-                *sample = -0.99 + (*sample + 0.99).tanh() * 0.01;
-            }
-        }
+    if max_reduction_db > 0.0 {
+        eprintln!("⚠️ [LUFS] Limiter engaged (up to {max_reduction_db:.1} dB reduction)");
     }
 
     gain_db
@@ -258,6 +270,31 @@ mod tests {
         assert!(lufs > -40.0);
     }
 
+    #[test]
+    fn test_lufs_normalize_hits_target_without_clipping() {
+        // Quiet 3-second sine, pushed up to -14 LUFS
+        let sample_rate = 48000u32;
+        let mut samples = Vec::with_capacity(sample_rate as usize * 6);
+        for i in 0..sample_rate * 3 {
+            let t = i as f32 / sample_rate as f32;
+            let val = 0.05 * (t * 1000.0 * 2.0 * std::f32::consts::PI).sin();
+            samples.push(val);
+            samples.push(val);
+        }
+
+        let gain_db = normalize_lufs(&mut samples, -14.0);
+        assert!(gain_db > 10.0);
+        let measured = LoudnessMeter::measure_integrated(&samples);
+        assert!((measured + 14.0).abs() < 0.5, "measured {measured}");
+
+        // A target louder than the material can take is caught by the limiter
+        let gain_db = normalize_lufs(&mut samples, 0.0);
+        assert!(gain_db > 10.0);
+        let ceiling = 10.0f32.powf(LOUDNESS_CEILING_DB / 20.0);
+        let peak = samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+        assert!(peak <= ceiling + 1e-4, "peak {peak}");
+    }
+
     #[test]
     fn test_limiter() {
         let mut samples = vec![1.5f32, -1.2, 0.5, 0.8];
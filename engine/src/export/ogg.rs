@@ -0,0 +1,182 @@
+//! Ogg Vorbis file export
+//!
+//! Encoded with the libvorbis (aoTuV) build bundled by the `vorbis_rs` crate, so
+//! unlike MP3 it needs no ffmpeg. Not available in the web build.
+
+use super::fade::apply_export_fades;
+use super::normalize::{normalize_lufs, normalize_peak};
+use super::options::{ExportFormat, ExportOptions, ExportResult};
+use super::resample::{mono_to_stereo, resample_stereo, stereo_to_mono};
+use super::wav::ENGINE_SAMPLE_RATE;
+use std::path::Path;
+
+/// Frames handed to the Vorbis encoder at a time
+const VORBIS_BLOCK_FRAMES: usize = 4096;
+
+/// Export audio samples to Ogg Vorbis file
+///
+/// # Arguments
+/// * `samples` - Stereo interleaved f32 samples from `render_offline`
+/// * `output_path` - Path to output OGG file
+/// * `options` - Export options (quality, sample rate, normalize)
+///
+/// # Returns
+/// Export result with file info
+pub fn export_ogg(
+    samples: &[f32],
+    output_path: &Path,
+    options: &ExportOptions,
+) -> Result<ExportResult, String> {
+    eprintln!("🎵 [OGG Export] Starting export to {}", output_path.display());
+
+    // Get quality from options
+    let ExportFormat::Ogg { quality } = options.format else {
+        return Err("export_ogg called with non-OGG format".to_string());
+    };
+
+    // Make a mutable copy of samples for processing
+    let mut processed = samples.to_vec();
+
+    // Apply mono mixdown if requested
+    if options.mono {
+        eprintln!("🔊 [OGG Export] Converting to mono");
+        processed = stereo_to_mono(&processed);
+        processed = mono_to_stereo(&processed); // Convert back for stereo encoding
+    }
+
+    // Loudness normalization runs at the engine rate the meter is calibrated for
+    let loudness_target = options.loudness_target();
+    if let Some(target_lufs) = loudness_target {
+        eprintln!("📊 [OGG Export] Normalizing to {target_lufs:.1} LUFS");
+        normalize_lufs(&mut processed, target_lufs);
+    }
+
+    // Apply sample rate conversion if needed
+    if options.sample_rate != ENGINE_SAMPLE_RATE {
+        eprintln!(
+            "🔄 [OGG Export] Resampling {}Hz → {}Hz",
+            ENGINE_SAMPLE_RATE, options.sample_rate
+        );
+        processed = resample_stereo(&processed, ENGINE_SAMPLE_RATE, options.sample_rate)?;
+    }
+
+    // Apply peak normalization if requested (a loudness target takes precedence)
+    if options.normalize && loudness_target.is_none() {
+        eprintln!("📊 [OGG Export] Normalizing to -0.1 dBFS");
+        normalize_peak(&mut processed, -0.1);
+    }
+
+    // Fade the final mix in/out after normalization
+    apply_export_fades(&mut processed, options.sample_rate, options.fade_in_seconds, options.fade_out_seconds);
+
+    // Calculate duration
+    let num_frames = processed.len() / 2;
+    let duration = num_frames as f64 / f64::from(options.sample_rate);
+
+    encode_vorbis(&processed, output_path, options.sample_rate, quality)?;
+
+    // Get file size
+    let file_size = std::fs::metadata(output_path).map_or(0, |m| m.len());
+
+    let format_description = format!("Ogg Vorbis q{quality}");
+
+    eprintln!(
+        "✅ [OGG Export] Complete: {:.2}s, {:.2} MB, {}",
+        duration,
+        file_size as f64 / 1024.0 / 1024.0,
+        format_description
+    );
+
+    Ok(ExportResult::new(
+        output_path.to_string_lossy().to_string(),
+        file_size,
+        duration,
+        options.sample_rate,
+        format_description,
+    ))
+}
+
+/// Encode stereo interleaved samples to an Ogg Vorbis file at VBR `quality`
+/// (0-10, as in `oggenc -q`)
+#[cfg(not(target_arch = "wasm32"))]
+fn encode_vorbis(interleaved: &[f32], output_path: &Path, sample_rate: u32, quality: u8) -> Result<(), String> {
+    use std::io::Write;
+    use std::num::{NonZeroU32, NonZeroU8};
+    use vorbis_rs::{VorbisBitrateManagementStrategy, VorbisEncoderBuilder};
+
+    let sample_rate = NonZeroU32::new(sample_rate).ok_or("Invalid sample rate for Ogg Vorbis export")?;
+    let channels = NonZeroU8::new(2).ok_or("Invalid channel count for Ogg Vorbis export")?;
+    let file = std::fs::File::create(output_path).map_err(|e| format!("Failed to create Ogg file: {e}"))?;
+    let mut encoder = VorbisEncoderBuilder::new(sample_rate, channels, std::io::BufWriter::new(file))
+        .map_err(|e| format!("Failed to start Vorbis encoder: {e}"))?
+        .bitrate_management_strategy(VorbisBitrateManagementStrategy::QualityVbr {
+            target_quality: f32::from(quality) / 10.0,
+        })
+        .build()
+        .map_err(|e| format!("Failed to start Vorbis encoder: {e}"))?;
+
+    let mut left = Vec::with_capacity(VORBIS_BLOCK_FRAMES);
+    let mut right = Vec::with_capacity(VORBIS_BLOCK_FRAMES);
+    for block in interleaved.chunks(VORBIS_BLOCK_FRAMES * 2) {
+        left.clear();
+        right.clear();
+        for frame in block.chunks_exact(2) {
+            left.push(frame[0]);
+            right.push(frame[1]);
+        }
+        encoder
+            .encode_audio_block([&left, &right])
+            .map_err(|e| format!("Vorbis encoding failed: {e}"))?;
+    }
+    encoder
+        .finish()
+        .map_err(|e| format!("Vorbis encoding failed: {e}"))?
+        .flush()
+        .map_err(|e| format!("Failed to write Ogg file: {e}"))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn encode_vorbis(_interleaved: &[f32], _output_path: &Path, _sample_rate: u32, _quality: u8) -> Result<(), String> {
+    Err("Ogg Vorbis export is not available in the web build".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_export_ogg_round_trip() {
+        // 0.5 seconds of a 440 Hz stereo sine at 48kHz
+        let samples: Vec<f32> = (0..24000)
+            .flat_map(|i| {
+                let s = 0.5 * (i as f32 / 48000.0 * 440.0 * std::f32::consts::TAU).sin();
+                [s, s]
+            })
+            .collect();
+        let output_path = env::temp_dir().join("boojy_test_export.ogg");
+        let options = ExportOptions::ogg(6).with_sample_rate(48000);
+
+        let result = export_ogg(&samples, &output_path, &options).unwrap();
+        assert_eq!(result.format_description, "Ogg Vorbis q6");
+        // Lossy: far smaller than the raw 16-bit PCM
+        assert!(result.file_size > 0 && result.file_size < (samples.len() * 2 / 4) as u64);
+
+        // The last page's granule position marks the end of the audio exactly
+        let bytes = std::fs::read(&output_path).unwrap();
+        let last_page = bytes.windows(4).rposition(|w| w == b"OggS").unwrap();
+        let granule = u64::from_le_bytes(bytes[last_page + 6..last_page + 14].try_into().unwrap());
+        assert_eq!(granule, 24000);
+
+        // Symphonia doesn't trim the final packet's padding, so compare what overlaps
+        let decoded = crate::audio_file::load_audio_file(&output_path).unwrap();
+        assert_eq!(decoded.channels, 2);
+        assert_eq!(decoded.sample_rate, 48000);
+        assert!(decoded.samples.len() >= samples.len());
+        let rms = |s: &[f32]| (s.iter().map(|x| x * x).sum::<f32>() / s.len() as f32).sqrt();
+        let (original, restored) = (rms(&samples), rms(&decoded.samples[..samples.len()]));
+        assert!((original - restored).abs() < original * 0.05, "rms {restored} vs {original}");
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+}
@@ -35,6 +35,47 @@ impl WavBitDepth {
     }
 }
 
+/// FLAC bit depth options
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum FlacBitDepth {
+    /// 16-bit integer (CD quality)
+    #[default]
+    Int16,
+    /// 24-bit integer (studio quality)
+    Int24,
+}
+
+impl FlacBitDepth {
+    /// Get bits per sample
+    pub fn bits_per_sample(&self) -> u32 {
+        match self {
+            FlacBitDepth::Int16 => 16,
+            FlacBitDepth::Int24 => 24,
+        }
+    }
+
+    /// Parse from integer bit depth
+    pub fn from_bits(bits: i32) -> Option<Self> {
+        match bits {
+            16 => Some(Self::Int16),
+            24 => Some(Self::Int24),
+            _ => None,
+        }
+    }
+}
+
+/// Seconds rendered past the last clip for reverb/delay tails (unless set otherwise)
+pub const DEFAULT_TAIL_SECONDS: f64 = 1.0;
+/// Longest tail rendered past the last clip (fixed or automatic)
+pub const MAX_TAIL_SECONDS: f64 = 30.0;
+
+fn default_tail_seconds() -> f64 {
+    DEFAULT_TAIL_SECONDS
+}
+
+/// Highest Ogg Vorbis quality level (0 = smallest, 10 = best)
+pub const MAX_OGG_QUALITY: u8 = 10;
+
 /// MP3 bitrate options
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum Mp3Bitrate {
@@ -68,6 +109,18 @@ impl Mp3Bitrate {
     }
 }
 
+/// Dither algorithm applied when quantizing to 16/24-bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum DitherType {
+    /// Plain rounding
+    None,
+    /// Triangular (TPDF) dither, ±1 LSB
+    #[default]
+    Tpdf,
+    /// TPDF dither with 2nd-order noise shaping (noise pushed toward high frequencies)
+    NoiseShaped,
+}
+
 /// Platform loudness targets (LUFS)
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 pub enum PlatformTarget {
@@ -107,6 +160,10 @@ pub enum ExportFormat {
     Wav { bit_depth: WavBitDepth },
     /// MP3 format with specified bitrate
     Mp3 { bitrate: Mp3Bitrate },
+    /// FLAC (lossless) with specified bit depth
+    Flac { bit_depth: FlacBitDepth },
+    /// Ogg Vorbis with quality level 0-10
+    Ogg { quality: u8 },
 }
 
 impl Default for ExportFormat {
@@ -124,18 +181,51 @@ pub struct ExportOptions {
     pub format: ExportFormat,
     /// Sample rate in Hz (44100 or 48000)
     pub sample_rate: u32,
-    /// Apply peak normalization to -0.1 dBFS
+    /// Apply peak normalization to -0.1 dBFS (ignored when a loudness target is set)
     pub normalize: bool,
+    /// Normalize to this integrated loudness (LUFS), limited to -1 dBFS
+    ///
+    /// Applied to the rendered mix (after effects) before resampling and dither.
+    #[serde(default)]
+    pub normalize_lufs: Option<f32>,
     /// Apply dithering when reducing bit depth
     pub dither: bool,
+    /// Dither algorithm used when `dither` is on
+    #[serde(default)]
+    pub dither_type: DitherType,
     /// Mix down to mono
     pub mono: bool,
     /// Start time for loop region export (None = 0)
     pub start_time: Option<f64>,
     /// End time for loop region export (None = project end)
     pub end_time: Option<f64>,
+    /// Render a second ahead of `start_time` (discarded) so tails from before it carry in
+    #[serde(default)]
+    pub pre_roll: bool,
     /// Platform loudness target
     pub platform_target: PlatformTarget,
+    /// Seconds rendered past the last clip so reverb/delay tails can decay
+    #[serde(default = "default_tail_seconds")]
+    pub export_tail_seconds: f64,
+    /// Render the tail until the master output stays silent (see `trim_silent_tail`)
+    /// instead of for `export_tail_seconds`; capped at `MAX_TAIL_SECONDS`.
+    /// Applies to whole-project mixdowns: range exports end where they are told to and
+    /// stems (which must line up) use the default tail.
+    #[serde(default)]
+    pub auto_tail: bool,
+    /// Equal-power fade-in over the start of the export (seconds, 0 = none)
+    #[serde(default)]
+    pub fade_in_seconds: f64,
+    /// Equal-power fade-out over the end of the export (seconds, 0 = none)
+    #[serde(default)]
+    pub fade_out_seconds: f64,
+    /// Stem export: render each track through its groups and the returns its sends
+    /// feed, so the stem carries its wet signal and the stems sum to the mix
+    #[serde(default)]
+    pub stems_with_sends: bool,
+    /// Stem export: also export group and return tracks as stems of their output
+    #[serde(default)]
+    pub stems_include_buses: bool,
 }
 
 impl Default for ExportOptions {
@@ -144,11 +234,20 @@ impl Default for ExportOptions {
             format: ExportFormat::default(),
             sample_rate: 44100,
             normalize: false,
+            normalize_lufs: None,
             dither: false,
+            dither_type: DitherType::Tpdf,
             mono: false,
             start_time: None,
             end_time: None,
+            pre_roll: false,
             platform_target: PlatformTarget::None,
+            export_tail_seconds: DEFAULT_TAIL_SECONDS,
+            auto_tail: false,
+            fade_in_seconds: 0.0,
+            fade_out_seconds: 0.0,
+            stems_with_sends: false,
+            stems_include_buses: false,
         }
     }
 }
@@ -170,6 +269,22 @@ impl ExportOptions {
         }
     }
 
+    /// Create FLAC export options with default settings
+    pub fn flac(bit_depth: FlacBitDepth) -> Self {
+        Self {
+            format: ExportFormat::Flac { bit_depth },
+            ..Default::default()
+        }
+    }
+
+    /// Create Ogg Vorbis export options (quality clamped to 0-10)
+    pub fn ogg(quality: u8) -> Self {
+        Self {
+            format: ExportFormat::Ogg { quality: quality.min(MAX_OGG_QUALITY) },
+            ..Default::default()
+        }
+    }
+
     /// Set sample rate
     pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
         self.sample_rate = sample_rate;
@@ -188,6 +303,22 @@ impl ExportOptions {
         self
     }
 
+    /// Select the dither algorithm (`DitherType::None` turns dithering off)
+    pub fn with_dither_type(mut self, dither_type: DitherType) -> Self {
+        self.dither = dither_type != DitherType::None;
+        self.dither_type = dither_type;
+        self
+    }
+
+    /// Dither algorithm to quantize with, taking the `dither` switch into account
+    pub fn effective_dither(&self) -> DitherType {
+        if self.dither {
+            self.dither_type
+        } else {
+            DitherType::None
+        }
+    }
+
     /// Enable mono mixdown
     pub fn with_mono(mut self, mono: bool) -> Self {
         self.mono = mono;
@@ -201,6 +332,87 @@ impl ExportOptions {
         self
     }
 
+    /// Enable pre-roll ahead of a range export
+    pub fn with_pre_roll(mut self, pre_roll: bool) -> Self {
+        self.pre_roll = pre_roll;
+        self
+    }
+
+    /// Resolve the export range against the project duration
+    ///
+    /// Returns None for a whole-project export, or the `(start, end)` window in seconds.
+    pub fn export_range(&self, project_duration: f64) -> Result<Option<(f64, f64)>, String> {
+        if self.start_time.is_none() && self.end_time.is_none() {
+            return Ok(None);
+        }
+        let start = self.start_time.unwrap_or(0.0);
+        let end = self.end_time.unwrap_or(project_duration);
+        if !start.is_finite() || !end.is_finite() || start < 0.0 {
+            return Err(format!("Invalid export range: {start} to {end}"));
+        }
+        if end <= start {
+            return Err(format!("Export range end ({end}) must be after start ({start})"));
+        }
+        Ok(Some((start, end)))
+    }
+
+    /// Set an integrated loudness target in LUFS (None = no loudness normalization)
+    pub fn with_normalize_lufs(mut self, target_lufs: Option<f32>) -> Self {
+        self.normalize_lufs = target_lufs;
+        self
+    }
+
+    /// Loudness target to normalize to: `normalize_lufs`, else the platform target
+    pub fn loudness_target(&self) -> Option<f32> {
+        self.normalize_lufs
+            .or_else(|| self.platform_target.target_lufs().map(|lufs| lufs as f32))
+    }
+
+    /// Render a fixed tail past the last clip (clamped to 0..`MAX_TAIL_SECONDS`)
+    pub fn with_tail_seconds(mut self, seconds: f64) -> Self {
+        self.export_tail_seconds = seconds;
+        self.auto_tail = false;
+        self
+    }
+
+    /// Render the tail until the mix falls silent
+    pub fn with_auto_tail(mut self, auto_tail: bool) -> Self {
+        self.auto_tail = auto_tail;
+        self
+    }
+
+    /// Seconds to render past the last clip (the cap in auto mode, trimmed afterwards)
+    pub fn render_tail_seconds(&self) -> f64 {
+        if self.auto_tail {
+            MAX_TAIL_SECONDS
+        } else if self.export_tail_seconds.is_finite() {
+            self.export_tail_seconds.clamp(0.0, MAX_TAIL_SECONDS)
+        } else {
+            DEFAULT_TAIL_SECONDS
+        }
+    }
+
+    /// Whether the mix can be encoded while it renders, a chunk at a time
+    ///
+    /// Normalization and the auto tail need the whole mix before anything is written.
+    pub fn can_stream(&self) -> bool {
+        !self.normalize && self.loudness_target().is_none() && !self.auto_tail
+    }
+
+    /// Fade the whole export in and out (seconds, 0 = no fade)
+    pub fn with_fades(mut self, fade_in_seconds: f64, fade_out_seconds: f64) -> Self {
+        self.fade_in_seconds = fade_in_seconds;
+        self.fade_out_seconds = fade_out_seconds;
+        self
+    }
+
+    /// Choose how stems handle sends and buses (see `stems_with_sends`)
+    pub fn with_stem_routing(mut self, with_sends: bool, include_buses: bool) -> Self {
+        self.stems_with_sends = with_sends;
+        self.stems_include_buses = include_buses;
+        self
+    }
+
     /// Set platform target for LUFS normalization
     pub fn with_platform(mut self, platform: PlatformTarget) -> Self {
         self.platform_target = platform;
@@ -212,6 +424,8 @@ impl ExportOptions {
         match &self.format {
             ExportFormat::Wav { .. } => "wav",
             ExportFormat::Mp3 { .. } => "mp3",
+            ExportFormat::Flac { .. } => "flac",
+            ExportFormat::Ogg { .. } => "ogg",
         }
     }
 
@@ -347,6 +561,11 @@ mod tests {
         assert_eq!(options.sample_rate, 48000);
         assert!(options.normalize);
         assert!(options.dither);
+        assert_eq!(options.effective_dither(), DitherType::Tpdf);
+
+        let shaped = options.with_dither_type(DitherType::NoiseShaped);
+        assert_eq!(shaped.effective_dither(), DitherType::NoiseShaped);
+        assert_eq!(shaped.with_dither(false).effective_dither(), DitherType::None);
     }
 
     #[test]
@@ -361,6 +580,25 @@ mod tests {
         assert_eq!(PlatformTarget::Spotify.target_lufs(), Some(-14.0));
         assert_eq!(PlatformTarget::AppleMusic.target_lufs(), Some(-16.0));
         assert_eq!(PlatformTarget::None.target_lufs(), None);
+
+        assert_eq!(ExportOptions::default().loudness_target(), None);
+        let platform = ExportOptions::default().with_platform(PlatformTarget::AppleMusic);
+        assert_eq!(platform.loudness_target(), Some(-16.0));
+        assert_eq!(platform.with_normalize_lufs(Some(-14.0)).loudness_target(), Some(-14.0));
+    }
+
+    #[test]
+    fn test_export_range() {
+        assert_eq!(ExportOptions::default().export_range(30.0), Ok(None));
+
+        let options = ExportOptions::default().with_range(16.0, 32.0);
+        assert_eq!(options.export_range(30.0), Ok(Some((16.0, 32.0))));
+
+        let open_end = ExportOptions { start_time: Some(4.0), ..Default::default() };
+        assert_eq!(open_end.export_range(30.0), Ok(Some((4.0, 30.0))));
+
+        assert!(ExportOptions::default().with_range(8.0, 8.0).export_range(30.0).is_err());
+        assert!(ExportOptions::default().with_range(-1.0, 8.0).export_range(30.0).is_err());
     }
 
     #[test]
@@ -370,4 +608,17 @@ mod tests {
         let parsed = ExportOptions::from_json(&json).unwrap();
         assert!(parsed.is_wav());
     }
+
+    #[test]
+    fn test_render_tail() {
+        // Options saved before the tail settings existed keep the 1s tail
+        let mut json: serde_json::Value = serde_json::from_str(&ExportOptions::default().to_json().unwrap()).unwrap();
+        json.as_object_mut().unwrap().retain(|key, _| key != "export_tail_seconds" && key != "auto_tail");
+        let legacy = ExportOptions::from_json(&json.to_string()).unwrap();
+        assert!((legacy.render_tail_seconds() - DEFAULT_TAIL_SECONDS).abs() < f64::EPSILON);
+
+        assert!(ExportOptions::default().with_tail_seconds(0.0).render_tail_seconds().abs() < f64::EPSILON);
+        assert!((ExportOptions::default().with_tail_seconds(90.0).render_tail_seconds() - MAX_TAIL_SECONDS).abs() < f64::EPSILON);
+        assert!((ExportOptions::default().with_auto_tail(true).render_tail_seconds() - MAX_TAIL_SECONDS).abs() < f64::EPSILON);
+    }
 }
@@ -21,6 +21,10 @@ pub struct ExportProgressState {
     status: RwLock<String>,
     /// Error message if export failed
     error: RwLock<Option<String>>,
+    /// Progress value at the start of the current render phase
+    render_start: AtomicU32,
+    /// Progress value at the end of the current render phase
+    render_end: AtomicU32,
 }
 
 impl ExportProgressState {
@@ -31,6 +35,8 @@ impl ExportProgressState {
             is_cancelled: AtomicBool::new(false),
             status: RwLock::new(String::new()),
             error: RwLock::new(None),
+            render_start: AtomicU32::new(0),
+            render_end: AtomicU32::new(0),
         }
     }
 
@@ -55,6 +61,33 @@ impl ExportProgressState {
         }
     }
 
+    /// Begin a render phase that `report_render` maps onto `start..end` percent
+    pub fn begin_render(&self, start: u32, end: u32, status: &str) {
+        self.render_start.store(start.min(100), Ordering::SeqCst);
+        self.render_end.store(end.clamp(start, 100), Ordering::SeqCst);
+        self.update(start, status);
+    }
+
+    /// Report render progress as a fraction (0.0-1.0) of the current render phase
+    ///
+    /// Does nothing unless an export is running, so renders outside an export
+    /// (freeze, previews) leave the state alone.
+    pub fn report_render(&self, fraction: f64) {
+        if !self.is_running() {
+            return;
+        }
+        let start = self.render_start.load(Ordering::SeqCst);
+        let end = self.render_end.load(Ordering::SeqCst);
+        let span = f64::from(end.saturating_sub(start));
+        let progress = start + (span * fraction.clamp(0.0, 1.0)) as u32;
+        self.progress.store(progress.min(100), Ordering::SeqCst);
+    }
+
+    /// Check whether a running export was cancelled (renders poll this to stop early)
+    pub fn is_render_cancelled(&self) -> bool {
+        self.is_running() && self.is_cancelled()
+    }
+
     /// Mark export as complete
     pub fn complete(&self) {
         self.progress.store(100, Ordering::SeqCst);
@@ -170,3 +203,29 @@ macro_rules! check_cancelled {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_progress_maps_onto_phase() {
+        let state = ExportProgressState::new();
+
+        // Ignored while no export is running
+        state.begin_render(20, 70, "Rendering audio...");
+        state.report_render(0.5);
+        assert_eq!(state.get_progress(), 20);
+        assert!(!state.is_render_cancelled());
+
+        state.start("Preparing export...");
+        state.begin_render(20, 70, "Rendering audio...");
+        state.report_render(0.5);
+        assert_eq!(state.get_progress(), 45);
+        state.report_render(2.0);
+        assert_eq!(state.get_progress(), 70);
+
+        state.cancel();
+        assert!(state.is_render_cancelled());
+    }
+}
@@ -5,6 +5,9 @@
 
 use rubato::{FftFixedInOut, Resampler};
 
+/// Input chunk size (in frames) of the FFT resampler
+const RESAMPLE_CHUNK_FRAMES: usize = 1024;
+
 /// Resample stereo audio from one sample rate to another
 ///
 /// # Arguments
@@ -27,92 +30,112 @@ pub fn resample_stereo(samples: &[f32], from_rate: u32, to_rate: u32) -> Result<
         samples.len()
     );
 
-    // Deinterleave stereo to separate channels
-    let num_frames = samples.len() / 2;
-    let mut left = Vec::with_capacity(num_frames);
-    let mut right = Vec::with_capacity(num_frames);
-
-    for i in 0..num_frames {
-        left.push(f64::from(samples[i * 2]));
-        right.push(f64::from(samples[i * 2 + 1]));
-    }
-
-    // Create resampler
-    // FftFixedInOut provides high quality with fixed input/output chunk sizes
-    let chunk_size = 1024;
-    let mut resampler = FftFixedInOut::<f64>::new(
-        from_rate as usize,
-        to_rate as usize,
-        chunk_size,
-        2, // stereo
-    )
-    .map_err(|e| format!("Failed to create resampler: {e}"))?;
-
-    // Calculate output size
-    let output_frames = ((num_frames as f64 * f64::from(to_rate)) / f64::from(from_rate)).ceil() as usize;
-    let mut output_left = Vec::with_capacity(output_frames);
-    let mut output_right = Vec::with_capacity(output_frames);
-
-    // Process in chunks
-    let frames_needed = resampler.input_frames_next();
-    let mut pos = 0;
+    let mut resampler = StereoResampler::new(from_rate, to_rate)?;
+    let mut output = Vec::with_capacity(resampler.output_frames(samples.len() / 2) * 2);
+    resampler.process(samples, &mut output)?;
+    resampler.finish(&mut output)?;
 
-    while pos + frames_needed <= num_frames {
-        let input_chunk = vec![
-            left[pos..pos + frames_needed].to_vec(),
-            right[pos..pos + frames_needed].to_vec(),
-        ];
+    eprintln!(
+        "✅ [Resample] Converted {} → {} samples",
+        samples.len(),
+        output.len()
+    );
 
-        let output_chunk = resampler
-            .process(&input_chunk, None)
-            .map_err(|e| format!("Resampling error: {e}"))?;
+    Ok(output)
+}
 
-        output_left.extend_from_slice(&output_chunk[0]);
-        output_right.extend_from_slice(&output_chunk[1]);
+/// Stereo sample rate converter fed a chunk at a time
+///
+/// Input chunks can be any length; the output is the same as `resample_stereo`
+/// on the whole signal.
+pub struct StereoResampler {
+    resampler: FftFixedInOut<f64>,
+    from_rate: u32,
+    to_rate: u32,
+    /// Deinterleaved input waiting for a full resampler chunk
+    pending: [Vec<f64>; 2],
+}
 
-        pos += frames_needed;
+impl StereoResampler {
+    pub fn new(from_rate: u32, to_rate: u32) -> Result<Self, String> {
+        // FftFixedInOut provides high quality with fixed input/output chunk sizes
+        let resampler = FftFixedInOut::<f64>::new(
+            from_rate as usize,
+            to_rate as usize,
+            RESAMPLE_CHUNK_FRAMES,
+            2, // stereo
+        )
+        .map_err(|e| format!("Failed to create resampler: {e}"))?;
+        let frames_needed = resampler.input_frames_next();
+
+        Ok(Self {
+            resampler,
+            from_rate,
+            to_rate,
+            pending: [Vec::with_capacity(frames_needed), Vec::with_capacity(frames_needed)],
+        })
     }
 
-    // Process remaining samples with padding
-    if pos < num_frames {
-        let remaining = num_frames - pos;
-        let mut padded_left = left[pos..].to_vec();
-        let mut padded_right = right[pos..].to_vec();
-
-        // Pad with zeros to fill chunk
-        padded_left.resize(frames_needed, 0.0);
-        padded_right.resize(frames_needed, 0.0);
-
-        let input_chunk = vec![padded_left, padded_right];
+    /// Number of frames `input_frames` frames of input resample to
+    pub fn output_frames(&self, input_frames: usize) -> usize {
+        let frames_needed = self.resampler.input_frames_next();
+        let remaining = input_frames % frames_needed;
+        let tail = if remaining == 0 {
+            0
+        } else {
+            self.tail_frames(remaining).min(self.resampler.output_frames_next())
+        };
+        input_frames / frames_needed * self.resampler.output_frames_next() + tail
+    }
 
-        let output_chunk = resampler
-            .process(&input_chunk, None)
-            .map_err(|e| format!("Resampling error: {e}"))?;
+    /// Resample interleaved `samples`, appending whatever output is ready to `output`
+    pub fn process(&mut self, samples: &[f32], output: &mut Vec<f32>) -> Result<(), String> {
+        let frames_needed = self.resampler.input_frames_next();
+        for frame in samples.chunks_exact(2) {
+            self.pending[0].push(f64::from(frame[0]));
+            self.pending[1].push(f64::from(frame[1]));
+            if self.pending[0].len() == frames_needed {
+                self.process_pending(frames_needed, output)?;
+            }
+        }
+        Ok(())
+    }
 
+    /// Resample the input left over at the end of the signal (padded with silence)
+    pub fn finish(&mut self, output: &mut Vec<f32>) -> Result<(), String> {
+        let remaining = self.pending[0].len();
+        if remaining == 0 {
+            return Ok(());
+        }
+        let frames_needed = self.resampler.input_frames_next();
+        for channel in &mut self.pending {
+            channel.resize(frames_needed, 0.0);
+        }
         // Only take the samples we need (proportional to remaining input)
-        let output_needed = ((remaining as f64 * f64::from(to_rate)) / f64::from(from_rate)).ceil() as usize;
-        let take_count = output_needed.min(output_chunk[0].len());
-
-        output_left.extend_from_slice(&output_chunk[0][..take_count]);
-        output_right.extend_from_slice(&output_chunk[1][..take_count]);
+        self.process_pending(self.tail_frames(remaining), output)
     }
 
-    // Interleave back to stereo
-    let output_len = output_left.len().min(output_right.len());
-    let mut output = Vec::with_capacity(output_len * 2);
-
-    for i in 0..output_len {
-        output.push(output_left[i] as f32);
-        output.push(output_right[i] as f32);
+    /// Output frames for the last `remaining` frames of input
+    fn tail_frames(&self, remaining: usize) -> usize {
+        ((remaining as f64 * f64::from(self.to_rate)) / f64::from(self.from_rate)).ceil() as usize
     }
 
-    eprintln!(
-        "✅ [Resample] Converted {} → {} samples",
-        samples.len(),
-        output.len()
-    );
-
-    Ok(output)
+    /// Resample the pending chunk, keeping at most `max_frames` frames of output
+    fn process_pending(&mut self, max_frames: usize, output: &mut Vec<f32>) -> Result<(), String> {
+        let output_chunk = self
+            .resampler
+            .process(&self.pending, None)
+            .map_err(|e| format!("Resampling error: {e}"))?;
+        let frames = output_chunk[0].len().min(output_chunk[1].len()).min(max_frames);
+        for (&left, &right) in output_chunk[0][..frames].iter().zip(&output_chunk[1][..frames]) {
+            output.push(left as f32);
+            output.push(right as f32);
+        }
+        for channel in &mut self.pending {
+            channel.clear();
+        }
+        Ok(())
+    }
 }
 
 /// Resample mono audio from one sample rate to another
@@ -3,7 +3,9 @@
 //! Exports each track as a separate audio file.
 
 use super::options::{ExportOptions, ExportResult};
+use super::flac::export_flac;
 use super::mp3::export_mp3;
+use super::ogg::export_ogg;
 use super::wav::export_wav;
 use std::path::{Path, PathBuf};
 
@@ -119,6 +121,8 @@ pub fn export_stem(
     match &options.format {
         super::options::ExportFormat::Wav { .. } => export_wav(samples, output_path, options),
         super::options::ExportFormat::Mp3 { .. } => export_mp3(samples, output_path, options),
+        super::options::ExportFormat::Flac { .. } => export_flac(samples, output_path, options),
+        super::options::ExportFormat::Ogg { .. } => export_ogg(samples, output_path, options),
     }
 }
 
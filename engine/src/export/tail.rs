@@ -0,0 +1,85 @@
+//! Automatic render tail: end the export once reverb/delay tails have died away
+
+/// Level below which the mix counts as silent (dBFS)
+pub const TAIL_SILENCE_THRESHOLD_DB: f32 = -90.0;
+/// How long the mix must stay below the threshold to end the tail (seconds)
+/// Longer than the longest delay time (2s), so the gaps between echoes don't end it
+pub const TAIL_SILENCE_WINDOW_SECONDS: f64 = 2.5;
+
+/// Cut interleaved stereo `samples` where the mix falls silent after `content_end_seconds`
+///
+/// The render stops at the start of the first stretch of `TAIL_SILENCE_WINDOW_SECONDS`
+/// below `TAIL_SILENCE_THRESHOLD_DB` after the last clip ends. Audio that never falls
+/// silent is left as rendered (the render length is the cap).
+pub fn trim_silent_tail(samples: &mut Vec<f32>, content_end_seconds: f64, sample_rate: u32) {
+    let threshold = 10.0_f32.powf(TAIL_SILENCE_THRESHOLD_DB / 20.0);
+    let window = (TAIL_SILENCE_WINDOW_SECONDS * f64::from(sample_rate)) as usize;
+    let content_end = (content_end_seconds.max(0.0) * f64::from(sample_rate)) as usize;
+
+    let mut silent_frames = 0;
+    for (frame, pair) in samples.chunks_exact(2).enumerate().skip(content_end) {
+        if pair[0].abs().max(pair[1].abs()) < threshold {
+            silent_frames += 1;
+            if silent_frames >= window {
+                samples.truncate((frame + 1 - silent_frames) * 2);
+                return;
+            }
+        } else {
+            silent_frames = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48000;
+
+    /// One second of content, then a delay repeating every 1.5s at -6 dB per
+    /// repeat (feedback 0.5) until it drops below -90 dBFS
+    fn delay_render(total_seconds: f64) -> (Vec<f32>, f64) {
+        let mut samples = vec![0.0; (total_seconds * f64::from(RATE)) as usize * 2];
+        let click = |samples: &mut Vec<f32>, seconds: f64, level: f32| {
+            let frame = (seconds * f64::from(RATE)) as usize;
+            for i in 0..480 {
+                samples[(frame + i) * 2] = level;
+                samples[(frame + i) * 2 + 1] = level;
+            }
+        };
+        click(&mut samples, 0.5, 1.0);
+        let mut level = 0.5f32;
+        let mut time = 0.5;
+        let mut last_echo = 0.0;
+        while level > 10.0_f32.powf(TAIL_SILENCE_THRESHOLD_DB / 20.0) {
+            time += 1.5;
+            click(&mut samples, time, level);
+            last_echo = time;
+            level *= 0.5;
+        }
+        (samples, last_echo)
+    }
+
+    #[test]
+    fn test_long_delay_tail_is_not_cut_off() {
+        let (mut samples, last_echo) = delay_render(30.0);
+        // The echoes run far past the old fixed 1s tail
+        assert!(last_echo > 20.0);
+
+        trim_silent_tail(&mut samples, 1.0, RATE);
+        let end_seconds = (samples.len() / 2) as f64 / f64::from(RATE);
+        assert!(end_seconds > last_echo, "cut at {end_seconds}s before the echo at {last_echo}s");
+        assert!(end_seconds < last_echo + 0.1);
+    }
+
+    #[test]
+    fn test_dry_render_stops_at_the_content_end_and_loud_render_is_kept() {
+        let mut dry = vec![0.0; RATE as usize * 2 * 30];
+        trim_silent_tail(&mut dry, 1.0, RATE);
+        assert_eq!(dry.len(), RATE as usize * 2);
+
+        let mut loud = vec![0.5; RATE as usize * 2 * 3];
+        trim_silent_tail(&mut loud, 1.0, RATE);
+        assert_eq!(loud.len(), RATE as usize * 2 * 3);
+    }
+}
@@ -2,11 +2,14 @@
 //!
 //! Supports 16-bit, 24-bit, and 32-bit float WAV formats.
 
-use super::dither::{convert_to_16bit, convert_to_24bit};
-use super::options::{ExportOptions, ExportResult, WavBitDepth};
-use super::normalize::normalize_peak;
-use super::resample::{resample_stereo, stereo_to_mono, mono_to_stereo};
-use std::path::Path;
+use super::dither::{convert_to_16bit, convert_to_24bit, Quantizer};
+use super::options::{DitherType, ExportOptions, ExportResult, WavBitDepth};
+use super::fade::{apply_export_fades, apply_export_fades_at};
+use super::normalize::{normalize_lufs, normalize_peak};
+use super::resample::{resample_stereo, stereo_to_mono, mono_to_stereo, StereoResampler};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
 
 /// Internal sample rate used by the audio engine
 pub const ENGINE_SAMPLE_RATE: u32 = 48000;
@@ -29,11 +32,7 @@ pub fn export_wav(
         "🎵 [WAV Export] Starting export to {}", output_path.display()
     );
 
-    // Get bit depth from options
-    let bit_depth = match &options.format {
-        super::options::ExportFormat::Wav { bit_depth } => *bit_depth,
-        super::options::ExportFormat::Mp3 { .. } => return Err("export_wav called with non-WAV format".to_string()),
-    };
+    let bit_depth = wav_bit_depth(options)?;
 
     // Make a mutable copy of samples for processing
     let mut processed = samples.to_vec();
@@ -45,6 +44,13 @@ pub fn export_wav(
         processed = mono_to_stereo(&processed); // Convert back for stereo output file
     }
 
+    // Loudness normalization runs at the engine rate the meter is calibrated for
+    let loudness_target = options.loudness_target();
+    if let Some(target_lufs) = loudness_target {
+        eprintln!("📊 [WAV Export] Normalizing to {target_lufs:.1} LUFS");
+        normalize_lufs(&mut processed, target_lufs);
+    }
+
     // Apply sample rate conversion if needed
     if options.sample_rate != ENGINE_SAMPLE_RATE {
         eprintln!(
@@ -54,31 +60,32 @@ pub fn export_wav(
         processed = resample_stereo(&processed, ENGINE_SAMPLE_RATE, options.sample_rate)?;
     }
 
-    // Apply normalization if requested
-    if options.normalize {
+    // Apply peak normalization if requested (a loudness target takes precedence)
+    if options.normalize && loudness_target.is_none() {
         eprintln!("📊 [WAV Export] Normalizing to -0.1 dBFS");
         normalize_peak(&mut processed, -0.1);
     }
 
+    // Fade the final mix in/out after normalization
+    apply_export_fades(&mut processed, options.sample_rate, options.fade_in_seconds, options.fade_out_seconds);
+
     // Calculate duration
     let num_frames = processed.len() / 2;
     let duration = num_frames as f64 / f64::from(options.sample_rate);
 
     // Write WAV based on bit depth
-    let format_description = match bit_depth {
+    match bit_depth {
         WavBitDepth::Int16 => {
-            write_wav_16bit(&processed, output_path, options.sample_rate, options.dither)?;
-            "WAV 16-bit".to_string()
+            write_wav_16bit(&processed, output_path, options.sample_rate, options.effective_dither())?;
         }
         WavBitDepth::Int24 => {
-            write_wav_24bit(&processed, output_path, options.sample_rate, options.dither)?;
-            "WAV 24-bit".to_string()
+            write_wav_24bit(&processed, output_path, options.sample_rate, options.effective_dither())?;
         }
         WavBitDepth::Float32 => {
             write_wav_float32(&processed, output_path, options.sample_rate)?;
-            "WAV 32-bit float".to_string()
         }
-    };
+    }
+    let format_description = format_description(bit_depth);
 
     // Get file size
     let file_size = std::fs::metadata(output_path)
@@ -101,12 +108,197 @@ pub fn export_wav(
     ))
 }
 
+/// WAV export written a chunk at a time while the mix renders
+///
+/// Applies the same processing as `export_wav` (mono mixdown, sample rate
+/// conversion, fades and dither) chunk by chunk, so memory use doesn't grow with
+/// the length of the export. Only for options that `can_stream`.
+pub struct WavStreamWriter {
+    writer: hound::WavWriter<BufWriter<File>>,
+    output_path: PathBuf,
+    bit_depth: WavBitDepth,
+    sample_rate: u32,
+    mono: bool,
+    fade_in_seconds: f64,
+    fade_out_seconds: f64,
+    quantizer: Option<Quantizer>,
+    resampler: Option<StereoResampler>,
+    /// Frames written so far, and the length of the whole export (for the fade-out)
+    frames_written: usize,
+    total_frames: usize,
+    /// Reused chunk buffers
+    buffer: Vec<f32>,
+    resampled: Vec<f32>,
+    /// First write error; later chunks are dropped and `finish` reports it
+    error: Option<String>,
+}
+
+impl WavStreamWriter {
+    /// Create the WAV file for an export of `input_frames` frames at the engine rate
+    pub fn create(output_path: &Path, options: &ExportOptions, input_frames: usize) -> Result<Self, String> {
+        let bit_depth = wav_bit_depth(options)?;
+        if !options.can_stream() {
+            return Err("Normalized and auto-tail exports can't be streamed".to_string());
+        }
+
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: options.sample_rate,
+            bits_per_sample: bit_depth.bits_per_sample(),
+            sample_format: match bit_depth {
+                WavBitDepth::Float32 => hound::SampleFormat::Float,
+                WavBitDepth::Int16 | WavBitDepth::Int24 => hound::SampleFormat::Int,
+            },
+        };
+        let writer = hound::WavWriter::create(output_path, spec)
+            .map_err(|e| format!("Failed to create WAV file: {e}"))?;
+
+        let quantizer = match bit_depth {
+            WavBitDepth::Int16 => Some(Quantizer::for_16bit(options.effective_dither())),
+            WavBitDepth::Int24 => Some(Quantizer::for_24bit(options.effective_dither())),
+            WavBitDepth::Float32 => None,
+        };
+        let resampler = if options.sample_rate == ENGINE_SAMPLE_RATE {
+            None
+        } else {
+            Some(StereoResampler::new(ENGINE_SAMPLE_RATE, options.sample_rate)?)
+        };
+        let total_frames = resampler.as_ref().map_or(input_frames, |resampler| resampler.output_frames(input_frames));
+
+        Ok(Self {
+            writer,
+            output_path: output_path.to_path_buf(),
+            bit_depth,
+            sample_rate: options.sample_rate,
+            mono: options.mono,
+            fade_in_seconds: options.fade_in_seconds,
+            fade_out_seconds: options.fade_out_seconds,
+            quantizer,
+            resampler,
+            frames_written: 0,
+            total_frames,
+            buffer: Vec::new(),
+            resampled: Vec::new(),
+            error: None,
+        })
+    }
+
+    /// Process and write the next chunk of the mix (interleaved stereo at the
+    /// engine rate)
+    pub fn write(&mut self, samples: &[f32]) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        buffer.extend_from_slice(samples);
+        if self.mono {
+            for frame in buffer.chunks_exact_mut(2) {
+                let mono = (frame[0] + frame[1]) * 0.5;
+                frame[0] = mono;
+                frame[1] = mono;
+            }
+        }
+
+        let result = if let Some(resampler) = self.resampler.as_mut() {
+            let mut resampled = std::mem::take(&mut self.resampled);
+            resampled.clear();
+            let result = resampler.process(&buffer, &mut resampled).and_then(|()| self.write_output(&mut resampled));
+            self.resampled = resampled;
+            result
+        } else {
+            self.write_output(&mut buffer)
+        };
+        self.buffer = buffer;
+
+        if let Err(e) = result {
+            self.error = Some(e);
+        }
+    }
+
+    /// Write what's left and finalize the file
+    pub fn finish(mut self) -> Result<ExportResult, String> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        if let Some(mut resampler) = self.resampler.take() {
+            let mut resampled = std::mem::take(&mut self.resampled);
+            resampled.clear();
+            resampler.finish(&mut resampled)?;
+            self.write_output(&mut resampled)?;
+        }
+
+        self.writer
+            .finalize()
+            .map_err(|e| format!("Failed to finalize WAV: {e}"))?;
+
+        let duration = self.frames_written as f64 / f64::from(self.sample_rate);
+        let file_size = std::fs::metadata(&self.output_path).map_or(0, |m| m.len());
+
+        eprintln!(
+            "✅ [WAV Export] Streamed: {:.2}s, {:.2} MB, {}",
+            duration,
+            file_size as f64 / 1024.0 / 1024.0,
+            format_description(self.bit_depth)
+        );
+
+        Ok(ExportResult::new(
+            self.output_path.to_string_lossy().to_string(),
+            file_size,
+            duration,
+            self.sample_rate,
+            format_description(self.bit_depth),
+        ))
+    }
+
+    /// Fade, quantize and write interleaved samples at the output rate
+    fn write_output(&mut self, samples: &mut [f32]) -> Result<(), String> {
+        apply_export_fades_at(
+            samples,
+            self.frames_written,
+            self.total_frames,
+            self.sample_rate,
+            self.fade_in_seconds,
+            self.fade_out_seconds,
+        );
+
+        for &sample in &*samples {
+            let written = match (self.bit_depth, self.quantizer.as_mut()) {
+                (WavBitDepth::Int16, Some(quantizer)) => self.writer.write_sample(quantizer.quantize(sample) as i16),
+                (WavBitDepth::Int24, Some(quantizer)) => self.writer.write_sample(quantizer.quantize(sample) as i32),
+                _ => self.writer.write_sample(sample),
+            };
+            written.map_err(|e| format!("Failed to write sample: {e}"))?;
+        }
+        self.frames_written += samples.len() / 2;
+        Ok(())
+    }
+}
+
+/// Bit depth of WAV export options
+fn wav_bit_depth(options: &ExportOptions) -> Result<WavBitDepth, String> {
+    match &options.format {
+        super::options::ExportFormat::Wav { bit_depth } => Ok(*bit_depth),
+        _ => Err("export_wav called with non-WAV format".to_string()),
+    }
+}
+
+/// Format shown in the export result
+fn format_description(bit_depth: WavBitDepth) -> String {
+    match bit_depth {
+        WavBitDepth::Int16 => "WAV 16-bit".to_string(),
+        WavBitDepth::Int24 => "WAV 24-bit".to_string(),
+        WavBitDepth::Float32 => "WAV 32-bit float".to_string(),
+    }
+}
+
 /// Write 16-bit WAV file
 fn write_wav_16bit(
     samples: &[f32],
     output_path: &Path,
     sample_rate: u32,
-    dither: bool,
+    dither: DitherType,
 ) -> Result<(), String> {
     let spec = hound::WavSpec {
         channels: 2,
@@ -139,7 +331,7 @@ fn write_wav_24bit(
     samples: &[f32],
     output_path: &Path,
     sample_rate: u32,
-    dither: bool,
+    dither: DitherType,
 ) -> Result<(), String> {
     let spec = hound::WavSpec {
         channels: 2,
@@ -262,4 +454,47 @@ mod tests {
         // Clean up
         let _ = std::fs::remove_file(&temp_path);
     }
+
+    #[test]
+    fn test_streamed_export_matches_whole_buffer_export() {
+        let samples = create_test_samples();
+        let whole_path = env::temp_dir().join("test_export_whole.wav");
+        let streamed_path = env::temp_dir().join("test_export_streamed.wav");
+
+        for options in [
+            ExportOptions::wav(WavBitDepth::Int16)
+                .with_sample_rate(44100)
+                .with_dither(true)
+                .with_dither_type(DitherType::NoiseShaped)
+                .with_mono(true)
+                .with_fades(0.1, 0.3),
+            ExportOptions::wav(WavBitDepth::Float32).with_sample_rate(48000).with_fades(0.0, 0.5),
+        ] {
+            let whole = export_wav(&samples, &whole_path, &options).unwrap();
+
+            // Uneven chunks, like a render sink that isn't aligned to anything
+            let mut writer = WavStreamWriter::create(&streamed_path, &options, samples.len() / 2).unwrap();
+            for chunk in samples.chunks(2 * 1000 + 6) {
+                writer.write(chunk);
+            }
+            let streamed = writer.finish().unwrap();
+            assert!((streamed.duration - whole.duration).abs() < 1e-9);
+
+            let read = |path: &Path| -> Vec<f32> {
+                let mut reader = hound::WavReader::open(path).unwrap();
+                match reader.spec().sample_format {
+                    hound::SampleFormat::Float => reader.samples::<f32>().map(Result::unwrap).collect(),
+                    hound::SampleFormat::Int => reader.samples::<i32>().map(|s| s.unwrap() as f32).collect(),
+                }
+            };
+            assert_eq!(read(&streamed_path), read(&whole_path));
+        }
+
+        // Options that need the whole mix can't stream
+        let normalized = ExportOptions::wav(WavBitDepth::Int24).with_normalize(true);
+        assert!(WavStreamWriter::create(&streamed_path, &normalized, 48000).is_err());
+
+        let _ = std::fs::remove_file(&whole_path);
+        let _ = std::fs::remove_file(&streamed_path);
+    }
 }
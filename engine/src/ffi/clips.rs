@@ -2,7 +2,7 @@ use std::ffi::CStr;
 use std::os::raw::c_char;
 use std::panic::AssertUnwindSafe;
 use crate::api;
-use super::{safe_cstring, ffi_catch};
+use super::{safe_cstring, ffi_catch, ffi_ok, ffi_err, ffi_result, FfiErrorCode};
 
 /// Load an audio file to a specific track and return clip ID
 #[no_mangle]
@@ -27,6 +27,29 @@ pub extern "C" fn load_audio_file_to_track_ffi(path: *const c_char, track_id: u6
     }))
 }
 
+/// Open an audio file for streaming on a specific track and return clip ID
+#[no_mangle]
+pub extern "C" fn load_audio_file_to_track_streaming_ffi(path: *const c_char, track_id: u64, start_time: f64) -> i64 {
+    ffi_catch(-1, AssertUnwindSafe(|| {
+        if path.is_null() {
+            return -1;
+        }
+
+        let c_str = unsafe { CStr::from_ptr(path) };
+        let Ok(path_str) = c_str.to_str() else {
+            return -1;
+        };
+
+        match api::load_audio_file_to_track_streaming_api(path_str.to_string(), track_id, start_time) {
+            Ok(id) => id as i64,
+            Err(e) => {
+                eprintln!("[FFI] load_audio_file_to_track_streaming_ffi error: {e}");
+                -1
+            }
+        }
+    }))
+}
+
 /// Load an audio file and return clip ID (legacy - adds to first available track)
 #[no_mangle]
 pub extern "C" fn load_audio_file_ffi(path: *const c_char) -> i64 {
@@ -55,6 +78,16 @@ pub extern "C" fn get_clip_duration_ffi(clip_id: u64) -> f64 {
     })
 }
 
+/// Get a loaded audio clip's properties
+/// Returns JSON: `{"sample_rate", "channels", "duration", "file_path"}`
+/// Caller must free the returned string
+#[no_mangle]
+pub extern "C" fn get_clip_info_ffi(clip_id: u64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::get_clip_info(clip_id))
+    })
+}
+
 /// Set clip start time (position) on timeline
 /// Used for dragging clips to reposition them
 #[no_mangle]
@@ -103,6 +136,39 @@ pub extern "C" fn set_audio_clip_gain_ffi(track_id: u64, clip_id: u64, gain_db:
     })
 }
 
+/// Set audio clip gain envelope (volume breakpoints drawn on the clip)
+/// `csv_data` format: "`time_seconds,db;time_seconds,db`;..." (time from the clip start) or empty to clear
+#[no_mangle]
+pub extern "C" fn set_clip_gain_envelope_ffi(track_id: u64, clip_id: u64, csv_data: *const c_char) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        let csv = if csv_data.is_null() {
+            String::new()
+        } else {
+            unsafe {
+                match CStr::from_ptr(csv_data).to_str() {
+                    Ok(s) => s.to_string(),
+                    Err(_) => return ffi_err(FfiErrorCode::InvalidArg, "Invalid UTF-8 in csv_data"),
+                }
+            }
+        };
+
+        ffi_result(api::set_clip_gain_envelope(track_id, clip_id, &csv))
+    }))
+}
+
+/// Normalize an audio clip's gain so its peak reaches `target_db` (non-destructive)
+///
+/// Returns the clip's resulting gain in dB as a string.
+#[no_mangle]
+pub extern "C" fn normalize_audio_clip_ffi(track_id: u64, clip_id: u64, target_db: f32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        match api::normalize_audio_clip(track_id, clip_id, target_db) {
+            Ok(gain_db) => ffi_ok(&format!("{gain_db}")),
+            Err(e) => ffi_err(FfiErrorCode::Internal, &e),
+        }
+    })
+}
+
 /// Set audio clip warp settings for tempo sync
 /// Used to enable/disable time-stretching in the Audio Editor
 /// `warp_mode`: 0 = warp (pitch preserved), 1 = repitch (pitch follows speed)
@@ -140,6 +206,46 @@ pub extern "C" fn set_audio_clip_transpose_ffi(
     })
 }
 
+/// Invert audio clip polarity (non-destructive)
+#[no_mangle]
+pub extern "C" fn set_clip_polarity_invert_ffi(track_id: u64, clip_id: u64, inverted: bool) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_clip_polarity_invert(track_id, clip_id, inverted))
+    })
+}
+
+/// Play an audio clip backwards (non-destructive)
+#[no_mangle]
+pub extern "C" fn set_audio_clip_reversed_ffi(track_id: u64, clip_id: u64, reversed: bool) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_audio_clip_reversed(track_id, clip_id, reversed))
+    })
+}
+
+/// Convert an audio clip's audio to mono (1) or stereo (2)
+#[no_mangle]
+pub extern "C" fn convert_clip_channels_ffi(clip_id: u64, channels: u32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::convert_clip_channels(clip_id, channels as usize))
+    })
+}
+
+/// Sum audio clip stereo channels to mono (non-destructive)
+#[no_mangle]
+pub extern "C" fn set_clip_mono_sum_ffi(track_id: u64, clip_id: u64, enabled: bool) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_clip_mono_sum(track_id, clip_id, enabled))
+    })
+}
+
+/// Set audio clip fade-in/fade-out lengths in seconds (non-destructive)
+#[no_mangle]
+pub extern "C" fn set_clip_fades_ffi(track_id: u64, clip_id: u64, fade_in: f64, fade_out: f64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_clip_fades(track_id, clip_id, fade_in, fade_out))
+    })
+}
+
 /// Get waveform peaks
 /// Returns pointer to float array, and writes the length to `out_length`
 /// Caller must free the returned array with `free_waveform_peaks_ffi`
@@ -150,29 +256,48 @@ pub extern "C" fn get_waveform_peaks_ffi(
     out_length: *mut usize,
 ) -> *mut f32 {
     ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
-        if let Ok(peaks) = api::get_waveform_peaks(clip_id, resolution) {
-            let len = peaks.len();
-            // Convert to boxed slice to guarantee capacity == length,
-            // avoiding UB when reconstructing in free_waveform_peaks_ffi
-            let boxed = peaks.into_boxed_slice();
-            let ptr = Box::into_raw(boxed).cast::<f32>();
-
-            if !out_length.is_null() {
-                unsafe {
-                    *out_length = len;
-                }
+        peaks_to_ffi(api::get_waveform_peaks(clip_id, resolution), out_length)
+    }))
+}
+
+/// Get waveform peaks in dBFS (floored at -60 dB), as min/max pairs like `get_waveform_peaks_ffi`
+/// Returns pointer to float array, and writes the length to `out_length`
+/// Caller must free the returned array with `free_waveform_peaks_ffi`
+#[no_mangle]
+pub extern "C" fn get_waveform_peaks_db_ffi(
+    clip_id: u64,
+    resolution: usize,
+    out_length: *mut usize,
+) -> *mut f32 {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        peaks_to_ffi(api::get_waveform_peaks_db(clip_id, resolution), out_length)
+    }))
+}
+
+/// Hand a peaks array to the caller (null and length 0 on error)
+fn peaks_to_ffi(peaks: Result<Vec<f32>, String>, out_length: *mut usize) -> *mut f32 {
+    if let Ok(peaks) = peaks {
+        let len = peaks.len();
+        // Convert to boxed slice to guarantee capacity == length,
+        // avoiding UB when reconstructing in free_waveform_peaks_ffi
+        let boxed = peaks.into_boxed_slice();
+        let ptr = Box::into_raw(boxed).cast::<f32>();
+
+        if !out_length.is_null() {
+            unsafe {
+                *out_length = len;
             }
+        }
 
-            ptr
-        } else {
-            if !out_length.is_null() {
-                unsafe {
-                    *out_length = 0;
-                }
+        ptr
+    } else {
+        if !out_length.is_null() {
+            unsafe {
+                *out_length = 0;
             }
-            std::ptr::null_mut()
         }
-    }))
+        std::ptr::null_mut()
+    }
 }
 
 /// Free waveform peaks array allocated by get_waveform_peaks_ffi
@@ -200,6 +325,32 @@ pub extern "C" fn move_clip_to_track_ffi(track_id: u64, clip_id: u64) -> *mut c_
     })
 }
 
+/// Split an audio clip in two at a timeline position
+///
+/// Returns "`first_id,second_id`" on success.
+#[no_mangle]
+pub extern "C" fn split_audio_clip_ffi(track_id: u64, clip_id: u64, split_time_seconds: f64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        match api::split_audio_clip(track_id, clip_id, split_time_seconds) {
+            Ok((first, second)) => ffi_ok(&format!("{first},{second}")),
+            Err(e) => ffi_err(FfiErrorCode::Internal, &e),
+        }
+    })
+}
+
+/// Split a MIDI clip in two at a timeline position
+///
+/// Returns "`first_id,second_id`" on success.
+#[no_mangle]
+pub extern "C" fn split_midi_clip_ffi(track_id: u64, clip_id: u64, split_time_seconds: f64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        match api::split_midi_clip(track_id, clip_id, split_time_seconds) {
+            Ok((first, second)) => ffi_ok(&format!("{first},{second}")),
+            Err(e) => ffi_err(FfiErrorCode::Internal, &e),
+        }
+    })
+}
+
 /// Duplicate an audio clip on the same track at a new position
 ///
 /// Returns the new clip ID on success, or -1 on failure.
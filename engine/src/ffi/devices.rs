@@ -2,7 +2,7 @@ use std::ffi::CStr;
 use std::os::raw::c_char;
 use std::panic::AssertUnwindSafe;
 use crate::api;
-use super::{safe_cstring, ffi_catch};
+use super::{safe_cstring, ffi_catch, ffi_err, ffi_result, FfiErrorCode};
 
 // ============================================================================
 // Audio Device Management FFI
@@ -55,6 +55,51 @@ pub extern "C" fn set_audio_input_device_ffi(device_index: i32) -> *mut c_char {
     })
 }
 
+/// Set audio input device by name
+/// Pass empty string to use system default
+#[no_mangle]
+pub extern "C" fn set_audio_input_device_by_name_ffi(device_name: *const c_char) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        let name = unsafe {
+            if device_name.is_null() {
+                ""
+            } else {
+                match CStr::from_ptr(device_name).to_str() {
+                    Ok(s) => s,
+                    Err(_) => return ffi_err(FfiErrorCode::InvalidArg, "Invalid UTF-8"),
+                }
+            }
+        };
+
+        ffi_result(api::set_audio_input_device_by_name(name))
+    }))
+}
+
+/// Get currently selected audio input device name
+/// Returns empty string for system default
+#[no_mangle]
+pub extern "C" fn get_selected_audio_input_device_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::get_selected_audio_input_device())
+    })
+}
+
+/// Get the output device status (0 = running, 1 = disconnected, 2 = no stream, -1 = error)
+#[no_mangle]
+pub extern "C" fn get_audio_device_status_ffi() -> i32 {
+    ffi_catch(-1, || {
+        api::get_audio_device_status().unwrap_or(-1)
+    })
+}
+
+/// Whether the output device changed since the last poll (lost, or stream rebuilt)
+#[no_mangle]
+pub extern "C" fn poll_audio_device_changed_ffi() -> bool {
+    ffi_catch(false, || {
+        api::take_audio_device_changed().unwrap_or(false)
+    })
+}
+
 /// Get current sample rate
 #[no_mangle]
 pub extern "C" fn get_sample_rate_ffi() -> u32 {
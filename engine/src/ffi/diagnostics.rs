@@ -0,0 +1,15 @@
+use std::os::raw::c_char;
+use crate::api;
+use super::{ffi_catch, ffi_result};
+
+// ============================================================================
+// Diagnostics FFI
+// ============================================================================
+
+/// Get engine diagnostics as a JSON object string
+#[no_mangle]
+pub extern "C" fn get_engine_diagnostics_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::get_engine_diagnostics())
+    })
+}
@@ -2,7 +2,7 @@ use std::ffi::CStr;
 use std::os::raw::c_char;
 use std::panic::AssertUnwindSafe;
 use crate::api;
-use super::{safe_cstring, ffi_catch};
+use super::{safe_cstring, ffi_catch, ffi_err, ffi_result, FfiErrorCode};
 
 // ============================================================================
 // M4: Effect Management FFI
@@ -88,6 +88,36 @@ pub extern "C" fn set_effect_parameter_ffi(
     }))
 }
 
+/// Set an effect parameter automation curve
+/// `csv_data` format: "`time_seconds,value;time_seconds,value`;..." or empty to clear
+#[no_mangle]
+pub extern "C" fn set_effect_parameter_automation_ffi(
+    effect_id: u64,
+    param_name: *const c_char,
+    csv_data: *const c_char,
+) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        let param_name_str = unsafe {
+            match CStr::from_ptr(param_name).to_str() {
+                Ok(s) => s,
+                Err(_) => return ffi_err(FfiErrorCode::InvalidArg, "Invalid parameter name"),
+            }
+        };
+        let csv = if csv_data.is_null() {
+            ""
+        } else {
+            unsafe {
+                match CStr::from_ptr(csv_data).to_str() {
+                    Ok(s) => s,
+                    Err(_) => return ffi_err(FfiErrorCode::InvalidArg, "Invalid UTF-8 in csv_data"),
+                }
+            }
+        };
+
+        ffi_result(api::set_effect_parameter_automation(effect_id, param_name_str, csv))
+    }))
+}
+
 /// Set effect bypass state
 /// Returns 1 on success, 0 on failure
 #[no_mangle]
@@ -136,3 +166,36 @@ pub extern "C" fn reorder_track_effects_ffi(track_id: u64, effect_ids_csv: *cons
         }
     }))
 }
+
+/// Route a compressor's detector to another track (negative `source_track_id` clears it)
+#[no_mangle]
+pub extern "C" fn set_compressor_sidechain_ffi(effect_id: u64, source_track_id: i64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        let source = u64::try_from(source_track_id).ok();
+        ffi_result(api::set_compressor_sidechain(effect_id, source))
+    })
+}
+
+/// Lock a delay's time to a note value (`division_type`: 0 = straight, 1 = dotted, 2 = triplet)
+#[no_mangle]
+pub extern "C" fn set_delay_sync_ffi(effect_id: u64, enabled: i32, note_division: u32, division_type: u32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_delay_sync(effect_id, enabled != 0, note_division, division_type))
+    })
+}
+
+/// Configure the master limiter ceiling (dBFS) and release (ms)
+#[no_mangle]
+pub extern "C" fn set_master_limiter_params_ffi(threshold_db: f32, release_ms: f32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_master_limiter_params(threshold_db, release_ms))
+    })
+}
+
+/// Get the master limiter's current gain reduction in dB (0.0 = not limiting)
+#[no_mangle]
+pub extern "C" fn get_master_limiter_gain_reduction_ffi() -> f32 {
+    ffi_catch(0.0, || {
+        api::get_master_limiter_gain_reduction().unwrap_or(0.0)
+    })
+}
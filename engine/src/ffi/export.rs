@@ -2,7 +2,7 @@ use std::ffi::CStr;
 use std::os::raw::c_char;
 use std::panic::AssertUnwindSafe;
 use crate::api;
-use super::{safe_cstring, ffi_catch};
+use super::{safe_cstring, ffi_catch, ffi_err, ffi_result, FfiErrorCode};
 
 // ============================================================================
 // M8: EXPORT FFI
@@ -123,6 +123,54 @@ pub extern "C" fn export_mp3_with_options_ffi(
     }))
 }
 
+/// Export FLAC (lossless) with configurable options
+/// `bit_depth`: 16 or 24
+/// `sample_rate`: 44100 or 48000
+/// Returns `{"ok": "<ExportResult JSON>"}` on success, or an `{"error": ...}` result
+#[no_mangle]
+pub extern "C" fn export_flac_with_options_ffi(
+    output_path: *const c_char,
+    bit_depth: i32,
+    sample_rate: u32,
+    normalize: bool,
+    mono: bool,
+) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        let output_path_str = unsafe {
+            match CStr::from_ptr(output_path).to_str() {
+                Ok(s) => s.to_string(),
+                Err(_) => return ffi_err(FfiErrorCode::InvalidArg, "Invalid output path"),
+            }
+        };
+
+        ffi_result(api::export_flac_with_options(output_path_str, bit_depth, sample_rate, normalize, mono))
+    }))
+}
+
+/// Export Ogg Vorbis with configurable options
+/// quality: 0 (smallest) to 10 (best)
+/// `sample_rate`: 44100 or 48000
+/// Returns `{"ok": "<ExportResult JSON>"}` on success, or an `{"error": ...}` result
+#[no_mangle]
+pub extern "C" fn export_ogg_with_options_ffi(
+    output_path: *const c_char,
+    quality: i32,
+    sample_rate: u32,
+    normalize: bool,
+    mono: bool,
+) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        let output_path_str = unsafe {
+            match CStr::from_ptr(output_path).to_str() {
+                Ok(s) => s.to_string(),
+                Err(_) => return ffi_err(FfiErrorCode::InvalidArg, "Invalid output path"),
+            }
+        };
+
+        ffi_result(api::export_ogg_with_options(output_path_str, quality, sample_rate, normalize, mono))
+    }))
+}
+
 /// Write ID3 metadata to an MP3 file
 /// `metadata_json`: JSON string of `ExportMetadata`
 /// Returns success message or "Error: <message>"
@@ -1,7 +1,7 @@
 use std::os::raw::c_char;
 use std::panic::AssertUnwindSafe;
 use crate::api;
-use super::{safe_cstring, ffi_catch};
+use super::{safe_cstring, ffi_catch, ffi_result};
 
 // ============================================================================
 // Latency Control FFI
@@ -35,6 +35,38 @@ pub extern "C" fn get_actual_buffer_size_ffi() -> u32 {
     })
 }
 
+/// Get the plugin delay compensation latency in samples (0 if the engine isn't running)
+#[no_mangle]
+pub extern "C" fn get_reported_latency_ffi() -> u64 {
+    ffi_catch(0, || {
+        api::get_reported_latency().unwrap_or(0)
+    })
+}
+
+/// Get the audio callback's DSP load in percent (0 until the callback has run)
+#[no_mangle]
+pub extern "C" fn get_dsp_load_percent_ffi() -> f32 {
+    ffi_catch(0.0, || {
+        api::get_dsp_load_percent().unwrap_or(0.0)
+    })
+}
+
+/// Get the number of xruns (dropouts) since startup or the last reset
+#[no_mangle]
+pub extern "C" fn get_xrun_count_ffi() -> u64 {
+    ffi_catch(0, || {
+        api::get_xrun_count().unwrap_or(0)
+    })
+}
+
+/// Reset the xrun counter
+#[no_mangle]
+pub extern "C" fn reset_xrun_count_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::reset_xrun_count())
+    })
+}
+
 /// Get audio latency info
 /// Returns: `buffer_size`, `input_latency_ms`, `output_latency_ms`, `total_roundtrip_ms`
 /// Output is written to the provided pointers
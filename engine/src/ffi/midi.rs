@@ -1,6 +1,8 @@
+use std::ffi::CStr;
 use std::os::raw::c_char;
+use std::panic::AssertUnwindSafe;
 use crate::api;
-use super::{safe_cstring, ffi_catch};
+use super::{safe_cstring, ffi_catch, ffi_ok, ffi_err, ffi_result, FfiErrorCode};
 
 // ============================================================================
 // M3: MIDI FFI
@@ -220,6 +222,30 @@ pub extern "C" fn add_midi_note_to_clip_ffi(
     })
 }
 
+/// Move a note (by ID) to a new start time in seconds and a new pitch
+#[no_mangle]
+pub extern "C" fn move_midi_note_ffi(clip_id: u64, note_id: u32, new_start: f64, new_pitch: u8) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::move_midi_note(clip_id, note_id, new_start, new_pitch))
+    })
+}
+
+/// Change the length of a note (by ID) in seconds
+#[no_mangle]
+pub extern "C" fn resize_midi_note_ffi(clip_id: u64, note_id: u32, new_duration: f64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::resize_midi_note(clip_id, note_id, new_duration))
+    })
+}
+
+/// Delete a note (by ID) from a MIDI clip
+#[no_mangle]
+pub extern "C" fn delete_midi_note_ffi(clip_id: u64, note_id: u32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::delete_midi_note(clip_id, note_id))
+    })
+}
+
 /// Clear all notes from a MIDI clip
 #[no_mangle]
 pub extern "C" fn clear_midi_clip_ffi(clip_id: u64) -> *mut c_char {
@@ -231,14 +257,47 @@ pub extern "C" fn clear_midi_clip_ffi(clip_id: u64) -> *mut c_char {
     })
 }
 
-/// Quantize a MIDI clip
+/// Quantize a MIDI clip (straight grid, full strength)
 #[no_mangle]
 pub extern "C" fn quantize_midi_clip_ffi(clip_id: u64, grid_division: u32) -> *mut c_char {
+    quantize_midi_clip_groove_ffi(clip_id, grid_division, 0.0, 1.0)
+}
+
+/// Quantize a MIDI clip with swing (0-100%) and strength (0.0-1.0)
+#[no_mangle]
+pub extern "C" fn quantize_midi_clip_groove_ffi(
+    clip_id: u64,
+    grid_division: u32,
+    swing: f32,
+    strength: f32,
+) -> *mut c_char {
     ffi_catch(std::ptr::null_mut(), || {
-        match api::quantize_midi_clip(clip_id, grid_division) {
-            Ok(msg) => safe_cstring(msg).into_raw(),
-            Err(e) => safe_cstring(format!("Error: {e}")).into_raw(),
-        }
+        ffi_result(api::quantize_midi_clip(clip_id, grid_division, swing, strength))
+    })
+}
+
+/// Randomly vary note timing (±`timing_ms`) and velocity (±`velocity_amount`) in a MIDI clip
+/// The same `seed` always gives the same result
+#[no_mangle]
+pub extern "C" fn humanize_midi_clip_ffi(clip_id: u64, timing_ms: f64, velocity_amount: u8, seed: u64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::humanize_midi_clip(clip_id, timing_ms, velocity_amount, seed))
+    })
+}
+
+/// Transpose a MIDI clip by semitones (notes pushed outside 0-127 are removed)
+#[no_mangle]
+pub extern "C" fn transpose_midi_clip_ffi(clip_id: u64, semitones: i32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::transpose_midi_clip(clip_id, semitones))
+    })
+}
+
+/// Snap a MIDI clip's notes to a scale (`root` 0-11, bit `i` of `scale_mask` = `root + i` in scale)
+#[no_mangle]
+pub extern "C" fn scale_quantize_midi_clip_ffi(clip_id: u64, root: u8, scale_mask: u16) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::scale_quantize_midi_clip(clip_id, root, scale_mask))
     })
 }
 
@@ -280,7 +339,7 @@ pub extern "C" fn get_all_midi_clips_info_ffi() -> *mut c_char {
 }
 
 /// Get MIDI notes from a clip
-/// Returns semicolon-separated list: "`note,velocity,start_time,duration`"
+/// Returns semicolon-separated list: "`note,velocity,start_time,duration,note_id`"
 #[no_mangle]
 pub extern "C" fn get_midi_clip_notes_ffi(clip_id: u64) -> *mut c_char {
     ffi_catch(std::ptr::null_mut(), || {
@@ -290,3 +349,51 @@ pub extern "C" fn get_midi_clip_notes_ffi(clip_id: u64) -> *mut c_char {
         }
     })
 }
+
+// ============================================================================
+// MIDI File Import/Export FFI
+// ============================================================================
+
+/// Import a standard MIDI file, creating one MIDI track + clip per file track
+/// Returns comma-separated list of new clip IDs: "`clip_id,clip_id`,..."
+#[no_mangle]
+pub extern "C" fn import_midi_file_ffi(path: *const c_char) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        if path.is_null() {
+            return ffi_err(FfiErrorCode::InvalidArg, "Invalid path");
+        }
+
+        let c_str = unsafe { CStr::from_ptr(path) };
+        let Ok(path_str) = c_str.to_str() else {
+            return ffi_err(FfiErrorCode::InvalidArg, "Invalid path");
+        };
+
+        match api::import_midi_file(path_str.to_string()) {
+            Ok(clip_ids) => {
+                let ids: Vec<String> = clip_ids.iter().map(ToString::to_string).collect();
+                ffi_ok(&ids.join(","))
+            }
+            Err(e) => ffi_err(FfiErrorCode::Internal, &e),
+        }
+    }))
+}
+
+/// Export a MIDI clip to a type-0 standard MIDI file
+#[no_mangle]
+pub extern "C" fn export_midi_clip_ffi(clip_id: u64, path: *const c_char) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        if path.is_null() {
+            return ffi_err(FfiErrorCode::InvalidArg, "Invalid path");
+        }
+
+        let c_str = unsafe { CStr::from_ptr(path) };
+        let Ok(path_str) = c_str.to_str() else {
+            return ffi_err(FfiErrorCode::InvalidArg, "Invalid path");
+        };
+
+        match api::export_midi_clip(clip_id, path_str.to_string()) {
+            Ok(()) => ffi_ok(&format!("Exported MIDI clip {clip_id}")),
+            Err(e) => ffi_err(FfiErrorCode::Internal, &e),
+        }
+    }))
+}
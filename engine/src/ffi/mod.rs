@@ -17,6 +17,7 @@ mod export;
 mod synth;
 mod preview;
 mod devices;
+mod diagnostics;
 
 #[cfg(all(feature = "vst3", not(target_os = "ios")))]
 mod vst3;
@@ -49,7 +50,7 @@ pub(crate) fn ffi_catch<T>(default: T, f: impl FnOnce() -> T + std::panic::Unwin
 /// Error categories for FFI results.
 /// Each maps to a string code the Dart side can match on.
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)] // Incrementally adopted — not all FFI functions use this yet
+#[allow(dead_code)] // EngineState, AudioDevice and FileError are not returned yet
 pub(crate) enum FfiErrorCode {
     /// Resource not found (track, clip, effect, device)
     NotFound,
@@ -79,14 +80,12 @@ impl FfiErrorCode {
 }
 
 /// Return a structured JSON success: `{"ok": "<data>"}`
-#[allow(dead_code)] // Incrementally adopted
 pub(crate) fn ffi_ok(data: &str) -> *mut c_char {
     let json = format!(r#"{{"ok":{}}}"#, serde_json::Value::String(data.to_string()));
     safe_cstring(json).into_raw()
 }
 
 /// Return a structured JSON error: `{"error": {"code": "<code>", "msg": "<message>"}}`
-#[allow(dead_code)] // Incrementally adopted
 pub(crate) fn ffi_err(code: FfiErrorCode, msg: &str) -> *mut c_char {
     let json = format!(
         r#"{{"error":{{"code":"{}","msg":{}}}}}"#,
@@ -99,7 +98,6 @@ pub(crate) fn ffi_err(code: FfiErrorCode, msg: &str) -> *mut c_char {
 /// Convenience: convert a `Result<String, String>` to a structured JSON FFI result.
 /// Maps all errors to `FfiErrorCode::Internal` — use `ffi_ok`/`ffi_err` directly
 /// when you need a specific error code.
-#[allow(dead_code)] // Incrementally adopted
 pub(crate) fn ffi_result(result: Result<String, String>) -> *mut c_char {
     match result {
         Ok(data) => ffi_ok(&data),
@@ -74,6 +74,23 @@ pub extern "C" fn preview_stop_ffi() {
     });
 }
 
+/// Set the stop fade-out curve (0=linear, 1=exponential, 2=equal-power)
+/// Returns false if the curve index is invalid
+#[no_mangle]
+pub extern "C" fn preview_set_fade_curve_ffi(curve: i32) -> bool {
+    ffi_catch(false, || {
+        api::preview_set_fade_curve(curve).is_ok()
+    })
+}
+
+/// Set the stop fade-out length in milliseconds (0 = stop immediately)
+#[no_mangle]
+pub extern "C" fn preview_set_fade_duration_ms_ffi(ms: u32) {
+    ffi_catch((), || {
+        let _ = api::preview_set_fade_duration_ms(ms);
+    });
+}
+
 /// Seek to position in seconds
 #[no_mangle]
 pub extern "C" fn preview_seek_ffi(position_seconds: f64) {
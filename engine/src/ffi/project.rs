@@ -2,7 +2,7 @@ use std::ffi::CStr;
 use std::os::raw::c_char;
 use std::panic::AssertUnwindSafe;
 use crate::api;
-use super::{safe_cstring, ffi_catch};
+use super::{safe_cstring, ffi_catch, ffi_err, ffi_result, FfiErrorCode};
 
 // ============================================================================
 // M5: SAVE/LOAD PROJECT FFI
@@ -53,3 +53,56 @@ pub extern "C" fn load_project_ffi(project_path: *const c_char) -> *mut c_char {
         }
     }))
 }
+
+// ============================================================================
+// AUTO-SAVE / RECOVERY FFI
+// ============================================================================
+
+/// Start writing a recovery snapshot every `interval_seconds` to `recovery_path`
+#[no_mangle]
+pub extern "C" fn start_autosave_ffi(interval_seconds: f64, recovery_path: *const c_char) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        let recovery_path_str = unsafe {
+            match CStr::from_ptr(recovery_path).to_str() {
+                Ok(s) => s.to_string(),
+                Err(_) => return ffi_err(FfiErrorCode::InvalidArg, "Invalid recovery path"),
+            }
+        };
+
+        ffi_result(api::start_autosave(interval_seconds, recovery_path_str))
+    }))
+}
+
+/// Stop auto-save
+/// Returns 1 if it was running, 0 if not
+#[no_mangle]
+pub extern "C" fn stop_autosave_ffi() -> i32 {
+    ffi_catch(0, || i32::from(api::stop_autosave()))
+}
+
+/// Check whether a recovery snapshot exists (call on launch to offer recovery)
+/// Returns 1 if it exists, 0 if not
+#[no_mangle]
+pub extern "C" fn has_recovery_snapshot_ffi(recovery_path: *const c_char) -> i32 {
+    ffi_catch(0, AssertUnwindSafe(|| {
+        let Ok(recovery_path_str) = (unsafe { CStr::from_ptr(recovery_path).to_str() }) else {
+            return 0;
+        };
+        i32::from(api::has_recovery_snapshot(recovery_path_str.to_string()))
+    }))
+}
+
+/// Replace the current project with a recovery snapshot
+#[no_mangle]
+pub extern "C" fn load_recovery_snapshot_ffi(recovery_path: *const c_char) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        let recovery_path_str = unsafe {
+            match CStr::from_ptr(recovery_path).to_str() {
+                Ok(s) => s.to_string(),
+                Err(_) => return ffi_err(FfiErrorCode::InvalidArg, "Invalid recovery path"),
+            }
+        };
+
+        ffi_result(api::load_recovery_snapshot(recovery_path_str))
+    }))
+}
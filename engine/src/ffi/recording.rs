@@ -1,6 +1,6 @@
 use std::os::raw::c_char;
 use crate::api;
-use super::{safe_cstring, ffi_catch};
+use super::{safe_cstring, ffi_catch, ffi_result};
 
 // ============================================================================
 // M2: Recording & Input FFI
@@ -17,13 +17,13 @@ pub extern "C" fn start_recording_ffi() -> *mut c_char {
     })
 }
 
-/// Stop recording and return clip ID (-1 if no recording)
+/// Stop recording and return the first clip ID (-1 if no recording)
+/// All take clip IDs are available from `get_last_recording_takes_ffi`
 #[no_mangle]
 pub extern "C" fn stop_recording_ffi() -> i64 {
     ffi_catch(-1, || {
         match api::stop_recording() {
-            Ok(Some(clip_id)) => clip_id as i64,
-            Ok(None) => -1,  // No recording to stop
+            Ok(clip_ids) => clip_ids.first().map_or(-1, |&id| id as i64), // -1: nothing recorded
             Err(e) => {
                 eprintln!("[FFI] Stop recording failed: {e}");
                 -1
@@ -32,6 +32,16 @@ pub extern "C" fn stop_recording_ffi() -> i64 {
     })
 }
 
+/// Get clip IDs created by the last recording, in take order
+/// Returns comma-separated IDs (empty if none)
+#[no_mangle]
+pub extern "C" fn get_last_recording_takes_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        let ids: Vec<String> = api::get_last_recording_takes().iter().map(u64::to_string).collect();
+        safe_cstring(ids.join(",")).into_raw()
+    })
+}
+
 /// Get recording state (0=Idle, 1=CountingIn, 2=Recording)
 #[no_mangle]
 pub extern "C" fn get_recording_state_ffi() -> i32 {
@@ -168,3 +178,35 @@ pub extern "C" fn is_punch_complete_ffi() -> i32 {
         i32::from(api::is_punch_complete().unwrap_or(false))
     })
 }
+
+/// Set the input monitor mode (0=Off, 1=Software, 2=Direct)
+#[no_mangle]
+pub extern "C" fn set_monitor_mode_ffi(mode: i32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_monitor_mode(mode))
+    })
+}
+
+/// Get the input monitor mode (-1 on error)
+#[no_mangle]
+pub extern "C" fn get_monitor_mode_ffi() -> i32 {
+    ffi_catch(-1, || {
+        api::get_monitor_mode().unwrap_or(-1)
+    })
+}
+
+/// Get the recording latency compensation in samples
+#[no_mangle]
+pub extern "C" fn get_recording_latency_compensation_ffi() -> u64 {
+    ffi_catch(0, || {
+        api::get_recording_latency_compensation().unwrap_or(0)
+    })
+}
+
+/// Override the recording latency compensation in samples
+#[no_mangle]
+pub extern "C" fn set_recording_latency_compensation_ffi(samples: u64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_recording_latency_compensation(samples))
+    })
+}
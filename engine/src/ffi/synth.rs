@@ -2,7 +2,7 @@ use std::ffi::CStr;
 use std::os::raw::c_char;
 use std::panic::AssertUnwindSafe;
 use crate::api;
-use super::{safe_cstring, ffi_catch};
+use super::{safe_cstring, ffi_catch, ffi_ok, ffi_err, ffi_result, FfiErrorCode};
 
 // ============================================================================
 // M6: PER-TRACK SYNTHESIZER FFI
@@ -32,6 +32,14 @@ pub extern "C" fn set_track_instrument_ffi(
     }))
 }
 
+/// Turn a step of a track's step sequencer on or off (`lane` and `step` 0-15)
+#[no_mangle]
+pub extern "C" fn set_sequencer_step_ffi(track_id: u64, lane: u32, step: u32, on: bool, velocity: u8) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_sequencer_step(track_id, lane, step, on, velocity))
+    })
+}
+
 /// Set a synthesizer parameter for a track
 #[no_mangle]
 pub extern "C" fn set_synth_parameter_ffi(
@@ -74,6 +82,75 @@ pub extern "C" fn get_synth_parameters_ffi(track_id: u64) -> *mut c_char {
     })
 }
 
+/// Set the velocity curve of a track's instrument ("linear", "exponential", "logarithmic", "fixed")
+#[no_mangle]
+pub extern "C" fn set_track_velocity_curve_ffi(track_id: u64, curve: *const c_char) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        let curve_str = unsafe {
+            match CStr::from_ptr(curve).to_str() {
+                Ok(s) => s.to_string(),
+                Err(_) => return ffi_err(FfiErrorCode::InvalidArg, "Invalid velocity curve"),
+            }
+        };
+
+        ffi_result(api::set_track_velocity_curve(track_id, curve_str))
+    }))
+}
+
+/// Turn a track's arpeggiator on or off
+#[no_mangle]
+pub extern "C" fn set_track_arp_enabled_ffi(track_id: u64, enabled: bool) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_track_arp_enabled(track_id, enabled))
+    })
+}
+
+/// Set a track's arpeggiator pattern
+/// `mode`: "up", "down", "updown" or "random"; `rate_beats` 0.25 = 1/16 note
+#[no_mangle]
+pub extern "C" fn set_track_arp_params_ffi(
+    track_id: u64,
+    rate_beats: f64,
+    mode: *const c_char,
+    octaves: u8,
+    gate: f32,
+) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        let mode_str = unsafe {
+            match CStr::from_ptr(mode).to_str() {
+                Ok(s) => s.to_string(),
+                Err(_) => return ffi_err(FfiErrorCode::InvalidArg, "Invalid arpeggiator mode"),
+            }
+        };
+
+        ffi_result(api::set_track_arp_params(track_id, rate_beats, mode_str, octaves, gate))
+    }))
+}
+
+/// Set a track synth's pitch wheel range (semitones each way, default 2)
+#[no_mangle]
+pub extern "C" fn set_synth_pitch_bend_range_ffi(track_id: u64, semitones: f32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_synth_pitch_bend_range(track_id, semitones))
+    })
+}
+
+/// Get a track synth's envelope
+/// Returns: "`attack,decay,sustain,release`" (seconds, seconds, 0-1 level, seconds)
+/// Caller must free the returned string
+#[no_mangle]
+pub extern "C" fn get_synth_envelope_ffi(track_id: u64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        match api::get_synth_envelope(track_id) {
+            Ok(Some((attack, decay, sustain, release))) => {
+                ffi_ok(&format!("{attack},{decay},{sustain},{release}"))
+            }
+            Ok(None) => ffi_err(FfiErrorCode::NotFound, &format!("Track {track_id} has no synth")),
+            Err(e) => ffi_err(FfiErrorCode::Internal, &e),
+        }
+    })
+}
+
 /// Send MIDI note on event to track synthesizer
 #[no_mangle]
 pub extern "C" fn send_track_midi_note_on_ffi(track_id: u64, note: u8, velocity: u8) -> *mut c_char {
@@ -156,8 +233,34 @@ pub extern "C" fn load_sample_for_track_ffi(
     }))
 }
 
+/// Add a velocity-layered sample zone to a sampler track
+/// Returns `{"ok": "<message>"}` or an `{"error": ...}` result
+#[no_mangle]
+pub extern "C" fn add_sample_zone_for_track_ffi(
+    track_id: u64,
+    path: *const c_char,
+    root_note: u8,
+    low_note: u8,
+    high_note: u8,
+    low_velocity: u8,
+    high_velocity: u8,
+) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        let path_str = unsafe {
+            match CStr::from_ptr(path).to_str() {
+                Ok(s) => s.to_string(),
+                Err(_) => return ffi_err(FfiErrorCode::InvalidArg, "Invalid path"),
+            }
+        };
+
+        ffi_result(api::add_sample_zone_for_track(
+            track_id, path_str, root_note, low_note, high_note, low_velocity, high_velocity,
+        ))
+    }))
+}
+
 /// Set sampler parameter for a track
-/// `param_name`: "`root_note`", "attack", "`attack_ms`", "release", "`release_ms`"
+/// `param_name`: "`root_note`", "attack", "`attack_ms`", "release", "`release_ms`", "`interpolation_mode`"
 /// Returns success message or error
 #[no_mangle]
 pub extern "C" fn set_sampler_parameter_ffi(
@@ -2,7 +2,7 @@ use std::ffi::CStr;
 use std::os::raw::c_char;
 use std::panic::AssertUnwindSafe;
 use crate::api;
-use super::{safe_cstring, ffi_catch};
+use super::{safe_cstring, ffi_catch, ffi_ok, ffi_err, ffi_result, FfiErrorCode};
 
 // ============================================================================
 // M4: TRACK & MIXING FFI
@@ -79,6 +79,26 @@ pub extern "C" fn set_track_volume_automation_ffi(track_id: u64, csv_data: *cons
     }))
 }
 
+/// Set track pan automation curve
+/// `csv_data` format: "`time_seconds,pan;time_seconds,pan`;..." or empty to clear
+#[no_mangle]
+pub extern "C" fn set_track_pan_automation_ffi(track_id: u64, csv_data: *const c_char) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        let csv = if csv_data.is_null() {
+            String::new()
+        } else {
+            unsafe {
+                match CStr::from_ptr(csv_data).to_str() {
+                    Ok(s) => s.to_string(),
+                    Err(_) => return ffi_err(FfiErrorCode::InvalidArg, "Invalid UTF-8 in csv_data"),
+                }
+            }
+        };
+
+        ffi_result(api::set_track_pan_automation(track_id, &csv))
+    }))
+}
+
 /// Set track pan
 #[no_mangle]
 pub extern "C" fn set_track_pan_ffi(track_id: u64, pan: f32) -> *mut c_char {
@@ -112,6 +132,14 @@ pub extern "C" fn set_track_solo_ffi(track_id: u64, solo: bool) -> *mut c_char {
     })
 }
 
+/// Keep a track playing while other tracks are soloed
+#[no_mangle]
+pub extern "C" fn set_track_solo_safe_ffi(track_id: u64, solo_safe: bool) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_track_solo_safe(track_id, solo_safe))
+    })
+}
+
 /// Set track armed (for recording)
 #[no_mangle]
 pub extern "C" fn set_track_armed_ffi(track_id: u64, armed: bool) -> *mut c_char {
@@ -123,19 +151,27 @@ pub extern "C" fn set_track_armed_ffi(track_id: u64, armed: bool) -> *mut c_char
     })
 }
 
-/// Set track input device and channel
+/// Set track input device and channel (mono)
 #[no_mangle]
 pub extern "C" fn set_track_input_ffi(track_id: u64, device_index: i32, channel: u32) -> *mut c_char {
     ffi_catch(std::ptr::null_mut(), || {
-        match api::set_track_input(track_id, device_index, channel) {
+        match api::set_track_input(track_id, device_index, channel, 1) {
             Ok(msg) => safe_cstring(msg).into_raw(),
             Err(e) => safe_cstring(format!("Error: {e}")).into_raw(),
         }
     })
 }
 
-/// Get track input device and channel
-/// Returns: "`device_index,channel`" (-1 if no input assigned)
+/// Set track input to a stereo pair or a mono channel (`channel_count` 2 or 1)
+#[no_mangle]
+pub extern "C" fn set_track_input_channels_ffi(track_id: u64, device_index: i32, channel: u32, channel_count: u32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_track_input(track_id, device_index, channel, channel_count))
+    })
+}
+
+/// Get track input device and channels
+/// Returns: "`device_index,channel,channel_count`" (-1 if no input assigned)
 #[no_mangle]
 pub extern "C" fn get_track_input_ffi(track_id: u64) -> *mut c_char {
     ffi_catch(std::ptr::null_mut(), || {
@@ -157,6 +193,54 @@ pub extern "C" fn set_track_input_monitoring_ffi(track_id: u64, enabled: bool) -
     })
 }
 
+/// Set track input trim in dB (-24 to +24)
+#[no_mangle]
+pub extern "C" fn set_track_input_gain_ffi(track_id: u64, gain_db: f32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_track_input_gain(track_id, gain_db))
+    })
+}
+
+/// Set whether monitored input is heard through the track's FX chain
+#[no_mangle]
+pub extern "C" fn set_track_monitor_through_fx_ffi(track_id: u64, enabled: bool) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_track_monitor_through_fx(track_id, enabled))
+    })
+}
+
+/// Invert a track's polarity (applied before volume/pan)
+#[no_mangle]
+pub extern "C" fn set_track_phase_invert_ffi(track_id: u64, inverted: bool) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_track_phase_invert(track_id, inverted))
+    })
+}
+
+/// Sum a track to mono (applied before volume/pan)
+#[no_mangle]
+pub extern "C" fn set_track_mono_sum_ffi(track_id: u64, enabled: bool) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_track_mono_sum(track_id, enabled))
+    })
+}
+
+/// Layer the built-in synth under a VST3 instrument on the track
+#[no_mangle]
+pub extern "C" fn set_track_layer_synth_ffi(track_id: u64, enabled: bool) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_track_layer_synth(track_id, enabled))
+    })
+}
+
+/// Route a track into a group track (negative `group_id` routes it to the master)
+#[no_mangle]
+pub extern "C" fn set_track_group_ffi(track_id: u64, group_id: i64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_track_group(track_id, u64::try_from(group_id).ok()))
+    })
+}
+
 /// Get input channel peak level for metering
 /// Returns peak amplitude as a float string (e.g., "0.42")
 #[no_mangle]
@@ -177,6 +261,14 @@ pub extern "C" fn get_input_channel_count_ffi() -> u32 {
     })
 }
 
+/// Get the input device's capture rate in Hz (0 if the engine isn't running)
+#[no_mangle]
+pub extern "C" fn get_input_sample_rate_ffi() -> u32 {
+    ffi_catch(0, || {
+        api::get_input_sample_rate().unwrap_or(0)
+    })
+}
+
 /// Set track name
 #[no_mangle]
 pub extern "C" fn set_track_name_ffi(track_id: u64, name: *const c_char) -> *mut c_char {
@@ -194,6 +286,20 @@ pub extern "C" fn set_track_name_ffi(track_id: u64, name: *const c_char) -> *mut
     }))
 }
 
+/// Choose where a track's meter reads ("pre_fx", "post_fx_pre_fader" or "post_fader")
+#[no_mangle]
+pub extern "C" fn set_track_metering_point_ffi(track_id: u64, point: *const c_char) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        let point_str = unsafe {
+            if point.is_null() {
+                return ffi_err(FfiErrorCode::InvalidArg, "point is null");
+            }
+            CStr::from_ptr(point).to_string_lossy().to_string()
+        };
+        ffi_result(api::set_track_metering_point(track_id, &point_str))
+    }))
+}
+
 /// Get track count
 #[no_mangle]
 pub extern "C" fn get_track_count_ffi() -> usize {
@@ -240,6 +346,149 @@ pub extern "C" fn get_track_peak_levels_ffi(track_id: u64) -> *mut c_char {
     })
 }
 
+/// Get master loudness in LUFS
+/// Returns: "`momentary,short_term,integrated`"
+/// Caller must free the returned string
+#[no_mangle]
+pub extern "C" fn get_master_loudness_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        match api::get_master_loudness() {
+            Ok((momentary, short_term, integrated)) => {
+                ffi_ok(&format!("{momentary:.2},{short_term:.2},{integrated:.2}"))
+            }
+            Err(e) => ffi_err(FfiErrorCode::Internal, &e),
+        }
+    })
+}
+
+/// Reset the master loudness meter (restarts integrated measurement)
+#[no_mangle]
+pub extern "C" fn reset_master_loudness_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::reset_master_loudness())
+    })
+}
+
+/// Get master true peak in dBTP
+/// Returns: "`left,right,max`" (max since playback started)
+/// Caller must free the returned string
+#[no_mangle]
+pub extern "C" fn get_master_true_peak_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        match api::get_master_true_peak() {
+            Ok((left, right, max)) => ffi_ok(&format!("{left:.2},{right:.2},{max:.2}")),
+            Err(e) => ffi_err(FfiErrorCode::Internal, &e),
+        }
+    })
+}
+
+/// Enable/disable master true-peak metering (4x oversampling in the callback)
+#[no_mangle]
+pub extern "C" fn set_master_true_peak_enabled_ffi(enabled: bool) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_master_true_peak_enabled(enabled))
+    })
+}
+
+/// Get the master spectrum as comma-separated linear magnitudes
+/// Returns: "`m0,m1,...`" with `num_bins` values from 0Hz to Nyquist
+/// Caller must free the returned string
+#[no_mangle]
+pub extern "C" fn get_master_spectrum_ffi(num_bins: u32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        match api::get_master_spectrum(num_bins as usize) {
+            Ok(bins) => {
+                let csv: Vec<String> = bins.iter().map(|m| format!("{m:.5}")).collect();
+                ffi_ok(&csv.join(","))
+            }
+            Err(e) => ffi_err(FfiErrorCode::Internal, &e),
+        }
+    })
+}
+
+/// Enable/disable the master spectrum analyzer and set its window size (512, 1024, 2048)
+#[no_mangle]
+pub extern "C" fn set_master_spectrum_settings_ffi(enabled: bool, window_size: u32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_master_spectrum_settings(enabled, window_size as usize))
+    })
+}
+
+/// Load an audio file as the reference track for A/B against the master
+/// Returns the reference's integrated loudness ("`lufs`")
+/// Caller must free the returned string
+#[no_mangle]
+pub extern "C" fn load_reference_track_ffi(path: *const c_char) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        let path = unsafe {
+            if path.is_null() {
+                return ffi_err(FfiErrorCode::InvalidArg, "Null path");
+            }
+            match CStr::from_ptr(path).to_str() {
+                Ok(s) => s,
+                Err(_) => return ffi_err(FfiErrorCode::InvalidArg, "Invalid UTF-8"),
+            }
+        };
+
+        match api::load_reference_track(path) {
+            Ok(lufs) => ffi_ok(&format!("{lufs:.2}")),
+            Err(e) => ffi_err(FfiErrorCode::Internal, &e),
+        }
+    }))
+}
+
+/// Remove the reference track
+#[no_mangle]
+pub extern "C" fn clear_reference_track_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::clear_reference_track())
+    })
+}
+
+/// Switch the output between the reference (true) and the master (false)
+#[no_mangle]
+pub extern "C" fn toggle_reference_ffi(enabled: bool) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::toggle_reference(enabled))
+    })
+}
+
+/// Set the reference track gain in dB
+#[no_mangle]
+pub extern "C" fn set_reference_gain_db_ffi(gain_db: f32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_reference_gain_db(gain_db))
+    })
+}
+
+/// Get the reference track state
+/// Returns: "`enabled,gain_db,lufs`" (enabled is 0 or 1)
+/// Caller must free the returned string
+#[no_mangle]
+pub extern "C" fn get_reference_state_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        match api::get_reference_state() {
+            Ok((enabled, gain_db, lufs)) => {
+                ffi_ok(&format!("{},{gain_db:.2},{lufs:.2}", u8::from(enabled)))
+            }
+            Err(e) => ffi_err(FfiErrorCode::Internal, &e),
+        }
+    })
+}
+
+/// Match the reference gain to the master's integrated loudness
+/// Returns the new reference gain in dB ("`gain_db`")
+/// Caller must free the returned string
+#[no_mangle]
+pub extern "C" fn match_reference_loudness_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        match api::match_reference_loudness() {
+            Ok(gain_db) => ffi_ok(&format!("{gain_db:.2}")),
+            Err(e) => ffi_err(FfiErrorCode::Internal, &e),
+        }
+    })
+}
+
 /// Delete a track
 #[no_mangle]
 pub extern "C" fn delete_track_ffi(track_id: u64) -> *mut c_char {
@@ -278,3 +527,67 @@ pub extern "C" fn duplicate_track_ffi(track_id: u64) -> i64 {
         }
     })
 }
+
+/// Freeze a track: render it to audio and replace its clips, instrument and FX
+///
+/// Returns the frozen clip ID on success, or -1 on failure.
+#[no_mangle]
+pub extern "C" fn freeze_track_ffi(track_id: u64) -> i64 {
+    ffi_catch(-1, || {
+        match api::freeze_track(track_id) {
+            Ok(clip_id) => clip_id as i64,
+            Err(e) => {
+                eprintln!("[FFI] Failed to freeze track {track_id}: {e}");
+                -1
+            }
+        }
+    })
+}
+
+/// Unfreeze a track, restoring its original clips, instrument and FX
+#[no_mangle]
+pub extern "C" fn unfreeze_track_ffi(track_id: u64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::unfreeze_track(track_id))
+    })
+}
+
+/// Check whether a track is frozen (1 = frozen, 0 = not frozen or not found)
+#[no_mangle]
+pub extern "C" fn is_track_frozen_ffi(track_id: u64) -> i32 {
+    ffi_catch(0, || {
+        i32::from(api::is_track_frozen(track_id).unwrap_or(false))
+    })
+}
+
+// ============================================================================
+// UNDO / REDO FFI
+// ============================================================================
+
+/// Undo the most recent change
+/// Returns a description of what was undone, or an `{"error": ...}` result
+#[no_mangle]
+pub extern "C" fn undo_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || ffi_result(api::undo()))
+}
+
+/// Redo the most recently undone change
+/// Returns a description of what was redone, or an `{"error": ...}` result
+#[no_mangle]
+pub extern "C" fn redo_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || ffi_result(api::redo()))
+}
+
+/// Check whether there is a change to undo
+/// Returns 1 if there is, 0 if not
+#[no_mangle]
+pub extern "C" fn can_undo_ffi() -> i32 {
+    ffi_catch(0, || i32::from(api::can_undo()))
+}
+
+/// Check whether there is a change to redo
+/// Returns 1 if there is, 0 if not
+#[no_mangle]
+pub extern "C" fn can_redo_ffi() -> i32 {
+    ffi_catch(0, || i32::from(api::can_redo()))
+}
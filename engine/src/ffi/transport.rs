@@ -1,6 +1,8 @@
+use std::ffi::CStr;
 use std::os::raw::c_char;
+use std::panic::AssertUnwindSafe;
 use crate::api;
-use super::{safe_cstring, ffi_catch};
+use super::{safe_cstring, ffi_catch, ffi_err, ffi_result, FfiErrorCode};
 
 /// Start playback
 #[no_mangle]
@@ -35,6 +37,22 @@ pub extern "C" fn transport_stop_ffi() -> *mut c_char {
     })
 }
 
+/// Start playback after a metronome count-in
+#[no_mangle]
+pub extern "C" fn start_playback_with_count_in_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::start_playback_with_count_in())
+    })
+}
+
+/// Check if counting in before playback (1 = counting in, 0 = not)
+#[no_mangle]
+pub extern "C" fn is_playback_counting_in_ffi() -> i32 {
+    ffi_catch(0, || {
+        i32::from(api::is_playback_counting_in().unwrap_or(false))
+    })
+}
+
 /// Seek to position in seconds
 #[no_mangle]
 pub extern "C" fn transport_seek_ffi(position_seconds: f64) -> *mut c_char {
@@ -46,6 +64,30 @@ pub extern "C" fn transport_seek_ffi(position_seconds: f64) -> *mut c_char {
     })
 }
 
+/// Seek to position in seconds, resetting all effects and the master limiter
+#[no_mangle]
+pub extern "C" fn transport_seek_clean_ffi(position_seconds: f64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::transport_seek_clean(position_seconds))
+    })
+}
+
+/// Scrub: loop a short grain of the mix around a position (while stopped)
+#[no_mangle]
+pub extern "C" fn scrub_to_ffi(position_seconds: f64, window_ms: f64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::scrub_to(position_seconds, window_ms))
+    })
+}
+
+/// Stop scrubbing
+#[no_mangle]
+pub extern "C" fn scrub_stop_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::scrub_stop())
+    })
+}
+
 /// Get playhead position in seconds
 #[no_mangle]
 pub extern "C" fn get_playhead_position_ffi() -> f64 {
@@ -119,6 +161,46 @@ pub extern "C" fn get_tempo_ffi() -> f64 {
     })
 }
 
+/// Change the tempo from a beat on
+#[no_mangle]
+pub extern "C" fn add_tempo_change_ffi(beat: f64, bpm: f64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::add_tempo_change(beat, bpm))
+    })
+}
+
+/// Remove the tempo change at a beat
+#[no_mangle]
+pub extern "C" fn remove_tempo_change_ffi(beat: f64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::remove_tempo_change(beat))
+    })
+}
+
+/// Get the tempo map as JSON
+#[no_mangle]
+pub extern "C" fn get_tempo_map_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::get_tempo_map())
+    })
+}
+
+/// Convert a beat position to seconds through the tempo map
+#[no_mangle]
+pub extern "C" fn beat_to_seconds_ffi(beat: f64) -> f64 {
+    ffi_catch(0.0, || {
+        api::beat_to_seconds(beat).unwrap_or(0.0)
+    })
+}
+
+/// Convert seconds to a beat position through the tempo map
+#[no_mangle]
+pub extern "C" fn seconds_to_beat_ffi(seconds: f64) -> f64 {
+    ffi_catch(0.0, || {
+        api::seconds_to_beat(seconds).unwrap_or(0.0)
+    })
+}
+
 /// Enable or disable metronome
 #[no_mangle]
 pub extern "C" fn set_metronome_enabled_ffi(enabled: i32) -> *mut c_char {
@@ -138,6 +220,40 @@ pub extern "C" fn is_metronome_enabled_ffi() -> i32 {
     })
 }
 
+/// Set metronome clicks per beat (1-4)
+#[no_mangle]
+pub extern "C" fn set_metronome_subdivision_ffi(subdivision: u32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_metronome_subdivision(subdivision))
+    })
+}
+
+/// Enable or disable the metronome downbeat accent
+#[no_mangle]
+pub extern "C" fn set_metronome_accent_enabled_ffi(enabled: i32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_metronome_accent_enabled(enabled != 0))
+    })
+}
+
+/// Load custom metronome click samples (two empty paths restore the synthesized click)
+#[no_mangle]
+pub extern "C" fn set_metronome_samples_ffi(accent_path: *const c_char, normal_path: *const c_char) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), AssertUnwindSafe(|| {
+        if accent_path.is_null() || normal_path.is_null() {
+            return ffi_err(FfiErrorCode::InvalidArg, "Null path");
+        }
+
+        let accent = unsafe { CStr::from_ptr(accent_path) };
+        let normal = unsafe { CStr::from_ptr(normal_path) };
+        let (Ok(accent), Ok(normal)) = (accent.to_str(), normal.to_str()) else {
+            return ffi_err(FfiErrorCode::InvalidArg, "Invalid UTF-8 in path");
+        };
+
+        ffi_result(api::set_metronome_samples(accent.to_string(), normal.to_string()))
+    }))
+}
+
 /// Set time signature (beats per bar)
 #[no_mangle]
 pub extern "C" fn set_time_signature_ffi(beats_per_bar: u32) -> *mut c_char {
@@ -156,3 +272,122 @@ pub extern "C" fn get_time_signature_ffi() -> u32 {
         api::get_time_signature().unwrap_or(4)
     })
 }
+
+/// Change the time signature from a bar on (bar 0 = first bar)
+#[no_mangle]
+pub extern "C" fn add_time_signature_change_ffi(bar: u32, numerator: u32, denominator: u32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::add_time_signature_change(bar, numerator, denominator))
+    })
+}
+
+/// Remove the time-signature change at a bar
+#[no_mangle]
+pub extern "C" fn remove_time_signature_change_ffi(bar: u32) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::remove_time_signature_change(bar))
+    })
+}
+
+/// Get the time-signature map as JSON
+#[no_mangle]
+pub extern "C" fn get_time_signature_map_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::get_time_signature_map())
+    })
+}
+
+/// Set loop region (timeline seconds)
+#[no_mangle]
+pub extern "C" fn set_loop_region_ffi(start_seconds: f64, end_seconds: f64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_loop_region(start_seconds, end_seconds))
+    })
+}
+
+/// Enable or disable loop playback
+#[no_mangle]
+pub extern "C" fn set_loop_enabled_ffi(enabled: bool) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_loop_enabled(enabled))
+    })
+}
+
+/// Check if loop playback is enabled
+#[no_mangle]
+pub extern "C" fn is_loop_enabled_ffi() -> bool {
+    ffi_catch(false, || {
+        api::is_loop_enabled().unwrap_or(false)
+    })
+}
+
+/// Add a named marker at a timeline position; returns the marker ID, or -1 on error
+#[no_mangle]
+pub extern "C" fn add_marker_ffi(time_seconds: f64, label: *const c_char) -> i64 {
+    ffi_catch(-1, AssertUnwindSafe(|| {
+        if label.is_null() {
+            return -1;
+        }
+
+        let c_str = unsafe { CStr::from_ptr(label) };
+        let Ok(label_str) = c_str.to_str() else {
+            return -1;
+        };
+
+        match api::add_marker(time_seconds, label_str.to_string()) {
+            Ok(id) => id as i64,
+            Err(e) => {
+                eprintln!("[FFI] add_marker_ffi error: {e}");
+                -1
+            }
+        }
+    }))
+}
+
+/// Remove a marker
+#[no_mangle]
+pub extern "C" fn remove_marker_ffi(marker_id: u64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::remove_marker(marker_id))
+    })
+}
+
+/// Get all markers as JSON
+#[no_mangle]
+pub extern "C" fn get_markers_ffi() -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::get_markers())
+    })
+}
+
+/// Move the playhead to a marker; returns the new position (seconds), or -1.0 on error
+#[no_mangle]
+pub extern "C" fn jump_to_marker_ffi(marker_id: u64) -> f64 {
+    ffi_catch(-1.0, || {
+        api::jump_to_marker(marker_id).unwrap_or(-1.0)
+    })
+}
+
+/// Move the playhead to the next marker; returns the new position (seconds), or -1.0 if none
+#[no_mangle]
+pub extern "C" fn jump_to_next_marker_ffi() -> f64 {
+    ffi_catch(-1.0, || {
+        api::jump_to_next_marker().unwrap_or(-1.0)
+    })
+}
+
+/// Move the playhead to the previous marker; returns the new position (seconds), or -1.0 if none
+#[no_mangle]
+pub extern "C" fn jump_to_previous_marker_ffi() -> f64 {
+    ffi_catch(-1.0, || {
+        api::jump_to_previous_marker().unwrap_or(-1.0)
+    })
+}
+
+/// Set the loop region between two markers
+#[no_mangle]
+pub extern "C" fn set_loop_to_markers_ffi(start_marker_id: u64, end_marker_id: u64) -> *mut c_char {
+    ffi_catch(std::ptr::null_mut(), || {
+        ffi_result(api::set_loop_to_markers(start_marker_id, end_marker_id))
+    })
+}
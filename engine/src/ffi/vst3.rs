@@ -72,6 +72,20 @@ pub extern "C" fn get_vst3_parameter_count_ffi(effect_id: i64) -> i32 {
     })
 }
 
+/// Get the processing latency a VST3 effect reports, in samples (-1 on error)
+#[no_mangle]
+pub extern "C" fn get_vst3_latency_ffi(effect_id: i64) -> i32 {
+    ffi_catch(-1, || {
+        match api::get_vst3_latency(effect_id as u64) {
+            Ok(latency) => latency as i32,
+            Err(e) => {
+                eprintln!("[FFI] Failed to get VST3 latency: {e}");
+                -1
+            }
+        }
+    })
+}
+
 /// Get information about a VST3 parameter
 /// Returns a CSV string: "name,min,max,default"
 #[no_mangle]
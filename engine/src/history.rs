@@ -0,0 +1,321 @@
+/// Engine-level undo/redo history
+///
+/// Mutations made through the API are recorded as `Command`s. Each command knows
+/// its inverse, and clips keep their IDs across undo/redo so IDs held by the UI
+/// stay valid.
+use crate::audio_file::AudioClip;
+use crate::track::{ClipId, TimelineClip, TrackId, TrackManager};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Maximum number of undo steps kept
+const MAX_UNDO_STEPS: usize = 200;
+
+/// Repeated changes to the same target within this window merge into one step
+/// (a fader or clip drag sends many updates per gesture)
+const COALESCE_WINDOW: Duration = Duration::from_millis(500);
+
+/// An undoable mutation
+#[derive(Clone)]
+pub enum Command {
+    /// An audio clip was added to a track
+    AddClip { track_id: TrackId, clip: TimelineClip },
+    /// An audio clip was removed from a track
+    RemoveClip { track_id: TrackId, clip: TimelineClip },
+    /// A clip (audio or MIDI) was moved along its track
+    MoveClip { track_id: TrackId, clip_id: ClipId, from: f64, to: f64 },
+    /// Track volume changed (dB)
+    SetTrackVolume { track_id: TrackId, from: f32, to: f32 },
+    /// Track pan changed (-1.0 to +1.0)
+    SetTrackPan { track_id: TrackId, from: f32, to: f32 },
+}
+
+impl Command {
+    /// The command that reverts this one
+    pub fn inverse(&self) -> Command {
+        match self {
+            Command::AddClip { track_id, clip } => {
+                Command::RemoveClip { track_id: *track_id, clip: clip.clone() }
+            }
+            Command::RemoveClip { track_id, clip } => {
+                Command::AddClip { track_id: *track_id, clip: clip.clone() }
+            }
+            Command::MoveClip { track_id, clip_id, from, to } => {
+                Command::MoveClip { track_id: *track_id, clip_id: *clip_id, from: *to, to: *from }
+            }
+            Command::SetTrackVolume { track_id, from, to } => {
+                Command::SetTrackVolume { track_id: *track_id, from: *to, to: *from }
+            }
+            Command::SetTrackPan { track_id, from, to } => {
+                Command::SetTrackPan { track_id: *track_id, from: *to, to: *from }
+            }
+        }
+    }
+
+    /// Short description for the UI (e.g. "Undo Move clip 3")
+    pub fn description(&self) -> String {
+        match self {
+            Command::AddClip { clip, .. } => format!("Add clip {}", clip.id),
+            Command::RemoveClip { clip, .. } => format!("Remove clip {}", clip.id),
+            Command::MoveClip { clip_id, .. } => format!("Move clip {clip_id}"),
+            Command::SetTrackVolume { track_id, .. } => format!("Track {track_id} volume"),
+            Command::SetTrackPan { track_id, .. } => format!("Track {track_id} pan"),
+        }
+    }
+
+    /// Perform the command
+    ///
+    /// Added clips are (re)registered in `clips_map` so waveforms and saving see them.
+    pub fn apply(
+        &self,
+        track_manager: &TrackManager,
+        clips_map: &mut HashMap<ClipId, Arc<AudioClip>>,
+    ) -> Result<(), String> {
+        let track_id = match self {
+            Command::AddClip { track_id, .. }
+            | Command::RemoveClip { track_id, .. }
+            | Command::MoveClip { track_id, .. }
+            | Command::SetTrackVolume { track_id, .. }
+            | Command::SetTrackPan { track_id, .. } => *track_id,
+        };
+        let track_arc = track_manager
+            .get_track(track_id)
+            .ok_or_else(|| format!("Track {track_id} not found"))?;
+        let mut track = track_arc.lock();
+
+        match self {
+            Command::AddClip { clip, .. } => {
+                if track.audio_clips.iter().any(|c| c.id == clip.id) {
+                    return Err(format!("Clip {} is already on track {track_id}", clip.id));
+                }
+                clips_map.insert(clip.id, clip.clip.clone());
+                track.audio_clips.push(clip.clone());
+            }
+            Command::RemoveClip { clip, .. } => {
+                let index = track
+                    .audio_clips
+                    .iter()
+                    .position(|c| c.id == clip.id)
+                    .ok_or_else(|| format!("Clip {} not found on track {track_id}", clip.id))?;
+                // Audio stays in the clips map, as with `remove_audio_clip`
+                track.audio_clips.remove(index);
+            }
+            Command::MoveClip { clip_id, to, .. } => {
+                if let Some(clip) = track.audio_clips.iter_mut().find(|c| c.id == *clip_id) {
+                    clip.start_time = to.max(0.0);
+                } else if let Some(clip) = track.midi_clips.iter_mut().find(|c| c.id == *clip_id) {
+                    clip.start_time = to.max(0.0);
+                } else {
+                    return Err(format!("Clip {clip_id} not found on track {track_id}"));
+                }
+            }
+            Command::SetTrackVolume { to, .. } => track.volume_db = *to,
+            Command::SetTrackPan { to, .. } => track.pan = *to,
+        }
+        Ok(())
+    }
+
+    /// Fold a follow-up change to the same target into this command
+    ///
+    /// Returns false (leaving `self` untouched) if `next` isn't a continuation.
+    fn merge(&mut self, next: &Command) -> bool {
+        match (self, next) {
+            (
+                Command::MoveClip { track_id, clip_id, to, .. },
+                Command::MoveClip { track_id: next_track, clip_id: next_clip, to: next_to, .. },
+            ) if track_id == next_track && clip_id == next_clip => *to = *next_to,
+            (
+                Command::SetTrackVolume { track_id, to, .. },
+                Command::SetTrackVolume { track_id: next_track, to: next_to, .. },
+            )
+            | (
+                Command::SetTrackPan { track_id, to, .. },
+                Command::SetTrackPan { track_id: next_track, to: next_to, .. },
+            ) if track_id == next_track => *to = *next_to,
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Undo and redo stacks
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Command>,
+    redo: Vec<Command>,
+    /// When the top undo step was last recorded (for coalescing)
+    last_recorded: Option<Instant>,
+}
+
+impl History {
+    pub const fn new() -> Self {
+        Self { undo: Vec::new(), redo: Vec::new(), last_recorded: None }
+    }
+
+    /// Record a new user action (clears the redo stack)
+    pub fn record(&mut self, command: Command) {
+        self.record_at(command, Instant::now());
+    }
+
+    fn record_at(&mut self, command: Command, now: Instant) {
+        self.redo.clear();
+
+        let recent = self
+            .last_recorded
+            .is_some_and(|last| now.duration_since(last) < COALESCE_WINDOW);
+        self.last_recorded = Some(now);
+        if recent {
+            if let Some(top) = self.undo.last_mut() {
+                if top.merge(&command) {
+                    return;
+                }
+            }
+        }
+
+        self.undo.push(command);
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Take the most recent step to undo
+    pub fn pop_undo(&mut self) -> Option<Command> {
+        self.last_recorded = None;
+        self.undo.pop()
+    }
+
+    /// Take the most recently undone step to redo
+    pub fn pop_redo(&mut self) -> Option<Command> {
+        self.redo.pop()
+    }
+
+    /// Return a step that was successfully undone (it becomes redoable)
+    pub fn push_undone(&mut self, command: Command) {
+        self.redo.push(command);
+    }
+
+    /// Return a step that was successfully redone (it becomes undoable again)
+    pub fn push_redone(&mut self, command: Command) {
+        self.last_recorded = None;
+        self.undo.push(command);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forget all history (e.g. when a project is loaded)
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last_recorded = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::track::TrackType;
+
+    fn test_clip(id: ClipId, start_time: f64) -> TimelineClip {
+        let audio = Arc::new(AudioClip {
+            samples: vec![0.0; 96],
+            channels: 2,
+            sample_rate: 48000,
+            duration_seconds: 0.001,
+            file_path: String::new(),
+            stream: None,
+        });
+        TimelineClip::new(id, audio, start_time)
+    }
+
+    /// Undo the top step the way the API does
+    fn undo(history: &mut History, tracks: &TrackManager, clips: &mut HashMap<ClipId, Arc<AudioClip>>) {
+        let command = history.pop_undo().unwrap();
+        command.inverse().apply(tracks, clips).unwrap();
+        history.push_undone(command);
+    }
+
+    fn redo(history: &mut History, tracks: &TrackManager, clips: &mut HashMap<ClipId, Arc<AudioClip>>) {
+        let command = history.pop_redo().unwrap();
+        command.apply(tracks, clips).unwrap();
+        history.push_redone(command);
+    }
+
+    #[test]
+    fn test_clip_add_move_remove_round_trip() {
+        let mut tracks = TrackManager::new();
+        let track_id = tracks.create_track(TrackType::Audio, "Audio 1".to_string());
+        let mut clips = HashMap::new();
+        let mut history = History::new();
+        let start = Instant::now();
+
+        let add = Command::AddClip { track_id, clip: test_clip(7, 1.0) };
+        add.apply(&tracks, &mut clips).unwrap();
+        history.record_at(add, start);
+        let moved = Command::MoveClip { track_id, clip_id: 7, from: 1.0, to: 4.0 };
+        moved.apply(&tracks, &mut clips).unwrap();
+        history.record_at(moved, start + Duration::from_secs(1));
+        let placed = tracks.get_track(track_id).unwrap().lock().audio_clips[0].clone();
+        let removed = Command::RemoveClip { track_id, clip: placed };
+        removed.apply(&tracks, &mut clips).unwrap();
+        history.record_at(removed, start + Duration::from_secs(2));
+
+        let clip_start = |tracks: &TrackManager| {
+            let track_arc = tracks.get_track(track_id).unwrap();
+            let track = track_arc.lock();
+            track.audio_clips.iter().find(|c| c.id == 7).map(|c| c.start_time)
+        };
+        assert_eq!(clip_start(&tracks), None);
+
+        // Undo restores the clip with its original ID, then its position, then removes it
+        undo(&mut history, &tracks, &mut clips);
+        assert_eq!(clip_start(&tracks), Some(4.0));
+        undo(&mut history, &tracks, &mut clips);
+        assert_eq!(clip_start(&tracks), Some(1.0));
+        undo(&mut history, &tracks, &mut clips);
+        assert_eq!(clip_start(&tracks), None);
+        assert!(!history.can_undo());
+        assert!(clips.contains_key(&7));
+
+        redo(&mut history, &tracks, &mut clips);
+        redo(&mut history, &tracks, &mut clips);
+        assert_eq!(clip_start(&tracks), Some(4.0));
+        assert!(history.can_redo());
+
+        // A new action drops the redo stack
+        history.record(Command::SetTrackPan { track_id, from: 0.0, to: 0.5 });
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_fader_gesture_coalesces_into_one_step() {
+        let mut tracks = TrackManager::new();
+        let track_id = tracks.create_track(TrackType::Audio, "Audio 1".to_string());
+        let mut clips = HashMap::new();
+        let mut history = History::new();
+        let start = Instant::now();
+
+        for (i, (from, to)) in [(0.0, -1.0), (-1.0, -2.0), (-2.0, -3.0)].into_iter().enumerate() {
+            let command = Command::SetTrackVolume { track_id, from, to };
+            command.apply(&tracks, &mut clips).unwrap();
+            history.record_at(command, start + Duration::from_millis(100 * i as u64));
+        }
+        // A later, separate gesture is its own step
+        let command = Command::SetTrackVolume { track_id, from: -3.0, to: -6.0 };
+        command.apply(&tracks, &mut clips).unwrap();
+        history.record_at(command, start + Duration::from_secs(3));
+
+        let volume = |tracks: &TrackManager| tracks.get_track(track_id).unwrap().lock().volume_db;
+        undo(&mut history, &tracks, &mut clips);
+        assert!((volume(&tracks) + 3.0).abs() < 1e-6);
+        undo(&mut history, &tracks, &mut clips);
+        assert!(volume(&tracks).abs() < 1e-6);
+        assert!(!history.can_undo());
+    }
+}
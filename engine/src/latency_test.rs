@@ -7,6 +7,7 @@
 //! 4. Calculating latency from sample offset
 
 use std::sync::atomic::{AtomicU8, AtomicU64, AtomicU32, Ordering};
+use std::sync::Arc;
 use parking_lot::Mutex;
 use std::f32::consts::PI;
 
@@ -94,6 +95,9 @@ pub struct LatencyTest {
 
     /// Current write position in input buffer
     input_write_pos: AtomicU32,
+
+    /// Receives each successful measurement in samples (recording latency compensation)
+    result_target: Option<Arc<AtomicU64>>,
 }
 
 impl LatencyTest {
@@ -121,9 +125,16 @@ impl LatencyTest {
             config,
             input_buffer: Mutex::new(vec![0.0; buffer_size]),
             input_write_pos: AtomicU32::new(0),
+            result_target: None,
         }
     }
 
+    /// Store each successful measurement (in samples) in `target` as well
+    pub fn with_result_target(mut self, target: Arc<AtomicU64>) -> Self {
+        self.result_target = Some(target);
+        self
+    }
+
     /// Start a new latency test
     pub fn start(&self) -> Result<(), String> {
         let current_state = LatencyTestState::from(self.state.load(Ordering::SeqCst));
@@ -289,6 +300,9 @@ impl LatencyTest {
             { let mut result = self.result_ms.lock();
                 *result = Some(latency_ms);
             }
+            if let Some(target) = &self.result_target {
+                target.store(latency_samples, Ordering::SeqCst);
+            }
 
             self.state.store(LatencyTestState::Done as u8, Ordering::SeqCst);
             eprintln!("🎚️ [LatencyTest] Result: {latency_ms:.1}ms ({latency_samples} samples)");
@@ -349,4 +363,34 @@ mod tests {
         let sample = test.generate_output(5000);
         assert!(sample.abs() > 0.0);
     }
+
+    #[test]
+    fn test_measurement_is_stored_in_the_result_target() {
+        let target = Arc::new(AtomicU64::new(0));
+        let test = LatencyTest::new(48000).with_result_target(target.clone());
+        test.start().unwrap();
+
+        let mut sample_idx = 1;
+        while test.get_state() != LatencyTestState::Playing {
+            test.process_input(0.0, sample_idx);
+            sample_idx += 1;
+        }
+        let tone_start = sample_idx - 1;
+
+        // Play the tone out, then hear it come back 250 samples after it started
+        while test.get_state() == LatencyTestState::Playing {
+            test.generate_output(sample_idx);
+            test.process_input(0.0, sample_idx);
+            sample_idx += 1;
+        }
+        let detected = tone_start + 4800 + 250;
+        while sample_idx < detected {
+            test.process_input(0.0, sample_idx);
+            sample_idx += 1;
+        }
+        test.process_input(0.5, detected);
+
+        assert_eq!(test.get_state(), LatencyTestState::Done);
+        assert_eq!(target.load(Ordering::SeqCst), detected - tone_start);
+    }
 }
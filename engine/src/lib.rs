@@ -34,14 +34,29 @@
 pub mod audio_file;
 mod audio_graph;
 pub mod midi;
+mod midi_file;  // Standard MIDI file import/export
 pub mod synth;
+mod arpeggiator; // Tempo-synced arpeggiator for MIDI tracks
+mod step_sequencer; // Drum-machine step sequencer for MIDI tracks
 mod sampler;    // Sampler instrument (plays samples via MIDI)
 mod track;      // M4: Track system
 pub mod effects;    // M4: Audio effects
+mod loudness;   // LUFS loudness metering
+mod spectrum;   // Master spectrum analyzer
+mod true_peak;  // Master true-peak (inter-sample) metering
+mod dsp_load;   // Audio callback DSP load and xrun measurement
 mod project;    // M5: Project serialization
 mod export;     // M8: Audio export (WAV, MP3, stems)
 mod stretch;    // Pitch-preserved time-stretching
 mod preview;    // Library audio preview
+mod scrub;      // Scrub playback while dragging the playhead
+mod reference;  // Reference track for A/B against the master
+mod waveform;   // Cached waveform overview peaks
+mod clip_stream; // On-demand streaming of very large clips
+mod markers;    // Named timeline markers
+mod tempo_map;  // Tempo changes over the timeline
+mod time_signature; // Time-signature changes over the timeline
+mod history;    // Undo/redo command history
 
 // ============================================
 // Native platform modules (non-WASM)
@@ -0,0 +1,345 @@
+/// Loudness metering (ITU-R BS.1770 / EBU R128)
+///
+/// Computes momentary (400ms), short-term (3s) and gated integrated loudness
+/// in LUFS from K-weighted stereo audio. The meter state lives on the audio
+/// thread; results are published through atomics so the UI can read them
+/// without locking.
+use crate::audio_file::TARGET_SAMPLE_RATE;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Reported loudness when there is no measurable signal
+pub const LOUDNESS_FLOOR_LUFS: f32 = -120.0;
+
+/// Measurement step (100ms blocks, 75% overlap for the 400ms momentary window)
+const SUB_BLOCK_SAMPLES: u32 = TARGET_SAMPLE_RATE / 10;
+/// Momentary window = 4 sub-blocks (400ms)
+const MOMENTARY_BLOCKS: usize = 4;
+/// Short-term window = 30 sub-blocks (3s)
+const SHORT_TERM_BLOCKS: usize = 30;
+
+/// Absolute gate for integrated loudness (LUFS)
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+/// Relative gate below the ungated level (LU)
+const RELATIVE_GATE_LU: f64 = -10.0;
+/// Integrated histogram range and resolution: -70..+10 LUFS in 0.1 LU bins
+const HISTOGRAM_MAX_LUFS: f64 = 10.0;
+const HISTOGRAM_BINS: usize = 800;
+
+/// Direct-form I biquad used for K-weighting
+#[derive(Clone, Copy)]
+struct KWeightingStage {
+    b: [f64; 3],
+    a: [f64; 2],
+    x1: f64,
+    x2: f64,
+    y1: f64,
+    y2: f64,
+}
+
+impl KWeightingStage {
+    const fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self { b, a, x1: 0.0, x2: 0.0, y1: 0.0, y2: 0.0 }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x1 + self.b[2] * self.x2
+            - self.a[0] * self.y1
+            - self.a[1] * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+/// K-weighting filter for one channel: high-shelf pre-filter + RLB high-pass (48kHz coefficients)
+#[derive(Clone, Copy)]
+struct KWeighting {
+    shelf: KWeightingStage,
+    high_pass: KWeightingStage,
+}
+
+impl KWeighting {
+    const fn new() -> Self {
+        Self {
+            shelf: KWeightingStage::new(
+                [1.535_124_859_586_97, -2.691_696_189_406_38, 1.198_392_810_852_85],
+                [-1.690_659_293_182_41, 0.732_480_774_215_85],
+            ),
+            high_pass: KWeightingStage::new(
+                [1.0, -2.0, 1.0],
+                [-1.990_047_454_833_98, 0.990_072_250_366_21],
+            ),
+        }
+    }
+
+    fn process(&mut self, x: f32) -> f64 {
+        self.high_pass.process(self.shelf.process(f64::from(x)))
+    }
+}
+
+fn power_to_lufs(power: f64) -> f64 {
+    if power <= 0.0 {
+        f64::NEG_INFINITY
+    } else {
+        -0.691 + 10.0 * power.log10()
+    }
+}
+
+fn lufs_to_published(lufs: f64) -> f32 {
+    if lufs.is_finite() {
+        (lufs as f32).max(LOUDNESS_FLOOR_LUFS)
+    } else {
+        LOUDNESS_FLOOR_LUFS
+    }
+}
+
+/// Latest loudness readings, shared between the audio thread and the API
+pub struct LoudnessReadings {
+    momentary: AtomicU32,
+    short_term: AtomicU32,
+    integrated: AtomicU32,
+    reset_requested: AtomicBool,
+}
+
+impl Default for LoudnessReadings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoudnessReadings {
+    pub fn new() -> Self {
+        Self {
+            momentary: AtomicU32::new(LOUDNESS_FLOOR_LUFS.to_bits()),
+            short_term: AtomicU32::new(LOUDNESS_FLOOR_LUFS.to_bits()),
+            integrated: AtomicU32::new(LOUDNESS_FLOOR_LUFS.to_bits()),
+            reset_requested: AtomicBool::new(false),
+        }
+    }
+
+    /// Get (momentary, short-term, integrated) loudness in LUFS
+    pub fn get(&self) -> (f32, f32, f32) {
+        (
+            f32::from_bits(self.momentary.load(Ordering::Relaxed)),
+            f32::from_bits(self.short_term.load(Ordering::Relaxed)),
+            f32::from_bits(self.integrated.load(Ordering::Relaxed)),
+        )
+    }
+
+    /// Ask the audio thread to clear the meter (e.g. when playback starts)
+    pub fn request_reset(&self) {
+        self.reset_requested.store(true, Ordering::SeqCst);
+    }
+
+    fn publish(&self, momentary: f64, short_term: f64, integrated: f64) {
+        self.momentary.store(lufs_to_published(momentary).to_bits(), Ordering::Relaxed);
+        self.short_term.store(lufs_to_published(short_term).to_bits(), Ordering::Relaxed);
+        self.integrated.store(lufs_to_published(integrated).to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Loudness meter state (owned by the audio thread, allocation-free after construction)
+pub struct LoudnessMeter {
+    filters: [KWeighting; 2],
+    /// Sum of K-weighted squares in the current sub-block (both channels)
+    sub_block_sum: f64,
+    sub_block_len: u32,
+    /// Mean-square power of the most recent sub-blocks (ring buffer)
+    sub_blocks: [f64; SHORT_TERM_BLOCKS],
+    sub_block_pos: usize,
+    sub_block_count: usize,
+    /// Gating histogram of 400ms block powers: (count, power sum) per bin
+    histogram_count: Vec<u64>,
+    histogram_power: Vec<f64>,
+}
+
+impl Default for LoudnessMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoudnessMeter {
+    pub fn new() -> Self {
+        Self {
+            filters: [KWeighting::new(); 2],
+            sub_block_sum: 0.0,
+            sub_block_len: 0,
+            sub_blocks: [0.0; SHORT_TERM_BLOCKS],
+            sub_block_pos: 0,
+            sub_block_count: 0,
+            histogram_count: vec![0; HISTOGRAM_BINS],
+            histogram_power: vec![0.0; HISTOGRAM_BINS],
+        }
+    }
+
+    /// Clear all measurement history
+    pub fn reset(&mut self) {
+        self.filters = [KWeighting::new(); 2];
+        self.sub_block_sum = 0.0;
+        self.sub_block_len = 0;
+        self.sub_blocks = [0.0; SHORT_TERM_BLOCKS];
+        self.sub_block_pos = 0;
+        self.sub_block_count = 0;
+        self.histogram_count.fill(0);
+        self.histogram_power.fill(0.0);
+    }
+
+    /// Feed one stereo frame; publishes new readings every 100ms
+    pub fn process_frame(&mut self, left: f32, right: f32, readings: &LoudnessReadings) {
+        if readings.reset_requested.swap(false, Ordering::SeqCst) {
+            self.reset();
+            readings.publish(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+        }
+
+        let l = self.filters[0].process(left);
+        let r = self.filters[1].process(right);
+        self.sub_block_sum += l * l + r * r;
+        self.sub_block_len += 1;
+
+        if self.sub_block_len >= SUB_BLOCK_SAMPLES {
+            self.finish_sub_block(readings);
+        }
+    }
+
+    fn finish_sub_block(&mut self, readings: &LoudnessReadings) {
+        self.sub_blocks[self.sub_block_pos] = self.sub_block_sum / f64::from(self.sub_block_len);
+        self.sub_block_pos = (self.sub_block_pos + 1) % SHORT_TERM_BLOCKS;
+        self.sub_block_count = (self.sub_block_count + 1).min(SHORT_TERM_BLOCKS);
+        self.sub_block_sum = 0.0;
+        self.sub_block_len = 0;
+
+        let momentary_power = self.window_power(MOMENTARY_BLOCKS);
+        let short_term_power = self.window_power(SHORT_TERM_BLOCKS);
+        let momentary = power_to_lufs(momentary_power);
+
+        // Each full 400ms block (every 100ms) contributes to the integrated measurement
+        if self.sub_block_count >= MOMENTARY_BLOCKS && momentary > ABSOLUTE_GATE_LUFS {
+            let bin = ((momentary - ABSOLUTE_GATE_LUFS) * 10.0) as usize;
+            let bin = bin.min(HISTOGRAM_BINS - 1);
+            self.histogram_count[bin] += 1;
+            self.histogram_power[bin] += momentary_power;
+        }
+
+        readings.publish(momentary, power_to_lufs(short_term_power), self.integrated_lufs());
+    }
+
+    /// Mean power over the most recent `blocks` sub-blocks (fewer if not yet filled)
+    fn window_power(&self, blocks: usize) -> f64 {
+        let n = blocks.min(self.sub_block_count);
+        if n == 0 {
+            return 0.0;
+        }
+        let sum: f64 = (1..=n)
+            .map(|i| self.sub_blocks[(self.sub_block_pos + SHORT_TERM_BLOCKS - i) % SHORT_TERM_BLOCKS])
+            .sum();
+        sum / n as f64
+    }
+
+    /// Gated integrated loudness of a whole stereo interleaved buffer at the engine rate
+    ///
+    /// Used offline (e.g. export loudness normalization); returns the floor for silence.
+    pub fn measure_integrated(samples: &[f32]) -> f32 {
+        let mut meter = Self::new();
+        let readings = LoudnessReadings::new();
+        for frame in samples.chunks_exact(2) {
+            meter.process_frame(frame[0], frame[1], &readings);
+        }
+        lufs_to_published(meter.integrated_lufs())
+    }
+
+    /// Gated integrated loudness from the block histogram
+    fn integrated_lufs(&self) -> f64 {
+        let gated_mean = |min_bin: usize| {
+            let count: u64 = self.histogram_count[min_bin..].iter().sum();
+            let power: f64 = self.histogram_power[min_bin..].iter().sum();
+            if count == 0 { 0.0 } else { power / count as f64 }
+        };
+
+        // Relative gate: 10 LU below the loudness of all blocks above the absolute gate
+        let relative_gate = power_to_lufs(gated_mean(0)) + RELATIVE_GATE_LU;
+        if !relative_gate.is_finite() {
+            return f64::NEG_INFINITY;
+        }
+        let gate_bin = ((relative_gate - ABSOLUTE_GATE_LUFS) * 10.0)
+            .clamp(0.0, (HISTOGRAM_MAX_LUFS - ABSOLUTE_GATE_LUFS) * 10.0 - 1.0) as usize;
+        power_to_lufs(gated_mean(gate_bin))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn measure_sine(meter: &mut LoudnessMeter, readings: &LoudnessReadings, amplitude: f32, seconds: f32) {
+        let frames = (seconds * TARGET_SAMPLE_RATE as f32) as u32;
+        for i in 0..frames {
+            let s = amplitude * (2.0 * PI * 1000.0 * i as f32 / TARGET_SAMPLE_RATE as f32).sin();
+            meter.process_frame(s, s, readings);
+        }
+    }
+
+    #[test]
+    fn test_full_scale_1khz_stereo_sine_reads_about_zero_lufs() {
+        // BS.1770: a 0 dBFS 1kHz sine in one channel reads -3.01 LUFS, so both channels ≈ 0 LUFS
+        let mut meter = LoudnessMeter::new();
+        let readings = LoudnessReadings::new();
+        measure_sine(&mut meter, &readings, 1.0, 4.0);
+
+        let (momentary, short_term, integrated) = readings.get();
+        assert!(momentary.abs() < 0.2, "momentary {momentary}");
+        assert!(short_term.abs() < 0.2, "short-term {short_term}");
+        assert!(integrated.abs() < 0.2, "integrated {integrated}");
+    }
+
+    #[test]
+    fn test_quiet_passages_are_gated_from_integrated() {
+        let mut meter = LoudnessMeter::new();
+        let readings = LoudnessReadings::new();
+        measure_sine(&mut meter, &readings, 0.5, 3.0);
+        let (_, _, loud_only) = readings.get();
+
+        // 20 dB quieter material falls below the relative gate
+        measure_sine(&mut meter, &readings, 0.05, 3.0);
+        let (momentary, _, integrated) = readings.get();
+        assert!(momentary < loud_only - 15.0);
+        assert!((integrated - loud_only).abs() < 0.5, "integrated {integrated} vs {loud_only}");
+    }
+
+    #[test]
+    fn test_silence_and_reset_report_floor() {
+        let mut meter = LoudnessMeter::new();
+        let readings = LoudnessReadings::new();
+        measure_sine(&mut meter, &readings, 0.0, 1.0);
+        let (momentary, short_term, integrated) = readings.get();
+        assert!((momentary - LOUDNESS_FLOOR_LUFS).abs() < 1e-6);
+        assert!((short_term - LOUDNESS_FLOOR_LUFS).abs() < 1e-6);
+        assert!((integrated - LOUDNESS_FLOOR_LUFS).abs() < 1e-6);
+
+        measure_sine(&mut meter, &readings, 1.0, 1.0);
+        readings.request_reset();
+        meter.process_frame(0.0, 0.0, &readings);
+        assert!((readings.get().2 - LOUDNESS_FLOOR_LUFS).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_measure_integrated_matches_live_meter() {
+        let mut meter = LoudnessMeter::new();
+        let readings = LoudnessReadings::new();
+        measure_sine(&mut meter, &readings, 0.25, 4.0);
+
+        let frames = (4.0 * TARGET_SAMPLE_RATE as f32) as u32;
+        let buffer: Vec<f32> = (0..frames)
+            .flat_map(|i| {
+                let s = 0.25 * (2.0 * PI * 1000.0 * i as f32 / TARGET_SAMPLE_RATE as f32).sin();
+                [s, s]
+            })
+            .collect();
+        let measured = LoudnessMeter::measure_integrated(&buffer);
+        assert!((measured - readings.get().2).abs() < 1e-3, "measured {measured}");
+        assert!((LoudnessMeter::measure_integrated(&vec![0.0; 96_000]) - LOUDNESS_FLOOR_LUFS).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,104 @@
+//! Named timeline markers ("Verse", "Chorus", ...) for arrangement and navigation
+//!
+//! Markers are kept sorted by time (markers at the same time stay in the order they
+//! were added). IDs are never reused within a session, so the UI can hold on to them
+//! while markers are added and removed around them.
+
+/// Marker ID, unique within a session
+pub type MarkerId = u64;
+
+/// One named position on the timeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    pub id: MarkerId,
+    /// Timeline position in seconds
+    pub time_seconds: f64,
+    pub label: String,
+}
+
+/// All markers of a project
+#[derive(Debug, Default)]
+pub struct MarkerList {
+    markers: Vec<Marker>,
+    next_id: MarkerId,
+}
+
+impl MarkerList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a marker at `time_seconds` (clamped to the timeline start); returns its ID
+    pub fn add(&mut self, time_seconds: f64, label: String) -> MarkerId {
+        let id = self.next_id;
+        self.next_id += 1;
+        let time_seconds = time_seconds.max(0.0);
+        let index = self.markers.partition_point(|marker| marker.time_seconds <= time_seconds);
+        self.markers.insert(index, Marker { id, time_seconds, label });
+        id
+    }
+
+    /// Remove a marker; returns it, or None if there is no such marker
+    pub fn remove(&mut self, id: MarkerId) -> Option<Marker> {
+        let index = self.markers.iter().position(|marker| marker.id == id)?;
+        Some(self.markers.remove(index))
+    }
+
+    pub fn get(&self, id: MarkerId) -> Option<&Marker> {
+        self.markers.iter().find(|marker| marker.id == id)
+    }
+
+    /// All markers, earliest first
+    pub fn markers(&self) -> &[Marker] {
+        &self.markers
+    }
+
+    /// First marker strictly after `time_seconds`
+    pub fn next_after(&self, time_seconds: f64) -> Option<&Marker> {
+        self.markers.iter().find(|marker| marker.time_seconds > time_seconds)
+    }
+
+    /// Last marker strictly before `time_seconds`
+    pub fn previous_before(&self, time_seconds: f64) -> Option<&Marker> {
+        self.markers.iter().rev().find(|marker| marker.time_seconds < time_seconds)
+    }
+
+    /// Replace all markers (project load); new IDs continue after the highest one
+    pub fn restore(&mut self, mut markers: Vec<Marker>) {
+        markers.sort_by(|a, b| a.time_seconds.total_cmp(&b.time_seconds));
+        self.next_id = markers.iter().map(|marker| marker.id + 1).max().unwrap_or(0);
+        self.markers = markers;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markers_stay_sorted_and_navigate_by_time() {
+        let mut list = MarkerList::new();
+        let chorus = list.add(30.0, "Chorus".to_string());
+        let intro = list.add(-5.0, "Intro".to_string());
+        let verse = list.add(10.0, "Verse".to_string());
+        let labels: Vec<&str> = list.markers().iter().map(|marker| marker.label.as_str()).collect();
+        assert_eq!(labels, ["Intro", "Verse", "Chorus"]);
+        assert_eq!(list.get(intro).map(|marker| marker.time_seconds), Some(0.0));
+
+        assert_eq!(list.next_after(10.0).map(|marker| marker.id), Some(chorus));
+        assert_eq!(list.previous_before(10.0).map(|marker| marker.id), Some(intro));
+        assert_eq!(list.previous_before(10.5).map(|marker| marker.id), Some(verse));
+        assert!(list.next_after(30.0).is_none());
+
+        assert_eq!(list.remove(verse).map(|marker| marker.label), Some("Verse".to_string()));
+        assert!(list.remove(verse).is_none());
+        assert_eq!(list.add(20.0, "Bridge".to_string()), 3, "IDs are not reused");
+
+        list.restore(vec![
+            Marker { id: 7, time_seconds: 40.0, label: "Outro".to_string() },
+            Marker { id: 2, time_seconds: 5.0, label: "Drop".to_string() },
+        ]);
+        assert_eq!(list.markers()[0].id, 2);
+        assert_eq!(list.add(50.0, "End".to_string()), 8);
+    }
+}
@@ -1,5 +1,8 @@
 /// MIDI event and clip data structures
+use crate::time_signature::TimeSignature;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::collections::HashMap;
 
 /// MIDI note number (0-127)
 pub type MidiNote = u8;
@@ -7,12 +10,74 @@ pub type MidiNote = u8;
 /// MIDI velocity (0-127)
 pub type MidiVelocity = u8;
 
+/// ID of a note within a clip, shared by its `NoteOn` and `NoteOff` (0 = none assigned)
+pub type NoteId = u32;
+
 /// MIDI event types
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MidiEventType {
     NoteOn { note: MidiNote, velocity: MidiVelocity },
     NoteOff { note: MidiNote, velocity: MidiVelocity },
-    // Future: CC, PitchBend, etc.
+    /// Pitch wheel position, -8192..=8191 (0 = centre)
+    PitchBend { value: i16 },
+    /// Control change (CC1 = mod wheel)
+    ControlChange { controller: u8, value: u8 },
+    /// Polyphonic key pressure for a held note
+    PolyAftertouch { note: MidiNote, pressure: u8 },
+}
+
+/// MIDI controller number of the mod wheel
+pub const CC_MOD_WHEEL: u8 = 1;
+
+/// Velocity every note plays at under `VelocityCurve::Fixed`
+pub const FIXED_VELOCITY: MidiVelocity = 100;
+
+/// How incoming note velocity maps to the velocity an instrument plays
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VelocityCurve {
+    /// Velocity passes through unchanged
+    #[default]
+    Linear,
+    /// Soft notes get softer (velocity squared)
+    Exponential,
+    /// Soft notes get louder (square root)
+    Logarithmic,
+    /// Every note plays at `FIXED_VELOCITY`
+    Fixed,
+}
+
+impl VelocityCurve {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "linear" => Some(Self::Linear),
+            "exponential" => Some(Self::Exponential),
+            "logarithmic" => Some(Self::Logarithmic),
+            "fixed" => Some(Self::Fixed),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Exponential => "exponential",
+            Self::Logarithmic => "logarithmic",
+            Self::Fixed => "fixed",
+        }
+    }
+
+    /// Map a note-on velocity (1-127) through the curve; the result is never 0
+    pub fn apply(self, velocity: MidiVelocity) -> MidiVelocity {
+        let normalized = f32::from(velocity.min(127)) / 127.0;
+        let mapped = match self {
+            Self::Linear => return velocity,
+            Self::Exponential => normalized * normalized,
+            Self::Logarithmic => normalized.sqrt(),
+            Self::Fixed => return FIXED_VELOCITY,
+        };
+        ((mapped * 127.0).round() as u8).clamp(1, 127)
+    }
 }
 
 /// MIDI event with sample-accurate timestamp
@@ -22,6 +87,8 @@ pub struct MidiEvent {
     pub event_type: MidiEventType,
     /// Timestamp in samples (relative to clip start)
     pub timestamp_samples: u64,
+    /// Note ID, assigned by the clip the event belongs to (0 for non-note events)
+    pub note_id: NoteId,
 }
 
 impl MidiEvent {
@@ -29,6 +96,7 @@ impl MidiEvent {
         Self {
             event_type,
             timestamp_samples,
+            note_id: 0,
         }
     }
 
@@ -91,14 +159,19 @@ pub struct MidiClip {
     pub duration_samples: u64,
     /// Sample rate (for converting to/from seconds)
     pub sample_rate: u32,
+    /// Signature of the bar the clip starts in (sets the bar length the duration snaps to)
+    pub time_signature: TimeSignature,
+    /// Next note ID to hand out (IDs are never reused within a clip)
+    next_note_id: NoteId,
 }
 
 impl MidiClip {
-    /// Snap duration to next bar boundary (at 120 BPM, 4/4 time)
-    /// One bar = 4 beats = 2 seconds = 96000 samples at 48kHz
+    /// Snap duration to next bar boundary (at 120 BPM, bars of `time_signature`)
+    /// One 4/4 bar = 4 beats = 2 seconds = 96000 samples at 48kHz; a 6/8 bar is 1.5 seconds
     /// Examples: 3.5 bars -> 4 bars, 3.1 bars -> 4 bars, 3.0 bars -> 3 bars
-    pub fn snap_to_bar(samples: u64, sample_rate: u32) -> u64 {
-        let samples_per_bar = u64::from(sample_rate) * 2; // 2 seconds per bar at 120 BPM
+    pub fn snap_to_bar(samples: u64, sample_rate: u32, time_signature: TimeSignature) -> u64 {
+        // Half a second per quarter note at 120 BPM
+        let samples_per_bar = (time_signature.quarter_notes_per_bar() * f64::from(sample_rate) * 0.5).round() as u64;
         if samples == 0 {
             return samples_per_bar; // Minimum 1 bar
         }
@@ -116,6 +189,8 @@ impl MidiClip {
             events: Vec::new(),
             duration_samples: 0,
             sample_rate,
+            time_signature: TimeSignature::default(),
+            next_note_id: 1,
         }
     }
 
@@ -128,23 +203,28 @@ impl MidiClip {
         let last_event_samples = events
             .last()
             .map_or(0, |e| e.timestamp_samples);
-        let duration_samples = Self::snap_to_bar(last_event_samples, sample_rate);
+        let duration_samples = Self::snap_to_bar(last_event_samples, sample_rate, TimeSignature::default());
 
-        Self {
+        let mut clip = Self {
             events,
             duration_samples,
             sample_rate,
-        }
+            time_signature: TimeSignature::default(),
+            next_note_id: 1,
+        };
+        clip.assign_note_ids();
+        clip
     }
 
     /// Add an event to the clip (maintains sorted order)
     pub fn add_event(&mut self, event: MidiEvent) {
         self.events.push(event);
         self.events.sort();
+        self.assign_note_ids();
 
         // Update duration if event extends beyond current duration, snap to bar
         if event.timestamp_samples > self.duration_samples {
-            self.duration_samples = Self::snap_to_bar(event.timestamp_samples, self.sample_rate);
+            self.duration_samples = Self::snap_to_bar(event.timestamp_samples, self.sample_rate, self.time_signature);
         }
     }
 
@@ -166,6 +246,168 @@ impl MidiClip {
             .collect()
     }
 
+    /// Randomly vary note timing and velocity
+    ///
+    /// Each note starts up to `timing_samples` earlier or later (never before the
+    /// clip start) and keeps its length; its velocity changes by up to
+    /// `velocity_amount` either way, staying within 1-127. The same `seed` always
+    /// gives the same result.
+    pub fn humanize(&mut self, timing_samples: u64, velocity_amount: u8, seed: u64) {
+        self.assign_note_ids();
+        let mut rng = if seed == 0 { 0x2545_F491_4F6C_DD1D } else { seed };
+        // xorshift64, mapped to -amount..=amount
+        let mut jitter = |amount: i64| {
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            (rng % (2 * amount as u64 + 1)) as i64 - amount
+        };
+
+        let mut shifts: HashMap<NoteId, i64> = HashMap::new();
+        for event in &mut self.events {
+            match &mut event.event_type {
+                MidiEventType::NoteOn { velocity, .. } => {
+                    let time = event.timestamp_samples as i64;
+                    let moved = (time + jitter(timing_samples as i64)).max(0);
+                    event.timestamp_samples = moved as u64;
+                    shifts.insert(event.note_id, moved - time);
+                    *velocity = (i64::from(*velocity) + jitter(i64::from(velocity_amount))).clamp(1, 127) as u8;
+                }
+                MidiEventType::NoteOff { .. } => {
+                    if let Some(shift) = shifts.get(&event.note_id) {
+                        event.timestamp_samples = (event.timestamp_samples as i64 + shift).max(0) as u64;
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.events.sort();
+        if let Some(last) = self.events.last() {
+            if last.timestamp_samples > self.duration_samples {
+                self.duration_samples = Self::snap_to_bar(last.timestamp_samples, self.sample_rate, self.time_signature);
+            }
+        }
+    }
+
+    /// Give note events without an ID one
+    ///
+    /// Each `NoteOn` gets a new ID; a `NoteOff` takes the ID of the earliest
+    /// still-open `NoteOn` of the same pitch.
+    pub fn assign_note_ids(&mut self) {
+        let mut open: Vec<(MidiNote, NoteId)> = Vec::new();
+        for event in &mut self.events {
+            match event.event_type {
+                MidiEventType::NoteOn { note, .. } => {
+                    if event.note_id == 0 {
+                        event.note_id = self.next_note_id;
+                        self.next_note_id += 1;
+                    }
+                    open.push((note, event.note_id));
+                }
+                MidiEventType::NoteOff { note, .. } => {
+                    let index = if event.note_id == 0 {
+                        open.iter().position(|&(n, _)| n == note)
+                    } else {
+                        open.iter().position(|&(_, id)| id == event.note_id)
+                    };
+                    if let Some(index) = index {
+                        event.note_id = open.remove(index).1;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// All complete notes with their IDs, in start order
+    pub fn notes(&self) -> Vec<(NoteId, Note)> {
+        let mut notes = Vec::new();
+        for (index, event) in self.events.iter().enumerate() {
+            if !matches!(event.event_type, MidiEventType::NoteOn { .. }) || event.note_id == 0 {
+                continue;
+            }
+            let note_off = self.events[index + 1..].iter().find(|e| {
+                e.note_id == event.note_id && matches!(e.event_type, MidiEventType::NoteOff { .. })
+            });
+            if let Some(note) = note_off.and_then(|off| Note::from_events(event, off)) {
+                notes.push((event.note_id, note));
+            }
+        }
+        notes
+    }
+
+    /// Add a note and return its ID
+    pub fn add_note(&mut self, note: Note) -> NoteId {
+        let note_id = self.next_note_id;
+        self.next_note_id += 1;
+        let (note_on, note_off) = note.to_events();
+        self.events.push(MidiEvent { note_id, ..note_on });
+        self.events.push(MidiEvent { note_id, ..note_off });
+        self.note_edited(note_off.timestamp_samples);
+        note_id
+    }
+
+    /// Indices of a note's `NoteOn` and `NoteOff` events
+    fn find_note(&self, note_id: NoteId) -> Result<(usize, usize), String> {
+        let position = |note_on: bool| {
+            self.events.iter().position(|e| {
+                e.note_id == note_id
+                    && match e.event_type {
+                        MidiEventType::NoteOn { .. } => note_on,
+                        MidiEventType::NoteOff { .. } => !note_on,
+                        _ => false,
+                    }
+            })
+        };
+        match (position(true), position(false)) {
+            (Some(on), Some(off)) if note_id != 0 => Ok((on, off)),
+            _ => Err(format!("Note {note_id} not found")),
+        }
+    }
+
+    /// Move a note to a new start (in samples) and pitch, keeping its length
+    pub fn move_note(&mut self, note_id: NoteId, start_samples: u64, pitch: MidiNote) -> Result<(), String> {
+        let (on, off) = self.find_note(note_id)?;
+        let length = self.events[off].timestamp_samples - self.events[on].timestamp_samples;
+        let pitch = pitch.min(127);
+        for (index, time) in [(on, start_samples), (off, start_samples + length)] {
+            let event = &mut self.events[index];
+            event.timestamp_samples = time;
+            if let MidiEventType::NoteOn { note, .. } | MidiEventType::NoteOff { note, .. } = &mut event.event_type {
+                *note = pitch;
+            }
+        }
+        self.note_edited(start_samples + length);
+        Ok(())
+    }
+
+    /// Change a note's length (in samples, at least 1)
+    pub fn resize_note(&mut self, note_id: NoteId, duration_samples: u64) -> Result<(), String> {
+        let (on, off) = self.find_note(note_id)?;
+        let end = self.events[on].timestamp_samples + duration_samples.max(1);
+        self.events[off].timestamp_samples = end;
+        self.note_edited(end);
+        Ok(())
+    }
+
+    /// Remove a note (both its `NoteOn` and `NoteOff`)
+    pub fn delete_note(&mut self, note_id: NoteId) -> Result<(), String> {
+        self.find_note(note_id)?;
+        self.events.retain(|e| {
+            e.note_id != note_id
+                || !matches!(e.event_type, MidiEventType::NoteOn { .. } | MidiEventType::NoteOff { .. })
+        });
+        Ok(())
+    }
+
+    /// Re-sort after a note edit and grow the clip if the note now ends past it
+    fn note_edited(&mut self, end_samples: u64) {
+        self.events.sort();
+        if end_samples > self.duration_samples {
+            self.duration_samples = Self::snap_to_bar(end_samples, self.sample_rate, self.time_signature);
+        }
+    }
+
     /// Get duration in seconds
     pub fn duration_seconds(&self) -> f64 {
         self.duration_samples as f64 / f64::from(self.sample_rate)
@@ -173,29 +415,155 @@ impl MidiClip {
 
     /// Quantize all events to the specified grid (in samples)
     pub fn quantize(&mut self, grid_samples: u64) {
+        self.quantize_with_groove(grid_samples, 0.0, 1.0);
+    }
+
+    /// Quantize all events to a swung grid (in samples)
+    ///
+    /// `swing` (0.0-1.0) delays every second grid line by up to half a grid step.
+    /// `strength` (0.0-1.0) is how far each event moves toward its grid line
+    /// (1.0 = snap onto it).
+    pub fn quantize_with_groove(&mut self, grid_samples: u64, swing: f32, strength: f32) {
         if grid_samples == 0 {
             return;
         }
+        let swing_delay = (f64::from(swing.clamp(0.0, 1.0)) * grid_samples as f64 / 2.0).round() as u64;
+        let strength = f64::from(strength.clamp(0.0, 1.0));
+        let grid_line = |index: u64| index * grid_samples + if index % 2 == 1 { swing_delay } else { 0 };
 
         for event in &mut self.events {
-            // Round to nearest grid position
-            let remainder = event.timestamp_samples % grid_samples;
-            if remainder < grid_samples / 2 {
-                event.timestamp_samples -= remainder;
-            } else {
-                event.timestamp_samples += grid_samples - remainder;
-            }
+            // Nearest grid line: the straight neighbour (which wins ties) or one
+            // either side of it once swung
+            let time = event.timestamp_samples;
+            let nearest = (time + grid_samples / 2) / grid_samples;
+            let target = [nearest, nearest.saturating_sub(1), nearest + 1]
+                .into_iter()
+                .map(grid_line)
+                .min_by_key(|&line| line.abs_diff(time))
+                .unwrap_or(time);
+
+            let moved = (target as f64 - time as f64) * strength;
+            event.timestamp_samples = (time as f64 + moved).round() as u64;
         }
 
         // Re-sort after quantization
         self.events.sort();
     }
 
+    /// Shift every note by `semitones`
+    ///
+    /// Notes pushed outside 0-127 are dropped (note-on, note-off and aftertouch alike).
+    /// Returns the number of notes dropped.
+    pub fn transpose(&mut self, semitones: i32) -> usize {
+        self.remap_notes(|note| MidiNote::try_from(i32::from(note) + semitones).ok().filter(|n| *n <= 127))
+    }
+
+    /// Move every note to the nearest pitch in a scale
+    ///
+    /// Bit `i` of `scale_mask` marks the pitch class `i` semitones above `root`
+    /// (0-11) as in the scale. Ties go to the lower pitch. An empty scale leaves
+    /// the clip unchanged.
+    pub fn scale_quantize(&mut self, root: u8, scale_mask: u16) {
+        let scale_mask = scale_mask & 0x0FFF;
+        if scale_mask == 0 {
+            return;
+        }
+        let in_scale = |pitch: i32| scale_mask & (1 << (pitch - i32::from(root % 12)).rem_euclid(12)) != 0;
+        self.remap_notes(|note| {
+            let pitch = i32::from(note);
+            (0..12)
+                .flat_map(|distance| [pitch - distance, pitch + distance])
+                .find(|&candidate| (0..=127).contains(&candidate) && in_scale(candidate))
+                .and_then(|candidate| MidiNote::try_from(candidate).ok())
+        });
+    }
+
+    /// Change the pitch of every note event, dropping those mapped to None
+    ///
+    /// The mapping depends only on the pitch, so note-ons and note-offs stay paired.
+    fn remap_notes(&mut self, map: impl Fn(MidiNote) -> Option<MidiNote>) -> usize {
+        let mut dropped = 0;
+        self.events.retain_mut(|event| {
+            let (MidiEventType::NoteOn { note, .. }
+            | MidiEventType::NoteOff { note, .. }
+            | MidiEventType::PolyAftertouch { note, .. }) = &mut event.event_type
+            else {
+                return true;
+            };
+            if let Some(mapped) = map(*note) {
+                *note = mapped;
+                true
+            } else {
+                if matches!(event.event_type, MidiEventType::NoteOn { .. }) {
+                    dropped += 1;
+                }
+                false
+            }
+        });
+        self.events.sort();
+        dropped
+    }
+
     /// Clear all events
     pub fn clear(&mut self) {
         self.events.clear();
         self.duration_samples = 0;
     }
+
+    /// Split the clip in two at a sample position (relative to clip start)
+    ///
+    /// Notes held across the split are cut: they end at the split in the first
+    /// clip and restart at the beginning of the second.
+    pub fn split_at(&self, split_samples: u64) -> (MidiClip, MidiClip) {
+        let mut before = Vec::new();
+        let mut after = Vec::new();
+        let mut held: Vec<(MidiNote, MidiVelocity, NoteId)> = Vec::new();
+
+        for event in &self.events {
+            let ends_at_split = event.timestamp_samples == split_samples
+                && matches!(event.event_type, MidiEventType::NoteOff { .. });
+            if event.timestamp_samples < split_samples || ends_at_split {
+                match event.event_type {
+                    MidiEventType::NoteOn { note, velocity } => held.push((note, velocity, event.note_id)),
+                    MidiEventType::NoteOff { note, .. } => {
+                        if let Some(index) = held.iter().position(|(n, _, _)| *n == note) {
+                            held.remove(index);
+                        }
+                    }
+                    _ => {}
+                }
+                before.push(*event);
+            } else {
+                after.push(MidiEvent { timestamp_samples: event.timestamp_samples - split_samples, ..*event });
+            }
+        }
+
+        // Both halves of a cut note keep its ID
+        for (note, velocity, note_id) in held {
+            before.push(MidiEvent { note_id, ..MidiEvent::note_off(note, 64, split_samples) });
+            after.push(MidiEvent { note_id, ..MidiEvent::note_on(note, velocity, 0) });
+        }
+        before.sort();
+        after.sort();
+
+        let mut first = MidiClip {
+            events: before,
+            duration_samples: split_samples,
+            sample_rate: self.sample_rate,
+            time_signature: self.time_signature,
+            next_note_id: self.next_note_id,
+        };
+        let mut second = MidiClip {
+            events: after,
+            duration_samples: self.duration_samples.saturating_sub(split_samples),
+            sample_rate: self.sample_rate,
+            time_signature: self.time_signature,
+            next_note_id: self.next_note_id,
+        };
+        first.assign_note_ids();
+        second.assign_note_ids();
+        (first, second)
+    }
 }
 
 /// MIDI note representation for piano roll editing (not to be confused with `MidiNote` type)
@@ -264,6 +632,20 @@ mod tests {
         assert_eq!(clip.events[1].timestamp_samples, 2000);
     }
 
+    #[test]
+    fn test_clip_duration_snaps_to_bars_of_its_signature() {
+        // 4/4 bars are 96000 samples at 120 BPM; 6/8 bars (three quarter notes) 72000
+        let mut clip = MidiClip::new(48000);
+        clip.add_event(MidiEvent::note_on(60, 100, 80_000));
+        assert_eq!(clip.duration_samples, 96_000);
+
+        let mut clip = MidiClip::new(48000);
+        clip.time_signature = TimeSignature::new(6, 8).unwrap();
+        clip.add_event(MidiEvent::note_on(60, 100, 80_000));
+        assert_eq!(clip.duration_samples, 144_000);
+        assert_eq!(MidiClip::snap_to_bar(72_000, 48000, clip.time_signature), 72_000);
+    }
+
     #[test]
     fn test_midi_clip_quantize() {
         let mut clip = MidiClip::new(48000);
@@ -277,6 +659,178 @@ mod tests {
         assert_eq!(clip.events[1].timestamp_samples, 3000);
     }
 
+    #[test]
+    fn test_midi_clip_swing_delays_off_beats() {
+        let mut clip = MidiClip::new(48000);
+        clip.add_event(MidiEvent::note_on(60, 100, 2010));
+        clip.add_event(MidiEvent::note_on(62, 100, 2990));
+        clip.add_event(MidiEvent::note_on(64, 100, 4300));
+
+        // 50% swing on a 1000-sample grid pushes odd grid lines back 250 samples
+        clip.quantize_with_groove(1000, 0.5, 1.0);
+
+        assert_eq!(clip.events[0].timestamp_samples, 2000);
+        assert_eq!(clip.events[1].timestamp_samples, 3250);
+        // Off-beats move away, so late on-beat notes still snap back to the beat
+        assert_eq!(clip.events[2].timestamp_samples, 4000);
+    }
+
+    #[test]
+    fn test_midi_clip_partial_quantize_strength() {
+        let mut clip = MidiClip::new(48000);
+        clip.add_event(MidiEvent::note_on(60, 100, 1200));
+        clip.add_event(MidiEvent::note_on(62, 100, 2900));
+
+        clip.quantize_with_groove(1000, 0.0, 0.5);
+
+        // Each note moves halfway toward its grid line
+        assert_eq!(clip.events[0].timestamp_samples, 1100);
+        assert_eq!(clip.events[1].timestamp_samples, 2950);
+    }
+
+    #[test]
+    fn test_midi_clip_transpose_drops_out_of_range_notes() {
+        let mut clip = MidiClip::new(48000);
+        clip.add_event(MidiEvent::note_on(2, 100, 0));
+        clip.add_event(MidiEvent::note_off(2, 64, 1000));
+        clip.add_event(MidiEvent::note_on(125, 100, 0));
+        clip.add_event(MidiEvent::note_off(125, 64, 1000));
+
+        assert_eq!(clip.transpose(2), 0);
+        assert!(clip.events.iter().all(|e| matches!(
+            e.event_type,
+            MidiEventType::NoteOn { note: 4 | 127, .. } | MidiEventType::NoteOff { note: 4 | 127, .. }
+        )));
+
+        // Up one more: 127 falls off the top, both its note-on and note-off
+        assert_eq!(clip.transpose(1), 1);
+        assert_eq!(clip.events.len(), 2);
+        // Down past 0 drops the other
+        assert_eq!(clip.transpose(-6), 1);
+        assert!(clip.events.is_empty());
+    }
+
+    #[test]
+    fn test_midi_clip_scale_quantize_stays_in_range() {
+        const C_MAJOR: u16 = 0b1010_1011_0101;
+        let mut clip = MidiClip::new(48000);
+        for (note, time) in [(1, 0), (61, 1000), (126, 2000), (127, 3000)] {
+            clip.add_event(MidiEvent::note_on(note, 100, time));
+            clip.add_event(MidiEvent::note_off(note, 64, time + 500));
+        }
+
+        clip.scale_quantize(0, C_MAJOR);
+
+        let pairs: Vec<(u8, u8)> = clip
+            .events
+            .chunks(2)
+            .map(|pair| match (pair[0].event_type, pair[1].event_type) {
+                (MidiEventType::NoteOn { note: on, .. }, MidiEventType::NoteOff { note: off, .. }) => (on, off),
+                other => panic!("unpaired events: {other:?}"),
+            })
+            .collect();
+        // C#0 -> C0 (tie goes down), C#4 -> C4, F#9 -> F9, G9 stays
+        assert_eq!(pairs, vec![(0, 0), (60, 60), (125, 125), (127, 127)]);
+
+        // Only G# in the scale: 127 can't move up to 128, so it drops to 116
+        let mut clip = MidiClip::new(48000);
+        clip.add_event(MidiEvent::note_on(127, 100, 0));
+        clip.scale_quantize(8, 0b1);
+        assert!(matches!(clip.events[0].event_type, MidiEventType::NoteOn { note: 116, .. }));
+    }
+
+    #[test]
+    fn test_note_edits_by_id_keep_events_paired() {
+        let mut clip = MidiClip::new(48000);
+        clip.add_event(MidiEvent::note_on(60, 100, 0));
+        clip.add_event(MidiEvent::note_off(60, 64, 1000));
+        let added = clip.add_note(Note { pitch: 60, velocity: 90, start_samples: 500, duration_samples: 1000 });
+
+        let notes = clip.notes();
+        assert_eq!(notes.len(), 2);
+        let first = notes[0].0;
+        assert_eq!(notes[1].0, added);
+        assert_ne!(first, added);
+
+        // Move the overlapping same-pitch note away; the first note is untouched
+        clip.move_note(added, 96_000 * 2, 64).unwrap();
+        clip.resize_note(first, 250).unwrap();
+        let notes = clip.notes();
+        assert_eq!(notes[0].0, first);
+        assert_eq!((notes[0].1.pitch, notes[0].1.start_samples, notes[0].1.duration_samples), (60, 0, 250));
+        assert_eq!((notes[1].1.pitch, notes[1].1.start_samples, notes[1].1.duration_samples), (64, 192_000, 1000));
+        // The clip grows to the bar the moved note now ends in
+        assert_eq!(clip.duration_samples, 96_000 * 3);
+
+        clip.delete_note(first).unwrap();
+        assert_eq!(clip.events.len(), 2);
+        assert!(clip.events.iter().all(|e| e.note_id == added));
+        assert!(clip.delete_note(first).is_err());
+    }
+
+    #[test]
+    fn test_humanize_is_reproducible_and_stays_after_clip_start() {
+        let mut clip = MidiClip::new(48000);
+        for i in 0..16 {
+            clip.add_note(Note { pitch: 36 + (i % 4) as u8, velocity: 100, start_samples: i * 6000, duration_samples: 3000 });
+        }
+
+        let mut first = clip.clone();
+        let mut second = clip.clone();
+        first.humanize(480, 20, 42);
+        second.humanize(480, 20, 42);
+        assert_eq!(first.events, second.events);
+        assert_ne!(first.events, clip.events);
+
+        let mut other_seed = clip.clone();
+        other_seed.humanize(480, 20, 7);
+        assert_ne!(first.events, other_seed.events);
+
+        let originals = clip.notes();
+        for (note_id, note) in first.notes() {
+            let original = originals.iter().find(|(id, _)| *id == note_id).unwrap().1;
+            assert_eq!(note.duration_samples, 3000);
+            assert!(note.start_samples.abs_diff(original.start_samples) <= 480);
+            assert!(note.velocity.abs_diff(original.velocity) <= 20);
+            assert!((1..=127).contains(&note.velocity));
+        }
+
+        // A note on the clip start can only move later
+        let mut clip = MidiClip::new(48000);
+        clip.add_note(Note { pitch: 60, velocity: 127, start_samples: 0, duration_samples: 100 });
+        for seed in 1..50 {
+            let mut humanized = clip.clone();
+            humanized.humanize(10_000, 50, seed);
+            let (_, note) = humanized.notes()[0];
+            assert!(matches!(humanized.events[0].event_type, MidiEventType::NoteOn { .. }));
+            assert_eq!(note.duration_samples, 100);
+            assert!(note.velocity >= 77);
+        }
+    }
+
+    #[test]
+    fn test_midi_clip_split_cuts_held_notes() {
+        let mut clip = MidiClip::new(48000);
+        clip.add_event(MidiEvent::note_on(60, 100, 0));
+        clip.add_event(MidiEvent::note_off(60, 64, 1000));
+        clip.add_event(MidiEvent::note_on(64, 90, 1500));
+        clip.add_event(MidiEvent::note_off(64, 64, 3000));
+        clip.add_event(MidiEvent::note_on(67, 80, 4000));
+        clip.add_event(MidiEvent::note_off(67, 64, 5000));
+
+        let (first, second) = clip.split_at(2000);
+        assert_eq!(first.duration_samples, 2000);
+        assert_eq!(second.duration_samples, clip.duration_samples - 2000);
+
+        // The held note ends at the split and restarts at the start of the second clip
+        assert_eq!(first.events.len(), 4);
+        assert_eq!(first.events[3], MidiEvent::note_off(64, 64, 2000));
+        assert_eq!(second.events[0], MidiEvent::note_on(64, 90, 0));
+        assert_eq!(second.events[1], MidiEvent::note_off(64, 64, 1000));
+        assert_eq!(second.events[2], MidiEvent::note_on(67, 80, 2000));
+        assert_eq!(second.events.len(), 4);
+    }
+
     #[test]
     fn test_note_conversion() {
         let note = Note {
@@ -296,7 +850,7 @@ mod tests {
                 assert_eq!(note, 60);
                 assert_eq!(velocity, 100);
             }
-            MidiEventType::NoteOff { .. } => panic!("Expected NoteOn"),
+            _ => panic!("Expected NoteOn"),
         }
     }
 }
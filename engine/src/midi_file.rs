@@ -0,0 +1,344 @@
+/// Standard MIDI file (SMF) import/export
+///
+/// Converts `.mid` files into `MidiEvent` lists with sample-accurate timestamps,
+/// honouring the file's tempo map, and writes clips back out as type-0 files.
+use crate::midi::{MidiEvent, MidiEventType};
+use midly::{Format, Header, MetaMessage, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind};
+
+/// Tempo assumed until the first Set Tempo event (120 BPM)
+const DEFAULT_MICROS_PER_BEAT: u32 = 500_000;
+
+/// Ticks per quarter note used for exported files
+const EXPORT_PPQ: u16 = 480;
+
+/// One track's worth of notes from a MIDI file
+#[derive(Debug, Clone)]
+pub struct ImportedMidiTrack {
+    /// Track name meta event, if present
+    pub name: Option<String>,
+    /// Note events (timestamps relative to the start of the file)
+    pub events: Vec<MidiEvent>,
+}
+
+/// Converts absolute ticks to seconds using the file's timing and tempo changes
+struct TickClock {
+    timing: Timing,
+    /// (tick, microseconds per beat), sorted by tick
+    tempo_map: Vec<(u64, u32)>,
+}
+
+impl TickClock {
+    fn ticks_to_seconds(&self, tick: u64) -> f64 {
+        match self.timing {
+            Timing::Metrical(ticks_per_beat) => {
+                let ticks_per_beat = f64::from(ticks_per_beat.as_int().max(1));
+                let mut seconds = 0.0;
+                let mut last_tick = 0;
+                let mut micros_per_beat = DEFAULT_MICROS_PER_BEAT;
+                for &(change_tick, change_tempo) in &self.tempo_map {
+                    if change_tick >= tick {
+                        break;
+                    }
+                    seconds += (change_tick - last_tick) as f64 / ticks_per_beat
+                        * f64::from(micros_per_beat) / 1_000_000.0;
+                    last_tick = change_tick;
+                    micros_per_beat = change_tempo;
+                }
+                seconds + (tick - last_tick) as f64 / ticks_per_beat
+                    * f64::from(micros_per_beat) / 1_000_000.0
+            }
+            Timing::Timecode(fps, subframes) => {
+                let frames_per_second = match fps.as_int() {
+                    29 => 29.97,
+                    n => f64::from(n),
+                };
+                tick as f64 / (frames_per_second * f64::from(subframes.max(1)))
+            }
+        }
+    }
+}
+
+/// Parse a standard MIDI file into per-track note events.
+///
+/// Type-0 files yield a single track; type-1/2 files yield one entry per file track
+/// that contains notes (tempo-only conductor tracks are dropped).
+pub fn parse_midi_file(bytes: &[u8], sample_rate: u32) -> Result<Vec<ImportedMidiTrack>, String> {
+    let smf = Smf::parse(bytes).map_err(|e| format!("Failed to parse MIDI file: {e}"))?;
+
+    // Collect the tempo map from all tracks (type-1 files keep it in the first track)
+    let mut tempo_map = Vec::new();
+    for track in &smf.tracks {
+        let mut tick = 0u64;
+        for event in track {
+            tick += u64::from(event.delta.as_int());
+            if let TrackEventKind::Meta(MetaMessage::Tempo(micros)) = event.kind {
+                tempo_map.push((tick, micros.as_int()));
+            }
+        }
+        // Sequential (type-2) files carry their own tempo per track; use the first
+        if smf.header.format == Format::Sequential {
+            break;
+        }
+    }
+    tempo_map.sort_by_key(|&(tick, _)| tick);
+    let clock = TickClock { timing: smf.header.timing, tempo_map };
+
+    let mut tracks = Vec::new();
+    for track in &smf.tracks {
+        let mut name = None;
+        let mut events = Vec::new();
+        let mut tick = 0u64;
+
+        for event in track {
+            tick += u64::from(event.delta.as_int());
+            let timestamp_samples =
+                (clock.ticks_to_seconds(tick) * f64::from(sample_rate)).round() as u64;
+
+            match event.kind {
+                TrackEventKind::Meta(MetaMessage::TrackName(raw)) => {
+                    let text = String::from_utf8_lossy(raw).trim().to_string();
+                    if !text.is_empty() {
+                        name = Some(text);
+                    }
+                }
+                TrackEventKind::Midi { message, .. } => match message {
+                    // Note-on with velocity 0 is a note-off by convention
+                    MidiMessage::NoteOn { key, vel } if vel.as_int() > 0 => {
+                        events.push(MidiEvent::new(
+                            MidiEventType::NoteOn { note: key.as_int(), velocity: vel.as_int() },
+                            timestamp_samples,
+                        ));
+                    }
+                    MidiMessage::NoteOn { key, vel } | MidiMessage::NoteOff { key, vel } => {
+                        events.push(MidiEvent::new(
+                            MidiEventType::NoteOff { note: key.as_int(), velocity: vel.as_int() },
+                            timestamp_samples,
+                        ));
+                    }
+                    MidiMessage::PitchBend { bend } => {
+                        events.push(MidiEvent::new(
+                            MidiEventType::PitchBend { value: bend.as_int() },
+                            timestamp_samples,
+                        ));
+                    }
+                    MidiMessage::Controller { controller, value } => {
+                        events.push(MidiEvent::new(
+                            MidiEventType::ControlChange { controller: controller.as_int(), value: value.as_int() },
+                            timestamp_samples,
+                        ));
+                    }
+                    MidiMessage::Aftertouch { key, vel } => {
+                        events.push(MidiEvent::new(
+                            MidiEventType::PolyAftertouch { note: key.as_int(), pressure: vel.as_int() },
+                            timestamp_samples,
+                        ));
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+
+        if !events.is_empty() {
+            tracks.push(ImportedMidiTrack { name, events });
+        }
+    }
+
+    Ok(tracks)
+}
+
+/// Write note events as a type-0 standard MIDI file at `EXPORT_PPQ` ticks per beat.
+///
+/// Overlapping notes of the same pitch are serialized as back-to-back notes: the
+/// sounding note is ended before the next note-on, and the note-off that would
+/// otherwise cut the newer note short is held back until the last one ends.
+pub fn write_midi_file(events: &[MidiEvent], sample_rate: u32, tempo_bpm: f64) -> Result<Vec<u8>, String> {
+    if tempo_bpm <= 0.0 {
+        return Err(format!("Invalid tempo: {tempo_bpm}"));
+    }
+    let ticks_per_sample = tempo_bpm / 60.0 * f64::from(EXPORT_PPQ) / f64::from(sample_rate);
+    let micros_per_beat = (60_000_000.0 / tempo_bpm).round() as u32;
+
+    let mut sorted = events.to_vec();
+    sorted.sort();
+
+    let mut track = vec![TrackEvent {
+        delta: 0.into(),
+        kind: TrackEventKind::Meta(MetaMessage::Tempo(micros_per_beat.into())),
+    }];
+    let mut last_tick = 0u64;
+    let mut push = |tick: u64, message: MidiMessage| {
+        track.push(TrackEvent {
+            delta: ((tick - last_tick) as u32).into(),
+            kind: TrackEventKind::Midi { channel: 0.into(), message },
+        });
+        last_tick = tick;
+    };
+
+    // Outstanding note-ons per pitch, and whether the pitch is currently sounding in the file
+    let mut held = [0u32; 128];
+    let mut sounding = [false; 128];
+
+    for event in &sorted {
+        let tick = (event.timestamp_samples as f64 * ticks_per_sample).round() as u64;
+        match event.event_type {
+            MidiEventType::NoteOn { note, velocity } => {
+                let pitch = usize::from(note.min(127));
+                if sounding[pitch] {
+                    push(tick, MidiMessage::NoteOff { key: note.into(), vel: 0.into() });
+                }
+                push(tick, MidiMessage::NoteOn { key: note.into(), vel: velocity.min(127).into() });
+                held[pitch] += 1;
+                sounding[pitch] = true;
+            }
+            MidiEventType::NoteOff { note, velocity } => {
+                let pitch = usize::from(note.min(127));
+                held[pitch] = held[pitch].saturating_sub(1);
+                if held[pitch] == 0 && sounding[pitch] {
+                    push(tick, MidiMessage::NoteOff { key: note.into(), vel: velocity.min(127).into() });
+                    sounding[pitch] = false;
+                }
+            }
+            MidiEventType::PitchBend { value } => {
+                push(tick, MidiMessage::PitchBend { bend: midly::PitchBend::from_int(value) });
+            }
+            MidiEventType::ControlChange { controller, value } => {
+                push(tick, MidiMessage::Controller { controller: controller.min(127).into(), value: value.min(127).into() });
+            }
+            MidiEventType::PolyAftertouch { note, pressure } => {
+                push(tick, MidiMessage::Aftertouch { key: note.min(127).into(), vel: pressure.min(127).into() });
+            }
+        }
+    }
+
+    track.push(TrackEvent { delta: 0.into(), kind: TrackEventKind::Meta(MetaMessage::EndOfTrack) });
+
+    let mut smf = Smf::new(Header::new(Format::SingleTrack, Timing::Metrical(EXPORT_PPQ.into())));
+    smf.tracks.push(track);
+
+    let mut bytes = Vec::new();
+    smf.write_std(&mut bytes).map_err(|e| format!("Failed to write MIDI file: {e}"))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(delta: u32, key: u8, vel: u8) -> TrackEvent<'static> {
+        TrackEvent {
+            delta: delta.into(),
+            kind: TrackEventKind::Midi {
+                channel: 0.into(),
+                message: MidiMessage::NoteOn { key: key.into(), vel: vel.into() },
+            },
+        }
+    }
+
+    fn end_of_track() -> TrackEvent<'static> {
+        TrackEvent { delta: 0.into(), kind: TrackEventKind::Meta(MetaMessage::EndOfTrack) }
+    }
+
+    fn to_bytes(smf: &Smf<'_>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        smf.write_std(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_parse_type0_uses_file_tempo() {
+        let mut smf = Smf::new(Header::new(Format::SingleTrack, Timing::Metrical(480.into())));
+        smf.tracks.push(vec![
+            // 60 BPM: one beat = one second
+            TrackEvent { delta: 0.into(), kind: TrackEventKind::Meta(MetaMessage::Tempo(1_000_000.into())) },
+            note(0, 60, 100),
+            note(480, 60, 0), // velocity 0 → note-off
+            end_of_track(),
+        ]);
+
+        let tracks = parse_midi_file(&to_bytes(&smf), 48000).unwrap();
+        assert_eq!(tracks.len(), 1);
+        let events = &tracks[0].events;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event_type, MidiEventType::NoteOn { note: 60, velocity: 100 });
+        assert_eq!(events[1].event_type, MidiEventType::NoteOff { note: 60, velocity: 0 });
+        assert_eq!(events[1].timestamp_samples, 48000);
+    }
+
+    #[test]
+    fn test_parse_type1_splits_tracks_and_skips_conductor() {
+        let mut smf = Smf::new(Header::new(Format::Parallel, Timing::Metrical(96.into())));
+        smf.tracks.push(vec![
+            TrackEvent { delta: 0.into(), kind: TrackEventKind::Meta(MetaMessage::TrackName(b"Tempo")) },
+            end_of_track(),
+        ]);
+        smf.tracks.push(vec![
+            TrackEvent { delta: 0.into(), kind: TrackEventKind::Meta(MetaMessage::TrackName(b"Bass")) },
+            note(0, 36, 90),
+            note(96, 36, 0),
+            end_of_track(),
+        ]);
+        smf.tracks.push(vec![note(96, 64, 80), note(96, 64, 0), end_of_track()]);
+
+        let tracks = parse_midi_file(&to_bytes(&smf), 48000).unwrap();
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(tracks[0].name.as_deref(), Some("Bass"));
+        assert_eq!(tracks[1].name, None);
+        // Default 120 BPM: one beat = 0.5 s
+        assert_eq!(tracks[1].events[0].timestamp_samples, 24000);
+        assert_eq!(tracks[1].events[1].timestamp_samples, 48000);
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        assert!(parse_midi_file(b"not a midi file", 48000).is_err());
+    }
+
+    #[test]
+    fn test_write_round_trips_through_parse() {
+        let events = vec![
+            MidiEvent::note_on(60, 100, 0),
+            MidiEvent::new(MidiEventType::PitchBend { value: -4096 }, 6000),
+            MidiEvent::note_on(64, 80, 12000),
+            MidiEvent::new(MidiEventType::ControlChange { controller: 1, value: 64 }, 18000),
+            MidiEvent::note_off(60, 0, 24000),
+            MidiEvent::new(MidiEventType::PolyAftertouch { note: 64, pressure: 50 }, 30000),
+            MidiEvent::note_off(64, 0, 48000),
+        ];
+
+        let bytes = write_midi_file(&events, 48000, 120.0).unwrap();
+        let smf = Smf::parse(&bytes).unwrap();
+        assert_eq!(smf.header.format, Format::SingleTrack);
+        assert_eq!(smf.header.timing, Timing::Metrical(EXPORT_PPQ.into()));
+
+        let tracks = parse_midi_file(&bytes, 48000).unwrap();
+        let parsed: Vec<(MidiEventType, u64)> =
+            tracks[0].events.iter().map(|e| (e.event_type, e.timestamp_samples)).collect();
+        let expected: Vec<(MidiEventType, u64)> =
+            events.iter().map(|e| (e.event_type, e.timestamp_samples)).collect();
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn test_write_serializes_overlapping_same_pitch() {
+        // Two overlapping C4s: 0..2 beats and 1..3 beats (at 120 BPM, 24000 samples per beat)
+        let events = vec![
+            MidiEvent::note_on(60, 100, 0),
+            MidiEvent::note_on(60, 90, 24000),
+            MidiEvent::note_off(60, 0, 48000),
+            MidiEvent::note_off(60, 0, 72000),
+        ];
+
+        let bytes = write_midi_file(&events, 48000, 120.0).unwrap();
+        let tracks = parse_midi_file(&bytes, 48000).unwrap();
+        let parsed: Vec<(MidiEventType, u64)> =
+            tracks[0].events.iter().map(|e| (e.event_type, e.timestamp_samples)).collect();
+
+        assert_eq!(parsed, vec![
+            (MidiEventType::NoteOn { note: 60, velocity: 100 }, 0),
+            (MidiEventType::NoteOff { note: 60, velocity: 0 }, 24000),
+            (MidiEventType::NoteOn { note: 60, velocity: 90 }, 24000),
+            (MidiEventType::NoteOff { note: 60, velocity: 0 }, 72000),
+        ]);
+    }
+}
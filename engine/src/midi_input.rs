@@ -1,6 +1,6 @@
 /// MIDI input device management
 /// Uses midir on desktop platforms, stub on iOS (midir not supported)
-use crate::midi::MidiEvent;
+use crate::midi::{MidiEvent, MidiEventType};
 use anyhow::{anyhow, Result};
 use std::sync::Arc;
 use parking_lot::Mutex;
@@ -275,7 +275,25 @@ fn parse_midi_message(message: &[u8], timestamp: u64) -> Option<MidiEvent> {
             Some(MidiEvent::note_off(note, velocity, timestamp))
         }
 
-        // Ignore other message types for now (CC, pitch bend, etc.)
+        // Polyphonic aftertouch (0xA0)
+        0xA0 if message.len() >= 3 => Some(MidiEvent::new(
+            MidiEventType::PolyAftertouch { note: message[1], pressure: message[2] },
+            timestamp,
+        )),
+
+        // Control change (0xB0)
+        0xB0 if message.len() >= 3 => Some(MidiEvent::new(
+            MidiEventType::ControlChange { controller: message[1], value: message[2] },
+            timestamp,
+        )),
+
+        // Pitch bend (0xE0): 14-bit value, LSB first, centred on 8192
+        0xE0 if message.len() >= 3 => {
+            let raw = (i16::from(message[2] & 0x7F) << 7) | i16::from(message[1] & 0x7F);
+            Some(MidiEvent::new(MidiEventType::PitchBend { value: raw - 8192 }, timestamp))
+        }
+
+        // Ignore other message types for now (channel pressure, program change, etc.)
         _ => {
             eprintln!(
                 "🎹 [MIDI] Ignoring message type: 0x{message_type:02X} (channel {channel})"
@@ -289,7 +307,6 @@ fn parse_midi_message(message: &[u8], timestamp: u64) -> Option<MidiEvent> {
 #[cfg(not(target_os = "ios"))]
 mod tests {
     use super::*;
-    use crate::midi::MidiEventType;
 
     #[test]
     fn test_midi_manager_creation() {
@@ -319,7 +336,7 @@ mod tests {
                 assert_eq!(note, 60);
                 assert_eq!(velocity, 100);
             }
-            MidiEventType::NoteOff { .. } => panic!("Expected NoteOn"),
+            _ => panic!("Expected NoteOn"),
         }
     }
 
@@ -336,7 +353,7 @@ mod tests {
                 assert_eq!(note, 60);
                 assert_eq!(velocity, 64);
             }
-            MidiEventType::NoteOn { .. } => panic!("Expected NoteOff"),
+            _ => panic!("Expected NoteOff"),
         }
     }
 
@@ -353,7 +370,23 @@ mod tests {
                 assert_eq!(note, 60);
                 assert_eq!(velocity, 0);
             }
-            MidiEventType::NoteOn { .. } => panic!("Expected NoteOff"),
+            _ => panic!("Expected NoteOff"),
         }
     }
+
+    #[test]
+    fn test_parse_pitch_bend_and_controllers() {
+        // Pitch bend: LSB 0, MSB 0x40 is centre; full range is -8192..=8191
+        let centre = parse_midi_message(&[0xE0, 0x00, 0x40], 0).unwrap();
+        assert_eq!(centre.event_type, MidiEventType::PitchBend { value: 0 });
+        let down = parse_midi_message(&[0xE3, 0x00, 0x00], 0).unwrap();
+        assert_eq!(down.event_type, MidiEventType::PitchBend { value: -8192 });
+        let up = parse_midi_message(&[0xE0, 0x7F, 0x7F], 0).unwrap();
+        assert_eq!(up.event_type, MidiEventType::PitchBend { value: 8191 });
+
+        let mod_wheel = parse_midi_message(&[0xB0, 1, 90], 0).unwrap();
+        assert_eq!(mod_wheel.event_type, MidiEventType::ControlChange { controller: 1, value: 90 });
+        let pressure = parse_midi_message(&[0xA0, 60, 33], 0).unwrap();
+        assert_eq!(pressure.event_type, MidiEventType::PolyAftertouch { note: 60, pressure: 33 });
+    }
 }
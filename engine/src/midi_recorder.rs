@@ -120,6 +120,7 @@ impl MidiRecorder {
                 MidiEventType::NoteOff { note, .. } => {
                     self.held_notes.remove(&note);
                 }
+                _ => {}
             }
             return;
         }
@@ -137,7 +138,7 @@ impl MidiRecorder {
                         );
                         MidiEvent::note_on(note, velocity, 0)
                     }
-                    MidiEventType::NoteOff { .. } => unreachable!(), // held_notes only stores NoteOn
+                    _ => unreachable!(), // held_notes only stores NoteOn
                 }
             }).collect();
             for e in held {
@@ -11,6 +11,40 @@ use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 
 const FADE_SAMPLES: usize = 882; // ~20ms at 44.1kHz, ~18ms at 48kHz
+const MAX_FADE_OUT_MS: u32 = 10_000;
+
+/// Gain curve used when a preview fades out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewFadeCurve {
+    Linear,
+    /// Constant dB-per-second drop (fast start, long quiet tail)
+    Exponential,
+    /// Quarter-sine (holds level longer, then drops)
+    EqualPower,
+}
+
+impl PreviewFadeCurve {
+    /// Map FFI index (0=linear, 1=exponential, 2=equal-power)
+    pub fn from_index(index: i32) -> Option<Self> {
+        match index {
+            0 => Some(Self::Linear),
+            1 => Some(Self::Exponential),
+            2 => Some(Self::EqualPower),
+            _ => None,
+        }
+    }
+
+    /// Gain for fade progress `remaining` (1.0 = fade start, 0.0 = silent)
+    fn gain(self, remaining: f32) -> f32 {
+        let x = remaining.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => x,
+            // 60 dB range, offset so the curve still lands exactly on zero
+            Self::Exponential => (1000.0_f32.powf(x) - 1.0) / 999.0,
+            Self::EqualPower => (x * std::f32::consts::FRAC_PI_2).sin(),
+        }
+    }
+}
 
 /// Either a raw preview clip (WAV), decoded clip, or streaming clip (MP3/FLAC)
 enum PreviewClipData {
@@ -80,6 +114,10 @@ pub struct PreviewPlayer {
     is_looping: Arc<AtomicBool>,
     /// Fade state: positive = fade in remaining, negative = fade out remaining
     fade_samples_remaining: i32,
+    /// Fade-out curve applied by `stop`
+    fade_curve: PreviewFadeCurve,
+    /// Fade-out length in output samples
+    fade_out_samples: i32,
     /// Total samples in loaded clip
     total_samples: u64,
     /// Cached waveform peaks for UI display
@@ -105,6 +143,8 @@ impl PreviewPlayer {
             is_playing: Arc::new(AtomicBool::new(false)),
             is_looping: Arc::new(AtomicBool::new(false)),
             fade_samples_remaining: 0,
+            fade_curve: PreviewFadeCurve::Linear,
+            fade_out_samples: FADE_SAMPLES as i32,
             total_samples: 0,
             waveform_peaks: Vec::new(),
             loaded_path: None,
@@ -250,13 +290,32 @@ impl PreviewPlayer {
     }
 
     /// Stop playback with fade out
+    /// A fade-out already in progress keeps running rather than restarting
     pub fn stop(&mut self) {
-        if self.is_playing.load(Ordering::SeqCst) {
+        if !self.is_playing.load(Ordering::SeqCst) || self.fade_samples_remaining < 0 {
+            return;
+        }
+        if self.fade_out_samples == 0 {
+            self.is_playing.store(false, Ordering::SeqCst);
+            self.position_samples.store(0, Ordering::SeqCst);
+            self.fade_samples_remaining = 0;
+        } else {
             // Start fade out (negative = fade out)
-            self.fade_samples_remaining = -(FADE_SAMPLES as i32);
+            self.fade_samples_remaining = -self.fade_out_samples;
         }
     }
 
+    /// Set the fade-out curve used by `stop`
+    pub fn set_fade_curve(&mut self, curve: PreviewFadeCurve) {
+        self.fade_curve = curve;
+    }
+
+    /// Set the fade-out length used by `stop` (0 = stop immediately)
+    pub fn set_fade_duration_ms(&mut self, ms: u32) {
+        let ms = ms.min(MAX_FADE_OUT_MS);
+        self.fade_out_samples = (u64::from(ms) * u64::from(TARGET_SAMPLE_RATE) / 1000) as i32;
+    }
+
     /// Seek to a position in seconds
     pub fn seek(&mut self, position_seconds: f64) {
         let clip_rate = self.clip.as_ref().map_or(TARGET_SAMPLE_RATE, |c| c.sample_rate());
@@ -403,7 +462,7 @@ impl PreviewPlayer {
         }
 
         // Check if fade out completed
-        if self.fade_samples_remaining < 0 && self.fade_samples_remaining >= -(FADE_SAMPLES as i32) {
+        if self.fade_samples_remaining < 0 {
             self.fade_samples_remaining += 1;
             if self.fade_samples_remaining == 0 {
                 // Fade out complete, stop playback
@@ -427,9 +486,9 @@ impl PreviewPlayer {
                 progress.clamp(0.0, 1.0)
             }
             std::cmp::Ordering::Less => {
-                // Fade out: progress from 1 to 0
+                // Fade out: progress from 1 to 0 along the selected curve
                 let remaining = (-self.fade_samples_remaining) as f32;
-                (remaining / FADE_SAMPLES as f32).clamp(0.0, 1.0)
+                self.fade_curve.gain(remaining / self.fade_out_samples.max(1) as f32)
             }
             std::cmp::Ordering::Equal => 1.0, // No fade active
         }
@@ -545,6 +604,41 @@ mod tests {
         assert!(player.calculate_fade_gain() < 0.1);
     }
 
+    #[test]
+    fn test_fade_curves() {
+        for curve in [PreviewFadeCurve::Linear, PreviewFadeCurve::Exponential, PreviewFadeCurve::EqualPower] {
+            assert!((curve.gain(1.0) - 1.0).abs() < 1e-6);
+            assert!(curve.gain(0.0).abs() < 1e-6);
+        }
+        // Exponential drops fastest, equal-power holds level longest
+        let linear = PreviewFadeCurve::Linear.gain(0.5);
+        assert!(PreviewFadeCurve::Exponential.gain(0.5) < linear);
+        assert!(PreviewFadeCurve::EqualPower.gain(0.5) > linear);
+        assert_eq!(PreviewFadeCurve::from_index(3), None);
+    }
+
+    #[test]
+    fn test_repeated_stop_keeps_fade_progress() {
+        let mut player = PreviewPlayer::new();
+        player.set_fade_duration_ms(100);
+        player.is_playing.store(true, Ordering::SeqCst);
+
+        player.stop();
+        assert_eq!(player.fade_samples_remaining, -4800);
+        player.fade_samples_remaining = -1200; // partway through the fade
+        player.stop();
+        assert_eq!(player.fade_samples_remaining, -1200);
+    }
+
+    #[test]
+    fn test_zero_fade_duration_stops_immediately() {
+        let mut player = PreviewPlayer::new();
+        player.set_fade_duration_ms(0);
+        player.is_playing.store(true, Ordering::SeqCst);
+        player.stop();
+        assert!(!player.is_playing());
+    }
+
     #[test]
     fn test_resample_peaks() {
         let peaks = vec![0.5, 1.0, 0.3, 0.8];
@@ -6,10 +6,19 @@
 /// - audio/ (imported audio files)
 /// - cache/ (waveform peaks, etc.)
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+
+/// Current project format version, written by every save
+///
+/// New fields carry a `#[serde(default)]` matching the old behavior, so older
+/// projects load without a migration and adding one needs no version bump. Bump
+/// this, and add a step to `migrate_project`, only for changes a default can't
+/// express: renamed, moved or reinterpreted fields.
+pub const PROJECT_FORMAT_VERSION: &str = "1.1";
 
 // ========================================================================
 // PROJECT DATA STRUCTURES
@@ -37,23 +46,47 @@ pub struct ProjectData {
     /// Metronome enabled state
     #[serde(default = "default_true")]
     pub metronome_enabled: bool,
+    /// Metronome clicks per beat (1-4)
+    #[serde(default = "default_metronome_subdivision")]
+    pub metronome_subdivision: u32,
+    /// Metronome accents beat 1 of each bar
+    #[serde(default = "default_true")]
+    pub metronome_accent: bool,
+    /// Custom metronome click samples (accent path, normal path); None = synthesized click
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metronome_samples: Option<(String, String)>,
     /// Count-in duration in bars
     #[serde(default = "default_count_in")]
     pub count_in_bars: u32,
     /// Buffer size preset (0=Lowest, 1=Low, 2=Balanced, 3=Safe, 4=High)
     #[serde(default = "default_buffer_size")]
     pub buffer_size_preset: u32,
+    /// Named timeline markers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<MarkerData>,
+    /// Tempo changes after the start (`tempo` holds the tempo at beat 0)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tempo_changes: Vec<TempoChangeData>,
+    /// Time-signature changes after the first bar (`time_sig_*` hold the signature of bar 0)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub time_signature_changes: Vec<TimeSignatureChangeData>,
 }
 
 fn default_true() -> bool { true }
 fn default_count_in() -> u32 { 2 }
+fn default_input_channel_count() -> u32 { 1 }
+fn default_metronome_subdivision() -> u32 { 1 }
 fn default_buffer_size() -> u32 { 2 } // Balanced
+fn default_unison_voices() -> u8 { 1 }
+fn default_pitch_bend_range() -> f32 { crate::synth::DEFAULT_PITCH_BEND_RANGE }
+fn default_mod_destination() -> String { "vibrato".to_string() }
+fn default_stretch_factor() -> f32 { 1.0 }
 
 impl ProjectData {
     /// Create a new empty project
     pub fn new(name: String) -> Self {
         Self {
-            version: "1.0".to_string(),
+            version: PROJECT_FORMAT_VERSION.to_string(),
             name,
             tempo: 120.0,
             sample_rate: 48000,
@@ -62,8 +95,14 @@ impl ProjectData {
             tracks: Vec::new(),
             audio_files: Vec::new(),
             metronome_enabled: true,
+            metronome_subdivision: 1,
+            metronome_accent: true,
+            metronome_samples: None,
             count_in_bars: 2,
             buffer_size_preset: 2, // Balanced
+            markers: Vec::new(),
+            tempo_changes: Vec::new(),
+            time_signature_changes: Vec::new(),
         }
     }
 }
@@ -97,6 +136,18 @@ pub struct TrackData {
     /// Sampler settings (for MIDI tracks with sampler instrument)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sampler_settings: Option<crate::sampler::SamplerData>,
+    /// Arpeggiator settings (for MIDI tracks that have used the arpeggiator)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arpeggiator: Option<crate::arpeggiator::ArpeggiatorData>,
+    /// Step sequencer pattern (for MIDI tracks using the step sequencer)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_sequencer: Option<crate::step_sequencer::StepSequencerData>,
+    /// Keeps playing under solo (None = the track type's default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solo_safe: Option<bool>,
+    /// Signal point the track meter reads ("pre_fx", "post_fx_pre_fader", "post_fader")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metering_point: Option<String>,
     /// Send routing to return tracks
     #[serde(default)]
     pub sends: Vec<SendData>,
@@ -106,9 +157,36 @@ pub struct TrackData {
     /// Input monitoring enabled
     #[serde(default)]
     pub input_monitoring: bool,
+    /// Monitor input through the FX chain
+    #[serde(default = "default_true")]
+    pub monitor_through_fx: bool,
+    /// Input trim in dB
+    #[serde(default)]
+    pub input_gain_db: f32,
+    /// Audio input device index (-1 = no input; absent in older projects)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_device_index: Option<i32>,
+    /// First input channel (0-based)
+    #[serde(default)]
+    pub input_channel: u32,
+    /// Input channels recorded: 1 = mono, 2 = stereo pair
+    #[serde(default = "default_input_channel_count")]
+    pub input_channel_count: u32,
+    /// Polarity inverted before volume/pan
+    #[serde(default)]
+    pub phase_invert: bool,
+    /// Summed to mono before volume/pan
+    #[serde(default)]
+    pub mono_sum: bool,
+    /// Built-in synth layered under a VST3 instrument
+    #[serde(default)]
+    pub layer_synth: bool,
     /// VST3 plugins on this track
     #[serde(default)]
     pub vst3_plugins: Vec<Vst3PluginData>,
+    /// Pan automation curve (pan -1.0 to +1.0)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pan_automation: Vec<AutomationPointData>,
 }
 
 /// Clip data (audio or MIDI)
@@ -126,6 +204,51 @@ pub struct ClipData {
     pub audio_file_id: Option<u64>,
     /// MIDI notes (for MIDI clips)
     pub midi_notes: Option<Vec<MidiNoteData>>,
+    /// Pitch bend, CC and aftertouch events (for MIDI clips)
+    #[serde(default)]
+    pub midi_controls: Vec<MidiControlData>,
+    /// Polarity inverted (audio clips)
+    #[serde(default)]
+    pub polarity_inverted: bool,
+    /// Stereo channels summed to mono (audio clips)
+    #[serde(default)]
+    pub mono_sum: bool,
+    /// Fade-in length in seconds (audio clips)
+    #[serde(default)]
+    pub fade_in_seconds: f64,
+    /// Fade-out length in seconds (audio clips)
+    #[serde(default)]
+    pub fade_out_seconds: f64,
+    /// Tempo sync enabled (audio clips)
+    #[serde(default)]
+    pub warp_enabled: bool,
+    /// Stretch factor, `project_bpm` / `clip_bpm` (audio clips)
+    #[serde(default = "default_stretch_factor")]
+    pub stretch_factor: f32,
+    /// Warp algorithm: 0 = warp (pitch preserved), 1 = repitch (audio clips)
+    #[serde(default)]
+    pub warp_mode: u8,
+    /// Played backwards (audio clips)
+    #[serde(default)]
+    pub reversed: bool,
+    /// Transpose in semitones (audio clips)
+    #[serde(default)]
+    pub transpose_semitones: i32,
+    /// Fine pitch adjustment in cents (audio clips)
+    #[serde(default)]
+    pub transpose_cents: i32,
+    /// Gain envelope breakpoints in dB, time from the clip start (audio clips)
+    #[serde(default)]
+    pub gain_envelope: Vec<AutomationPointData>,
+}
+
+/// Automation breakpoint data
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct AutomationPointData {
+    /// Time position (seconds)
+    pub time_seconds: f64,
+    /// Automation value
+    pub value: f32,
 }
 
 /// MIDI note data
@@ -141,6 +264,20 @@ pub struct MidiNoteData {
     pub duration: f64,
 }
 
+/// MIDI controller event data
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MidiControlData {
+    /// "pitch_bend", "cc" or "poly_aftertouch"
+    pub kind: String,
+    /// Controller number (cc) or note (`poly_aftertouch`); unused for pitch bend
+    #[serde(default)]
+    pub number: u8,
+    /// Pitch bend -8192..=8191, otherwise 0-127
+    pub value: i16,
+    /// Time (seconds from clip start)
+    pub time: f64,
+}
+
 /// Audio file metadata
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AudioFileData {
@@ -156,6 +293,38 @@ pub struct AudioFileData {
     pub sample_rate: u32,
     /// Number of channels
     pub channels: u32,
+    /// Whether the file is streamed from disk instead of loaded into memory
+    #[serde(default)]
+    pub streamed: bool,
+}
+
+/// Tempo change data
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TempoChangeData {
+    /// Beat (quarter notes from the timeline start) the tempo changes at
+    pub beat: f64,
+    /// Tempo in BPM from that beat on
+    pub bpm: f64,
+}
+
+/// Time-signature change data
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TimeSignatureChangeData {
+    /// Bar (counted from 0 at the timeline start) the signature changes at
+    pub bar: u32,
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+/// Timeline marker data
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MarkerData {
+    /// Marker ID
+    pub id: u64,
+    /// Timeline position in seconds
+    pub time_seconds: f64,
+    /// Marker name
+    pub label: String,
 }
 
 /// Effect data
@@ -163,10 +332,13 @@ pub struct AudioFileData {
 pub struct EffectData {
     /// Effect ID
     pub id: u64,
-    /// Effect type: "eq", "compressor", "reverb", "delay", "chorus", "limiter"
+    /// Effect type: "eq", "compressor", "reverb", "delay", "chorus", "limiter", "bitcrusher", "gate", "stereo_width", "filter", "gain"
     pub effect_type: String,
     /// Effect parameters
     pub parameters: HashMap<String, f32>,
+    /// Parameter automation curves keyed by parameter name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub automation: HashMap<String, Vec<AutomationPointData>>,
 }
 
 /// Send routing data for serialization
@@ -210,6 +382,21 @@ pub struct SynthData {
     pub sustain: f32,
     /// Envelope release time (seconds)
     pub release: f32,
+    /// Unison copies per note (1 - 7)
+    #[serde(default = "default_unison_voices")]
+    pub unison_voices: u8,
+    /// Unison detune spread (cents)
+    #[serde(default)]
+    pub unison_detune_cents: f32,
+    /// Pitch wheel range (semitones each way)
+    #[serde(default = "default_pitch_bend_range")]
+    pub pitch_bend_range: f32,
+    /// Mod wheel / aftertouch destination: "vibrato" or "filter_cutoff"
+    #[serde(default = "default_mod_destination")]
+    pub mod_destination: String,
+    /// Note velocity mapping
+    #[serde(default)]
+    pub velocity_curve: crate::midi::VelocityCurve,
 }
 
 impl Default for SynthData {
@@ -221,6 +408,11 @@ impl Default for SynthData {
             decay: 0.1,
             sustain: 0.7,
             release: 0.3,
+            unison_voices: 1,
+            unison_detune_cents: 0.0,
+            pitch_bend_range: default_pitch_bend_range(),
+            mod_destination: default_mod_destination(),
+            velocity_curve: crate::midi::VelocityCurve::Linear,
         }
     }
 }
@@ -267,8 +459,8 @@ pub fn load_project(project_path: &Path) -> Result<ProjectData> {
     let json = fs::read_to_string(&json_path)
         .context("Failed to read project.json")?;
 
-    // Deserialize project data
-    let project_data: ProjectData = serde_json::from_str(&json)
+    // Deserialize project data (upgrading older formats)
+    let project_data = parse_project_json(&json)
         .context("Failed to parse project.json")?;
 
     eprintln!("✅ [Project] Loaded project: {}", project_data.name);
@@ -278,30 +470,49 @@ pub fn load_project(project_path: &Path) -> Result<ProjectData> {
     Ok(project_data)
 }
 
-/// Copy audio file into project folder
-pub fn copy_audio_file_to_project(
-    source_path: &Path,
-    project_path: &Path,
-    file_id: u64,
-) -> Result<String> {
+/// Number of hex digits of the content hash used in stored file names
+const CONTENT_HASH_LEN: usize = 16;
+
+/// Hash an audio file's contents (blake3, truncated hex)
+pub fn audio_content_hash(path: &Path) -> Result<String> {
+    let file = fs::File::open(path)
+        .with_context(|| format!("Failed to open audio file {}", path.display()))?;
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(file)
+        .context("Failed to hash audio file")?;
+    Ok(hasher.finalize().to_hex()[..CONTENT_HASH_LEN].to_string())
+}
+
+/// Copy audio file into project folder, stored once per unique content
+///
+/// Files are named `audio/{hash}-{filename}`, so clips sharing a sample (or
+/// identical copies of it) point at a single stored file. Returns the relative path.
+pub fn copy_audio_file_to_project(source_path: &Path, project_path: &Path) -> Result<String> {
     let audio_dir = project_path.join("audio");
     fs::create_dir_all(&audio_dir)
         .context("Failed to create audio directory")?;
 
-    // Generate filename: 001-filename.wav
     let original_name = source_path
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("audio.wav");
 
-    let dest_filename = format!("{file_id:03}-{original_name}");
-    let dest_path = audio_dir.join(&dest_filename);
+    // Already stored (another clip, an identical copy, or a re-save of a loaded project)
+    let hash = audio_content_hash(source_path)?;
+    let prefix = format!("{hash}-");
+    let stored = fs::read_dir(&audio_dir)
+        .context("Failed to read audio directory")?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .find(|name| name.starts_with(&prefix));
+    if let Some(stored) = stored {
+        return Ok(format!("audio/{stored}"));
+    }
 
-    // Copy file
-    fs::copy(source_path, &dest_path)
+    // Generate filename: 1a2b3c4d5e6f7a8b-filename.wav
+    let dest_filename = format!("{prefix}{original_name}");
+    fs::copy(source_path, audio_dir.join(&dest_filename))
         .context("Failed to copy audio file")?;
 
-    // Return relative path
     let relative_path = format!("audio/{dest_filename}");
     eprintln!("📁 [Project] Copied audio file: {relative_path}");
     Ok(relative_path)
@@ -312,6 +523,82 @@ pub fn resolve_audio_file_path(project_path: &Path, relative_path: &str) -> Path
     project_path.join(relative_path)
 }
 
+// ========================================================================
+// RECOVERY SNAPSHOTS
+// ========================================================================
+
+/// Write a recovery snapshot (serialized `ProjectData`) to a single JSON file
+///
+/// The file is written next to the target and renamed into place, so a crash
+/// mid-write never leaves a truncated snapshot behind.
+pub fn write_recovery_snapshot(json: &str, snapshot_path: &Path) -> Result<()> {
+    if let Some(parent) = snapshot_path.parent() {
+        fs::create_dir_all(parent)
+            .context("Failed to create recovery directory")?;
+    }
+
+    let temp_path = snapshot_path.with_extension("tmp");
+    fs::write(&temp_path, json)
+        .context("Failed to write recovery snapshot")?;
+    fs::rename(&temp_path, snapshot_path)
+        .context("Failed to move recovery snapshot into place")?;
+    Ok(())
+}
+
+/// Load a recovery snapshot written by `write_recovery_snapshot`
+///
+/// Audio file paths in a snapshot point at the original source files.
+pub fn load_recovery_snapshot(snapshot_path: &Path) -> Result<ProjectData> {
+    let json = fs::read_to_string(snapshot_path)
+        .context("Failed to read recovery snapshot")?;
+    parse_project_json(&json)
+        .context("Failed to parse recovery snapshot")
+}
+
+// ========================================================================
+// FORMAT MIGRATION
+// ========================================================================
+
+/// Parse project JSON, migrating older format versions to the current one
+pub fn parse_project_json(json: &str) -> Result<ProjectData> {
+    let mut project: Value = serde_json::from_str(json)
+        .context("Invalid project JSON")?;
+    migrate_project(&mut project)?;
+    serde_json::from_value(project)
+        .context("Project data doesn't match the current format")
+}
+
+/// Upgrade a project's JSON in place to `PROJECT_FORMAT_VERSION`
+///
+/// Steps run in sequence, so a project several versions old passes through each;
+/// fields a step doesn't touch are filled in by their serde defaults when parsed.
+/// Projects without a version field predate versioning and are treated as "1.0".
+fn migrate_project(project: &mut Value) -> Result<()> {
+    let Some(project) = project.as_object_mut() else {
+        bail!("Project data is not a JSON object");
+    };
+
+    let mut version = project
+        .get("version")
+        .and_then(Value::as_str)
+        .unwrap_or("1.0")
+        .to_string();
+
+    while version != PROJECT_FORMAT_VERSION {
+        version = match version.as_str() {
+            // Everything 1.1 added has a serde default: nothing to rewrite
+            "1.0" => "1.1".to_string(),
+            other => bail!(
+                "Unsupported project format version {other} (this build reads up to {PROJECT_FORMAT_VERSION})"
+            ),
+        };
+        eprintln!("🔄 [Project] Migrated project format to {version}");
+    }
+
+    project.insert("version".to_string(), Value::String(version));
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -350,4 +637,112 @@ mod tests {
         // Clean up
         fs::remove_dir_all(&temp_dir).unwrap();
     }
+
+    #[test]
+    fn test_recovery_snapshot_round_trip() {
+        let temp_dir = env::temp_dir().join("boojy_test_recovery");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let snapshot_path = temp_dir.join("recovery.json");
+
+        let mut project = ProjectData::new("Recovered".to_string());
+        project.tempo = 96.0;
+        let json = serde_json::to_string(&project).unwrap();
+
+        write_recovery_snapshot(&json, &snapshot_path).unwrap();
+        assert!(!snapshot_path.with_extension("tmp").exists());
+
+        let loaded = load_recovery_snapshot(&snapshot_path).unwrap();
+        assert_eq!(loaded.version, PROJECT_FORMAT_VERSION);
+        assert_eq!(loaded.name, "Recovered");
+        assert!((loaded.tempo - 96.0).abs() < 1e-6);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_shared_audio_is_stored_once() {
+        let temp_dir = env::temp_dir().join("boojy_test_dedup");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let project_path = temp_dir.join("Dedup.audio");
+        let source_dir = temp_dir.join("samples");
+        fs::create_dir_all(&source_dir).unwrap();
+
+        let kick = source_dir.join("kick.wav");
+        let other_kick = source_dir.join("kick-copy.wav");
+        let snare = source_dir.join("snare.wav");
+        fs::write(&kick, b"RIFF kick sample data").unwrap();
+        fs::write(&other_kick, b"RIFF kick sample data").unwrap();
+        fs::write(&snare, b"RIFF snare sample data").unwrap();
+
+        // Two clips using the same file share one stored copy
+        let first = copy_audio_file_to_project(&kick, &project_path).unwrap();
+        let second = copy_audio_file_to_project(&kick, &project_path).unwrap();
+        assert_eq!(first, second);
+        let stored = fs::read_dir(project_path.join("audio")).unwrap().count();
+        assert_eq!(stored, 1);
+
+        // Identical content under another name reuses it too; different content doesn't
+        assert_eq!(copy_audio_file_to_project(&other_kick, &project_path).unwrap(), first);
+        assert_ne!(copy_audio_file_to_project(&snare, &project_path).unwrap(), first);
+        assert_eq!(fs::read_dir(project_path.join("audio")).unwrap().count(), 2);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    /// A project as saved by the 1.0 format, before any of the 1.1 fields existed
+    const PROJECT_V1_0: &str =
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/project_v1_0.json"));
+
+    #[test]
+    fn test_v1_0_project_migrates_with_defaults() {
+        let project = parse_project_json(PROJECT_V1_0).unwrap();
+        assert_eq!(project.version, PROJECT_FORMAT_VERSION);
+        assert_eq!(project.name, "Old Song");
+        assert!((project.tempo - 98.0).abs() < 1e-6);
+
+        // Each field added in 1.1 must take the value that keeps 1.0 behavior
+        assert_eq!(project.metronome_subdivision, 1);
+        assert!(project.metronome_accent);
+
+        let audio = &project.tracks[0];
+        assert!(audio.monitor_through_fx);
+        assert!(audio.input_gain_db.abs() < 1e-6);
+        assert!(audio.pan_automation.is_empty());
+        let clip = &audio.clips[0];
+        assert_eq!(clip.audio_file_id, Some(1));
+        assert!(!clip.warp_enabled && !clip.polarity_inverted && !clip.mono_sum);
+        assert!((clip.stretch_factor - 1.0).abs() < 1e-6);
+        assert_eq!(clip.warp_mode, 0);
+        assert!(clip.fade_in_seconds.abs() < 1e-9 && clip.fade_out_seconds.abs() < 1e-9);
+        // Fields added after 1.1 come from their serde defaults as well
+        assert!(!clip.reversed && !audio.phase_invert && !audio.mono_sum);
+        assert!(project.tempo_changes.is_empty());
+        assert!(audio.fx_chain[0].automation.is_empty());
+        assert!((audio.fx_chain[0].parameters["low_gain"] - 3.0).abs() < 1e-6);
+
+        let midi = &project.tracks[1];
+        let synth = midi.synth_settings.as_ref().unwrap();
+        assert_eq!(synth.osc_type, "square");
+        assert_eq!(synth.unison_voices, 1);
+        assert!(synth.unison_detune_cents.abs() < 1e-6);
+        assert_eq!(midi.clips[0].midi_notes.as_ref().unwrap().len(), 2);
+
+        let sampler = project.tracks[2].sampler_settings.as_ref().unwrap();
+        assert_eq!(sampler.root_note, 60);
+        assert_eq!(sampler.interpolation_mode, crate::sampler::InterpolationMode::Linear);
+        assert!(sampler.zones.is_empty());
+    }
+
+    #[test]
+    fn test_current_and_unknown_versions() {
+        let json = serde_json::to_string(&ProjectData::new("Current".to_string())).unwrap();
+        assert_eq!(parse_project_json(&json).unwrap().name, "Current");
+
+        let future = json.replace(
+            &format!("\"version\":\"{PROJECT_FORMAT_VERSION}\""),
+            "\"version\":\"9.0\"",
+        );
+        let err = parse_project_json(&future).unwrap_err();
+        assert!(format!("{err:#}").contains("9.0"), "{err:#}");
+    }
 }
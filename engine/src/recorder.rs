@@ -1,9 +1,99 @@
 /// Recording engine with metronome and count-in support
 use crate::audio_file::{AudioClip, TARGET_SAMPLE_RATE};
+use crate::tempo_map::TempoMap;
+use crate::time_signature::{TimeSignature, TimeSignatureMap};
 use std::f32::consts::PI;
 use std::sync::Arc;
 use parking_lot::Mutex;
-use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+
+/// Loop passes reserved per recording before marking a take may allocate
+const MAX_PREALLOCATED_TAKES: usize = 64;
+
+/// Allowed metronome subdivisions (clicks per beat)
+pub const MAX_METRONOME_SUBDIVISION: u32 = 4;
+
+/// Metronome click length on a beat (~80ms at 48kHz)
+const BEAT_CLICK_SAMPLES: u64 = 4000;
+/// Metronome click length on a subdivision (~40ms at 48kHz)
+const SUBDIVISION_CLICK_SAMPLES: u64 = 2000;
+
+/// Level of subdivision clicks relative to beat clicks
+const SUBDIVISION_CLICK_LEVEL: f32 = 0.5;
+
+/// User-supplied metronome click sounds, resampled to `TARGET_SAMPLE_RATE` on load
+#[derive(Clone)]
+pub struct MetronomeSamples {
+    /// Played on beat 1 of each bar when the accent is enabled
+    pub accent: Arc<AudioClip>,
+    /// Played on every other beat and on subdivisions
+    pub normal: Arc<AudioClip>,
+}
+
+impl MetronomeSamples {
+    /// Mono sample of a click at `frame`, silent past the end of the clip
+    fn sample(clip: &AudioClip, frame: usize) -> f32 {
+        match clip.channels {
+            0 => 0.0,
+            1 => clip.get_sample(frame, 0).unwrap_or(0.0),
+            _ => {
+                let left = clip.get_sample(frame, 0).unwrap_or(0.0);
+                let right = clip.get_sample(frame, 1).unwrap_or(0.0);
+                (left + right) * 0.5
+            }
+        }
+    }
+}
+
+/// Metronome click sample at a position within the bar
+///
+/// Beat 1 of the bar gets a higher-pitched click when `accent` is on; subdivisions
+/// between beats get a shorter, quieter click. Clicks never run past the next one.
+/// With `samples` loaded the click sounds are played instead of the synthesized tone.
+fn metronome_click(
+    position_in_bar: u64,
+    samples_per_beat: u64,
+    subdivision: u32,
+    accent: bool,
+    samples: Option<&MetronomeSamples>,
+) -> f32 {
+    let samples_per_beat = samples_per_beat.max(1);
+    let subdivision = u64::from(subdivision.clamp(1, MAX_METRONOME_SUBDIVISION));
+    let samples_per_tick = (samples_per_beat / subdivision).max(1);
+
+    let beat_in_bar = position_in_bar / samples_per_beat;
+    let position_in_beat = position_in_bar % samples_per_beat;
+    let tick_in_beat = position_in_beat / samples_per_tick;
+    let position_in_tick = position_in_beat % samples_per_tick;
+    // Integer division can leave a short tail after the last tick; it belongs to that tick
+    if tick_in_beat >= subdivision {
+        return 0.0;
+    }
+
+    if let Some(samples) = samples {
+        let is_accent = tick_in_beat == 0 && beat_in_bar == 0 && accent;
+        let clip = if is_accent { &samples.accent } else { &samples.normal };
+        let level = if tick_in_beat > 0 { SUBDIVISION_CLICK_LEVEL } else { 1.0 };
+        return MetronomeSamples::sample(clip, position_in_tick as usize) * level;
+    }
+
+    let (freq, level, length) = if tick_in_beat > 0 {
+        (800.0, 0.3, SUBDIVISION_CLICK_SAMPLES)
+    } else if beat_in_bar == 0 && accent {
+        (1200.0, 0.6, BEAT_CLICK_SAMPLES) // Higher pitch on downbeat
+    } else {
+        (800.0, 0.6, BEAT_CLICK_SAMPLES)
+    };
+    let length = length.min(samples_per_tick);
+    if position_in_tick >= length {
+        return 0.0;
+    }
+
+    // Short sine burst
+    let t = position_in_tick as f32 / TARGET_SAMPLE_RATE as f32;
+    let envelope = (1.0 - (position_in_tick as f32 / length as f32)).powi(2);
+    (2.0 * PI * freq * t).sin() * level * envelope
+}
 
 /// Recording state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,22 +104,56 @@ pub enum RecordingState {
     Recording,
 }
 
+/// One loop-recording pass, split out of the recorded buffer
+pub struct RecordedTake {
+    /// Recorded audio for this pass
+    pub clip: AudioClip,
+    /// Timeline position (seconds) where the pass started
+    pub start_seconds: f64,
+}
+
+impl RecordedTake {
+    /// Move the take `latency_samples` earlier on the timeline, where the performer
+    /// played it; audio that would land before the start of the timeline is dropped
+    pub fn compensate_latency(&mut self, latency_samples: u64) {
+        let sample_rate = f64::from(self.clip.sample_rate);
+        let latency_seconds = latency_samples as f64 / sample_rate;
+        let trim_frames = ((latency_seconds - self.start_seconds).max(0.0) * sample_rate).round() as usize;
+        self.start_seconds = (self.start_seconds - latency_seconds).max(0.0);
+
+        if trim_frames > 0 {
+            let channels = self.clip.channels.max(1);
+            let trim = (trim_frames * channels).min(self.clip.samples.len());
+            self.clip.samples.drain(..trim);
+            self.clip.duration_seconds = (self.clip.samples.len() / channels) as f64 / sample_rate;
+        }
+    }
+}
+
 /// The recording engine that manages audio recording
 pub struct Recorder {
     /// Current recording state
     state: Arc<Mutex<RecordingState>>,
-    /// Recorded audio buffer (interleaved stereo samples)
+    /// Recorded audio buffer (interleaved across `recorded_channels`)
     recorded_samples: Arc<Mutex<Vec<f32>>>,
+    /// Channels per recorded frame: every input device channel, at least 2
+    recorded_channels: Arc<AtomicUsize>,
     /// Sample count since recording started
     sample_counter: Arc<AtomicU64>,
     /// Count-in duration in bars
     count_in_bars: Arc<Mutex<u32>>,
-    /// Tempo in BPM
-    tempo: Arc<Mutex<f64>>,
+    /// Tempo changes over the timeline (the first one is the project tempo)
+    tempo_map: Arc<Mutex<TempoMap>>,
     /// Metronome enabled
     metronome_enabled: Arc<AtomicBool>,
-    /// Time signature (beats per bar)
-    time_signature: Arc<Mutex<u32>>,
+    /// Metronome clicks per beat (1-4)
+    metronome_subdivision: Arc<AtomicU32>,
+    /// Accent beat 1 of each bar with a higher click
+    metronome_accent: Arc<AtomicBool>,
+    /// Custom click sounds (None = synthesized click)
+    metronome_samples: Arc<Mutex<Option<MetronomeSamples>>>,
+    /// Time-signature changes over the timeline (the first one is the project signature)
+    time_signature_map: Arc<Mutex<TimeSignatureMap>>,
     /// Samples remaining to suppress metronome after seek (prevents click overlap)
     seek_cooldown: Arc<AtomicU64>,
     /// Playhead position (in seconds) where recording should be placed on the timeline
@@ -38,6 +162,12 @@ pub struct Recorder {
     count_in_beat: Arc<AtomicU32>,
     /// Count-in progress as fixed-point (0-10000 maps to 0.0-1.0)
     count_in_progress: Arc<AtomicU32>,
+    /// Counting in before playback (transport still stopped)
+    playback_count_in: Arc<AtomicBool>,
+    /// Metronome position to resume from once the playback count-in ends
+    playback_count_in_resume: Arc<AtomicU64>,
+    /// Set by audio callback when the playback count-in finishes (transport should start)
+    playback_count_in_done: Arc<AtomicBool>,
     /// Punch-in enabled (auto-start recording at region start)
     punch_in_enabled: Arc<AtomicBool>,
     /// Punch-out enabled (auto-stop recording at region end)
@@ -48,6 +178,8 @@ pub struct Recorder {
     punch_out_seconds: Arc<Mutex<f64>>,
     /// Set by audio callback when auto-punch-out fires
     punch_complete: Arc<AtomicBool>,
+    /// Loop passes after the first: (index into `recorded_samples`, timeline start seconds)
+    take_starts: Arc<Mutex<Vec<(usize, f64)>>>,
 }
 
 impl Default for Recorder {
@@ -62,20 +194,28 @@ impl Recorder {
         Self {
             state: Arc::new(Mutex::new(RecordingState::Idle)),
             recorded_samples: Arc::new(Mutex::new(Vec::new())),
+            recorded_channels: Arc::new(AtomicUsize::new(2)),
             sample_counter: Arc::new(AtomicU64::new(0)),
             count_in_bars: Arc::new(Mutex::new(1)), // Default: 1 bar
-            tempo: Arc::new(Mutex::new(120.0)), // Default: 120 BPM
+            tempo_map: Arc::new(Mutex::new(TempoMap::default())), // Default: 120 BPM
             metronome_enabled: Arc::new(AtomicBool::new(true)),
-            time_signature: Arc::new(Mutex::new(4)), // Default: 4/4
+            metronome_subdivision: Arc::new(AtomicU32::new(1)),
+            metronome_accent: Arc::new(AtomicBool::new(true)),
+            metronome_samples: Arc::new(Mutex::new(None)),
+            time_signature_map: Arc::new(Mutex::new(TimeSignatureMap::default())), // Default: 4/4
             seek_cooldown: Arc::new(AtomicU64::new(0)),
             recording_start_seconds: Arc::new(Mutex::new(0.0)),
             count_in_beat: Arc::new(AtomicU32::new(0)),
             count_in_progress: Arc::new(AtomicU32::new(0)),
+            playback_count_in: Arc::new(AtomicBool::new(false)),
+            playback_count_in_resume: Arc::new(AtomicU64::new(0)),
+            playback_count_in_done: Arc::new(AtomicBool::new(false)),
             punch_in_enabled: Arc::new(AtomicBool::new(false)),
             punch_out_enabled: Arc::new(AtomicBool::new(false)),
             punch_in_seconds: Arc::new(Mutex::new(0.0)),
             punch_out_seconds: Arc::new(Mutex::new(0.0)),
             punch_complete: Arc::new(AtomicBool::new(false)),
+            take_starts: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -84,19 +224,27 @@ impl Recorder {
         RecorderCallbackRefs {
             state: self.state.clone(),
             recorded_samples: self.recorded_samples.clone(),
+            recorded_channels: self.recorded_channels.clone(),
             sample_counter: self.sample_counter.clone(),
             count_in_bars: self.count_in_bars.clone(),
-            tempo: self.tempo.clone(),
+            tempo_map: self.tempo_map.clone(),
             metronome_enabled: self.metronome_enabled.clone(),
-            time_signature: self.time_signature.clone(),
+            metronome_subdivision: self.metronome_subdivision.clone(),
+            metronome_accent: self.metronome_accent.clone(),
+            metronome_samples: self.metronome_samples.clone(),
+            time_signature_map: self.time_signature_map.clone(),
             seek_cooldown: self.seek_cooldown.clone(),
             count_in_beat: self.count_in_beat.clone(),
             count_in_progress: self.count_in_progress.clone(),
+            playback_count_in: self.playback_count_in.clone(),
+            playback_count_in_resume: self.playback_count_in_resume.clone(),
+            playback_count_in_done: self.playback_count_in_done.clone(),
             punch_in_enabled: self.punch_in_enabled.clone(),
             punch_out_enabled: self.punch_out_enabled.clone(),
             punch_in_seconds: self.punch_in_seconds.clone(),
             punch_out_seconds: self.punch_out_seconds.clone(),
             punch_complete: self.punch_complete.clone(),
+            take_starts: self.take_starts.clone(),
         }
     }
 
@@ -115,6 +263,13 @@ impl Recorder {
             eprintln!("🎙️  [Recorder] Cleared {} previous samples", samples.len());
         }
 
+        // Reserve take markers up front so loop wraps don't allocate on the audio thread
+        {
+            let mut take_starts = self.take_starts.lock();
+            take_starts.clear();
+            take_starts.reserve(MAX_PREALLOCATED_TAKES);
+        }
+
         self.sample_counter.store(0, Ordering::SeqCst);
         self.punch_complete.store(false, Ordering::SeqCst);
 
@@ -138,6 +293,7 @@ impl Recorder {
     }
 
     /// Stop recording and return the recorded audio clip
+    /// Loop passes are joined into one clip; see `stop_recording_takes` to keep them apart
     pub fn stop_recording(&self) -> Result<Option<AudioClip>, String> {
         let mut state = self.state.lock();
         let punch_completed = self.punch_complete.load(Ordering::SeqCst);
@@ -169,13 +325,14 @@ impl Recorder {
             return Ok(None);
         }
 
-        // Create audio clip from recorded samples
-        let frame_count = samples.len() / 2; // Stereo
+        // Create audio clip from recorded samples (one channel per input channel)
+        let channels = self.get_recorded_channels();
+        let frame_count = samples.len() / channels;
         let duration_seconds = frame_count as f64 / f64::from(TARGET_SAMPLE_RATE);
 
         let clip = AudioClip {
             samples,
-            channels: 2,
+            channels,
             sample_rate: TARGET_SAMPLE_RATE,
             duration_seconds,
             file_path: format!("recorded_{}.wav",
@@ -184,11 +341,48 @@ impl Recorder {
                     .unwrap_or_default()
                     .as_secs()
             ),
+            stream: None,
         };
 
         Ok(Some(clip))
     }
 
+    /// Stop recording and split the buffer into one take per loop pass
+    /// A pass cut short by stopping mid-loop is kept as the final take
+    pub fn stop_recording_takes(&self) -> Result<Vec<RecordedTake>, String> {
+        let take_starts = std::mem::take(&mut *self.take_starts.lock());
+        let Some(clip) = self.stop_recording()? else {
+            return Ok(Vec::new());
+        };
+
+        let first_start = self.get_recording_start_seconds();
+        let mut bounds: Vec<(usize, f64)> = vec![(0, first_start)];
+        bounds.extend(take_starts.into_iter().filter(|&(index, _)| index < clip.samples.len()));
+
+        let mut takes = Vec::with_capacity(bounds.len());
+        for (i, &(begin, start_seconds)) in bounds.iter().enumerate() {
+            let end = bounds.get(i + 1).map_or(clip.samples.len(), |&(next, _)| next);
+            if end <= begin {
+                continue;
+            }
+            let samples = clip.samples[begin..end].to_vec();
+            let duration_seconds = (samples.len() / clip.channels) as f64 / f64::from(TARGET_SAMPLE_RATE);
+            takes.push(RecordedTake {
+                clip: AudioClip {
+                    samples,
+                    channels: clip.channels,
+                    sample_rate: TARGET_SAMPLE_RATE,
+                    duration_seconds,
+                    file_path: clip.file_path.clone(),
+                    stream: None,
+                },
+                start_seconds,
+            });
+        }
+
+        Ok(takes)
+    }
+
     /// Get current recording state
     pub fn get_state(&self) -> RecordingState {
         *self.state.lock()
@@ -204,14 +398,34 @@ impl Recorder {
         *self.count_in_bars.lock()
     }
 
-    /// Set tempo in BPM
+    /// Set tempo in BPM (the tempo at the timeline start; later tempo changes are kept)
     pub fn set_tempo(&self, bpm: f64) {
-        *self.tempo.lock() = bpm.clamp(20.0, 300.0);
+        self.tempo_map.lock().set_base_tempo(bpm);
     }
 
-    /// Get tempo in BPM
+    /// Get tempo in BPM (the tempo at the timeline start)
     pub fn get_tempo(&self) -> f64 {
-        *self.tempo.lock()
+        self.tempo_map.lock().base_tempo()
+    }
+
+    /// Change the tempo from `beat` on (beats at or before 0 set the project tempo)
+    pub fn add_tempo_change(&self, beat: f64, bpm: f64) {
+        self.tempo_map.lock().add_change(beat, bpm);
+    }
+
+    /// Remove the tempo change at `beat`; returns false if there is none
+    pub fn remove_tempo_change(&self, beat: f64) -> bool {
+        self.tempo_map.lock().remove_change(beat)
+    }
+
+    /// Copy of the tempo map
+    pub fn get_tempo_map(&self) -> TempoMap {
+        self.tempo_map.lock().clone()
+    }
+
+    /// Replace the whole tempo map (project load)
+    pub fn set_tempo_map(&self, tempo_map: TempoMap) {
+        *self.tempo_map.lock() = tempo_map;
     }
 
     /// Enable/disable metronome
@@ -224,15 +438,53 @@ impl Recorder {
         self.metronome_enabled.load(Ordering::SeqCst)
     }
 
+    /// Set metronome clicks per beat (clamped to 1-4)
+    pub fn set_metronome_subdivision(&self, subdivision: u32) {
+        self.metronome_subdivision.store(subdivision.clamp(1, MAX_METRONOME_SUBDIVISION), Ordering::SeqCst);
+    }
+
+    /// Get metronome clicks per beat
+    pub fn get_metronome_subdivision(&self) -> u32 {
+        self.metronome_subdivision.load(Ordering::SeqCst)
+    }
+
+    /// Enable or disable the downbeat accent
+    pub fn set_metronome_accent_enabled(&self, enabled: bool) {
+        self.metronome_accent.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Check if the downbeat accent is enabled
+    pub fn is_metronome_accent_enabled(&self) -> bool {
+        self.metronome_accent.load(Ordering::SeqCst)
+    }
+
+    /// Replace the synthesized click with custom samples (None restores the synthesized click)
+    pub fn set_metronome_samples(&self, samples: Option<MetronomeSamples>) {
+        *self.metronome_samples.lock() = samples;
+    }
+
+    /// Source paths of the custom click samples (accent, normal), if loaded
+    pub fn get_metronome_sample_paths(&self) -> Option<(String, String)> {
+        self.metronome_samples
+            .lock()
+            .as_ref()
+            .map(|s| (s.accent.file_path.clone(), s.normal.file_path.clone()))
+    }
+
     /// Get recorded sample count
     pub fn get_recorded_sample_count(&self) -> usize {
         self.recorded_samples.lock().len()
     }
 
+    /// Get the number of interleaved channels in the recorded buffer
+    pub fn get_recorded_channels(&self) -> usize {
+        self.recorded_channels.load(Ordering::Relaxed).max(1)
+    }
+
     /// Get recorded duration in seconds
     pub fn get_recorded_duration(&self) -> f64 {
         let sample_count = self.get_recorded_sample_count();
-        let frame_count = sample_count / 2; // Stereo
+        let frame_count = sample_count / self.get_recorded_channels();
         frame_count as f64 / f64::from(TARGET_SAMPLE_RATE)
     }
 
@@ -240,12 +492,13 @@ impl Recorder {
     /// Returns a list of peak values suitable for UI display
     /// Each peak represents multiple samples averaged together
     pub fn get_recording_waveform(&self, num_peaks: usize) -> Vec<f32> {
+        let channels = self.get_recorded_channels();
         let samples = self.recorded_samples.lock();
         if samples.is_empty() || num_peaks == 0 {
             return Vec::new();
         }
 
-        let frame_count = samples.len() / 2; // Stereo interleaved
+        let frame_count = samples.len() / channels;
         let frames_per_peak = (frame_count / num_peaks).max(1);
         let mut peaks = Vec::with_capacity(num_peaks);
 
@@ -258,13 +511,8 @@ impl Recorder {
             }
 
             let mut max_amplitude: f32 = 0.0;
-            for frame in start_frame..end_frame {
-                let left = samples.get(frame * 2).copied().unwrap_or(0.0).abs();
-                let right = samples.get(frame * 2 + 1).copied().unwrap_or(0.0).abs();
-                let amplitude = left.max(right);
-                if amplitude > max_amplitude {
-                    max_amplitude = amplitude;
-                }
+            for &sample in &samples[start_frame * channels..end_frame * channels] {
+                max_amplitude = max_amplitude.max(sample.abs());
             }
             peaks.push(max_amplitude);
         }
@@ -289,16 +537,49 @@ impl Recorder {
         self.seek_cooldown.store(0, Ordering::SeqCst);
     }
 
-    /// Set time signature (beats per bar)
+    /// Set time signature (beats per bar, in quarter notes) at the timeline start;
+    /// later signature changes are kept
     pub fn set_time_signature(&self, beats_per_bar: u32) {
-        let mut ts = self.time_signature.lock();
-        *ts = beats_per_bar;
-        eprintln!("⏱️  [Recorder] Time signature set to {beats_per_bar}/4");
+        let signature = TimeSignature { numerator: beats_per_bar.max(1), denominator: 4 };
+        self.time_signature_map.lock().set_base_signature(signature);
+        eprintln!("⏱️  [Recorder] Time signature set to {}/4", signature.numerator);
     }
 
-    /// Get time signature (beats per bar)
+    /// Get time signature (beats per bar at the timeline start)
     pub fn get_time_signature(&self) -> u32 {
-        *self.time_signature.lock()
+        self.time_signature_map.lock().base_signature().numerator
+    }
+
+    /// Signature at the timeline start
+    pub fn get_base_time_signature(&self) -> TimeSignature {
+        self.time_signature_map.lock().base_signature()
+    }
+
+    /// Change the time signature from `bar` on (bar 0 sets the project signature)
+    pub fn add_time_signature_change(&self, bar: u32, signature: TimeSignature) {
+        self.time_signature_map.lock().add_change(bar, signature);
+    }
+
+    /// Remove the time-signature change at `bar`; returns false if there is none
+    pub fn remove_time_signature_change(&self, bar: u32) -> bool {
+        self.time_signature_map.lock().remove_change(bar)
+    }
+
+    /// Copy of the time-signature map
+    pub fn get_time_signature_map(&self) -> TimeSignatureMap {
+        self.time_signature_map.lock().clone()
+    }
+
+    /// Replace the whole time-signature map (project load)
+    pub fn set_time_signature_map(&self, time_signature_map: TimeSignatureMap) {
+        *self.time_signature_map.lock() = time_signature_map;
+    }
+
+    /// Signature of the bar playing at `seconds` (real time from the timeline start)
+    pub fn time_signature_at_seconds(&self, seconds: f64) -> TimeSignature {
+        let sample = (seconds.max(0.0) * f64::from(TARGET_SAMPLE_RATE)) as u64;
+        let beat = self.tempo_map.lock().beat_at_sample(sample);
+        self.time_signature_map.lock().position_at_beat(beat).signature
     }
 
     /// Set the timeline position (in seconds) where the recording should be placed
@@ -322,6 +603,41 @@ impl Recorder {
         self.count_in_progress.load(Ordering::Relaxed) as f32 / 10000.0
     }
 
+    /// Start a metronome count-in before playback
+    ///
+    /// The audio callback sets the done flag after `count_in_bars` bars; the transport
+    /// starts playing then. Returns false (nothing to count) when count-in is 0 bars.
+    pub fn start_playback_count_in(&self) -> Result<bool, String> {
+        if *self.state.lock() != RecordingState::Idle {
+            return Err("Cannot count in for playback while recording".to_string());
+        }
+        if self.get_count_in_bars() == 0 {
+            return Ok(false);
+        }
+
+        let resume = self.sample_counter.swap(0, Ordering::SeqCst);
+        self.playback_count_in_resume.store(resume, Ordering::SeqCst);
+        self.seek_cooldown.store(0, Ordering::SeqCst);
+        self.playback_count_in_done.store(false, Ordering::SeqCst);
+        self.playback_count_in.store(true, Ordering::SeqCst);
+        Ok(true)
+    }
+
+    /// Abort a playback count-in (transport stopped or paused before it finished)
+    pub fn cancel_playback_count_in(&self) {
+        if self.playback_count_in.swap(false, Ordering::SeqCst) {
+            self.sample_counter.store(self.playback_count_in_resume.load(Ordering::SeqCst), Ordering::SeqCst);
+            self.count_in_beat.store(0, Ordering::Relaxed);
+            self.count_in_progress.store(0, Ordering::Relaxed);
+        }
+        self.playback_count_in_done.store(false, Ordering::SeqCst);
+    }
+
+    /// Check if counting in before playback
+    pub fn is_playback_counting_in(&self) -> bool {
+        self.playback_count_in.load(Ordering::SeqCst)
+    }
+
     // ── Punch In/Out ──────────────────────────────────────────────
 
     pub fn set_punch_in_enabled(&self, enabled: bool) {
@@ -340,9 +656,21 @@ impl Recorder {
         self.punch_out_enabled.load(Ordering::SeqCst)
     }
 
-    pub fn set_punch_region(&self, in_seconds: f64, out_seconds: f64) {
+    /// Set the punch region. Rejects non-finite or negative bounds and a
+    /// punch-out that is not after the punch-in; the stored region is left
+    /// unchanged on error.
+    pub fn set_punch_region(&self, in_seconds: f64, out_seconds: f64) -> Result<(), String> {
+        if !in_seconds.is_finite() || !out_seconds.is_finite() || in_seconds < 0.0 {
+            return Err(format!("Invalid punch region: {in_seconds} - {out_seconds}"));
+        }
+        if out_seconds <= in_seconds {
+            return Err(format!(
+                "Punch-out ({out_seconds:.3}s) must be after punch-in ({in_seconds:.3}s)"
+            ));
+        }
         *self.punch_in_seconds.lock() = in_seconds;
         *self.punch_out_seconds.lock() = out_seconds;
+        Ok(())
     }
 
     pub fn get_punch_in_seconds(&self) -> f64 {
@@ -366,28 +694,80 @@ impl Recorder {
 pub struct RecorderCallbackRefs {
     pub state: Arc<Mutex<RecordingState>>,
     pub recorded_samples: Arc<Mutex<Vec<f32>>>,
+    pub recorded_channels: Arc<AtomicUsize>,
     pub sample_counter: Arc<AtomicU64>,
     pub count_in_bars: Arc<Mutex<u32>>,
-    pub tempo: Arc<Mutex<f64>>,
+    pub tempo_map: Arc<Mutex<TempoMap>>,
     pub metronome_enabled: Arc<AtomicBool>,
-    pub time_signature: Arc<Mutex<u32>>,
+    pub metronome_subdivision: Arc<AtomicU32>,
+    pub metronome_accent: Arc<AtomicBool>,
+    pub metronome_samples: Arc<Mutex<Option<MetronomeSamples>>>,
+    pub time_signature_map: Arc<Mutex<TimeSignatureMap>>,
     pub seek_cooldown: Arc<AtomicU64>,
     pub count_in_beat: Arc<AtomicU32>,
     pub count_in_progress: Arc<AtomicU32>,
+    pub playback_count_in: Arc<AtomicBool>,
+    pub playback_count_in_resume: Arc<AtomicU64>,
+    pub playback_count_in_done: Arc<AtomicBool>,
     pub punch_in_enabled: Arc<AtomicBool>,
     pub punch_out_enabled: Arc<AtomicBool>,
     pub punch_in_seconds: Arc<Mutex<f64>>,
     pub punch_out_seconds: Arc<Mutex<f64>>,
     pub punch_complete: Arc<AtomicBool>,
+    pub take_starts: Arc<Mutex<Vec<(usize, f64)>>>,
 }
 
 impl RecorderCallbackRefs {
+    /// Start a new take on loop wrap while recording
+    /// `loop_start_seconds` is the timeline position the next pass starts at
+    pub fn mark_loop_take(&self, loop_start_seconds: f64) {
+        if *self.state.lock() != RecordingState::Recording {
+            return;
+        }
+        let index = self.recorded_samples.lock().len();
+        let mut take_starts = self.take_starts.lock();
+        let last = take_starts.last().map_or(0, |&(last, _)| last);
+        if index > last {
+            take_starts.push((index, loop_start_seconds));
+        }
+    }
+
+    /// Sync metronome position from the audio thread (e.g. on loop wrap)
+    pub fn seek_metronome(&self, sample_position: u64) {
+        self.sample_counter.store(sample_position, Ordering::SeqCst);
+        self.seek_cooldown.store(0, Ordering::SeqCst);
+    }
+
+    /// Take the "playback count-in finished" signal (audio thread starts the transport)
+    pub fn take_playback_count_in_done(&self) -> bool {
+        self.playback_count_in_done.swap(false, Ordering::SeqCst)
+    }
+
+    /// Publish count-in beat/progress for the UI ring timer
+    /// Returns true (and clears them) once `sample_idx` reaches the end of the count-in
+    fn update_count_in(&self, sample_idx: u64, samples_per_beat: u64, samples_per_bar: u64) -> bool {
+        let count_in_bars = *self.count_in_bars.lock();
+        let count_in_samples = samples_per_bar * u64::from(count_in_bars);
+
+        let beat_in_bar = ((sample_idx % samples_per_bar) / samples_per_beat) as u32 + 1; // 1-indexed
+        let progress = (sample_idx as f64 / count_in_samples.max(1) as f64).min(1.0);
+        self.count_in_beat.store(beat_in_bar, Ordering::Relaxed);
+        self.count_in_progress.store((progress * 10000.0) as u32, Ordering::Relaxed);
+
+        if sample_idx >= count_in_samples {
+            self.count_in_beat.store(0, Ordering::Relaxed);
+            self.count_in_progress.store(0, Ordering::Relaxed);
+            return true;
+        }
+        false
+    }
+
     /// Process audio for recording and generate metronome
-    /// Returns metronome output (left, right) and updates recording state
+    /// `input` holds one sample per input channel; the channel count is fixed by the
+    /// first recorded frame. Returns metronome output (left, right) and updates recording state
     pub fn process_frame(
         &self,
-        input_left: f32,
-        input_right: f32,
+        input: &[f32],
         is_playing: bool,
         playhead_seconds: f64,
     ) -> (f32, f32) {
@@ -397,9 +777,11 @@ impl RecorderCallbackRefs {
             *state
         }; // Lock released here
 
-        // Only increment counter when playing or recording
+        let playback_count_in = self.playback_count_in.load(Ordering::SeqCst);
+
+        // Only increment counter when playing, recording or counting in for playback
         // This ensures metronome resets properly when stopped
-        let should_tick = is_playing || current_state != RecordingState::Idle;
+        let should_tick = is_playing || current_state != RecordingState::Idle || playback_count_in;
 
         let sample_idx = if should_tick {
             self.sample_counter.fetch_add(1, Ordering::SeqCst)
@@ -407,13 +789,19 @@ impl RecorderCallbackRefs {
             self.sample_counter.load(Ordering::SeqCst)
         };
 
-        let tempo = *self.tempo.lock();
-        let time_sig = *self.time_signature.lock();
+        let tempo_segment = self.tempo_map.lock().segment_at_sample(sample_idx);
+        let beat = tempo_segment.beat_at_sample(sample_idx);
+        let bar = self.time_signature_map.lock().position_at_beat(beat);
         let metronome_enabled = self.metronome_enabled.load(Ordering::SeqCst);
 
-        // Calculate beat information
-        let samples_per_beat = (60.0 / tempo * f64::from(TARGET_SAMPLE_RATE)) as u64;
-        let samples_per_bar = samples_per_beat * u64::from(time_sig);
+        // Calculate beat information at the tempo and signature in effect here
+        // (a metronome beat is one note of the signature's denominator, e.g. an eighth in 6/8)
+        let quarter_notes_per_beat = bar.signature.quarter_notes_per_beat();
+        let samples_per_beat = (tempo_segment.samples_per_beat() * quarter_notes_per_beat) as u64;
+        let samples_per_bar = samples_per_beat * u64::from(bar.signature.numerator);
+        let beats_into_bar = (beat - bar.start_beat) / quarter_notes_per_beat;
+        let position_in_bar = beats_into_bar.floor() as u64 * samples_per_beat
+            + (beats_into_bar.fract() * samples_per_beat as f64) as u64;
 
         // Check and decrement seek cooldown (prevents click overlap on short loops)
         let cooldown = self.seek_cooldown.load(Ordering::SeqCst);
@@ -426,39 +814,30 @@ impl RecorderCallbackRefs {
 
         // Only generate click if not in cooldown period (prevents overlapping clicks after seek)
         if metronome_enabled && cooldown == 0 {
-            let position_in_bar = sample_idx % samples_per_bar;
-            let beat_in_bar = position_in_bar / samples_per_beat;
-            let position_in_beat = position_in_bar % samples_per_beat;
-
-            // Generate click (short sine burst)
-            if position_in_beat < 4000 { // ~80ms click at 48kHz (increased from 40ms for better audibility)
-                let t = position_in_beat as f32 / TARGET_SAMPLE_RATE as f32;
-                let freq = if beat_in_bar == 0 { 1200.0 } else { 800.0 }; // Higher pitch on downbeat
-                let envelope = (1.0 - (position_in_beat as f32 / 4000.0)).powi(2);
-                metronome_output = (2.0 * PI * freq * t).sin() * 0.6 * envelope;
-            }
+            metronome_output = metronome_click(
+                position_in_bar,
+                samples_per_beat,
+                self.metronome_subdivision.load(Ordering::Relaxed),
+                self.metronome_accent.load(Ordering::Relaxed),
+                self.metronome_samples.lock().as_ref(),
+            );
         }
 
         // Read punch state (atomics are lock-free)
         let punch_in = self.punch_in_enabled.load(Ordering::SeqCst);
         let punch_out = self.punch_out_enabled.load(Ordering::SeqCst);
 
+        // Playback count-in: metronome only, then hand over to the transport
+        if playback_count_in && self.update_count_in(sample_idx, samples_per_beat, samples_per_bar) {
+            self.playback_count_in.store(false, Ordering::SeqCst);
+            self.sample_counter.store(self.playback_count_in_resume.load(Ordering::SeqCst), Ordering::SeqCst);
+            self.playback_count_in_done.store(true, Ordering::SeqCst);
+        }
+
         // Handle count-in and recording state transitions
         match current_state {
             RecordingState::CountingIn => {
-                let count_in_bars = *self.count_in_bars.lock();
-                let count_in_samples = samples_per_bar * u64::from(count_in_bars);
-
-                // Calculate and store beat/progress for UI ring timer
-                let beat_in_bar = ((sample_idx % samples_per_bar) / samples_per_beat) as u32 + 1; // 1-indexed
-                let progress = (sample_idx as f64 / count_in_samples.max(1) as f64).min(1.0);
-                self.count_in_beat.store(beat_in_bar, Ordering::Relaxed);
-                self.count_in_progress.store((progress * 10000.0) as u32, Ordering::Relaxed);
-
-                if sample_idx >= count_in_samples {
-                    self.count_in_beat.store(0, Ordering::Relaxed);
-                    self.count_in_progress.store(0, Ordering::Relaxed);
-
+                if self.update_count_in(sample_idx, samples_per_beat, samples_per_bar) {
                     if punch_in {
                         // Punch-in enabled: wait for playhead to reach region start
                         let punch_in_s = *self.punch_in_seconds.lock();
@@ -518,13 +897,19 @@ impl RecorderCallbackRefs {
 
                 // Record input samples
                 { let mut samples = self.recorded_samples.lock();
-                    samples.push(input_left);
-                    samples.push(input_right);
+                    if samples.is_empty() {
+                        self.recorded_channels.store(input.len().max(1), Ordering::Relaxed);
+                    }
+                    let channels = self.recorded_channels.load(Ordering::Relaxed);
+                    for channel in 0..channels {
+                        samples.push(input.get(channel).copied().unwrap_or(0.0));
+                    }
 
                     // Log every second of recording
-                    if samples.len().is_multiple_of(96000) {
-                        eprintln!("🎙️  [Recorder] Recording... {} samples ({:.1}s)",
-                            samples.len(), samples.len() as f32 / (TARGET_SAMPLE_RATE as f32 * 2.0));
+                    let frames = samples.len() / channels;
+                    if frames.is_multiple_of(TARGET_SAMPLE_RATE as usize) {
+                        eprintln!("🎙️  [Recorder] Recording... {} frames ({:.1}s)",
+                            frames, frames as f32 / TARGET_SAMPLE_RATE as f32);
                     }
                 }
             }
@@ -572,6 +957,39 @@ mod tests {
         assert_eq!(recorder.get_state(), RecordingState::CountingIn);
     }
 
+    #[test]
+    fn test_playback_count_in() {
+        let recorder = Recorder::new();
+        recorder.set_count_in_bars(1);
+        recorder.seek_metronome(1234);
+        let refs = recorder.get_callback_refs();
+
+        assert!(recorder.start_playback_count_in().unwrap());
+        assert!(recorder.is_playback_counting_in());
+
+        // 120 BPM 4/4: one bar is 96000 samples
+        refs.process_frame(&[0.0, 0.0], false, 0.0);
+        assert_eq!(recorder.get_count_in_beat(), 1);
+        for _ in 0..48_000 {
+            refs.process_frame(&[0.0, 0.0], false, 0.0);
+        }
+        assert_eq!(recorder.get_count_in_beat(), 3);
+        assert!(!refs.take_playback_count_in_done());
+
+        for _ in 0..48_000 {
+            refs.process_frame(&[0.0, 0.0], false, 0.0);
+        }
+        assert!(!recorder.is_playback_counting_in());
+        assert!(refs.take_playback_count_in_done());
+        assert_eq!(recorder.get_count_in_beat(), 0);
+        // Metronome resumes where the transport was
+        assert_eq!(recorder.sample_counter.load(Ordering::SeqCst), 1234);
+
+        // No count-in configured: play immediately
+        recorder.set_count_in_bars(0);
+        assert!(!recorder.start_playback_count_in().unwrap());
+    }
+
     #[test]
     fn test_tempo() {
         let recorder = Recorder::new();
@@ -585,6 +1003,47 @@ mod tests {
         assert!((recorder.get_tempo() - 20.0).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_metronome_follows_tempo_changes() {
+        // Two beats at 120 BPM (24000 samples each), then 240 BPM (12000 samples each)
+        let recorder = Recorder::new();
+        recorder.add_tempo_change(2.0, 240.0);
+        let refs = recorder.get_callback_refs();
+        let output: Vec<f32> = (0..80_000).map(|_| refs.process_frame(&[0.0], true, 0.0).0).collect();
+        let peak = |range: std::ops::Range<usize>| output[range].iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+
+        for beat_start in [0, 24_000, 48_000, 60_000, 72_000] {
+            assert!(peak(beat_start..beat_start + 200) > 0.1, "click at {beat_start}");
+        }
+        // Halfway through the fast beats is silent
+        assert!(peak(53_000..55_000) < 1e-6);
+        assert!(peak(65_000..67_000) < 1e-6);
+    }
+
+    #[test]
+    fn test_metronome_downbeat_follows_time_signature_changes() {
+        // One bar of 4/4 at 120 BPM (96000 samples), then 6/8: eighth-note clicks
+        // (12000 samples), six to a bar, so the next downbeats are 72000 samples apart
+        let recorder = Recorder::new();
+        recorder.add_time_signature_change(1, TimeSignature::new(6, 8).unwrap());
+        let refs = recorder.get_callback_refs();
+        let output: Vec<f32> = (0..180_000).map(|_| refs.process_frame(&[0.0], true, 0.0).0).collect();
+        // The accented click is pitched higher, so it crosses zero more often
+        let crossings = |start: usize| output[start..start + 400].windows(2).filter(|w| w[0] * w[1] < 0.0).count();
+
+        let downbeat = crossings(0);
+        let normal = crossings(24_000);
+        assert!(downbeat > normal);
+        for start in [96_000, 168_000] {
+            assert_eq!(crossings(start), downbeat, "downbeat at {start}");
+        }
+        for start in [108_000, 120_000, 132_000, 156_000] {
+            assert_eq!(crossings(start), normal, "beat at {start}");
+        }
+        // Halfway between eighth-note clicks is silent
+        assert!(output[102_000..104_000].iter().all(|s| s.abs() < 1e-6));
+    }
+
     #[test]
     fn test_metronome_toggle() {
         let recorder = Recorder::new();
@@ -594,6 +1053,63 @@ mod tests {
         assert!(!recorder.is_metronome_enabled());
     }
 
+    #[test]
+    fn test_metronome_subdivision_and_accent() {
+        let beat = 24_000; // 120 BPM
+        let peak = |start: u64, subdivision: u32, accent: bool| {
+            (start..start + 2000)
+                .map(|p| metronome_click(p, beat, subdivision, accent, None).abs())
+                .fold(0.0f32, f32::max)
+        };
+
+        // No click halfway through a beat unless subdivided
+        assert!(peak(12_000, 1, true) < 1e-6);
+        let sub_click = peak(12_000, 2, true);
+        assert!(sub_click > 0.1 && sub_click < peak(beat, 2, true));
+
+        // Triplets click every third of a beat
+        assert!(peak(8_000, 3, true) > 0.1);
+
+        // Downbeat is pitched differently only when accented
+        let downbeat = |accent| metronome_click(10, beat, 1, accent, None);
+        assert!((downbeat(true) - downbeat(false)).abs() > 1e-3);
+        assert!((downbeat(false) - metronome_click(beat + 10, beat, 1, true, None)).abs() < 1e-6);
+
+        let recorder = Recorder::new();
+        recorder.set_metronome_subdivision(9);
+        assert_eq!(recorder.get_metronome_subdivision(), MAX_METRONOME_SUBDIVISION);
+        recorder.set_metronome_subdivision(0);
+        assert_eq!(recorder.get_metronome_subdivision(), 1);
+    }
+
+    #[test]
+    fn test_metronome_custom_samples() {
+        let clip = |value: f32| Arc::new(AudioClip {
+            samples: vec![value; 200],
+            channels: 2,
+            sample_rate: TARGET_SAMPLE_RATE,
+            duration_seconds: 100.0 / f64::from(TARGET_SAMPLE_RATE),
+            file_path: String::new(),
+            stream: None,
+        });
+        let samples = MetronomeSamples { accent: clip(0.9), normal: clip(0.4) };
+        let beat = 24_000;
+
+        assert!((metronome_click(10, beat, 1, true, Some(&samples)) - 0.9).abs() < 1e-6);
+        assert!((metronome_click(10, beat, 1, false, Some(&samples)) - 0.4).abs() < 1e-6);
+        assert!((metronome_click(beat + 10, beat, 1, true, Some(&samples)) - 0.4).abs() < 1e-6);
+        assert!((metronome_click(12_010, beat, 2, true, Some(&samples)) - 0.4 * SUBDIVISION_CLICK_LEVEL).abs() < 1e-6);
+        // Silent once the sample has finished
+        assert!(metronome_click(150, beat, 1, true, Some(&samples)).abs() < 1e-6);
+
+        let recorder = Recorder::new();
+        assert!(recorder.get_metronome_sample_paths().is_none());
+        recorder.set_metronome_samples(Some(samples));
+        assert!(recorder.get_metronome_sample_paths().is_some());
+        recorder.set_metronome_samples(None);
+        assert!(recorder.get_metronome_sample_paths().is_none());
+    }
+
     // ── Punch tests ────────────────────────────────────────────
 
     #[test]
@@ -607,17 +1123,68 @@ mod tests {
     #[test]
     fn test_punch_region_setters() {
         let recorder = Recorder::new();
-        recorder.set_punch_region(5.0, 10.0);
+        recorder.set_punch_region(5.0, 10.0).unwrap();
         assert!((recorder.get_punch_in_seconds() - 5.0).abs() < f64::EPSILON);
         assert!((recorder.get_punch_out_seconds() - 10.0).abs() < f64::EPSILON);
     }
 
+    #[test]
+    fn test_punch_region_rejects_invalid_ranges() {
+        let recorder = Recorder::new();
+        recorder.set_punch_region(5.0, 10.0).unwrap();
+
+        assert!(recorder.set_punch_region(3.0, 3.0).is_err(), "start == end");
+        assert!(recorder.set_punch_region(8.0, 4.0).is_err(), "start > end");
+        assert!(recorder.set_punch_region(-1.0, 4.0).is_err(), "negative start");
+        assert!(recorder.set_punch_region(f64::NAN, 4.0).is_err(), "NaN start");
+        assert!(recorder.set_punch_region(1.0, f64::INFINITY).is_err(), "infinite end");
+
+        // Rejected ranges leave the previous region in place
+        assert!((recorder.get_punch_in_seconds() - 5.0).abs() < f64::EPSILON);
+        assert!((recorder.get_punch_out_seconds() - 10.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_punch_mode_flags_toggle() {
+        let recorder = Recorder::new();
+        recorder.set_punch_in_enabled(true);
+        assert!(recorder.is_punch_in_enabled());
+        assert!(!recorder.is_punch_out_enabled());
+
+        recorder.set_punch_out_enabled(true);
+        recorder.set_punch_in_enabled(false);
+        assert!(!recorder.is_punch_in_enabled());
+        assert!(recorder.is_punch_out_enabled());
+    }
+
+    #[test]
+    fn test_punch_region_ignored_when_flags_off() {
+        let recorder = Recorder::new();
+        recorder.set_count_in_bars(0);
+        recorder.set_punch_region(1.0, 2.0).unwrap();
+
+        // No punch-in: recording starts immediately instead of waiting
+        recorder.start_recording().unwrap();
+        assert_eq!(recorder.get_state(), RecordingState::Recording);
+
+        let refs = recorder.get_callback_refs();
+        refs.process_frame(&[0.5, 0.5], true, 0.5);
+        refs.process_frame(&[0.5, 0.5], true, 1.5);
+        // No punch-out: passing the region end keeps recording
+        refs.process_frame(&[0.5, 0.5], true, 2.5);
+        assert_eq!(recorder.get_state(), RecordingState::Recording);
+        assert!(!recorder.is_punch_complete());
+
+        let clip = recorder.stop_recording().unwrap().expect("clip");
+        assert_eq!(clip.samples.len(), 6, "frames outside the region are captured");
+    }
+
     #[test]
     fn test_punch_in_starts_waiting() {
         let recorder = Recorder::new();
         recorder.set_count_in_bars(0);
         recorder.set_punch_in_enabled(true);
-        recorder.set_punch_region(5.0, 10.0);
+        recorder.set_punch_region(5.0, 10.0).unwrap();
 
         assert!(recorder.start_recording().is_ok());
         assert_eq!(recorder.get_state(), RecordingState::WaitingForPunchIn);
@@ -628,7 +1195,7 @@ mod tests {
         let recorder = Recorder::new();
         recorder.set_count_in_bars(0);
         recorder.set_punch_in_enabled(true);
-        recorder.set_punch_region(5.0, 10.0);
+        recorder.set_punch_region(5.0, 10.0).unwrap();
 
         recorder.start_recording().unwrap();
         assert_eq!(recorder.get_state(), RecordingState::WaitingForPunchIn);
@@ -642,7 +1209,7 @@ mod tests {
         let recorder = Recorder::new();
         recorder.set_count_in_bars(0);
         recorder.set_punch_in_enabled(true);
-        recorder.set_punch_region(0.01, 10.0); // punch-in at 0.01s
+        recorder.set_punch_region(0.01, 10.0).unwrap(); // punch-in at 0.01s
 
         recorder.start_recording().unwrap();
         assert_eq!(recorder.get_state(), RecordingState::WaitingForPunchIn);
@@ -650,12 +1217,12 @@ mod tests {
         let refs = recorder.get_callback_refs();
         // Feed frames with playhead before punch-in
         for _ in 0..100 {
-            refs.process_frame(0.5, 0.5, true, 0.005);
+            refs.process_frame(&[0.5, 0.5], true, 0.005);
         }
         assert_eq!(recorder.get_state(), RecordingState::WaitingForPunchIn);
 
         // Feed frame at punch-in point
-        refs.process_frame(0.5, 0.5, true, 0.01);
+        refs.process_frame(&[0.5, 0.5], true, 0.01);
         assert_eq!(recorder.get_state(), RecordingState::Recording);
     }
 
@@ -664,7 +1231,7 @@ mod tests {
         let recorder = Recorder::new();
         recorder.set_count_in_bars(0);
         recorder.set_punch_out_enabled(true);
-        recorder.set_punch_region(0.0, 0.01); // punch-out at 0.01s
+        recorder.set_punch_region(0.0, 0.01).unwrap(); // punch-out at 0.01s
 
         recorder.start_recording().unwrap();
         assert_eq!(recorder.get_state(), RecordingState::Recording);
@@ -672,13 +1239,13 @@ mod tests {
         let refs = recorder.get_callback_refs();
         // Record a few frames before punch-out
         for _ in 0..100 {
-            refs.process_frame(0.5, 0.5, true, 0.005);
+            refs.process_frame(&[0.5, 0.5], true, 0.005);
         }
         assert_eq!(recorder.get_state(), RecordingState::Recording);
         assert!(!recorder.is_punch_complete());
 
         // Feed frame at punch-out point
-        refs.process_frame(0.5, 0.5, true, 0.01);
+        refs.process_frame(&[0.5, 0.5], true, 0.01);
         assert_eq!(recorder.get_state(), RecordingState::Idle);
         assert!(recorder.is_punch_complete());
     }
@@ -689,7 +1256,7 @@ mod tests {
         recorder.set_count_in_bars(0);
         recorder.set_punch_in_enabled(true);
         recorder.set_punch_out_enabled(true);
-        recorder.set_punch_region(1.0, 2.0);
+        recorder.set_punch_region(1.0, 2.0).unwrap();
 
         recorder.start_recording().unwrap();
         assert_eq!(recorder.get_state(), RecordingState::WaitingForPunchIn);
@@ -697,19 +1264,19 @@ mod tests {
         let refs = recorder.get_callback_refs();
 
         // Before punch-in: waiting
-        refs.process_frame(0.1, 0.1, true, 0.5);
+        refs.process_frame(&[0.1, 0.1], true, 0.5);
         assert_eq!(recorder.get_state(), RecordingState::WaitingForPunchIn);
 
         // At punch-in: starts recording
-        refs.process_frame(0.1, 0.1, true, 1.0);
+        refs.process_frame(&[0.1, 0.1], true, 1.0);
         assert_eq!(recorder.get_state(), RecordingState::Recording);
 
         // During recording
-        refs.process_frame(0.5, 0.5, true, 1.5);
+        refs.process_frame(&[0.5, 0.5], true, 1.5);
         assert_eq!(recorder.get_state(), RecordingState::Recording);
 
         // At punch-out: auto-stops
-        refs.process_frame(0.5, 0.5, true, 2.0);
+        refs.process_frame(&[0.5, 0.5], true, 2.0);
         assert_eq!(recorder.get_state(), RecordingState::Idle);
         assert!(recorder.is_punch_complete());
 
@@ -719,5 +1286,71 @@ mod tests {
         assert!(clip.is_some(), "Should return recorded audio after auto-punch-out");
         assert!(!clip.unwrap().samples.is_empty());
     }
-}
 
+    #[test]
+    fn test_latency_compensation_moves_takes_earlier() {
+        let record_take = |start_seconds: f64| {
+            let recorder = Recorder::new();
+            recorder.set_count_in_bars(0);
+            recorder.set_metronome_enabled(false);
+            recorder.set_recording_start_seconds(start_seconds);
+            recorder.start_recording().unwrap();
+            let refs = recorder.get_callback_refs();
+            for i in 0..4800 {
+                let level = if i == 2400 { 1.0 } else { 0.0 };
+                refs.process_frame(&[level, level], true, 0.0);
+            }
+            recorder.stop_recording_takes().unwrap().remove(0)
+        };
+
+        // 10 ms of round trip: the take moves 10 ms earlier, audio untouched
+        let mut take = record_take(1.0);
+        take.compensate_latency(480);
+        assert!((take.start_seconds - 0.99).abs() < 1e-9);
+        assert_eq!(take.clip.frame_count(), 4800);
+
+        // Near the start of the timeline the audio that would land before 0 is cut,
+        // keeping the transient where it was played
+        let mut take = record_take(0.004);
+        take.compensate_latency(480);
+        assert!(take.start_seconds.abs() < 1e-9);
+        assert_eq!(take.clip.frame_count(), 4800 - 288);
+        let transient = take.clip.samples.iter().position(|&s| s > 0.5).unwrap() / 2;
+        assert_eq!(transient, 2400 - 288);
+        assert!((take.clip.duration_seconds - (4800.0 - 288.0) / 48000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_loop_recording_splits_takes() {
+        let recorder = Recorder::new();
+        recorder.set_count_in_bars(0);
+        recorder.set_metronome_enabled(false);
+        recorder.set_recording_start_seconds(1.5);
+        recorder.start_recording().unwrap();
+        let refs = recorder.get_callback_refs();
+
+        // First pass starts mid-loop, second is a full pass, third is stopped early
+        for _ in 0..100 { refs.process_frame(&[0.1, 0.1], true, 0.0); }
+        refs.mark_loop_take(1.0);
+        for _ in 0..200 { refs.process_frame(&[0.2, 0.2], true, 0.0); }
+        refs.mark_loop_take(1.0);
+        refs.mark_loop_take(1.0); // repeated wrap without new audio adds no empty take
+        for _ in 0..50 { refs.process_frame(&[0.3, 0.3], true, 0.0); }
+
+        let takes = recorder.stop_recording_takes().unwrap();
+        assert_eq!(takes.len(), 3);
+        assert_eq!(takes.iter().map(|t| t.clip.frame_count()).collect::<Vec<_>>(), vec![100, 200, 50]);
+        assert!((takes[0].start_seconds - 1.5).abs() < 1e-9);
+        assert!((takes[1].start_seconds - 1.0).abs() < 1e-9);
+        assert!((takes[2].clip.samples[0] - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_loop_take_ignored_when_not_recording() {
+        let recorder = Recorder::new();
+        let refs = recorder.get_callback_refs();
+        refs.mark_loop_take(0.0);
+        assert!(recorder.take_starts.lock().is_empty());
+        assert!(recorder.stop_recording_takes().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+/// Reference track for A/B comparison against the master bus
+///
+/// A loaded reference plays in sync with the transport (from the start of the
+/// timeline, at its own speed). While enabled it replaces the master output; the mix
+/// keeps running underneath and the master meters keep following it, so flipping
+/// between the two is instant and the mix's loudness can still be read.
+use crate::audio_file::AudioClip;
+use crate::loudness::LoudnessMeter;
+use std::sync::Arc;
+
+/// Allowed reference gain range (dB)
+pub const MIN_REFERENCE_GAIN_DB: f32 = -48.0;
+pub const MAX_REFERENCE_GAIN_DB: f32 = 24.0;
+
+/// Reference playback state, shared between the API and the audio callback
+pub struct ReferenceTrack {
+    clip: Option<Arc<AudioClip>>,
+    /// Integrated loudness of `clip` (LUFS), measured when it was loaded
+    loudness_lufs: f32,
+    enabled: bool,
+    gain_db: f32,
+    gain: f32,
+}
+
+impl Default for ReferenceTrack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReferenceTrack {
+    pub fn new() -> Self {
+        Self {
+            clip: None,
+            loudness_lufs: 0.0,
+            enabled: false,
+            gain_db: 0.0,
+            gain: 1.0,
+        }
+    }
+
+    /// Load a reference (at the engine rate); keeps the current gain and A/B state
+    pub fn load(&mut self, clip: Arc<AudioClip>) {
+        self.loudness_lufs = LoudnessMeter::measure_integrated(&clip.to_stereo().samples);
+        self.clip = Some(clip);
+    }
+
+    /// Remove the reference; the master is heard again
+    pub fn clear(&mut self) {
+        self.clip = None;
+        self.enabled = false;
+    }
+
+    pub fn clip(&self) -> Option<&Arc<AudioClip>> {
+        self.clip.as_ref()
+    }
+
+    /// Integrated loudness of the loaded reference (LUFS)
+    pub fn loudness_lufs(&self) -> Option<f32> {
+        self.clip.as_ref().map(|_| self.loudness_lufs)
+    }
+
+    /// Hear the reference (true) or the master (false)
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Whether the reference replaces the master output
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.clip.is_some()
+    }
+
+    /// Set the reference playback gain (dB, clamped to the allowed range)
+    pub fn set_gain_db(&mut self, gain_db: f32) {
+        self.gain_db = gain_db.clamp(MIN_REFERENCE_GAIN_DB, MAX_REFERENCE_GAIN_DB);
+        self.gain = 10f32.powf(self.gain_db / 20.0);
+    }
+
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// Set the gain that brings the reference to `master_lufs`; returns the gain (dB)
+    /// None without a loaded reference or when either side is silent
+    pub fn match_loudness(&mut self, master_lufs: f32) -> Option<f32> {
+        let reference_lufs = self.loudness_lufs()?;
+        let floor = crate::loudness::LOUDNESS_FLOOR_LUFS;
+        if master_lufs <= floor || reference_lufs <= floor {
+            return None;
+        }
+        self.set_gain_db(master_lufs - reference_lufs);
+        Some(self.gain_db)
+    }
+
+    /// Reference output at `frame` (engine-rate frames since the timeline start), with gain
+    pub fn frame(&self, frame: u64) -> (f32, f32) {
+        let Some(clip) = &self.clip else {
+            return (0.0, 0.0);
+        };
+        let Ok(frame) = usize::try_from(frame) else {
+            return (0.0, 0.0);
+        };
+        if frame >= clip.frame_count() {
+            return (0.0, 0.0);
+        }
+        let left = clip.get_sample(frame, 0).unwrap_or(0.0);
+        let right = clip.get_sample(frame, 1).unwrap_or(left);
+        (left * self.gain, right * self.gain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_file::TARGET_SAMPLE_RATE;
+
+    fn sine_clip(amplitude: f32) -> Arc<AudioClip> {
+        let frames = TARGET_SAMPLE_RATE as usize * 2;
+        let samples = (0..frames)
+            .flat_map(|i| {
+                let sample = (i as f32 * 1000.0 * std::f32::consts::TAU / TARGET_SAMPLE_RATE as f32).sin() * amplitude;
+                [sample, sample]
+            })
+            .collect();
+        Arc::new(AudioClip {
+            samples,
+            channels: 2,
+            sample_rate: TARGET_SAMPLE_RATE,
+            duration_seconds: 2.0,
+            file_path: "reference.wav".to_string(),
+            stream: None,
+        })
+    }
+
+    #[test]
+    fn test_reference_plays_only_when_enabled_and_matches_loudness() {
+        let mut reference = ReferenceTrack::new();
+        reference.set_enabled(true);
+        assert!(!reference.is_active(), "nothing to play without a clip");
+
+        reference.load(sine_clip(0.5));
+        assert!(reference.is_active());
+        let peak = (0..1000).map(|f| reference.frame(f).0.abs()).fold(0.0, f32::max);
+        assert!((peak - 0.5).abs() < 0.01);
+        assert_eq!(reference.frame(10 * u64::from(TARGET_SAMPLE_RATE)), (0.0, 0.0));
+
+        // A reference 6 dB louder than the mix is turned down 6 dB
+        let reference_lufs = reference.loudness_lufs().unwrap();
+        let gain_db = reference.match_loudness(reference_lufs - 6.0).unwrap();
+        assert!((gain_db + 6.0).abs() < 1e-3);
+        let peak = (0..1000).map(|f| reference.frame(f).0.abs()).fold(0.0, f32::max);
+        assert!((peak - 0.25).abs() < 0.01);
+
+        reference.set_enabled(false);
+        assert!(!reference.is_active());
+    }
+}
@@ -4,6 +4,7 @@
 /// Supports loop mode (sustain-loop) and one-shot mode (default)
 use std::sync::Arc;
 use crate::audio_file::AudioClip;
+use crate::midi::VelocityCurve;
 
 const MAX_VOICES: usize = 8;
 
@@ -34,6 +35,79 @@ impl Default for SamplerEnvelope {
     }
 }
 
+// ============================================================================
+// INTERPOLATION
+// ============================================================================
+
+/// Sample interpolation used when pitching samples
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InterpolationMode {
+    /// Two-point linear interpolation (cheap, aliases on large upward shifts)
+    #[default]
+    Linear,
+    /// Windowed-sinc interpolation with anti-aliasing cutoff
+    Sinc,
+}
+
+impl InterpolationMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "linear" | "0" => Some(Self::Linear),
+            "sinc" | "1" => Some(Self::Sinc),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Sinc => "sinc",
+        }
+    }
+}
+
+/// Zero crossings on each side of the sinc kernel at unity playback rate
+const SINC_HALF_TAPS: usize = 8;
+/// Highest playback rate the kernel widens for (bounds CPU on extreme shifts)
+const SINC_MAX_RATE: f64 = 4.0;
+
+/// Windowed-sinc read of one channel at a fractional frame position.
+/// The cutoff drops to `1 / playback_rate` so upward shifts don't alias.
+fn sinc_interpolate(sample: &AudioClip, channel: usize, position: f64, playback_rate: f64) -> f32 {
+    let stretch = playback_rate.abs().clamp(1.0, SINC_MAX_RATE);
+    let cutoff = 1.0 / stretch;
+    let half_width = SINC_HALF_TAPS as f64 * stretch;
+    let frame_count = sample.frame_count() as i64;
+
+    let first = (position - half_width).ceil() as i64;
+    let last = (position + half_width).floor() as i64;
+
+    let mut sum = 0.0;
+    let mut weight_sum = 0.0;
+    for n in first.max(0)..=last.min(frame_count - 1) {
+        let x = position - n as f64;
+        let sinc = if x.abs() < 1e-9 {
+            1.0
+        } else {
+            let px = std::f64::consts::PI * x * cutoff;
+            px.sin() / px
+        };
+        // Hann window across the kernel span
+        let window = 0.5 + 0.5 * (std::f64::consts::PI * x / half_width).cos();
+        let weight = sinc * window;
+        sum += weight * f64::from(sample.get_sample(n as usize, channel).unwrap_or(0.0));
+        weight_sum += weight;
+    }
+
+    // Normalise to unity DC gain (also keeps edges from dipping)
+    if weight_sum.abs() > 1e-9 {
+        (sum / weight_sum) as f32
+    } else {
+        0.0
+    }
+}
+
 // ============================================================================
 // SAMPLER VOICE
 // ============================================================================
@@ -49,6 +123,7 @@ struct SamplerVoice {
     env_time: f32,
     release_start_level: f32, // Level when release started (for smooth fade)
     is_active: bool,
+    zone: Option<usize>,      // Index into `Sampler::zones` (None = main sample)
 }
 
 impl SamplerVoice {
@@ -63,6 +138,7 @@ impl SamplerVoice {
             env_time: 0.0,
             release_start_level: 0.0,
             is_active: false,
+            zone: None,
         }
     }
 
@@ -96,6 +172,7 @@ impl SamplerVoice {
         loop_start: f64,
         loop_end: f64,
         reversed: bool,
+        interpolation: InterpolationMode,
     ) -> (f32, f32) {
         if !self.is_active {
             return (0.0, 0.0);
@@ -151,8 +228,18 @@ impl SamplerVoice {
         let frame_i = frame_f as usize;
         let frac = (frame_f - frame_i as f64) as f32;
 
-        // Linear interpolation between samples
-        let (left, right) = if frame_i < frame_count {
+        let (left, right) = if frame_i >= frame_count {
+            (0.0, 0.0)
+        } else if interpolation == InterpolationMode::Sinc {
+            let left = sinc_interpolate(sample, 0, frame_f, self.playback_rate);
+            let right = if sample.channels > 1 {
+                sinc_interpolate(sample, 1, frame_f, self.playback_rate)
+            } else {
+                left
+            };
+            (left, right)
+        } else {
+            // Linear interpolation between samples
             let l0 = sample.get_sample(frame_i, 0).unwrap_or(0.0);
             let r0 = sample.get_sample(frame_i, 1).unwrap_or(l0);
 
@@ -162,8 +249,6 @@ impl SamplerVoice {
             let left = l0 + (l1 - l0) * frac;
             let right = r0 + (r1 - r0) * frac;
             (left, right)
-        } else {
-            (0.0, 0.0)
         };
 
         // Advance playback position (reversed = decrement)
@@ -231,13 +316,39 @@ impl SamplerVoice {
     }
 }
 
+// ============================================================================
+// SAMPLE ZONES (velocity-layered multisamples)
+// ============================================================================
+
+/// A sample mapped to a key and velocity range
+#[derive(Debug, Clone)]
+pub struct SampleZone {
+    pub root_note: u8,      // MIDI note that plays the zone's sample at original pitch
+    pub low_note: u8,       // Lowest note (inclusive)
+    pub high_note: u8,      // Highest note (inclusive)
+    pub low_velocity: u8,   // Lowest velocity (inclusive)
+    pub high_velocity: u8,  // Highest velocity (inclusive)
+    pub clip: Arc<AudioClip>,
+}
+
+impl SampleZone {
+    /// Check if the zone responds to a note at a velocity
+    pub fn contains(&self, note: u8, velocity: u8) -> bool {
+        (self.low_note..=self.high_note).contains(&note)
+            && (self.low_velocity..=self.high_velocity).contains(&velocity)
+    }
+}
+
 // ============================================================================
 // SAMPLER
 // ============================================================================
 
+#[derive(Clone)]
 pub struct Sampler {
     voices: Vec<SamplerVoice>,
     sample: Option<Arc<AudioClip>>,
+    /// Multisample zones; notes outside every zone fall back to `sample`
+    zones: Vec<SampleZone>,
     pub root_note: u8,           // MIDI note that plays sample at original pitch (default 60 = C4)
     pub envelope: SamplerEnvelope,
     pub loop_enabled: bool,      // false = one-shot (default), true = sustain-loop
@@ -254,6 +365,34 @@ pub struct Sampler {
     pub warp_mode: u8,            // 0=repitch, 1=warp (default 0)
     pub beats_per_bar: i32,       // Time signature numerator (default 4)
     pub beat_unit: i32,           // Time signature denominator (default 4)
+    pub interpolation_mode: InterpolationMode, // Linear (default) or sinc
+    pub velocity_curve: VelocityCurve, // Note velocity mapping (default linear)
+    project_tempo: f64,           // Current project tempo (drives warp stretch ratio)
+    stretched_sample: Option<Arc<AudioClip>>, // Pitch-preserved copy of `sample` (warp_mode=1)
+    cached_stretch_factor: f64,   // Stretch factor `stretched_sample` was built with
+}
+
+/// Warp mode audio for one sampler at a new tempo, stretched away from the synth
+/// manager lock (see `TrackSynthManager::warp_stretches_for_tempo`)
+pub struct WarpStretch {
+    pub track_id: u64,
+    source: Arc<AudioClip>,
+    factor: f64,
+    stretched: Option<Arc<AudioClip>>,
+}
+
+impl WarpStretch {
+    /// Run the time-stretch (slow: call it without holding any engine lock)
+    pub fn build(mut self) -> Self {
+        self.stretched = Some(crate::stretch::stretch_audio_preserve_pitch(&self.source, self.factor as f32));
+        self
+    }
+
+    /// The stretched audio, if it was built from `source` with `factor`
+    fn built_for(&self, source: &Arc<AudioClip>, factor: f64) -> Option<&Arc<AudioClip>> {
+        let matches = Arc::ptr_eq(&self.source, source) && (self.factor - factor).abs() <= 0.001;
+        self.stretched.as_ref().filter(|_| matches)
+    }
 }
 
 impl Sampler {
@@ -261,6 +400,7 @@ impl Sampler {
         Self {
             voices: (0..MAX_VOICES).map(|_| SamplerVoice::new()).collect(),
             sample: None,
+            zones: Vec::new(),
             root_note: 60, // C4
             envelope: SamplerEnvelope::default(),
             loop_enabled: false, // One-shot by default
@@ -276,6 +416,11 @@ impl Sampler {
             warp_mode: 0, // repitch
             beats_per_bar: 4,
             beat_unit: 4,
+            interpolation_mode: InterpolationMode::Linear,
+            velocity_curve: VelocityCurve::Linear,
+            project_tempo: 120.0,
+            stretched_sample: None,
+            cached_stretch_factor: 0.0,
         }
     }
 
@@ -290,6 +435,8 @@ impl Sampler {
         self.loop_end = clip.frame_count() as f64;
         self.loop_start = 0.0;
         self.sample = Some(clip);
+        self.stretched_sample = None;
+        self.rebuild_stretched_cache();
     }
 
     /// Load sample and set root note
@@ -298,14 +445,121 @@ impl Sampler {
         self.root_note = root_note;
     }
 
+    /// Add a multisample zone (silences playing voices so zone indices stay valid)
+    pub fn add_zone(&mut self, zone: SampleZone) {
+        println!("🎹 Sampler: Added zone '{}' notes {}-{} velocity {}-{} (root {})",
+            zone.clip.file_path,
+            note_name(zone.low_note), note_name(zone.high_note),
+            zone.low_velocity, zone.high_velocity,
+            note_name(zone.root_note)
+        );
+        self.all_notes_off();
+        self.zones.push(zone);
+    }
+
+    /// Remove all multisample zones
+    pub fn clear_zones(&mut self) {
+        self.all_notes_off();
+        self.zones.clear();
+    }
+
+    /// Get the multisample zones
+    pub fn zones(&self) -> &[SampleZone] {
+        &self.zones
+    }
+
+    /// Set the project tempo the warp stretch ratio is measured against
+    pub fn set_project_tempo(&mut self, bpm: f64) {
+        self.set_project_tempo_prestretched(bpm, None);
+    }
+
+    /// Same as `set_project_tempo`, installing `prestretched` as the Warp mode audio
+    /// if it was built for the current sample at this tempo (stretching here otherwise)
+    pub fn set_project_tempo_prestretched(&mut self, bpm: f64, prestretched: Option<&WarpStretch>) {
+        if bpm > 0.0 && (bpm - self.project_tempo).abs() > f64::EPSILON {
+            self.project_tempo = bpm;
+            self.update_stretched_cache(prestretched);
+        }
+    }
+
+    /// The Warp mode stretch a change to `bpm` needs, if any (not built yet)
+    pub fn warp_stretch_for_tempo(&self, track_id: u64, bpm: f64) -> Option<WarpStretch> {
+        if bpm <= 0.0 || (bpm - self.project_tempo).abs() <= f64::EPSILON {
+            return None;
+        }
+        let (true, 1, Some(sample)) = (self.warp_enabled, self.warp_mode, &self.sample) else {
+            return None;
+        };
+        let factor = self.stretch_factor_at(bpm);
+        let cached = self.stretched_sample.is_some() && (self.cached_stretch_factor - factor).abs() <= 0.001;
+        (!cached).then(|| WarpStretch { track_id, source: Arc::clone(sample), factor, stretched: None })
+    }
+
+    /// Tempo ratio used when warp is enabled (`project_tempo / original_bpm`)
+    pub fn stretch_factor(&self) -> f64 {
+        self.stretch_factor_at(self.project_tempo)
+    }
+
+    fn stretch_factor_at(&self, bpm: f64) -> f64 {
+        if self.original_bpm > 0.0 {
+            bpm / self.original_bpm
+        } else {
+            1.0
+        }
+    }
+
+    /// Rebuild the pitch-preserved sample for Warp mode (`warp_mode` = 1).
+    /// Call this when warp settings, `original_bpm`, the project tempo, or the sample change.
+    /// Zones always play unwarped.
+    fn rebuild_stretched_cache(&mut self) {
+        self.update_stretched_cache(None);
+    }
+
+    fn update_stretched_cache(&mut self, prestretched: Option<&WarpStretch>) {
+        use crate::stretch::stretch_audio_preserve_pitch;
+
+        let old_frames = self.playback_sample().map(|s| s.frame_count());
+        let factor = self.stretch_factor();
+
+        if let (true, 1, Some(sample)) = (self.warp_enabled, self.warp_mode, &self.sample) {
+            if self.stretched_sample.is_none() || (self.cached_stretch_factor - factor).abs() > 0.001 {
+                let stretched = match prestretched.and_then(|stretch| stretch.built_for(sample, factor)) {
+                    Some(stretched) => Arc::clone(stretched),
+                    None => stretch_audio_preserve_pitch(sample, factor as f32),
+                };
+                self.stretched_sample = Some(stretched);
+                self.cached_stretch_factor = factor;
+            }
+        } else {
+            self.stretched_sample = None;
+            self.cached_stretch_factor = 0.0;
+        }
+
+        // Keep playing voices at the same relative position in the new audio
+        let new_frames = self.playback_sample().map(|s| s.frame_count());
+        if let (Some(old), Some(new)) = (old_frames, new_frames) {
+            if old != new && old > 0 {
+                let scale = new as f64 / old as f64;
+                for voice in self.voices.iter_mut().filter(|v| v.zone.is_none()) {
+                    voice.playback_position *= scale;
+                }
+            }
+        }
+    }
+
+    /// Audio played for the main sample (stretched copy in Warp mode)
+    fn playback_sample(&self) -> Option<&Arc<AudioClip>> {
+        self.stretched_sample.as_ref().or(self.sample.as_ref())
+    }
+
     /// Get the loaded sample path (for serialization)
     pub fn sample_path(&self) -> Option<&str> {
         self.sample.as_ref().map(|s| s.file_path.as_str())
     }
 
-    /// Check if a sample is loaded
+    /// Check if a sample or any zone is loaded
     pub fn has_sample(&self) -> bool {
-        self.sample.is_some()
+        self.sample.is_some() || !self.zones.is_empty()
     }
 
     /// Get sample duration in seconds
@@ -338,20 +592,34 @@ impl Sampler {
     }
 
     pub fn note_on(&mut self, note: u8, velocity: u8) {
-        if self.sample.is_none() {
-            println!("⚠️ Sampler: No sample loaded, ignoring note_on");
+        // The curve also picks the velocity layer
+        let velocity = self.velocity_curve.apply(velocity);
+
+        // First zone whose key and velocity range match, else the main sample
+        let zone = self.zones.iter().position(|z| z.contains(note, velocity));
+        let (clip, root_note) = if let Some(i) = zone {
+            (&self.zones[i].clip, self.zones[i].root_note)
+        } else if let Some(sample) = self.playback_sample() {
+            (sample, self.root_note)
+        } else {
+            println!("⚠️ Sampler: No sample or zone for note {note}, ignoring note_on");
             return;
-        }
+        };
 
         // Calculate playback rate with transpose + fine cents offset
-        let semitone_diff = f64::from(note) - f64::from(self.root_note)
+        let semitone_diff = f64::from(note) - f64::from(root_note)
             + f64::from(self.transpose_semitones)
             + f64::from(self.fine_cents) / 100.0;
-        let playback_rate = 2.0_f64.powf(semitone_diff / 12.0);
+        let mut playback_rate = 2.0_f64.powf(semitone_diff / 12.0);
+
+        // Re-Pitch mode follows the project tempo by changing speed and pitch together
+        if zone.is_none() && self.warp_enabled && self.warp_mode == 0 {
+            playback_rate *= self.stretch_factor();
+        }
 
         // Determine start position (reversed starts from end)
         let start_pos = if self.reversed {
-            self.sample.as_ref().map_or(0.0, |s| s.frame_count() as f64 - 1.0)
+            clip.frame_count() as f64 - 1.0
         } else {
             0.0
         };
@@ -359,6 +627,7 @@ impl Sampler {
         // Find free voice or steal oldest
         let idx = self.find_free_voice_index();
         self.voices[idx].note_on(note, velocity, playback_rate, start_pos);
+        self.voices[idx].zone = zone;
     }
 
     pub fn note_off(&mut self, note: u8) {
@@ -383,19 +652,47 @@ impl Sampler {
 
     /// Process one sample frame, returning stereo output (left, right)
     pub fn process_sample(&mut self) -> (f32, f32) {
-        let Some(ref sample) = self.sample else {
+        self.render_frame(self.interpolation_mode)
+    }
+
+    /// `process_sample` for preview renders: always linear interpolation, skipping
+    /// the windowed-sinc resampler
+    pub fn process_sample_preview(&mut self) -> (f32, f32) {
+        self.render_frame(InterpolationMode::Linear)
+    }
+
+    fn render_frame(&mut self, interpolation: InterpolationMode) -> (f32, f32) {
+        if !self.has_sample() {
             return (0.0, 0.0);
-        };
+        }
 
         let mut left_out = 0.0;
         let mut right_out = 0.0;
 
         let loop_enabled = self.loop_enabled;
-        let loop_start = self.loop_start;
-        let loop_end = self.loop_end;
         let reversed = self.reversed;
 
+        // Loop points are stored in source frames; map them onto the stretched audio
+        let loop_scale = match (&self.stretched_sample, &self.sample) {
+            (Some(stretched), Some(sample)) if sample.frame_count() > 0 => {
+                stretched.frame_count() as f64 / sample.frame_count() as f64
+            }
+            _ => 1.0,
+        };
+        let main_sample = self.stretched_sample.as_ref().or(self.sample.as_ref());
+
         for voice in &mut self.voices {
+            // Zones loop their whole sample; loop points apply to the main sample
+            let (sample, loop_start, loop_end) = match voice.zone {
+                Some(i) => match self.zones.get(i) {
+                    Some(zone) => (&zone.clip, 0.0, zone.clip.frame_count() as f64),
+                    None => continue,
+                },
+                None => match main_sample {
+                    Some(sample) => (sample, self.loop_start * loop_scale, self.loop_end * loop_scale),
+                    None => continue,
+                },
+            };
             let (l, r) = voice.process(
                 sample,
                 &self.envelope,
@@ -404,6 +701,7 @@ impl Sampler {
                 loop_start,
                 loop_end,
                 reversed,
+                interpolation,
             );
             left_out += l;
             right_out += r;
@@ -495,17 +793,20 @@ impl Sampler {
                 if let Ok(v) = value.parse::<f64>() {
                     self.original_bpm = v.clamp(20.0, 999.0);
                     println!("  → original_bpm = {:.1}", self.original_bpm);
+                    self.rebuild_stretched_cache();
                 }
             }
             "warp_enabled" => {
                 let enabled = value == "1" || value == "true";
                 self.warp_enabled = enabled;
                 println!("  → warp_enabled = {}", self.warp_enabled);
+                self.rebuild_stretched_cache();
             }
             "warp_mode" => {
                 if let Ok(v) = value.parse::<u8>() {
                     self.warp_mode = v.min(1); // 0=repitch, 1=warp
                     println!("  → warp_mode = {}", self.warp_mode);
+                    self.rebuild_stretched_cache();
                 }
             }
             "beats_per_bar" => {
@@ -520,6 +821,18 @@ impl Sampler {
                     println!("  → beat_unit = {}", self.beat_unit);
                 }
             }
+            "interpolation_mode" => {
+                if let Some(mode) = InterpolationMode::from_name(value) {
+                    self.interpolation_mode = mode;
+                    println!("  → interpolation_mode = {}", mode.name());
+                }
+            }
+            "velocity_curve" => {
+                if let Some(curve) = VelocityCurve::from_name(value) {
+                    self.velocity_curve = curve;
+                    println!("  → velocity_curve = {}", curve.name());
+                }
+            }
             _ => {
                 println!("  ⚠️ Unknown sampler parameter: {key}");
             }
@@ -628,6 +941,24 @@ pub struct SamplerData {
     pub beats_per_bar: i32,
     #[serde(default = "default_beat_unit")]
     pub beat_unit: i32,
+    #[serde(default)]
+    pub interpolation_mode: InterpolationMode,
+    #[serde(default)]
+    pub velocity_curve: VelocityCurve,
+    /// Multisample zones (older projects have none)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub zones: Vec<ZoneData>,
+}
+
+/// Multisample zone data (sample is reloaded from `sample_path`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneData {
+    pub sample_path: String,
+    pub root_note: u8,
+    pub low_note: u8,
+    pub high_note: u8,
+    pub low_velocity: u8,
+    pub high_velocity: u8,
 }
 
 fn default_loop_end() -> f64 {
@@ -649,8 +980,11 @@ fn default_beat_unit() -> i32 {
 impl Sampler {
     /// Get sampler parameters for serialization
     pub fn get_parameters(&self) -> Option<SamplerData> {
-        self.sample.as_ref().map(|s| SamplerData {
-            sample_path: s.file_path.clone(),
+        if !self.has_sample() {
+            return None;
+        }
+        Some(SamplerData {
+            sample_path: self.sample_path().unwrap_or_default().to_string(),
             root_note: self.root_note,
             attack_ms: self.envelope.attack_ms,
             release_ms: self.envelope.release_ms,
@@ -666,6 +1000,16 @@ impl Sampler {
             warp_mode: self.warp_mode,
             beats_per_bar: self.beats_per_bar,
             beat_unit: self.beat_unit,
+            interpolation_mode: self.interpolation_mode,
+            velocity_curve: self.velocity_curve,
+            zones: self.zones.iter().map(|zone| ZoneData {
+                sample_path: zone.clip.file_path.clone(),
+                root_note: zone.root_note,
+                low_note: zone.low_note,
+                high_note: zone.high_note,
+                low_velocity: zone.low_velocity,
+                high_velocity: zone.high_velocity,
+            }).collect(),
         })
     }
 
@@ -694,6 +1038,9 @@ impl Sampler {
         self.warp_mode = data.warp_mode;
         self.beats_per_bar = data.beats_per_bar;
         self.beat_unit = data.beat_unit;
+        self.interpolation_mode = data.interpolation_mode;
+        self.velocity_curve = data.velocity_curve;
+        self.rebuild_stretched_cache();
         println!("✅ Restored sampler parameters: root={}, attack={}ms, release={}ms, loop={}, vol={:.1}dB, transpose={}st",
             note_name(self.root_note), data.attack_ms, data.release_ms,
             self.loop_enabled, self.volume_db, self.transpose_semitones);
@@ -753,6 +1100,65 @@ mod tests {
         assert!(right.abs() < f32::EPSILON);
     }
 
+    fn zone(root_note: u8, low_note: u8, high_note: u8, low_velocity: u8, high_velocity: u8, path: &str) -> SampleZone {
+        SampleZone {
+            root_note,
+            low_note,
+            high_note,
+            low_velocity,
+            high_velocity,
+            clip: Arc::new(AudioClip {
+                samples: vec![0.5; 2000],
+                channels: 2,
+                sample_rate: 48000,
+                duration_seconds: 1000.0 / 48000.0,
+                file_path: path.to_string(),
+                stream: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_zone_selection_by_note_and_velocity() {
+        let mut sampler = Sampler::new(48000.0);
+        sampler.add_zone(zone(60, 48, 71, 0, 63, "soft.wav"));
+        sampler.add_zone(zone(60, 48, 71, 64, 127, "hard.wav"));
+        sampler.add_zone(zone(84, 72, 96, 0, 127, "high.wav"));
+        assert!(sampler.has_sample());
+
+        sampler.note_on(60, 40);
+        sampler.note_on(62, 100);
+        sampler.note_on(96, 100);
+        let zones: Vec<_> = sampler.voices.iter().filter(|v| v.is_active).map(|v| v.zone).collect();
+        assert_eq!(zones, vec![Some(0), Some(1), Some(2)]);
+
+        // Playback rate follows the zone's root note
+        let high = sampler.voices.iter().find(|v| v.note == 96).unwrap();
+        assert!((high.playback_rate - 2.0).abs() < 0.001);
+
+        // No zone and no main sample: ignored
+        sampler.note_on(20, 100);
+        assert_eq!(sampler.active_voice_count(), 3);
+
+        // Zone voices render once the attack ramps up
+        let left: f32 = (0..10).map(|_| sampler.process_sample().0).sum();
+        assert!(left.abs() > 0.0);
+    }
+
+    #[test]
+    fn test_zone_serialization() {
+        let mut sampler = Sampler::new(48000.0);
+        sampler.add_zone(zone(60, 0, 127, 0, 127, "zone.wav"));
+        let data = sampler.get_parameters().unwrap();
+        assert!(data.sample_path.is_empty());
+        assert_eq!(data.zones.len(), 1);
+        assert_eq!(data.zones[0].sample_path, "zone.wav");
+
+        let json = serde_json::to_string(&data).unwrap();
+        let restored: SamplerData = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.zones[0].high_velocity, 127);
+    }
+
     #[test]
     fn test_envelope_defaults() {
         let env = SamplerEnvelope::default();
@@ -900,4 +1306,171 @@ mod tests {
         assert_eq!(sampler.beats_per_bar, 4);
         assert_eq!(sampler.beat_unit, 4);
     }
+
+    /// RMS of a 15 kHz sine played an octave up (30 kHz folds back to 18 kHz)
+    fn pitched_sine_rms(mode: InterpolationMode) -> f32 {
+        let frames = 4800;
+        let samples = (0..frames)
+            .map(|n| (2.0 * std::f32::consts::PI * 15_000.0 * n as f32 / 48_000.0).sin())
+            .collect();
+        let mut sampler = Sampler::new(48000.0);
+        sampler.load_sample(Arc::new(AudioClip {
+            samples,
+            channels: 1,
+            sample_rate: 48000,
+            duration_seconds: 0.1,
+            file_path: "sine.wav".to_string(),
+            stream: None,
+        }));
+        sampler.set_parameter("interpolation_mode", mode.name());
+        sampler.note_on(72, 127);
+
+        // Skip the attack and measure the steady state
+        let output: Vec<f32> = (0..2000).map(|_| sampler.process_sample().0).collect();
+        let tail = &output[200..];
+        (tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_sinc_interpolation_suppresses_aliasing() {
+        let linear = pitched_sine_rms(InterpolationMode::Linear);
+        let sinc = pitched_sine_rms(InterpolationMode::Sinc);
+        // The pitched tone is above Nyquist, so everything audible is aliasing
+        assert!(linear > 0.1, "linear alias rms = {linear}");
+        assert!(sinc < linear * 0.1, "sinc rms = {sinc}, linear rms = {linear}");
+    }
+
+    #[test]
+    fn test_preview_skips_sinc_interpolation() {
+        let sampler_with = |mode: InterpolationMode| {
+            let samples = (0..4800).map(|n| (n as f32 * 0.37).sin()).collect();
+            let mut sampler = Sampler::new(48000.0);
+            sampler.load_sample(Arc::new(AudioClip {
+                samples,
+                channels: 1,
+                sample_rate: 48000,
+                duration_seconds: 0.1,
+                file_path: "tone.wav".to_string(),
+                stream: None,
+            }));
+            sampler.set_parameter("interpolation_mode", mode.name());
+            sampler.note_on(67, 127);
+            sampler
+        };
+        let render = |sampler: &mut Sampler, preview: bool| -> Vec<(f32, f32)> {
+            (0..1000)
+                .map(|_| if preview { sampler.process_sample_preview() } else { sampler.process_sample() })
+                .collect()
+        };
+        let preview = render(&mut sampler_with(InterpolationMode::Sinc), true);
+        let linear = render(&mut sampler_with(InterpolationMode::Linear), false);
+        let sinc = render(&mut sampler_with(InterpolationMode::Sinc), false);
+
+        // A sinc sampler previews exactly like a linear one, and plays sinc otherwise
+        assert_eq!(preview, linear);
+        let max_diff = sinc.iter().zip(&linear).map(|(s, l)| (s.0 - l.0).abs()).fold(0.0, f32::max);
+        assert!(max_diff > 1e-4, "sinc and linear playback match: {max_diff}");
+    }
+
+    #[test]
+    fn test_sinc_preserves_passband() {
+        let clip = AudioClip {
+            samples: (0..1000).map(|n| (n as f32 * 0.05).sin()).collect(),
+            channels: 1,
+            sample_rate: 48000,
+            duration_seconds: 1000.0 / 48000.0,
+            file_path: String::new(),
+            stream: None,
+        };
+        let expected = (500.5_f32 * 0.05).sin();
+        let value = sinc_interpolate(&clip, 0, 500.5, 1.0);
+        assert!((value - expected).abs() < 0.01, "{value} vs {expected}");
+    }
+
+    /// Play a 440 Hz sine at its root note; returns (active frames, estimated Hz)
+    fn play_sine_with_warp(warp_mode: &str) -> (usize, f32) {
+        let frames = 24_000;
+        let samples = (0..frames)
+            .map(|n| (2.0 * std::f32::consts::PI * 440.0 * n as f32 / 48_000.0).sin() * 0.5)
+            .collect();
+        let mut sampler = Sampler::new(48000.0);
+        sampler.load_sample(Arc::new(AudioClip {
+            samples,
+            channels: 1,
+            sample_rate: 48000,
+            duration_seconds: 0.5,
+            file_path: "sine.wav".to_string(),
+            stream: None,
+        }));
+        sampler.set_parameter("original_bpm", "120");
+        sampler.set_parameter("warp_mode", warp_mode);
+        sampler.set_parameter("warp_enabled", "true");
+        sampler.set_project_tempo(60.0);
+        sampler.note_on(60, 127);
+
+        let mut output = Vec::new();
+        while sampler.active_voice_count() > 0 && output.len() < 200_000 {
+            output.push(sampler.process_sample().0);
+        }
+
+        // Zero-crossing pitch estimate over the steady middle section
+        let window = &output[output.len() / 4..output.len() / 2];
+        let crossings = window.windows(2).filter(|w| (w[0] < 0.0) != (w[1] < 0.0)).count();
+        let hz = crossings as f32 / 2.0 / (window.len() as f32 / 48_000.0);
+        (output.len(), hz)
+    }
+
+    #[test]
+    fn test_prestretched_warp_audio_is_installed() {
+        let mut sampler = Sampler::new(48000.0);
+        sampler.load_sample(Arc::new(AudioClip {
+            samples: (0..4800).map(|n| (n as f32 * 0.05).sin()).collect(),
+            channels: 1,
+            sample_rate: 48000,
+            duration_seconds: 0.1,
+            file_path: "sine.wav".to_string(),
+            stream: None,
+        }));
+        sampler.set_parameter("warp_mode", "1");
+        sampler.set_parameter("warp_enabled", "true");
+
+        // Built outside the sampler, then swapped in as is
+        let stretch = sampler.warp_stretch_for_tempo(7, 60.0).unwrap().build();
+        let built = Arc::clone(stretch.stretched.as_ref().unwrap());
+        sampler.set_project_tempo_prestretched(60.0, Some(&stretch));
+        assert!(Arc::ptr_eq(sampler.stretched_sample.as_ref().unwrap(), &built));
+        assert!(sampler.warp_stretch_for_tempo(7, 60.0).is_none());
+
+        // A stretch built for another tempo is ignored and the sampler stretches itself
+        let stale = sampler.warp_stretch_for_tempo(7, 90.0).unwrap().build();
+        sampler.set_project_tempo_prestretched(100.0, Some(&stale));
+        assert!(!Arc::ptr_eq(sampler.stretched_sample.as_ref().unwrap(), stale.stretched.as_ref().unwrap()));
+        assert!((sampler.cached_stretch_factor - 100.0 / 120.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_warp_mode_preserves_pitch_while_stretching() {
+        // Project at half the sample's tempo: playback lasts twice as long
+        let (frames, hz) = play_sine_with_warp("1");
+        assert!((45_000..52_000).contains(&frames), "warp frames = {frames}");
+        assert!((hz - 440.0).abs() < 20.0, "warp pitch = {hz}");
+
+        // Re-Pitch changes duration and pitch together
+        let (frames, hz) = play_sine_with_warp("0");
+        assert!((45_000..52_000).contains(&frames), "repitch frames = {frames}");
+        assert!((hz - 220.0).abs() < 10.0, "repitch pitch = {hz}");
+    }
+
+    #[test]
+    fn test_interpolation_mode_serialization() {
+        let mut sampler = Sampler::new(48000.0);
+        assert_eq!(sampler.interpolation_mode, InterpolationMode::Linear);
+        sampler.set_parameter("interpolation_mode", "sinc");
+        assert_eq!(sampler.interpolation_mode, InterpolationMode::Sinc);
+        sampler.set_parameter("interpolation_mode", "bogus");
+        assert_eq!(sampler.interpolation_mode, InterpolationMode::Sinc);
+
+        let json = serde_json::to_string(&InterpolationMode::Sinc).unwrap();
+        assert_eq!(json, "\"sinc\"");
+    }
 }
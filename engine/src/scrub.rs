@@ -0,0 +1,156 @@
+/// Scrub playback: a short grain of the mix looped while the playhead is dragged
+///
+/// `AudioGraph::scrub_to` renders a window of the mix around the dragged position
+/// (with the fast preview render) and queues it here; the audio callback loops it
+/// while the transport is stopped, without moving the playhead. A queued grain
+/// replaces any grain still waiting and is swapped in when the current one finishes
+/// a pass, where both are faded to silence, so rapid scrubbing neither stacks nor
+/// clicks. Scrubbing falls silent once a grain has looped for `SCRUB_HOLD_SECONDS`
+/// without a new position.
+use crate::audio_file::TARGET_SAMPLE_RATE;
+
+/// Fade at each end of a grain (seconds)
+const GRAIN_FADE_SECONDS: f64 = 0.005;
+/// How long a grain keeps looping after the last scrub position (seconds)
+pub const SCRUB_HOLD_SECONDS: f64 = 0.25;
+/// Allowed scrub window lengths (milliseconds)
+pub const MIN_SCRUB_WINDOW_MS: f64 = 10.0;
+pub const MAX_SCRUB_WINDOW_MS: f64 = 500.0;
+
+/// Grain playback state, shared between the API and the audio callback
+pub struct ScrubPlayer {
+    /// Interleaved stereo grain being looped
+    grain: Vec<f32>,
+    /// Next grain, swapped in at the end of the current pass
+    pending: Option<Vec<f32>>,
+    /// Grain swapped out by the audio thread, freed by the next `queue_grain`
+    retired: Option<Vec<f32>>,
+    /// Frame position in `grain`
+    position: usize,
+    /// Frames until the grain stops at the end of a pass
+    hold_frames: usize,
+    playing: bool,
+}
+
+impl Default for ScrubPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScrubPlayer {
+    pub fn new() -> Self {
+        Self {
+            grain: Vec::new(),
+            pending: None,
+            retired: None,
+            position: 0,
+            hold_frames: 0,
+            playing: false,
+        }
+    }
+
+    /// Queue a grain (interleaved stereo at the engine rate) to loop; it replaces
+    /// any grain still waiting, and starts at once if nothing is playing
+    pub fn queue_grain(&mut self, mut grain: Vec<f32>) {
+        self.retired = None;
+        if grain.len() < 2 {
+            return;
+        }
+        fade_grain_edges(&mut grain);
+        if self.playing {
+            self.pending = Some(grain);
+        } else {
+            self.grain = grain;
+            self.position = 0;
+            self.playing = true;
+        }
+        self.hold_frames = (SCRUB_HOLD_SECONDS * f64::from(TARGET_SAMPLE_RATE)) as usize;
+    }
+
+    /// Stop once the current pass ends (drops any queued grain)
+    pub fn stop(&mut self) {
+        self.pending = None;
+        self.hold_frames = 0;
+    }
+
+    /// Silence scrubbing immediately (the transport is starting)
+    pub fn cancel(&mut self) {
+        self.pending = None;
+        self.playing = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Next output frame (audio thread; never allocates or frees)
+    pub fn next_frame(&mut self) -> (f32, f32) {
+        if !self.playing {
+            return (0.0, 0.0);
+        }
+        let frame = (self.grain[self.position * 2], self.grain[self.position * 2 + 1]);
+        self.hold_frames = self.hold_frames.saturating_sub(1);
+        self.position += 1;
+
+        if self.position * 2 + 1 >= self.grain.len() {
+            self.position = 0;
+            if let Some(next) = self.pending.take() {
+                self.retired = Some(std::mem::replace(&mut self.grain, next));
+            } else if self.hold_frames == 0 {
+                self.playing = false;
+            }
+        }
+        frame
+    }
+}
+
+/// Fade both ends of a grain to silence so it loops and swaps without clicks
+fn fade_grain_edges(grain: &mut [f32]) {
+    let frames = grain.len() / 2;
+    let fade = ((GRAIN_FADE_SECONDS * f64::from(TARGET_SAMPLE_RATE)) as usize).min(frames / 2);
+    for i in 0..fade {
+        let gain = i as f32 / fade as f32;
+        for index in [i * 2, i * 2 + 1, (frames - 1 - i) * 2, (frames - 1 - i) * 2 + 1] {
+            grain[index] *= gain;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grain(frames: usize, value: f32) -> Vec<f32> {
+        vec![value; frames * 2]
+    }
+
+    #[test]
+    fn test_grains_loop_replace_and_stop_after_the_hold() {
+        let mut player = ScrubPlayer::new();
+        assert_eq!(player.next_frame(), (0.0, 0.0));
+
+        // 50 ms grain, faded in from silence
+        let frames = TARGET_SAMPLE_RATE as usize / 20;
+        player.queue_grain(grain(frames, 0.5));
+        assert_eq!(player.next_frame(), (0.0, 0.0));
+        let middle: Vec<f32> = (1..frames).map(|_| player.next_frame().0).collect();
+        assert!((middle[frames / 2] - 0.5).abs() < 1e-6);
+
+        // Two quick scrubs: only the last one plays, after the current pass
+        player.queue_grain(grain(frames, 0.25));
+        player.queue_grain(grain(frames, 0.75));
+        let pass: Vec<f32> = (0..frames).map(|_| player.next_frame().0).collect();
+        assert!((pass[frames / 2] - 0.5).abs() < 1e-6);
+        let pass: Vec<f32> = (0..frames).map(|_| player.next_frame().0).collect();
+        assert!((pass[frames / 2] - 0.75).abs() < 1e-6);
+
+        // It keeps looping for the hold time, then goes quiet at the end of a pass
+        let hold = (SCRUB_HOLD_SECONDS * f64::from(TARGET_SAMPLE_RATE)) as usize;
+        for _ in 0..hold + frames {
+            player.next_frame();
+        }
+        assert!(!player.is_playing());
+        assert_eq!(player.next_frame(), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,254 @@
+/// Spectrum analysis of the master output
+///
+/// The audio thread feeds mono-summed master samples into a circular buffer
+/// and runs a Hann-windowed FFT every half window (50% overlap). FFT plans and
+/// scratch buffers are allocated up front, so analysis never allocates inside
+/// the callback. Magnitudes are published through atomics for the UI.
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Supported FFT window sizes
+pub const SPECTRUM_WINDOW_SIZES: [usize; 3] = [512, 1024, 2048];
+/// Window size used until the UI picks one
+pub const DEFAULT_SPECTRUM_WINDOW_SIZE: usize = 1024;
+const MAX_WINDOW_SIZE: usize = 2048;
+/// Number of published bins for the largest window (window / 2)
+const MAX_BINS: usize = MAX_WINDOW_SIZE / 2;
+
+/// Latest spectrum magnitudes, shared between the audio thread and the API
+pub struct SpectrumReadings {
+    /// Linear magnitude per FFT bin (f32 bits), 0..window/2
+    magnitudes: Vec<AtomicU32>,
+    /// Number of valid bins for the current window
+    bin_count: AtomicUsize,
+    window_size: AtomicUsize,
+    enabled: AtomicBool,
+}
+
+impl Default for SpectrumReadings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpectrumReadings {
+    pub fn new() -> Self {
+        Self {
+            magnitudes: (0..MAX_BINS).map(|_| AtomicU32::new(0)).collect(),
+            bin_count: AtomicUsize::new(DEFAULT_SPECTRUM_WINDOW_SIZE / 2),
+            window_size: AtomicUsize::new(DEFAULT_SPECTRUM_WINDOW_SIZE),
+            enabled: AtomicBool::new(false),
+        }
+    }
+
+    /// Enable or disable the analysis pass (disabled costs one atomic load per frame)
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Set the FFT window size (must be one of `SPECTRUM_WINDOW_SIZES`)
+    pub fn set_window_size(&self, window_size: usize) -> Result<(), String> {
+        if !SPECTRUM_WINDOW_SIZES.contains(&window_size) {
+            return Err(format!(
+                "Invalid spectrum window size {window_size} (expected 512, 1024 or 2048)"
+            ));
+        }
+        self.window_size.store(window_size, Ordering::SeqCst);
+        Ok(())
+    }
+
+    pub fn window_size(&self) -> usize {
+        self.window_size.load(Ordering::Relaxed)
+    }
+
+    /// Get `num_bins` magnitudes spanning 0Hz..Nyquist
+    ///
+    /// FFT bins are grouped linearly; each output bin holds the loudest FFT
+    /// bin in its range so narrow peaks survive the reduction.
+    pub fn get(&self, num_bins: usize) -> Vec<f32> {
+        let available = self.bin_count.load(Ordering::Relaxed).min(MAX_BINS);
+        if num_bins == 0 || available == 0 {
+            return Vec::new();
+        }
+
+        (0..num_bins)
+            .map(|i| {
+                let start = i * available / num_bins;
+                let end = ((i + 1) * available / num_bins).max(start + 1).min(available);
+                self.magnitudes[start..end]
+                    .iter()
+                    .map(|m| f32::from_bits(m.load(Ordering::Relaxed)))
+                    .fold(0.0, f32::max)
+            })
+            .collect()
+    }
+
+    fn clear(&self) {
+        for magnitude in &self.magnitudes {
+            magnitude.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Spectrum analyzer state (owned by the audio thread, allocation-free after construction)
+pub struct SpectrumAnalyzer {
+    /// Forward FFT plans, one per entry in `SPECTRUM_WINDOW_SIZES`
+    plans: Vec<Arc<dyn Fft<f32>>>,
+    /// Hann windows, one per entry in `SPECTRUM_WINDOW_SIZES`
+    windows: Vec<Vec<f32>>,
+    /// Circular buffer of the most recent mono samples
+    history: Vec<f32>,
+    write_pos: usize,
+    /// Samples received since the last analysis
+    hop_count: usize,
+    window_size: usize,
+    buffer: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl Default for SpectrumAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpectrumAnalyzer {
+    pub fn new() -> Self {
+        let mut planner = FftPlanner::<f32>::new();
+        let plans: Vec<Arc<dyn Fft<f32>>> = SPECTRUM_WINDOW_SIZES
+            .iter()
+            .map(|&size| planner.plan_fft_forward(size))
+            .collect();
+        let scratch_len = plans.iter().map(|p| p.get_inplace_scratch_len()).max().unwrap_or(0);
+        let windows = SPECTRUM_WINDOW_SIZES
+            .iter()
+            .map(|&size| {
+                (0..size)
+                    .map(|i| {
+                        0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / size as f32).cos()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            plans,
+            windows,
+            history: vec![0.0; MAX_WINDOW_SIZE],
+            write_pos: 0,
+            hop_count: 0,
+            window_size: DEFAULT_SPECTRUM_WINDOW_SIZE,
+            buffer: vec![Complex::new(0.0, 0.0); MAX_WINDOW_SIZE],
+            scratch: vec![Complex::new(0.0, 0.0); scratch_len],
+        }
+    }
+
+    /// Feed one stereo frame; publishes a new spectrum every half window
+    pub fn process_frame(&mut self, left: f32, right: f32, readings: &SpectrumReadings) {
+        if !readings.enabled.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let requested = readings.window_size.load(Ordering::Relaxed);
+        if requested != self.window_size {
+            self.window_size = requested;
+            self.hop_count = 0;
+            readings.clear();
+            readings.bin_count.store(requested / 2, Ordering::Relaxed);
+        }
+
+        self.history[self.write_pos] = (left + right) * 0.5;
+        self.write_pos = (self.write_pos + 1) % MAX_WINDOW_SIZE;
+        self.hop_count += 1;
+
+        if self.hop_count >= self.window_size / 2 {
+            self.hop_count = 0;
+            self.analyze(readings);
+        }
+    }
+
+    fn analyze(&mut self, readings: &SpectrumReadings) {
+        let Some(index) = SPECTRUM_WINDOW_SIZES.iter().position(|&s| s == self.window_size) else {
+            return;
+        };
+        let size = self.window_size;
+        let window = &self.windows[index];
+
+        // Oldest sample of the window sits `size` samples behind the write position
+        let start = (self.write_pos + MAX_WINDOW_SIZE - size) % MAX_WINDOW_SIZE;
+        for (i, slot) in self.buffer[..size].iter_mut().enumerate() {
+            let sample = self.history[(start + i) % MAX_WINDOW_SIZE];
+            *slot = Complex::new(sample * window[i], 0.0);
+        }
+
+        let plan = &self.plans[index];
+        let scratch_len = plan.get_inplace_scratch_len();
+        plan.process_with_scratch(&mut self.buffer[..size], &mut self.scratch[..scratch_len]);
+
+        // Hann coherent gain is 0.5, so a full-scale sine reads 1.0
+        let scale = 4.0 / size as f32;
+        for (bin, magnitude) in readings.magnitudes[..size / 2].iter().enumerate() {
+            let value = self.buffer[bin].norm() * scale;
+            magnitude.store(value.to_bits(), Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_file::TARGET_SAMPLE_RATE;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_sine_peaks_in_expected_bin() {
+        let mut analyzer = SpectrumAnalyzer::new();
+        let readings = SpectrumReadings::new();
+        readings.set_enabled(true);
+
+        // Centre of FFT bin 64 at 1024 points
+        let freq = 64.0 * TARGET_SAMPLE_RATE as f32 / 1024.0;
+        for i in 0..4096 {
+            let s = (2.0 * PI * freq * i as f32 / TARGET_SAMPLE_RATE as f32).sin();
+            analyzer.process_frame(s, s, &readings);
+        }
+
+        let bins = readings.get(512);
+        let (peak_bin, peak) = bins
+            .iter()
+            .enumerate()
+            .fold((0, 0.0_f32), |acc, (i, &m)| if m > acc.1 { (i, m) } else { acc });
+        assert_eq!(peak_bin, 64);
+        assert!((peak - 1.0).abs() < 0.05, "peak magnitude {peak}");
+        assert!(bins[200] < 0.01);
+    }
+
+    #[test]
+    fn test_disabled_analyzer_publishes_nothing() {
+        let mut analyzer = SpectrumAnalyzer::new();
+        let readings = SpectrumReadings::new();
+        for _ in 0..4096 {
+            analyzer.process_frame(1.0, 1.0, &readings);
+        }
+        assert!(readings.get(64).iter().all(|&m| m <= 0.0));
+    }
+
+    #[test]
+    fn test_window_size_validation_and_bin_reduction() {
+        let readings = SpectrumReadings::new();
+        assert!(readings.set_window_size(1000).is_err());
+        assert!(readings.set_window_size(2048).is_ok());
+        assert_eq!(readings.window_size(), 2048);
+        assert_eq!(readings.get(32).len(), 32);
+        assert!(readings.get(0).is_empty());
+    }
+}
@@ -0,0 +1,198 @@
+/// Step sequencer - drum-machine style pattern that plays a track's synth or sampler
+/// 16 lanes x 16 sixteenth-note steps (one bar of 4/4, looped). Lane `n` plays MIDI
+/// note `SEQUENCER_BASE_NOTE + n`, so lanes line up with a GM drum map from C1.
+/// Steps follow the playhead and only play while the transport runs.
+use crate::midi::{MidiEventType, MidiVelocity};
+use serde::{Deserialize, Serialize};
+
+pub const SEQUENCER_LANES: usize = 16;
+pub const SEQUENCER_STEPS: usize = 16;
+/// MIDI note of lane 0 (C1, kick drum in the GM drum map)
+pub const SEQUENCER_BASE_NOTE: u8 = 36;
+/// Length of one step in beats (a sixteenth note)
+pub const STEP_BEATS: f64 = 0.25;
+/// Fraction of a step each hit is held for
+const STEP_GATE: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Step {
+    pub on: bool,
+    pub velocity: MidiVelocity,
+}
+
+/// One active step in a saved pattern
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SequencerStepData {
+    pub lane: u8,
+    pub step: u8,
+    pub velocity: u8,
+}
+
+/// Saved step sequencer pattern (only steps that are on)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StepSequencerData {
+    pub steps: Vec<SequencerStepData>,
+}
+
+#[derive(Clone)]
+pub struct StepSequencer {
+    steps: [[Step; SEQUENCER_STEPS]; SEQUENCER_LANES],
+    /// Absolute step (since beat 0) last played
+    last_step: Option<i64>,
+    /// Lanes whose note is held (bit per lane)
+    sounding: u16,
+    /// Beat at which sounding notes are released
+    note_off_beat: f64,
+}
+
+impl Default for StepSequencer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StepSequencer {
+    pub fn new() -> Self {
+        Self {
+            steps: [[Step::default(); SEQUENCER_STEPS]; SEQUENCER_LANES],
+            last_step: None,
+            sounding: 0,
+            note_off_beat: 0.0,
+        }
+    }
+
+    /// Turn a step on or off (velocity is clamped to 1-127)
+    pub fn set_step(&mut self, lane: usize, step: usize, on: bool, velocity: MidiVelocity) -> Result<(), String> {
+        if lane >= SEQUENCER_LANES || step >= SEQUENCER_STEPS {
+            return Err(format!("Step {lane}:{step} is outside the {SEQUENCER_LANES}x{SEQUENCER_STEPS} grid"));
+        }
+        self.steps[lane][step] = Step { on, velocity: velocity.clamp(1, 127) };
+        Ok(())
+    }
+
+    /// Advance by one sample, sending note events for this sample to `play`
+    ///
+    /// `playhead_beats` is the transport position while playing (None when stopped).
+    pub fn tick(&mut self, playhead_beats: Option<f64>, mut play: impl FnMut(MidiEventType)) {
+        let Some(position) = playhead_beats else {
+            self.release(&mut play);
+            self.last_step = None;
+            return;
+        };
+
+        if self.sounding != 0 && position >= self.note_off_beat {
+            self.release(&mut play);
+        }
+
+        let step = (position / STEP_BEATS).floor() as i64;
+        if self.last_step == Some(step) {
+            return;
+        }
+        self.last_step = Some(step);
+        self.release(&mut play);
+
+        let column = step.rem_euclid(SEQUENCER_STEPS as i64) as usize;
+        for (lane, steps) in self.steps.iter().enumerate() {
+            let Step { on, velocity } = steps[column];
+            if on {
+                play(MidiEventType::NoteOn { note: SEQUENCER_BASE_NOTE + lane as u8, velocity });
+                self.sounding |= 1 << lane;
+            }
+        }
+        self.note_off_beat = (step as f64 + STEP_GATE) * STEP_BEATS;
+    }
+
+    /// Note-offs for every held lane
+    fn release(&mut self, play: &mut impl FnMut(MidiEventType)) {
+        for lane in 0..SEQUENCER_LANES {
+            if self.sounding & (1 << lane) != 0 {
+                play(MidiEventType::NoteOff { note: SEQUENCER_BASE_NOTE + lane as u8, velocity: 64 });
+            }
+        }
+        self.sounding = 0;
+    }
+
+    /// Forget held notes (the instrument is silenced separately)
+    pub fn reset(&mut self) {
+        self.sounding = 0;
+        self.last_step = None;
+    }
+
+    pub fn get_data(&self) -> StepSequencerData {
+        let mut data = StepSequencerData::default();
+        for (lane, steps) in self.steps.iter().enumerate() {
+            for (step, s) in steps.iter().enumerate() {
+                if s.on {
+                    data.steps.push(SequencerStepData { lane: lane as u8, step: step as u8, velocity: s.velocity });
+                }
+            }
+        }
+        data
+    }
+
+    pub fn restore_data(&mut self, data: &StepSequencerData) {
+        self.steps = [[Step::default(); SEQUENCER_STEPS]; SEQUENCER_LANES];
+        for s in &data.steps {
+            // Steps outside the grid (corrupt project data) are skipped
+            let _ = self.set_step(usize::from(s.lane), usize::from(s.step), true, s.velocity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run the transport from beat 0 for `beats` beats, collecting (beat, event)
+    fn run(sequencer: &mut StepSequencer, beats: f64) -> Vec<(f64, MidiEventType)> {
+        let mut events = Vec::new();
+        for i in 0..(beats * 1000.0) as usize {
+            let beat = i as f64 / 1000.0;
+            sequencer.tick(Some(beat), |event| events.push((beat, event)));
+        }
+        events
+    }
+
+    #[test]
+    fn test_steps_play_on_sixteenths_and_loop_each_bar() {
+        let mut sequencer = StepSequencer::new();
+        sequencer.set_step(0, 0, true, 120).unwrap();
+        sequencer.set_step(2, 4, true, 90).unwrap();
+        assert!(sequencer.set_step(16, 0, true, 100).is_err());
+
+        let note_ons: Vec<(f64, u8, u8)> = run(&mut sequencer, 8.0)
+            .into_iter()
+            .filter_map(|(beat, event)| match event {
+                MidiEventType::NoteOn { note, velocity } => Some((beat, note, velocity)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            note_ons,
+            vec![(0.0, 36, 120), (1.0, 38, 90), (4.0, 36, 120), (5.0, 38, 90)]
+        );
+    }
+
+    #[test]
+    fn test_hits_are_released_within_the_step_and_on_stop() {
+        let mut sequencer = StepSequencer::new();
+        sequencer.set_step(1, 0, true, 100).unwrap();
+
+        let events = run(&mut sequencer, 0.25);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].1, MidiEventType::NoteOff { note: 37, velocity: 64 });
+        assert!((events[1].0 - 0.125).abs() < 1e-9);
+
+        // Stopping mid-hit releases the note
+        let mut stopped = Vec::new();
+        sequencer.tick(Some(4.0), |_| {});
+        sequencer.tick(None, |event| stopped.push(event));
+        assert_eq!(stopped, vec![MidiEventType::NoteOff { note: 37, velocity: 64 }]);
+
+        let data = sequencer.get_data();
+        let mut restored = StepSequencer::new();
+        restored.restore_data(&data);
+        assert_eq!(restored.steps[1][0], Step { on: true, velocity: 100 });
+        assert_eq!(restored.steps[0][0], Step::default());
+    }
+}
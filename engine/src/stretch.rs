@@ -2,7 +2,8 @@
 ///
 /// This module provides functionality to time-stretch audio while preserving pitch,
 /// used when `warp_mode` = 0 (Warp). When `warp_mode` = 1 (Re-Pitch), simple sample-rate
-/// shifting is used instead (handled in `audio_graph.rs`).
+/// shifting is used instead (handled in `audio_graph.rs`). The same stretcher also
+/// transposes clips without changing their length.
 use signalsmith_stretch::Stretch;
 use std::sync::Arc;
 use crate::audio_file::{AudioClip, TARGET_SAMPLE_RATE};
@@ -26,8 +27,22 @@ pub fn stretch_audio_preserve_pitch(
     clip: &AudioClip,
     stretch_factor: f32,
 ) -> Arc<AudioClip> {
-    // If stretch factor is effectively 1.0, return a clone wrapped in Arc
-    if (stretch_factor - 1.0).abs() < 0.001 {
+    stretch_and_transpose_audio(clip, stretch_factor, 1.0)
+}
+
+/// Time-stretch an audio clip and shift its pitch independently.
+///
+/// # Arguments
+/// * `clip` - The source audio clip
+/// * `stretch_factor` - Speed multiplier, as for `stretch_audio_preserve_pitch`
+/// * `pitch_ratio` - Frequency multiplier (2.0 = up an octave), independent of length
+pub fn stretch_and_transpose_audio(
+    clip: &AudioClip,
+    stretch_factor: f32,
+    pitch_ratio: f32,
+) -> Arc<AudioClip> {
+    // If both factors are effectively 1.0, return a clone wrapped in Arc
+    if (stretch_factor - 1.0).abs() < 0.001 && (pitch_ratio - 1.0).abs() < 0.0001 {
         return Arc::new(clip.clone());
     }
 
@@ -43,6 +58,9 @@ pub fn stretch_audio_preserve_pitch(
 
     // Create stretcher instance
     let mut stretcher = Stretch::preset_default(channels, sample_rate);
+    if (pitch_ratio - 1.0).abs() >= 0.0001 {
+        stretcher.set_transpose_factor(pitch_ratio, None);
+    }
 
     // Prepare output buffer (interleaved, same format as input)
     let mut output_samples = vec![0.0f32; output_frames * clip.channels];
@@ -74,6 +92,7 @@ pub fn stretch_audio_preserve_pitch(
         sample_rate: TARGET_SAMPLE_RATE,
         duration_seconds,
         file_path: clip.file_path.clone(),
+        stream: None,
     })
 }
 
@@ -97,6 +116,7 @@ mod tests {
             sample_rate: 48000,
             duration_seconds: frames as f64 / 48000.0,
             file_path: "test.wav".to_string(),
+            stream: None,
         }
     }
 
@@ -1,13 +1,25 @@
 /// Minimal per-track synthesizer
 /// Clean rewrite: 1 oscillator, ADSR envelope, simple filter, 8-voice polyphony
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::f32::consts::PI;
 use std::sync::Arc;
+use crate::arpeggiator::{ArpMode, Arpeggiator, ArpeggiatorData};
 use crate::audio_file::AudioClip;
+use crate::midi::{MidiEventType, VelocityCurve, CC_MOD_WHEEL};
 use crate::project::SynthData;
-use crate::sampler::{Sampler, SamplerData};
+use crate::sampler::{SampleZone, Sampler, SamplerData, WarpStretch};
+use crate::step_sequencer::{StepSequencer, StepSequencerData};
 
 const MAX_VOICES: usize = 8;
+const MAX_UNISON: usize = 7;
+
+/// Pitch wheel range in semitones each way (default and maximum)
+pub const DEFAULT_PITCH_BEND_RANGE: f32 = 2.0;
+pub const MAX_PITCH_BEND_RANGE: f32 = 24.0;
+
+/// Vibrato LFO rate, and its depth at full modulation (semitones each way)
+const VIBRATO_RATE_HZ: f32 = 5.5;
+const VIBRATO_DEPTH_SEMITONES: f32 = 0.5;
 
 // ============================================================================
 // OSCILLATOR
@@ -42,6 +54,30 @@ fn generate_waveform(osc_type: OscillatorType, phase: f32) -> f32 {
     }
 }
 
+/// Where the mod wheel (CC1) and aftertouch are routed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModDestination {
+    Vibrato,
+    FilterCutoff,
+}
+
+impl ModDestination {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "vibrato" => Some(ModDestination::Vibrato),
+            "filter_cutoff" | "filter" => Some(ModDestination::FilterCutoff),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ModDestination::Vibrato => "vibrato",
+            ModDestination::FilterCutoff => "filter_cutoff",
+        }
+    }
+}
+
 // ============================================================================
 // ENVELOPE
 // ============================================================================
@@ -55,6 +91,15 @@ enum EnvelopeState {
     Release,
 }
 
+/// Envelope stage time limits (seconds): 1ms to 10s
+pub const MIN_ENVELOPE_TIME: f32 = 0.001;
+pub const MAX_ENVELOPE_TIME: f32 = 10.0;
+
+/// Parse a parameter value, rejecting NaN/infinity (e.g. from malformed project files)
+fn parse_finite(value: &str) -> Option<f32> {
+    value.parse::<f32>().ok().filter(|v| v.is_finite())
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct EnvelopeParams {
     pub attack: f32,  // seconds
@@ -82,12 +127,15 @@ impl Default for EnvelopeParams {
 struct Voice {
     note: u8,
     velocity: f32,
-    phase: f32,
+    /// One oscillator phase per unison copy
+    phases: [f32; MAX_UNISON],
     frequency: f32,
     env_state: EnvelopeState,
     env_level: f32,
     env_time: f32,
     is_active: bool,
+    /// Polyphonic aftertouch (0.0-1.0)
+    pressure: f32,
 }
 
 impl Voice {
@@ -95,24 +143,29 @@ impl Voice {
         Self {
             note: 0,
             velocity: 0.0,
-            phase: 0.0,
+            phases: [0.0; MAX_UNISON],
             frequency: 440.0,
             env_state: EnvelopeState::Idle,
             env_level: 0.0,
             env_time: 0.0,
             is_active: false,
+            pressure: 0.0,
         }
     }
 
     fn note_on(&mut self, note: u8, velocity: u8) {
         self.note = note;
         self.velocity = f32::from(velocity) / 127.0;
-        self.phase = 0.0;
+        // Stagger unison phases so the copies don't start in lockstep
+        for (i, phase) in self.phases.iter_mut().enumerate() {
+            *phase = i as f32 / MAX_UNISON as f32;
+        }
         self.frequency = midi_to_freq(note);
         self.env_state = EnvelopeState::Attack;
         self.env_level = 0.0;
         self.env_time = 0.0;
         self.is_active = true;
+        self.pressure = 0.0;
     }
 
     fn note_off(&mut self) {
@@ -122,30 +175,57 @@ impl Voice {
         }
     }
 
-    fn process(&mut self, osc_type: OscillatorType, env_params: &EnvelopeParams, sample_rate: f32) -> f32 {
+    /// `pitch_ratio` scales the note frequency (pitch bend and vibrato)
+    fn process(
+        &mut self,
+        osc_type: OscillatorType,
+        env_params: &EnvelopeParams,
+        unison: &UnisonParams,
+        pitch_ratio: f32,
+        sample_rate: f32,
+    ) -> (f32, f32) {
         if !self.is_active {
-            return 0.0;
+            return (0.0, 0.0);
         }
 
-        // Generate oscillator
-        let osc_out = generate_waveform(osc_type, self.phase);
-
-        // Advance phase
-        self.phase += self.frequency / sample_rate;
-        if self.phase >= 1.0 {
-            self.phase -= 1.0;
+        // Generate unison copies, each detuned and panned across the stereo field
+        let count = unison.voices.clamp(1, MAX_UNISON as u8) as usize;
+        let mut osc_left = 0.0;
+        let mut osc_right = 0.0;
+        for (i, phase) in self.phases.iter_mut().take(count).enumerate() {
+            // Spread position: -1.0 (hard left) .. 1.0 (hard right), 0.0 for a single copy
+            let spread = if count > 1 {
+                (i as f32 / (count - 1) as f32) * 2.0 - 1.0
+            } else {
+                0.0
+            };
+
+            let osc_out = generate_waveform(osc_type, *phase);
+            osc_left += osc_out * (1.0 - spread).min(1.0);
+            osc_right += osc_out * (1.0 + spread).min(1.0);
+
+            // Advance phase
+            let detune_ratio = 2.0_f32.powf(spread * unison.detune_cents / 1200.0);
+            *phase += self.frequency * pitch_ratio * detune_ratio / sample_rate;
+            if *phase >= 1.0 {
+                *phase -= 1.0;
+            }
         }
 
+        // Keep overall level roughly constant as copies are added
+        let unison_gain = 1.0 / (count as f32).sqrt();
+
         // Process envelope
         let env_out = self.process_envelope(env_params, sample_rate);
 
         // Check if voice finished
         if self.env_state == EnvelopeState::Idle {
             self.is_active = false;
-            return 0.0;
+            return (0.0, 0.0);
         }
 
-        osc_out * env_out * self.velocity
+        let gain = env_out * self.velocity * unison_gain;
+        (osc_left * gain, osc_right * gain)
     }
 
     fn process_envelope(&mut self, params: &EnvelopeParams, sample_rate: f32) -> f32 {
@@ -206,6 +286,22 @@ impl Voice {
     }
 }
 
+/// Unison settings shared by all voices of a synth
+#[derive(Debug, Clone, Copy)]
+pub struct UnisonParams {
+    pub voices: u8,        // 1-7 detuned copies per note
+    pub detune_cents: f32, // Spread between the outermost copies (each side)
+}
+
+impl Default for UnisonParams {
+    fn default() -> Self {
+        Self {
+            voices: 1,
+            detune_cents: 0.0,
+        }
+    }
+}
+
 fn midi_to_freq(note: u8) -> f32 {
     440.0 * 2.0_f32.powf((f32::from(note) - 69.0) / 12.0)
 }
@@ -214,32 +310,53 @@ fn midi_to_freq(note: u8) -> f32 {
 // SYNTH (per-track)
 // ============================================================================
 
+#[derive(Clone)]
 pub struct Synth {
-    voices: [Voice; MAX_VOICES],
+    voices: Vec<Voice>,
     pub osc_type: OscillatorType,
     pub filter_cutoff: f32, // 0.0-1.0
     pub envelope: EnvelopeParams,
+    pub unison: UnisonParams,
+    /// Pitch wheel range in semitones each way
+    pub pitch_bend_range: f32,
+    pub mod_destination: ModDestination,
+    pub velocity_curve: VelocityCurve,
     sample_rate: f32,
-    // Simple one-pole lowpass filter state
-    filter_state: f32,
+    // Simple one-pole lowpass filter state (per channel)
+    filter_state_left: f32,
+    filter_state_right: f32,
+    /// Pitch wheel position (-1.0 to 1.0)
+    pitch_bend: f32,
+    /// Mod wheel (0.0-1.0)
+    mod_wheel: f32,
+    /// Vibrato LFO phase (0.0-1.0)
+    vibrato_phase: f32,
 }
 
 impl Synth {
     pub fn new(sample_rate: f32) -> Self {
         Self {
-            voices: [Voice::new(); MAX_VOICES],
+            voices: vec![Voice::new(); MAX_VOICES],
             osc_type: OscillatorType::Saw,
             filter_cutoff: 1.0, // Fully open
             envelope: EnvelopeParams::default(),
+            unison: UnisonParams::default(),
+            pitch_bend_range: DEFAULT_PITCH_BEND_RANGE,
+            mod_destination: ModDestination::Vibrato,
+            velocity_curve: VelocityCurve::Linear,
             sample_rate,
-            filter_state: 0.0,
+            filter_state_left: 0.0,
+            filter_state_right: 0.0,
+            pitch_bend: 0.0,
+            mod_wheel: 0.0,
+            vibrato_phase: 0.0,
         }
     }
 
     pub fn note_on(&mut self, note: u8, velocity: u8) {
         // Find free voice or steal oldest
         let idx = self.find_free_voice_index();
-        self.voices[idx].note_on(note, velocity);
+        self.voices[idx].note_on(note, self.velocity_curve.apply(velocity));
     }
 
     pub fn note_off(&mut self, note: u8) {
@@ -250,37 +367,101 @@ impl Synth {
         }
     }
 
+    /// Silence all voices and re-centre the pitch and mod wheels
     pub fn all_notes_off(&mut self) {
         for voice in &mut self.voices {
             voice.is_active = false;
             voice.env_state = EnvelopeState::Idle;
             voice.env_level = 0.0;
         }
+        self.pitch_bend = 0.0;
+        self.mod_wheel = 0.0;
     }
 
-    pub fn process_sample(&mut self) -> f32 {
-        let mut output = 0.0;
+    /// Set the pitch wheel position (-8192..=8191, 0 = centre)
+    pub fn pitch_bend(&mut self, value: i16) {
+        self.pitch_bend = (f32::from(value) / 8192.0).clamp(-1.0, 1.0);
+    }
+
+    /// Handle a control change (only the mod wheel is used)
+    pub fn control_change(&mut self, controller: u8, value: u8) {
+        if controller == CC_MOD_WHEEL {
+            self.mod_wheel = f32::from(value.min(127)) / 127.0;
+        }
+    }
+
+    /// Set the key pressure of a held note (adds to the mod wheel for that note)
+    pub fn poly_aftertouch(&mut self, note: u8, pressure: u8) {
+        for voice in &mut self.voices {
+            if voice.is_active && voice.note == note {
+                voice.pressure = f32::from(pressure.min(127)) / 127.0;
+            }
+        }
+    }
+
+    /// Process and return a stereo sample
+    pub fn process_sample_stereo(&mut self) -> (f32, f32) {
+        let mut left = 0.0;
+        let mut right = 0.0;
+
+        let bend_ratio = if self.pitch_bend == 0.0 {
+            1.0
+        } else {
+            2.0_f32.powf(self.pitch_bend * self.pitch_bend_range / 12.0)
+        };
+        let vibrato = (self.vibrato_phase * 2.0 * PI).sin();
+        self.vibrato_phase = (self.vibrato_phase + VIBRATO_RATE_HZ / self.sample_rate).fract();
 
         // Mix all active voices
+        let mut max_pressure = 0.0_f32;
         for voice in &mut self.voices {
-            output += voice.process(self.osc_type, &self.envelope, self.sample_rate);
+            if !voice.is_active {
+                continue;
+            }
+            max_pressure = max_pressure.max(voice.pressure);
+            let modulation = (self.mod_wheel + voice.pressure).min(1.0);
+            let pitch_ratio = if self.mod_destination == ModDestination::Vibrato && modulation > 0.0 {
+                let semitones = vibrato * modulation * VIBRATO_DEPTH_SEMITONES;
+                bend_ratio * 2.0_f32.powf(semitones / 12.0)
+            } else {
+                bend_ratio
+            };
+            let (voice_left, voice_right) =
+                voice.process(self.osc_type, &self.envelope, &self.unison, pitch_ratio, self.sample_rate);
+            left += voice_left;
+            right += voice_right;
         }
 
+        // Modulation opens the filter from its set cutoff towards fully open
+        let cutoff = if self.mod_destination == ModDestination::FilterCutoff {
+            let modulation = (self.mod_wheel + max_pressure).min(1.0);
+            self.filter_cutoff + (1.0 - self.filter_cutoff) * modulation
+        } else {
+            self.filter_cutoff
+        };
+
         // Apply simple one-pole lowpass filter
-        output = self.apply_filter(output);
+        let (left, right) = self.apply_filter(left, right, cutoff);
 
         // Reduce volume to prevent clipping with multiple voices
-        output * 0.3
+        (left * 0.3, right * 0.3)
     }
 
-    fn apply_filter(&mut self, input: f32) -> f32 {
+    /// Process and return mono sample (for backwards compatibility)
+    pub fn process_sample(&mut self) -> f32 {
+        let (left, right) = self.process_sample_stereo();
+        (left + right) * 0.5
+    }
+
+    fn apply_filter(&mut self, left: f32, right: f32, cutoff: f32) -> (f32, f32) {
         // Map cutoff 0.0-1.0 to coefficient
         // cutoff=1.0 means no filtering, cutoff=0.0 means heavy filtering
-        let coeff = self.filter_cutoff.clamp(0.01, 1.0);
+        let coeff = cutoff.clamp(0.01, 1.0);
 
         // Simple one-pole lowpass: y[n] = coeff * x[n] + (1-coeff) * y[n-1]
-        self.filter_state = coeff * input + (1.0 - coeff) * self.filter_state;
-        self.filter_state
+        self.filter_state_left = coeff * left + (1.0 - coeff) * self.filter_state_left;
+        self.filter_state_right = coeff * right + (1.0 - coeff) * self.filter_state_right;
+        (self.filter_state_left, self.filter_state_right)
     }
 
     pub fn set_parameter(&mut self, key: &str, value: &str) {
@@ -298,29 +479,59 @@ impl Synth {
                 }
             }
             "env_attack" | "attack" => {
-                if let Ok(v) = value.parse::<f32>() {
-                    self.envelope.attack = v.max(0.001);
+                if let Some(v) = parse_finite(value) {
+                    self.envelope.attack = v.clamp(MIN_ENVELOPE_TIME, MAX_ENVELOPE_TIME);
                     println!("  → attack = {}", self.envelope.attack);
                 }
             }
             "env_decay" | "decay" => {
-                if let Ok(v) = value.parse::<f32>() {
-                    self.envelope.decay = v.max(0.001);
+                if let Some(v) = parse_finite(value) {
+                    self.envelope.decay = v.clamp(MIN_ENVELOPE_TIME, MAX_ENVELOPE_TIME);
                     println!("  → decay = {}", self.envelope.decay);
                 }
             }
             "env_sustain" | "sustain" => {
-                if let Ok(v) = value.parse::<f32>() {
+                if let Some(v) = parse_finite(value) {
                     self.envelope.sustain = v.clamp(0.0, 1.0);
                     println!("  → sustain = {}", self.envelope.sustain);
                 }
             }
             "env_release" | "release" => {
-                if let Ok(v) = value.parse::<f32>() {
-                    self.envelope.release = v.max(0.001);
+                if let Some(v) = parse_finite(value) {
+                    self.envelope.release = v.clamp(MIN_ENVELOPE_TIME, MAX_ENVELOPE_TIME);
                     println!("  → release = {}", self.envelope.release);
                 }
             }
+            "unison_voices" => {
+                if let Ok(v) = value.parse::<f32>() {
+                    self.unison.voices = v.round().clamp(1.0, MAX_UNISON as f32) as u8;
+                    println!("  → unison_voices = {}", self.unison.voices);
+                }
+            }
+            "unison_detune_cents" | "unison_detune" => {
+                if let Ok(v) = value.parse::<f32>() {
+                    self.unison.detune_cents = v.clamp(0.0, 100.0);
+                    println!("  → unison_detune_cents = {}", self.unison.detune_cents);
+                }
+            }
+            "pitch_bend_range" => {
+                if let Some(v) = parse_finite(value) {
+                    self.pitch_bend_range = v.clamp(0.0, MAX_PITCH_BEND_RANGE);
+                    println!("  → pitch_bend_range = {}", self.pitch_bend_range);
+                }
+            }
+            "mod_destination" => {
+                if let Some(destination) = ModDestination::parse(value) {
+                    self.mod_destination = destination;
+                    println!("  → mod_destination = {}", destination.name());
+                }
+            }
+            "velocity_curve" => {
+                if let Some(curve) = VelocityCurve::from_name(value) {
+                    self.velocity_curve = curve;
+                    println!("  → velocity_curve = {}", curve.name());
+                }
+            }
             _ => {
                 println!("  ⚠️ Unknown parameter: {key}");
             }
@@ -357,6 +568,11 @@ impl Synth {
             decay: self.envelope.decay,
             sustain: self.envelope.sustain,
             release: self.envelope.release,
+            unison_voices: self.unison.voices,
+            unison_detune_cents: self.unison.detune_cents,
+            pitch_bend_range: self.pitch_bend_range,
+            mod_destination: self.mod_destination.name().to_string(),
+            velocity_curve: self.velocity_curve,
         }
     }
 }
@@ -366,6 +582,7 @@ impl Synth {
 // ============================================================================
 
 /// Unified instrument type for tracks
+#[derive(Clone)]
 pub enum TrackInstrument {
     Synth(Synth),
     Sampler(Sampler),
@@ -404,14 +621,19 @@ impl TrackInstrument {
     /// Process and return stereo sample
     pub fn process_sample_stereo(&mut self) -> (f32, f32) {
         match self {
-            TrackInstrument::Synth(s) => {
-                let mono = s.process_sample();
-                (mono, mono)
-            }
+            TrackInstrument::Synth(s) => s.process_sample_stereo(),
             TrackInstrument::Sampler(s) => s.process_sample(),
         }
     }
 
+    /// `process_sample_stereo` for preview renders (see `Sampler::process_sample_preview`)
+    pub fn process_sample_stereo_preview(&mut self) -> (f32, f32) {
+        match self {
+            TrackInstrument::Synth(s) => s.process_sample_stereo(),
+            TrackInstrument::Sampler(s) => s.process_sample_preview(),
+        }
+    }
+
     pub fn set_parameter(&mut self, key: &str, value: &str) {
         match self {
             TrackInstrument::Synth(s) => s.set_parameter(key, value),
@@ -453,16 +675,58 @@ impl TrackInstrument {
 // TRACK SYNTH MANAGER (manages both Synths and Samplers)
 // ============================================================================
 
+#[derive(Clone)]
 pub struct TrackSynthManager {
     instruments: HashMap<u64, TrackInstrument>,
+    /// Tracks whose instrument is frozen (kept for saving, but silent and not triggered)
+    frozen: HashSet<u64>,
+    /// Per-track arpeggiators (only tracks that have had one set up)
+    arpeggiators: HashMap<u64, Arpeggiator>,
+    /// Per-track step sequencers (drive the track's synth or sampler)
+    sequencers: HashMap<u64, StepSequencer>,
     sample_rate: f32,
+    project_tempo: f64,
 }
 
 impl TrackSynthManager {
     pub fn new(sample_rate: f32) -> Self {
         Self {
             instruments: HashMap::new(),
+            frozen: HashSet::new(),
+            arpeggiators: HashMap::new(),
+            sequencers: HashMap::new(),
             sample_rate,
+            project_tempo: 120.0,
+        }
+    }
+
+    /// Update the project tempo used by warp-enabled samplers
+    pub fn set_project_tempo(&mut self, bpm: f64) {
+        self.set_project_tempo_prestretched(bpm, &[]);
+    }
+
+    /// Warp mode stretches that a change to `bpm` needs. Build them (`WarpStretch::build`)
+    /// after releasing this manager's lock and hand them to `set_project_tempo_prestretched`,
+    /// so the audio thread isn't blocked for the length of the time-stretch.
+    pub fn warp_stretches_for_tempo(&self, bpm: f64) -> Vec<WarpStretch> {
+        self.instruments
+            .iter()
+            .filter_map(|(&track_id, inst)| match inst {
+                TrackInstrument::Sampler(sampler) => sampler.warp_stretch_for_tempo(track_id, bpm),
+                TrackInstrument::Synth(_) => None,
+            })
+            .collect()
+    }
+
+    /// Same as `set_project_tempo`, using already built warp stretches where they still
+    /// match the sampler (one whose sample changed in between is stretched here)
+    pub fn set_project_tempo_prestretched(&mut self, bpm: f64, stretches: &[WarpStretch]) {
+        self.project_tempo = bpm;
+        for (&track_id, inst) in &mut self.instruments {
+            if let TrackInstrument::Sampler(sampler) = inst {
+                let stretch = stretches.iter().find(|stretch| stretch.track_id == track_id);
+                sampler.set_project_tempo_prestretched(bpm, stretch);
+            }
         }
     }
 
@@ -474,9 +738,76 @@ impl TrackSynthManager {
         track_id
     }
 
+    /// Add a step sequencer to a track, creating a synth to play it if the track
+    /// has no instrument yet (an existing synth or sampler is kept)
+    pub fn create_step_sequencer(&mut self, track_id: u64) -> u64 {
+        if !self.instruments.contains_key(&track_id) {
+            self.create_synth(track_id);
+        }
+        self.sequencers.entry(track_id).or_default();
+        track_id
+    }
+
+    /// Remove a track's step sequencer (its instrument stays)
+    pub fn remove_step_sequencer(&mut self, track_id: u64) {
+        if self.sequencers.remove(&track_id).is_some() {
+            if let Some(inst) = self.instruments.get_mut(&track_id) {
+                inst.all_notes_off();
+            }
+        }
+    }
+
+    /// Set one step of a track's step sequencer
+    pub fn set_sequencer_step(
+        &mut self,
+        track_id: u64,
+        lane: usize,
+        step: usize,
+        on: bool,
+        velocity: u8,
+    ) -> Result<(), String> {
+        self.sequencers
+            .get_mut(&track_id)
+            .ok_or_else(|| format!("Track {track_id} has no step sequencer"))?
+            .set_step(lane, step, on, velocity)
+    }
+
+    /// Advance a track's step sequencer by one sample and play the notes it produces
+    ///
+    /// `playhead_seconds` is the transport position while playing, None when stopped.
+    pub fn tick_step_sequencer(&mut self, track_id: u64, playhead_seconds: Option<f64>) {
+        let Some(sequencer) = self.sequencers.get_mut(&track_id) else {
+            return;
+        };
+        let beats_per_second = self.project_tempo / 60.0;
+        let frozen = self.frozen.contains(&track_id);
+        let mut instrument = self.instruments.get_mut(&track_id);
+        sequencer.tick(playhead_seconds.map(|seconds| seconds * beats_per_second), |event| {
+            match (instrument.as_deref_mut(), event) {
+                (Some(inst), MidiEventType::NoteOn { note, velocity }) if !frozen => inst.note_on(note, velocity),
+                (Some(inst), MidiEventType::NoteOff { note, .. }) => inst.note_off(note),
+                _ => {}
+            }
+        });
+    }
+
+    /// Get a track's step sequencer pattern for serialization
+    pub fn get_step_sequencer_data(&self, track_id: u64) -> Option<StepSequencerData> {
+        self.sequencers.get(&track_id).map(StepSequencer::get_data)
+    }
+
+    /// Restore a track's step sequencer from saved data
+    pub fn restore_step_sequencer(&mut self, track_id: u64, data: &StepSequencerData) {
+        self.create_step_sequencer(track_id);
+        if let Some(sequencer) = self.sequencers.get_mut(&track_id) {
+            sequencer.restore_data(data);
+        }
+    }
+
     /// Create a sampler for a track
     pub fn create_sampler(&mut self, track_id: u64) -> u64 {
-        let sampler = Sampler::new(self.sample_rate);
+        let mut sampler = Sampler::new(self.sample_rate);
+        sampler.set_project_tempo(self.project_tempo);
         self.instruments.insert(track_id, TrackInstrument::Sampler(sampler));
         println!("✅ Created sampler for track {track_id}");
         track_id
@@ -493,6 +824,17 @@ impl TrackSynthManager {
         }
     }
 
+    /// Add a multisample zone to a sampler track
+    pub fn add_sample_zone(&mut self, track_id: u64, zone: SampleZone) -> bool {
+        if let Some(TrackInstrument::Sampler(sampler)) = self.instruments.get_mut(&track_id) {
+            sampler.add_zone(zone);
+            true
+        } else {
+            println!("⚠️ add_sample_zone: Track {track_id} is not a sampler");
+            false
+        }
+    }
+
     pub fn set_parameter(&mut self, track_id: u64, key: &str, value: &str) {
         if let Some(inst) = self.instruments.get_mut(&track_id) {
             inst.set_parameter(key, value);
@@ -501,7 +843,32 @@ impl TrackSynthManager {
         }
     }
 
+    /// Freeze or unfreeze a track's instrument
+    /// A frozen instrument ignores note-ons and outputs silence until unfrozen
+    pub fn set_instrument_frozen(&mut self, track_id: u64, frozen: bool) {
+        if frozen {
+            if let Some(inst) = self.instruments.get_mut(&track_id) {
+                inst.all_notes_off();
+            }
+            self.frozen.insert(track_id);
+        } else {
+            self.frozen.remove(&track_id);
+        }
+    }
+
+    /// Check if a track's instrument is frozen
+    pub fn is_instrument_frozen(&self, track_id: u64) -> bool {
+        self.frozen.contains(&track_id)
+    }
+
     pub fn note_on(&mut self, track_id: u64, note: u8, velocity: u8) {
+        if self.frozen.contains(&track_id) {
+            return;
+        }
+        if let Some(arp) = self.arpeggiators.get_mut(&track_id).filter(|arp| arp.enabled) {
+            arp.note_on(note, velocity);
+            return;
+        }
         if let Some(inst) = self.instruments.get_mut(&track_id) {
             inst.note_on(note, velocity);
         } else {
@@ -510,12 +877,119 @@ impl TrackSynthManager {
     }
 
     pub fn note_off(&mut self, track_id: u64, note: u8) {
+        if let Some(arp) = self.arpeggiators.get_mut(&track_id).filter(|arp| arp.enabled) {
+            arp.note_off(note);
+            return;
+        }
         if let Some(inst) = self.instruments.get_mut(&track_id) {
             inst.note_off(note);
         }
     }
 
+    /// Enable or disable a track's arpeggiator
+    /// Sounding notes are released so nothing hangs when switching modes
+    pub fn set_arp_enabled(&mut self, track_id: u64, enabled: bool) {
+        let arp = self.arpeggiators.entry(track_id).or_default();
+        arp.enabled = enabled;
+        arp.reset();
+        if let Some(inst) = self.instruments.get_mut(&track_id) {
+            inst.all_notes_off();
+        }
+    }
+
+    /// Set a track's arpeggiator pattern (rate in beats, clamped like `Arpeggiator::set_params`)
+    pub fn set_arp_params(&mut self, track_id: u64, rate_beats: f64, mode: ArpMode, octaves: u8, gate: f32) {
+        self.arpeggiators
+            .entry(track_id)
+            .or_default()
+            .set_params(rate_beats, mode, octaves, gate);
+    }
+
+    /// Advance a track's arpeggiator by one sample and play the notes it produces
+    ///
+    /// `playhead_seconds` is the transport position while playing, None when stopped.
+    pub fn tick_arpeggiator(&mut self, track_id: u64, playhead_seconds: Option<f64>) {
+        let Some(arp) = self.arpeggiators.get_mut(&track_id) else {
+            return;
+        };
+        if !arp.enabled {
+            return;
+        }
+        let beats_per_second = self.project_tempo / 60.0;
+        let events = arp.tick(
+            playhead_seconds.map(|seconds| seconds * beats_per_second),
+            beats_per_second / f64::from(self.sample_rate),
+        );
+        if self.frozen.contains(&track_id) {
+            return;
+        }
+        if let Some(inst) = self.instruments.get_mut(&track_id) {
+            if let Some(note) = events.note_off {
+                inst.note_off(note);
+            }
+            if let Some((note, velocity)) = events.note_on {
+                inst.note_on(note, velocity);
+            }
+        }
+    }
+
+    /// Get arpeggiator settings for serialization
+    pub fn get_arp_parameters(&self, track_id: u64) -> Option<ArpeggiatorData> {
+        self.arpeggiators.get(&track_id).map(Arpeggiator::get_parameters)
+    }
+
+    /// Restore arpeggiator settings from saved data
+    pub fn restore_arp_parameters(&mut self, track_id: u64, data: &ArpeggiatorData) {
+        self.arpeggiators.entry(track_id).or_default().restore_parameters(data);
+    }
+
+    /// Pitch wheel for a track's synth (-8192..=8191); samplers ignore it
+    pub fn pitch_bend(&mut self, track_id: u64, value: i16) {
+        if let Some(TrackInstrument::Synth(synth)) = self.instruments.get_mut(&track_id) {
+            synth.pitch_bend(value);
+        }
+    }
+
+    /// Control change for a track's synth (CC1 = mod wheel); samplers ignore it
+    pub fn control_change(&mut self, track_id: u64, controller: u8, value: u8) {
+        if let Some(TrackInstrument::Synth(synth)) = self.instruments.get_mut(&track_id) {
+            synth.control_change(controller, value);
+        }
+    }
+
+    /// Polyphonic aftertouch for a track's synth; samplers ignore it
+    pub fn poly_aftertouch(&mut self, track_id: u64, note: u8, pressure: u8) {
+        if let Some(TrackInstrument::Synth(synth)) = self.instruments.get_mut(&track_id) {
+            synth.poly_aftertouch(note, pressure);
+        }
+    }
+
+    /// Set the velocity curve of a track's synth or sampler
+    /// Returns false if the track has no instrument
+    pub fn set_velocity_curve(&mut self, track_id: u64, curve: VelocityCurve) -> bool {
+        match self.instruments.get_mut(&track_id) {
+            Some(TrackInstrument::Synth(synth)) => synth.velocity_curve = curve,
+            Some(TrackInstrument::Sampler(sampler)) => sampler.velocity_curve = curve,
+            None => return false,
+        }
+        true
+    }
+
+    /// Set the pitch wheel range of a track's synth (semitones, clamped to 0..24)
+    /// Returns false if the track has no synth
+    pub fn set_pitch_bend_range(&mut self, track_id: u64, semitones: f32) -> bool {
+        if let Some(TrackInstrument::Synth(synth)) = self.instruments.get_mut(&track_id) {
+            synth.pitch_bend_range = semitones.clamp(0.0, MAX_PITCH_BEND_RANGE);
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn process_sample(&mut self, track_id: u64) -> f32 {
+        if self.frozen.contains(&track_id) {
+            return 0.0;
+        }
         if let Some(inst) = self.instruments.get_mut(&track_id) {
             inst.process_sample()
         } else {
@@ -525,6 +999,9 @@ impl TrackSynthManager {
 
     /// Process and return stereo output
     pub fn process_sample_stereo(&mut self, track_id: u64) -> (f32, f32) {
+        if self.frozen.contains(&track_id) {
+            return (0.0, 0.0);
+        }
         if let Some(inst) = self.instruments.get_mut(&track_id) {
             inst.process_sample_stereo()
         } else {
@@ -532,6 +1009,18 @@ impl TrackSynthManager {
         }
     }
 
+    /// Preview-quality variant of `process_sample_stereo` (offline previews only)
+    pub fn process_sample_stereo_preview(&mut self, track_id: u64) -> (f32, f32) {
+        if self.frozen.contains(&track_id) {
+            return (0.0, 0.0);
+        }
+        if let Some(inst) = self.instruments.get_mut(&track_id) {
+            inst.process_sample_stereo_preview()
+        } else {
+            (0.0, 0.0)
+        }
+    }
+
     pub fn has_synth(&self, track_id: u64) -> bool {
         self.instruments.contains_key(&track_id)
     }
@@ -547,12 +1036,24 @@ impl TrackSynthManager {
     }
 
     pub fn all_notes_off(&mut self, track_id: u64) {
+        if let Some(arp) = self.arpeggiators.get_mut(&track_id) {
+            arp.reset();
+        }
+        if let Some(sequencer) = self.sequencers.get_mut(&track_id) {
+            sequencer.reset();
+        }
         if let Some(inst) = self.instruments.get_mut(&track_id) {
             inst.all_notes_off();
         }
     }
 
     pub fn all_notes_off_all_tracks(&mut self) {
+        for arp in self.arpeggiators.values_mut() {
+            arp.reset();
+        }
+        for sequencer in self.sequencers.values_mut() {
+            sequencer.reset();
+        }
         for inst in self.instruments.values_mut() {
             inst.all_notes_off();
         }
@@ -574,6 +1075,9 @@ impl TrackSynthManager {
 
         let mut output = 0.0;
         for (track_id, inst) in &mut self.instruments {
+            if self.frozen.contains(track_id) {
+                continue;
+            }
             let sample = inst.process_sample();
             if sample.abs() > 0.001 {
                 // Debug: only log once per note
@@ -588,6 +1092,9 @@ impl TrackSynthManager {
     }
 
     pub fn remove_synth(&mut self, track_id: u64) -> bool {
+        self.frozen.remove(&track_id);
+        self.arpeggiators.remove(&track_id);
+        self.sequencers.remove(&track_id);
         self.instruments.remove(&track_id).is_some()
     }
 
@@ -597,6 +1104,7 @@ impl TrackSynthManager {
             new_synth.osc_type = source.osc_type;
             new_synth.filter_cutoff = source.filter_cutoff;
             new_synth.envelope = source.envelope;
+            new_synth.unison = source.unison;
             self.instruments.insert(dest_id, TrackInstrument::Synth(new_synth));
             println!("✅ Copied synth from track {source_id} to {dest_id}");
             true
@@ -614,6 +1122,16 @@ impl TrackSynthManager {
         }
     }
 
+    /// Get synth envelope as (attack s, decay s, sustain 0-1, release s)
+    pub fn get_synth_envelope(&self, track_id: u64) -> Option<(f32, f32, f32, f32)> {
+        if let Some(TrackInstrument::Synth(synth)) = self.instruments.get(&track_id) {
+            let env = synth.envelope;
+            Some((env.attack, env.decay, env.sustain, env.release))
+        } else {
+            None
+        }
+    }
+
     /// Get sampler parameters for serialization
     pub fn get_sampler_parameters(&self, track_id: u64) -> Option<SamplerData> {
         if let Some(TrackInstrument::Sampler(sampler)) = self.instruments.get(&track_id) {
@@ -632,6 +1150,11 @@ impl TrackSynthManager {
             synth.set_parameter("decay", &data.decay.to_string());
             synth.set_parameter("sustain", &data.sustain.to_string());
             synth.set_parameter("release", &data.release.to_string());
+            synth.set_parameter("unison_voices", &data.unison_voices.to_string());
+            synth.set_parameter("unison_detune_cents", &data.unison_detune_cents.to_string());
+            synth.set_parameter("pitch_bend_range", &data.pitch_bend_range.to_string());
+            synth.set_parameter("mod_destination", &data.mod_destination);
+            synth.velocity_curve = data.velocity_curve;
             println!("✅ Restored synth parameters for track {}: osc={}", track_id, data.osc_type);
         }
     }
@@ -760,4 +1283,155 @@ mod tests {
         // Should produce some audio (may be 0 during attack phase)
         let _ = manager.process_sample(1);
     }
+
+    #[test]
+    fn test_single_unison_voice_is_centered() {
+        let mut synth = Synth::new(48000.0);
+        synth.envelope.attack = 0.0;
+        synth.note_on(69, 127);
+
+        for _ in 0..256 {
+            let (left, right) = synth.process_sample_stereo();
+            assert!((left - right).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_unison_spreads_stereo_field() {
+        let mut synth = Synth::new(48000.0);
+        synth.envelope.attack = 0.0;
+        synth.set_parameter("unison_voices", "5");
+        synth.set_parameter("unison_detune_cents", "25");
+        assert_eq!(synth.unison.voices, 5);
+        synth.note_on(57, 127);
+
+        let max_side = (0..4800)
+            .map(|_| {
+                let (left, right) = synth.process_sample_stereo();
+                (left - right).abs()
+            })
+            .fold(0.0_f32, f32::max);
+        assert!(max_side > 0.01, "unison output should differ between channels");
+    }
+
+    /// Rising zero crossings of a held note over one second (its frequency in Hz)
+    fn measure_frequency(synth: &mut Synth) -> usize {
+        let mut previous = 0.0_f32;
+        let mut crossings = 0;
+        for _ in 0..48000 {
+            let (sample, _) = synth.process_sample_stereo();
+            if previous < 0.0 && sample >= 0.0 {
+                crossings += 1;
+            }
+            previous = sample;
+        }
+        crossings
+    }
+
+    #[test]
+    fn test_pitch_bend_scales_frequency_by_range() {
+        let mut synth = Synth::new(48000.0);
+        synth.osc_type = OscillatorType::Sine;
+        synth.envelope.attack = 0.0;
+        synth.note_on(69, 127);
+        assert!(measure_frequency(&mut synth).abs_diff(440) <= 1);
+
+        // Full bend up with the default ±2 semitone range: B4
+        synth.pitch_bend(8191);
+        assert!(measure_frequency(&mut synth).abs_diff(494) <= 1);
+
+        // Full bend down an octave
+        synth.set_parameter("pitch_bend_range", "12");
+        synth.pitch_bend(-8192);
+        assert!(measure_frequency(&mut synth).abs_diff(220) <= 1);
+
+        // Stopping re-centres the wheel
+        synth.all_notes_off();
+        synth.note_on(69, 127);
+        assert!(measure_frequency(&mut synth).abs_diff(440) <= 1);
+    }
+
+    #[test]
+    fn test_mod_wheel_opens_filter() {
+        let render_energy = |mod_wheel: u8| {
+            let mut synth = Synth::new(48000.0);
+            synth.envelope.attack = 0.0;
+            synth.filter_cutoff = 0.02;
+            synth.set_parameter("mod_destination", "filter_cutoff");
+            synth.control_change(CC_MOD_WHEEL, mod_wheel);
+            synth.note_on(93, 127);
+            (0..4800).map(|_| synth.process_sample_stereo().0.powi(2)).sum::<f32>()
+        };
+        assert!(render_energy(127) > render_energy(0) * 4.0);
+    }
+
+    #[test]
+    fn test_velocity_curve_sets_voice_level() {
+        let peak_level = |curve: &str, velocity: u8| {
+            let mut synth = Synth::new(48000.0);
+            synth.envelope.attack = 0.0;
+            synth.set_parameter("velocity_curve", curve);
+            synth.note_on(69, velocity);
+            (0..480).map(|_| synth.process_sample_stereo().0.abs()).fold(0.0_f32, f32::max)
+        };
+
+        let soft = peak_level("fixed", 10);
+        let hard = peak_level("fixed", 127);
+        assert!(soft > 0.0 && (soft - hard).abs() < 1e-6, "fixed curve ignores velocity");
+
+        assert!(peak_level("exponential", 40) < peak_level("linear", 40) * 0.5);
+        assert!(peak_level("logarithmic", 40) > peak_level("linear", 40));
+    }
+
+    #[test]
+    fn test_unison_voices_clamped() {
+        let mut synth = Synth::new(48000.0);
+        synth.set_parameter("unison_voices", "12");
+        assert_eq!(synth.unison.voices, 7);
+        synth.set_parameter("unison_voices", "0");
+        assert_eq!(synth.unison.voices, 1);
+    }
+
+    #[test]
+    fn test_restored_envelope_is_clamped() {
+        let mut manager = TrackSynthManager::new(48000.0);
+        manager.create_synth(1);
+        let data = SynthData {
+            attack: -5.0,
+            decay: 3600.0,
+            sustain: 4.0,
+            release: f32::NAN,
+            ..SynthData::default()
+        };
+        manager.restore_synth_parameters(1, &data);
+
+        let (attack, decay, sustain, release) = manager.get_synth_envelope(1).unwrap();
+        assert!((attack - MIN_ENVELOPE_TIME).abs() < 1e-6);
+        assert!((decay - MAX_ENVELOPE_TIME).abs() < 1e-6);
+        assert!((sustain - 1.0).abs() < 1e-6);
+        // NaN is rejected, keeping the default release
+        assert!((release - EnvelopeParams::default().release).abs() < 1e-6);
+        assert!(manager.get_synth_envelope(2).is_none());
+    }
+
+    #[test]
+    fn test_frozen_instrument_is_silent() {
+        let mut manager = TrackSynthManager::new(48000.0);
+        manager.create_synth(1);
+        manager.set_instrument_frozen(1, true);
+        assert!(manager.is_instrument_frozen(1));
+
+        manager.note_on(1, 60, 100);
+        let frozen_peak = (0..480)
+            .map(|_| manager.process_sample_stereo(1).0.abs())
+            .fold(0.0f32, f32::max);
+        assert!(frozen_peak < 1e-6);
+
+        manager.set_instrument_frozen(1, false);
+        manager.note_on(1, 60, 100);
+        let peak = (0..480)
+            .map(|_| manager.process_sample_stereo(1).0.abs())
+            .fold(0.0f32, f32::max);
+        assert!(peak > 1e-3);
+    }
 }
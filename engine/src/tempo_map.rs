@@ -0,0 +1,234 @@
+//! Tempo map: tempo changes over the timeline
+//!
+//! Tempo is piecewise constant: each change holds from its beat until the next one
+//! (no ramps yet; a ritardando is written as a series of steps). Beats are quarter
+//! notes counted from the timeline start, and the first change always sits at beat 0.
+//!
+//! Audio clips, automation and the loop region are placed in timeline seconds, which
+//! are beats at `TIMELINE_REFERENCE_BPM`; the playhead counts real samples. At a
+//! constant 120 BPM the two agree, otherwise the map converts between them.
+
+use crate::audio_file::TARGET_SAMPLE_RATE;
+
+/// Tempo at which one timeline second is one real second
+pub const TIMELINE_REFERENCE_BPM: f64 = 120.0;
+/// Allowed tempo range (BPM)
+pub const MIN_TEMPO_BPM: f64 = 20.0;
+pub const MAX_TEMPO_BPM: f64 = 300.0;
+
+/// Tempo from `beat` on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TempoChange {
+    pub beat: f64,
+    pub bpm: f64,
+}
+
+/// One constant-tempo stretch of the timeline, in real samples
+///
+/// The audio callback keeps the segment under the playhead for a whole buffer and only
+/// goes back to the map when the playhead leaves it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TempoSegment {
+    start_sample: f64,
+    /// Start of the next segment (infinite for the last one)
+    end_sample: f64,
+    start_beat: f64,
+    bpm: f64,
+}
+
+impl TempoSegment {
+    fn new(start_sample: f64, start_beat: f64, bpm: f64) -> Self {
+        Self { start_sample, end_sample: f64::INFINITY, start_beat, bpm }
+    }
+
+    pub fn bpm(&self) -> f64 {
+        self.bpm
+    }
+
+    pub fn samples_per_beat(&self) -> f64 {
+        60.0 / self.bpm * f64::from(TARGET_SAMPLE_RATE)
+    }
+
+    /// Whether `sample` (real samples from the timeline start) falls in this segment
+    pub fn contains(&self, sample: u64) -> bool {
+        let sample = sample as f64;
+        sample >= self.start_sample && sample < self.end_sample
+    }
+
+    pub fn beat_at_sample(&self, sample: u64) -> f64 {
+        self.start_beat + (sample as f64 - self.start_sample) / self.samples_per_beat()
+    }
+
+    pub fn timeline_seconds_at_sample(&self, sample: u64) -> f64 {
+        beats_to_timeline_seconds(self.beat_at_sample(sample))
+    }
+
+    fn sample_at_beat(&self, beat: f64) -> f64 {
+        self.start_sample + (beat - self.start_beat) * self.samples_per_beat()
+    }
+}
+
+/// Timeline seconds of a beat position
+pub fn beats_to_timeline_seconds(beat: f64) -> f64 {
+    beat * 60.0 / TIMELINE_REFERENCE_BPM
+}
+
+/// Beat position of a timeline position (seconds)
+pub fn timeline_seconds_to_beats(timeline_seconds: f64) -> f64 {
+    timeline_seconds * TIMELINE_REFERENCE_BPM / 60.0
+}
+
+/// Tempo changes of a project, sorted by beat
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoMap {
+    changes: Vec<TempoChange>,
+}
+
+impl Default for TempoMap {
+    fn default() -> Self {
+        Self::new(TIMELINE_REFERENCE_BPM)
+    }
+}
+
+impl TempoMap {
+    /// A constant tempo
+    pub fn new(bpm: f64) -> Self {
+        Self { changes: vec![TempoChange { beat: 0.0, bpm: clamp_tempo(bpm) }] }
+    }
+
+    /// Tempo at the timeline start (the project tempo)
+    pub fn base_tempo(&self) -> f64 {
+        self.changes[0].bpm
+    }
+
+    /// Set the tempo at the timeline start; later changes are kept
+    pub fn set_base_tempo(&mut self, bpm: f64) {
+        self.changes[0].bpm = clamp_tempo(bpm);
+    }
+
+    /// Change the tempo from `beat` on (replacing a change at the same beat)
+    /// Beats at or before 0 set the base tempo.
+    pub fn add_change(&mut self, beat: f64, bpm: f64) {
+        let bpm = clamp_tempo(bpm);
+        if beat <= 0.0 {
+            self.changes[0].bpm = bpm;
+            return;
+        }
+        match self.changes.binary_search_by(|change| change.beat.total_cmp(&beat)) {
+            Ok(index) => self.changes[index].bpm = bpm,
+            Err(index) => self.changes.insert(index, TempoChange { beat, bpm }),
+        }
+    }
+
+    /// Remove the change at `beat`; the base tempo can't be removed
+    pub fn remove_change(&mut self, beat: f64) -> bool {
+        let Some(index) = self.changes.iter().skip(1).position(|change| (change.beat - beat).abs() < 1e-9) else {
+            return false;
+        };
+        self.changes.remove(index + 1);
+        true
+    }
+
+    /// All changes, starting with the base tempo at beat 0
+    pub fn changes(&self) -> &[TempoChange] {
+        &self.changes
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.changes.len() == 1
+    }
+
+    /// Tempo in effect at `beat`
+    pub fn tempo_at_beat(&self, beat: f64) -> f64 {
+        self.changes.iter().rev().find(|change| change.beat <= beat).map_or(self.base_tempo(), |change| change.bpm)
+    }
+
+    /// Constant-tempo segments in timeline order
+    fn segments(&self) -> impl Iterator<Item = TempoSegment> + '_ {
+        let mut start_sample = 0.0;
+        self.changes.iter().enumerate().map(move |(index, change)| {
+            let mut segment = TempoSegment::new(start_sample, change.beat, change.bpm);
+            if let Some(next) = self.changes.get(index + 1) {
+                segment.end_sample = segment.sample_at_beat(next.beat);
+                start_sample = segment.end_sample;
+            }
+            segment
+        })
+    }
+
+    /// Segment containing `sample` (real samples from the timeline start)
+    pub fn segment_at_sample(&self, sample: u64) -> TempoSegment {
+        let position = sample as f64;
+        self.segments()
+            .find(|segment| position < segment.end_sample)
+            .expect("the last segment never ends")
+    }
+
+    fn segment_at_beat(&self, beat: f64) -> TempoSegment {
+        let last = self.changes.iter().rposition(|change| change.beat <= beat).unwrap_or(0);
+        self.segments().nth(last).expect("one segment per change")
+    }
+
+    /// Real samples from the timeline start to `beat`
+    pub fn sample_at_beat(&self, beat: f64) -> f64 {
+        self.segment_at_beat(beat).sample_at_beat(beat)
+    }
+
+    /// Beat position at `sample` (real samples from the timeline start)
+    pub fn beat_at_sample(&self, sample: u64) -> f64 {
+        self.segment_at_sample(sample).beat_at_sample(sample)
+    }
+
+    /// Timeline position (seconds) at `sample`
+    pub fn timeline_seconds_at_sample(&self, sample: u64) -> f64 {
+        beats_to_timeline_seconds(self.beat_at_sample(sample))
+    }
+
+    /// Real samples from the timeline start to a timeline position (seconds)
+    pub fn sample_at_timeline_seconds(&self, timeline_seconds: f64) -> u64 {
+        self.sample_at_beat(timeline_seconds_to_beats(timeline_seconds)).max(0.0) as u64
+    }
+}
+
+fn clamp_tempo(bpm: f64) -> f64 {
+    bpm.clamp(MIN_TEMPO_BPM, MAX_TEMPO_BPM)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_beats_to_samples_across_a_tempo_change() {
+        let sr = f64::from(TARGET_SAMPLE_RATE);
+        // 4 beats at 120 BPM (2 s), then 60 BPM (1 s per beat)
+        let mut map = TempoMap::new(120.0);
+        map.add_change(4.0, 60.0);
+        assert!(!map.is_constant());
+
+        assert!((map.sample_at_beat(2.0) - sr).abs() < 1e-6);
+        assert!((map.sample_at_beat(4.0) - 2.0 * sr).abs() < 1e-6);
+        assert!((map.sample_at_beat(6.0) - 4.0 * sr).abs() < 1e-6);
+        assert!((map.beat_at_sample((3.0 * sr) as u64) - 5.0).abs() < 1e-9);
+        assert!((map.tempo_at_beat(3.9) - 120.0).abs() < 1e-9);
+        assert!((map.tempo_at_beat(4.0) - 60.0).abs() < 1e-9);
+
+        // Timeline seconds are beats at 120 BPM: beat 6 is 3 s, reached after 4 real seconds
+        assert!((map.timeline_seconds_at_sample((4.0 * sr) as u64) - 3.0).abs() < 1e-9);
+        assert_eq!(map.sample_at_timeline_seconds(3.0), (4.0 * sr) as u64);
+
+        let segment = map.segment_at_sample((2.0 * sr) as u64 - 1);
+        assert!((segment.bpm() - 120.0).abs() < 1e-9);
+        assert!(!segment.contains((2.0 * sr) as u64));
+        assert!(map.segment_at_sample((2.0 * sr) as u64).contains((2.0 * sr) as u64));
+
+        // Replacing and removing changes; the base tempo always stays
+        map.add_change(4.0, 240.0);
+        assert!((map.sample_at_beat(8.0) - 3.0 * sr).abs() < 1e-6);
+        assert!(map.remove_change(4.0));
+        assert!(!map.remove_change(0.0));
+        assert!(map.is_constant());
+        map.add_change(-1.0, 1000.0);
+        assert!((map.base_tempo() - MAX_TEMPO_BPM).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,198 @@
+//! Time-signature map: meter changes over the timeline
+//!
+//! Each change holds from the start of its bar until the next one. Bars are counted from
+//! the timeline start (bar 0 is the first bar), and the first change always sits at bar 0.
+//! Bar positions are measured in quarter-note beats, the unit of the tempo map, so a 6/8
+//! bar is 3 beats long and its metronome clicks fall on the eighth notes.
+
+/// Largest numerator accepted (beats per bar)
+pub const MAX_TIME_SIG_NUMERATOR: u32 = 32;
+/// Largest denominator accepted (note value of one beat)
+pub const MAX_TIME_SIG_DENOMINATOR: u32 = 32;
+
+/// Meter of a bar, e.g. 6/8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSignature {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Default for TimeSignature {
+    fn default() -> Self {
+        Self { numerator: 4, denominator: 4 }
+    }
+}
+
+impl TimeSignature {
+    /// Validate a signature: 1-32 beats of a whole, half, quarter, ... 32nd note
+    pub fn new(numerator: u32, denominator: u32) -> Result<Self, String> {
+        if !(1..=MAX_TIME_SIG_NUMERATOR).contains(&numerator) {
+            return Err(format!("Time signature numerator must be 1-{MAX_TIME_SIG_NUMERATOR}, got {numerator}"));
+        }
+        if !denominator.is_power_of_two() || denominator > MAX_TIME_SIG_DENOMINATOR {
+            return Err(format!("Time signature denominator must be a power of two up to {MAX_TIME_SIG_DENOMINATOR}, got {denominator}"));
+        }
+        Ok(Self { numerator, denominator })
+    }
+
+    /// Length of one beat (one metronome click) in quarter notes
+    pub fn quarter_notes_per_beat(&self) -> f64 {
+        4.0 / f64::from(self.denominator)
+    }
+
+    /// Length of one bar in quarter notes
+    pub fn quarter_notes_per_bar(&self) -> f64 {
+        f64::from(self.numerator) * self.quarter_notes_per_beat()
+    }
+}
+
+/// Signature from the start of `bar` on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSignatureChange {
+    pub bar: u32,
+    pub signature: TimeSignature,
+}
+
+/// Bar containing a beat position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BarPosition {
+    pub bar: u32,
+    /// Quarter-note beat the bar starts on
+    pub start_beat: f64,
+    pub signature: TimeSignature,
+}
+
+/// Time-signature changes of a project, sorted by bar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeSignatureMap {
+    changes: Vec<TimeSignatureChange>,
+}
+
+impl Default for TimeSignatureMap {
+    fn default() -> Self {
+        Self::new(TimeSignature::default())
+    }
+}
+
+impl TimeSignatureMap {
+    /// A constant signature
+    pub fn new(signature: TimeSignature) -> Self {
+        Self { changes: vec![TimeSignatureChange { bar: 0, signature }] }
+    }
+
+    /// Signature at the timeline start (the project signature)
+    pub fn base_signature(&self) -> TimeSignature {
+        self.changes[0].signature
+    }
+
+    /// Set the signature at the timeline start; later changes are kept
+    pub fn set_base_signature(&mut self, signature: TimeSignature) {
+        self.changes[0].signature = signature;
+    }
+
+    /// Change the signature from `bar` on (replacing a change at the same bar)
+    /// Bar 0 sets the base signature.
+    pub fn add_change(&mut self, bar: u32, signature: TimeSignature) {
+        match self.changes.binary_search_by_key(&bar, |change| change.bar) {
+            Ok(index) => self.changes[index].signature = signature,
+            Err(index) => self.changes.insert(index, TimeSignatureChange { bar, signature }),
+        }
+    }
+
+    /// Remove the change at `bar`; the base signature can't be removed
+    pub fn remove_change(&mut self, bar: u32) -> bool {
+        match self.changes.binary_search_by_key(&bar, |change| change.bar) {
+            Ok(index) if index > 0 => {
+                self.changes.remove(index);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// All changes, starting with the base signature at bar 0
+    pub fn changes(&self) -> &[TimeSignatureChange] {
+        &self.changes
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.changes.len() == 1
+    }
+
+    /// Changes paired with the quarter-note beat each one starts on
+    fn change_starts(&self) -> impl Iterator<Item = (TimeSignatureChange, f64)> + '_ {
+        let mut previous: Option<(TimeSignatureChange, f64)> = None;
+        self.changes.iter().map(move |&change| {
+            let start_beat = previous.map_or(0.0, |(last, last_start)| {
+                last_start + f64::from(change.bar - last.bar) * last.signature.quarter_notes_per_bar()
+            });
+            previous = Some((change, start_beat));
+            (change, start_beat)
+        })
+    }
+
+    /// Quarter-note beat each change starts on, in change order (for drawing bar lines)
+    pub fn change_start_beats(&self) -> Vec<f64> {
+        self.change_starts().map(|(_, start_beat)| start_beat).collect()
+    }
+
+    /// Signature in effect in `bar`
+    pub fn signature_at_bar(&self, bar: u32) -> TimeSignature {
+        self.changes.iter().rev().find(|change| change.bar <= bar).map_or(self.base_signature(), |change| change.signature)
+    }
+
+    /// Quarter-note beat `bar` starts on
+    pub fn bar_start_beat(&self, bar: u32) -> f64 {
+        let (change, start_beat) = self
+            .change_starts()
+            .take_while(|(change, _)| change.bar <= bar)
+            .last()
+            .expect("the base signature starts at bar 0");
+        start_beat + f64::from(bar - change.bar) * change.signature.quarter_notes_per_bar()
+    }
+
+    /// Bar containing `beat` (quarter notes from the timeline start; negative is bar 0)
+    pub fn position_at_beat(&self, beat: f64) -> BarPosition {
+        let beat = beat.max(0.0);
+        let (change, start_beat) = self
+            .change_starts()
+            .take_while(|&(_, start_beat)| start_beat <= beat)
+            .last()
+            .expect("the base signature starts at beat 0");
+        let bar_length = change.signature.quarter_notes_per_bar();
+        let bars_in = ((beat - start_beat) / bar_length).floor();
+        BarPosition {
+            bar: change.bar + bars_in as u32,
+            start_beat: start_beat + bars_in * bar_length,
+            signature: change.signature,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bars_follow_signature_changes() {
+        // Two bars of 4/4 (8 beats), then 6/8 (3 beats per bar)
+        let mut map = TimeSignatureMap::default();
+        map.add_change(2, TimeSignature::new(6, 8).unwrap());
+        assert!(!map.is_constant());
+        assert_eq!(map.change_start_beats(), [0.0, 8.0]);
+
+        assert!((map.bar_start_beat(1) - 4.0).abs() < 1e-9);
+        assert!((map.bar_start_beat(3) - 11.0).abs() < 1e-9);
+        let position = map.position_at_beat(12.5);
+        assert_eq!((position.bar, position.signature.numerator), (3, 6));
+        assert!((position.start_beat - 11.0).abs() < 1e-9);
+        assert_eq!(map.position_at_beat(7.99).bar, 1);
+        assert_eq!(map.signature_at_bar(1), TimeSignature::default());
+
+        assert!(TimeSignature::new(0, 4).is_err());
+        assert!(TimeSignature::new(7, 6).is_err());
+        assert!(map.remove_change(2));
+        assert!(!map.remove_change(0));
+        assert!(map.is_constant());
+    }
+}
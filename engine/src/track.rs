@@ -16,6 +16,26 @@ pub type TrackId = u64;
 /// Unique identifier for clips (both audio and MIDI)
 pub type ClipId = u64;
 
+/// Track input trim range (dB)
+pub const MIN_INPUT_GAIN_DB: f32 = -24.0;
+pub const MAX_INPUT_GAIN_DB: f32 = 24.0;
+
+/// Sum a stereo sample to mono (average of L and R) and/or negate it
+#[inline]
+pub fn apply_channel_utilities(left: f32, right: f32, mono_sum: bool, invert: bool) -> (f32, f32) {
+    let (left, right) = if mono_sum {
+        let mono = (left + right) * 0.5;
+        (mono, mono)
+    } else {
+        (left, right)
+    };
+    if invert {
+        (-left, -right)
+    } else {
+        (left, right)
+    }
+}
+
 /// Represents an audio clip placed on a track's timeline
 #[derive(Clone)]
 pub struct TimelineClip {
@@ -36,15 +56,31 @@ pub struct TimelineClip {
     pub stretch_factor: f32,
     /// Warp algorithm mode: 0 = warp (pitch preserved), 1 = repitch (pitch follows speed)
     pub warp_mode: u8,
-    /// Cached stretched audio for Warp mode (pitch-preserved time-stretching)
-    /// Only used when `warp_enabled=true` AND `warp_mode=0` (Warp)
+    /// Cached stretched audio for Warp mode (pitch-preserved time-stretching), transposed
+    /// by the clip's pitch ratio. Used when `warp_enabled=true` AND `warp_mode=0` (Warp),
+    /// or when the clip is transposed (then only pitch-shifted, at its original length)
     pub stretched_cache: Option<Arc<AudioClip>>,
     /// Stretch factor used when the cache was built (to detect when rebuild is needed)
     pub cached_stretch_factor: f32,
+    /// Pitch ratio used when the cache was built
+    pub cached_pitch_ratio: f32,
     /// Transpose in semitones (-48 to +48)
     pub transpose_semitones: i32,
     /// Fine pitch adjustment in cents (-50 to +50)
     pub transpose_cents: i32,
+    /// Invert polarity (negate samples) during playback
+    pub polarity_inverted: bool,
+    /// Sum stereo channels to mono (average of L and R) during playback
+    pub mono_sum: bool,
+    /// Fade-in length in seconds from the clip's timeline start (0.0 = no fade)
+    pub fade_in_seconds: f64,
+    /// Fade-out length in seconds before the clip's timeline end (0.0 = no fade)
+    pub fade_out_seconds: f64,
+    /// Play the clip's active region backwards (non-destructive)
+    pub reversed: bool,
+    /// Gain envelope in dB on top of `gain_db` (time in seconds from the clip's
+    /// timeline start); empty = no envelope
+    pub gain_envelope: Vec<AutomationPoint>,
 
     // --- Clip-based Automation ---
     /// Volume automation curve (time in beats relative to clip start)
@@ -56,6 +92,34 @@ pub struct TimelineClip {
 }
 
 impl TimelineClip {
+    /// Create a timeline clip with default settings (full length, unity gain, no warp)
+    pub fn new(id: ClipId, clip: Arc<AudioClip>, start_time: f64) -> Self {
+        Self {
+            id,
+            clip,
+            start_time,
+            offset: 0.0,
+            duration: None,
+            gain_db: 0.0,
+            warp_enabled: false,
+            stretch_factor: 1.0,
+            warp_mode: 0,
+            stretched_cache: None,
+            cached_stretch_factor: 0.0,
+            cached_pitch_ratio: 0.0,
+            transpose_semitones: 0,
+            transpose_cents: 0,
+            polarity_inverted: false,
+            mono_sum: false,
+            fade_in_seconds: 0.0,
+            fade_out_seconds: 0.0,
+            reversed: false,
+            gain_envelope: Vec::new(),
+            volume_automation: Vec::new(),
+            pan_automation: Vec::new(),
+        }
+    }
+
     /// Convert clip gain from dB to linear
     /// -70 dB → 0.0 (silent)
     /// 0 dB → 1.0 (unity)
@@ -75,24 +139,72 @@ impl TimelineClip {
         2_f32.powf(total_semitones / 12.0)
     }
 
-    /// Rebuild the stretched audio cache for Warp mode (pitch-preserved time-stretching).
-    /// Call this when warp settings change (`warp_enabled`, `stretch_factor`, `warp_mode`).
-    pub fn rebuild_stretched_cache(&mut self) {
-        use crate::stretch::stretch_audio_preserve_pitch;
+    /// Linear gain of the gain envelope at `time_from_start` seconds into the clip
+    /// (on the timeline). Returns 1.0 without an envelope.
+    pub fn get_envelope_gain(&self, time_from_start: f64) -> f32 {
+        crate::audio_graph::interpolate_automation_gain(&self.gain_envelope, time_from_start)
+    }
 
-        // Only build cache for Warp mode (warp_mode=0) when warp is enabled
-        if self.warp_enabled && self.warp_mode == 0 {
-            // Check if we need to rebuild (stretch factor changed)
+    /// Set the gain envelope from a CSV string
+    /// Format: "time,db;time,db;..." where time is in seconds from the clip's start
+    /// Empty string clears the envelope; -96 dB and below is silence
+    pub fn set_gain_envelope_csv(&mut self, csv: &str) {
+        self.gain_envelope = AutomationPoint::parse_csv(csv);
+        for point in &mut self.gain_envelope {
+            point.value = point.value.min(24.0);
+        }
+    }
+
+    /// Equal-power fade gain at `time_from_start` seconds into a clip of `length` seconds
+    /// (both measured on the timeline). Returns 1.0 outside the fade regions.
+    pub fn get_fade_gain(&self, time_from_start: f64, length: f64) -> f32 {
+        let mut gain = 1.0_f64;
+        if self.fade_in_seconds > 0.0 && time_from_start < self.fade_in_seconds {
+            gain *= (time_from_start / self.fade_in_seconds * std::f64::consts::FRAC_PI_2).sin();
+        }
+        let time_to_end = length - time_from_start;
+        if self.fade_out_seconds > 0.0 && time_to_end < self.fade_out_seconds {
+            gain *= (time_to_end / self.fade_out_seconds * std::f64::consts::FRAC_PI_2).sin();
+        }
+        gain.max(0.0) as f32
+    }
+
+    /// Apply mono summing and polarity inversion to a stereo sample from this clip
+    pub fn apply_channel_utilities(&self, left: f32, right: f32) -> (f32, f32) {
+        apply_channel_utilities(left, right, self.mono_sum, self.polarity_inverted)
+    }
+
+    /// Whether the clip is transposed (its pitch ratio is not 1.0)
+    pub fn is_transposed(&self) -> bool {
+        self.transpose_semitones != 0 || self.transpose_cents != 0
+    }
+
+    /// Rebuild the stretched audio cache for Warp mode (pitch-preserved time-stretching)
+    /// and transposition. Call this when warp settings change (`warp_enabled`,
+    /// `stretch_factor`, `warp_mode`) or the transpose changes.
+    pub fn rebuild_stretched_cache(&mut self) {
+        use crate::stretch::stretch_and_transpose_audio;
+
+        // Warp mode (warp_mode=0) stretches the cache; a transposed clip is only pitch-shifted
+        // Streamed clips never get a cache: it would hold the whole file in memory
+        let warp_stretch = self.warp_enabled && self.warp_mode == 0;
+        if self.clip.is_streaming() {
+            self.clear_stretched_cache();
+        } else if warp_stretch || self.is_transposed() {
+            let stretch_factor = if warp_stretch { self.stretch_factor } else { 1.0 };
+            let pitch_ratio = self.get_pitch_ratio();
+            // Check if we need to rebuild (stretch factor or pitch changed)
             if self.stretched_cache.is_none()
-                || (self.cached_stretch_factor - self.stretch_factor).abs() > 0.001
+                || (self.cached_stretch_factor - stretch_factor).abs() > 0.001
+                || (self.cached_pitch_ratio - pitch_ratio).abs() > 0.0001
             {
-                self.stretched_cache = Some(stretch_audio_preserve_pitch(&self.clip, self.stretch_factor));
-                self.cached_stretch_factor = self.stretch_factor;
+                self.stretched_cache = Some(stretch_and_transpose_audio(&self.clip, stretch_factor, pitch_ratio));
+                self.cached_stretch_factor = stretch_factor;
+                self.cached_pitch_ratio = pitch_ratio;
             }
         } else {
-            // Clear cache for Re-Pitch mode or when warp is disabled
-            self.stretched_cache = None;
-            self.cached_stretch_factor = 0.0;
+            // Clear cache for Re-Pitch mode or when warp is disabled (and not transposed)
+            self.clear_stretched_cache();
         }
     }
 
@@ -100,6 +212,111 @@ impl TimelineClip {
     pub fn clear_stretched_cache(&mut self) {
         self.stretched_cache = None;
         self.cached_stretch_factor = 0.0;
+        self.cached_pitch_ratio = 0.0;
+    }
+
+    /// Length of the clip on the timeline in seconds (after warp stretching)
+    pub fn timeline_duration(&self) -> f64 {
+        let clip_duration = self.duration.unwrap_or(self.clip.duration_seconds);
+        if self.warp_enabled {
+            clip_duration / f64::from(self.stretch_factor)
+        } else {
+            clip_duration
+        }
+    }
+
+    /// Source frames within the clip's active region (`offset..offset + duration`)
+    pub fn active_region_frames(&self) -> std::ops::Range<usize> {
+        let sample_rate = f64::from(self.clip.sample_rate);
+        let clip_duration = self.duration.unwrap_or(self.clip.duration_seconds);
+        let frame_count = self.clip.frame_count();
+        let start = ((self.offset * sample_rate) as usize).min(frame_count);
+        let end = (((self.offset + clip_duration) * sample_rate) as usize).clamp(start, frame_count);
+        start..end
+    }
+
+    /// Absolute peak of the source audio within the clip's active region,
+    /// before clip gain
+    pub fn active_region_peak(&self) -> f32 {
+        let region = self.active_region_frames();
+        self.clip.peak(region.start, region.len())
+    }
+
+    /// Split the clip in two at `split_time` (timeline seconds)
+    ///
+    /// Both halves share the underlying audio and keep the clip's settings; the
+    /// fade-in stays on the first half and the fade-out on the second. The gain
+    /// envelope and the clip's volume and pan automation are split with the audio.
+    /// Returns None if `split_time` is not strictly inside the clip.
+    pub fn split_at(&self, split_time: f64, first_id: ClipId, second_id: ClipId) -> Option<(Self, Self)> {
+        let timeline_duration = self.timeline_duration();
+        let split_elapsed = split_time - self.start_time;
+        if split_elapsed <= 0.0 || split_elapsed >= timeline_duration {
+            return None;
+        }
+
+        // `offset` and `duration` are in source time
+        let clip_duration = self.duration.unwrap_or(self.clip.duration_seconds);
+        let source_split = if self.warp_enabled {
+            split_elapsed * f64::from(self.stretch_factor)
+        } else {
+            split_elapsed
+        };
+        let remaining = clip_duration - source_split;
+
+        let mut first = self.clone();
+        first.id = first_id;
+        first.duration = Some(source_split);
+        first.fade_in_seconds = self.fade_in_seconds.min(split_elapsed);
+        first.fade_out_seconds = 0.0;
+
+        let mut second = self.clone();
+        second.id = second_id;
+        second.start_time = split_time;
+        second.duration = Some(remaining);
+        second.fade_in_seconds = 0.0;
+        second.fade_out_seconds = self.fade_out_seconds.min(timeline_duration - split_elapsed);
+        let split_beat = crate::tempo_map::timeline_seconds_to_beats(split_elapsed);
+        second.volume_automation = Self::split_clip_automation(&self.volume_automation, split_beat);
+        second.pan_automation = Self::split_clip_automation(&self.pan_automation, split_beat);
+        if !self.gain_envelope.is_empty() {
+            // Re-time the envelope to the second half, starting from its level at the split
+            let split_db = crate::audio_graph::interpolate_automation_value(&self.gain_envelope, split_elapsed);
+            second.gain_envelope = std::iter::once(AutomationPoint::new(0.0, split_db))
+                .chain(
+                    self.gain_envelope
+                        .iter()
+                        .filter(|point| point.time_seconds > split_elapsed)
+                        .map(|point| AutomationPoint::new(point.time_seconds - split_elapsed, point.value)),
+                )
+                .collect();
+        }
+
+        // A reversed clip plays the end of its region first
+        if self.reversed {
+            first.offset = self.offset + remaining;
+        } else {
+            second.offset = self.offset + source_split;
+        }
+
+        Some((first, second))
+    }
+
+    /// Clip automation after `split_beat`, re-timed to the second half's start and
+    /// beginning from its level at the split (empty stays empty)
+    fn split_clip_automation(points: &[ClipAutomationPoint], split_beat: f64) -> Vec<ClipAutomationPoint> {
+        if points.is_empty() {
+            return Vec::new();
+        }
+        let split_value = Self::interpolate_clip_automation(points, split_beat, 0.0);
+        std::iter::once(ClipAutomationPoint::new(0.0, split_value))
+            .chain(
+                points
+                    .iter()
+                    .filter(|point| point.time_beats > split_beat)
+                    .map(|point| ClipAutomationPoint::new(point.time_beats - split_beat, point.value)),
+            )
+            .collect()
     }
 
     /// Check if clip has volume automation
@@ -258,6 +475,37 @@ impl TimelineMidiClip {
     }
 }
 
+/// Where in a track's signal chain its meter reads
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MeteringPoint {
+    /// Clips, instrument and input before the FX chain
+    PreFx,
+    /// After the FX chain, before volume and pan
+    PostFxPreFader,
+    /// The track's output
+    #[default]
+    PostFader,
+}
+
+impl MeteringPoint {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pre_fx" => Some(Self::PreFx),
+            "post_fx_pre_fader" => Some(Self::PostFxPreFader),
+            "post_fader" => Some(Self::PostFader),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PreFx => "pre_fx",
+            Self::PostFxPreFader => "post_fx_pre_fader",
+            Self::PostFader => "post_fader",
+        }
+    }
+}
+
 /// Track types supported in Boojy Audio
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TrackType {
@@ -292,14 +540,38 @@ pub struct Send {
 pub struct AutomationPoint {
     /// Time position in seconds
     pub time_seconds: f64,
-    /// Value in dB (for volume automation)
-    pub value_db: f32,
+    /// Value (dB for volume automation, -1.0 to +1.0 for pan automation)
+    pub value: f32,
 }
 
 impl AutomationPoint {
     /// Create a new automation point
-    pub fn new(time_seconds: f64, value_db: f32) -> Self {
-        Self { time_seconds, value_db }
+    pub fn new(time_seconds: f64, value: f32) -> Self {
+        Self { time_seconds, value }
+    }
+
+    /// Parse an automation curve from a CSV string
+    /// Format: "time,value;time,value;..." where time is in seconds
+    /// Malformed pairs are skipped; the result is sorted by time
+    pub fn parse_csv(csv: &str) -> Vec<Self> {
+        let mut points: Vec<Self> = csv
+            .split(';')
+            .filter_map(|pair| {
+                let parts: Vec<&str> = pair.split(',').collect();
+                if parts.len() == 2 {
+                    if let (Ok(time), Ok(value)) = (parts[0].parse::<f64>(), parts[1].parse::<f32>()) {
+                        return Some(Self::new(time, value));
+                    }
+                }
+                None
+            })
+            .collect();
+
+        // Sort by time (should already be sorted, but ensure it)
+        points.sort_by(|a, b| {
+            a.time_seconds.partial_cmp(&b.time_seconds).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        points
     }
 }
 
@@ -319,6 +591,19 @@ impl ClipAutomationPoint {
     }
 }
 
+/// Original contents of a frozen track, restored on unfreeze
+#[derive(Clone)]
+pub struct FrozenTrackState {
+    /// Audio clips the track had before freezing
+    pub audio_clips: Vec<TimelineClip>,
+    /// MIDI clips the track had before freezing
+    pub midi_clips: Vec<TimelineMidiClip>,
+    /// Effect chain the track had before freezing
+    pub fx_chain: Vec<EffectId>,
+    /// ID of the rendered audio clip that replaces them while frozen
+    pub clip_id: ClipId,
+}
+
 /// A track in the DAW
 pub struct Track {
     /// Unique ID
@@ -344,6 +629,12 @@ pub struct Track {
     pub mute: bool,
     /// Solo state
     pub solo: bool,
+    /// Keep playing while other tracks are soloed (on by default for returns)
+    pub solo_safe: bool,
+    /// Invert polarity (negate both channels) before volume/pan
+    pub phase_invert: bool,
+    /// Sum to mono (average of L and R) before volume/pan
+    pub mono_sum: bool,
 
     // --- Routing ---
     /// Send destinations (to Return tracks)
@@ -354,22 +645,32 @@ pub struct Track {
     // --- Effects ---
     /// Effect chain (processed in order)
     pub fx_chain: Vec<EffectId>,
+    /// Keep the built-in synth playing under a VST3 instrument in the chain
+    pub layer_synth: bool,
 
     // --- Recording ---
     /// Armed for recording (Audio/MIDI tracks only)
     pub armed: bool,
     /// Input monitoring enabled (hear input through track when armed)
     pub input_monitoring: bool,
+    /// Monitor input through the FX chain (monitoring only — recordings stay dry)
+    pub monitor_through_fx: bool,
     /// Fade gain for monitoring transitions (0.0-1.0, avoids clicks on arm/disarm)
     pub monitoring_fade_gain: f64,
+    /// Input trim in dB (-24 to +24), applied before monitoring, metering and recording
+    pub input_gain_db: f32,
 
     // --- Input Routing ---
     /// Audio input device index (None = no input assigned)
     pub input_device_index: Option<usize>,
-    /// Audio input channel within the device (0-based, mono)
+    /// First audio input channel within the device (0-based)
     pub input_channel: u32,
+    /// Input channels recorded: 1 = mono, 2 = stereo pair (`input_channel` and the next)
+    pub input_channel_count: u32,
 
     // --- Metering ---
+    /// Signal point the peak meters follow
+    pub metering_point: MeteringPoint,
     /// Peak level for left channel (for meters)
     pub peak_left: f32,
     /// Peak level for right channel
@@ -379,6 +680,13 @@ pub struct Track {
     /// Volume automation curve (sorted by `time_seconds`)
     /// When not empty, overrides static `volume_db` during playback
     pub volume_automation: Vec<AutomationPoint>,
+    /// Pan automation curve (sorted by `time_seconds`, values -1.0 to +1.0)
+    /// When not empty, overrides static `pan` during playback
+    pub pan_automation: Vec<AutomationPoint>,
+
+    // --- Freeze ---
+    /// Original clips and FX while the track is frozen (None = not frozen)
+    pub frozen: Option<FrozenTrackState>,
 }
 
 impl Track {
@@ -404,17 +712,27 @@ impl Track {
             pan: 0.0,        // Center
             mute: false,
             solo: false,
+            solo_safe: track_type == TrackType::Return,
+            phase_invert: false,
+            mono_sum: false,
             sends: Vec::new(),
             parent_group: None,
             fx_chain: Vec::new(),
+            layer_synth: false,
             armed,
             input_monitoring: armed,
+            monitor_through_fx: true,
             monitoring_fade_gain: if armed { 1.0 } else { 0.0 },
+            input_gain_db: 0.0,
             input_device_index,
             input_channel: 0,
+            input_channel_count: 1,
+            metering_point: MeteringPoint::default(),
             peak_left: 0.0,
             peak_right: 0.0,
             volume_automation: Vec::new(),
+            pan_automation: Vec::new(),
+            frozen: None,
         }
     }
 
@@ -430,6 +748,88 @@ impl Track {
         }
     }
 
+    /// Set input trim, clamped to -24..+24 dB
+    pub fn set_input_gain_db(&mut self, gain_db: f32) {
+        if gain_db.is_finite() {
+            self.input_gain_db = gain_db.clamp(MIN_INPUT_GAIN_DB, MAX_INPUT_GAIN_DB);
+        }
+    }
+
+    /// Input trim as linear gain
+    pub fn get_input_gain(&self) -> f32 {
+        10_f32.powf(self.input_gain_db / 20.0)
+    }
+
+    /// Whether MIDI for this track should reach the built-in synth
+    ///
+    /// A VST3 instrument in the chain replaces the synth unless `layer_synth` is set;
+    /// effect plugins (EQ, etc.) never do.
+    pub fn plays_builtin_synth(&self, chain_has_instrument: bool) -> bool {
+        !chain_has_instrument || self.layer_synth
+    }
+
+    /// Replace the FX chain order
+    ///
+    /// `new_order` must contain exactly the effects already in the chain, each once;
+    /// otherwise the chain is left untouched so no effect is dropped or duplicated.
+    pub fn reorder_fx_chain(&mut self, new_order: Vec<EffectId>) -> Result<(), String> {
+        let mut seen = std::collections::HashSet::with_capacity(new_order.len());
+        for id in &new_order {
+            if !seen.insert(*id) {
+                return Err(format!("Effect {id} listed more than once"));
+            }
+            if !self.fx_chain.contains(id) {
+                return Err(format!("Effect {id} not found in track {}'s FX chain", self.id));
+            }
+        }
+
+        let missing: Vec<String> = self.fx_chain.iter()
+            .filter(|id| !seen.contains(id))
+            .map(ToString::to_string)
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("New order is missing effect(s) {}", missing.join(", ")));
+        }
+
+        self.fx_chain = new_order;
+        Ok(())
+    }
+
+    /// Replace the track's clips and FX chain with a rendered clip
+    ///
+    /// The originals are kept in `frozen` until `unfreeze` puts them back.
+    pub fn freeze(&mut self, rendered: TimelineClip) -> Result<(), String> {
+        if self.frozen.is_some() {
+            return Err(format!("Track {} is already frozen", self.id));
+        }
+        self.frozen = Some(FrozenTrackState {
+            audio_clips: std::mem::take(&mut self.audio_clips),
+            midi_clips: std::mem::take(&mut self.midi_clips),
+            fx_chain: std::mem::take(&mut self.fx_chain),
+            clip_id: rendered.id,
+        });
+        self.audio_clips.push(rendered);
+        Ok(())
+    }
+
+    /// Restore the clips and FX chain from before `freeze`
+    /// Returns the ID of the rendered clip that was removed, or None if not frozen
+    pub fn unfreeze(&mut self) -> Option<ClipId> {
+        let state = self.frozen.take()?;
+        self.audio_clips = state.audio_clips;
+        self.midi_clips = state.midi_clips;
+        self.fx_chain = state.fx_chain;
+        Some(state.clip_id)
+    }
+
+    /// Clips and FX chain to save with the project (the originals while frozen)
+    pub fn saved_contents(&self) -> (&[TimelineClip], &[TimelineMidiClip], &[EffectId]) {
+        match &self.frozen {
+            Some(state) => (&state.audio_clips, &state.midi_clips, &state.fx_chain),
+            None => (&self.audio_clips, &self.midi_clips, &self.fx_chain),
+        }
+    }
+
     /// Get pan coefficients for stereo panning
     /// Returns (`left_gain`, `right_gain`)
     ///
@@ -438,7 +838,13 @@ impl Track {
     /// - pan =  0.0 → (0.707, 0.707) = center (-3 dB each)
     /// - pan = +1.0 → (0.0, 1.0) = full right
     pub fn get_pan_gains(&self) -> (f32, f32) {
-        let pan_normalized = f32::midpoint(self.pan, 1.0); // Map -1..1 to 0..1
+        Self::pan_gains(self.pan)
+    }
+
+    /// Equal-power pan coefficients for a pan position (-1.0 to +1.0)
+    /// Returns (`left_gain`, `right_gain`)
+    pub fn pan_gains(pan: f32) -> (f32, f32) {
+        let pan_normalized = f32::midpoint(pan, 1.0); // Map -1..1 to 0..1
         let pan_radians = pan_normalized * std::f32::consts::FRAC_PI_2; // 0 to π/2
 
         let left_gain = pan_radians.cos();
@@ -482,12 +888,12 @@ impl Track {
 
         // Before first point - use first point's value
         if time_seconds <= points[0].time_seconds {
-            return points[0].value_db;
+            return points[0].value;
         }
 
         // After last point - use last point's value
         if time_seconds >= points[points.len() - 1].time_seconds {
-            return points[points.len() - 1].value_db;
+            return points[points.len() - 1].value;
         }
 
         // Find surrounding points and interpolate (binary search for efficiency)
@@ -507,7 +913,7 @@ impl Track {
         let p1 = &points[low];
         let p2 = &points[high];
         let t = (time_seconds - p1.time_seconds) / (p2.time_seconds - p1.time_seconds);
-        p1.value_db + (p2.value_db - p1.value_db) * t as f32
+        p1.value + (p2.value - p1.value) * t as f32
     }
 
     /// Get interpolated gain (linear) at a specific time
@@ -525,30 +931,37 @@ impl Track {
     /// Format: "time,db;time,db;..." where time is in seconds
     /// Empty string clears the automation
     pub fn set_volume_automation_csv(&mut self, csv: &str) {
-        self.volume_automation.clear();
+        self.volume_automation = AutomationPoint::parse_csv(csv);
+    }
 
-        if csv.is_empty() {
-            return;
-        }
+    /// Check if track has volume automation
+    pub fn has_volume_automation(&self) -> bool {
+        !self.volume_automation.is_empty()
+    }
 
-        for pair in csv.split(';') {
-            let parts: Vec<&str> = pair.split(',').collect();
-            if parts.len() == 2 {
-                if let (Ok(time), Ok(db)) = (parts[0].parse::<f64>(), parts[1].parse::<f32>()) {
-                    self.volume_automation.push(AutomationPoint::new(time, db));
-                }
-            }
+    /// Get interpolated pan at a specific time (in seconds)
+    /// Returns static `pan` if no automation exists
+    pub fn get_pan_at(&self, time_seconds: f64) -> f32 {
+        if self.pan_automation.is_empty() {
+            self.pan
+        } else {
+            crate::audio_graph::interpolate_automation_value(&self.pan_automation, time_seconds)
         }
+    }
 
-        // Sort by time (should already be sorted, but ensure it)
-        self.volume_automation.sort_by(|a, b| {
-            a.time_seconds.partial_cmp(&b.time_seconds).unwrap_or(std::cmp::Ordering::Equal)
-        });
+    /// Set pan automation curve from a CSV string
+    /// Format: "time,pan;time,pan;..." where time is in seconds and pan is -1.0 to +1.0
+    /// Empty string clears the automation
+    pub fn set_pan_automation_csv(&mut self, csv: &str) {
+        self.pan_automation = AutomationPoint::parse_csv(csv);
+        for point in &mut self.pan_automation {
+            point.value = point.value.clamp(-1.0, 1.0);
+        }
     }
 
-    /// Check if track has volume automation
-    pub fn has_volume_automation(&self) -> bool {
-        !self.volume_automation.is_empty()
+    /// Check if track has pan automation
+    pub fn has_pan_automation(&self) -> bool {
+        !self.pan_automation.is_empty()
     }
 }
 
@@ -634,6 +1047,35 @@ impl TrackManager {
         self.tracks.iter()
             .any(|t| t.lock().solo)
     }
+
+    /// Put a track into a group track (None = back to the master)
+    /// Groups can be nested, but not into themselves or one of their own children.
+    pub fn set_parent_group(&self, track_id: TrackId, group_id: Option<TrackId>) -> Result<(), String> {
+        let track_arc = self.get_track(track_id).ok_or_else(|| format!("Track {track_id} not found"))?;
+        if let Some(group_id) = group_id {
+            let track_type = track_arc.lock().track_type;
+            if matches!(track_type, TrackType::Master | TrackType::Return) {
+                return Err(format!("{track_type:?} track {track_id} cannot be grouped"));
+            }
+            // Walk up from the group: reaching the track means it would contain itself
+            // (bounded, in case a loaded project already holds a cycle higher up)
+            let mut ancestor = Some(group_id);
+            for _ in 0..=self.tracks.len() {
+                let Some(id) = ancestor else { break };
+                if id == track_id {
+                    return Err(format!("Track {track_id} cannot be grouped into its own group {group_id}"));
+                }
+                let group_arc = self.get_track(id).ok_or_else(|| format!("Track {id} not found"))?;
+                let group = group_arc.lock();
+                if id == group_id && group.track_type != TrackType::Group {
+                    return Err(format!("Track {group_id} is not a group track"));
+                }
+                ancestor = group.parent_group;
+            }
+        }
+        track_arc.lock().parent_group = group_id;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -690,6 +1132,154 @@ mod tests {
         assert!((right - 1.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_pan_automation_interpolates_and_clamps() {
+        let mut track = Track::new(1, TrackType::Audio, "Test".to_string());
+        track.pan = 0.25;
+        assert!((track.get_pan_at(3.0) - 0.25).abs() < 1e-6);
+
+        // Out-of-order points are sorted; out-of-range pans clamped
+        track.set_pan_automation_csv("2.0,1.5;0.0,-1.0;bad");
+        assert_eq!(track.pan_automation.len(), 2);
+        assert!((track.get_pan_at(0.0) + 1.0).abs() < 1e-6);
+        assert!(track.get_pan_at(1.0).abs() < 1e-6);
+        assert!((track.get_pan_at(5.0) - 1.0).abs() < 1e-6);
+
+        track.set_pan_automation_csv("");
+        assert!(!track.has_pan_automation());
+    }
+
+    #[test]
+    fn test_input_gain_clamps_to_trim_range() {
+        let mut track = Track::new(1, TrackType::Audio, "Test".to_string());
+        assert!((track.get_input_gain() - 1.0).abs() < 1e-6);
+
+        track.set_input_gain_db(-6.0);
+        assert!((track.get_input_gain() - 0.501).abs() < 1e-3);
+
+        track.set_input_gain_db(40.0);
+        assert!((track.input_gain_db - MAX_INPUT_GAIN_DB).abs() < 1e-6);
+        track.set_input_gain_db(f32::NAN);
+        assert!((track.input_gain_db - MAX_INPUT_GAIN_DB).abs() < 1e-6);
+        track.set_input_gain_db(-100.0);
+        assert!((track.input_gain_db - MIN_INPUT_GAIN_DB).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_reorder_fx_chain_rejects_mismatched_sets() {
+        let mut track = Track::new(1, TrackType::Audio, "Test".to_string());
+        track.fx_chain = vec![10, 11, 12];
+
+        // Missing, extra, duplicate and foreign IDs leave the chain untouched
+        assert!(track.reorder_fx_chain(vec![12, 10]).is_err());
+        assert!(track.reorder_fx_chain(vec![12, 10, 11, 13]).is_err());
+        assert!(track.reorder_fx_chain(vec![12, 12, 10]).is_err());
+        assert!(track.reorder_fx_chain(vec![12, 10, 13]).is_err());
+        assert_eq!(track.fx_chain, vec![10, 11, 12]);
+
+        assert!(track.reorder_fx_chain(vec![12, 10, 11]).is_ok());
+        assert_eq!(track.fx_chain, vec![12, 10, 11]);
+    }
+
+    #[test]
+    fn test_freeze_and_unfreeze_restore_contents() {
+        let clip = |seconds: f64| Arc::new(AudioClip {
+            samples: vec![0.0; 2],
+            channels: 2,
+            sample_rate: 48000,
+            duration_seconds: seconds,
+            file_path: String::new(),
+            stream: None,
+        });
+        let mut track = Track::new(1, TrackType::Midi, "Test".to_string());
+        track.audio_clips.push(TimelineClip::new(3, clip(1.0), 0.5));
+        track.fx_chain = vec![10, 11];
+
+        assert!(track.freeze(TimelineClip::new(7, clip(2.0), 0.0)).is_ok());
+        assert_eq!(track.audio_clips.len(), 1);
+        assert_eq!(track.audio_clips[0].id, 7);
+        assert!(track.fx_chain.is_empty());
+        assert!(track.freeze(TimelineClip::new(8, clip(2.0), 0.0)).is_err());
+
+        // Saving while frozen keeps the original contents
+        let (audio, _, fx) = track.saved_contents();
+        assert_eq!(audio[0].id, 3);
+        assert_eq!(fx, &[10, 11]);
+
+        assert_eq!(track.unfreeze(), Some(7));
+        assert_eq!(track.audio_clips[0].id, 3);
+        assert_eq!(track.fx_chain, vec![10, 11]);
+        assert_eq!(track.unfreeze(), None);
+    }
+
+    #[test]
+    fn test_channel_utilities_sum_then_invert() {
+        assert_eq!(apply_channel_utilities(0.4, -0.2, false, false), (0.4, -0.2));
+        assert_eq!(apply_channel_utilities(0.4, -0.2, false, true), (-0.4, 0.2));
+        let (left, right) = apply_channel_utilities(0.4, 0.2, true, false);
+        assert!((left - 0.3).abs() < 1e-6 && (right - 0.3).abs() < 1e-6);
+        let (left, right) = apply_channel_utilities(0.4, 0.2, true, true);
+        assert!((left + 0.3).abs() < 1e-6 && (right + 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_active_region_peak_ignores_trimmed_audio() {
+        let mut samples = vec![0.25_f32; 200];
+        samples[10] = -0.9; // inside the trimmed-off start
+        samples[150] = 0.5;
+        let clip = AudioClip {
+            samples,
+            channels: 2,
+            sample_rate: 100,
+            duration_seconds: 1.0,
+            file_path: String::new(),
+            stream: None,
+        };
+        let mut clip = TimelineClip::new(0, Arc::new(clip), 0.0);
+        assert!((clip.active_region_peak() - 0.9).abs() < 1e-6);
+
+        clip.offset = 0.5;
+        assert!((clip.active_region_peak() - 0.5).abs() < 1e-6);
+        clip.duration = Some(0.2);
+        assert!((clip.active_region_peak() - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_split_rebases_clip_automation_onto_second_half() {
+        let audio = AudioClip {
+            samples: vec![0.0; 800],
+            channels: 2,
+            sample_rate: 100,
+            duration_seconds: 4.0,
+            file_path: String::new(),
+            stream: None,
+        };
+        let mut clip = TimelineClip::new(0, Arc::new(audio), 10.0);
+        // 4 timeline seconds = 8 beats
+        clip.volume_automation = vec![ClipAutomationPoint::new(0.0, 0.2), ClipAutomationPoint::new(4.0, 1.0), ClipAutomationPoint::new(8.0, 0.0)];
+        clip.pan_automation = vec![ClipAutomationPoint::new(1.0, 0.3)];
+
+        // Split one second (two beats) in, halfway up the first volume ramp
+        let (first, second) = clip.split_at(11.0, 1, 2).unwrap();
+        assert_eq!(first.volume_automation.len(), 3);
+        let points: Vec<(f64, f32)> = second.volume_automation.iter().map(|p| (p.time_beats, p.value)).collect();
+        assert_eq!(points.len(), 3);
+        assert!(points[0].0.abs() < 1e-9 && (points[0].1 - 0.6).abs() < 1e-6);
+        assert!((points[1].0 - 2.0).abs() < 1e-9 && (points[1].1 - 1.0).abs() < 1e-6);
+        assert!((points[2].0 - 6.0).abs() < 1e-9 && points[2].1.abs() < 1e-6);
+        // The second half hears what the original clip did at the same timeline position
+        for beat in [0.0, 1.5, 3.0, 5.0] {
+            let original = clip.get_volume_at_beat(beat + 2.0);
+            assert!((second.get_volume_at_beat(beat) - original).abs() < 1e-6, "beat {beat}");
+        }
+        assert!((second.get_pan_at_beat(0.0) - 0.3).abs() < 1e-6);
+
+        // Without automation, neither half gets any
+        clip.volume_automation.clear();
+        let (_, second) = clip.split_at(11.0, 1, 2).unwrap();
+        assert!(!second.has_volume_automation());
+    }
+
     #[test]
     fn test_track_manager() {
         let mut manager = TrackManager::new();
@@ -713,4 +1303,22 @@ mod tests {
         // Cannot remove master
         assert!(!manager.remove_track(0));
     }
+
+    #[test]
+    fn test_nested_groups_reject_cycles() {
+        let mut manager = TrackManager::new();
+        let outer = manager.create_track(TrackType::Group, "Outer".to_string());
+        let inner = manager.create_track(TrackType::Group, "Inner".to_string());
+        let drums = manager.create_track(TrackType::Audio, "Drums".to_string());
+
+        manager.set_parent_group(drums, Some(inner)).unwrap();
+        manager.set_parent_group(inner, Some(outer)).unwrap();
+        assert!(manager.set_parent_group(outer, Some(inner)).is_err());
+        assert!(manager.set_parent_group(outer, Some(outer)).is_err());
+        assert!(manager.set_parent_group(inner, Some(drums)).is_err());
+        assert!(manager.set_parent_group(0, Some(outer)).is_err());
+
+        manager.set_parent_group(drums, None).unwrap();
+        assert_eq!(manager.get_track(drums).unwrap().lock().parent_group, None);
+    }
 }
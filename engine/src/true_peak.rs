@@ -0,0 +1,225 @@
+/// True-peak (inter-sample peak) metering (ITU-R BS.1770 Annex 2)
+///
+/// The limited master output is upsampled 4x with a polyphase FIR and the
+/// largest absolute value of the interpolated signal is taken as the true
+/// peak, catching overs that sample-peak meters miss. Oversampling costs CPU,
+/// so the meter is off until enabled. Readings are published through atomics.
+use crate::audio_file::TARGET_SAMPLE_RATE;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Reported level when there is no signal (dBTP)
+pub const TRUE_PEAK_FLOOR_DB: f32 = -96.0;
+
+const OVERSAMPLING: usize = 4;
+/// FIR taps per polyphase branch (48-tap prototype filter)
+const TAPS_PER_PHASE: usize = 12;
+/// Readings are published every 100ms
+const PUBLISH_SAMPLES: u32 = TARGET_SAMPLE_RATE / 10;
+
+fn amplitude_to_db(amplitude: f32) -> f32 {
+    if amplitude > 0.0 {
+        (20.0 * amplitude.log10()).max(TRUE_PEAK_FLOOR_DB)
+    } else {
+        TRUE_PEAK_FLOOR_DB
+    }
+}
+
+/// Latest true-peak readings, shared between the audio thread and the API
+pub struct TruePeakReadings {
+    /// Peak of the last 100ms per channel (linear, f32 bits)
+    left: AtomicU32,
+    right: AtomicU32,
+    /// Highest peak on either channel since the last reset (linear, f32 bits)
+    max: AtomicU32,
+    enabled: AtomicBool,
+    reset_requested: AtomicBool,
+}
+
+impl Default for TruePeakReadings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TruePeakReadings {
+    pub fn new() -> Self {
+        Self {
+            left: AtomicU32::new(0),
+            right: AtomicU32::new(0),
+            max: AtomicU32::new(0),
+            enabled: AtomicBool::new(false),
+            reset_requested: AtomicBool::new(false),
+        }
+    }
+
+    /// Enable or disable oversampling (disabled costs one atomic load per frame)
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+        if !enabled {
+            self.left.store(0, Ordering::Relaxed);
+            self.right.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Get (left, right, maximum) true peak in dBTP
+    pub fn get(&self) -> (f32, f32, f32) {
+        let read = |value: &AtomicU32| amplitude_to_db(f32::from_bits(value.load(Ordering::Relaxed)));
+        (read(&self.left), read(&self.right), read(&self.max))
+    }
+
+    /// Ask the audio thread to clear the maximum (e.g. when playback starts)
+    pub fn request_reset(&self) {
+        self.reset_requested.store(true, Ordering::SeqCst);
+    }
+}
+
+/// True-peak meter state (owned by the audio thread, allocation-free)
+pub struct TruePeakMeter {
+    /// Polyphase branches of the interpolation filter
+    phases: [[f32; TAPS_PER_PHASE]; OVERSAMPLING],
+    /// Most recent input samples per channel (ring buffer)
+    history: [[f32; TAPS_PER_PHASE]; 2],
+    write_pos: usize,
+    block_peak: [f32; 2],
+    block_len: u32,
+    max_peak: f32,
+}
+
+impl Default for TruePeakMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TruePeakMeter {
+    pub fn new() -> Self {
+        // Blackman-windowed sinc low-pass at the original Nyquist, centred on a
+        // tap so branch 0 passes the input samples through unchanged
+        let length = OVERSAMPLING * TAPS_PER_PHASE;
+        let centre = (length / 2) as f64;
+        let mut phases = [[0.0; TAPS_PER_PHASE]; OVERSAMPLING];
+        for n in 0..length {
+            let x = (n as f64 - centre) / OVERSAMPLING as f64;
+            let sinc = if x == 0.0 {
+                1.0
+            } else {
+                (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x)
+            };
+            let w = 2.0 * std::f64::consts::PI * n as f64 / length as f64;
+            let window = 0.42 - 0.5 * w.cos() + 0.08 * (2.0 * w).cos();
+            phases[n % OVERSAMPLING][n / OVERSAMPLING] = (sinc * window) as f32;
+        }
+        // Unity DC gain on every branch
+        for phase in &mut phases {
+            let sum: f32 = phase.iter().sum();
+            for tap in phase.iter_mut() {
+                *tap /= sum;
+            }
+        }
+
+        Self {
+            phases,
+            history: [[0.0; TAPS_PER_PHASE]; 2],
+            write_pos: 0,
+            block_peak: [0.0; 2],
+            block_len: 0,
+            max_peak: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.history = [[0.0; TAPS_PER_PHASE]; 2];
+        self.block_peak = [0.0; 2];
+        self.block_len = 0;
+        self.max_peak = 0.0;
+    }
+
+    /// Feed one stereo frame; publishes new readings every 100ms
+    pub fn process_frame(&mut self, left: f32, right: f32, readings: &TruePeakReadings) {
+        if !readings.enabled.load(Ordering::Relaxed) {
+            return;
+        }
+        if readings.reset_requested.swap(false, Ordering::SeqCst) {
+            self.reset();
+            readings.max.store(0, Ordering::Relaxed);
+        }
+
+        self.history[0][self.write_pos] = left;
+        self.history[1][self.write_pos] = right;
+        for channel in 0..2 {
+            let peak = self.interpolated_peak(channel);
+            self.block_peak[channel] = self.block_peak[channel].max(peak);
+        }
+        self.write_pos = (self.write_pos + 1) % TAPS_PER_PHASE;
+
+        self.block_len += 1;
+        if self.block_len >= PUBLISH_SAMPLES {
+            let [block_left, block_right] = self.block_peak;
+            self.max_peak = self.max_peak.max(block_left).max(block_right);
+            readings.left.store(block_left.to_bits(), Ordering::Relaxed);
+            readings.right.store(block_right.to_bits(), Ordering::Relaxed);
+            readings.max.store(self.max_peak.to_bits(), Ordering::Relaxed);
+            self.block_peak = [0.0; 2];
+            self.block_len = 0;
+        }
+    }
+
+    /// Largest absolute value of the four interpolated samples for the newest input
+    fn interpolated_peak(&self, channel: usize) -> f32 {
+        let history = &self.history[channel];
+        let mut peak = 0.0f32;
+        for phase in &self.phases {
+            let mut sum = 0.0;
+            for (k, tap) in phase.iter().enumerate() {
+                sum += tap * history[(self.write_pos + TAPS_PER_PHASE - k) % TAPS_PER_PHASE];
+            }
+            peak = peak.max(sum.abs());
+        }
+        peak
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_catches_inter_sample_peak_missed_by_sample_peak() {
+        // Full-scale sine at fs/4 sampled 45 degrees off its crests: every
+        // sample reads -3 dB while the waveform between them reaches 0 dBTP
+        let mut meter = TruePeakMeter::new();
+        let readings = TruePeakReadings::new();
+        readings.set_enabled(true);
+        let mut sample_peak = 0.0f32;
+        for i in 0..PUBLISH_SAMPLES {
+            let s = (PI / 2.0 * i as f32 + PI / 4.0).sin();
+            sample_peak = sample_peak.max(s.abs());
+            meter.process_frame(s, 0.0, &readings);
+        }
+
+        let (left, right, max) = readings.get();
+        assert!(amplitude_to_db(sample_peak) < -2.9);
+        assert!(left.abs() < 0.2, "true peak {left} dBTP");
+        assert!((max - left).abs() < 1e-6);
+        assert!((right - TRUE_PEAK_FLOOR_DB).abs() < 1e-6);
+
+        readings.request_reset();
+        meter.process_frame(0.0, 0.0, &readings);
+        assert!((readings.get().2 - TRUE_PEAK_FLOOR_DB).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_disabled_meter_publishes_nothing() {
+        let mut meter = TruePeakMeter::new();
+        let readings = TruePeakReadings::new();
+        for _ in 0..PUBLISH_SAMPLES * 2 {
+            meter.process_frame(1.0, 1.0, &readings);
+        }
+        assert!((readings.get().2 - TRUE_PEAK_FLOOR_DB).abs() < 1e-6);
+    }
+}
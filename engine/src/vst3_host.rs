@@ -142,6 +142,9 @@ extern "C" {
         sample_offset: c_int,
     ) -> bool;
 
+    #[cfg(feature = "vst3-latency")]
+    pub fn vst3_get_latency_samples(handle: *mut VST3PluginHandle) -> u32;
+
     pub fn vst3_get_parameter_count(handle: *mut VST3PluginHandle) -> c_int;
 
     pub fn vst3_get_parameter_info(
@@ -429,6 +432,19 @@ impl VST3Plugin {
         }
     }
 
+    /// Processing latency the plugin reports (`IAudioProcessor::getLatencySamples`)
+    /// Always 0 unless built with the `vst3-latency` feature (rebuilt host libraries).
+    pub fn get_latency_samples(&self) -> u32 {
+        #[cfg(feature = "vst3-latency")]
+        {
+            unsafe { vst3_get_latency_samples(self.handle) }
+        }
+        #[cfg(not(feature = "vst3-latency"))]
+        {
+            0
+        }
+    }
+
     pub fn get_parameter_count(&self) -> i32 {
         unsafe { vst3_get_parameter_count(self.handle) }
     }
@@ -653,6 +669,13 @@ impl VST3Effect {
         Ok(())
     }
 
+    /// Processing latency in samples, as the plugin currently reports it
+    /// (read on every call: plugins may change it, e.g. with a lookahead setting)
+    pub fn latency_samples(&self) -> usize {
+        let plugin = self.plugin.lock();
+        plugin.get_latency_samples() as usize
+    }
+
     /// Get parameter count
     pub fn get_parameter_count(&self) -> i32 {
         let plugin = self.plugin.lock();
@@ -805,6 +828,10 @@ impl crate::effects::Effect for VST3Effect {
         let _ = plugin.activate();
     }
 
+    fn latency_samples(&self) -> usize {
+        VST3Effect::latency_samples(self)
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
@@ -0,0 +1,202 @@
+/// Waveform overview peaks for audio clips
+///
+/// A `WaveformPeaks` holds min/max pairs of a clip's first channel at several block
+/// sizes (a mip-map: each level merges pairs of blocks from the level below). Each
+/// output window is covered by the largest blocks that fit inside it, plus raw samples
+/// for the part-blocks at its edges (under `BASE_BLOCK_FRAMES` each side), so zooming
+/// and scrolling never rescan the whole clip and the peaks match a full scan exactly.
+use crate::audio_file::AudioClip;
+
+/// Frames per block in the finest cached level
+const BASE_BLOCK_FRAMES: usize = 64;
+/// Quietest level shown by dB-scaled peaks (dBFS)
+pub const WAVEFORM_DB_FLOOR: f32 = -60.0;
+
+/// Cached min/max levels of one clip
+pub struct WaveformPeaks {
+    frames: usize,
+    /// `levels[k]` holds (min, max) per block of `BASE_BLOCK_FRAMES << k` frames
+    levels: Vec<Vec<(f32, f32)>>,
+}
+
+impl WaveformPeaks {
+    /// Scan the clip once and build every level
+    pub fn new(clip: &AudioClip) -> Self {
+        let frames = clip.frame_count();
+        let channels = clip.channels.max(1);
+        // Read in large spans so streamed clips aren't scanned through their window cache
+        let span_frames = BASE_BLOCK_FRAMES * 1024;
+        let mut base: Vec<(f32, f32)> = Vec::with_capacity(frames.div_ceil(BASE_BLOCK_FRAMES));
+        for span_start in (0..frames).step_by(span_frames) {
+            let span = clip.read_frames(span_start, span_frames);
+            base.extend(span.chunks(BASE_BLOCK_FRAMES * channels).map(|block| {
+                block
+                    .iter()
+                    .step_by(channels)
+                    .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &sample| (min.min(sample), max.max(sample)))
+            }));
+        }
+
+        let mut levels = vec![base];
+        while let Some(last) = levels.last().filter(|level| level.len() > 1) {
+            let next = last
+                .chunks(2)
+                .map(|pair| pair.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |acc, block| merge(acc, *block)))
+                .collect();
+            levels.push(next);
+        }
+        Self { frames, levels }
+    }
+
+    /// Min/max pairs (`[min0, max0, min1, max1, ...]`) for `resolution` windows of
+    /// the clip's first channel; `clip` must be the clip the peaks were built from
+    pub fn peaks(&self, clip: &AudioClip, resolution: usize) -> Vec<f32> {
+        if resolution == 0 {
+            return Vec::new();
+        }
+        let frames = self.frames;
+        let samples_per_peak = (frames / resolution).max(1);
+
+        let mut peaks = Vec::with_capacity(resolution * 2); // min/max pairs
+        for i in 0..resolution {
+            let start = i * samples_per_peak;
+            let end = ((i + 1) * samples_per_peak).min(frames);
+            if start >= frames {
+                break;
+            }
+
+            // Peaks start from (1.0, -1.0), so silence still draws a line through zero
+            let (min, max) = self.range_peak(clip, start, end, (1.0, -1.0));
+            peaks.push(min);
+            peaks.push(max);
+        }
+        peaks
+    }
+
+    /// Merge the min/max of frames `start..end` into `acc`
+    fn range_peak(&self, clip: &AudioClip, start: usize, end: usize, acc: (f32, f32)) -> (f32, f32) {
+        // Whole base blocks inside the range; the part-blocks at the edges are read raw
+        let mut low = start.div_ceil(BASE_BLOCK_FRAMES);
+        let mut high = end / BASE_BLOCK_FRAMES;
+        if low >= high {
+            return raw_peak(clip, start..end, acc);
+        }
+        let mut acc = raw_peak(clip, start..low * BASE_BLOCK_FRAMES, acc);
+        acc = raw_peak(clip, high * BASE_BLOCK_FRAMES..end, acc);
+
+        // Climb the levels, taking the unpaired block at each end of the range
+        for level in &self.levels {
+            if low >= high {
+                break;
+            }
+            if low % 2 == 1 {
+                acc = merge(acc, level[low]);
+                low += 1;
+            }
+            if high % 2 == 1 {
+                high -= 1;
+                acc = merge(acc, level[high]);
+            }
+            low /= 2;
+            high /= 2;
+        }
+        acc
+    }
+}
+
+/// Merge the min/max of the clip's first channel over `frames` into `acc`
+fn raw_peak(clip: &AudioClip, frames: std::ops::Range<usize>, acc: (f32, f32)) -> (f32, f32) {
+    frames
+        .filter_map(|frame| clip.get_sample(frame, 0))
+        .fold(acc, |(min, max), sample| (min.min(sample), max.max(sample)))
+}
+
+/// Convert min/max pairs to dBFS: the min becomes the level of the window's negative
+/// excursion and the max of its positive one, both floored at `WAVEFORM_DB_FLOOR`
+pub fn peaks_to_db(peaks: &[f32]) -> Vec<f32> {
+    peaks
+        .chunks_exact(2)
+        .flat_map(|pair| [amplitude_to_db(-pair[0]), amplitude_to_db(pair[1])])
+        .collect()
+}
+
+/// dBFS of a (positive) excursion, floored; zero or negative is below the floor
+fn amplitude_to_db(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        return WAVEFORM_DB_FLOOR;
+    }
+    (20.0 * amplitude.log10()).max(WAVEFORM_DB_FLOOR)
+}
+
+fn merge((min, max): (f32, f32), (block_min, block_max): (f32, f32)) -> (f32, f32) {
+    (min.min(block_min), max.max(block_max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_file::TARGET_SAMPLE_RATE;
+
+    /// Min/max per window straight from the samples (the uncached algorithm)
+    fn brute_force_peaks(clip: &AudioClip, resolution: usize) -> Vec<f32> {
+        let frames = clip.frame_count();
+        let samples_per_peak = (frames / resolution).max(1);
+        let mut peaks = Vec::new();
+        for i in 0..resolution {
+            let start = i * samples_per_peak;
+            let end = ((i + 1) * samples_per_peak).min(frames);
+            if start >= frames {
+                break;
+            }
+            let (mut min, mut max) = (1.0f32, -1.0f32);
+            for frame in start..end {
+                let sample = clip.get_sample(frame, 0).unwrap();
+                min = min.min(sample);
+                max = max.max(sample);
+            }
+            peaks.push(min);
+            peaks.push(max);
+        }
+        peaks
+    }
+
+    #[test]
+    fn test_cached_peaks_match_brute_force() {
+        // 10 s of stereo: a swept tone under a slow swell; the right channel is ignored
+        let frames = TARGET_SAMPLE_RATE as usize * 10 + 123;
+        let samples = (0..frames)
+            .flat_map(|n| {
+                let t = n as f32 / TARGET_SAMPLE_RATE as f32;
+                let swell = 0.2 + 0.7 * (t * 0.4).sin().abs();
+                [(t * (50.0 + 20.0 * t) * std::f32::consts::TAU).sin() * swell, 0.9]
+            })
+            .collect();
+        let clip = AudioClip {
+            samples,
+            channels: 2,
+            sample_rate: TARGET_SAMPLE_RATE,
+            duration_seconds: frames as f64 / f64::from(TARGET_SAMPLE_RATE),
+            file_path: "sweep.wav".to_string(),
+            stream: None,
+        };
+
+        let cache = WaveformPeaks::new(&clip);
+        for resolution in [1, 7, 100, 1000, 4000, 20_000, frames * 2] {
+            let cached = cache.peaks(&clip, resolution);
+            let expected = brute_force_peaks(&clip, resolution);
+            assert_eq!(cached, expected, "resolution {resolution}");
+        }
+        assert!(cache.peaks(&clip, 0).is_empty());
+    }
+
+    #[test]
+    fn test_peaks_to_db_scales_each_excursion_and_floors_silence() {
+        let peaks = [-0.5, 1.0, 0.0, 0.0, 0.25, 0.1, -1e-6, 2e-4];
+        let db = peaks_to_db(&peaks);
+        let expected = [-6.0206, 0.0, -60.0, -60.0, -60.0, -20.0, -60.0, -60.0];
+        for (a, b) in db.iter().zip(expected) {
+            assert!((a - b).abs() < 1e-3, "{db:?}");
+        }
+        assert_eq!(db.len(), peaks.len());
+    }
+}
@@ -835,6 +835,15 @@ bool vst3_process_midi_event(
     return true;
 }
 
+uint32_t vst3_get_latency_samples(VST3PluginHandle handle) {
+    if (!handle) return 0;
+
+    auto instance = static_cast<VST3PluginInstance*>(handle);
+    if (!instance->processor) return 0;
+
+    return instance->processor->getLatencySamples();
+}
+
 int vst3_get_parameter_count(VST3PluginHandle handle) {
     if (!handle) {
         printf("🎛️ [C++] vst3_get_parameter_count: handle is null\n");
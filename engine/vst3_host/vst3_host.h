@@ -86,6 +86,9 @@ bool vst3_process_midi_event(
     int sample_offset
 );
 
+// Processing latency reported by the plugin, in samples (0 if unknown)
+uint32_t vst3_get_latency_samples(VST3PluginHandle handle);
+
 // Parameter management
 int vst3_get_parameter_count(VST3PluginHandle handle);
 